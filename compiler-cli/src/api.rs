@@ -0,0 +1,186 @@
+//! Compares the public API of the project with the one of a previous version,
+//! to tell which version it has to be published as. See
+//! `gleam_core::package_interface::diff` for how each change is classified.
+//!
+//! The interface of a version published to Hex is read from the
+//! `package-interface.json` file included in its HTML documentation.
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use gleam_core::{
+    Error, Result,
+    analyse::TargetSupport,
+    build::{Built, Codegen, Compile, Mode, Options},
+    hex,
+    package_interface::{PackageInterface, diff::InterfaceDiff},
+    paths::ProjectPaths,
+};
+use hexpm::version::Version;
+
+use crate::http::HttpClient;
+
+/// An interface to compare the current one with, and the version it is the
+/// interface of.
+pub struct PreviousInterface {
+    pub version: Version,
+    pub interface: PackageInterface,
+}
+
+pub fn diff(
+    paths: &ProjectPaths,
+    version: Option<String>,
+    file: Option<Utf8PathBuf>,
+) -> Result<()> {
+    let config = crate::config::root_config(paths)?;
+    let previous = match (file, version) {
+        (Some(path), _) => read_interface(&path)?,
+        (None, Some(version)) => {
+            let version =
+                Version::parse(&version).map_err(|error| Error::InvalidVersionFormat {
+                    input: version.clone(),
+                    error: error.to_string(),
+                })?;
+            if !published_versions(&config.name)?.contains(&version) {
+                return Err(Error::PackageInterfaceUnavailable {
+                    from: format!("{} v{version}", config.name).into(),
+                    reason: "This version has not been published to Hex.".into(),
+                });
+            }
+            published_interface(&config.name, version)?
+        }
+        (None, None) => {
+            let Some(version) = published_versions(&config.name)?.into_iter().max() else {
+                return Err(Error::PackageInterfaceUnavailable {
+                    from: config.name.clone(),
+                    reason: "The package has not been published to Hex.".into(),
+                });
+            };
+            published_interface(&config.name, version)?
+        }
+    };
+
+    let mut built = build(paths)?;
+    built.root_package.attach_doc_and_module_comments();
+    let current = PackageInterface::from_package(&built.root_package, &built.module_interfaces);
+
+    let diff = InterfaceDiff::new(&previous.interface, &current);
+    println!("Changes since v{}:\n", previous.version);
+    print!("{}", diff.render());
+
+    let Some(kind) = diff.kind() else {
+        return Ok(());
+    };
+    match diff.minimum_version(&previous.version) {
+        Some(minimum) if config.version < minimum => println!(
+            "\nWith {kind} changes the next version must be at least v{minimum}, \
+but gleam.toml has v{}.",
+            config.version
+        ),
+        Some(minimum) => {
+            println!("\nWith {kind} changes the next version must be at least v{minimum}.")
+        }
+        None => println!("\nWith {kind} changes any later version can be published."),
+    }
+    Ok(())
+}
+
+/// Builds the project as it would be published, for its interface.
+pub(crate) fn build(paths: &ProjectPaths) -> Result<Built> {
+    crate::build::main(
+        paths,
+        Options {
+            mode: Mode::Prod,
+            target: None,
+            codegen: Codegen::All,
+            compile: Compile::All,
+            warnings_as_errors: false,
+            root_target_support: TargetSupport::Enforced,
+            no_print_progress: false,
+        },
+        crate::build::download_dependencies(paths, crate::cli::Reporter::new())?,
+    )
+}
+
+fn read_interface(path: &Utf8PathBuf) -> Result<PreviousInterface> {
+    let interface = parse_interface(path.as_str().into(), &crate::fs::read(path)?)?;
+    let version = Version::parse(interface.version()).map_err(|error| {
+        Error::PackageInterfaceUnavailable {
+            from: path.as_str().into(),
+            reason: format!("Its version is not valid: {error}"),
+        }
+    })?;
+    Ok(PreviousInterface { version, interface })
+}
+
+fn parse_interface(from: EcoString, json: &str) -> Result<PackageInterface> {
+    serde_json::from_str(json).map_err(|error| Error::PackageInterfaceUnavailable {
+        from,
+        reason: format!("It is not a valid package interface: {error}"),
+    })
+}
+
+/// The versions of the package published to Hex.
+pub fn published_versions(name: &str) -> Result<Vec<Version>> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    runtime.block_on(hex::get_published_versions(
+        name,
+        &hexpm::Config::new(),
+        &HttpClient::new(),
+    ))
+}
+
+/// The latest version published before the given one, which a release of that
+/// version is compared with.
+pub fn previous_version(versions: Vec<Version>, version: &Version) -> Option<Version> {
+    versions
+        .into_iter()
+        .filter(|published| published < version)
+        .max()
+}
+
+/// Downloads the interface of a version of the package published to Hex.
+pub fn published_interface(name: &str, version: Version) -> Result<PreviousInterface> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let from = EcoString::from(format!("{name} v{version}"));
+    let interface = runtime.block_on(hex::get_package_interface(
+        name,
+        &version,
+        &hexpm::Config::new(),
+        &HttpClient::new(),
+    ))?;
+    let Some(interface) = interface else {
+        return Err(Error::PackageInterfaceUnavailable {
+            from,
+            reason: "Its documentation on Hex does not include a package interface, \
+it may have been built by an older version of Gleam."
+                .into(),
+        });
+    };
+    Ok(PreviousInterface {
+        interface: parse_interface(from, &interface)?,
+        version,
+    })
+}
+
+#[test]
+fn previous_version_is_the_latest_one_before() {
+    let versions = vec![
+        Version::new(1, 0, 0),
+        Version::new(2, 1, 0),
+        Version::new(1, 2, 0),
+        Version::new(2, 0, 0),
+    ];
+    assert_eq!(
+        previous_version(versions.clone(), &Version::new(2, 2, 0)),
+        Some(Version::new(2, 1, 0))
+    );
+    assert_eq!(
+        previous_version(versions.clone(), &Version::new(1, 2, 1)),
+        Some(Version::new(1, 2, 0))
+    );
+    assert_eq!(
+        previous_version(versions.clone(), &Version::new(2, 0, 0)),
+        Some(Version::new(1, 2, 0))
+    );
+    assert_eq!(previous_version(versions, &Version::new(0, 1, 0)), None);
+}
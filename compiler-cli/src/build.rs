@@ -3,6 +3,7 @@ use std::{rc::Rc, time::Instant};
 use gleam_core::{
     Result,
     build::{Built, Codegen, NullTelemetry, Options, ProjectCompiler, Telemetry},
+    config::PackageConfig,
     manifest::Manifest,
     paths::ProjectPaths,
     warning::WarningEmitterIO,
@@ -37,8 +38,20 @@ pub(crate) fn main_with_warnings(
     manifest: Manifest,
     warnings: Rc<dyn WarningEmitterIO>,
 ) -> Result<Built> {
-    let perform_codegen = options.codegen;
     let root_config = crate::config::root_config(paths)?;
+    main_with_config(paths, root_config, options, manifest, warnings)
+}
+
+/// Builds the project using the given configuration for the root package
+/// rather than the one read from its `gleam.toml`.
+pub(crate) fn main_with_config(
+    paths: &ProjectPaths,
+    root_config: PackageConfig,
+    options: Options,
+    manifest: Manifest,
+    warnings: Rc<dyn WarningEmitterIO>,
+) -> Result<Built> {
+    let perform_codegen = options.codegen;
     let telemetry: &'static dyn Telemetry = if options.no_print_progress {
         &NullTelemetry
     } else {
@@ -116,6 +116,14 @@ fn locking_lsp_erlang() {
     println!("Locked!")
 }
 
+#[test]
+fn locking_coverage_erlang() {
+    let paths = crate::project_paths_at_current_directory_without_toml();
+    let lock = BuildLock::new_target(&paths, Mode::Coverage, Target::Erlang).expect("make lock");
+    let _guard1: Guard = lock.lock(&gleam_core::build::NullTelemetry).unwrap();
+    println!("Locked!")
+}
+
 #[test]
 fn locking_dev_javascript() {
     let paths = crate::project_paths_at_current_directory_without_toml();
@@ -139,3 +147,12 @@ fn locking_lsp_javascript() {
     let _guard1: Guard = lock.lock(&gleam_core::build::NullTelemetry).unwrap();
     println!("Locked!")
 }
+
+#[test]
+fn locking_coverage_javascript() {
+    let paths = crate::project_paths_at_current_directory_without_toml();
+    let lock =
+        BuildLock::new_target(&paths, Mode::Coverage, Target::JavaScript).expect("make lock");
+    let _guard1: Guard = lock.lock(&gleam_core::build::NullTelemetry).unwrap();
+    println!("Locked!")
+}
@@ -148,6 +148,10 @@ pub(crate) fn print_running(text: &str) {
     print_colourful_prefix("Running", text)
 }
 
+pub(crate) fn print_watching() {
+    print_colourful_prefix("Watching", "for changes")
+}
+
 pub(crate) fn print_added(text: &str) {
     print_colourful_prefix("Added", text)
 }
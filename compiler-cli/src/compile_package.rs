@@ -7,7 +7,8 @@ use ecow::EcoString;
 use gleam_core::{
     Error, Result,
     build::{
-        Mode, NullTelemetry, PackageCompiler, StaleTracker, Target, TargetCodegenConfiguration,
+        Mode, NullTelemetry, PackageCompiler, SourceFingerprints, StaleTracker, Target,
+        TargetCodegenConfiguration,
     },
     metadata,
     paths::{self, ProjectPaths},
@@ -29,6 +30,7 @@ pub fn command(options: CompilePackage) -> Result<()> {
         Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
         Target::JavaScript => TargetCodegenConfiguration::JavaScript {
             emit_typescript_definitions: false,
+            source_maps: config.javascript.source_maps,
             prelude_location: options
                 .javascript_prelude
                 .ok_or_else(|| Error::JavaScriptPreludeRequired)?,
@@ -57,13 +59,14 @@ pub fn command(options: CompilePackage) -> Result<()> {
             &mut defined_modules,
             &mut StaleTracker::default(),
             &mut HashSet::new(),
+            &mut SourceFingerprints::default(),
             &NullTelemetry,
         )
         .into_result()
         .map(|_| ())
 }
 
-fn load_libraries(
+pub(crate) fn load_libraries(
     ids: &UniqueIdGenerator,
     lib: &Utf8Path,
 ) -> Result<im::HashMap<EcoString, ModuleInterface>> {
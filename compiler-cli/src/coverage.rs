@@ -0,0 +1,203 @@
+//! Measures the code coverage of the tests run by the built-in test runner,
+//! writing it to `build/coverage-report` as `lcov.info` and `cobertura.xml`.
+//! See the `gleam_core::coverage` module for how it is measured on each target.
+//!
+//! The project is built in the `Coverage` mode, so the build it is measured
+//! with is kept apart from the one used by `gleam run` and `gleam test`.
+//!
+//! The runtimes write the coverage they collect to `build/coverage-report/raw`
+//! when they exit, so the coverage of a runner stopped after a test timed out
+//! is lost.
+
+use std::{
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use gleam_core::{
+    Result,
+    build::{Built, Mode, Options, Origin, Target},
+    config::SourceMaps,
+    coverage::{self, ErlangCounts, FileCoverage, SourceModule},
+    javascript::SourceMap,
+    manifest::Manifest,
+    paths::{self, ProjectPaths},
+    type_::ModuleInterface,
+};
+use itertools::Itertools;
+
+use crate::{cli, fs::ConsoleWarningEmitter};
+
+/// The directory the runtimes write the coverage they collect to.
+pub fn raw_directory(paths: &ProjectPaths) -> Utf8PathBuf {
+    paths.build_coverage_report_directory().join("raw")
+}
+
+/// Removes the coverage collected by any previous run of the tests.
+pub fn clear(paths: &ProjectPaths) -> Result<()> {
+    crate::fs::mkdir(raw_directory(paths))?;
+    for path in raw_files(paths, "json")?
+        .into_iter()
+        .chain(raw_files(paths, "tsv")?)
+    {
+        crate::fs::delete_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Builds the project in the `Coverage` mode, with source maps for the root
+/// package as they are needed to map the coverage back to the Gleam code. The
+/// Erlang modules built in this mode always have them, and on JavaScript they
+/// are turned on here. As every build in this mode has them, there are never
+/// modules left over from a build without source maps.
+pub fn build(
+    paths: &ProjectPaths,
+    target: Target,
+    options: Options,
+    manifest: Manifest,
+) -> Result<Built> {
+    let mut root_config = crate::config::root_config(paths)?;
+    if target == Target::JavaScript {
+        root_config.javascript.source_maps = SourceMaps::External;
+    }
+    let options = Options {
+        mode: Mode::Coverage,
+        ..options
+    };
+    crate::build::main_with_config(
+        paths,
+        root_config,
+        options,
+        manifest,
+        Rc::new(ConsoleWarningEmitter),
+    )
+}
+
+/// The modules of the root package whose coverage is measured: those of its
+/// `src` directory.
+fn modules(built: &Built) -> Vec<&ModuleInterface> {
+    built
+        .module_interfaces
+        .values()
+        .filter(|module| {
+            module.package == built.root_package.config.name && module.origin == Origin::Src
+        })
+        .sorted_by_key(|module| &module.name)
+        .collect()
+}
+
+pub fn module_names(built: &Built) -> Vec<EcoString> {
+    modules(built)
+        .into_iter()
+        .map(|module| module.name.clone())
+        .collect()
+}
+
+/// Reads the coverage collected while running the tests, writes it as lcov
+/// and Cobertura reports, and prints a summary of it.
+pub fn report(paths: &ProjectPaths, built: &Built, target: Target) -> Result<()> {
+    let package = &built.root_package.config.name;
+    let modules = modules(built)
+        .into_iter()
+        .map(|module| {
+            let src = crate::fs::read(&module.src_path)?;
+            let path = module
+                .src_path
+                .strip_prefix(paths.root())
+                .unwrap_or(&module.src_path)
+                .to_path_buf();
+            SourceModule::new(module.name.clone(), path, &src)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let files = match target {
+        Target::JavaScript => javascript_coverage(paths, package, &modules)?,
+        Target::Erlang => erlang_coverage(paths, package, &modules)?,
+    };
+
+    let directory = paths.build_coverage_report_directory();
+    crate::fs::write(&directory.join("lcov.info"), &coverage::lcov(&files))?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64);
+    crate::fs::write(
+        &directory.join("cobertura.xml"),
+        &coverage::cobertura(package, paths.root(), &files, timestamp),
+    )?;
+
+    cli::print_colourful_prefix("Coverage", &coverage::summary(&files));
+    Ok(())
+}
+
+/// The files of the given kind written by the runtimes.
+fn raw_files(paths: &ProjectPaths, extension: &str) -> Result<Vec<Utf8PathBuf>> {
+    Ok(crate::fs::read_dir(raw_directory(paths))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension() == Some(extension))
+        .sorted()
+        .collect())
+}
+
+fn javascript_coverage(
+    paths: &ProjectPaths,
+    package: &str,
+    modules: &[SourceModule],
+) -> Result<Vec<FileCoverage>> {
+    let mut scripts = vec![];
+    for path in raw_files(paths, "json")? {
+        let suffix = format!("/{package}/");
+        scripts.extend(
+            coverage::parse_v8_coverage(&crate::fs::read(&path)?)
+                .into_iter()
+                .filter(|script| script.url.contains(&suffix)),
+        );
+    }
+
+    let build = paths.build_directory_for_package(Mode::Coverage, Target::JavaScript, package);
+    modules
+        .iter()
+        .map(|module| {
+            let path = build.join(format!("{}.mjs", module.name));
+            let generated = crate::fs::read(&path)?;
+            let source_map = crate::fs::read(format!("{path}.map"))?;
+            // Only the functions of a module whose source map cannot be read
+            // are covered.
+            let source_map = SourceMap::from_json(&source_map).unwrap_or_default();
+            Ok(coverage::javascript_coverage(
+                module,
+                package,
+                &generated,
+                &source_map,
+                &scripts,
+            ))
+        })
+        .collect()
+}
+
+fn erlang_coverage(
+    paths: &ProjectPaths,
+    package: &str,
+    modules: &[SourceModule],
+) -> Result<Vec<FileCoverage>> {
+    let mut counts = ErlangCounts::new();
+    for path in raw_files(paths, "tsv")? {
+        counts.parse(&crate::fs::read(&path)?);
+    }
+
+    let build = paths
+        .build_directory_for_package(Mode::Coverage, Target::Erlang, package)
+        .join(paths::ARTEFACT_DIRECTORY_NAME);
+    modules
+        .iter()
+        .map(|module| {
+            let path = build.join(format!("{}.erl.map", module.name.replace("/", "@")));
+            // Only the functions of a module whose source map cannot be read
+            // are covered.
+            let source_map = SourceMap::from_json(&crate::fs::read(&path)?).unwrap_or_default();
+            Ok(coverage::erlang_coverage(module, &source_map, &counts))
+        })
+        .collect()
+}
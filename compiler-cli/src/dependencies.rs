@@ -14,7 +14,7 @@ use flate2::read::GzDecoder;
 use gleam_core::{
     Error, Result,
     build::{Mode, Target, Telemetry},
-    config::PackageConfig,
+    config::{PackageConfig, WorkspaceConfig},
     dependency::{self, PackageFetchError},
     error::{FileIoAction, FileKind, ShellCommandFailureReason, StandardIoAction},
     hex::{self, HEXPM_PUBLIC_KEY},
@@ -105,6 +105,10 @@ fn get_manifest_details(paths: &ProjectPaths) -> Result<(PackageConfig, Manifest
         Mode::Dev,
     );
     let (_, manifest) = dependency_manager.get_manifest(paths, &config, Vec::new())?;
+    let manifest = match paths.workspace() {
+        Some(_) => workspace_member_manifest(manifest, &config, Mode::Dev)?,
+        None => manifest,
+    };
     Ok((config, manifest))
 }
 
@@ -542,7 +546,7 @@ fn read_manifest_from_disc(paths: &ProjectPaths) -> Result<Manifest> {
 
 fn write_manifest_to_disc(paths: &ProjectPaths, manifest: &Manifest) -> Result<()> {
     let path = paths.manifest();
-    fs::write(&path, &manifest.to_toml(paths.root()))
+    fs::write(&path, &manifest.to_toml(paths.workspace_root()))
 }
 
 // This is the container for locally pinned packages, representing the current contents of
@@ -618,6 +622,132 @@ impl LocalPackages {
     }
 }
 
+/// The name of the package that dependencies are resolved for in a workspace,
+/// which depends on every member of the workspace.
+const WORKSPACE_PACKAGE_NAME: &str = "<workspace>";
+
+/// The config the dependencies of a workspace member are resolved with, given
+/// the config of the member itself. As all the members share one manifest
+/// this is a package that depends on every member and that directly requires
+/// all of their dependencies, so that changes to any of them are noticed.
+///
+/// Returns `None` if the package is not part of a workspace.
+fn workspace_config(paths: &ProjectPaths, member: &PackageConfig) -> Result<Option<PackageConfig>> {
+    let Some(root) = paths.workspace() else {
+        return Ok(None);
+    };
+    let Some(workspace) = WorkspaceConfig::read(root.join("gleam.toml"), &ProjectIO::new())? else {
+        return Ok(None);
+    };
+
+    let mut config = PackageConfig {
+        name: WORKSPACE_PACKAGE_NAME.into(),
+        ..Default::default()
+    };
+    let mut members = Vec::with_capacity(workspace.members.len());
+    for path in workspace.members {
+        let member_config = crate::config::read(root.join(&path).join("gleam.toml"))?;
+        // The config of the member being built may have been changed, for
+        // example by `gleam add`, and not yet written to disc.
+        let member_config = if member_config.name == member.name {
+            member.clone()
+        } else {
+            member_config
+        };
+        let requirement = Requirement::Path { path: path.clone() };
+        _ = config
+            .dependencies
+            .insert(member_config.name.clone(), requirement);
+        members.push((path, member_config));
+    }
+
+    for (path, member_config) in members {
+        for (name, requirement) in member_config.all_direct_dependencies()? {
+            // Other members are provided by the workspace itself
+            if config.dependencies.contains_key(&name) {
+                continue;
+            }
+            // Paths are relative to the member, but the manifest is at the root
+            let requirement = match requirement {
+                Requirement::Path { path: dependency } => Requirement::Path {
+                    path: path.join(dependency),
+                },
+                requirement => requirement,
+            };
+            let requirement = match config.dev_dependencies.remove(&name) {
+                Some(other) => combine_requirements(&name, other, requirement, root)?,
+                None => requirement,
+            };
+            _ = config.dev_dependencies.insert(name, requirement);
+        }
+    }
+
+    Ok(Some(config))
+}
+
+/// A requirement satisfied by the versions of a package satisfying both of
+/// the given ones. Packages other than Hex ones can only be required from a
+/// single source.
+fn combine_requirements(
+    name: &str,
+    requirement1: Requirement,
+    requirement2: Requirement,
+    root_path: &Utf8Path,
+) -> Result<Requirement> {
+    match (&requirement1, &requirement2) {
+        (Requirement::Hex { version: range1 }, Requirement::Hex { version: range2 }) => {
+            match dependency::intersect_ranges(range1, range2) {
+                Some(version) => Ok(Requirement::Hex { version }),
+                None => Err(Error::ProvidedDependencyConflict {
+                    package: name.into(),
+                    source_1: requirement1.to_toml(root_path),
+                    source_2: requirement2.to_toml(root_path),
+                }),
+            }
+        }
+        _ if same_requirements(&requirement1, Some(&requirement2), root_path)? => Ok(requirement1),
+        _ => Err(Error::ProvidedDependencyConflict {
+            package: name.into(),
+            source_1: requirement1.to_toml(root_path),
+            source_2: requirement2.to_toml(root_path),
+        }),
+    }
+}
+
+/// The part of the manifest of a workspace needed by one of its members: the
+/// packages it depends on, directly or not.
+fn workspace_member_manifest(
+    manifest: Manifest,
+    config: &PackageConfig,
+    mode: Mode,
+) -> Result<Manifest> {
+    let requirements = config.all_direct_dependencies()?;
+    let mut required = HashSet::new();
+    let mut to_visit = config.dependencies_for(mode)?.into_keys().collect_vec();
+    while let Some(name) = to_visit.pop() {
+        if !required.insert(name.clone()) {
+            continue;
+        }
+        if let Some(package) = manifest
+            .packages
+            .iter()
+            .find(|package| package.name == name)
+        {
+            to_visit.extend(package.requirements.iter().cloned());
+        }
+    }
+
+    let packages = manifest
+        .packages
+        .into_iter()
+        .filter(|package| required.contains(&package.name))
+        .collect();
+    Ok(Manifest {
+        packages,
+        requirements,
+    })
+}
+
 fn is_same_requirements(
     requirements1: &HashMap<EcoString, Requirement>,
     requirements2: &HashMap<EcoString, Requirement>,
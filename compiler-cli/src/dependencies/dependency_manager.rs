@@ -21,7 +21,7 @@ use crate::{
 use super::{
     CheckMajorVersions, LocalPackages, UseManifest, add_missing_packages, is_same_requirements,
     lookup_package, provide_git_package, provide_local_package, read_manifest_from_disc,
-    remove_extra_packages, unlock_packages,
+    remove_extra_packages, unlock_packages, workspace_config, workspace_member_manifest,
 };
 
 pub struct DependencyManagerConfig {
@@ -74,6 +74,11 @@ where
         config: &PackageConfig,
         packages_to_update: Vec<EcoString>,
     ) -> Result<(bool, Manifest)> {
+        // The members of a workspace share a manifest, so all of their
+        // dependencies are resolved together
+        let workspace_config = workspace_config(paths, config)?;
+        let config = workspace_config.as_ref().unwrap_or(config);
+
         // If there's no manifest (or we have been asked not to use it) then resolve
         // the versions anew
         let should_resolve = match self.use_manifest {
@@ -101,7 +106,7 @@ where
             && is_same_requirements(
                 &manifest.requirements,
                 &config.all_direct_dependencies()?,
-                paths.root(),
+                paths.workspace_root(),
             )?
         {
             tracing::debug!("manifest_up_to_date");
@@ -172,6 +177,12 @@ where
         }
         LocalPackages::from_manifest(&manifest).write_to_disc(paths)?;
 
+        // Only the packages the member depends on are built for it
+        let manifest = match paths.workspace() {
+            Some(_) => workspace_member_manifest(manifest, &config, self.mode)?,
+            None => manifest,
+        };
+
         if let CheckMajorVersions::Yes = self.check_major_versions {
             let major_versions_available =
                 dependency::check_for_major_version_updates(&manifest, &self.package_fetcher);
@@ -213,7 +224,7 @@ where
                 Requirement::Path { path } => provide_local_package(
                     name.clone(),
                     &path,
-                    project_paths.root(),
+                    project_paths.workspace_root(),
                     project_paths,
                     &mut provided_packages,
                     &mut vec![],
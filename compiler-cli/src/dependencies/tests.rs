@@ -8,7 +8,7 @@ use pretty_assertions::assert_eq;
 use gleam_core::{
     Error,
     build::Runtime,
-    config::{DenoConfig, DenoFlag, Docs, ErlangConfig, JavaScriptConfig},
+    config::{DenoConfig, DenoFlag, Docs, ErlangConfig, JavaScriptConfig, SourceMaps},
     manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
     requirement::Requirement,
 };
@@ -1198,9 +1198,11 @@ fn package_config(
                 unstable: true,
                 location: None,
             },
+            source_maps: SourceMaps::None,
         },
         target: Target::Erlang,
         internal_modules: None,
+        warnings: Default::default(),
     }
 }
 
@@ -1347,3 +1349,155 @@ fn test_pretty_print_major_versions_available() {
 
     insta::assert_snapshot!(output);
 }
+
+#[test]
+fn workspace_config_requires_members_and_their_dependencies() {
+    let temp_dir = tempfile::tempdir().expect("Failed to create a temp directory");
+    let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_path_buf())
+        .expect("Path should be valid UTF-8");
+    fs::write(
+        &root.join("gleam.toml"),
+        "[workspace]\nmembers = [\"packages/wibble\", \"packages/wobble\"]",
+    )
+    .expect("write workspace config");
+    fs::write(
+        &root.join("packages/wibble/gleam.toml"),
+        r#"name = "wibble"
+[dependencies]
+gleam_stdlib = ">= 0.40.0 and < 2.0.0"
+wobble = { path = "../wobble" }
+[dev-dependencies]
+helpers = { path = "../../helpers" }
+"#,
+    )
+    .expect("write wibble config");
+    fs::write(
+        &root.join("packages/wobble/gleam.toml"),
+        r#"name = "wobble"
+[dependencies]
+gleam_stdlib = ">= 0.44.0 and < 2.0.0"
+"#,
+    )
+    .expect("write wobble config");
+
+    let paths = ProjectPaths::workspace_member(root.join("packages/wibble"), root.clone());
+    let member = crate::config::root_config(&paths).expect("read wibble config");
+    let config = workspace_config(&paths, &member)
+        .expect("workspace config")
+        .expect("package is a workspace member");
+
+    assert_eq!(config.name, "<workspace>");
+    assert_eq!(
+        config.dependencies,
+        HashMap::from([
+            ("wibble".into(), Requirement::path("packages/wibble")),
+            ("wobble".into(), Requirement::path("packages/wobble")),
+        ])
+    );
+    assert_eq!(
+        config.dev_dependencies,
+        HashMap::from([
+            (
+                "gleam_stdlib".into(),
+                Requirement::hex(">= 0.44.0 and < 2.0.0").expect("valid range")
+            ),
+            (
+                "helpers".into(),
+                Requirement::path("packages/wibble/../../helpers")
+            ),
+        ])
+    );
+}
+
+#[test]
+fn workspace_config_outside_a_workspace() {
+    let paths = ProjectPaths::new("/app".into());
+    let config = workspace_config(&paths, &PackageConfig::default()).expect("no workspace");
+    assert_eq!(config, None);
+}
+
+#[test]
+fn combine_hex_requirements() {
+    let combined = combine_requirements(
+        "wibble",
+        Requirement::hex("~> 1.0 or ~> 2.0").expect("valid range"),
+        Requirement::hex(">= 1.5.0").expect("valid range"),
+        Utf8Path::new("/app"),
+    )
+    .expect("compatible requirements");
+    assert_eq!(
+        combined,
+        Requirement::hex(">= 1.5.0 and < 3.0.0").expect("valid range")
+    );
+}
+
+#[test]
+fn combine_incompatible_hex_requirements() {
+    let result = combine_requirements(
+        "wibble",
+        Requirement::hex("~> 1.0").expect("valid range"),
+        Requirement::hex(">= 2.0.0").expect("valid range"),
+        Utf8Path::new("/app"),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ProvidedDependencyConflict { package, .. }) if package == "wibble"
+    ));
+}
+
+#[test]
+fn combine_requirements_from_different_sources() {
+    let result = combine_requirements(
+        "wibble",
+        Requirement::hex(">= 1.0.0").expect("valid range"),
+        Requirement::git("https://github.com/gleam-lang/wibble.git", "main"),
+        Utf8Path::new("/app"),
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ProvidedDependencyConflict { package, .. }) if package == "wibble"
+    ));
+}
+
+#[test]
+fn workspace_member_manifest_only_has_its_dependencies() {
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![
+            manifest_package("wibble", "1.0.0", vec!["wobble".into(), "stdlib".into()]),
+            manifest_package("wobble", "1.0.0", vec!["stdlib".into()]),
+            manifest_package("wubble", "1.0.0", vec!["json".into()]),
+            manifest_package("stdlib", "1.0.0", vec![]),
+            manifest_package("json", "1.0.0", vec!["stdlib".into()]),
+            manifest_package("gleeunit", "1.0.0", vec!["stdlib".into()]),
+        ],
+    };
+    let mut config = package_config(
+        HashMap::from([("wobble".into(), Requirement::path("../wobble"))]),
+        HashMap::from([(
+            "gleeunit".into(),
+            Requirement::hex(">= 1.0.0").expect("valid range"),
+        )]),
+    );
+    config.name = "wibble".into();
+
+    let names = |manifest: Manifest| {
+        manifest
+            .packages
+            .into_iter()
+            .map(|package| package.name)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(workspace_member_manifest(manifest.clone(), &config, Mode::Prod).expect("manifest")),
+        vec![EcoString::from("wobble"), "stdlib".into()]
+    );
+    assert_eq!(
+        names(workspace_member_manifest(manifest, &config, Mode::Dev).expect("manifest")),
+        vec![
+            EcoString::from("wobble"),
+            "stdlib".into(),
+            "gleeunit".into()
+        ]
+    );
+}
@@ -0,0 +1,177 @@
+use std::{cell::RefCell, rc::Rc};
+
+use clap::ValueEnum;
+use gleam_core::{
+    Error, Result,
+    ast::SrcSpan,
+    diagnostic::{self, Diagnostic, Label, Level, Location},
+    error::Unformatted,
+    warning::{Warning, WarningEmitterIO},
+};
+
+use crate::fs::ConsoleWarningEmitter;
+
+/// The format in which the errors and warnings of a command are printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    /// Coloured text, printed to stderr
+    #[default]
+    Human,
+    /// A JSON object for each error and warning, one per line, printed to stdout
+    Json,
+    /// A single SARIF 2.1.0 log, printed to stdout
+    Sarif,
+}
+
+/// The machine readable formats, which are printed to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MachineFormat {
+    Json,
+    Sarif,
+}
+
+/// Runs a command, reporting the warnings it emits and the error it may
+/// return in the given format.
+///
+/// With the machine readable formats any error is printed to stdout together
+/// with the warnings and the process exits with a non-zero status, rather than
+/// the error being returned to be printed as text.
+///
+pub fn report<T>(
+    format: MessageFormat,
+    command: impl FnOnce(Rc<dyn WarningEmitterIO>) -> Result<T>,
+) -> Result<()> {
+    let format = match format {
+        MessageFormat::Human => return command(Rc::new(ConsoleWarningEmitter)).map(|_| ()),
+        MessageFormat::Json => MachineFormat::Json,
+        MessageFormat::Sarif => MachineFormat::Sarif,
+    };
+
+    let emitter = Rc::new(DiagnosticEmitter::new(format));
+    let error = command(emitter.clone()).err();
+
+    let mut diagnostics = emitter.diagnostics.take();
+    if let Some(error) = &error {
+        diagnostics.extend(error_diagnostics(error));
+    }
+
+    match format {
+        MachineFormat::Json => {
+            for diagnostic in diagnostics {
+                println!("{}", diagnostic.to_json());
+            }
+        }
+        MachineFormat::Sarif => println!("{}", diagnostic::to_sarif(&diagnostics)),
+    }
+
+    if error.is_some() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Prints the long form explanation of an error or warning code.
+pub fn explain(code: &str) -> Result<()> {
+    let code = code.trim().to_uppercase();
+    match diagnostic::explanation(&code) {
+        Some(explanation) => {
+            print!("{explanation}");
+            Ok(())
+        }
+        None => Err(Error::UnknownDiagnosticCode { code: code.into() }),
+    }
+}
+
+/// Prints warnings as JSON lines as soon as they are emitted, or collects them
+/// to be printed in the SARIF log once the command has finished.
+#[derive(Debug)]
+struct DiagnosticEmitter {
+    format: MachineFormat,
+    diagnostics: RefCell<Vec<Diagnostic>>,
+}
+
+impl DiagnosticEmitter {
+    fn new(format: MachineFormat) -> Self {
+        Self {
+            format,
+            diagnostics: RefCell::new(vec![]),
+        }
+    }
+}
+
+impl WarningEmitterIO for DiagnosticEmitter {
+    fn emit_warning(&self, warning: Warning) {
+        let diagnostic = warning.to_diagnostic();
+        match self.format {
+            MachineFormat::Json => println!("{}", diagnostic.to_json()),
+            MachineFormat::Sarif => self.diagnostics.borrow_mut().push(diagnostic),
+        }
+    }
+}
+
+fn error_diagnostics(error: &Error) -> Vec<Diagnostic> {
+    match error {
+        // When printed as text all the unformatted files are listed in a
+        // single error, but tools want to be able to point at each file.
+        Error::Format { problem_files } => problem_files
+            .iter()
+            .map(|file| unformatted_file(file, error.code()))
+            .collect(),
+        _ => error.to_diagnostics(),
+    }
+}
+
+fn unformatted_file(file: &Unformatted, code: Option<&'static str>) -> Diagnostic {
+    let start = first_difference(&file.input, &file.output);
+    Diagnostic {
+        title: "File has not been formatted".into(),
+        text: "".into(),
+        hint: Some("Run `gleam format` to format it.".into()),
+        level: Level::Error,
+        code,
+        location: Some(Location {
+            src: file.input.clone(),
+            path: file.source.clone(),
+            label: Label {
+                text: Some("The formatted code differs from here".into()),
+                span: SrcSpan::new(start, start),
+            },
+            extra_labels: vec![],
+        }),
+    }
+}
+
+/// The byte offset of the first character that differs between the two
+/// strings.
+fn first_difference(input: &str, output: &str) -> u32 {
+    let mut offset = input
+        .bytes()
+        .zip(output.bytes())
+        .take_while(|(input, output)| input == output)
+        .count();
+    while !input.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_difference_of_equal_strings() {
+        assert_eq!(first_difference("wibble", "wibble"), 6);
+    }
+
+    #[test]
+    fn first_difference_of_different_strings() {
+        assert_eq!(first_difference("wibble  ", "wibble\n"), 6);
+    }
+
+    #[test]
+    fn first_difference_is_a_char_boundary() {
+        // Both characters are encoded as two bytes starting with `0xC3`
+        assert_eq!(first_difference("wobbleé", "wobbleü"), 6);
+    }
+}
@@ -143,20 +143,7 @@ pub fn typescript_prelude() -> Result<()> {
 }
 
 pub fn package_interface(paths: &ProjectPaths, out: Utf8PathBuf) -> Result<()> {
-    // Build the project
-    let mut built = crate::build::main(
-        paths,
-        Options {
-            mode: Mode::Prod,
-            target: None,
-            codegen: Codegen::All,
-            compile: Compile::All,
-            warnings_as_errors: false,
-            root_target_support: TargetSupport::Enforced,
-            no_print_progress: false,
-        },
-        crate::build::download_dependencies(paths, crate::cli::Reporter::new())?,
-    )?;
+    let mut built = crate::api::build(paths)?;
     built.root_package.attach_doc_and_module_comments();
 
     let out = gleam_core::docs::generate_json_package_interface(
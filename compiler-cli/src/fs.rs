@@ -3,8 +3,9 @@ use gleam_core::{
     build::{NullTelemetry, Target},
     error::{Error, FileIoAction, FileKind, OS, ShellCommandFailureReason, parse_os},
     io::{
-        BeamCompiler, Command, CommandExecutor, Content, DirEntry, FileSystemReader,
-        FileSystemWriter, OutputFile, ReadDir, Stdio, WrappedReader, is_native_file_extension,
+        BackgroundCommand, BeamCompiler, Command, CommandExecutor, CommandOutput, CommandSpawner,
+        Content, DirEntry, FileSystemReader, FileSystemWriter, OutputFile, ReadDir, Stdio,
+        WrappedReader, is_native_file_extension,
     },
     language_server::{DownloadDependencies, Locker, MakeLocker},
     manifest::Manifest,
@@ -15,8 +16,9 @@ use std::{
     collections::HashSet,
     fmt::Debug,
     fs::File,
-    io::{self, BufRead, BufReader, Write},
-    sync::{Arc, Mutex, OnceLock},
+    io::{self, BufRead, BufReader, Read, Write},
+    sync::{Arc, Mutex, OnceLock, mpsc},
+    thread,
     time::SystemTime,
 };
 
@@ -205,19 +207,111 @@ impl CommandExecutor for ProjectIO {
 
         match result {
             Ok(status) => Ok(status.code().unwrap_or_default()),
+            Err(error) => Err(command_error(program, error)),
+        }
+    }
+}
+
+impl CommandSpawner for ProjectIO {
+    fn spawn(
+        &self,
+        command: BackgroundCommand,
+        mut on_output: Box<dyn FnMut(CommandOutput) + Send>,
+    ) -> Result<(), Error> {
+        let BackgroundCommand {
+            program,
+            args,
+            env,
+            cwd,
+        } = command;
+        tracing::trace!(program=program, args=?args.join(" "), env=?env, cwd=?cwd, "command_spawn");
+        let mut child = std::process::Command::new(&program)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .envs(env.iter().map(|pair| (&pair.0, &pair.1)))
+            .current_dir(cwd.unwrap_or_else(|| Utf8Path::new("./").to_path_buf()))
+            .spawn()
+            .map_err(|error| command_error(program, error))?;
+
+        // The lines of both outputs are read on their own threads, so that
+        // neither can fill up while the other one is being waited on.
+        let (sender, receiver) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            forward_lines(stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forward_lines(stderr, sender);
+        }
 
-            Err(error) => Err(match error.kind() {
-                io::ErrorKind::NotFound => Error::ShellProgramNotFound {
-                    program,
-                    os: get_os(),
-                },
+        let _ = thread::spawn(move || {
+            for line in receiver {
+                on_output(CommandOutput::Line(line));
+            }
+            let code = child.wait().ok().and_then(|status| status.code());
+            on_output(CommandOutput::Exit(code));
+        });
+        Ok(())
+    }
+}
 
-                other => Error::ShellCommand {
-                    program,
-                    reason: ShellCommandFailureReason::IoError(other),
-                },
-            }),
+fn forward_lines(output: impl Read + Send + 'static, sender: mpsc::Sender<String>) {
+    let _ = thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
         }
+    });
+}
+
+/// Start a command without waiting for it to finish, returning the running
+/// child process.
+pub fn spawn(command: Command) -> Result<std::process::Child, Error> {
+    let stdout = command.stdio.get_process_stdio();
+    spawn_with_stdout(command, stdout)
+}
+
+/// Start a command without waiting for it to finish, returning the running
+/// child process with its standard output piped so it can be read.
+pub fn spawn_with_piped_stdout(command: Command) -> Result<std::process::Child, Error> {
+    spawn_with_stdout(command, std::process::Stdio::piped())
+}
+
+fn spawn_with_stdout(
+    command: Command,
+    stdout: std::process::Stdio,
+) -> Result<std::process::Child, Error> {
+    let Command {
+        program,
+        args,
+        env,
+        cwd,
+        stdio,
+    } = command;
+    tracing::trace!(program=program, args=?args.join(" "), env=?env, cwd=?cwd, "command_spawn");
+    std::process::Command::new(&program)
+        .args(args)
+        .stdin(stdio.get_process_stdio())
+        .stdout(stdout)
+        .envs(env.iter().map(|pair| (&pair.0, &pair.1)))
+        .current_dir(cwd.unwrap_or_else(|| Utf8Path::new("./").to_path_buf()))
+        .spawn()
+        .map_err(|error| command_error(program, error))
+}
+
+fn command_error(program: String, error: io::Error) -> Error {
+    match error.kind() {
+        io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+            program,
+            os: get_os(),
+        },
+
+        other => Error::ShellCommand {
+            program,
+            reason: ShellCommandFailureReason::IoError(other),
+        },
     }
 }
 
@@ -608,19 +702,22 @@ pub fn copy(
         .map(|_| ())
 }
 
-// pub fn rename(path: impl AsRef<Utf8Path> + Debug, to: impl AsRef<Utf8Path> + Debug) -> Result<(), Error> {
-//     tracing::trace!(from=?path, to=?to, "renaming_file");
+pub fn rename(
+    path: impl AsRef<Utf8Path> + Debug,
+    to: impl AsRef<Utf8Path> + Debug,
+) -> Result<(), Error> {
+    tracing::trace!(from=?path, to=?to, "renaming_file");
 
-//     // TODO: include the destination in the error message
-//     std::fs::rename(&path, &to)
-//         .map_err(|err| Error::FileIo {
-//             action: FileIoAction::Rename,
-//             kind: FileKind::File,
-//             path: Utf8PathBuf::from(path.as_ref()),
-//             err: Some(err.to_string()),
-//         })
-//         .map(|_| ())
-// }
+    // TODO: include the destination in the error message
+    std::fs::rename(path.as_ref(), to.as_ref())
+        .map_err(|err| Error::FileIo {
+            action: FileIoAction::Rename,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from(path.as_ref()),
+            err: Some(err.to_string()),
+        })
+        .map(|_| ())
+}
 
 pub fn copy_dir(
     path: impl AsRef<Utf8Path> + Debug,
@@ -187,3 +187,46 @@ HOME_URL=\"https://www.ubuntu.com/\"
         "id first"
     );
 }
+
+#[cfg(unix)]
+fn spawn_outputs(script: &str) -> Vec<gleam_core::io::CommandOutput> {
+    use gleam_core::io::{BackgroundCommand, CommandSpawner};
+    use std::sync::mpsc;
+
+    let (sender, receiver) = mpsc::channel();
+    let command = BackgroundCommand {
+        program: "sh".into(),
+        args: vec!["-c".into(), script.into()],
+        env: vec![],
+        cwd: None,
+    };
+    super::ProjectIO::new()
+        .spawn(
+            command,
+            Box::new(move |output| sender.send(output).expect("send output")),
+        )
+        .expect("spawn");
+    receiver.into_iter().collect_vec()
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_reports_output_and_exit_code() {
+    use gleam_core::io::CommandOutput;
+
+    assert_eq!(
+        spawn_outputs("echo wibble; exit 3"),
+        vec![
+            CommandOutput::Line("wibble".into()),
+            CommandOutput::Exit(Some(3))
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn spawn_reports_no_exit_code_when_killed() {
+    use gleam_core::io::CommandOutput;
+
+    assert_eq!(spawn_outputs("kill -9 $$"), vec![CommandOutput::Exit(None)]);
+}
@@ -52,13 +52,16 @@
 extern crate pretty_assertions;
 
 mod add;
+mod api;
 mod beam_compiler;
 mod build;
 mod build_lock;
 mod cli;
 mod compile_package;
 mod config;
+mod coverage;
 mod dependencies;
+mod diagnostics;
 mod docs;
 mod export;
 mod fix;
@@ -71,21 +74,28 @@ mod new;
 mod panic;
 mod publish;
 mod remove;
+mod rename_module;
 pub mod run;
 mod shell;
+pub mod test_runner;
+mod watch;
 
 use config::root_config;
+use diagnostics::MessageFormat;
 use fs::{get_current_directory, get_project_root};
 pub use gleam_core::error::{Error, Result};
 
 use gleam_core::{
     analyse::TargetSupport,
     build::{Codegen, Compile, Mode, NullTelemetry, Options, Runtime, Target},
+    config::WorkspaceConfig,
     hex::RetirementReason,
     paths::ProjectPaths,
+    test_runner::TestFilter,
     version::COMPILER_VERSION,
+    warning::WarningEmitterIO,
 };
-use std::str::FromStr;
+use std::{rc::Rc, str::FromStr, time::Duration};
 
 use camino::Utf8PathBuf;
 
@@ -153,12 +163,25 @@ enum Command {
         /// Don't print progress information
         #[clap(long)]
         no_print_progress: bool,
+
+        /// Rebuild the project whenever its files, or those of its path
+        /// dependencies, change
+        #[arg(long)]
+        watch: bool,
+
+        /// The format in which errors and warnings are printed
+        #[arg(long, value_enum, default_value_t, conflicts_with = "watch")]
+        message_format: MessageFormat,
     },
 
     /// Type check the project
     Check {
         #[arg(short, long, ignore_case = true, help = target_doc())]
         target: Option<Target>,
+
+        /// The format in which errors and warnings are printed
+        #[arg(long, value_enum, default_value_t)]
+        message_format: MessageFormat,
     },
 
     /// Publish the project to the Hex package manager
@@ -179,6 +202,10 @@ enum Command {
     #[command(subcommand)]
     Docs(Docs),
 
+    /// Work with the public API of the project
+    #[command(subcommand)]
+    Api(Api),
+
     /// Work with dependency packages
     #[command(subcommand)]
     Deps(Dependencies),
@@ -206,10 +233,23 @@ enum Command {
         /// Check if inputs are formatted without changing them
         #[arg(long)]
         check: bool,
+
+        /// The format in which errors are printed
+        #[arg(long, value_enum, default_value_t, requires = "check")]
+        message_format: MessageFormat,
     },
     /// Rewrite deprecated Gleam code
     Fix,
 
+    /// Rename or move a module, updating the imports of the modules using it
+    RenameModule {
+        /// The name of the module to rename, for example `app/wibble`
+        module: String,
+
+        /// The new name of the module, for example `app/internal/wibble`
+        new_name: String,
+    },
+
     /// Start an Erlang shell
     Shell,
 
@@ -230,6 +270,11 @@ enum Command {
         #[clap(long)]
         no_print_progress: bool,
 
+        /// Rebuild and restart the project whenever its files, or those of its
+        /// path dependencies, change
+        #[arg(long)]
+        watch: bool,
+
         arguments: Vec<String>,
     },
 
@@ -242,6 +287,38 @@ enum Command {
         #[arg(long, ignore_case = true, help = runtime_doc())]
         runtime: Option<Runtime>,
 
+        /// Rebuild and run the tests again whenever the project's files, or those
+        /// of its path dependencies, change
+        #[arg(long)]
+        watch: bool,
+
+        /// Only run the tests whose name, such as `wibble_test.wobble_test`,
+        /// contains this pattern
+        #[arg(long, value_name = "PATTERN")]
+        filter: Option<String>,
+
+        /// Only run the test with this exact name, such as
+        /// `wibble_test.wobble_test`
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Only run the tests defined in this module
+        #[arg(long)]
+        module: Option<String>,
+
+        /// Stop any test that runs for longer than this number of seconds
+        #[arg(long, value_name = "SECONDS")]
+        timeout: Option<u64>,
+
+        /// Write a JUnit XML report of the test results to this file
+        #[arg(long, value_name = "PATH")]
+        junit: Option<Utf8PathBuf>,
+
+        /// Measure the code coverage of the tests, writing lcov and Cobertura
+        /// reports to `build/coverage-report`
+        #[arg(long, conflicts_with = "watch")]
+        coverage: bool,
+
         arguments: Vec<String>,
     },
 
@@ -254,6 +331,11 @@ enum Command {
         #[arg(long, ignore_case = true, help = runtime_doc())]
         runtime: Option<Runtime>,
 
+        /// Rebuild and restart the entrypoint whenever the project's files, or
+        /// those of its path dependencies, change
+        #[arg(long)]
+        watch: bool,
+
         arguments: Vec<String>,
     },
 
@@ -290,6 +372,12 @@ enum Command {
     #[command(name = "lsp")]
     LanguageServer,
 
+    /// Explain an error or warning code in detail
+    Explain {
+        /// The code to explain, for example E0203
+        code: String,
+    },
+
     /// Export something useful from the Gleam project
     #[command(subcommand)]
     Export(ExportTarget),
@@ -449,6 +537,26 @@ enum Hex {
     Authenticate,
 }
 
+#[derive(Subcommand, Debug)]
+enum Api {
+    /// Compare the public API with the one of a previous version, classifying
+    /// each change as breaking, additive or patch
+    ///
+    /// This command compares with the latest version published to Hex unless
+    /// another version or a file is given.
+    ///
+    Diff {
+        /// The version published to Hex to compare with
+        #[arg(long, conflicts_with = "file")]
+        version: Option<String>,
+
+        /// A package interface file to compare with, as written by
+        /// `gleam export package-interface`
+        #[arg(long)]
+        file: Option<Utf8PathBuf>,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum Docs {
     /// Render HTML docs locally
@@ -513,14 +621,37 @@ fn parse_and_run_command() -> Result<(), Error> {
             target,
             warnings_as_errors,
             no_print_progress,
+            watch,
+            message_format,
         } => {
-            let paths = find_project_paths()?;
-            command_build(&paths, target, warnings_as_errors, no_print_progress)
+            if watch {
+                let paths = find_project_paths()?;
+                return command_watch(&paths, target, warnings_as_errors, no_print_progress);
+            }
+            let packages = find_packages_paths()?;
+            diagnostics::report(message_format, |warnings| {
+                packages.iter().try_for_each(|paths| {
+                    command_build(
+                        paths,
+                        target,
+                        warnings_as_errors,
+                        no_print_progress,
+                        warnings.clone(),
+                    )
+                })
+            })
         }
 
-        Command::Check { target } => {
-            let paths = find_project_paths()?;
-            command_check(&paths, target)
+        Command::Check {
+            target,
+            message_format,
+        } => {
+            let packages = find_packages_paths()?;
+            diagnostics::report(message_format, |warnings| {
+                packages
+                    .iter()
+                    .try_for_each(|paths| command_check(paths, target, warnings.clone()))
+            })
         }
 
         Command::Docs(Docs::Build { open, target }) => {
@@ -535,27 +666,37 @@ fn parse_and_run_command() -> Result<(), Error> {
 
         Command::Docs(Docs::Remove { package, version }) => docs::remove(package, version),
 
+        Command::Api(Api::Diff { version, file }) => {
+            let paths = find_project_paths()?;
+            api::diff(&paths, version, file)
+        }
+
         Command::Format {
             stdin,
             files,
             check,
-        } => format::run(stdin, check, files),
+            message_format,
+        } => diagnostics::report(message_format, |_| format::run(stdin, check, files)),
 
         Command::Fix => {
             let paths = find_project_paths()?;
             fix::run(&paths)
         }
 
-        Command::Deps(Dependencies::List) => {
+        Command::RenameModule { module, new_name } => {
             let paths = find_project_paths()?;
-            dependencies::list(&paths)
+            rename_module::run(&paths, module, new_name)
         }
 
-        Command::Deps(Dependencies::Download) => {
+        Command::Deps(Dependencies::List) => {
             let paths = find_project_paths()?;
-            download_dependencies(&paths)
+            dependencies::list(&paths)
         }
 
+        Command::Deps(Dependencies::Download) => find_packages_paths()?
+            .iter()
+            .try_for_each(download_dependencies),
+
         Command::Deps(Dependencies::Update(options)) => {
             let paths = find_project_paths()?;
             dependencies::update(&paths, options.packages)
@@ -581,9 +722,11 @@ fn parse_and_run_command() -> Result<(), Error> {
             runtime,
             module,
             no_print_progress,
+            watch,
         } => {
             let paths = find_project_paths()?;
-            run::command(
+            let run = if watch { run::watch } else { run::command };
+            run(
                 &paths,
                 arguments,
                 target,
@@ -598,26 +741,46 @@ fn parse_and_run_command() -> Result<(), Error> {
             target,
             arguments,
             runtime,
+            watch,
+            filter,
+            name,
+            module,
+            timeout,
+            junit,
+            coverage,
         } => {
-            let paths = find_project_paths()?;
-            run::command(
-                &paths,
-                arguments,
-                target,
-                runtime,
-                None,
-                run::Which::Test,
-                false,
-            )
+            let options = test_runner::Options {
+                filter: TestFilter {
+                    pattern: filter,
+                    name,
+                    module,
+                },
+                timeout: timeout.map(Duration::from_secs),
+                junit,
+                coverage,
+            };
+            if watch {
+                let paths = find_project_paths()?;
+                return run::watch_tests(&paths, arguments, target, runtime, options, false);
+            }
+            // The reports are written for a single package
+            let packages = if options.junit.is_some() || options.coverage {
+                vec![find_project_paths()?]
+            } else {
+                find_packages_paths()?
+            };
+            run::test(&packages, arguments, target, runtime, options, false)
         }
 
         Command::Dev {
             target,
             arguments,
             runtime,
+            watch,
         } => {
             let paths = find_project_paths()?;
-            run::command(
+            let run = if watch { run::watch } else { run::command };
+            run(
                 &paths,
                 arguments,
                 target,
@@ -676,6 +839,8 @@ fn parse_and_run_command() -> Result<(), Error> {
 
         Command::LanguageServer => lsp::main(),
 
+        Command::Explain { code } => diagnostics::explain(&code),
+
         Command::Export(ExportTarget::ErlangShipment) => {
             let paths = find_project_paths()?;
             export::erlang_shipment(&paths)
@@ -697,8 +862,12 @@ fn parse_and_run_command() -> Result<(), Error> {
     }
 }
 
-fn command_check(paths: &ProjectPaths, target: Option<Target>) -> Result<()> {
-    let _ = build::main(
+fn command_check(
+    paths: &ProjectPaths,
+    target: Option<Target>,
+    warnings: Rc<dyn WarningEmitterIO>,
+) -> Result<()> {
+    let _ = build::main_with_warnings(
         paths,
         Options {
             root_target_support: TargetSupport::Enforced,
@@ -710,37 +879,59 @@ fn command_check(paths: &ProjectPaths, target: Option<Target>) -> Result<()> {
             no_print_progress: false,
         },
         build::download_dependencies(paths, cli::Reporter::new())?,
+        warnings,
     )?;
     Ok(())
 }
 
+fn build_options(
+    target: Option<Target>,
+    warnings_as_errors: bool,
+    no_print_progress: bool,
+) -> Options {
+    Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors,
+        codegen: Codegen::All,
+        compile: Compile::All,
+        mode: Mode::Dev,
+        target,
+        no_print_progress,
+    }
+}
+
 fn command_build(
     paths: &ProjectPaths,
     target: Option<Target>,
     warnings_as_errors: bool,
     no_print_progress: bool,
+    warnings: Rc<dyn WarningEmitterIO>,
 ) -> Result<()> {
+    let options = build_options(target, warnings_as_errors, no_print_progress);
     let manifest = if no_print_progress {
         build::download_dependencies(paths, NullTelemetry)?
     } else {
         build::download_dependencies(paths, cli::Reporter::new())?
     };
-    let _ = build::main(
-        paths,
-        Options {
-            root_target_support: TargetSupport::Enforced,
-            warnings_as_errors,
-            codegen: Codegen::All,
-            compile: Compile::All,
-            mode: Mode::Dev,
-            target,
-            no_print_progress,
-        },
-        manifest,
-    )?;
+    let _ = build::main_with_warnings(paths, options, manifest, warnings)?;
     Ok(())
 }
 
+fn command_watch(
+    paths: &ProjectPaths,
+    target: Option<Target>,
+    warnings_as_errors: bool,
+    no_print_progress: bool,
+) -> Result<()> {
+    let options = build_options(target, warnings_as_errors, no_print_progress);
+    watch::watch(
+        paths,
+        no_print_progress,
+        |_| Ok((options.clone(), ())),
+        |_, _| Ok(()),
+    )
+}
+
 fn print_config(paths: &ProjectPaths) -> Result<()> {
     let config = root_config(paths)?;
     println!("{config:#?}");
@@ -762,9 +953,39 @@ fn initialise_logger() {
         .init();
 }
 
+/// The paths of the package in the current directory, which may be a member of
+/// a workspace. Returns an error in the root of a workspace, which is not a
+/// package itself.
 fn find_project_paths() -> Result<ProjectPaths> {
     let current_dir = get_current_directory()?;
-    get_project_root(current_dir).map(ProjectPaths::new)
+    let root = get_project_root(current_dir)?;
+    let io = fs::ProjectIO::new();
+    if let Some(workspace) = WorkspaceConfig::read(root.join("gleam.toml"), &io)? {
+        return Err(Error::NotAvailableInWorkspaceRoot {
+            path: root,
+            members: workspace.members,
+        });
+    }
+    match WorkspaceConfig::find_root(&root, &io)? {
+        Some(workspace) => Ok(ProjectPaths::workspace_member(root, workspace)),
+        None => Ok(ProjectPaths::new(root)),
+    }
+}
+
+/// The paths of the packages a command run in the current directory applies
+/// to: all the members in the root of a workspace, or the package in the
+/// current directory otherwise.
+fn find_packages_paths() -> Result<Vec<ProjectPaths>> {
+    let current_dir = get_current_directory()?;
+    let root = get_project_root(current_dir)?;
+    match WorkspaceConfig::read(root.join("gleam.toml"), &fs::ProjectIO::new())? {
+        Some(workspace) => Ok(workspace
+            .members
+            .iter()
+            .map(|member| ProjectPaths::workspace_member(root.join(member), root.clone()))
+            .collect()),
+        None => Ok(vec![find_project_paths()?]),
+    }
 }
 
 #[cfg(test)]
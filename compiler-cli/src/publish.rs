@@ -10,6 +10,7 @@ use gleam_core::{
     error::{SmallVersion, wrap},
     hex,
     manifest::ManifestPackageSource,
+    package_interface::{PackageInterface, diff::InterfaceDiff},
     paths::{self, ProjectPaths},
     requirement::Requirement,
     type_,
@@ -55,6 +56,12 @@ pub fn command(paths: &ProjectPaths, replace: bool, i_am_sure: bool) -> Result<(
         &cached_modules,
     )?)?;
 
+    let interface = PackageInterface::from_package(&compile_result, &cached_modules);
+    if !check_for_incompatible_version(&config, &interface, i_am_sure)? {
+        println!("Not publishing.");
+        return Ok(());
+    }
+
     // Ask user if this is correct
     if !generated_files_added.is_empty() {
         println!("\nGenerated files:");
@@ -206,6 +213,54 @@ For example:
     Ok(())
 }
 
+/// Refuse to publish a version lower than semantic versioning requires for
+/// the changes to the public API since the previous release, unless the user
+/// is sure
+fn check_for_incompatible_version(
+    config: &PackageConfig,
+    interface: &PackageInterface,
+    i_am_sure: bool,
+) -> Result<bool, Error> {
+    let versions = crate::api::published_versions(&config.name)?;
+    let Some(previous) = crate::api::previous_version(versions, &config.version) else {
+        return Ok(true);
+    };
+
+    let previous = match crate::api::published_interface(&config.name, previous.clone()) {
+        Ok(previous) => previous,
+        Err(Error::PackageInterfaceUnavailable { reason, .. }) => {
+            println!(
+                "The public API cannot be compared with the one of v{previous} to check
+the version follows semantic versioning. {reason}"
+            );
+            let should_publish = i_am_sure || cli::confirm("\nDo you wish to continue?")?;
+            println!();
+            return Ok(should_publish);
+        }
+        Err(error) => return Err(error),
+    };
+
+    let diff = InterfaceDiff::new(&previous.interface, interface);
+    let (Some(changes), Some(minimum)) = (diff.kind(), diff.minimum_version(&previous.version))
+    else {
+        return Ok(true);
+    };
+    if config.version >= minimum {
+        return Ok(true);
+    }
+
+    println!("Changes since v{}:\n\n{}", previous.version, diff.render());
+    if i_am_sure {
+        return Ok(true);
+    }
+    Err(Error::CannotPublishIncompatibleVersion {
+        previous: previous.version.to_string().into(),
+        version: config.version.to_string().into(),
+        minimum: minimum.to_string().into(),
+        changes,
+    })
+}
+
 fn check_repo_url(config: &PackageConfig, i_am_sure: bool) -> Result<bool, Error> {
     let Some(repo) = config.repository.as_ref() else {
         return Ok(true);
@@ -0,0 +1,192 @@
+//! Renaming or moving a module of the project, along with the modules nested
+//! under it, and rewriting the imports of the modules using them. See
+//! `gleam_core::rename_module` for how the code is updated.
+
+use std::collections::HashSet;
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use gleam_core::{
+    Error, Result,
+    analyse::TargetSupport,
+    build::{
+        Codegen, Compile, Mode, Module, NullTelemetry, Options, PackageCompiler,
+        SourceFingerprints, StaleTracker, Target, TargetCodegenConfiguration,
+        package_compiler::CheckModuleConflicts,
+    },
+    config::PackageConfig,
+    paths::ProjectPaths,
+    rename_module::{
+        ModuleRename, apply_edits, import_edits, internal_modules_edits, is_valid_module_name,
+        renamed_modules,
+    },
+    uid::UniqueIdGenerator,
+    warning::WarningEmitter,
+};
+
+use crate::{build, cli, compile_package::load_libraries, fs::ProjectIO};
+
+pub fn run(paths: &ProjectPaths, module: String, new_name: String) -> Result<()> {
+    // The other members of the workspace may import the modules of this
+    // package, and their imports would not be updated.
+    if let Some(workspace) = paths.workspace() {
+        return Err(Error::CannotRenameModuleInWorkspace {
+            workspace: workspace.to_path_buf(),
+        });
+    }
+
+    if !is_valid_module_name(&new_name) {
+        return Err(Error::InvalidModuleName { module: new_name });
+    }
+
+    let config = crate::config::root_config(paths)?;
+    let modules = analyse_root_package(paths, &config)?;
+
+    let renames = renamed_modules(
+        modules.iter().map(|module| &module.name),
+        &module,
+        &new_name,
+    );
+    if renames.is_empty() {
+        return Err(Error::ModuleDoesNotExist {
+            module: module.into(),
+            suggestion: None,
+        });
+    }
+
+    // Work out every change before making any of them, so that nothing is
+    // changed if a module would replace another one.
+    let mut moves = vec![];
+    for rename in &renames {
+        let Some(renamed) = find_module(&modules, &rename.old_name) else {
+            continue;
+        };
+        let new_path = renamed_path(renamed, rename);
+        if let Some(existing) = find_module(&modules, &rename.new_name) {
+            return Err(Error::DuplicateModule {
+                module: rename.new_name.clone(),
+                first: existing.input_path.clone(),
+                second: renamed.input_path.clone(),
+            });
+        }
+        if new_path.exists() {
+            return Err(Error::DuplicateModule {
+                module: rename.new_name.clone(),
+                first: new_path,
+                second: renamed.input_path.clone(),
+            });
+        }
+        moves.push((renamed.input_path.clone(), new_path));
+    }
+
+    let mut writes = vec![];
+    for module in &modules {
+        let edits = import_edits(module, &renames);
+        if !edits.is_empty() {
+            writes.push((module.input_path.clone(), apply_edits(&module.code, &edits)));
+        }
+    }
+
+    let root_config = paths.root_config();
+    let toml = crate::fs::read(&root_config)?;
+    let edits = internal_modules_edits(&config, &toml, &renames);
+    if !edits.is_empty() {
+        writes.push((root_config, apply_edits(&toml, &edits)));
+    }
+
+    for (path, code) in writes {
+        crate::fs::write(&path, &code)?;
+    }
+
+    for (old_path, new_path) in moves {
+        if let Some(parent) = new_path.parent() {
+            crate::fs::mkdir(parent)?;
+        }
+        crate::fs::rename(old_path, new_path)?;
+    }
+
+    for rename in renames {
+        println!("- Renamed {} to {}", rename.old_name, rename.new_name);
+    }
+    Ok(())
+}
+
+/// Analyses every module of the root package, after compiling its
+/// dependencies. The build caches of the root package do not hold the typed
+/// AST needed to find the references to the renamed modules, so they are not
+/// used, and nothing is written to the build directory for the root package.
+///
+fn analyse_root_package(paths: &ProjectPaths, config: &PackageConfig) -> Result<Vec<Module>> {
+    let _ = build::main(
+        paths,
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            codegen: Codegen::None,
+            compile: Compile::DepsOnly,
+            mode: Mode::Dev,
+            target: None,
+            no_print_progress: false,
+        },
+        build::download_dependencies(paths, cli::Reporter::new())?,
+    )?;
+
+    let ids = UniqueIdGenerator::new();
+    let lib = paths.build_directory_for_target(Mode::Dev, config.target);
+    let mut module_interfaces = load_libraries(&ids, &lib)?;
+    module_interfaces.retain(|_, module| module.package != config.name);
+
+    let target = match config.target {
+        Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
+        Target::JavaScript => TargetCodegenConfiguration::JavaScript {
+            emit_typescript_definitions: false,
+            source_maps: config.javascript.source_maps,
+            prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+        },
+    };
+    // As no caches are written, the output directory is never created.
+    let out = paths.build_directory_for_package(Mode::Dev, config.target, "rename-module");
+
+    let mut compiler = PackageCompiler::new(
+        config,
+        Mode::Dev,
+        paths.root(),
+        &out,
+        &lib,
+        &target,
+        ids,
+        ProjectIO::new(),
+    );
+    compiler.write_metadata = false;
+    compiler.perform_codegen = false;
+    compiler.compile_beam_bytecode = false;
+    compiler.copy_native_files = false;
+    compiler.target_support = TargetSupport::Enforced;
+    compiler.check_module_conflicts = CheckModuleConflicts::Check;
+    let compiled = compiler
+        .compile(
+            &WarningEmitter::null(),
+            &mut module_interfaces,
+            &mut im::HashMap::new(),
+            &mut StaleTracker::default(),
+            &mut HashSet::new(),
+            &mut SourceFingerprints::default(),
+            &NullTelemetry,
+        )
+        .into_result()?;
+    Ok(compiled.modules)
+}
+
+fn find_module<'a>(modules: &'a [Module], name: &EcoString) -> Option<&'a Module> {
+    modules.iter().find(|module| &module.name == name)
+}
+
+/// The path the file of a module is moved to, in the same source directory.
+///
+fn renamed_path(module: &Module, rename: &ModuleRename) -> Utf8PathBuf {
+    let mut path = module.input_path.clone();
+    for _ in rename.old_name.split('/') {
+        let _ = path.pop();
+    }
+    path.join(format!("{}.gleam", rename.new_name))
+}
@@ -5,14 +5,19 @@ use ecow::EcoString;
 use gleam_core::{
     analyse::TargetSupport,
     build::{Built, Codegen, Compile, Mode, NullTelemetry, Options, Runtime, Target, Telemetry},
-    config::{DenoFlag, PackageConfig},
+    config::{DenoFlag, PackageConfig, SourceMaps},
+    coverage::erlang_coverage_term,
     error::Error,
     io::{Command, CommandExecutor, Stdio},
+    manifest::Manifest,
     paths::ProjectPaths,
+    test_runner::{
+        ERLANG_TESTS_FILE, JAVASCRIPT_RUNNER_FILE, TestFunction, erlang_tests, javascript_runner,
+    },
     type_::ModuleFunction,
 };
 
-use crate::{config::PackageKind, fs::ProjectIO};
+use crate::{config::PackageKind, coverage, fs::ProjectIO, test_runner};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Which {
@@ -46,7 +51,9 @@ pub fn command(
     std::process::exit(status);
 }
 
-pub fn setup(
+/// Run the project, building it again and restarting the program each time
+/// its source files change.
+pub fn watch(
     paths: &ProjectPaths,
     arguments: Vec<String>,
     target: Option<Target>,
@@ -54,129 +61,488 @@ pub fn setup(
     module: Option<String>,
     which: Which,
     no_print_progress: bool,
-) -> Result<Command, Error> {
-    // Validate the module path
-    if let Some(mod_path) = &module {
-        if !is_gleam_module(mod_path) {
-            return Err(Error::InvalidModuleName {
-                module: mod_path.to_owned(),
-            });
+) -> Result<(), Error> {
+    validate_module(&module)?;
+    let telemetry = telemetry(no_print_progress);
+    let mut running: Option<std::process::Child> = None;
+
+    crate::watch::watch(
+        paths,
+        no_print_progress,
+        // The entrypoint is found again whenever the `gleam.toml` changes, as
+        // the name or the target of the package may have changed.
+        |manifest| {
+            let entrypoint = Entrypoint::new(paths, manifest, target, module.clone(), which)?;
+            Ok((entrypoint.build_options(no_print_progress), entrypoint))
+        },
+        |entrypoint, built| {
+            let command =
+                entrypoint.command(paths, built, runtime, arguments.clone(), telemetry)?;
+
+            // The previous run of the program is stopped before the newly
+            // built one is started.
+            if let Some(mut child) = running.take() {
+                // The program may already have exited, in which case there is
+                // nothing to stop.
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            running = Some(crate::fs::spawn(command)?);
+            Ok(())
+        },
+    )
+}
+
+/// Run the tests of each of the given packages, which are either a single
+/// project or the members of a workspace. When the test module has a `main`
+/// function, as is the case when using a test framework such as gleeunit, and
+/// none of the built-in test runner's options have been given, that function
+/// is run. Otherwise the tests are run by the built-in test runner.
+pub fn test(
+    packages: &[ProjectPaths],
+    arguments: Vec<String>,
+    target: Option<Target>,
+    runtime: Option<Runtime>,
+    options: test_runner::Options,
+    no_print_progress: bool,
+) -> Result<(), Error> {
+    validate_test_options(&options)?;
+    let mut status = 0;
+    for paths in packages {
+        let package_status = test_package(
+            paths,
+            arguments.clone(),
+            target,
+            runtime,
+            &options,
+            no_print_progress,
+        )?;
+        // The tests of the other packages are still run if one fails, and the
+        // status of the first failure is the one exited with.
+        if status == 0 {
+            status = package_status;
         }
+    }
+    std::process::exit(status);
+}
+
+/// Run the tests of a single package, returning the exit status.
+fn test_package(
+    paths: &ProjectPaths,
+    arguments: Vec<String>,
+    target: Option<Target>,
+    runtime: Option<Runtime>,
+    options: &test_runner::Options,
+    no_print_progress: bool,
+) -> Result<i32, Error> {
+    let telemetry = telemetry(no_print_progress);
+    let manifest = download_dependencies(paths, no_print_progress)?;
+    let mut entrypoint = Entrypoint::new(paths, &manifest, target, None, Which::Test)?;
+    if options.coverage {
+        entrypoint.mode = Mode::Coverage;
+    }
+    let build_options = entrypoint.build_options(no_print_progress);
+    let built = if options.coverage {
+        entrypoint.validate_coverage_runtime(runtime)?;
+        coverage::clear(paths)?;
+        coverage::build(paths, entrypoint.target, build_options, manifest)?
+    } else {
+        crate::build::main(paths, build_options, manifest)?
     };
 
-    let telemetry: &'static dyn Telemetry = if no_print_progress {
+    if entrypoint.uses_main_function(&built, options) {
+        // Don't exit on ctrl+c as it is used by child erlang shell. The
+        // handler is already set if the tests of another package have run.
+        match ctrlc::set_handler(move || {}) {
+            Ok(()) | Err(ctrlc::Error::MultipleHandlers) => (),
+            Err(error) => panic!("Error setting Ctrl-C handler: {error}"),
+        }
+        let command = entrypoint.command(paths, &built, runtime, arguments, telemetry)?;
+        return ProjectIO::new().exec(command);
+    }
+
+    let tests = built.test_functions(entrypoint.target);
+    let passed = test_runner::run(paths, &built, tests, options, |tests| {
+        entrypoint.test_runner_command(
+            paths,
+            &built,
+            runtime,
+            arguments.clone(),
+            tests,
+            options.coverage,
+        )
+    })?;
+    if options.coverage {
+        coverage::report(paths, &built, entrypoint.target)?;
+    }
+    Ok(if passed { 0 } else { 1 })
+}
+
+/// Run the project's tests, building the project and running them again each
+/// time its source files change.
+pub fn watch_tests(
+    paths: &ProjectPaths,
+    arguments: Vec<String>,
+    target: Option<Target>,
+    runtime: Option<Runtime>,
+    options: test_runner::Options,
+    no_print_progress: bool,
+) -> Result<(), Error> {
+    validate_test_options(&options)?;
+    let telemetry = telemetry(no_print_progress);
+    let mut running: Option<std::process::Child> = None;
+
+    crate::watch::watch(
+        paths,
+        no_print_progress,
+        |manifest| {
+            let entrypoint = Entrypoint::new(paths, manifest, target, None, Which::Test)?;
+            Ok((entrypoint.build_options(no_print_progress), entrypoint))
+        },
+        |entrypoint, built| {
+            // The previous run of the test module is stopped before the
+            // tests are run again.
+            if let Some(mut child) = running.take() {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+
+            if entrypoint.uses_main_function(built, &options) {
+                let command =
+                    entrypoint.command(paths, built, runtime, arguments.clone(), telemetry)?;
+                running = Some(crate::fs::spawn(command)?);
+                return Ok(());
+            }
+
+            // The tests are run by the built-in runner before watching for
+            // changes again, so a failing test does not stop the watching.
+            let tests = built.test_functions(entrypoint.target);
+            let _ = test_runner::run(paths, built, tests, &options, |tests| {
+                entrypoint.test_runner_command(
+                    paths,
+                    built,
+                    runtime,
+                    arguments.clone(),
+                    tests,
+                    false,
+                )
+            })?;
+            Ok(())
+        },
+    )
+}
+
+fn validate_test_options(options: &test_runner::Options) -> Result<(), Error> {
+    match &options.filter.module {
+        Some(module) if !is_gleam_module(module) => Err(Error::InvalidModuleName {
+            module: module.to_owned(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub fn setup(
+    paths: &ProjectPaths,
+    arguments: Vec<String>,
+    target: Option<Target>,
+    runtime: Option<Runtime>,
+    module: Option<String>,
+    which: Which,
+    no_print_progress: bool,
+) -> Result<Command, Error> {
+    validate_module(&module)?;
+    let telemetry = telemetry(no_print_progress);
+    let manifest = download_dependencies(paths, no_print_progress)?;
+    let entrypoint = Entrypoint::new(paths, &manifest, target, module, which)?;
+    let options = entrypoint.build_options(no_print_progress);
+    let built = crate::build::main(paths, options, manifest)?;
+    entrypoint.command(paths, &built, runtime, arguments, telemetry)
+}
+
+fn validate_module(module: &Option<String>) -> Result<(), Error> {
+    if let Some(mod_path) = module
+        && !is_gleam_module(mod_path)
+    {
+        return Err(Error::InvalidModuleName {
+            module: mod_path.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+fn telemetry(no_print_progress: bool) -> &'static dyn Telemetry {
+    if no_print_progress {
         &NullTelemetry
     } else {
         &crate::cli::Reporter
-    };
+    }
+}
 
-    // Download dependencies
-    let manifest = if no_print_progress {
-        crate::build::download_dependencies(paths, NullTelemetry)?
+fn download_dependencies(paths: &ProjectPaths, no_print_progress: bool) -> Result<Manifest, Error> {
+    if no_print_progress {
+        crate::build::download_dependencies(paths, NullTelemetry)
     } else {
-        crate::build::download_dependencies(paths, crate::cli::Reporter::new())?
-    };
+        crate::build::download_dependencies(paths, crate::cli::Reporter::new())
+    }
+}
+
+/// The module whose main function is to be run, along with the configuration
+/// needed to build and run it.
+#[derive(Debug)]
+struct Entrypoint {
+    module: String,
+    target: Target,
+    /// The mode the project is built in, which decides the build directory
+    /// the module is run from.
+    mode: Mode,
+    package_kind: PackageKind,
+    root_config: PackageConfig,
+    mod_config: PackageConfig,
+}
+
+impl Entrypoint {
+    fn new(
+        paths: &ProjectPaths,
+        manifest: &Manifest,
+        target: Option<Target>,
+        module: Option<String>,
+        which: Which,
+    ) -> Result<Self, Error> {
+        // Get the config for the module that is being run to check the target.
+        // Also get the kind of the package the module belongs to: wether the module
+        // belongs to a dependency or to the root package.
+        let (mod_config, package_kind) = match &module {
+            Some(mod_path) => {
+                crate::config::find_package_config_for_module(mod_path, manifest, paths)?
+            }
+            _ => (crate::config::root_config(paths)?, PackageKind::Root),
+        };
 
-    // Get the config for the module that is being run to check the target.
-    // Also get the kind of the package the module belongs to: wether the module
-    // belongs to a dependency or to the root package.
-    let (mod_config, package_kind) = match &module {
-        Some(mod_path) => {
-            crate::config::find_package_config_for_module(mod_path, &manifest, paths)?
+        // The root config is required to run the project.
+        let root_config = crate::config::root_config(paths)?;
+
+        // Determine which module to run
+        let module = module.unwrap_or(match which {
+            Which::Src => root_config.name.to_string(),
+            Which::Test => format!("{}_test", &root_config.name),
+            Which::Dev => format!("{}_dev", &root_config.name),
+        });
+
+        let target = target.unwrap_or(mod_config.target);
+
+        Ok(Self {
+            module,
+            target,
+            mode: Mode::Dev,
+            package_kind,
+            root_config,
+            mod_config,
+        })
+    }
+
+    /// Whether the tests are run by the `main` function of the test module
+    /// rather than by the built-in test runner.
+    fn uses_main_function(&self, built: &Built, options: &test_runner::Options) -> bool {
+        if options.given() {
+            return false;
         }
-        _ => (crate::config::root_config(paths)?, PackageKind::Root),
-    };
+        // When the test module has a `main` function that cannot be run the
+        // error is reported rather than falling back to the built-in runner.
+        !matches!(
+            built.get_main_function(&self.module.as_str().into(), self.target),
+            Err(Error::ModuleDoesNotExist { .. } | Error::ModuleDoesNotHaveMainFunction { .. })
+        )
+    }
 
-    // The root config is required to run the project.
-    let root_config = crate::config::root_config(paths)?;
-
-    // Determine which module to run
-    let module = module.unwrap_or(match which {
-        Which::Src => root_config.name.to_string(),
-        Which::Test => format!("{}_test", &root_config.name),
-        Which::Dev => format!("{}_dev", &root_config.name),
-    });
-
-    let target = target.unwrap_or(mod_config.target);
-
-    let options = Options {
-        warnings_as_errors: false,
-        compile: match package_kind {
-            // If we're trying to run a dependecy module we do not compile and
-            // check the root package. So we can run the main function from a
-            // dependency's module even if the root package doesn't compile.
-            PackageKind::Dependency => Compile::DepsOnly,
-            PackageKind::Root => Compile::All,
-        },
-        codegen: Codegen::All,
-        mode: Mode::Dev,
-        target: Some(target),
-        root_target_support: match package_kind {
-            // The module we want to run is in the root package, so we make sure that the package
-            // can compile successfully for the current target.
-            PackageKind::Root => TargetSupport::Enforced,
-            // On the other hand, if we're trying to run a module that belongs to a dependency, we
-            // only care if the dependency can compile for the current target.
-            PackageKind::Dependency => TargetSupport::NotEnforced,
-        },
-        no_print_progress,
-    };
+    fn build_options(&self, no_print_progress: bool) -> Options {
+        Options {
+            warnings_as_errors: false,
+            compile: match self.package_kind {
+                // If we're trying to run a dependecy module we do not compile and
+                // check the root package. So we can run the main function from a
+                // dependency's module even if the root package doesn't compile.
+                PackageKind::Dependency => Compile::DepsOnly,
+                PackageKind::Root => Compile::All,
+            },
+            codegen: Codegen::All,
+            mode: self.mode,
+            target: Some(self.target),
+            root_target_support: match self.package_kind {
+                // The module we want to run is in the root package, so we make sure that the package
+                // can compile successfully for the current target.
+                PackageKind::Root => TargetSupport::Enforced,
+                // On the other hand, if we're trying to run a module that belongs to a dependency, we
+                // only care if the dependency can compile for the current target.
+                PackageKind::Dependency => TargetSupport::NotEnforced,
+            },
+            no_print_progress,
+        }
+    }
 
-    let built = crate::build::main(paths, options, manifest)?;
+    /// The command to run the main function of the module, once it has been
+    /// built.
+    fn command(
+        &self,
+        paths: &ProjectPaths,
+        built: &Built,
+        runtime: Option<Runtime>,
+        arguments: Vec<String>,
+        telemetry: &dyn Telemetry,
+    ) -> Result<Command, Error> {
+        let module = &self.module;
+
+        // A module can not be run if it does not exist or does not have a public main function.
+        let main_function = get_or_suggest_main_function(built, module, self.target)?;
+
+        telemetry.running(&format!("{module}.main"));
+
+        // Get the command to run the project.
+        match self.target {
+            Target::Erlang => {
+                // gleam modules are separated by `/`. Erlang modules are separated by `@`.
+                let module = module.replace('/', "@");
+                let package = &self.root_config.name;
+                self.erlang_command(
+                    paths,
+                    runtime,
+                    format!("{package}@@main:run({module})"),
+                    arguments,
+                )
+            }
+            Target::JavaScript => {
+                let entrypoint =
+                    write_javascript_entrypoint(paths, self.mode, &main_function.package, module)?;
+                self.javascript_command(runtime, entrypoint, arguments)
+            }
+        }
+    }
 
-    // A module can not be run if it does not exist or does not have a public main function.
-    let main_function = get_or_suggest_main_function(built, &module, target)?;
+    /// Coverage is measured on JavaScript by the V8 engine, which Bun does
+    /// not use.
+    fn validate_coverage_runtime(&self, runtime: Option<Runtime>) -> Result<(), Error> {
+        match (self.target, self.javascript_runtime(runtime)) {
+            (Target::JavaScript, Runtime::Bun) => Err(Error::CoverageUnsupportedRuntime {
+                runtime: Runtime::Bun,
+            }),
+            (Target::JavaScript, Runtime::NodeJs | Runtime::Deno) | (Target::Erlang, _) => Ok(()),
+        }
+    }
 
-    telemetry.running(&format!("{module}.main"));
+    /// The command to run the given tests with the built-in test runner, once
+    /// the project has been built, measuring their coverage if `coverage` is
+    /// true.
+    fn test_runner_command(
+        &self,
+        paths: &ProjectPaths,
+        built: &Built,
+        runtime: Option<Runtime>,
+        arguments: Vec<String>,
+        tests: &[TestFunction],
+        coverage: bool,
+    ) -> Result<Command, Error> {
+        let package = &self.root_config.name;
+        let build_directory = paths.build_directory_for_package(self.mode, self.target, package);
+        let coverage_directory = coverage.then(|| coverage::raw_directory(paths));
+        match self.target {
+            Target::Erlang => {
+                let tests_file = build_directory.join(ERLANG_TESTS_FILE);
+                let mut terms = erlang_tests(tests);
+                if let Some(directory) = coverage_directory {
+                    // Each run of the runner has fewer tests left to run than
+                    // the one before, which gives its output a unique name.
+                    let output = directory.join(format!("erlang-{}.tsv", tests.len()));
+                    let modules = coverage::module_names(built);
+                    terms.push_str(&erlang_coverage_term(&output, &modules));
+                }
+                crate::fs::write(&tests_file, &terms)?;
+                let tests_file = tests_file
+                    .as_str()
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"");
+                self.erlang_command(
+                    paths,
+                    runtime,
+                    format!("{package}@@test:run(\"{tests_file}\")"),
+                    arguments,
+                )
+            }
+            Target::JavaScript => {
+                let entrypoint = build_directory.join(JAVASCRIPT_RUNNER_FILE);
+                crate::fs::write(&entrypoint, &javascript_runner(tests))?;
+                let mut command = self.javascript_command(runtime, entrypoint, arguments)?;
+                if let Some(directory) = coverage_directory {
+                    match self.javascript_runtime(runtime) {
+                        Runtime::NodeJs => command
+                            .env
+                            .push(("NODE_V8_COVERAGE".into(), directory.into())),
+                        // The option goes right after the `run` subcommand.
+                        Runtime::Deno => command.args.insert(1, format!("--coverage={directory}")),
+                        Runtime::Bun => {
+                            return Err(Error::CoverageUnsupportedRuntime {
+                                runtime: Runtime::Bun,
+                            });
+                        }
+                    }
+                }
+                Ok(command)
+            }
+        }
+    }
 
-    // Get the command to run the project.
-    match target {
-        Target::Erlang => match runtime {
+    fn erlang_command(
+        &self,
+        paths: &ProjectPaths,
+        runtime: Option<Runtime>,
+        eval: String,
+        arguments: Vec<String>,
+    ) -> Result<Command, Error> {
+        match runtime {
             Some(r) => Err(Error::InvalidRuntime {
                 target: Target::Erlang,
                 invalid_runtime: r,
             }),
-            _ => run_erlang_command(paths, &root_config.name, &module, arguments),
-        },
-        Target::JavaScript => match runtime.unwrap_or(mod_config.javascript.runtime) {
-            Runtime::Deno => run_javascript_deno_command(
-                paths,
-                &root_config,
-                &main_function.package,
-                &module,
-                arguments,
-            ),
+            _ => run_erlang_command(paths, self.mode, eval, arguments),
+        }
+    }
+
+    fn javascript_runtime(&self, runtime: Option<Runtime>) -> Runtime {
+        runtime.unwrap_or(self.mod_config.javascript.runtime)
+    }
+
+    fn javascript_command(
+        &self,
+        runtime: Option<Runtime>,
+        entrypoint: Utf8PathBuf,
+        arguments: Vec<String>,
+    ) -> Result<Command, Error> {
+        match self.javascript_runtime(runtime) {
+            Runtime::Deno => run_javascript_deno_command(&self.root_config, entrypoint, arguments),
             Runtime::NodeJs => {
-                run_javascript_node_command(paths, &main_function.package, &module, arguments)
+                run_javascript_node_command(&self.root_config, entrypoint, arguments)
             }
-            Runtime::Bun => {
-                run_javascript_bun_command(paths, &main_function.package, &module, arguments)
-            }
-        },
+            Runtime::Bun => run_javascript_bun_command(entrypoint, arguments),
+        }
     }
 }
 
 fn run_erlang_command(
     paths: &ProjectPaths,
-    package: &str,
-    module: &str,
+    mode: Mode,
+    eval: String,
     arguments: Vec<String>,
 ) -> Result<Command, Error> {
     let mut args = vec![];
 
     // Specify locations of Erlang applications
-    let packages = paths.build_directory_for_target(Mode::Dev, Target::Erlang);
+    let packages = paths.build_directory_for_target(mode, Target::Erlang);
 
     for entry in crate::fs::read_dir(packages)?.filter_map(Result::ok) {
         args.push("-pa".into());
         args.push(entry.path().join("ebin").into());
     }
 
-    // gleam modules are separated by `/`. Erlang modules are separated by `@`.
-    let module = module.replace('/', "@");
-
     args.push("-eval".into());
-    args.push(format!("{package}@@main:run({module})"));
+    args.push(eval);
 
     // Don't run the Erlang shell
     args.push("-noshell".into());
@@ -197,15 +563,12 @@ fn run_erlang_command(
 }
 
 fn run_javascript_bun_command(
-    paths: &ProjectPaths,
-    package: &str,
-    module: &str,
+    entrypoint: Utf8PathBuf,
     arguments: Vec<String>,
 ) -> Result<Command, Error> {
     let mut args = vec!["run".to_string()];
-    let entry = write_javascript_entrypoint(paths, package, module)?;
 
-    args.push(entry.to_string());
+    args.push(entrypoint.to_string());
 
     for arg in arguments.into_iter() {
         args.push(arg);
@@ -221,15 +584,20 @@ fn run_javascript_bun_command(
 }
 
 fn run_javascript_node_command(
-    paths: &ProjectPaths,
-    package: &str,
-    module: &str,
+    config: &PackageConfig,
+    entrypoint: Utf8PathBuf,
     arguments: Vec<String>,
 ) -> Result<Command, Error> {
     let mut args = vec![];
-    let entry = write_javascript_entrypoint(paths, package, module)?;
 
-    args.push(entry.to_string());
+    // Node.js only uses source maps to print the locations of errors in the
+    // Gleam code when asked to.
+    match config.javascript.source_maps {
+        SourceMaps::External | SourceMaps::Inline => args.push("--enable-source-maps".into()),
+        SourceMaps::None => (),
+    }
+
+    args.push(entrypoint.to_string());
 
     for argument in arguments.into_iter() {
         args.push(argument);
@@ -246,11 +614,12 @@ fn run_javascript_node_command(
 
 fn write_javascript_entrypoint(
     paths: &ProjectPaths,
+    mode: Mode,
     package: &str,
     module: &str,
 ) -> Result<Utf8PathBuf, Error> {
     let path = paths
-        .build_directory_for_package(Mode::Dev, Target::JavaScript, package)
+        .build_directory_for_package(mode, Target::JavaScript, package)
         .to_path_buf()
         .join("gleam.main.mjs");
     let module = format!(
@@ -263,10 +632,8 @@ main();
 }
 
 fn run_javascript_deno_command(
-    paths: &ProjectPaths,
     config: &PackageConfig,
-    package: &str,
-    module: &str,
+    entrypoint: Utf8PathBuf,
     arguments: Vec<String>,
 ) -> Result<Command, Error> {
     let mut args = vec![];
@@ -328,7 +695,6 @@ fn run_javascript_deno_command(
         );
     }
 
-    let entrypoint = write_javascript_entrypoint(paths, package, module)?;
     args.push(entrypoint.to_string());
 
     for argument in arguments.into_iter() {
@@ -373,7 +739,7 @@ fn is_gleam_module(module: &str) -> bool {
 
 /// If provided module is not executable, suggest a possible valid module.
 fn get_or_suggest_main_function(
-    built: Built,
+    built: &Built,
     module: &str,
     target: Target,
 ) -> Result<ModuleFunction, Error> {
@@ -0,0 +1,328 @@
+//! Runs the tests of the project with the built-in test runner, reading the
+//! events printed by the runner of the target language. See the
+//! `gleam_core::test_runner` module for how tests are discovered and run.
+
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufReader, Write},
+    process::Child,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use camino::Utf8PathBuf;
+use gleam_core::{
+    Error, Result,
+    build::Built,
+    error::ShellCommandFailureReason,
+    io::Command,
+    paths::ProjectPaths,
+    test_runner::{self, Event, Outcome, TestFilter, TestFunction, TestResult},
+};
+use itertools::Itertools;
+use termcolor::{Color, ColorSpec, WriteColor};
+
+use crate::cli;
+
+/// The options of the built-in test runner.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub filter: TestFilter,
+    /// How long each test can run for before it is stopped.
+    pub timeout: Option<Duration>,
+    /// Where to write a JUnit XML report of the results.
+    pub junit: Option<Utf8PathBuf>,
+    /// Whether to measure the code coverage of the tests.
+    pub coverage: bool,
+}
+
+impl Options {
+    /// Whether any of the options of the built-in test runner have been
+    /// given, in which case it is used even if the test module has a `main`
+    /// function.
+    pub fn given(&self) -> bool {
+        self.filter != TestFilter::default()
+            || self.timeout.is_some()
+            || self.junit.is_some()
+            || self.coverage
+    }
+}
+
+/// Runs the tests that match the filter, using `command` to get the command
+/// that starts the test runner for a list of tests. Returns whether all the
+/// tests passed.
+///
+/// If a test times out, or the runner exits while running it, a new runner is
+/// started for the tests that have not been run yet.
+///
+pub fn run(
+    paths: &ProjectPaths,
+    built: &Built,
+    tests: Vec<TestFunction>,
+    options: &Options,
+    mut command: impl FnMut(&[TestFunction]) -> Result<Command>,
+) -> Result<bool> {
+    let tests = filter_tests(tests, &options.filter)?;
+    let start = Instant::now();
+    cli::print_running(&match tests.len() {
+        1 => "1 test".into(),
+        count => format!("{count} tests"),
+    });
+
+    let mut runner = Runner {
+        paths,
+        built,
+        timeout: options.timeout,
+        results: vec![],
+    };
+    loop {
+        let finished: HashSet<_> = runner
+            .results
+            .iter()
+            .map(|result| (&result.test.module, &result.test.name))
+            .collect();
+        let remaining = tests
+            .iter()
+            .filter(|test| !finished.contains(&(&test.module, &test.name)))
+            .cloned()
+            .collect_vec();
+        if remaining.is_empty() {
+            break;
+        }
+
+        let command = command(&remaining)?;
+        let program = command.program.clone();
+        let finished = runner.results.len();
+        runner.run(&remaining, command)?;
+
+        if runner.results.len() == finished {
+            return Err(Error::ShellCommand {
+                program,
+                reason: ShellCommandFailureReason::ShellCommandError(
+                    "The test runner exited without running any test.".into(),
+                ),
+            });
+        }
+    }
+
+    let results = runner.results;
+    cli::print_colourful_prefix(
+        "Finished",
+        &format!(
+            "{} in {}",
+            test_runner::summary(&results),
+            cli::seconds(start.elapsed())
+        ),
+    );
+
+    if let Some(path) = &options.junit {
+        let package = &built.root_package.config.name;
+        crate::fs::write(path, &test_runner::junit_xml(package, &results))?;
+    }
+
+    Ok(results.iter().all(TestResult::passed))
+}
+
+/// The tests that match the filter. A filter that matches no tests is an
+/// error, as it is most likely a typo in the name of a test or module.
+fn filter_tests(tests: Vec<TestFunction>, filter: &TestFilter) -> Result<Vec<TestFunction>> {
+    let tests = tests
+        .into_iter()
+        .filter(|test| filter.matches(test))
+        .collect_vec();
+    if tests.is_empty() && *filter != TestFilter::default() {
+        return Err(Error::NoTestsMatchFilter);
+    }
+    Ok(tests)
+}
+
+struct Runner<'a> {
+    paths: &'a ProjectPaths,
+    built: &'a Built,
+    timeout: Option<Duration>,
+    results: Vec<TestResult>,
+}
+
+impl Runner<'_> {
+    /// Starts the test runner and reads its events until it has run all the
+    /// given tests, a test times out, or the runner exits.
+    fn run(&mut self, tests: &[TestFunction], command: Command) -> Result<()> {
+        let mut child = crate::fs::spawn_with_piped_stdout(command)?;
+        let lines = read_lines(&mut child);
+        let mut running: Option<(&TestFunction, Instant)> = None;
+
+        loop {
+            let line = match (running, self.timeout) {
+                (Some((test, started)), Some(timeout)) => {
+                    match lines.recv_timeout(timeout.saturating_sub(started.elapsed())) {
+                        Ok(line) => Some(line),
+                        Err(RecvTimeoutError::Disconnected) => None,
+                        Err(RecvTimeoutError::Timeout) => {
+                            stop(&mut child);
+                            self.finish(test, Outcome::TimedOut, timeout);
+                            return Ok(());
+                        }
+                    }
+                }
+                (Some(_), None) | (None, _) => lines.recv().ok(),
+            };
+
+            // The output has been closed, so the runner has exited.
+            let Some(line) = line else {
+                let status = child.wait().ok().and_then(|status| status.code());
+                if let Some((test, started)) = running {
+                    self.finish(test, Outcome::Crashed { status }, started.elapsed());
+                }
+                return Ok(());
+            };
+
+            let Some((output, event)) = Event::parse(&line) else {
+                println!("{line}");
+                continue;
+            };
+            if !output.is_empty() {
+                println!("{output}");
+            }
+
+            match event {
+                Event::Start { module, name } => {
+                    running = tests
+                        .iter()
+                        .find(|test| test.is(&module, &name))
+                        .map(|test| (test, Instant::now()))
+                }
+                Event::Pass => {
+                    if let Some((test, started)) = running.take() {
+                        self.finish(test, Outcome::Passed, started.elapsed());
+                    }
+                }
+                Event::Fail(failure) => {
+                    if let Some((test, started)) = running.take() {
+                        self.finish(test, Outcome::Failed(failure), started.elapsed());
+                    }
+                }
+                Event::Done => {
+                    wait_for_exit(&mut child);
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, test: &TestFunction, outcome: Outcome, duration: Duration) {
+        let result = TestResult::new(
+            test.clone(),
+            outcome,
+            duration,
+            &self.built.module_interfaces,
+            self.paths.root(),
+        );
+        if let Some(text) = result.failure_text() {
+            print_failure(&result.test.full_name(), &text);
+        }
+        self.results.push(result);
+    }
+}
+
+/// Reads the lines printed by the child process on a separate thread, so that
+/// they can be waited for with a timeout.
+fn read_lines(child: &mut Child) -> mpsc::Receiver<String> {
+    let stdout = child.stdout.take().expect("piped stdout");
+    let (sender, receiver) = mpsc::channel();
+    let _ = thread::spawn(move || {
+        for line in BufReader::new(stdout).split(b'\n') {
+            let Ok(line) = line else { break };
+            let line = String::from_utf8_lossy(&line);
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if sender.send(line.to_string()).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// How long a runner that is done has to exit, writing any coverage it has
+/// collected, before it is stopped.
+const EXIT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// Waits for a runner that is done to exit. The runner may be kept alive by
+/// timers or processes started by the tests, in which case it is stopped.
+fn wait_for_exit(child: &mut Child) {
+    let start = Instant::now();
+    while start.elapsed() < EXIT_GRACE_PERIOD {
+        match child.try_wait() {
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Ok(Some(_)) | Err(_) => return,
+        }
+    }
+    stop(child);
+}
+
+fn stop(child: &mut Child) {
+    // The runner may have already exited, in which case there is nothing to
+    // stop.
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+fn print_failure(name: &str, text: &str) {
+    let buffer_writer = cli::stderr_buffer_writer();
+    let mut buffer = buffer_writer.buffer();
+    buffer
+        .set_color(ColorSpec::new().set_intense(true).set_fg(Some(Color::Red)))
+        .expect("print_failure");
+    write!(buffer, "{: >11}", "Failed").expect("print_failure");
+    buffer.set_color(&ColorSpec::new()).expect("print_failure");
+    writeln!(buffer, " {name}").expect("print_failure");
+    for line in text.lines() {
+        writeln!(buffer, "{: >11} {line}", "").expect("print_failure");
+    }
+    buffer_writer.print(&buffer).expect("print_failure");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_function(module: &str, name: &str) -> TestFunction {
+        TestFunction {
+            module: module.into(),
+            name: name.into(),
+            src_path: Utf8PathBuf::from(format!("test/{module}.gleam")),
+            line: 1,
+        }
+    }
+
+    #[test]
+    fn filter_matching_some_tests() {
+        let tests = vec![
+            test_function("wibble_test", "one_test"),
+            test_function("wobble_test", "two_test"),
+        ];
+        let filter = TestFilter {
+            module: Some("wobble_test".into()),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter_tests(tests, &filter),
+            Ok(vec![test_function("wobble_test", "two_test")])
+        );
+    }
+
+    #[test]
+    fn filter_matching_no_tests() {
+        let tests = vec![test_function("wibble_test", "one_test")];
+        let filter = TestFilter {
+            name: Some("wibble_test.two_test".into()),
+            ..Default::default()
+        };
+        assert_eq!(filter_tests(tests, &filter), Err(Error::NoTestsMatchFilter));
+    }
+
+    #[test]
+    fn no_tests_without_a_filter() {
+        assert_eq!(filter_tests(vec![], &TestFilter::default()), Ok(vec![]));
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant, SystemTime},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::{
+    Error, Result,
+    build::{Built, Codegen, NullTelemetry, Options, ProjectCompiler, Telemetry},
+    manifest::{Manifest, ManifestPackageSource},
+    paths::ProjectPaths,
+};
+use itertools::Itertools;
+
+use crate::{
+    build_lock::BuildLock,
+    cli,
+    fs::{self, ConsoleWarningEmitter, ProjectIO},
+};
+
+/// How often the files of the project are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    /// A Gleam or native source file has been added, changed, or removed.
+    Source,
+    /// A source file of a path dependency has been added, changed, or
+    /// removed, so the dependencies have to be compiled again.
+    Dependency,
+    /// The `gleam.toml` of the project or of a path dependency has been
+    /// changed, so the dependencies may have to be downloaded again.
+    Config,
+}
+
+/// Builds the project and then rebuilds it each time any of its files change,
+/// calling `on_built` after every successful build. This function never
+/// returns: errors from building the project or from `on_built` are printed
+/// and the project is watched for the next change.
+///
+/// The project compiler is kept between builds, so only the modules that have
+/// changed, and the modules that depend on them, are compiled again. The
+/// source files of path dependencies are watched too, and the dependencies
+/// are compiled again when they change. When a `gleam.toml` changes the
+/// dependencies are downloaded again, `setup` is called again to get the build
+/// options and any state derived from the configuration, and a new compiler is
+/// created.
+///
+pub fn watch<State>(
+    paths: &ProjectPaths,
+    no_print_progress: bool,
+    mut setup: impl FnMut(&Manifest) -> Result<(Options, State)>,
+    mut on_built: impl FnMut(&State, &Built) -> Result<()>,
+) -> ! {
+    let mut watcher = Watcher::new(paths);
+
+    loop {
+        let mut session = match new_compiler(paths, no_print_progress, &mut setup) {
+            Ok((session, path_dependencies)) => {
+                watcher.watch_path_dependencies(path_dependencies);
+                Some(session)
+            }
+            Err(error) => {
+                print_error(&error);
+                None
+            }
+        };
+
+        loop {
+            if let Some((compiler, options, state)) = session.as_mut() {
+                let result =
+                    rebuild(paths, compiler, options).and_then(|built| on_built(state, &built));
+                if let Err(error) = result {
+                    print_error(&error);
+                }
+            }
+
+            cli::print_watching();
+            match watcher.wait_for_change() {
+                Change::Source => (),
+                Change::Dependency => {
+                    if let Some((compiler, _, _)) = session.as_mut() {
+                        compiler.dependencies_changed();
+                    }
+                }
+                Change::Config => break,
+            }
+        }
+    }
+}
+
+type Session<State> = (ProjectCompiler<ProjectIO>, Options, State);
+
+/// Creates a compiler for the project, returning it along with the root
+/// directories of its path dependencies.
+fn new_compiler<State>(
+    paths: &ProjectPaths,
+    no_print_progress: bool,
+    setup: &mut impl FnMut(&Manifest) -> Result<(Options, State)>,
+) -> Result<(Session<State>, Vec<Utf8PathBuf>)> {
+    let manifest = if no_print_progress {
+        crate::build::download_dependencies(paths, NullTelemetry)?
+    } else {
+        crate::build::download_dependencies(paths, cli::Reporter::new())?
+    };
+    let (options, state) = setup(&manifest)?;
+    let root_config = crate::config::root_config(paths)?;
+    let path_dependencies = manifest
+        .packages
+        .iter()
+        .filter_map(|package| match &package.source {
+            // Relative paths are relative to the root of the workspace.
+            ManifestPackageSource::Local { path } => Some(paths.workspace_root().join(path)),
+            ManifestPackageSource::Hex { .. } | ManifestPackageSource::Git { .. } => None,
+        })
+        .collect();
+    let compiler = ProjectCompiler::new(
+        root_config,
+        options.clone(),
+        manifest.packages,
+        telemetry(&options),
+        Rc::new(ConsoleWarningEmitter),
+        paths.clone(),
+        ProjectIO::new(),
+    );
+    Ok(((compiler, options, state), path_dependencies))
+}
+
+fn rebuild(
+    paths: &ProjectPaths,
+    compiler: &mut ProjectCompiler<ProjectIO>,
+    options: &Options,
+) -> Result<Built> {
+    let telemetry = telemetry(options);
+    let start = Instant::now();
+    let lock = BuildLock::new_target(paths, compiler.mode(), compiler.target())?;
+    let built = {
+        let _guard = lock.lock(telemetry)?;
+        compiler.compile_incrementally()?
+    };
+
+    match options.codegen {
+        Codegen::All | Codegen::DepsOnly => telemetry.compiled_package(start.elapsed()),
+        Codegen::None => telemetry.checked_package(start.elapsed()),
+    };
+
+    Ok(built)
+}
+
+fn telemetry(options: &Options) -> &'static dyn Telemetry {
+    if options.no_print_progress {
+        &NullTelemetry
+    } else {
+        &cli::Reporter
+    }
+}
+
+fn print_error(error: &Error) {
+    let stderr = cli::stderr_buffer_writer();
+    let mut buffer = stderr.buffer();
+    error.pretty(&mut buffer);
+    stderr.print(&buffer).expect("Writing error to stderr");
+}
+
+/// Watches the files of a project and of its path dependencies by
+/// periodically comparing their modification times.
+#[derive(Debug)]
+struct Watcher {
+    paths: ProjectPaths,
+    /// The root directories of the project's path dependencies.
+    path_dependencies: Vec<Utf8PathBuf>,
+    files: HashMap<Utf8PathBuf, SystemTime>,
+}
+
+impl Watcher {
+    fn new(paths: &ProjectPaths) -> Self {
+        Self {
+            files: modification_times(paths, &[]),
+            paths: paths.clone(),
+            path_dependencies: vec![],
+        }
+    }
+
+    fn watch_path_dependencies(&mut self, path_dependencies: Vec<Utf8PathBuf>) {
+        self.files = modification_times(&self.paths, &path_dependencies);
+        self.path_dependencies = path_dependencies;
+    }
+
+    /// Blocks until one of the files of the project has been added, changed,
+    /// or removed.
+    fn wait_for_change(&mut self) -> Change {
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let mut files = modification_times(&self.paths, &self.path_dependencies);
+            if files == self.files {
+                continue;
+            }
+
+            // Editors and tools such as `git checkout` may write several
+            // files in quick succession, so we wait for the files to stop
+            // changing before reporting the change.
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let latest = modification_times(&self.paths, &self.path_dependencies);
+                if latest == files {
+                    break;
+                }
+                files = latest;
+            }
+
+            let previous = std::mem::replace(&mut self.files, files);
+            return change(
+                &previous,
+                &self.files,
+                &self.paths.root_config(),
+                &self.path_dependencies,
+            );
+        }
+    }
+}
+
+/// The modification times of the source files and `gleam.toml` of the project
+/// and of its path dependencies. Only the `src` directory of a dependency is
+/// compiled, so its other directories are not watched.
+fn modification_times(
+    paths: &ProjectPaths,
+    path_dependencies: &[Utf8PathBuf],
+) -> HashMap<Utf8PathBuf, SystemTime> {
+    let directories = [
+        paths.src_directory(),
+        paths.test_directory(),
+        paths.dev_directory(),
+    ]
+    .into_iter()
+    .chain(path_dependencies.iter().map(|root| root.join("src")))
+    .collect_vec();
+    let configs = path_dependencies.iter().map(|root| root.join("gleam.toml"));
+    directories
+        .iter()
+        .flat_map(|directory| fs::private_files(directory))
+        .chain(std::iter::once(paths.root_config()))
+        .chain(configs)
+        .filter_map(|path| {
+            let modified = path.metadata().ok()?.modified().ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+fn change(
+    before: &HashMap<Utf8PathBuf, SystemTime>,
+    after: &HashMap<Utf8PathBuf, SystemTime>,
+    config: &Utf8Path,
+    path_dependencies: &[Utf8PathBuf],
+) -> Change {
+    let changed = |path: &Utf8Path| before.get(path) != after.get(path);
+    let dependency_configs = path_dependencies.iter().map(|root| root.join("gleam.toml"));
+    if changed(config) || dependency_configs.into_iter().any(|path| changed(&path)) {
+        return Change::Config;
+    }
+
+    let dependency_changed = before
+        .keys()
+        .chain(after.keys())
+        .filter(|path| changed(path))
+        .any(|path| path_dependencies.iter().any(|root| path.starts_with(root)));
+    if dependency_changed {
+        Change::Dependency
+    } else {
+        Change::Source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_change() {
+        let config = Utf8Path::new("/gleam.toml");
+        let before = HashMap::from([
+            (config.to_path_buf(), SystemTime::UNIX_EPOCH),
+            ("/src/one.gleam".into(), SystemTime::UNIX_EPOCH),
+        ]);
+        let after = HashMap::from([
+            (config.to_path_buf(), SystemTime::UNIX_EPOCH),
+            (
+                "/src/one.gleam".into(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1),
+            ),
+        ]);
+        assert_eq!(change(&before, &after, config, &[]), Change::Source);
+    }
+
+    #[test]
+    fn config_change() {
+        let config = Utf8Path::new("/gleam.toml");
+        let before = HashMap::from([
+            (config.to_path_buf(), SystemTime::UNIX_EPOCH),
+            ("/src/one.gleam".into(), SystemTime::UNIX_EPOCH),
+        ]);
+        let after = HashMap::from([
+            (
+                config.to_path_buf(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1),
+            ),
+            ("/src/one.gleam".into(), SystemTime::UNIX_EPOCH),
+        ]);
+        assert_eq!(change(&before, &after, config, &[]), Change::Config);
+    }
+
+    #[test]
+    fn path_dependency_source_change() {
+        let config = Utf8Path::new("/app/gleam.toml");
+        let dependencies = [Utf8PathBuf::from("/lib")];
+        let before = HashMap::from([
+            (config.to_path_buf(), SystemTime::UNIX_EPOCH),
+            ("/lib/gleam.toml".into(), SystemTime::UNIX_EPOCH),
+            ("/app/src/one.gleam".into(), SystemTime::UNIX_EPOCH),
+        ]);
+        let mut after = before.clone();
+        let _ = after.insert("/lib/src/lib.gleam".into(), SystemTime::UNIX_EPOCH);
+        assert_eq!(
+            change(&before, &after, config, &dependencies),
+            Change::Dependency
+        );
+    }
+
+    #[test]
+    fn path_dependency_config_change() {
+        let config = Utf8Path::new("/app/gleam.toml");
+        let dependencies = [Utf8PathBuf::from("/lib")];
+        let before = HashMap::from([
+            (config.to_path_buf(), SystemTime::UNIX_EPOCH),
+            ("/lib/gleam.toml".into(), SystemTime::UNIX_EPOCH),
+        ]);
+        let after = HashMap::from([
+            (config.to_path_buf(), SystemTime::UNIX_EPOCH),
+            (
+                "/lib/gleam.toml".into(),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(1),
+            ),
+        ]);
+        assert_eq!(
+            change(&before, &after, config, &dependencies),
+            Change::Config
+        );
+    }
+
+    #[test]
+    fn modification_times_of_project_files() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+        let paths = ProjectPaths::new(root.to_path_buf());
+        fs::write(&paths.root_config(), "name = \"wibble\"").unwrap();
+        fs::write(&paths.src_directory().join("wibble.gleam"), "").unwrap();
+        fs::write(&paths.test_directory().join("wibble_ffi.mjs"), "").unwrap();
+        fs::write(&paths.build_directory().join("wobble.gleam"), "").unwrap();
+
+        let mut files = modification_times(&paths, &[])
+            .into_keys()
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                paths.root_config(),
+                paths.src_directory().join("wibble.gleam"),
+                paths.test_directory().join("wibble_ffi.mjs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn modification_times_of_path_dependency_files() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let root = Utf8Path::from_path(tmp_dir.path()).expect("Non Utf-8 Path");
+        let paths = ProjectPaths::new(root.join("app"));
+        let dependency = root.join("lib");
+        fs::write(&paths.root_config(), "name = \"wibble\"").unwrap();
+        fs::write(&dependency.join("gleam.toml"), "name = \"lib\"").unwrap();
+        fs::write(&dependency.join("src/lib.gleam"), "").unwrap();
+        fs::write(&dependency.join("test/lib_test.gleam"), "").unwrap();
+
+        let mut files = modification_times(&paths, &[dependency.clone()])
+            .into_keys()
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                paths.root_config(),
+                dependency.join("gleam.toml"),
+                dependency.join("src/lib.gleam"),
+            ]
+        );
+    }
+}
@@ -11,8 +11,9 @@ use crate::{
         GroupedDefinitions, Import, ModuleConstant, Publicity, RecordConstructor,
         RecordConstructorArg, SrcSpan, Statement, TypeAlias, TypeAst, TypeAstConstructor,
         TypeAstFn, TypeAstHole, TypeAstTuple, TypeAstVar, TypedDefinition, TypedExpr,
-        TypedFunction, TypedModule, UntypedArg, UntypedCustomType, UntypedFunction, UntypedImport,
-        UntypedModule, UntypedModuleConstant, UntypedStatement, UntypedTypeAlias,
+        TypedFunction, TypedModule, UntypedArg, UntypedCustomType, UntypedDefinition,
+        UntypedFunction, UntypedImport, UntypedModule, UntypedModuleConstant, UntypedStatement,
+        UntypedTypeAlias,
     },
     build::{Origin, Outcome, Target},
     call_graph::{CallGraphNode, into_dependency_order},
@@ -34,7 +35,7 @@ use crate::{
         prelude::*,
     },
     uid::UniqueIdGenerator,
-    warning::TypeWarningEmitter,
+    warning::{TypeWarningEmitter, WarningKind},
 };
 use camino::Utf8PathBuf;
 use ecow::EcoString;
@@ -223,6 +224,11 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         }
         .build();
 
+        let allowed_warnings = module
+            .definitions
+            .iter()
+            .filter_map(|definition| allowed_warnings(&definition.definition))
+            .collect_vec();
         let definitions = GroupedDefinitions::new(module.into_iter_definitions(self.target));
         let definitions_count = definitions.len();
 
@@ -335,7 +341,15 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
         // make the output predictable.
         self.problems.sort();
 
-        let warnings = self.problems.take_warnings();
+        // Warnings silenced with an `@allow` attribute are dropped entirely
+        // so they are not stored in the cache either.
+        let mut warnings = self.problems.take_warnings();
+        warnings.retain(|warning| {
+            let start = warning.location().start;
+            !allowed_warnings
+                .iter()
+                .any(|(span, kinds)| span.contains(start) && kinds.contains(&warning.kind()))
+        });
         for warning in &warnings {
             // TODO: remove this clone
             self.warnings.emit(warning.clone());
@@ -399,6 +413,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             publicity,
             value,
             deprecation,
+            allowed_warnings,
             ..
         } = c;
         self.check_name_case(name_location, &name, Named::Constant);
@@ -479,6 +494,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             value: Box::new(typed_expr),
             type_,
             deprecation,
+            allowed_warnings,
             implementations,
         })
     }
@@ -502,6 +518,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             return_annotation,
             end_position: end_location,
             deprecation,
+            allowed_warnings,
             external_erlang,
             external_javascript,
             return_type: (),
@@ -714,6 +731,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             name: Some((name_location, name)),
             publicity,
             deprecation,
+            allowed_warnings,
             arguments: typed_arguments,
             body_start,
             end_position: end_location,
@@ -889,6 +907,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             parameters,
             constructors,
             deprecation,
+            allowed_warnings,
             ..
         } = t;
 
@@ -1010,6 +1029,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             constructors,
             typed_parameters,
             deprecation,
+            allowed_warnings,
         }))
     }
 
@@ -1323,6 +1343,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             name_location,
             type_ast: resolved_type,
             deprecation,
+            allowed_warnings: _,
             type_: _,
             documentation,
         } = t;
@@ -1439,6 +1460,7 @@ impl<'a, A> ModuleAnalyzer<'a, A> {
             external_erlang,
             external_javascript,
             deprecation,
+            allowed_warnings: _,
             end_position: _,
             body: _,
             body_start: _,
@@ -1639,6 +1661,7 @@ fn analyse_type_alias(t: UntypedTypeAlias, environment: &mut Environment<'_>) ->
         parameters: arguments,
         type_ast: resolved_type,
         deprecation,
+        allowed_warnings,
         ..
     } = t;
 
@@ -1660,6 +1683,7 @@ fn analyse_type_alias(t: UntypedTypeAlias, environment: &mut Environment<'_>) ->
         type_ast: resolved_type,
         type_,
         deprecation,
+        allowed_warnings,
     })
 }
 
@@ -1742,6 +1766,7 @@ fn generalise_module_constant(
         value,
         type_,
         deprecation,
+        allowed_warnings,
         implementations,
     } = constant;
     let type_ = type_.clone();
@@ -1782,6 +1807,7 @@ fn generalise_module_constant(
         value,
         type_,
         deprecation,
+        allowed_warnings,
         implementations,
     })
 }
@@ -1797,6 +1823,7 @@ fn generalise_function(
         name,
         publicity,
         deprecation,
+        allowed_warnings,
         arguments,
         body,
         return_annotation,
@@ -1860,6 +1887,7 @@ fn generalise_function(
         name: Some((name_location, name)),
         publicity,
         deprecation,
+        allowed_warnings,
         arguments,
         end_position: end_location,
         body_start,
@@ -2030,6 +2058,28 @@ fn get_type_dependencies(type_: &TypeAst) -> Vec<EcoString> {
     deps
 }
 
+/// The span of a definition and the kinds of warning allowed inside it with
+/// an `@allow` attribute, if any.
+fn allowed_warnings(definition: &UntypedDefinition) -> Option<(SrcSpan, Vec<WarningKind>)> {
+    let (span, kinds) = match definition {
+        Definition::Function(function) => (function.full_location(), &function.allowed_warnings),
+        Definition::CustomType(custom_type) => {
+            (custom_type.full_location(), &custom_type.allowed_warnings)
+        }
+        Definition::TypeAlias(type_alias) => (type_alias.location, &type_alias.allowed_warnings),
+        Definition::ModuleConstant(constant) => (
+            constant.location.merge(&constant.value.location()),
+            &constant.allowed_warnings,
+        ),
+        Definition::Import(_) => return None,
+    };
+    if kinds.is_empty() {
+        None
+    } else {
+        Some((span, kinds.clone()))
+    }
+}
+
 fn sorted_type_aliases(aliases: &Vec<UntypedTypeAlias>) -> Result<Vec<&UntypedTypeAlias>, Error> {
     let mut deps: Vec<(EcoString, Vec<EcoString>)> = Vec::with_capacity(aliases.len());
 
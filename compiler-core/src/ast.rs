@@ -23,6 +23,7 @@ use crate::type_::{
     self, Deprecation, HasType, ModuleValueConstructor, PatternConstructor, Type, TypedCallArg,
     ValueConstructor, ValueConstructorVariant, nil,
 };
+use crate::warning::WarningKind;
 use itertools::Itertools;
 use num_traits::Zero;
 use std::collections::HashSet;
@@ -692,6 +693,8 @@ pub struct Function<T, Expr> {
     pub body: Vec1<Statement<T, Expr>>,
     pub publicity: Publicity,
     pub deprecation: Deprecation,
+    /// The kinds of warning silenced by an `@allow` attribute.
+    pub allowed_warnings: Vec<WarningKind>,
     pub return_annotation: Option<TypeAst>,
     pub return_type: T,
     pub documentation: Option<(u32, EcoString)>,
@@ -771,6 +774,7 @@ pub struct ModuleConstant<T, ConstantRecordTag> {
     pub value: Box<Constant<T, ConstantRecordTag>>,
     pub type_: T,
     pub deprecation: Deprecation,
+    pub allowed_warnings: Vec<WarningKind>,
     pub implementations: Implementations,
 }
 
@@ -802,6 +806,7 @@ pub struct CustomType<T> {
     pub constructors: Vec<RecordConstructor<T>>,
     pub documentation: Option<(u32, EcoString)>,
     pub deprecation: Deprecation,
+    pub allowed_warnings: Vec<WarningKind>,
     pub opaque: bool,
     /// The names of the type parameters.
     pub parameters: Vec<SpannedString>,
@@ -840,6 +845,7 @@ pub struct TypeAlias<T> {
     pub publicity: Publicity,
     pub documentation: Option<(u32, EcoString)>,
     pub deprecation: Deprecation,
+    pub allowed_warnings: Vec<WarningKind>,
 }
 
 pub type TypedDefinition = Definition<Arc<Type>, TypedExpr, EcoString, EcoString>;
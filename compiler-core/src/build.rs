@@ -12,7 +12,7 @@ mod telemetry;
 mod tests;
 
 pub use self::package_compiler::PackageCompiler;
-pub use self::package_loader::StaleTracker;
+pub use self::package_loader::{SourceFingerprints, StaleTracker};
 pub use self::project_compiler::{Built, Options, ProjectCompiler};
 pub use self::telemetry::{NullTelemetry, Telemetry};
 
@@ -24,7 +24,7 @@ use crate::ast::{
 use crate::type_::{Type, TypedCallArg};
 use crate::{
     ast::{Definition, SrcSpan, TypedModule},
-    config::{self, PackageConfig},
+    config::{self, PackageConfig, SourceMaps},
     erlang,
     error::{Error, FileIoAction, FileKind},
     io::OutputFile,
@@ -143,6 +143,7 @@ impl Default for Runtime {
 pub enum TargetCodegenConfiguration {
     JavaScript {
         emit_typescript_definitions: bool,
+        source_maps: SourceMaps,
         prelude_location: Utf8PathBuf,
     },
     Erlang {
@@ -185,6 +186,9 @@ pub enum Mode {
     Dev,
     Prod,
     Lsp,
+    /// Development code built to measure the coverage of the tests, kept apart
+    /// from the `Dev` build as it may be compiled with different options.
+    Coverage,
 }
 
 impl Mode {
@@ -192,14 +196,14 @@ impl Mode {
     ///
     pub fn includes_dev_code(&self) -> bool {
         match self {
-            Self::Dev | Self::Lsp => true,
+            Self::Dev | Self::Lsp | Self::Coverage => true,
             Self::Prod => false,
         }
     }
 
     pub fn includes_dev_dependencies(&self) -> bool {
         match self {
-            Mode::Dev | Mode::Lsp => true,
+            Mode::Dev | Mode::Lsp | Mode::Coverage => true,
             Mode::Prod => false,
         }
     }
@@ -209,6 +213,7 @@ impl Mode {
 fn mode_includes_dev_code() {
     assert!(Mode::Dev.includes_dev_code());
     assert!(Mode::Lsp.includes_dev_code());
+    assert!(Mode::Coverage.includes_dev_code());
     assert!(!Mode::Prod.includes_dev_code());
 }
 
@@ -634,7 +639,7 @@ fn doc_comments_before<'a>(
     (comment_start, comments)
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub(crate) struct SourceFingerprint(u64);
 
 impl SourceFingerprint {
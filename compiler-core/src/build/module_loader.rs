@@ -131,6 +131,7 @@ where
             origin: self.origin,
             name: file.module_name,
             line_numbers: meta.line_numbers,
+            fingerprint: meta.fingerprint,
         }
     }
 }
@@ -10,17 +10,18 @@ use crate::{
         Mode, Module, Origin, Outcome, Package, SourceFingerprint, Target,
         elixir_libraries::ElixirLibraries,
         native_file_copier::NativeFileCopier,
-        package_loader::{CodegenRequired, PackageLoader, StaleTracker},
+        package_loader::{CodegenRequired, PackageLoader, SourceFingerprints, StaleTracker},
     },
     codegen::{Erlang, ErlangApp, JavaScript, TypeScriptDeclarations},
-    config::PackageConfig,
+    config::{PackageConfig, SourceMaps},
     dep_tree, error,
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
     metadata::ModuleEncoder,
+    parallel,
     parse::extra::ModuleExtra,
     paths, type_,
     uid::UniqueIdGenerator,
-    warning::{TypeWarningEmitter, WarningEmitter},
+    warning::{TypeWarningEmitter, WarningEmitter, WarningLevel},
 };
 use askama::Template;
 use ecow::EcoString;
@@ -114,6 +115,7 @@ where
         already_defined_modules: &mut im::HashMap<EcoString, Utf8PathBuf>,
         stale_modules: &mut StaleTracker,
         incomplete_modules: &mut HashSet<EcoString>,
+        source_fingerprints: &mut SourceFingerprints,
         telemetry: &dyn Telemetry,
     ) -> Outcome<Compiled, Error> {
         let span = tracing::info_span!("compile", package = %self.config.name.as_str());
@@ -145,6 +147,8 @@ where
             stale_modules,
             already_defined_modules,
             incomplete_modules,
+            existing_modules,
+            source_fingerprints,
         );
 
         let loaded = if self.compile_modules {
@@ -159,7 +163,7 @@ where
         let mut cached_module_names = Vec::new();
 
         // Load the cached modules that have previously been compiled
-        for module in loaded.cached.into_iter() {
+        for (module, fingerprint) in loaded.cached.into_iter() {
             // Emit any cached warnings.
             // Note that `self.cached_warnings` is set to `Ignore` (such as for
             // dependency packages) then this field will not be populated.
@@ -171,6 +175,7 @@ where
 
             // Register the cached module so its type information etc can be
             // used for compiling futher modules.
+            source_fingerprints.insert(module.name.clone(), fingerprint);
             _ = existing_modules.insert(module.name.clone(), module);
         }
 
@@ -195,6 +200,7 @@ where
             warnings,
             self.target_support,
             incomplete_modules,
+            source_fingerprints,
         );
 
         let modules = match outcome {
@@ -333,10 +339,12 @@ where
         match self.target {
             TargetCodegenConfiguration::JavaScript {
                 emit_typescript_definitions,
+                source_maps,
                 prelude_location,
             } => self.perform_javascript_codegen(
                 modules,
                 *emit_typescript_definitions,
+                *source_maps,
                 prelude_location,
             ),
             TargetCodegenConfiguration::Erlang { app_file } => {
@@ -373,7 +381,14 @@ where
         // we overwrite any precompiled Erlang that was included in the Hex
         // package. Otherwise we will build the potentially outdated precompiled
         // version and not the newly compiled version.
-        Erlang::new(&build_dir, &include_dir).render(io.clone(), modules, self.root)?;
+        // The lines of the modules built to measure coverage are mapped back
+        // to the Gleam code through source maps.
+        let source_maps = self.mode == Mode::Coverage;
+        Erlang::new(&build_dir, &include_dir, source_maps).render(
+            io.clone(),
+            modules,
+            self.root,
+        )?;
 
         let native_modules: Vec<EcoString> = if self.compile_beam_bytecode {
             written.extend(modules.iter().map(Module::compiled_erlang_path));
@@ -398,6 +413,7 @@ where
         &mut self,
         modules: &[Module],
         typescript: bool,
+        source_maps: SourceMaps,
         prelude_location: &Utf8Path,
     ) -> Result<(), Error> {
         let mut written = HashSet::new();
@@ -407,11 +423,14 @@ where
             TypeScriptDeclarations::None
         };
 
-        JavaScript::new(&self.out, typescript, prelude_location, &self.root).render(
-            &self.io,
-            modules,
-            self.stdlib_package(),
-        )?;
+        JavaScript::new(
+            &self.out,
+            typescript,
+            source_maps,
+            prelude_location,
+            &self.root,
+        )
+        .render(&self.io, modules, self.stdlib_package())?;
 
         if self.copy_native_files {
             self.copy_project_native_files(&self.out, &mut written)?;
@@ -427,23 +446,36 @@ where
         out: &Utf8Path,
         modules_to_compile: &mut HashSet<Utf8PathBuf>,
     ) -> Result<(), Error> {
-        let name = format!("{name}@@main.erl", name = self.config.name);
-        let path = out.join(&name);
+        let application = &self.config.name;
+        let entrypoints = [
+            (
+                format!("{application}@@main.erl"),
+                ErlangEntrypointModule { application }
+                    .render()
+                    .expect("Erlang entrypoint rendering"),
+            ),
+            (
+                format!("{application}@@test.erl"),
+                ErlangTestRunnerModule { application }
+                    .render()
+                    .expect("Erlang test runner rendering"),
+            ),
+        ];
 
-        // If the entrypoint module has already been created then we don't need
-        // to write and compile it again.
-        if self.io.is_file(&path) {
-            tracing::debug!("erlang_entrypoint_already_exists");
-            return Ok(());
-        }
+        for (name, module) in entrypoints {
+            let path = out.join(&name);
 
-        let template = ErlangEntrypointModule {
-            application: &self.config.name,
-        };
-        let module = template.render().expect("Erlang entrypoint rendering");
-        self.io.write(&path, &module)?;
-        let _ = modules_to_compile.insert(name.into());
-        tracing::debug!("erlang_entrypoint_written");
+            // If the entrypoint module has already been created then we don't need
+            // to write and compile it again.
+            if self.io.is_file(&path) {
+                tracing::debug!(name, "erlang_entrypoint_already_exists");
+                continue;
+            }
+
+            self.io.write(&path, &module)?;
+            let _ = modules_to_compile.insert(name.into());
+            tracing::debug!("erlang_entrypoint_written");
+        }
         Ok(())
     }
 
@@ -453,12 +485,14 @@ where
         module: &type_::ModuleInterface,
     ) -> Result<()> {
         for warning in &module.warnings {
+            let level = self.config.warnings.level(warning.kind());
             let src = self.io.read(&module.src_path)?;
-            warnings.emit(Warning::Type {
+            let warning = Warning::Type {
                 path: module.src_path.clone(),
                 src: src.into(),
                 warning: warning.clone(),
-            });
+            };
+            warnings.emit_with_level(warning, level);
         }
 
         Ok(())
@@ -486,11 +520,12 @@ fn analyse(
     target: Target,
     mode: Mode,
     ids: &UniqueIdGenerator,
-    mut parsed_modules: Vec<UncompiledModule>,
+    parsed_modules: Vec<UncompiledModule>,
     module_types: &mut im::HashMap<EcoString, type_::ModuleInterface>,
     warnings: &WarningEmitter,
     target_support: TargetSupport,
     incomplete_modules: &mut HashSet<EcoString>,
+    source_fingerprints: &mut SourceFingerprints,
 ) -> Outcome<Vec<Module>, Error> {
     let mut modules = Vec::with_capacity(parsed_modules.len() + 1);
     let direct_dependencies = package_config.dependencies_for(mode).expect("Package deps");
@@ -502,98 +537,194 @@ fn analyse(
     // place.
     let _ = module_types.insert(PRELUDE_MODULE_NAME.into(), type_::build_prelude(ids));
 
-    for UncompiledModule {
-        name,
-        code,
-        ast,
-        path,
-        mtime,
-        origin,
-        package,
-        dependencies,
-        extra,
-    } in parsed_modules
-    {
-        tracing::debug!(module = ?name, "Type checking");
-
-        let line_numbers = LineNumbers::new(&code);
-
-        let analysis = crate::analyse::ModuleAnalyzerConstructor {
-            target,
-            ids,
-            origin,
-            importable_modules: module_types,
-            warnings: &TypeWarningEmitter::new(path.clone(), code.clone(), warnings.clone()),
-            direct_dependencies: &direct_dependencies,
-            target_support,
-            package_config,
-        }
-        .infer_module(ast, line_numbers, path.clone());
-
-        match analysis {
-            Outcome::Ok(ast) => {
-                // Module has compiled successfully. Make sure it isn't marked as incomplete.
-                let _ = incomplete_modules.remove(&name.clone());
-
-                let mut module = Module {
-                    dependencies,
-                    origin,
-                    extra,
-                    mtime,
-                    name,
-                    code,
-                    ast,
-                    input_path: path,
-                };
-                module.attach_doc_and_module_comments();
-
-                // Register the types from this module so they can be imported into
-                // other modules.
-                let _ = module_types.insert(module.name.clone(), module.ast.type_info.clone());
-                // Register the successfully type checked module data so that it can be
-                // used for code generation and in the language server.
-                modules.push(module);
-            }
-
-            Outcome::PartialFailure(ast, errors) => {
-                let error = Error::Type {
-                    names: Box::new(ast.names.clone()),
-                    path: path.clone(),
-                    src: code.clone(),
-                    errors,
-                };
-                // Mark as incomplete so that this module isn't reloaded from cache.
-                let _ = incomplete_modules.insert(name.clone());
-                // Register the partially type checked module data so that it can be
-                // used in the language server.
-                modules.push(Module {
-                    dependencies,
-                    origin,
-                    extra,
-                    mtime,
-                    name,
-                    code,
-                    ast,
-                    input_path: path,
-                });
-                // WARNING: This cannot be used for code generation as the code has errors.
-                return Outcome::PartialFailure(modules, error);
+    for layer in independent_layers(parsed_modules) {
+        // The modules of a layer only import modules from earlier layers, so
+        // they are type checked concurrently. Each is given its own id
+        // generator and the results are then handled in order, so the
+        // generated code, warnings, and errors do not depend on how many
+        // threads are used.
+        let importable_modules = &*module_types;
+        let layer_ids = ids.split(layer.len());
+        let layer = layer.into_iter().zip(layer_ids.iter().cloned()).collect();
+        let analysed = parallel::map(layer, |(module, ids)| {
+            let UncompiledModule {
+                name,
+                code,
+                ast,
+                path,
+                mtime,
+                origin,
+                package: _,
+                dependencies,
+                extra,
+            } = module;
+            tracing::debug!(module = ?name, "Type checking");
+
+            let line_numbers = LineNumbers::new(&code);
+
+            // Warnings are collected and emitted once the module has been
+            // analysed, as the package's emitter cannot be shared between
+            // threads.
+            let (emitter, emitted) = WarningEmitter::vector();
+            let analysis = crate::analyse::ModuleAnalyzerConstructor {
+                target,
+                ids: &ids,
+                origin,
+                importable_modules,
+                warnings: &TypeWarningEmitter::new(
+                    path.clone(),
+                    code.clone(),
+                    package_config.warnings.clone(),
+                    emitter.clone(),
+                ),
+                direct_dependencies: &direct_dependencies,
+                target_support,
+                package_config,
             }
-
-            Outcome::TotalFailure(errors) => {
-                return Outcome::TotalFailure(Error::Type {
-                    names: Default::default(),
-                    path: path.clone(),
-                    src: code.clone(),
-                    errors,
-                });
+            .infer_module(ast, line_numbers, path.clone());
+
+            AnalysedModule {
+                name,
+                code,
+                path,
+                mtime,
+                origin,
+                dependencies,
+                extra,
+                analysis,
+                warnings: emitted.take(),
+                denied_warnings: emitter.denied_count(),
             }
-        };
+        });
+        ids.join(&layer_ids);
+
+        for AnalysedModule {
+            name,
+            code,
+            path,
+            mtime,
+            origin,
+            dependencies,
+            extra,
+            analysis,
+            warnings: emitted,
+            denied_warnings,
+        } in analysed
+        {
+            // The warnings have already been given their level by the type
+            // warning emitter they were collected with.
+            warnings.emit_collected(emitted, denied_warnings);
+
+            match analysis {
+                Outcome::Ok(ast) => {
+                    // Module has compiled successfully. Make sure it isn't marked as incomplete.
+                    let _ = incomplete_modules.remove(&name.clone());
+
+                    let mut module = Module {
+                        dependencies,
+                        origin,
+                        extra,
+                        mtime,
+                        name,
+                        code,
+                        ast,
+                        input_path: path,
+                    };
+                    module.attach_doc_and_module_comments();
+
+                    // Register the types from this module so they can be imported into
+                    // other modules.
+                    source_fingerprints
+                        .insert(module.name.clone(), SourceFingerprint::new(&module.code));
+                    let _ = module_types.insert(module.name.clone(), module.ast.type_info.clone());
+                    // Register the successfully type checked module data so that it can be
+                    // used for code generation and in the language server.
+                    modules.push(module);
+                }
+
+                Outcome::PartialFailure(ast, errors) => {
+                    let error = Error::Type {
+                        names: Box::new(ast.names.clone()),
+                        path: path.clone(),
+                        src: code.clone(),
+                        errors,
+                    };
+                    // Mark as incomplete so that this module isn't reloaded from cache.
+                    let _ = incomplete_modules.insert(name.clone());
+                    // Register the partially type checked module data so that it can be
+                    // used in the language server.
+                    modules.push(Module {
+                        dependencies,
+                        origin,
+                        extra,
+                        mtime,
+                        name,
+                        code,
+                        ast,
+                        input_path: path,
+                    });
+                    // WARNING: This cannot be used for code generation as the code has errors.
+                    return Outcome::PartialFailure(modules, error);
+                }
+
+                Outcome::TotalFailure(errors) => {
+                    return Outcome::TotalFailure(Error::Type {
+                        names: Default::default(),
+                        path: path.clone(),
+                        src: code.clone(),
+                        errors,
+                    });
+                }
+            };
+        }
     }
 
     Outcome::Ok(modules)
 }
 
+/// A module that has been type checked, along with the warnings emitted while
+/// doing so.
+struct AnalysedModule {
+    name: EcoString,
+    code: EcoString,
+    path: Utf8PathBuf,
+    mtime: SystemTime,
+    origin: Origin,
+    dependencies: Vec<(EcoString, SrcSpan)>,
+    extra: ModuleExtra,
+    analysis: Outcome<TypedModule, Vec1<type_::Error>>,
+    warnings: Vec<Warning>,
+    /// The number of the warnings that were denied by the package's config.
+    denied_warnings: usize,
+}
+
+/// Group the modules, which are ordered so that each comes after the modules
+/// it imports, into layers of modules that do not import each other.
+fn independent_layers(modules: Vec<UncompiledModule>) -> Vec<Vec<UncompiledModule>> {
+    let ordered = modules
+        .iter()
+        .map(|module| {
+            let dependencies = module.dependencies.iter();
+            let dependencies = dependencies.map(|(name, _)| name.clone()).collect();
+            (module.name.clone(), dependencies)
+        })
+        .collect();
+    let mut modules: HashMap<_, _> = modules
+        .into_iter()
+        .map(|module| (module.name.clone(), module))
+        .collect();
+
+    dep_tree::layers(ordered)
+        .into_iter()
+        .map(|layer| {
+            layer
+                .iter()
+                .filter_map(|name| modules.remove(name))
+                .collect()
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 pub(crate) enum Input {
     New(UncompiledModule),
@@ -646,6 +777,7 @@ pub(crate) struct CachedModule {
     pub dependencies: Vec<(EcoString, SrcSpan)>,
     pub source_path: Utf8PathBuf,
     pub line_numbers: LineNumbers,
+    pub fingerprint: SourceFingerprint,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -670,7 +802,7 @@ impl CacheMetadata {
 #[derive(Debug, Default, PartialEq, Eq)]
 pub(crate) struct Loaded {
     pub to_compile: Vec<UncompiledModule>,
-    pub cached: Vec<type_::ModuleInterface>,
+    pub cached: Vec<(type_::ModuleInterface, SourceFingerprint)>,
 }
 
 impl Loaded {
@@ -701,6 +833,12 @@ struct ErlangEntrypointModule<'a> {
     application: &'a str,
 }
 
+#[derive(Template)]
+#[template(path = "gleam@@test.erl", escape = "none")]
+struct ErlangTestRunnerModule<'a> {
+    application: &'a str,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum CachedWarnings {
     Use,
@@ -17,7 +17,7 @@ use vec1::Vec1;
 use crate::{
     Error, Result,
     ast::SrcSpan,
-    build::{Module, Origin, module_loader::ModuleLoader},
+    build::{Module, Origin, SourceFingerprint, module_loader::ModuleLoader},
     config::PackageConfig,
     dep_tree,
     error::{FileIoAction, FileKind, ImportCycleLocationDetails},
@@ -67,6 +67,8 @@ pub struct PackageLoader<'a, IO> {
     stale_modules: &'a mut StaleTracker,
     already_defined_modules: &'a mut im::HashMap<EcoString, Utf8PathBuf>,
     incomplete_modules: &'a HashSet<EcoString>,
+    existing_modules: &'a im::HashMap<EcoString, type_::ModuleInterface>,
+    source_fingerprints: &'a SourceFingerprints,
     cached_warnings: CachedWarnings,
 }
 
@@ -88,6 +90,8 @@ where
         stale_modules: &'a mut StaleTracker,
         already_defined_modules: &'a mut im::HashMap<EcoString, Utf8PathBuf>,
         incomplete_modules: &'a HashSet<EcoString>,
+        existing_modules: &'a im::HashMap<EcoString, type_::ModuleInterface>,
+        source_fingerprints: &'a SourceFingerprints,
     ) -> Self {
         Self {
             io,
@@ -103,6 +107,8 @@ where
             stale_modules,
             already_defined_modules,
             incomplete_modules,
+            existing_modules,
+            source_fingerprints,
         }
     }
 
@@ -110,8 +116,17 @@ where
         // First read the source files. This will use the `ModuleLoader`, which
         // will check the mtimes and hashes of sources and caches to determine
         // which should be loaded.
+        self.forget_defined_modules();
         let mut inputs = self.read_sources_and_caches()?;
 
+        // Record the modules of this package, so that packages compiled after
+        // it can't define modules with the same names.
+        for input in inputs.values() {
+            let _ = self
+                .already_defined_modules
+                .insert(input.name().clone(), input.source_path().to_path_buf());
+        }
+
         // Check for any removed modules, by looking at cache files that don't exist in inputs.
         // Delete the cache files for removed modules and mark them as stale
         // to trigger refreshing dependent modules.
@@ -172,8 +187,9 @@ where
                 // and does not need to be recompiled.
                 Input::Cached(info) => {
                     tracing::debug!(module = %info.name, "module_to_load_from_cache");
+                    let fingerprint = info.fingerprint.clone();
                     let module = self.load_cached_module(info)?;
-                    loaded.cached.push(module);
+                    loaded.cached.push((module, fingerprint));
                 }
             }
         }
@@ -181,10 +197,39 @@ where
         Ok(loaded)
     }
 
+    /// Removes the modules recorded for this package by an earlier
+    /// compilation, as the compiler may be reused (as it is in watch mode and
+    /// in the language server) after the package's modules have been moved or
+    /// deleted.
+    fn forget_defined_modules(&mut self) {
+        let directories = [
+            self.paths.src_directory(),
+            self.paths.test_directory(),
+            self.paths.dev_directory(),
+        ];
+        self.already_defined_modules.retain(|_, path| {
+            !directories
+                .iter()
+                .any(|directory| path.starts_with(directory))
+        });
+    }
+
     fn load_cached_module(&self, info: CachedModule) -> Result<type_::ModuleInterface, Error> {
+        // If the compiler is being reused (as it is in watch mode and in the
+        // language server) it may already hold the interface for this module,
+        // in which case there's no need to read and decode the cache file
+        // again.
+        if let Some(module) = self.existing_interface(&info) {
+            tracing::debug!(module = %info.name, "reusing_module_interface_from_memory");
+            return Ok(module);
+        }
+
         let cache_files = CacheFiles::new(&self.artefact_directory, &info.name);
         let bytes = self.io.read_bytes(&cache_files.cache_path)?;
         let mut module = metadata::ModuleDecoder::new(self.ids.clone()).read(bytes.as_slice())?;
+        // The origin is not stored in the cache file as it is known from the
+        // directory the module is in.
+        module.origin = info.origin;
 
         // Load warnings
         if self.cached_warnings.should_use() {
@@ -203,6 +248,20 @@ where
         Ok(module)
     }
 
+    fn existing_interface(&self, info: &CachedModule) -> Option<type_::ModuleInterface> {
+        if !self
+            .source_fingerprints
+            .matches(&info.name, &info.fingerprint)
+        {
+            return None;
+        }
+        let mut module = self.existing_modules.get(&info.name)?.clone();
+        if !self.cached_warnings.should_use() {
+            module.warnings = vec![];
+        }
+        Some(module)
+    }
+
     fn read_sources_and_caches(&self) -> Result<HashMap<EcoString, Input>> {
         let span = tracing::info_span!("load");
         let _enter = span.enter();
@@ -1641,11 +1700,11 @@ fn ensure_gleam_module_does_not_overwrite_standard_erlang_module(input: &Input)
         path: input.path.to_owned(),
     })
 }
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct StaleTracker(HashSet<EcoString>);
 
 impl StaleTracker {
-    fn add(&mut self, name: EcoString) {
+    pub(crate) fn add(&mut self, name: EcoString) {
         _ = self.0.insert(name);
     }
 
@@ -1662,6 +1721,45 @@ impl StaleTracker {
     }
 }
 
+impl Extend<EcoString> for StaleTracker {
+    fn extend<T: IntoIterator<Item = EcoString>>(&mut self, names: T) {
+        self.0.extend(names)
+    }
+}
+
+impl IntoIterator for StaleTracker {
+    type Item = EcoString;
+    type IntoIter = std::collections::hash_set::IntoIter<EcoString>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// The fingerprints of the sources that each of the module interfaces held by
+/// the compiler were produced from.
+///
+/// A module interface can only be reused in place of the cache file when the
+/// cache was written from that same source, so this is checked before reusing
+/// an interface that is already in memory.
+///
+#[derive(Debug, Default, Clone)]
+pub struct SourceFingerprints(HashMap<EcoString, SourceFingerprint>);
+
+impl SourceFingerprints {
+    pub(crate) fn insert(&mut self, name: EcoString, fingerprint: SourceFingerprint) {
+        _ = self.0.insert(name, fingerprint);
+    }
+
+    fn matches(&self, name: &EcoString, fingerprint: &SourceFingerprint) -> bool {
+        self.0.get(name) == Some(fingerprint)
+    }
+
+    pub(crate) fn extend(&mut self, other: Self) {
+        self.0.extend(other.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct Inputs<'a> {
     collection: HashMap<EcoString, Input>,
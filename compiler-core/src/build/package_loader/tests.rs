@@ -6,7 +6,7 @@ use crate::{
     Warning,
     build::SourceFingerprint,
     io::{FileSystemWriter, memory::InMemoryFileSystem},
-    line_numbers,
+    line_numbers::{self, LineNumbers},
     parse::extra::ModuleExtra,
     warning::NullWarningEmitterIO,
 };
@@ -50,7 +50,17 @@ fn write_cache(
     let path = Utf8Path::new("/artefact").join(format!("{artefact_name}.cache_meta"));
     fs.write_bytes(&path, &cache_metadata.to_binary()).unwrap();
 
-    let cache = crate::type_::ModuleInterface {
+    let cache = module_interface(name, line_numbers);
+    let path = Utf8Path::new("/artefact").join(format!("{artefact_name}.cache"));
+    fs.write_bytes(
+        &path,
+        &metadata::ModuleEncoder::new(&cache).encode().unwrap(),
+    )
+    .unwrap();
+}
+
+fn module_interface(name: &str, line_numbers: LineNumbers) -> type_::ModuleInterface {
+    type_::ModuleInterface {
         name: name.into(),
         origin: Origin::Src,
         package: "my_package".into(),
@@ -58,7 +68,7 @@ fn write_cache(
         types_value_constructors: Default::default(),
         values: Default::default(),
         accessors: Default::default(),
-        line_numbers: line_numbers.clone(),
+        line_numbers,
         is_internal: false,
         src_path: Utf8PathBuf::from(format!("/src/{}.gleam", name)),
         warnings: vec![],
@@ -67,26 +77,44 @@ fn write_cache(
         documentation: Default::default(),
         contains_echo: false,
         references: Default::default(),
-    };
-    let path = Utf8Path::new("/artefact").join(format!("{artefact_name}.cache"));
-    fs.write_bytes(
-        &path,
-        &metadata::ModuleEncoder::new(&cache).encode().unwrap(),
-    )
-    .unwrap();
+    }
 }
 
 fn run_loader(fs: InMemoryFileSystem, root: &Utf8Path, artefact: &Utf8Path) -> LoaderTestOutput {
+    let (emitter, warnings) = WarningEmitter::vector();
+    let loaded = run_loader_with_existing_modules(
+        fs,
+        root,
+        artefact,
+        &emitter,
+        &im::HashMap::new(),
+        &SourceFingerprints::default(),
+    );
+
+    LoaderTestOutput {
+        to_compile: loaded.to_compile.into_iter().map(|m| m.name).collect(),
+        cached: loaded.cached.into_iter().map(|(m, _)| m.name).collect(),
+        warnings: warnings.take(),
+    }
+}
+
+fn run_loader_with_existing_modules(
+    fs: InMemoryFileSystem,
+    root: &Utf8Path,
+    artefact: &Utf8Path,
+    emitter: &WarningEmitter,
+    existing_modules: &im::HashMap<EcoString, type_::ModuleInterface>,
+    source_fingerprints: &SourceFingerprints,
+) -> Loaded {
     let mut defined = im::HashMap::new();
     let ids = UniqueIdGenerator::new();
-    let (emitter, warnings) = WarningEmitter::vector();
 
     let loader = PackageLoader {
         io: fs.clone(),
         ids,
         mode: Mode::Dev,
         paths: ProjectPaths::new(root.into()),
-        warnings: &emitter,
+        warnings: emitter,
         codegen: CodegenRequired::Yes,
         artefact_directory: &artefact,
         package_name: &"my_package".into(),
@@ -94,15 +122,11 @@ fn run_loader(fs: InMemoryFileSystem, root: &Utf8Path, artefact: &Utf8Path) -> L
         stale_modules: &mut StaleTracker::default(),
         already_defined_modules: &mut defined,
         incomplete_modules: &mut HashSet::new(),
+        existing_modules,
+        source_fingerprints,
         cached_warnings: CachedWarnings::Ignore,
     };
-    let loaded = loader.run().unwrap();
-
-    LoaderTestOutput {
-        to_compile: loaded.to_compile.into_iter().map(|m| m.name).collect(),
-        cached: loaded.cached.into_iter().map(|m| m.name).collect(),
-        warnings: warnings.take(),
-    }
+    loader.run().unwrap()
 }
 
 #[test]
@@ -297,6 +321,65 @@ fn module_continues_to_be_stale_if_deps_get_updated() {
     );
 }
 
+#[test]
+fn cached_module_interface_is_reused_from_memory() {
+    let fs = InMemoryFileSystem::new();
+    let root = Utf8Path::new("/");
+    let artefact = Utf8Path::new("/artefact");
+
+    write_src(&fs, "/src/one.gleam", 0, TEST_SOURCE_1);
+    write_cache(&fs, "one", 0, vec![], TEST_SOURCE_1);
+
+    // The cache file is removed, so the interface can only come from memory
+    fs.delete_file(Utf8Path::new("/artefact/one.cache"))
+        .unwrap();
+
+    let mut interface = module_interface("one", LineNumbers::new(TEST_SOURCE_1));
+    interface.package = "from_memory".into();
+    let existing = im::hashmap! { "one".into() => interface };
+    let mut fingerprints = SourceFingerprints::default();
+    fingerprints.insert("one".into(), SourceFingerprint::new(TEST_SOURCE_1));
+
+    let (emitter, _) = WarningEmitter::vector();
+    let loaded =
+        run_loader_with_existing_modules(fs, root, artefact, &emitter, &existing, &fingerprints);
+    assert!(loaded.to_compile.is_empty());
+    let packages = loaded
+        .cached
+        .iter()
+        .map(|(module, _)| module.package.clone())
+        .collect_vec();
+    assert_eq!(packages, vec![EcoString::from("from_memory")]);
+}
+
+#[test]
+fn cached_module_interface_is_read_from_disk_if_fingerprint_differs() {
+    let fs = InMemoryFileSystem::new();
+    let root = Utf8Path::new("/");
+    let artefact = Utf8Path::new("/artefact");
+
+    write_src(&fs, "/src/one.gleam", 0, TEST_SOURCE_1);
+    write_cache(&fs, "one", 0, vec![], TEST_SOURCE_1);
+
+    // The interface in memory was produced from a different source
+    let mut interface = module_interface("one", LineNumbers::new(TEST_SOURCE_2));
+    interface.package = "from_memory".into();
+    let existing = im::hashmap! { "one".into() => interface };
+    let mut fingerprints = SourceFingerprints::default();
+    fingerprints.insert("one".into(), SourceFingerprint::new(TEST_SOURCE_2));
+
+    let (emitter, _) = WarningEmitter::vector();
+    let loaded =
+        run_loader_with_existing_modules(fs, root, artefact, &emitter, &existing, &fingerprints);
+    assert!(loaded.to_compile.is_empty());
+    let packages = loaded
+        .cached
+        .iter()
+        .map(|(module, _)| module.package.clone())
+        .collect_vec();
+    assert_eq!(packages, vec![EcoString::from("my_package")]);
+}
+
 #[test]
 fn invalid_module_name() {
     let fs = InMemoryFileSystem::new();
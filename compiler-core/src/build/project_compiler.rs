@@ -4,7 +4,7 @@ use crate::{
     build::{
         Mode, Module, Origin, Package, Target,
         package_compiler::{self, PackageCompiler},
-        package_loader::StaleTracker,
+        package_loader::{SourceFingerprints, StaleTracker},
         project_compiler,
         telemetry::Telemetry,
     },
@@ -14,8 +14,9 @@ use crate::{
     error::{FileIoAction, FileKind, ShellCommandFailureReason},
     io::{BeamCompiler, Command, CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
     manifest::{ManifestPackage, ManifestPackageSource},
-    metadata,
+    metadata, parallel,
     paths::{self, ProjectPaths},
+    test_runner::TestFunction,
     type_::{self, ModuleFunction},
     uid::UniqueIdGenerator,
     version::COMPILER_VERSION,
@@ -36,7 +37,7 @@ use std::{
 };
 
 use super::{
-    Codegen, Compile, ErlangAppCodegenConfiguration, Outcome,
+    Codegen, Compile, ErlangAppCodegenConfiguration, Outcome, TargetCodegenConfiguration,
     elixir_libraries::ElixirLibraries,
     package_compiler::{CachedWarnings, CheckModuleConflicts, Compiled},
 };
@@ -55,7 +56,7 @@ const ELIXIR_EXECUTABLE: &str = "elixir";
 #[cfg(target_os = "windows")]
 const ELIXIR_EXECUTABLE: &str = "elixir.bat";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Options {
     pub mode: Mode,
     pub target: Option<Target>,
@@ -88,6 +89,17 @@ impl Built {
         }
     }
 
+    /// The tests of the root package that can be run on the given target,
+    /// grouped by module.
+    pub fn test_functions(&self, target: Target) -> Vec<TestFunction> {
+        self.module_interfaces
+            .values()
+            .filter(|module| module.package == self.root_package.config.name)
+            .sorted_by_key(|module| &module.name)
+            .flat_map(|module| module.test_functions(target))
+            .collect()
+    }
+
     pub fn minimum_required_version(&self) -> Version {
         self.module_interfaces
             .values()
@@ -109,6 +121,13 @@ pub struct ProjectCompiler<IO> {
     /// The set of modules that have had partial compilation done since the last
     /// successful compilation.
     incomplete_modules: HashSet<EcoString>,
+    /// The fingerprints of the sources the importable module interfaces were
+    /// produced from, used to reuse them in later compilations rather than
+    /// reading their cache files again.
+    source_fingerprints: SourceFingerprints,
+    /// Whether the dependency packages have already been compiled by this
+    /// compiler. See `compile_incrementally`.
+    dependencies_compiled: bool,
     warnings: WarningEmitter,
     telemetry: &'static dyn Telemetry,
     options: Options,
@@ -125,7 +144,7 @@ pub struct ProjectCompiler<IO> {
 
 impl<IO> ProjectCompiler<IO>
 where
-    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone,
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone + Send + Sync,
 {
     pub fn new(
         config: PackageConfig,
@@ -146,6 +165,8 @@ where
             defined_modules: im::HashMap::new(),
             stale_modules: StaleTracker::default(),
             incomplete_modules: HashSet::new(),
+            source_fingerprints: SourceFingerprints::default(),
+            dependencies_compiled: false,
             ids: UniqueIdGenerator::new(),
             warnings: WarningEmitter::new(warning_emitter),
             subprocess_stdio: Stdio::Inherit,
@@ -179,6 +200,20 @@ where
         self.stale_modules.empty();
     }
 
+    /// Marks modules of the root package as stale, so that the next
+    /// compilation of the root package analyses again all the modules that
+    /// depend on them. Used by the language server, which knows which modules
+    /// have been edited.
+    ///
+    /// This must be called after `reset_state_for_new_compile_run`, and the
+    /// dependency packages must not be compiled again before the root package.
+    ///
+    pub fn mark_modules_stale(&mut self, names: impl IntoIterator<Item = EcoString>) {
+        for name in names {
+            self.stale_modules.add(name);
+        }
+    }
+
     /// Compiles all packages in the project and returns the compiled
     /// information from the root package
     pub fn compile(mut self) -> Result<Built> {
@@ -199,19 +234,73 @@ where
         self.warnings.reset_count();
 
         let root_package = self.compile_root_package().into_result()?;
+        self.check_root_package_warnings()?;
+
+        Ok(Built {
+            root_package,
+            module_interfaces: self.importable_modules,
+            compiled_dependency_modules,
+        })
+    }
+
+    /// Compiles all packages in the project, keeping the compiler's state so
+    /// that it can be called again to rebuild the project after its source
+    /// files have changed. Used by watch mode.
+    ///
+    /// Dependency packages are only compiled by the first successful call, or
+    /// after `dependencies_changed` is called, and any root package module
+    /// that has not changed, and does not depend on a changed module, is
+    /// reused from memory rather than being read from its cache files again.
+    ///
+    pub fn compile_incrementally(&mut self) -> Result<Built> {
+        self.reset_state_for_new_compile_run();
+
+        if !self.dependencies_compiled {
+            self.check_gleam_version()?;
+            self.write_prelude()?;
+            let _ = self.compile_dependencies()?;
+            self.dependencies_compiled = true;
+        }
+
+        // We reset the warning count as we don't want to fail the build if a
+        // dependency has warnings, only if the root package does.
+        self.warnings.reset_count();
+
+        let root_package = self.compile_root_package().into_result()?;
+        self.check_root_package_warnings()?;
+
+        Ok(Built {
+            root_package,
+            module_interfaces: self.importable_modules.clone(),
+            compiled_dependency_modules: vec![],
+        })
+    }
+
+    /// Makes the next call to `compile_incrementally` compile the dependency
+    /// packages again, as the source files of a path dependency have changed.
+    /// Only the changed modules, and the modules that depend on them, are
+    /// compiled again.
+    pub fn dependencies_changed(&mut self) {
+        self.dependencies_compiled = false;
+    }
+
+    /// Fails the build if the root package emitted any warning that its
+    /// config sets to `deny`, or any warning at all when compiling with
+    /// `--warnings-as-errors`.
+    fn check_root_package_warnings(&self) -> Result<()> {
+        if self.warnings.denied_count() > 0 {
+            return Err(Error::DeniedWarnings {
+                count: self.warnings.denied_count(),
+            });
+        }
 
-        // TODO: test
         if self.options.warnings_as_errors && self.warnings.count() > 0 {
             return Err(Error::ForbiddenWarnings {
                 count: self.warnings.count(),
             });
         }
 
-        Ok(Built {
-            root_package,
-            module_interfaces: self.importable_modules,
-            compiled_dependency_modules,
-        })
+        Ok(())
     }
 
     pub fn compile_root_package(&mut self) -> Outcome<Package, Error> {
@@ -267,12 +356,23 @@ where
             "The project compiler stale tracker was not emptied from the previous compilation"
         );
 
-        let sequence = order_packages(&self.packages)?;
         let mut modules = vec![];
 
-        for name in sequence {
-            let compiled = self.load_cache_or_compile_package(&name)?;
-            modules.extend(compiled);
+        // Packages in the same layer do not depend on each other, so the Gleam
+        // packages of a layer are compiled concurrently. Packages built with
+        // rebar3 or mix share the Elixir libraries and their build tools'
+        // caches, so they are still built one at a time.
+        for layer in package_layers(&self.packages)? {
+            let mut gleam_packages = vec![];
+            for name in layer {
+                let package = self.packages.get(name.as_str()).expect("Missing package");
+                if usable_build_tools(package)?.as_slice() == [BuildTool::Gleam] {
+                    gleam_packages.push(name);
+                } else {
+                    modules.extend(self.load_cache_or_compile_package(&name)?);
+                }
+            }
+            modules.extend(self.compile_gleam_dep_packages(&gleam_packages)?);
         }
 
         Ok(modules)
@@ -326,15 +426,8 @@ where
             }
         };
 
-        // TODO: test. This one is not covered by the integration tests.
         if result.is_err() {
-            tracing::debug!(package=%name, "removing_failed_build");
-            let path = self.paths.build_directory_for_package(
-                self.mode(),
-                self.target(),
-                package.application_name(),
-            );
-            self.io.delete_directory(&path)?;
+            self.remove_failed_build(package.application_name())?;
         }
 
         result
@@ -515,19 +608,182 @@ where
         }
     }
 
+    /// Compiles Gleam dependency packages that do not depend on each other
+    /// concurrently, each starting from the state left by the packages compiled
+    /// before them. Once they are all compiled their modules, state and
+    /// warnings are added to the project compiler's in the order the packages
+    /// were given in, so that the outcome does not depend on which thread
+    /// finished first.
+    fn compile_gleam_dep_packages(&mut self, names: &[EcoString]) -> Result<Vec<Module>, Error> {
+        let mut packages = Vec::with_capacity(names.len());
+        for name in names {
+            let package = self.packages.get(name.as_str()).expect("Missing package");
+            match self.gleam_dep_package_config(package) {
+                Ok((root, config)) => {
+                    let out = self.paths.build_directory_for_package(
+                        self.mode(),
+                        self.target(),
+                        &config.name,
+                    );
+                    packages.push((root, out, config));
+                }
+                Err(error) => {
+                    self.remove_failed_build(package.application_name())?;
+                    return Err(error);
+                }
+            }
+        }
+
+        let lib = self
+            .paths
+            .build_directory_for_target(self.mode(), self.target());
+        let target = self.codegen_configuration(false);
+        let ids = self.ids.split(packages.len());
+        let state = CompilationState {
+            importable_modules: self.importable_modules.clone(),
+            defined_modules: self.defined_modules.clone(),
+            stale_modules: self.stale_modules.clone(),
+            incomplete_modules: self.incomplete_modules.clone(),
+            source_fingerprints: self.source_fingerprints.clone(),
+        };
+        let jobs = packages
+            .iter()
+            .zip(&ids)
+            .map(|((root, out, config), ids)| {
+                let compiler =
+                    self.package_compiler(config, false, root, out, &lib, &target, ids.clone());
+                (compiler, state.clone())
+            })
+            .collect();
+
+        let telemetry = self.telemetry;
+        let compiled = parallel::map(jobs, |(compiler, mut state)| {
+            // The warning emitter can't be shared between threads, so the
+            // warnings of each package are collected and emitted afterwards.
+            let (warnings, emitted) = WarningEmitter::vector();
+            let outcome = compiler.compile(
+                &warnings,
+                &mut state.importable_modules,
+                &mut state.defined_modules,
+                &mut state.stale_modules,
+                &mut state.incomplete_modules,
+                &mut state.source_fingerprints,
+                telemetry,
+            );
+            let result = outcome.into_result().map(|compiled| compiled.modules);
+            (result, state, emitted.take())
+        });
+        self.ids.join(&ids);
+
+        let mut modules = vec![];
+        let mut first_error = None;
+        for ((result, compiled_state, warnings), name) in compiled.into_iter().zip(names) {
+            for warning in warnings {
+                self.warnings.emit(warning);
+            }
+            if let Err(error) = self.add_compilation_state(&state, compiled_state) {
+                let _ = first_error.get_or_insert(error);
+            }
+            match result {
+                Ok(compiled) => modules.extend(compiled),
+                Err(error) => {
+                    let package = self.packages.get(name.as_str()).expect("Missing package");
+                    self.remove_failed_build(package.application_name())?;
+                    let _ = first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        match first_error {
+            Some(error) => Err(error),
+            None => Ok(modules),
+        }
+    }
+
+    /// Adds what compiling a package added to or removed from the state it
+    /// started from.
+    ///
+    /// Packages compiled concurrently can't see each other's modules, so an
+    /// error is returned here if the package defines a module that one of
+    /// the packages added before it also defines.
+    fn add_compilation_state(
+        &mut self,
+        before: &CompilationState,
+        after: CompilationState,
+    ) -> Result<()> {
+        for (name, path) in &before.defined_modules {
+            if !after.defined_modules.contains_key(name)
+                && self.defined_modules.get(name) == Some(path)
+            {
+                let _ = self.defined_modules.remove(name);
+            }
+        }
+        for (name, path) in after.defined_modules {
+            if before.defined_modules.get(&name) == Some(&path) {
+                continue;
+            }
+            match self.defined_modules.get(&name) {
+                Some(first)
+                    if *first != path && before.defined_modules.get(&name) != Some(first) =>
+                {
+                    return Err(Error::DuplicateModule {
+                        module: name,
+                        first: first.clone(),
+                        second: path,
+                    });
+                }
+                _ => {
+                    let _ = self.defined_modules.insert(name, path);
+                }
+            }
+        }
+        for (name, interface) in after.importable_modules {
+            let _ = self.importable_modules.insert(name, interface);
+        }
+        self.stale_modules.extend(after.stale_modules);
+        self.source_fingerprints.extend(after.source_fingerprints);
+        for name in before
+            .incomplete_modules
+            .difference(&after.incomplete_modules)
+        {
+            let _ = self.incomplete_modules.remove(name);
+        }
+        self.incomplete_modules.extend(after.incomplete_modules);
+        Ok(())
+    }
+
+    fn remove_failed_build(&self, application_name: &EcoString) -> Result<()> {
+        // TODO: test. This one is not covered by the integration tests.
+        tracing::debug!(package=%application_name, "removing_failed_build");
+        let path =
+            self.paths
+                .build_directory_for_package(self.mode(), self.target(), application_name);
+        self.io.delete_directory(&path)
+    }
+
     fn compile_gleam_dep_package(
         &mut self,
         package: &ManifestPackage,
     ) -> Result<Vec<Module>, Error> {
+        let (root, config) = self.gleam_dep_package_config(package)?;
+        self.compile_gleam_package(&config, false, root)
+            .into_result()
+            .map(|compiled| compiled.modules)
+    }
+
+    fn gleam_dep_package_config(
+        &self,
+        package: &ManifestPackage,
+    ) -> Result<(Utf8PathBuf, PackageConfig), Error> {
         // TODO: Test
         let package_root = match &package.source {
             // If the path is relative it is relative to the root of the
             // project, not to the current working directory. The language server
             // could have the working directory and the project root in different
             // places.
-            ManifestPackageSource::Local { path } if path.is_relative() => {
-                self.io.canonicalise(&self.paths.root().join(path))?
-            }
+            ManifestPackageSource::Local { path } if path.is_relative() => self
+                .io
+                .canonicalise(&self.paths.workspace_root().join(path))?,
 
             // If the path is absolute we can use it as-is.
             ManifestPackageSource::Local { path } => path.clone(),
@@ -540,9 +796,7 @@ where
         };
         let config_path = package_root.join("gleam.toml");
         let config = PackageConfig::read(config_path, &self.io)?;
-        self.compile_gleam_package(&config, false, package_root)
-            .into_result()
-            .map(|compiled| compiled.modules)
+        Ok((package_root, config))
     }
 
     fn compile_gleam_package(
@@ -557,8 +811,31 @@ where
         let lib_path = self
             .paths
             .build_directory_for_target(self.mode(), self.target());
-        let mode = if is_root { self.mode() } else { Mode::Prod };
-        let target = match self.target() {
+        let target = self.codegen_configuration(is_root);
+        let compiler = self.package_compiler(
+            config,
+            is_root,
+            &root_path,
+            &out_path,
+            &lib_path,
+            &target,
+            self.ids.clone(),
+        );
+
+        // Compile project to Erlang or JavaScript source code
+        compiler.compile(
+            &self.warnings,
+            &mut self.importable_modules,
+            &mut self.defined_modules,
+            &mut self.stale_modules,
+            &mut self.incomplete_modules,
+            &mut self.source_fingerprints,
+            self.telemetry,
+        )
+    }
+
+    fn codegen_configuration(&self, is_root: bool) -> TargetCodegenConfiguration {
+        match self.target() {
             Target::Erlang => {
                 let package_name_overrides = self
                     .packages
@@ -568,7 +845,7 @@ where
                         Some((p.name.clone(), overriden.clone()))
                     })
                     .collect();
-                super::TargetCodegenConfiguration::Erlang {
+                TargetCodegenConfiguration::Erlang {
                     app_file: Some(ErlangAppCodegenConfiguration {
                         include_dev_deps: is_root && self.mode().includes_dev_dependencies(),
                         package_name_overrides,
@@ -576,21 +853,35 @@ where
                 }
             }
 
-            Target::JavaScript => super::TargetCodegenConfiguration::JavaScript {
+            Target::JavaScript => TargetCodegenConfiguration::JavaScript {
                 emit_typescript_definitions: self.config.javascript.typescript_declarations,
+                source_maps: self.config.javascript.source_maps,
                 // This path is relative to each package output directory
                 prelude_location: Utf8PathBuf::from("../prelude.mjs"),
             },
-        };
+        }
+    }
 
+    #[allow(clippy::too_many_arguments)]
+    fn package_compiler<'a>(
+        &self,
+        config: &'a PackageConfig,
+        is_root: bool,
+        root_path: &'a Utf8Path,
+        out_path: &'a Utf8Path,
+        lib_path: &'a Utf8Path,
+        target: &'a TargetCodegenConfiguration,
+        ids: UniqueIdGenerator,
+    ) -> PackageCompiler<'a, IO> {
+        let mode = if is_root { self.mode() } else { Mode::Prod };
         let mut compiler = PackageCompiler::new(
             config,
             mode,
-            &root_path,
-            &out_path,
-            &lib_path,
-            &target,
-            self.ids.clone(),
+            root_path,
+            out_path,
+            lib_path,
+            target,
+            ids,
             self.io.clone(),
         );
         compiler.write_metadata = true;
@@ -624,40 +915,60 @@ where
             compiler.check_module_conflicts = CheckModuleConflicts::DoNotCheck;
         };
 
-        // Compile project to Erlang or JavaScript source code
-        compiler.compile(
-            &mut self.warnings,
-            &mut self.importable_modules,
-            &mut self.defined_modules,
-            &mut self.stale_modules,
-            &mut self.incomplete_modules,
-            self.telemetry,
-        )
+        compiler
     }
 }
 
-fn order_packages(packages: &HashMap<String, ManifestPackage>) -> Result<Vec<EcoString>, Error> {
-    dep_tree::toposort_deps(
-        packages
-            .values()
-            // Making sure that the package order is deterministic, to prevent different
-            // compilations of the same project compiling in different orders. This could impact
-            // any bugged outcomes, though not any where the compiler is working correctly, so it's
-            // mostly to aid debugging.
-            .sorted_by(|a, b| a.name.cmp(&b.name))
-            .map(|package| {
-                (
-                    package.name.as_str().into(),
-                    package
-                        .requirements
-                        .iter()
-                        .map(|r| EcoString::from(r.as_ref()))
-                        .collect(),
-                )
-            })
-            .collect(),
-    )
-    .map_err(convert_deps_tree_error)
+/// The state that compiling a package adds to, of which each of the dependency
+/// packages compiled concurrently is given its own copy.
+#[derive(Debug, Clone)]
+struct CompilationState {
+    importable_modules: im::HashMap<EcoString, type_::ModuleInterface>,
+    defined_modules: im::HashMap<EcoString, Utf8PathBuf>,
+    stale_modules: StaleTracker,
+    incomplete_modules: HashSet<EcoString>,
+    source_fingerprints: SourceFingerprints,
+}
+
+/// Group the packages into layers of packages that do not depend on each
+/// other, each layer only depending on the layers before it.
+pub(crate) fn package_layers(
+    packages: &HashMap<String, ManifestPackage>,
+) -> Result<Vec<Vec<EcoString>>, Error> {
+    let requirements = package_requirements(packages);
+    let mut requirements_of: HashMap<_, _> = requirements.iter().cloned().collect();
+    let ordered = dep_tree::toposort_deps(requirements)
+        .map_err(convert_deps_tree_error)?
+        .into_iter()
+        .map(|name| {
+            let requirements = requirements_of.remove(&name).unwrap_or_default();
+            (name, requirements)
+        })
+        .collect();
+    Ok(dep_tree::layers(ordered))
+}
+
+fn package_requirements(
+    packages: &HashMap<String, ManifestPackage>,
+) -> Vec<(EcoString, Vec<EcoString>)> {
+    packages
+        .values()
+        // Making sure that the package order is deterministic, to prevent different
+        // compilations of the same project compiling in different orders. This could impact
+        // any bugged outcomes, though not any where the compiler is working correctly, so it's
+        // mostly to aid debugging.
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .map(|package| {
+            (
+                package.name.as_str().into(),
+                package
+                    .requirements
+                    .iter()
+                    .map(|r| EcoString::from(r.as_ref()))
+                    .collect(),
+            )
+        })
+        .collect()
 }
 
 fn convert_deps_tree_error(e: dep_tree::Error) -> Error {
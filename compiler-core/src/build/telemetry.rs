@@ -5,7 +5,7 @@ use std::{
 
 use crate::Warning;
 
-pub trait Telemetry: Debug {
+pub trait Telemetry: Debug + Send + Sync {
     fn waiting_for_build_directory_lock(&self);
     fn running(&self, name: &str);
     fn resolving_package_versions(&self);
@@ -1,6 +1,22 @@
-use crate::{Error, manifest::ManifestPackage};
+use std::rc::Rc;
 
-use super::project_compiler::{BuildTool, usable_build_tools};
+use camino::Utf8PathBuf;
+
+use crate::{
+    Error,
+    analyse::TargetSupport,
+    config::PackageConfig,
+    io::{FileSystemWriter, memory::InMemoryFileSystem},
+    manifest::{ManifestPackage, ManifestPackageSource},
+    paths::ProjectPaths,
+    warning::{NullWarningEmitterIO, VectorWarningEmitterIO},
+};
+
+use super::{
+    Codegen, Compile, Mode, Options, ProjectCompiler, Target,
+    project_compiler::{BuildTool, package_layers, usable_build_tools},
+    telemetry::NullTelemetry,
+};
 
 #[test]
 fn usable_build_tool_unknown() {
@@ -55,3 +71,131 @@ fn usable_build_tool_mix_then_rebar3() {
         Ok(vec![BuildTool::Mix, BuildTool::Rebar3])
     )
 }
+
+fn package(name: &str, requirements: &[&str]) -> (String, ManifestPackage) {
+    let package = ManifestPackage {
+        name: name.into(),
+        requirements: requirements.iter().map(|name| (*name).into()).collect(),
+        ..Default::default()
+    };
+    (name.into(), package)
+}
+
+#[test]
+fn package_layers_group_packages_that_do_not_depend_on_each_other() {
+    let packages = [
+        package("app_helpers", &["gleam_stdlib"]),
+        package("gleam_json", &["gleam_stdlib"]),
+        package("gleam_stdlib", &[]),
+        package("thoas", &[]),
+        package("web", &["app_helpers", "gleam_json", "thoas"]),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(
+        package_layers(&packages),
+        Ok(vec![
+            vec!["gleam_stdlib".into(), "thoas".into()],
+            vec!["app_helpers".into(), "gleam_json".into()],
+            vec!["web".into()],
+        ])
+    )
+}
+
+#[test]
+fn package_layers_cycle() {
+    let packages = [package("one", &["two"]), package("two", &["one"])]
+        .into_iter()
+        .collect();
+
+    assert!(matches!(
+        package_layers(&packages),
+        Err(Error::PackageCycle { .. })
+    ))
+}
+
+fn options() -> Options {
+    Options {
+        mode: Mode::Dev,
+        target: Some(Target::JavaScript),
+        compile: Compile::All,
+        codegen: Codegen::None,
+        warnings_as_errors: false,
+        root_target_support: TargetSupport::Enforced,
+        no_print_progress: true,
+    }
+}
+
+fn path_package(name: &str, path: &str) -> ManifestPackage {
+    ManifestPackage {
+        name: name.into(),
+        source: ManifestPackageSource::Local { path: path.into() },
+        ..Default::default()
+    }
+    .with_build_tools(&["gleam"])
+}
+
+#[test]
+fn sibling_packages_defining_the_same_module() {
+    let io = InMemoryFileSystem::new();
+    for (name, path) in [("one", "/one"), ("two", "/two")] {
+        io.write(
+            &Utf8PathBuf::from(path).join("gleam.toml"),
+            &format!("name = \"{name}\"\nversion = \"1.0.0\""),
+        )
+        .unwrap();
+        io.write(
+            &Utf8PathBuf::from(path).join("src/shared/x.gleam"),
+            "pub const x = 1",
+        )
+        .unwrap();
+    }
+    let compiler = ProjectCompiler::new(
+        PackageConfig::default(),
+        options(),
+        vec![path_package("one", "/one"), path_package("two", "/two")],
+        &NullTelemetry,
+        Rc::new(NullWarningEmitterIO),
+        ProjectPaths::new("/app".into()),
+        io,
+    );
+
+    assert_eq!(
+        compiler.compile().map(|_| ()),
+        Err(Error::DuplicateModule {
+            module: "shared/x".into(),
+            first: "/one/src/shared/x.gleam".into(),
+            second: "/two/src/shared/x.gleam".into(),
+        })
+    );
+}
+
+#[test]
+fn denied_warnings_fail_the_build() {
+    let io = InMemoryFileSystem::new();
+    io.write(
+        &Utf8PathBuf::from("/app/src/app.gleam"),
+        "pub fn main() {\n  let x = 1\n  Nil\n}",
+    )
+    .unwrap();
+    let config =
+        toml::from_str::<PackageConfig>("name = \"app\"\n[warnings]\nunused_variable = \"deny\"")
+            .unwrap();
+    let warnings = Rc::new(VectorWarningEmitterIO::default());
+    let compiler = ProjectCompiler::new(
+        config,
+        options(),
+        vec![],
+        &NullTelemetry,
+        warnings.clone(),
+        ProjectPaths::new("/app".into()),
+        io,
+    );
+
+    assert_eq!(
+        compiler.compile().map(|_| ()),
+        Err(Error::DeniedWarnings { count: 1 })
+    );
+    assert_eq!(warnings.take().len(), 1);
+}
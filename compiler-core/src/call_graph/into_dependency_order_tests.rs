@@ -37,6 +37,7 @@ fn parse_and_order(
             return_annotation: None,
             publicity: Publicity::Public,
             deprecation: Deprecation::NotDeprecated,
+            allowed_warnings: vec![],
             end_position: src.len() as u32,
             return_type: (),
             documentation: None,
@@ -73,6 +74,7 @@ fn parse_and_order(
                 },
                 type_: (),
                 deprecation: Deprecation::NotDeprecated,
+                allowed_warnings: vec![],
             }
         })
         .collect_vec();
@@ -3,18 +3,19 @@ use crate::{
     build::{
         ErlangAppCodegenConfiguration, Module, module_erlang_name, package_compiler::StdlibPackage,
     },
-    config::PackageConfig,
+    config::{PackageConfig, SourceMaps},
     erlang,
     io::FileSystemWriter,
-    javascript::{self, ModuleConfig},
+    javascript::{self, ModuleConfig, SourceMap},
     line_numbers::LineNumbers,
+    parallel,
 };
 use ecow::EcoString;
 use erlang::escape_atom_string;
 use itertools::Itertools;
 use std::fmt::Debug;
 
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 
 /// A code generator that creates a .erl Erlang module and record header files
 /// for each Gleam module in the package.
@@ -22,13 +23,21 @@ use camino::Utf8Path;
 pub struct Erlang<'a> {
     build_directory: &'a Utf8Path,
     include_directory: &'a Utf8Path,
+    /// Whether a source map is written next to each module, as needed to map
+    /// the lines counted when measuring coverage back to the Gleam code.
+    source_maps: bool,
 }
 
 impl<'a> Erlang<'a> {
-    pub fn new(build_directory: &'a Utf8Path, include_directory: &'a Utf8Path) -> Self {
+    pub fn new(
+        build_directory: &'a Utf8Path,
+        include_directory: &'a Utf8Path,
+        source_maps: bool,
+    ) -> Self {
         Self {
             build_directory,
             include_directory,
+            source_maps,
         }
     }
 
@@ -38,17 +47,34 @@ impl<'a> Erlang<'a> {
         modules: &[Module],
         root: &Utf8Path,
     ) -> Result<()> {
-        for module in modules {
-            let erl_name = module.erlang_name();
-            self.erlang_module(&writer, module, &erl_name, root)?;
-            self.erlang_record_headers(&writer, module, &erl_name)?;
+        // The modules are generated concurrently and then written in order.
+        let generated = parallel::map(modules.iter().collect(), |module| {
+            self.generate_module(module, root)
+        });
+        for files in generated {
+            for (path, text) in files? {
+                writer.write(&path, &text)?;
+            }
         }
         Ok(())
     }
 
-    fn erlang_module<Writer: FileSystemWriter>(
+    /// Generate the Erlang module and the record header files for a module.
+    fn generate_module(
+        &self,
+        module: &Module,
+        root: &Utf8Path,
+    ) -> Result<Vec<(Utf8PathBuf, String)>> {
+        let erl_name = module.erlang_name();
+        let mut files = vec![];
+        self.erlang_module(&mut files, module, &erl_name, root)?;
+        files.extend(self.erlang_record_headers(module, &erl_name));
+        Ok(files)
+    }
+
+    fn erlang_module(
         &self,
-        writer: &Writer,
+        files: &mut Vec<(Utf8PathBuf, String)>,
         module: &Module,
         erl_name: &str,
         root: &Utf8Path,
@@ -56,23 +82,41 @@ impl<'a> Erlang<'a> {
         let name = format!("{erl_name}.erl");
         let path = self.build_directory.join(&name);
         let line_numbers = LineNumbers::new(&module.code);
-        let output = erlang::module(&module.ast, &line_numbers, root);
+        let output = if self.source_maps {
+            // The source is referred to relative to the generated module so
+            // the build directory can be moved around.
+            let source = pathdiff::diff_utf8_paths(&module.input_path, self.build_directory)
+                .unwrap_or_else(|| module.input_path.clone());
+            let (output, source_map) = erlang::module_with_source_map(
+                &module.ast,
+                &line_numbers,
+                root,
+                &module.code,
+                &name,
+                &source,
+            )?;
+            files.push((
+                path.with_file_name(format!("{name}.map")),
+                source_map.to_json(),
+            ));
+            output
+        } else {
+            erlang::module(&module.ast, &line_numbers, root)?
+        };
         tracing::debug!(name = ?name, "Generated Erlang module");
-        writer.write(&path, &output?)
+        files.push((path, output));
+        Ok(())
     }
 
-    fn erlang_record_headers<Writer: FileSystemWriter>(
-        &self,
-        writer: &Writer,
-        module: &Module,
-        erl_name: &str,
-    ) -> Result<()> {
-        for (name, text) in erlang::records(&module.ast) {
-            let name = format!("{erl_name}_{name}.hrl");
-            tracing::debug!(name = ?name, "Generated Erlang header");
-            writer.write(&self.include_directory.join(name), &text)?;
-        }
-        Ok(())
+    fn erlang_record_headers(&self, module: &Module, erl_name: &str) -> Vec<(Utf8PathBuf, String)> {
+        erlang::records(&module.ast)
+            .into_iter()
+            .map(|(name, text)| {
+                let name = format!("{erl_name}_{name}.hrl");
+                tracing::debug!(name = ?name, "Generated Erlang header");
+                (self.include_directory.join(name), text)
+            })
+            .collect()
     }
 }
 
@@ -170,12 +214,14 @@ pub struct JavaScript<'a> {
     prelude_location: &'a Utf8Path,
     project_root: &'a Utf8Path,
     typescript: TypeScriptDeclarations,
+    source_maps: SourceMaps,
 }
 
 impl<'a> JavaScript<'a> {
     pub fn new(
         output_directory: &'a Utf8Path,
         typescript: TypeScriptDeclarations,
+        source_maps: SourceMaps,
         prelude_location: &'a Utf8Path,
         project_root: &'a Utf8Path,
     ) -> Self {
@@ -184,6 +230,7 @@ impl<'a> JavaScript<'a> {
             output_directory,
             project_root,
             typescript,
+            source_maps,
         }
     }
 
@@ -193,17 +240,33 @@ impl<'a> JavaScript<'a> {
         modules: &[Module],
         stdlib_package: StdlibPackage,
     ) -> Result<()> {
-        for module in modules {
-            let js_name = module.name.clone();
-            if self.typescript == TypeScriptDeclarations::Emit {
-                self.ts_declaration(writer, module, &js_name)?;
-            }
-            self.js_module(writer, module, &js_name, stdlib_package)?
+        // The modules are generated concurrently and then written in order.
+        let generated = parallel::map(modules.iter().collect(), |module| {
+            self.generate_module(module, stdlib_package)
+        });
+        for (path, text) in generated.into_iter().flatten() {
+            writer.write(&path, &text)?;
         }
         self.write_prelude(writer)?;
         Ok(())
     }
 
+    /// Generate the JavaScript module for a module, along with its TypeScript
+    /// declaration and source map if they are enabled.
+    fn generate_module(
+        &self,
+        module: &Module,
+        stdlib_package: StdlibPackage,
+    ) -> Vec<(Utf8PathBuf, String)> {
+        let js_name = module.name.clone();
+        let mut files = Vec::new();
+        if self.typescript == TypeScriptDeclarations::Emit {
+            files.push(self.ts_declaration(module, &js_name));
+        }
+        self.js_module(&mut files, module, &js_name, stdlib_package);
+        files
+    }
+
     fn write_prelude(&self, writer: &impl FileSystemWriter) -> Result<()> {
         let rexport = format!("export * from \"{}\";\n", self.prelude_location);
         let prelude_path = &self.output_directory.join("gleam.mjs");
@@ -231,30 +294,25 @@ impl<'a> JavaScript<'a> {
         Ok(())
     }
 
-    fn ts_declaration(
-        &self,
-        writer: &impl FileSystemWriter,
-        module: &Module,
-        js_name: &str,
-    ) -> Result<()> {
+    fn ts_declaration(&self, module: &Module, js_name: &str) -> (Utf8PathBuf, String) {
         let name = format!("{js_name}.d.mts");
         let path = self.output_directory.join(name);
         let output = javascript::ts_declaration(&module.ast);
         tracing::debug!(name = ?js_name, "Generated TS declaration");
-        writer.write(&path, &output)
+        (path, output)
     }
 
     fn js_module(
         &self,
-        writer: &impl FileSystemWriter,
+        files: &mut Vec<(Utf8PathBuf, String)>,
         module: &Module,
         js_name: &str,
         stdlib_package: StdlibPackage,
-    ) -> Result<()> {
+    ) {
         let name = format!("{js_name}.mjs");
         let path = self.output_directory.join(name);
         let line_numbers = LineNumbers::new(&module.code);
-        let output = javascript::module(ModuleConfig {
+        let config = ModuleConfig {
             module: &module.ast,
             line_numbers: &line_numbers,
             path: &module.input_path,
@@ -262,8 +320,32 @@ impl<'a> JavaScript<'a> {
             src: &module.code,
             typescript: self.typescript,
             stdlib_package,
-        });
+        };
+
+        let output = match self.source_maps {
+            SourceMaps::None => javascript::module(config),
+            SourceMaps::External | SourceMaps::Inline => {
+                let file = path.file_name().expect("JavaScript module file name");
+                // The source is referred to relative to the generated module so
+                // the build directory can be moved around.
+                let source = path
+                    .parent()
+                    .and_then(|directory| pathdiff::diff_utf8_paths(&module.input_path, directory))
+                    .unwrap_or_else(|| module.input_path.clone());
+                let (mut output, source_map) =
+                    javascript::module_with_source_map(config, file, &source);
+
+                if self.source_maps == SourceMaps::Inline {
+                    output.push_str(&source_map.inline_comment());
+                } else {
+                    let map_file = format!("{file}.map");
+                    files.push((path.with_file_name(&map_file), source_map.to_json()));
+                    output.push_str(&SourceMap::url_comment(&map_file));
+                }
+                output
+            }
+        };
         tracing::debug!(name = ?js_name, "Generated js module");
-        writer.write(&path, &output)
+        files.push((path, output));
     }
 }
@@ -5,6 +5,7 @@ use crate::io::ordered_map;
 use crate::manifest::Manifest;
 use crate::requirement::Requirement;
 use crate::version::COMPILER_VERSION;
+use crate::warning::WarningLevels;
 use crate::{Error, Result};
 use camino::{Utf8Path, Utf8PathBuf};
 use ecow::EcoString;
@@ -19,6 +20,8 @@ use std::marker::PhantomData;
 
 #[cfg(test)]
 use crate::manifest::ManifestPackage;
+#[cfg(test)]
+use crate::warning::{WarningKind, WarningLevel};
 
 use crate::build::{Mode, Runtime, Target};
 
@@ -163,6 +166,8 @@ pub struct PackageConfig {
     pub target: Target,
     #[serde(default)]
     pub internal_modules: Option<Vec<Glob>>,
+    #[serde(default)]
+    pub warnings: WarningLevels,
 }
 
 pub fn serialise_gleam_version<S>(
@@ -194,7 +199,7 @@ where
 impl PackageConfig {
     pub fn dependencies_for(&self, mode: Mode) -> Result<Dependencies> {
         match mode {
-            Mode::Dev | Mode::Lsp => self.all_direct_dependencies(),
+            Mode::Dev | Mode::Lsp | Mode::Coverage => self.all_direct_dependencies(),
             Mode::Prod => Ok(self.dependencies.clone()),
         }
     }
@@ -322,6 +327,105 @@ impl PackageConfig {
     }
 }
 
+/// The `[workspace]` table of the `gleam.toml` file at the root of a
+/// workspace. The root is not a package itself, its members are the packages
+/// in the listed directories, which share the manifest and build directory of
+/// the root.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct WorkspaceConfig {
+    #[serde(default)]
+    pub members: Vec<Utf8PathBuf>,
+}
+
+impl WorkspaceConfig {
+    /// Reads the `[workspace]` table of a `gleam.toml` file, if it has one.
+    pub fn read<FS: FileSystemReader, P: AsRef<Utf8Path>>(
+        path: P,
+        fs: &FS,
+    ) -> Result<Option<WorkspaceConfig>, Error> {
+        #[derive(Deserialize)]
+        struct Config {
+            workspace: Option<WorkspaceConfig>,
+        }
+
+        let toml = fs.read(path.as_ref())?;
+        let config: Config = toml::from_str(&toml).map_err(|e| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: path.as_ref().to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        Ok(config.workspace)
+    }
+
+    /// Finds the root of the workspace the package in the given directory is
+    /// a member of. Only the nearest `gleam.toml` file above the package is
+    /// considered, so a package nested inside another one is never part of a
+    /// workspace further up.
+    pub fn find_root<FS: FileSystemReader>(
+        package_root: &Utf8Path,
+        fs: &FS,
+    ) -> Result<Option<Utf8PathBuf>, Error> {
+        let Some(root) = package_root
+            .ancestors()
+            .skip(1)
+            .find(|directory| fs.is_file(&directory.join("gleam.toml")))
+        else {
+            return Ok(None);
+        };
+        let Some(workspace) = Self::read(root.join("gleam.toml"), fs)? else {
+            return Ok(None);
+        };
+        let is_member = workspace
+            .members
+            .iter()
+            .any(|member| root.join(member) == package_root);
+        Ok(is_member.then(|| root.to_path_buf()))
+    }
+}
+
+#[test]
+fn workspace_config() {
+    let io = crate::io::memory::InMemoryFileSystem::new();
+    let path = Utf8Path::new("/app/gleam.toml");
+    crate::io::FileSystemWriter::write(&io, path, "name = \"wibble\"").expect("write");
+    assert_eq!(WorkspaceConfig::read(path, &io).expect("read"), None);
+
+    crate::io::FileSystemWriter::write(&io, path, "[workspace]\nmembers = [\"wibble\"]")
+        .expect("write");
+    assert_eq!(
+        WorkspaceConfig::read(path, &io).expect("read"),
+        Some(WorkspaceConfig {
+            members: vec!["wibble".into()]
+        })
+    );
+}
+
+#[test]
+fn find_workspace_root() {
+    let io = crate::io::memory::InMemoryFileSystem::new();
+    let write = |path: &str, content: &str| {
+        crate::io::FileSystemWriter::write(&io, Utf8Path::new(path), content).expect("write")
+    };
+    write(
+        "/app/gleam.toml",
+        "[workspace]\nmembers = [\"packages/wibble\", \"./wobble/\"]",
+    );
+    write("/app/packages/wibble/gleam.toml", "name = \"wibble\"");
+    write("/app/wobble/gleam.toml", "name = \"wobble\"");
+    write("/app/wubble/gleam.toml", "name = \"wubble\"");
+    write("/app/wobble/examples/one/gleam.toml", "name = \"one\"");
+    let find = |path: &str| WorkspaceConfig::find_root(Utf8Path::new(path), &io).expect("find");
+
+    assert_eq!(find("/app/packages/wibble"), Some("/app".into()));
+    assert_eq!(find("/app/wobble"), Some("/app".into()));
+    // Not listed as a member
+    assert_eq!(find("/app/wubble"), None);
+    // Nested inside a member rather than in the workspace
+    assert_eq!(find("/app/wobble/examples/one"), None);
+    assert_eq!(find("/app"), None);
+}
+
 #[test]
 fn locked_no_manifest() {
     let mut config = PackageConfig::default();
@@ -662,6 +766,7 @@ impl Default for PackageConfig {
             links: Default::default(),
             internal_modules: Default::default(),
             target: Target::Erlang,
+            warnings: Default::default(),
         }
     }
 }
@@ -682,6 +787,73 @@ pub struct JavaScriptConfig {
     pub runtime: Runtime,
     #[serde(default, rename = "deno")]
     pub deno: DenoConfig,
+    #[serde(default)]
+    pub source_maps: SourceMaps,
+}
+
+/// Whether source maps are generated for the JavaScript modules, configured
+/// with `source_maps = true` to write them to `.mjs.map` files next to the
+/// modules, or with `source_maps = "inline"` to embed them in the modules
+/// themselves.
+#[derive(Debug, PartialEq, Eq, Default, Clone, Copy)]
+pub enum SourceMaps {
+    #[default]
+    None,
+    External,
+    Inline,
+}
+
+impl Serialize for SourceMaps {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            SourceMaps::None => serializer.serialize_bool(false),
+            SourceMaps::External => serializer.serialize_bool(true),
+            SourceMaps::Inline => serializer.serialize_str("inline"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceMaps {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct BoolOrInline;
+
+        impl serde::de::Visitor<'_> for BoolOrInline {
+            type Value = SourceMaps;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a bool or \"inline\"")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                if value {
+                    Ok(SourceMaps::External)
+                } else {
+                    Ok(SourceMaps::None)
+                }
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                match value {
+                    "inline" => Ok(SourceMaps::Inline),
+                    _ => Err(E::invalid_value(serde::de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(BoolOrInline)
+    }
 }
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -1055,6 +1227,74 @@ name = "1"
     )
 }
 
+#[test]
+fn warning_levels() {
+    let input = r#"
+name = "my_project"
+
+[warnings]
+unused_variable = "deny"
+echo = "warn"
+todo = "allow"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.warnings.level(WarningKind::UnusedVariable),
+        WarningLevel::Deny
+    );
+    assert_eq!(config.warnings.level(WarningKind::Echo), WarningLevel::Warn);
+    assert_eq!(
+        config.warnings.level(WarningKind::Todo),
+        WarningLevel::Allow
+    );
+    assert_eq!(
+        config.warnings.level(WarningKind::DeprecatedItem),
+        WarningLevel::Warn
+    );
+}
+
+#[test]
+fn default_warning_levels() {
+    let config = PackageConfig::default();
+    assert_eq!(config.warnings.level(WarningKind::Todo), WarningLevel::Warn);
+    assert_eq!(
+        config.warnings.level(WarningKind::Echo),
+        WarningLevel::Allow
+    );
+}
+
+#[test]
+fn unknown_warning_kind() {
+    let input = r#"
+name = "my_project"
+
+[warnings]
+unused_thing = "deny"
+"#;
+    assert_eq!(
+        toml::from_str::<PackageConfig>(input)
+            .unwrap_err()
+            .to_string(),
+        "`unused_thing` is not a kind of warning for key `warnings` at line 4 column 1"
+    )
+}
+
+#[test]
+fn source_maps() {
+    let config = |value: &str| {
+        let input = format!("name = \"my_project\"\n[javascript]\nsource_maps = {value}\n");
+        toml::from_str::<PackageConfig>(&input).map(|config| config.javascript.source_maps)
+    };
+    assert_eq!(config("false").unwrap(), SourceMaps::None);
+    assert_eq!(config("true").unwrap(), SourceMaps::External);
+    assert_eq!(config("\"inline\"").unwrap(), SourceMaps::Inline);
+    assert!(config("\"external\"").is_err());
+    assert_eq!(
+        PackageConfig::default().javascript.source_maps,
+        SourceMaps::None
+    );
+}
+
 #[test]
 fn package_config_to_json() {
     let input = r#"
@@ -1082,6 +1322,11 @@ pages = [{ title = "My Page", path = "my-page.html", source = "./path/to/my-page
 application_start_module = "my_app/application"
 extra_applications = ["inets", "ssl"]
 
+[warnings]
+unused_variable = "deny"
+echo = "warn"
+todo = "allow"
+
 [javascript]
 typescript_declarations = true
 runtime = "node"
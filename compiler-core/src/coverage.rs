@@ -0,0 +1,724 @@
+//! Code coverage of the root package's `src` modules, measured while its tests
+//! are run by the built-in test runner and reported for the Gleam code rather
+//! than for the code it was compiled to.
+//!
+//! On JavaScript the runtime collects V8's block coverage, which counts how
+//! many times each function and block of the generated code has run. Those
+//! counts are mapped back to the Gleam code through the source maps of the
+//! modules, giving coverage for lines, functions, and each clause of a case
+//! expression, which are reported as branches.
+//!
+//! On Erlang the modules are instrumented with `cover`, which counts how many
+//! times each function and each line of the generated code has run. The
+//! modules built to measure coverage have source maps too, through which those
+//! lines are mapped back to the Gleam code in the same way. As `cover` counts
+//! lines rather than blocks, clauses generated on the same line of Erlang are
+//! counted together.
+//!
+//! The coverage is written both as an lcov tracefile and as a Cobertura XML
+//! report, with paths relative to the project root.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::{
+    Error, Result,
+    ast::{SrcSpan, UntypedClause, UntypedExpr, UntypedFunction},
+    ast_folder::{
+        PatternFolder, TypeAstFolder, UntypedConstantFolder, UntypedExprFolder, UntypedModuleFolder,
+    },
+    build::Target,
+    javascript::SourceMap,
+    line_numbers::LineNumbers,
+    test_runner::escape_xml,
+    warning::WarningEmitter,
+};
+
+/// A position in a Gleam module as a 0-indexed line and column, the column
+/// being counted in UTF-16 code units like the positions of a source map.
+type Position = (u32, u32);
+
+/// The functions and case expressions of a Gleam module, which its coverage
+/// is measured for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceModule {
+    pub name: EcoString,
+    /// The path of the module, relative to the project root.
+    pub path: Utf8PathBuf,
+    functions: Vec<SourceFunction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SourceFunction {
+    name: EcoString,
+    /// The 1-indexed line the function is defined on.
+    line: u32,
+    body: (Position, Position),
+    external_erlang: bool,
+    external_javascript: bool,
+    cases: Vec<SourceCase>,
+}
+
+impl SourceFunction {
+    fn is_external(&self, target: Target) -> bool {
+        match target {
+            Target::Erlang => self.external_erlang,
+            Target::JavaScript => self.external_javascript,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SourceCase {
+    /// The 1-indexed line the case expression starts on.
+    line: u32,
+    /// Where the body of each clause starts and ends.
+    clauses: Vec<(Position, Position)>,
+}
+
+impl SourceModule {
+    pub fn new(name: EcoString, path: Utf8PathBuf, src: &str) -> Result<Self> {
+        let parsed = crate::parse::parse_module(path.clone(), src, &WarningEmitter::null())
+            .map_err(|error| Error::Parse {
+                path: path.clone(),
+                src: src.into(),
+                error: Box::new(error),
+            })?;
+        let line_numbers = LineNumbers::new(src);
+        let mut collector = Collector {
+            line_numbers: &line_numbers,
+            functions: vec![],
+        };
+        let _ = collector.fold_module(parsed.module);
+        Ok(Self {
+            name,
+            path,
+            functions: collector.functions,
+        })
+    }
+}
+
+/// Collects the functions of a module, and the case expressions in their
+/// bodies.
+struct Collector<'a> {
+    line_numbers: &'a LineNumbers,
+    functions: Vec<SourceFunction>,
+}
+
+impl Collector<'_> {
+    fn position(&self, byte_index: u32) -> Position {
+        let position = self.line_numbers.line_and_column_number(byte_index);
+        (position.line - 1, position.column - 1)
+    }
+
+    fn span(&self, span: SrcSpan) -> (Position, Position) {
+        (self.position(span.start), self.position(span.end))
+    }
+}
+
+impl UntypedModuleFolder for Collector<'_> {
+    fn fold_function_definition(
+        &mut self,
+        function: UntypedFunction,
+        _target: Option<Target>,
+    ) -> UntypedFunction {
+        // Functions without a body are implemented in the target language,
+        // and there is no Gleam code to cover.
+        if let (Some(body_start), Some((_, name))) = (function.body_start, &function.name) {
+            self.functions.push(SourceFunction {
+                name: name.clone(),
+                line: self.line_numbers.line_number(function.location.start),
+                body: self.span(SrcSpan::new(body_start, function.end_position)),
+                external_erlang: function.external_erlang.is_some(),
+                external_javascript: function.external_javascript.is_some(),
+                cases: vec![],
+            });
+        }
+        function
+    }
+}
+
+impl UntypedExprFolder for Collector<'_> {
+    fn fold_case(
+        &mut self,
+        location: SrcSpan,
+        subjects: Vec<UntypedExpr>,
+        clauses: Option<Vec<UntypedClause>>,
+    ) -> UntypedExpr {
+        // The bodies of functions are walked right after the functions
+        // themselves are collected, so this case is in the last one.
+        let case = SourceCase {
+            line: self.line_numbers.line_number(location.start),
+            clauses: clauses
+                .iter()
+                .flatten()
+                .map(|clause| self.span(clause.then.location()))
+                .collect(),
+        };
+        if let Some(function) = self.functions.last_mut() {
+            function.cases.push(case);
+        }
+        UntypedExpr::Case {
+            location,
+            subjects,
+            clauses,
+        }
+    }
+}
+
+impl TypeAstFolder for Collector<'_> {}
+impl UntypedConstantFolder for Collector<'_> {}
+impl PatternFolder for Collector<'_> {}
+
+/// The coverage of a Gleam module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCoverage {
+    pub module: EcoString,
+    /// The path of the module, relative to the project root.
+    pub path: Utf8PathBuf,
+    /// How many times each line has run, for the lines with code to run.
+    pub lines: BTreeMap<u32, u64>,
+    pub functions: Vec<FunctionCoverage>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    pub name: EcoString,
+    pub line: u32,
+    pub count: u64,
+}
+
+/// How many times a clause of a case expression has run. `block` is the index
+/// of the case expression in the module, and `branch` the index of the clause
+/// in it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchCoverage {
+    pub line: u32,
+    pub block: u32,
+    pub branch: u32,
+    pub count: u64,
+}
+
+impl FileCoverage {
+    fn new(module: &SourceModule) -> Self {
+        Self {
+            module: module.name.clone(),
+            path: module.path.clone(),
+            lines: BTreeMap::new(),
+            functions: vec![],
+            branches: vec![],
+        }
+    }
+
+    /// Adds the counts of another run of the same module.
+    pub fn merge(&mut self, other: &FileCoverage) {
+        for (line, count) in &other.lines {
+            *self.lines.entry(*line).or_default() += count;
+        }
+        for (function, other) in self.functions.iter_mut().zip(&other.functions) {
+            function.count += other.count;
+        }
+        for (branch, other) in self.branches.iter_mut().zip(&other.branches) {
+            branch.count += other.count;
+        }
+    }
+
+    fn functions_hit(&self) -> usize {
+        self.functions
+            .iter()
+            .filter(|function| function.count > 0)
+            .count()
+    }
+
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+
+    fn branches_hit(&self) -> usize {
+        self.branches
+            .iter()
+            .filter(|branch| branch.count > 0)
+            .count()
+    }
+}
+
+/// The coverage of a script collected by V8, as written by Node.js to the
+/// `NODE_V8_COVERAGE` directory and by Deno to its `--coverage` one.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct V8Script {
+    pub url: String,
+    pub functions: Vec<V8Function>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct V8Function {
+    pub function_name: String,
+    /// The first range is the whole function, and the following ones the
+    /// blocks within it that ran a different number of times.
+    pub ranges: Vec<V8Range>,
+}
+
+/// A range of the generated code, from offsets counted in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct V8Range {
+    pub start_offset: u32,
+    pub end_offset: u32,
+    pub count: u64,
+}
+
+/// Parses a V8 coverage file: Node.js writes all the scripts of a process to
+/// a single file, while Deno writes a file for each script. Returns no scripts
+/// if the file is not valid.
+pub fn parse_v8_coverage(json: &str) -> Vec<V8Script> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum File {
+        Process { result: Vec<V8Script> },
+        Script(V8Script),
+    }
+
+    match serde_json::from_str(json) {
+        Ok(File::Process { result }) => result,
+        Ok(File::Script(script)) => vec![script],
+        Err(_) => vec![],
+    }
+}
+
+impl V8Script {
+    /// How many times the code at an offset has run: the count of the
+    /// innermost range containing it.
+    fn count_at(&self, offset: u32) -> u64 {
+        self.functions
+            .iter()
+            .flat_map(|function| &function.ranges)
+            .filter(|range| range.start_offset <= offset && offset < range.end_offset)
+            .min_by_key(|range| range.end_offset - range.start_offset)
+            .map_or(0, |range| range.count)
+    }
+
+    fn function_count(&self, name: &str) -> u64 {
+        self.functions
+            .iter()
+            // Functions whose name is a JavaScript reserved word are renamed
+            // by adding a `$` at the end.
+            .find(|function| {
+                function.function_name == name
+                    || function.function_name.strip_suffix('$') == Some(name)
+            })
+            .and_then(|function| function.ranges.first())
+            .map_or(0, |range| range.count)
+    }
+}
+
+/// The coverage of a module compiled to JavaScript, from the V8 coverage of
+/// the scripts run. `generated` is the compiled module, and `source_map` its
+/// source map. A module that was never loaded is reported as not covered.
+pub fn javascript_coverage(
+    module: &SourceModule,
+    package: &str,
+    generated: &str,
+    source_map: &SourceMap,
+    scripts: &[V8Script],
+) -> FileCoverage {
+    let line_offsets = utf16_line_offsets(generated);
+    let mappings = source_map
+        .decode_mappings()
+        .into_iter()
+        .filter_map(|mapping| {
+            let offset =
+                line_offsets.get(mapping.generated_line as usize)? + mapping.generated_column;
+            Some((offset, (mapping.source_line, mapping.source_column)))
+        })
+        .collect_vec();
+
+    let suffix = format!("/{package}/{}.mjs", module.name);
+    let scripts = scripts
+        .iter()
+        .filter(|script| script.url.ends_with(&suffix))
+        .collect_vec();
+
+    let mut coverage = javascript_script_coverage(module, &mappings, scripts.first().copied());
+    for script in scripts.iter().skip(1) {
+        coverage.merge(&javascript_script_coverage(module, &mappings, Some(script)));
+    }
+    coverage
+}
+
+fn javascript_script_coverage(
+    module: &SourceModule,
+    mappings: &[(u32, Position)],
+    script: Option<&V8Script>,
+) -> FileCoverage {
+    mapped_coverage(
+        module,
+        Target::JavaScript,
+        mappings,
+        |name| script.map_or(0, |script| script.function_count(name)),
+        |offset| Some(script.map_or(0, |script| script.count_at(offset))),
+    )
+}
+
+/// The coverage of a module from the counts of the code it was compiled to.
+/// `mappings` are the positions in the generated code the source map points
+/// back to the Gleam code from. `count_at` gives how many times the code at
+/// one of those positions has run, or `None` if no code there is counted.
+fn mapped_coverage(
+    module: &SourceModule,
+    target: Target,
+    mappings: &[(u32, Position)],
+    function_count: impl Fn(&str) -> u64,
+    count_at: impl Fn(u32) -> Option<u64>,
+) -> FileCoverage {
+    let mut coverage = FileCoverage::new(module);
+    let mut block = 0;
+
+    for function in &module.functions {
+        if function.is_external(target) {
+            continue;
+        }
+        let count = function_count(&function.name);
+        coverage.functions.push(FunctionCoverage {
+            name: function.name.clone(),
+            line: function.line,
+            count,
+        });
+        set_max(&mut coverage.lines, function.line, count);
+
+        for (generated, position) in mappings {
+            if !contains(function.body, *position) {
+                continue;
+            }
+            if let Some(count) = count_at(*generated) {
+                set_max(&mut coverage.lines, position.0 + 1, count);
+            }
+        }
+
+        for case in &function.cases {
+            for (branch, clause) in case.clauses.iter().enumerate() {
+                // A clause has run as many times as the first code generated
+                // for its body.
+                let first = mappings
+                    .iter()
+                    .filter(|(_, position)| contains(*clause, *position))
+                    .filter_map(|(generated, position)| Some((position, count_at(*generated)?)))
+                    .min_set_by_key(|(position, _)| **position);
+                coverage.branches.push(BranchCoverage {
+                    line: case.line,
+                    block,
+                    branch: branch as u32,
+                    count: first.iter().map(|(_, count)| *count).max().unwrap_or(0),
+                });
+            }
+            block += 1;
+        }
+    }
+    coverage
+}
+
+fn contains((start, end): (Position, Position), position: Position) -> bool {
+    start <= position && position < end
+}
+
+fn set_max(lines: &mut BTreeMap<u32, u64>, line: u32, count: u64) {
+    let entry = lines.entry(line).or_default();
+    *entry = (*entry).max(count);
+}
+
+/// The offset of the start of each line of the code, counted in UTF-16 code
+/// units.
+fn utf16_line_offsets(code: &str) -> Vec<u32> {
+    let mut offsets = vec![0];
+    let mut offset = 0;
+    for char in code.chars() {
+        offset += char.len_utf16() as u32;
+        if char == '\n' {
+            offsets.push(offset);
+        }
+    }
+    offsets
+}
+
+/// The counts written by the Erlang test runner.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErlangCounts {
+    /// How many times each function was called, by Erlang module and function
+    /// name.
+    functions: HashMap<(EcoString, EcoString), u64>,
+    /// How many times each line has run, by Erlang module and 1-indexed line
+    /// of the generated code.
+    lines: HashMap<(EcoString, u32), u64>,
+}
+
+impl ErlangCounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the counts written by the Erlang test runner, one per line as
+    /// fields separated by tabs: `function`, the module, name, arity, and
+    /// number of calls of a function, or `line`, the module, line, and number
+    /// of times a line has run.
+    pub fn parse(&mut self, text: &str) {
+        for line in text.lines() {
+            match line.split('\t').collect_vec().as_slice() {
+                ["function", module, function, _arity, count] => {
+                    if let Ok(count) = count.parse::<u64>() {
+                        *self
+                            .functions
+                            .entry(((*module).into(), (*function).into()))
+                            .or_default() += count;
+                    }
+                }
+                ["line", module, line, count] => {
+                    if let (Ok(line), Ok(count)) = (line.parse::<u32>(), count.parse::<u64>()) {
+                        *self.lines.entry(((*module).into(), line)).or_default() += count;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+/// The Erlang term telling the Erlang test runner to measure the coverage of
+/// the given modules, and to write the counts of their functions and lines to
+/// `output`.
+pub fn erlang_coverage_term(output: &Utf8Path, modules: &[EcoString]) -> String {
+    let output = output.as_str().replace('\\', "\\\\").replace('"', "\\\"");
+    let modules = modules
+        .iter()
+        .map(|module| format!("'{}'", module.replace("/", "@")))
+        .join(", ");
+    format!("{{coverage, \"{output}\", [{modules}]}}.\n")
+}
+
+/// The coverage of a module compiled to Erlang, from the counts of its
+/// functions and lines. `source_map` is the source map of the compiled module.
+pub fn erlang_coverage(
+    module: &SourceModule,
+    source_map: &SourceMap,
+    counts: &ErlangCounts,
+) -> FileCoverage {
+    let erlang_module: EcoString = module.name.replace("/", "@");
+    let mappings = source_map
+        .decode_mappings()
+        .into_iter()
+        .map(|mapping| {
+            (
+                mapping.generated_line + 1,
+                (mapping.source_line, mapping.source_column),
+            )
+        })
+        .collect_vec();
+
+    mapped_coverage(
+        module,
+        Target::Erlang,
+        &mappings,
+        |name| {
+            counts
+                .functions
+                .get(&(erlang_module.clone(), name.into()))
+                .copied()
+                .unwrap_or(0)
+        },
+        // Only the lines with code to run are counted by `cover`.
+        |line| counts.lines.get(&(erlang_module.clone(), line)).copied(),
+    )
+}
+
+/// The share of lines, functions, and branches covered, for the summary
+/// printed once the tests have run. Kinds with nothing to cover are left out.
+pub fn summary(files: &[FileCoverage]) -> String {
+    let parts = [
+        ("lines", line_totals(files)),
+        ("functions", function_totals(files)),
+        ("branches", branch_totals(files)),
+    ]
+    .into_iter()
+    .filter(|(_, (_, total))| *total > 0)
+    .map(|(kind, (hit, total))| format!("{} of {kind}", percentage(hit, total)))
+    .collect_vec();
+
+    match parts.as_slice() {
+        [] => "no code to cover".into(),
+        [part] => part.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
+
+fn line_totals(files: &[FileCoverage]) -> (usize, usize) {
+    files.iter().fold((0, 0), |(hit, total), file| {
+        (hit + file.lines_hit(), total + file.lines.len())
+    })
+}
+
+fn function_totals(files: &[FileCoverage]) -> (usize, usize) {
+    files.iter().fold((0, 0), |(hit, total), file| {
+        (hit + file.functions_hit(), total + file.functions.len())
+    })
+}
+
+fn branch_totals(files: &[FileCoverage]) -> (usize, usize) {
+    files.iter().fold((0, 0), |(hit, total), file| {
+        (hit + file.branches_hit(), total + file.branches.len())
+    })
+}
+
+fn percentage(hit: usize, total: usize) -> String {
+    format!("{:.1}%", rate(hit, total) * 100.0)
+}
+
+/// The share of things covered. When there is nothing to cover everything is
+/// covered, as Cobertura reports do.
+fn rate(hit: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        hit as f64 / total as f64
+    }
+}
+
+/// Renders the coverage as an lcov tracefile.
+pub fn lcov(files: &[FileCoverage]) -> String {
+    let mut lcov = String::new();
+    for file in files {
+        writeln!(lcov, "TN:\nSF:{}", file.path).expect("writing to string");
+        for function in &file.functions {
+            writeln!(lcov, "FN:{},{}", function.line, function.name).expect("writing to string");
+        }
+        for function in &file.functions {
+            writeln!(lcov, "FNDA:{},{}", function.count, function.name).expect("writing to string");
+        }
+        writeln!(
+            lcov,
+            "FNF:{}\nFNH:{}",
+            file.functions.len(),
+            file.functions_hit()
+        )
+        .expect("writing to string");
+
+        for branch in &file.branches {
+            writeln!(
+                lcov,
+                "BRDA:{},{},{},{}",
+                branch.line, branch.block, branch.branch, branch.count
+            )
+            .expect("writing to string");
+        }
+        writeln!(
+            lcov,
+            "BRF:{}\nBRH:{}",
+            file.branches.len(),
+            file.branches_hit()
+        )
+        .expect("writing to string");
+
+        for (line, count) in &file.lines {
+            writeln!(lcov, "DA:{line},{count}").expect("writing to string");
+        }
+        writeln!(
+            lcov,
+            "LF:{}\nLH:{}\nend_of_record",
+            file.lines.len(),
+            file.lines_hit()
+        )
+        .expect("writing to string");
+    }
+    lcov
+}
+
+/// Renders the coverage as a Cobertura XML report, with a class for each
+/// module and a method for each of its functions. `timestamp` is the time the report was made at, in milliseconds
+/// since the Unix epoch.
+pub fn cobertura(package: &str, root: &Utf8Path, files: &[FileCoverage], timestamp: u64) -> String {
+    let (lines_hit, lines) = line_totals(files);
+    let (branches_hit, branches) = branch_totals(files);
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">
+"#,
+    );
+    writeln!(
+        xml,
+        r#"<coverage line-rate="{line_rate:.4}" branch-rate="{branch_rate:.4}" lines-covered="{lines_hit}" lines-valid="{lines}" branches-covered="{branches_hit}" branches-valid="{branches}" complexity="0" timestamp="{timestamp}">
+  <sources>
+    <source>{root}</source>
+  </sources>
+  <packages>
+    <package name="{package}" line-rate="{line_rate:.4}" branch-rate="{branch_rate:.4}" complexity="0">
+      <classes>"#,
+        line_rate = rate(lines_hit, lines),
+        branch_rate = rate(branches_hit, branches),
+        root = escape_xml(root.as_str()),
+        package = escape_xml(package),
+    )
+    .expect("writing to string");
+
+    for file in files {
+        writeln!(
+            xml,
+            r#"        <class name="{}" filename="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">
+          <methods>"#,
+            escape_xml(&file.module),
+            escape_xml(file.path.as_str()),
+            rate(file.lines_hit(), file.lines.len()),
+            rate(file.branches_hit(), file.branches.len()),
+        )
+        .expect("writing to string");
+
+        for function in &file.functions {
+            writeln!(
+                xml,
+                r#"            <method name="{}" signature="" line-rate="{:.4}" branch-rate="1.0000" complexity="0">
+              <lines>
+                <line number="{}" hits="{}" branch="false"/>
+              </lines>
+            </method>"#,
+                escape_xml(&function.name),
+                rate(usize::from(function.count > 0), 1),
+                function.line,
+                function.count,
+            )
+            .expect("writing to string");
+        }
+        xml.push_str("          </methods>\n          <lines>\n");
+
+        let branches = file.branches.iter().into_group_map_by(|branch| branch.line);
+        for (line, count) in &file.lines {
+            write!(xml, r#"            <line number="{line}" hits="{count}""#)
+                .expect("writing to string");
+            match branches.get(line) {
+                Some(branches) => {
+                    let hit = branches.iter().filter(|branch| branch.count > 0).count();
+                    write!(
+                        xml,
+                        r#" branch="true" condition-coverage="{:.0}% ({hit}/{})""#,
+                        rate(hit, branches.len()) * 100.0,
+                        branches.len()
+                    )
+                    .expect("writing to string");
+                }
+                None => xml.push_str(r#" branch="false""#),
+            }
+            xml.push_str("/>\n");
+        }
+        xml.push_str("          </lines>\n        </class>\n");
+    }
+    xml.push_str("      </classes>\n    </package>\n  </packages>\n</coverage>\n");
+    xml
+}
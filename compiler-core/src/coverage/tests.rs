@@ -0,0 +1,387 @@
+use camino::Utf8Path;
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    analyse::TargetSupport,
+    build::{Target, package_compiler::StdlibPackage},
+    codegen::TypeScriptDeclarations,
+    erlang,
+    javascript::{ModuleConfig, SourceMap, module_with_source_map},
+    line_numbers::LineNumbers,
+    type_::tests::compile_module_with_opts,
+};
+
+use super::{
+    ErlangCounts, FileCoverage, SourceModule, V8Function, V8Range, V8Script, cobertura,
+    erlang_coverage, erlang_coverage_term, javascript_coverage, lcov, parse_v8_coverage, summary,
+};
+
+const MODULE: &str = r#"pub fn main() {
+  wibble(1)
+}
+
+pub fn wibble(x) {
+  case x {
+    1 -> 10
+    _ -> {
+      let y = x + 1
+      y
+    }
+  }
+}
+
+pub fn unused() {
+  Nil
+}
+"#;
+
+fn compile_js_with_source_map(src: &str) -> (String, SourceMap) {
+    let module = compile_module_with_opts(
+        "wibble",
+        src,
+        None,
+        vec![],
+        Target::JavaScript,
+        TargetSupport::NotEnforced,
+        None,
+    )
+    .expect("module compiles");
+    module_with_source_map(
+        ModuleConfig {
+            module: &module,
+            line_numbers: &LineNumbers::new(src),
+            src: &src.into(),
+            typescript: TypeScriptDeclarations::None,
+            stdlib_package: StdlibPackage::Present,
+            path: Utf8Path::new("src/wibble.gleam"),
+            project_root: "project/root".into(),
+        },
+        "wibble.mjs",
+        Utf8Path::new("../../src/wibble.gleam"),
+    )
+}
+
+fn compile_erlang_with_source_map(src: &str) -> (String, SourceMap) {
+    let module = compile_module_with_opts(
+        "wibble",
+        src,
+        None,
+        vec![],
+        Target::Erlang,
+        TargetSupport::NotEnforced,
+        None,
+    )
+    .expect("module compiles");
+    erlang::module_with_source_map(
+        &module,
+        &LineNumbers::new(src),
+        Utf8Path::new("project/root"),
+        &src.into(),
+        "wibble.erl",
+        Utf8Path::new("../../../../src/wibble.gleam"),
+    )
+    .expect("module generates")
+}
+
+fn source_module(src: &str) -> SourceModule {
+    SourceModule::new("wibble".into(), "src/wibble.gleam".into(), src).expect("module parses")
+}
+
+/// A range of the generated code, from the start of the first snippet to the
+/// end of the second one found after it.
+fn range(code: &str, from: &str, to: &str, count: u64) -> V8Range {
+    let start = code.find(from).expect("start of range");
+    let end = start
+        + code
+            .get(start..)
+            .and_then(|rest| rest.find(to))
+            .expect("end of range")
+        + to.len();
+    V8Range {
+        start_offset: start as u32,
+        end_offset: end as u32,
+        count,
+    }
+}
+
+fn function(name: &str, ranges: Vec<V8Range>) -> V8Function {
+    V8Function {
+        function_name: name.into(),
+        ranges,
+    }
+}
+
+/// The V8 coverage of running `main` once.
+fn main_run_once(code: &str) -> V8Script {
+    V8Script {
+        url: "file:///project/build/dev/javascript/my_project/wibble.mjs".into(),
+        functions: vec![
+            function(
+                "",
+                vec![V8Range {
+                    start_offset: 0,
+                    end_offset: code.len() as u32,
+                    count: 1,
+                }],
+            ),
+            function(
+                "wibble",
+                vec![
+                    range(code, "function wibble", "\n}", 1),
+                    range(code, "{\n    let y", "return y;\n  }", 0),
+                ],
+            ),
+            function("main", vec![range(code, "function main", "\n}", 1)]),
+            function("unused", vec![range(code, "function unused", "\n}", 0)]),
+        ],
+    }
+}
+
+fn javascript(scripts: impl Fn(&str) -> Vec<V8Script>) -> FileCoverage {
+    let (code, source_map) = compile_js_with_source_map(MODULE);
+    javascript_coverage(
+        &source_module(MODULE),
+        "my_project",
+        &code,
+        &source_map,
+        &scripts(&code),
+    )
+}
+
+#[test]
+fn functions_and_cases_of_a_module() {
+    let module = source_module(
+        r#"pub fn wibble(x) {
+  case x {
+    1 -> case x { _ -> 1 }
+    _ -> 2
+  }
+}
+
+@external(erlang, "wibble", "wobble")
+pub fn wobble() -> Nil
+
+@external(javascript, "./wibble.mjs", "wubble")
+fn wubble() { Nil }
+
+const wabble = 1
+"#,
+    );
+    insta::assert_debug_snapshot!(module);
+}
+
+#[test]
+fn javascript_lines_functions_and_branches() {
+    let coverage = javascript(|code| vec![main_run_once(code)]);
+    insta::assert_snapshot!(lcov(&[coverage]));
+}
+
+#[test]
+fn module_never_loaded_is_not_covered() {
+    let coverage = javascript(|_| vec![]);
+    assert!(coverage.lines.values().all(|count| *count == 0));
+    assert!(
+        coverage
+            .functions
+            .iter()
+            .all(|function| function.count == 0)
+    );
+    assert!(coverage.branches.iter().all(|branch| branch.count == 0));
+    assert_eq!(
+        coverage.lines.keys().copied().collect_vec(),
+        vec![1, 2, 5, 6, 7, 9, 10, 15, 16]
+    );
+}
+
+#[test]
+fn scripts_of_other_modules_are_ignored() {
+    let coverage = javascript(|code| {
+        vec![V8Script {
+            url: "file:///project/build/dev/javascript/other/wibble.mjs".into(),
+            ..main_run_once(code)
+        }]
+    });
+    assert!(coverage.lines.values().all(|count| *count == 0));
+}
+
+#[test]
+fn coverage_of_each_run_is_added_up() {
+    let once = javascript(|code| vec![main_run_once(code)]);
+    let twice = javascript(|code| vec![main_run_once(code), main_run_once(code)]);
+    assert_eq!(
+        twice.lines.get(&2).copied(),
+        once.lines.get(&2).map(|count| count * 2)
+    );
+    assert_eq!(
+        twice
+            .functions
+            .iter()
+            .map(|function| function.count)
+            .collect_vec(),
+        vec![2, 2, 0]
+    );
+    assert_eq!(
+        twice
+            .branches
+            .iter()
+            .map(|branch| branch.count)
+            .collect_vec(),
+        vec![2, 0]
+    );
+}
+
+#[test]
+fn parse_node_and_deno_coverage() {
+    let script = r#"{"scriptId":"1","url":"file:///wibble.mjs","functions":[{"functionName":"wibble","ranges":[{"startOffset":0,"endOffset":10,"count":2}],"isBlockCoverage":true}]}"#;
+    let expected = vec![V8Script {
+        url: "file:///wibble.mjs".into(),
+        functions: vec![function(
+            "wibble",
+            vec![V8Range {
+                start_offset: 0,
+                end_offset: 10,
+                count: 2,
+            }],
+        )],
+    }];
+    assert_eq!(parse_v8_coverage(script), expected);
+    assert_eq!(
+        parse_v8_coverage(&format!(r#"{{"result":[{script}],"timestamp":1}}"#)),
+        expected
+    );
+    assert_eq!(parse_v8_coverage("not json"), vec![]);
+}
+
+/// The counts `cover` gives for running `main` once: the lines counted are
+/// those where each clause starts and where its expressions change line.
+const ERLANG_MAIN_RUN_ONCE: &str = "function\twibble\tmain\t0\t1
+function\twibble\twibble\t1\t1
+function\twibble\tunused\t0\t0
+line\twibble\t8\t1
+line\twibble\t10\t1
+line\twibble\t13\t0
+line\twibble\t14\t0
+line\twibble\t19\t1
+line\twibble\t23\t0
+";
+
+fn erlang(counts: &[&str]) -> FileCoverage {
+    let (_, source_map) = compile_erlang_with_source_map(MODULE);
+    let mut parsed = ErlangCounts::new();
+    for counts in counts {
+        parsed.parse(counts);
+    }
+    erlang_coverage(&source_module(MODULE), &source_map, &parsed)
+}
+
+#[test]
+fn erlang_lines_functions_and_branches() {
+    let coverage = erlang(&[ERLANG_MAIN_RUN_ONCE]);
+    insta::assert_snapshot!(lcov(&[coverage]));
+}
+
+#[test]
+fn erlang_counts_of_each_run_are_added_up() {
+    let once = erlang(&[ERLANG_MAIN_RUN_ONCE]);
+    let twice = erlang(&[ERLANG_MAIN_RUN_ONCE, ERLANG_MAIN_RUN_ONCE]);
+    assert_eq!(
+        twice.lines.get(&7).copied(),
+        once.lines.get(&7).map(|count| count * 2)
+    );
+    assert_eq!(
+        twice
+            .functions
+            .iter()
+            .map(|function| function.count)
+            .collect_vec(),
+        vec![2, 2, 0]
+    );
+    assert_eq!(
+        twice
+            .branches
+            .iter()
+            .map(|branch| branch.count)
+            .collect_vec(),
+        vec![2, 0]
+    );
+}
+
+#[test]
+fn erlang_lines_not_counted_by_cover_are_left_out() {
+    let coverage = erlang(&["function\twibble\tmain\t0\t1\nline\twibble\t19\t1\n"]);
+    assert_eq!(
+        coverage.lines.into_iter().collect_vec(),
+        vec![(1, 1), (2, 1), (5, 0), (15, 0)]
+    );
+}
+
+#[test]
+fn erlang_counts_of_other_modules_and_invalid_lines_are_ignored() {
+    let coverage = erlang(&[
+        "function\tother\tmain\t0\t1\nline\tother\t19\t1\n",
+        "function\twibble\tmain\t0\tmany\nline\twibble\t19\nnot a count\n",
+    ]);
+    assert_eq!(
+        coverage.lines.into_iter().collect_vec(),
+        vec![(1, 0), (5, 0), (15, 0)]
+    );
+    assert!(
+        coverage
+            .functions
+            .iter()
+            .all(|function| function.count == 0)
+    );
+}
+
+#[test]
+fn erlang_coverage_term_lists_the_modules() {
+    assert_eq!(
+        erlang_coverage_term(
+            Utf8Path::new("/project/build/coverage-report/raw/erlang\"1\".tsv"),
+            &[EcoString::from("wibble"), "wibble/wobble".into()]
+        ),
+        "{coverage, \"/project/build/coverage-report/raw/erlang\\\"1\\\".tsv\", ['wibble', 'wibble@wobble']}.\n"
+    );
+}
+
+#[test]
+fn cobertura_report() {
+    let coverage = javascript(|code| vec![main_run_once(code)]);
+    insta::assert_snapshot!(cobertura(
+        "my_project",
+        Utf8Path::new("/project"),
+        &[coverage],
+        1700000000000
+    ));
+}
+
+#[test]
+fn erlang_cobertura_report() {
+    let coverage = erlang(&[ERLANG_MAIN_RUN_ONCE]);
+    insta::assert_snapshot!(cobertura(
+        "my_project",
+        Utf8Path::new("/project"),
+        &[coverage],
+        1700000000000
+    ));
+}
+
+#[test]
+fn summary_of_lines_functions_and_branches() {
+    let coverage = javascript(|code| vec![main_run_once(code)]);
+    assert_eq!(
+        summary(&[coverage]),
+        "55.6% of lines, 66.7% of functions and 50.0% of branches"
+    );
+    assert_eq!(summary(&[]), "no code to cover");
+}
+
+#[test]
+fn summary_of_erlang_lines_functions_and_branches() {
+    let coverage = erlang(&[ERLANG_MAIN_RUN_ONCE]);
+    assert_eq!(
+        summary(&[coverage]),
+        "55.6% of lines, 66.7% of functions and 50.0% of branches"
+    );
+}
@@ -42,6 +42,34 @@ pub fn toposort_deps(inputs: Vec<(EcoString, Vec<EcoString>)>) -> Result<Vec<Eco
     }
 }
 
+/// Take a sequence of values and their deps, ordered so that deps come before
+/// the dependants as returned by `toposort_deps`, and group the values into
+/// layers. Each value only depends on values in earlier layers, so the values
+/// within a layer can be processed at the same time.
+///
+/// Any deps that are not values in the sequence are ignored.
+///
+pub fn layers(ordered: Vec<(EcoString, Vec<EcoString>)>) -> Vec<Vec<EcoString>> {
+    let mut depths: HashMap<EcoString, usize> = HashMap::with_capacity(ordered.len());
+    let mut layers: Vec<Vec<EcoString>> = Vec::new();
+
+    for (value, deps) in ordered {
+        let depth = deps
+            .iter()
+            .filter_map(|dep| depths.get(dep))
+            .map(|depth| depth + 1)
+            .max()
+            .unwrap_or(0);
+        let _ = depths.insert(value.clone(), depth);
+        match layers.get_mut(depth) {
+            Some(layer) => layer.push(value),
+            None => layers.push(vec![value]),
+        }
+    }
+
+    layers
+}
+
 fn import_cycle(
     cycle: Cycle<NodeIndex>,
     graph: &petgraph::Graph<(), ()>,
@@ -90,7 +118,7 @@ pub enum Error {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use super::{assert_eq, *};
 
     #[test]
     fn toposort_deps_test() {
@@ -123,6 +151,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn layers_test() {
+        // a -> b -> d
+        // |         ^
+        // +--> c ---+
+        assert_eq!(
+            layers(vec![
+                ("d".into(), vec![]),
+                ("b".into(), vec!["d".into()]),
+                ("c".into(), vec!["d".into()]),
+                ("a".into(), vec!["b".into(), "c".into()]),
+            ]),
+            vec![vec!["d"], vec!["b", "c"], vec!["a"]]
+        );
+
+        // No deps
+        assert_eq!(
+            layers(vec![("a".into(), vec![]), ("b".into(), vec![])]),
+            vec![vec!["a", "b"]]
+        );
+
+        // Some deps are not values (and thus are ignored)
+        assert_eq!(
+            layers(vec![
+                ("b".into(), vec!["x".into()]),
+                ("a".into(), vec!["b".into(), "z".into()]),
+                ("c".into(), vec!["y".into()]),
+            ]),
+            vec![vec!["b", "c"], vec!["a"]]
+        );
+    }
+
     #[test]
     fn cycle_detection() {
         // a ---+
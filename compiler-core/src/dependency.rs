@@ -1,6 +1,6 @@
 use std::{cell::RefCell, cmp::Reverse, collections::HashMap, rc::Rc};
 
-use crate::{Error, Result, manifest};
+use crate::{Error, Result, derivation_tree, manifest};
 
 use ecow::EcoString;
 use hexpm::{
@@ -115,6 +115,20 @@ pub fn check_for_major_version_updates(
     resolve_major_versions(package_fetcher, versions)
 }
 
+/// The versions in both of the given ranges, as a Hex version requirement.
+/// Returns `None` if there is no version in both.
+pub fn intersect_ranges(range1: &Range, range2: &Range) -> Option<Range> {
+    let range = range1.to_pubgrub().intersection(range2.to_pubgrub());
+    if &range == range1.to_pubgrub() {
+        Some(range1.clone())
+    } else if range.is_empty() {
+        None
+    } else {
+        let range = derivation_tree::pretty_range(&range);
+        Some(Range::new(range).expect("Intersection of ranges is a valid range"))
+    }
+}
+
 // If the string would parse to an exact version then return the version
 fn parse_exact_version(ver: &str) -> Option<Version> {
     let version = ver.trim();
@@ -1093,4 +1107,27 @@ mod tests {
             panic!("expected a resolution error message")
         }
     }
+
+    #[test]
+    fn intersect_ranges_test() {
+        let range = |spec: &str| Range::new(spec.into()).expect("valid range");
+        let intersection = |range1: &str, range2: &str| {
+            intersect_ranges(&range(range1), &range(range2)).map(|range| range.to_string())
+        };
+
+        assert_eq!(
+            intersection("~> 1.0 or ~> 2.0", ">= 1.5.0"),
+            Some(">= 1.5.0 and < 3.0.0".into())
+        );
+        assert_eq!(
+            intersection(">= 1.0.0", ">= 1.0.0 and < 2.0.0"),
+            Some(">= 1.0.0 and < 2.0.0".into())
+        );
+        assert_eq!(
+            intersection(">= 1.0.0 and < 2.0.0", ">= 1.0.0"),
+            Some(">= 1.0.0 and < 2.0.0".into())
+        );
+        assert_eq!(intersection("~> 1.2.3", "== 1.2.5"), Some("1.2.5".into()));
+        assert_eq!(intersection("~> 1.0", ">= 2.0.0"), None);
+    }
 }
@@ -338,7 +338,8 @@ fn collect_conflicting_packages<'dt>(
     }
 }
 
-fn pretty_range(range: &Ranges<Version>) -> String {
+/// Formats a range of versions using the syntax of Hex version requirements.
+pub fn pretty_range(range: &Ranges<Version>) -> String {
     range
         .iter()
         .map(|(lower, upper)| match (lower, upper) {
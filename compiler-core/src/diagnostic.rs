@@ -1,3 +1,10 @@
+mod explanations;
+mod json;
+mod sarif;
+
+#[cfg(test)]
+mod tests;
+
 use std::collections::HashMap;
 
 use camino::Utf8PathBuf;
@@ -5,11 +12,13 @@ use camino::Utf8PathBuf;
 pub use codespan_reporting::diagnostic::{LabelStyle, Severity};
 use codespan_reporting::{diagnostic::Label as CodespanLabel, files::SimpleFiles};
 use ecow::EcoString;
+pub use explanations::{codes, explanation};
 use termcolor::Buffer;
 
 use crate::ast::SrcSpan;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Level {
     Error,
     Warning,
@@ -55,11 +64,20 @@ pub struct Diagnostic {
     pub title: String,
     pub text: String,
     pub level: Level,
+    /// The stable code identifying the kind of error or warning, such as
+    /// `E0203` or `W0213`.
+    pub code: Option<&'static str>,
     pub location: Option<Location>,
     pub hint: Option<String>,
 }
 
 impl Diagnostic {
+    /// Renders the diagnostic as a single line of JSON, for use by tools that
+    /// consume the compiler's output.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&json::Diagnostic::new(self)).expect("diagnostic to json")
+    }
+
     pub fn write(&self, buffer: &mut Buffer) {
         use std::io::Write;
         match &self.location {
@@ -117,9 +135,12 @@ impl Diagnostic {
             Level::Warning => Severity::Warning,
         };
 
-        let diagnostic = codespan_reporting::diagnostic::Diagnostic::new(severity)
+        let mut diagnostic = codespan_reporting::diagnostic::Diagnostic::new(severity)
             .with_message(&self.title)
             .with_labels(labels);
+        if let Some(code) = self.code {
+            diagnostic = diagnostic.with_code(code);
+        }
         let config = codespan_reporting::term::Config::default();
         codespan_reporting::term::emit(buffer, &config, &files, &diagnostic)
             .expect("write_diagnostic");
@@ -136,6 +157,9 @@ impl Diagnostic {
             .set_color(ColorSpec::new().set_bold(true).set_fg(Some(colour)))
             .expect("write_title_color1");
         write!(buffer, "{kind}").expect("write_title_kind");
+        if let Some(code) = self.code {
+            write!(buffer, "[{code}]").expect("write_title_code");
+        }
         buffer
             .set_color(ColorSpec::new().set_bold(true))
             .expect("write_title_color2");
@@ -145,3 +169,10 @@ impl Diagnostic {
             .expect("write_title_reset");
     }
 }
+
+/// Renders the diagnostics as a [SARIF 2.1.0](https://sarifweb.azurewebsites.net/)
+/// log, as understood by code scanning tools such as the one provided by
+/// GitHub.
+pub fn to_sarif(diagnostics: &[Diagnostic]) -> String {
+    serde_json::to_string_pretty(&sarif::Log::new(diagnostics)).expect("diagnostics to sarif")
+}
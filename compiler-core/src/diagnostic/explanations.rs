@@ -0,0 +1,254 @@
+//! Long form explanations of the errors and warnings of the compiler, as
+//! printed by `gleam explain`.
+
+/// The explanation of each code, sorted by code.
+const EXPLANATIONS: &[(&str, &str)] = &[
+    ("E0001", include_str!("explanations/E0001.md")),
+    ("E0002", include_str!("explanations/E0002.md")),
+    ("E0003", include_str!("explanations/E0003.md")),
+    ("E0004", include_str!("explanations/E0004.md")),
+    ("E0005", include_str!("explanations/E0005.md")),
+    ("E0006", include_str!("explanations/E0006.md")),
+    ("E0007", include_str!("explanations/E0007.md")),
+    ("E0008", include_str!("explanations/E0008.md")),
+    ("E0009", include_str!("explanations/E0009.md")),
+    ("E0010", include_str!("explanations/E0010.md")),
+    ("E0011", include_str!("explanations/E0011.md")),
+    ("E0012", include_str!("explanations/E0012.md")),
+    ("E0013", include_str!("explanations/E0013.md")),
+    ("E0014", include_str!("explanations/E0014.md")),
+    ("E0015", include_str!("explanations/E0015.md")),
+    ("E0016", include_str!("explanations/E0016.md")),
+    ("E0017", include_str!("explanations/E0017.md")),
+    ("E0018", include_str!("explanations/E0018.md")),
+    ("E0019", include_str!("explanations/E0019.md")),
+    ("E0020", include_str!("explanations/E0020.md")),
+    ("E0021", include_str!("explanations/E0021.md")),
+    ("E0022", include_str!("explanations/E0022.md")),
+    ("E0023", include_str!("explanations/E0023.md")),
+    ("E0024", include_str!("explanations/E0024.md")),
+    ("E0025", include_str!("explanations/E0025.md")),
+    ("E0026", include_str!("explanations/E0026.md")),
+    ("E0027", include_str!("explanations/E0027.md")),
+    ("E0028", include_str!("explanations/E0028.md")),
+    ("E0029", include_str!("explanations/E0029.md")),
+    ("E0030", include_str!("explanations/E0030.md")),
+    ("E0031", include_str!("explanations/E0031.md")),
+    ("E0032", include_str!("explanations/E0032.md")),
+    ("E0033", include_str!("explanations/E0033.md")),
+    ("E0034", include_str!("explanations/E0034.md")),
+    ("E0035", include_str!("explanations/E0035.md")),
+    ("E0036", include_str!("explanations/E0036.md")),
+    ("E0037", include_str!("explanations/E0037.md")),
+    ("E0038", include_str!("explanations/E0038.md")),
+    ("E0039", include_str!("explanations/E0039.md")),
+    ("E0040", include_str!("explanations/E0040.md")),
+    ("E0041", include_str!("explanations/E0041.md")),
+    ("E0042", include_str!("explanations/E0042.md")),
+    ("E0043", include_str!("explanations/E0043.md")),
+    ("E0044", include_str!("explanations/E0044.md")),
+    ("E0045", include_str!("explanations/E0045.md")),
+    ("E0046", include_str!("explanations/E0046.md")),
+    ("E0047", include_str!("explanations/E0047.md")),
+    ("E0048", include_str!("explanations/E0048.md")),
+    ("E0049", include_str!("explanations/E0049.md")),
+    ("E0050", include_str!("explanations/E0050.md")),
+    ("E0051", include_str!("explanations/E0051.md")),
+    ("E0052", include_str!("explanations/E0052.md")),
+    ("E0053", include_str!("explanations/E0053.md")),
+    ("E0054", include_str!("explanations/E0054.md")),
+    ("E0055", include_str!("explanations/E0055.md")),
+    ("E0056", include_str!("explanations/E0056.md")),
+    ("E0057", include_str!("explanations/E0057.md")),
+    ("E0058", include_str!("explanations/E0058.md")),
+    ("E0059", include_str!("explanations/E0059.md")),
+    ("E0060", include_str!("explanations/E0060.md")),
+    ("E0061", include_str!("explanations/E0061.md")),
+    ("E0062", include_str!("explanations/E0062.md")),
+    ("E0063", include_str!("explanations/E0063.md")),
+    ("E0064", include_str!("explanations/E0064.md")),
+    ("E0065", include_str!("explanations/E0065.md")),
+    ("E0066", include_str!("explanations/E0066.md")),
+    ("E0067", include_str!("explanations/E0067.md")),
+    ("E0068", include_str!("explanations/E0068.md")),
+    ("E0100", include_str!("explanations/E0100.md")),
+    ("E0101", include_str!("explanations/E0101.md")),
+    ("E0102", include_str!("explanations/E0102.md")),
+    ("E0103", include_str!("explanations/E0103.md")),
+    ("E0104", include_str!("explanations/E0104.md")),
+    ("E0105", include_str!("explanations/E0105.md")),
+    ("E0106", include_str!("explanations/E0106.md")),
+    ("E0107", include_str!("explanations/E0107.md")),
+    ("E0108", include_str!("explanations/E0108.md")),
+    ("E0109", include_str!("explanations/E0109.md")),
+    ("E0110", include_str!("explanations/E0110.md")),
+    ("E0111", include_str!("explanations/E0111.md")),
+    ("E0112", include_str!("explanations/E0112.md")),
+    ("E0113", include_str!("explanations/E0113.md")),
+    ("E0114", include_str!("explanations/E0114.md")),
+    ("E0115", include_str!("explanations/E0115.md")),
+    ("E0116", include_str!("explanations/E0116.md")),
+    ("E0117", include_str!("explanations/E0117.md")),
+    ("E0118", include_str!("explanations/E0118.md")),
+    ("E0119", include_str!("explanations/E0119.md")),
+    ("E0120", include_str!("explanations/E0120.md")),
+    ("E0121", include_str!("explanations/E0121.md")),
+    ("E0122", include_str!("explanations/E0122.md")),
+    ("E0123", include_str!("explanations/E0123.md")),
+    ("E0124", include_str!("explanations/E0124.md")),
+    ("E0125", include_str!("explanations/E0125.md")),
+    ("E0126", include_str!("explanations/E0126.md")),
+    ("E0127", include_str!("explanations/E0127.md")),
+    ("E0128", include_str!("explanations/E0128.md")),
+    ("E0129", include_str!("explanations/E0129.md")),
+    ("E0130", include_str!("explanations/E0130.md")),
+    ("E0131", include_str!("explanations/E0131.md")),
+    ("E0132", include_str!("explanations/E0132.md")),
+    ("E0133", include_str!("explanations/E0133.md")),
+    ("E0134", include_str!("explanations/E0134.md")),
+    ("E0135", include_str!("explanations/E0135.md")),
+    ("E0136", include_str!("explanations/E0136.md")),
+    ("E0137", include_str!("explanations/E0137.md")),
+    ("E0138", include_str!("explanations/E0138.md")),
+    ("E0139", include_str!("explanations/E0139.md")),
+    ("E0140", include_str!("explanations/E0140.md")),
+    ("E0141", include_str!("explanations/E0141.md")),
+    ("E0142", include_str!("explanations/E0142.md")),
+    ("E0143", include_str!("explanations/E0143.md")),
+    ("E0144", include_str!("explanations/E0144.md")),
+    ("E0145", include_str!("explanations/E0145.md")),
+    ("E0146", include_str!("explanations/E0146.md")),
+    ("E0147", include_str!("explanations/E0147.md")),
+    ("E0148", include_str!("explanations/E0148.md")),
+    ("E0149", include_str!("explanations/E0149.md")),
+    ("E0150", include_str!("explanations/E0150.md")),
+    ("E0151", include_str!("explanations/E0151.md")),
+    ("E0152", include_str!("explanations/E0152.md")),
+    ("E0153", include_str!("explanations/E0153.md")),
+    ("E0200", include_str!("explanations/E0200.md")),
+    ("E0201", include_str!("explanations/E0201.md")),
+    ("E0202", include_str!("explanations/E0202.md")),
+    ("E0203", include_str!("explanations/E0203.md")),
+    ("E0204", include_str!("explanations/E0204.md")),
+    ("E0205", include_str!("explanations/E0205.md")),
+    ("E0206", include_str!("explanations/E0206.md")),
+    ("E0207", include_str!("explanations/E0207.md")),
+    ("E0208", include_str!("explanations/E0208.md")),
+    ("E0209", include_str!("explanations/E0209.md")),
+    ("E0210", include_str!("explanations/E0210.md")),
+    ("E0211", include_str!("explanations/E0211.md")),
+    ("E0212", include_str!("explanations/E0212.md")),
+    ("E0213", include_str!("explanations/E0213.md")),
+    ("E0214", include_str!("explanations/E0214.md")),
+    ("E0215", include_str!("explanations/E0215.md")),
+    ("E0216", include_str!("explanations/E0216.md")),
+    ("E0217", include_str!("explanations/E0217.md")),
+    ("E0218", include_str!("explanations/E0218.md")),
+    ("E0219", include_str!("explanations/E0219.md")),
+    ("E0220", include_str!("explanations/E0220.md")),
+    ("E0221", include_str!("explanations/E0221.md")),
+    ("E0222", include_str!("explanations/E0222.md")),
+    ("E0223", include_str!("explanations/E0223.md")),
+    ("E0224", include_str!("explanations/E0224.md")),
+    ("E0225", include_str!("explanations/E0225.md")),
+    ("E0226", include_str!("explanations/E0226.md")),
+    ("E0227", include_str!("explanations/E0227.md")),
+    ("E0228", include_str!("explanations/E0228.md")),
+    ("E0229", include_str!("explanations/E0229.md")),
+    ("E0230", include_str!("explanations/E0230.md")),
+    ("E0231", include_str!("explanations/E0231.md")),
+    ("E0232", include_str!("explanations/E0232.md")),
+    ("E0233", include_str!("explanations/E0233.md")),
+    ("E0234", include_str!("explanations/E0234.md")),
+    ("E0235", include_str!("explanations/E0235.md")),
+    ("E0236", include_str!("explanations/E0236.md")),
+    ("E0237", include_str!("explanations/E0237.md")),
+    ("E0238", include_str!("explanations/E0238.md")),
+    ("E0239", include_str!("explanations/E0239.md")),
+    ("E0240", include_str!("explanations/E0240.md")),
+    ("E0241", include_str!("explanations/E0241.md")),
+    ("E0242", include_str!("explanations/E0242.md")),
+    ("E0243", include_str!("explanations/E0243.md")),
+    ("E0244", include_str!("explanations/E0244.md")),
+    ("E0245", include_str!("explanations/E0245.md")),
+    ("E0246", include_str!("explanations/E0246.md")),
+    ("E0247", include_str!("explanations/E0247.md")),
+    ("E0248", include_str!("explanations/E0248.md")),
+    ("E0249", include_str!("explanations/E0249.md")),
+    ("E0250", include_str!("explanations/E0250.md")),
+    ("E0251", include_str!("explanations/E0251.md")),
+    ("E0252", include_str!("explanations/E0252.md")),
+    ("E0253", include_str!("explanations/E0253.md")),
+    ("E0254", include_str!("explanations/E0254.md")),
+    ("E0255", include_str!("explanations/E0255.md")),
+    ("E0256", include_str!("explanations/E0256.md")),
+    ("E0257", include_str!("explanations/E0257.md")),
+    ("E0258", include_str!("explanations/E0258.md")),
+    ("E0259", include_str!("explanations/E0259.md")),
+    ("E0260", include_str!("explanations/E0260.md")),
+    ("E0261", include_str!("explanations/E0261.md")),
+    ("E0262", include_str!("explanations/E0262.md")),
+    ("E0263", include_str!("explanations/E0263.md")),
+    ("E0264", include_str!("explanations/E0264.md")),
+    ("E0265", include_str!("explanations/E0265.md")),
+    ("E0266", include_str!("explanations/E0266.md")),
+    ("W0001", include_str!("explanations/W0001.md")),
+    ("W0002", include_str!("explanations/W0002.md")),
+    ("W0100", include_str!("explanations/W0100.md")),
+    ("W0101", include_str!("explanations/W0101.md")),
+    ("W0102", include_str!("explanations/W0102.md")),
+    ("W0103", include_str!("explanations/W0103.md")),
+    ("W0104", include_str!("explanations/W0104.md")),
+    ("W0200", include_str!("explanations/W0200.md")),
+    ("W0201", include_str!("explanations/W0201.md")),
+    ("W0202", include_str!("explanations/W0202.md")),
+    ("W0203", include_str!("explanations/W0203.md")),
+    ("W0204", include_str!("explanations/W0204.md")),
+    ("W0205", include_str!("explanations/W0205.md")),
+    ("W0206", include_str!("explanations/W0206.md")),
+    ("W0207", include_str!("explanations/W0207.md")),
+    ("W0208", include_str!("explanations/W0208.md")),
+    ("W0209", include_str!("explanations/W0209.md")),
+    ("W0210", include_str!("explanations/W0210.md")),
+    ("W0211", include_str!("explanations/W0211.md")),
+    ("W0212", include_str!("explanations/W0212.md")),
+    ("W0213", include_str!("explanations/W0213.md")),
+    ("W0214", include_str!("explanations/W0214.md")),
+    ("W0215", include_str!("explanations/W0215.md")),
+    ("W0216", include_str!("explanations/W0216.md")),
+    ("W0217", include_str!("explanations/W0217.md")),
+    ("W0218", include_str!("explanations/W0218.md")),
+    ("W0219", include_str!("explanations/W0219.md")),
+    ("W0220", include_str!("explanations/W0220.md")),
+    ("W0221", include_str!("explanations/W0221.md")),
+    ("W0222", include_str!("explanations/W0222.md")),
+    ("W0223", include_str!("explanations/W0223.md")),
+    ("W0224", include_str!("explanations/W0224.md")),
+    ("W0225", include_str!("explanations/W0225.md")),
+    ("W0226", include_str!("explanations/W0226.md")),
+    ("W0227", include_str!("explanations/W0227.md")),
+    ("W0228", include_str!("explanations/W0228.md")),
+    ("W0229", include_str!("explanations/W0229.md")),
+    ("W0230", include_str!("explanations/W0230.md")),
+    ("W0231", include_str!("explanations/W0231.md")),
+    ("W0232", include_str!("explanations/W0232.md")),
+    ("W0233", include_str!("explanations/W0233.md")),
+    ("W0234", include_str!("explanations/W0234.md")),
+    ("W0235", include_str!("explanations/W0235.md")),
+    ("W0236", include_str!("explanations/W0236.md")),
+    ("W0237", include_str!("explanations/W0237.md")),
+];
+
+/// Returns the explanation of an error or warning code, such as `E0203`.
+pub fn explanation(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .binary_search_by(|(known, _)| (*known).cmp(code))
+        .ok()
+        .and_then(|index| EXPLANATIONS.get(index))
+        .map(|(_, explanation)| *explanation)
+}
+
+/// All the known error and warning codes.
+pub fn codes() -> impl Iterator<Item = &'static str> {
+    EXPLANATIONS.iter().map(|(code, _)| *code)
+}
@@ -0,0 +1,114 @@
+use camino::Utf8Path;
+use serde::Serialize;
+
+use crate::line_numbers::{LineColumn, LineNumbers};
+
+use super::Level;
+
+/// A diagnostic as it is serialised when printing JSON lines.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Diagnostic<'a> {
+    pub level: Level,
+    pub code: Option<&'static str>,
+    pub title: &'a str,
+    pub text: &'a str,
+    pub hint: Option<&'a str>,
+    pub location: Option<Location<'a>>,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub fn new(diagnostic: &'a super::Diagnostic) -> Self {
+        Self {
+            level: diagnostic.level,
+            code: diagnostic.code,
+            title: &diagnostic.title,
+            text: &diagnostic.text,
+            hint: diagnostic.hint.as_deref(),
+            location: diagnostic.location.as_ref().map(Location::new),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Location<'a> {
+    pub path: &'a Utf8Path,
+    pub label: Label<'a>,
+    pub extra_labels: Vec<ExtraLabel<'a>>,
+}
+
+impl<'a> Location<'a> {
+    fn new(location: &'a super::Location) -> Self {
+        let line_numbers = LineNumbers::new(&location.src);
+        let extra_labels = location
+            .extra_labels
+            .iter()
+            .map(|extra| match &extra.src_info {
+                Some((src, path)) => ExtraLabel {
+                    path,
+                    label: Label::new(&extra.label, &LineNumbers::new(src)),
+                },
+                None => ExtraLabel {
+                    path: &location.path,
+                    label: Label::new(&extra.label, &line_numbers),
+                },
+            })
+            .collect();
+
+        Self {
+            path: &location.path,
+            label: Label::new(&location.label, &line_numbers),
+            extra_labels,
+        }
+    }
+}
+
+/// A label pointing to a piece of code in a different position, and possibly a
+/// different file, than the main one of a diagnostic.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExtraLabel<'a> {
+    pub path: &'a Utf8Path,
+    #[serde(flatten)]
+    pub label: Label<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct Label<'a> {
+    pub text: Option<&'a str>,
+    /// The byte offsets of the labelled code.
+    pub span: Span,
+    /// The 1-indexed line and column numbers of the labelled code. Columns
+    /// are counted in UTF-16 code units.
+    pub range: Range,
+}
+
+impl<'a> Label<'a> {
+    fn new(label: &'a super::Label, line_numbers: &LineNumbers) -> Self {
+        Self {
+            text: label.text.as_deref(),
+            span: Span {
+                start: label.span.start,
+                end: label.span.end,
+            },
+            range: Range {
+                start: line_numbers.line_and_column_number(label.span.start),
+                end: line_numbers.line_and_column_number(label.span.end),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct Range {
+    pub start: LineColumn,
+    pub end: LineColumn,
+}
@@ -0,0 +1,199 @@
+//! The subset of the [SARIF 2.1.0 format](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html)
+//! needed to report the errors and warnings of the compiler.
+
+use camino::Utf8Path;
+use serde::Serialize;
+
+use crate::version::COMPILER_VERSION;
+
+use super::{
+    Level,
+    json::{self, Label},
+};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Serialize, Debug)]
+pub struct Log<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run<'a>>,
+}
+
+impl<'a> Log<'a> {
+    pub fn new(diagnostics: &'a [super::Diagnostic]) -> Self {
+        let run = Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "gleam",
+                    information_uri: "https://gleam.run",
+                    version: COMPILER_VERSION,
+                },
+            },
+            results: diagnostics.iter().map(SarifResult::new).collect(),
+        };
+
+        Self {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: vec![run],
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct Run<'a> {
+    tool: Tool,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    information_uri: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<&'static str>,
+    level: Level,
+    message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location<'a>>,
+}
+
+impl<'a> SarifResult<'a> {
+    fn new(diagnostic: &'a super::Diagnostic) -> Self {
+        let diagnostic = json::Diagnostic::new(diagnostic);
+
+        let mut text = diagnostic.title.to_string();
+        if !diagnostic.text.is_empty() {
+            text.push_str("\n\n");
+            text.push_str(diagnostic.text.trim_end());
+        }
+        if let Some(hint) = diagnostic.hint {
+            text.push_str("\n\nHint: ");
+            text.push_str(hint);
+        }
+
+        let (locations, related_locations) = match diagnostic.location {
+            None => (vec![], vec![]),
+            Some(location) => (
+                vec![Location::new(location.path, location.label)],
+                location
+                    .extra_labels
+                    .into_iter()
+                    .map(|extra| Location::new(extra.path, extra.label))
+                    .collect(),
+            ),
+        };
+
+        Self {
+            rule_id: diagnostic.code,
+            level: diagnostic.level,
+            message: Message { text },
+            locations,
+            related_locations,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Location<'a> {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<MessageRef<'a>>,
+}
+
+impl<'a> Location<'a> {
+    fn new(path: &Utf8Path, label: Label<'a>) -> Self {
+        let region = Region {
+            start_line: label.range.start.line,
+            start_column: label.range.start.column,
+            end_line: label.range.end.line,
+            end_column: label.range.end.column,
+            byte_offset: label.span.start,
+            byte_length: label.span.end.saturating_sub(label.span.start),
+        };
+
+        Self {
+            physical_location: PhysicalLocation {
+                artifact_location: ArtifactLocation { uri: uri(path) },
+                region,
+            },
+            message: label.text.map(|text| MessageRef { text }),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+struct MessageRef<'a> {
+    text: &'a str,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize, Debug)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+/// SARIF columns are 1-indexed and counted in UTF-16 code units by default,
+/// the same as the ones computed by `LineNumbers`.
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: u32,
+    start_column: u32,
+    end_line: u32,
+    end_column: u32,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+/// SARIF requires artifact locations to be URIs: relative paths are kept
+/// relative, while absolute ones are turned into `file://` URIs.
+///
+fn uri(path: &Utf8Path) -> String {
+    let path = path.as_str().replace('\\', "/");
+    let mut uri = String::with_capacity(path.len());
+    if path.starts_with('/') {
+        uri.push_str("file://");
+    } else if path.get(1..3) == Some(":/") {
+        uri.push_str("file:///");
+    }
+
+    for char in path.chars() {
+        match char {
+            ' ' => uri.push_str("%20"),
+            '#' => uri.push_str("%23"),
+            '%' => uri.push_str("%25"),
+            '?' => uri.push_str("%3F"),
+            _ => uri.push(char),
+        }
+    }
+    uri
+}
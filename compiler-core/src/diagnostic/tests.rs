@@ -0,0 +1,166 @@
+use camino::Utf8PathBuf;
+
+use crate::{ast::SrcSpan, version::COMPILER_VERSION};
+
+use super::{Diagnostic, ExtraLabel, Label, Level, Location, codes, explanation, to_sarif};
+
+const SRC: &str = r#"pub fn main() {
+  let ünïcödé = 1
+  wibble(ünïcödé)
+}
+"#;
+
+const OTHER_SRC: &str = r#"pub fn wibble(x: String) {
+  x
+}
+"#;
+
+fn locationless_diagnostic() -> Diagnostic {
+    Diagnostic {
+        title: "These files have not been formatted".into(),
+        text: "  - src/wibble.gleam\n".into(),
+        level: Level::Error,
+        code: Some("E0012"),
+        location: None,
+        hint: None,
+    }
+}
+
+fn type_mismatch_diagnostic() -> Diagnostic {
+    Diagnostic {
+        title: "Type mismatch".into(),
+        text: "Expected type:\n\n    String\n\nFound type:\n\n    Int".into(),
+        level: Level::Error,
+        code: Some("E0215"),
+        location: Some(Location {
+            src: SRC.into(),
+            path: Utf8PathBuf::from("src/wobble.gleam"),
+            label: Label {
+                text: None,
+                span: SrcSpan::new(47, 58),
+            },
+            extra_labels: vec![
+                ExtraLabel {
+                    src_info: None,
+                    label: Label {
+                        text: Some("Defined here".into()),
+                        span: SrcSpan::new(22, 33),
+                    },
+                },
+                ExtraLabel {
+                    src_info: Some((
+                        OTHER_SRC.into(),
+                        Utf8PathBuf::from("/my project/src/wibble.gleam"),
+                    )),
+                    label: Label {
+                        text: Some("Expects a String".into()),
+                        span: SrcSpan::new(14, 23),
+                    },
+                },
+            ],
+        }),
+        hint: None,
+    }
+}
+
+fn unused_variable_diagnostic() -> Diagnostic {
+    Diagnostic {
+        title: "Unused variable".into(),
+        text: "".into(),
+        level: Level::Warning,
+        code: Some("W0213"),
+        location: Some(Location {
+            src: OTHER_SRC.into(),
+            path: Utf8PathBuf::from("src/wibble.gleam"),
+            label: Label {
+                text: Some("This variable is never used".into()),
+                span: SrcSpan::new(14, 15),
+            },
+            extra_labels: vec![],
+        }),
+        hint: Some("You can ignore it with an underscore: `_x`.".into()),
+    }
+}
+
+#[test]
+fn locationless_diagnostic_to_json() {
+    insta::assert_snapshot!(locationless_diagnostic().to_json());
+}
+
+#[test]
+fn diagnostic_with_extra_labels_to_json() {
+    insta::assert_snapshot!(type_mismatch_diagnostic().to_json());
+}
+
+#[test]
+fn warning_with_hint_to_json() {
+    insta::assert_snapshot!(unused_variable_diagnostic().to_json());
+}
+
+#[test]
+fn json_diagnostic_is_a_single_line() {
+    assert!(!type_mismatch_diagnostic().to_json().contains('\n'));
+}
+
+#[test]
+fn diagnostics_to_sarif() {
+    let sarif = to_sarif(&[
+        locationless_diagnostic(),
+        type_mismatch_diagnostic(),
+        unused_variable_diagnostic(),
+    ]);
+    insta::assert_snapshot!(sarif.replace(COMPILER_VERSION, "<version>"));
+}
+
+#[test]
+fn no_diagnostics_to_sarif() {
+    insta::assert_snapshot!(to_sarif(&[]).replace(COMPILER_VERSION, "<version>"));
+}
+
+#[test]
+fn explanation_codes_are_sorted_and_unique() {
+    let codes: Vec<_> = codes().collect();
+    let mut sorted = codes.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(codes, sorted);
+}
+
+#[test]
+fn explanations_start_with_their_code() {
+    for code in codes() {
+        let explanation = explanation(code).expect("explanation");
+        assert!(explanation.starts_with(&format!("# {code}: ")), "{code}");
+    }
+}
+
+#[test]
+fn unknown_code_has_no_explanation() {
+    assert_eq!(explanation("E9999"), None);
+    assert_eq!(explanation("e0203"), None);
+}
+
+/// Every code given to an error or warning must have an explanation, and
+/// every explanation must be for a code that is still in use.
+#[test]
+fn every_code_has_an_explanation() {
+    let sources = [
+        include_str!("../error.rs"),
+        include_str!("../parse/error.rs"),
+        include_str!("../type_/error.rs"),
+        include_str!("../warning.rs"),
+    ];
+    let mut used: Vec<_> = sources
+        .iter()
+        .flat_map(|source| source.split('"'))
+        .filter(|literal| {
+            literal.len() == 5
+                && literal.starts_with(['E', 'W'])
+                && literal[1..].bytes().all(|byte| byte.is_ascii_digit())
+        })
+        .collect();
+    used.sort_unstable();
+    used.dedup();
+
+    assert_eq!(used, codes().collect::<Vec<_>>());
+}
@@ -11,7 +11,7 @@ use super::{
 };
 use crate::{
     build::{
-        self, Mode, NullTelemetry, Origin, PackageCompiler, StaleTracker,
+        self, Mode, NullTelemetry, Origin, PackageCompiler, SourceFingerprints, StaleTracker,
         TargetCodegenConfiguration,
     },
     config::{DocsPage, PackageConfig, Repository},
@@ -81,6 +81,7 @@ fn compile_with_markdown_pages(
             &mut defined_modules,
             &mut StaleTracker::default(),
             &mut HashSet::new(),
+            &mut SourceFingerprints::default(),
             &NullTelemetry,
         )
         .unwrap()
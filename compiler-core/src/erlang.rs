@@ -8,11 +8,12 @@ mod tests;
 
 use crate::build::{Target, module_erlang_name};
 use crate::strings::{convert_string_escape_chars, to_snake_case};
-use crate::type_::is_prelude_module;
+use crate::type_::{TypeVarCell, is_prelude_module};
 use crate::{
     Result,
     ast::{CustomType, Function, Import, ModuleConstant, TypeAlias, *},
     docvec,
+    javascript::{SourceMap, SourceMapWriter},
     line_numbers::LineNumbers,
     pretty::*,
     type_::{
@@ -46,10 +47,16 @@ struct Env<'a> {
     echo_used: bool,
     current_scope_vars: im::HashMap<String, usize>,
     erl_function_scope_vars: im::HashMap<String, usize>,
+    file_attributes: FileAttributes,
 }
 
 impl<'env> Env<'env> {
-    pub fn new(module: &'env str, function: &'env str, line_numbers: &'env LineNumbers) -> Self {
+    pub fn new(
+        module: &'env str,
+        function: &'env str,
+        line_numbers: &'env LineNumbers,
+        file_attributes: FileAttributes,
+    ) -> Self {
         let vars: im::HashMap<_, _> = std::iter::once(("_".into(), 0)).collect();
         Self {
             current_scope_vars: vars.clone(),
@@ -59,6 +66,17 @@ impl<'env> Env<'env> {
             line_numbers,
             function,
             module,
+            file_attributes,
+        }
+    }
+
+    /// Adds the location of the Gleam code a document was generated from in
+    /// front of it. Source locations are only used to build a source map,
+    /// which is generated when the `-file` attributes are omitted.
+    fn with_source_location<'a>(&self, start: u32, document: Document<'a>) -> Document<'a> {
+        match self.file_attributes {
+            FileAttributes::Omit => docvec![source_location(start), document],
+            FileAttributes::Emit => document,
         }
     }
 
@@ -149,13 +167,52 @@ pub fn module<'a>(
     line_numbers: &'a LineNumbers,
     root: &'a Utf8Path,
 ) -> Result<String> {
-    Ok(module_document(module, line_numbers, root)?.to_pretty_string(MAX_COLUMNS))
+    Ok(
+        module_document(module, line_numbers, root, FileAttributes::Emit)?
+            .to_pretty_string(MAX_COLUMNS),
+    )
+}
+
+/// Generates a module together with the source map pointing each of its
+/// lines back to the Gleam code it was compiled from, which is how the lines
+/// counted by `cover` are mapped back to the Gleam code.
+///
+/// The functions don't get `-file` attributes: those restart the line numbers
+/// from the line of each function in the Gleam module, so the lines of
+/// different functions would be counted together by `cover`.
+///
+/// `file` is the name of the generated file, and `source` the path of the
+/// Gleam module relative to the directory the generated file is written to.
+///
+pub fn module_with_source_map<'a>(
+    module: &'a TypedModule,
+    line_numbers: &'a LineNumbers,
+    root: &'a Utf8Path,
+    src: &EcoString,
+    file: &str,
+    source: &Utf8Path,
+) -> Result<(String, SourceMap)> {
+    let document = module_document(module, line_numbers, root, FileAttributes::Omit)?;
+    let mut writer = SourceMapWriter::new();
+    document
+        .pretty_print(MAX_COLUMNS, &mut writer)
+        .expect("Writing to string buffer failed");
+    Ok(writer.finish(file, source, src, line_numbers))
+}
+
+/// Whether each function is preceded by a `-file` attribute pointing back to
+/// the line it is defined on in the Gleam module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileAttributes {
+    Emit,
+    Omit,
 }
 
 fn module_document<'a>(
     module: &'a TypedModule,
     line_numbers: &'a LineNumbers,
     root: &'a Utf8Path,
+    file_attributes: FileAttributes,
 ) -> Result<Document<'a>> {
     let mut exports = vec![];
     let mut type_defs = vec![];
@@ -236,6 +293,7 @@ fn module_document<'a>(
             module.type_info.is_internal,
             line_numbers,
             src_path_relative.clone(),
+            file_attributes,
             &module.unused_definition_positions,
         ) {
             needs_function_docs = needs_function_docs || env.needs_function_docs;
@@ -352,7 +410,7 @@ fn register_imports_and_exports(
                 .filter(|&id| !constructor_var_usages.contains_key(id))
                 .sorted()
                 .map(|&id| Type::Var {
-                    type_: Arc::new(std::cell::RefCell::new(TypeVar::Generic { id })),
+                    type_: Arc::new(TypeVarCell::new(TypeVar::Generic { id })),
                 })
                 .collect();
             let phantom_vars_constructor = if !phantom_vars.is_empty() {
@@ -428,6 +486,7 @@ fn module_statement<'a>(
     is_internal_module: bool,
     line_numbers: &'a LineNumbers,
     src_path: EcoString,
+    file_attributes: FileAttributes,
     unused_definition_positions: &HashSet<u32>,
 ) -> Option<(Document<'a>, Env<'a>)> {
     match statement {
@@ -438,9 +497,14 @@ fn module_statement<'a>(
             None
         }
 
-        Definition::Function(function) => {
-            module_function(function, module, is_internal_module, line_numbers, src_path)
-        }
+        Definition::Function(function) => module_function(
+            function,
+            module,
+            is_internal_module,
+            line_numbers,
+            src_path,
+            file_attributes,
+        ),
 
         Definition::TypeAlias(TypeAlias { .. })
         | Definition::CustomType(CustomType { .. })
@@ -455,6 +519,7 @@ fn module_function<'a>(
     is_internal_module: bool,
     line_numbers: &'a LineNumbers,
     src_path: EcoString,
+    file_attributes: FileAttributes,
 ) -> Option<(Document<'a>, Env<'a>)> {
     // Private external functions don't need to render anything, the underlying
     // Erlang implementation is used directly at the call site.
@@ -473,9 +538,8 @@ fn module_function<'a>(
         .as_ref()
         .expect("A module's function must be named");
     let function_name = escape_erlang_existing_name(function_name);
-    let file_attribute = file_attribute(src_path, function, line_numbers);
 
-    let mut env = Env::new(module, function_name, line_numbers);
+    let mut env = Env::new(module, function_name, line_numbers, file_attributes);
     let var_usages = collect_type_var_usages(
         HashMap::new(),
         std::iter::once(&function.return_type).chain(function.arguments.iter().map(|a| &a.type_)),
@@ -507,32 +571,33 @@ fn module_function<'a>(
         })
         .unwrap_or_else(|| statement_sequence(&function.body, &mut env));
 
-    let attributes = file_attribute;
-    let attributes = if is_internal_module || function.publicity.is_internal() {
+    let mut attributes = vec![];
+    if file_attributes == FileAttributes::Emit {
+        attributes.push(file_attribute(src_path, function, line_numbers));
+    }
+    if is_internal_module || function.publicity.is_internal() {
         // If a function is marked as internal or comes from an internal module
         // we want to hide its documentation in the Erlang shell!
         // So the doc directive will look like this: `-doc(false).`
         env.needs_function_docs = true;
-        docvec![attributes, line(), hidden_function_doc()]
-    } else {
-        match &function.documentation {
-            Some((_, documentation)) => {
-                env.needs_function_docs = true;
-                let doc_lines = documentation
-                    .trim_end()
-                    .split('\n')
-                    .map(EcoString::from)
-                    .collect_vec();
-                docvec![attributes, line(), function_doc(&doc_lines)]
-            }
-            _ => attributes,
-        }
-    };
+        attributes.push(hidden_function_doc());
+    } else if let Some((_, documentation)) = &function.documentation {
+        env.needs_function_docs = true;
+        let doc_lines = documentation
+            .trim_end()
+            .split('\n')
+            .map(EcoString::from)
+            .collect_vec();
+        attributes.push(function_doc(&doc_lines));
+    }
 
     Some((
         docvec![
-            attributes,
-            line(),
+            concat(
+                attributes
+                    .into_iter()
+                    .map(|attribute| attribute.append(line()))
+            ),
             spec,
             atom_string(escape_erlang_existing_name(function_name).into()),
             arguments,
@@ -896,12 +961,13 @@ fn statement<'a>(
     env: &mut Env<'a>,
     position: Position,
 ) -> Document<'a> {
-    match statement {
+    let document = match statement {
         Statement::Expression(e) => expr(e, env),
         Statement::Assignment(a) => assignment(a, env, position),
         Statement::Use(use_) => expr(&use_.call, env),
         Statement::Assert(a) => assert(a, env),
-    }
+    };
+    env.with_source_location(statement.location().start, document)
 }
 
 fn expr_segment<'a>(
@@ -2107,7 +2173,7 @@ fn erlang_error<'a>(
 }
 
 fn expr<'a>(expression: &'a TypedExpr, env: &mut Env<'a>) -> Document<'a> {
-    match expression {
+    let document = match expression {
         TypedExpr::Todo {
             message: label,
             location,
@@ -2218,7 +2284,8 @@ fn expr<'a>(expression: &'a TypedExpr, env: &mut Env<'a>) -> Document<'a> {
         ),
 
         TypedExpr::Invalid { .. } => panic!("invalid expressions should not reach code generation"),
-    }
+    };
+    env.with_source_location(expression.location().start, document)
 }
 
 fn pipeline<'a>(
@@ -5,6 +5,7 @@ use crate::dependency::{PackageFetcher, ResolutionError};
 use crate::diagnostic::{Diagnostic, ExtraLabel, Label, Location};
 
 use crate::derivation_tree::DerivationTreePrinter;
+use crate::package_interface::diff::ChangeKind;
 use crate::parse::error::ParseErrorDetails;
 use crate::strings::{to_snake_case, to_upper_camel_case};
 use crate::type_::collapse_links;
@@ -217,12 +218,21 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("warnings are not permitted")]
     ForbiddenWarnings { count: usize },
 
+    #[error("denied warnings were emitted")]
+    DeniedWarnings { count: usize },
+
     #[error("Invalid runtime for {target} target: {invalid_runtime}")]
     InvalidRuntime {
         target: Target,
         invalid_runtime: Runtime,
     },
 
+    #[error("Coverage cannot be measured on the {runtime} runtime")]
+    CoverageUnsupportedRuntime { runtime: Runtime },
+
+    #[error("No tests match the filter")]
+    NoTestsMatchFilter,
+
     #[error("package downloading failed: {error}")]
     DownloadPackageError {
         package_name: String,
@@ -324,6 +334,29 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
 
     #[error("Cannot add a package with the same name as a dependency")]
     CannotAddSelfAsDependency { name: EcoString },
+
+    #[error("{code} is not a known error or warning code")]
+    UnknownDiagnosticCode { code: EcoString },
+
+    #[error("The version is too low for the changes to the public API")]
+    CannotPublishIncompatibleVersion {
+        previous: EcoString,
+        version: EcoString,
+        minimum: EcoString,
+        changes: ChangeKind,
+    },
+
+    #[error("The public API of {from} cannot be compared")]
+    PackageInterfaceUnavailable { from: EcoString, reason: String },
+
+    #[error("{path} is the root of a workspace rather than a package")]
+    NotAvailableInWorkspaceRoot {
+        path: Utf8PathBuf,
+        members: Vec<Utf8PathBuf>,
+    },
+
+    #[error("Modules of a workspace member cannot be renamed")]
+    CannotRenameModuleInWorkspace { workspace: Utf8PathBuf },
 }
 
 /// This is to make clippy happy and not make the error variant too big by
@@ -567,7 +600,7 @@ pub enum FileIoAction {
     Read,
     Parse,
     Delete,
-    // Rename,
+    Rename,
     Create,
     WriteTo,
     Canonicalise,
@@ -585,7 +618,7 @@ impl FileIoAction {
             FileIoAction::Read => "read",
             FileIoAction::Parse => "parse",
             FileIoAction::Delete => "delete",
-            // FileIoAction::Rename => "rename",
+            FileIoAction::Rename => "rename",
             FileIoAction::Create => "create",
             FileIoAction::WriteTo => "write to",
             FileIoAction::FindParent => "find the parent of",
@@ -747,7 +780,7 @@ fn edit_distance_with_substrings(a: &str, b: &str, limit: usize) -> Option<usize
     (score <= limit).then_some(score)
 }
 
-fn did_you_mean(name: &str, options: &[EcoString]) -> Option<String> {
+pub(crate) fn did_you_mean(name: &str, options: &[EcoString]) -> Option<String> {
     // If only one option is given, return that option.
     // This seems to solve the `unknown_variable_3` test.
     if options.len() == 1 {
@@ -783,6 +816,87 @@ fn did_you_mean(name: &str, options: &[EcoString]) -> Option<String> {
 }
 
 impl Error {
+    /// The stable code identifying this kind of error, as printed alongside
+    /// its diagnostics and accepted by `gleam explain`.
+    ///
+    /// Parse and type errors are identified by the code of the errors they
+    /// wrap instead, so this returns `None` for them.
+    ///
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            Error::Parse { error, .. } => Some(error.error.code()),
+            Error::Type { .. } => None,
+            Error::UnknownImport { .. } => Some("E0001"),
+            Error::DuplicateModule { .. } => Some("E0002"),
+            Error::DuplicateSourceFile { .. } => Some("E0003"),
+            Error::DuplicateNativeErlangModule { .. } => Some("E0004"),
+            Error::ClashingGleamModuleAndNativeFileName { .. } => Some("E0005"),
+            Error::ImportCycle { .. } => Some("E0006"),
+            Error::PackageCycle { .. } => Some("E0007"),
+            Error::FileIo { .. } => Some("E0008"),
+            Error::NonUtf8Path { .. } => Some("E0009"),
+            Error::GitInitialization { .. } => Some("E0010"),
+            Error::StandardIo { .. } => Some("E0011"),
+            Error::Format { .. } => Some("E0012"),
+            Error::Hex(_) => Some("E0013"),
+            Error::ExpandTar { .. } => Some("E0014"),
+            Error::AddTar { .. } => Some("E0015"),
+            Error::TarFinish(_) => Some("E0016"),
+            Error::Gzip(_) => Some("E0017"),
+            Error::ShellProgramNotFound { .. } => Some("E0018"),
+            Error::ShellCommand { .. } => Some("E0019"),
+            Error::InvalidProjectName { .. } => Some("E0020"),
+            Error::InvalidModuleName { .. } => Some("E0021"),
+            Error::ModuleDoesNotExist { .. } => Some("E0022"),
+            Error::ModuleDoesNotHaveMainFunction { .. } => Some("E0023"),
+            Error::MainFunctionIsPrivate { .. } => Some("E0024"),
+            Error::MainFunctionHasWrongArity { .. } => Some("E0025"),
+            Error::MainFunctionDoesNotSupportTarget { .. } => Some("E0026"),
+            Error::InvalidVersionFormat { .. } => Some("E0027"),
+            Error::IncompatibleLockedVersion { .. } => Some("E0028"),
+            Error::ProjectRootAlreadyExist { .. } => Some("E0029"),
+            Error::OutputFilesAlreadyExist { .. } => Some("E0030"),
+            Error::RemovedPackagesNotExist { .. } => Some("E0031"),
+            Error::UnableToFindProjectRoot { .. } => Some("E0032"),
+            Error::VersionDoesNotMatch { .. } => Some("E0033"),
+            Error::MetadataDecodeError { .. } => Some("E0034"),
+            Error::ForbiddenWarnings { .. } => Some("E0035"),
+            Error::DeniedWarnings { .. } => Some("E0062"),
+            Error::CoverageUnsupportedRuntime { .. } => Some("E0063"),
+            Error::InvalidRuntime { .. } => Some("E0036"),
+            Error::DownloadPackageError { .. } => Some("E0037"),
+            Error::Http(_) => Some("E0038"),
+            Error::DependencyCanonicalizationFailed(_) => Some("E0039"),
+            Error::DependencyResolutionFailed(_) => Some("E0040"),
+            Error::DuplicateDependency(_) => Some("E0041"),
+            Error::WrongDependencyProvided { .. } => Some("E0042"),
+            Error::ProvidedDependencyConflict { .. } => Some("E0043"),
+            Error::MissingHexPublishFields { .. } => Some("E0044"),
+            Error::PublishNonHexDependencies { .. } => Some("E0045"),
+            Error::UnsupportedBuildTool { .. } => Some("E0046"),
+            Error::FailedToOpenDocs { .. } => Some("E0047"),
+            Error::IncompatibleCompilerVersion { .. } => Some("E0048"),
+            Error::JavaScriptPreludeRequired => Some("E0049"),
+            Error::CannotPublishTodo { .. } => Some("E0050"),
+            Error::CannotPublishEcho { .. } => Some("E0051"),
+            Error::CannotPublishLeakedInternalType { .. } => Some("E0052"),
+            Error::HexPackageSquatting => Some("E0053"),
+            Error::CorruptManifest => Some("E0054"),
+            Error::GleamModuleWouldOverwriteStandardErlangModule { .. } => Some("E0055"),
+            Error::HexPublishReplaceRequired { .. } => Some("E0056"),
+            Error::CannotPublishWrongVersion { .. } => Some("E0057"),
+            Error::FailedToEncryptLocalHexApiKey { .. } => Some("E0058"),
+            Error::FailedToDecryptLocalHexApiKey { .. } => Some("E0059"),
+            Error::CannotAddSelfAsDependency { .. } => Some("E0060"),
+            Error::UnknownDiagnosticCode { .. } => Some("E0061"),
+            Error::CannotPublishIncompatibleVersion { .. } => Some("E0064"),
+            Error::PackageInterfaceUnavailable { .. } => Some("E0065"),
+            Error::NotAvailableInWorkspaceRoot { .. } => Some("E0066"),
+            Error::CannotRenameModuleInWorkspace { .. } => Some("E0067"),
+            Error::NoTestsMatchFilter => Some("E0068"),
+        }
+    }
+
     pub fn pretty_string(&self) -> String {
         let mut nocolor = Buffer::no_color();
         self.pretty(&mut nocolor);
@@ -811,6 +925,7 @@ package deletion or account suspension.
                     title: "Invalid Hex package".into(),
                     text,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                     hint: None,
                 }]
@@ -829,6 +944,7 @@ of the Gleam dependency modules."
                     title: "Failed to decode module metadata".into(),
                     text,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                     hint: None,
                 }]
@@ -842,6 +958,7 @@ of the Gleam dependency modules."
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -854,6 +971,7 @@ Module names can only contain lowercase letters, underscore, and
 forward slash and must not end with a slash."
                 ),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: None,
             }],
@@ -867,6 +985,7 @@ forward slash and must not end with a slash."
                     title: "Module does not exist".into(),
                     text: format!("Module `{module}` was not found."),
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                     hint: Some(hint),
                 }]
@@ -878,6 +997,7 @@ forward slash and must not end with a slash."
                     "`{module}` does not have a main function so the module can not be run."
                 ),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: Some(format!(
                     "Add a public `main` function to \
@@ -892,6 +1012,7 @@ to `{}/{module}.gleam`.",
                     "`{module}` has a main function, but it is private, so it cannot be run."
                 ),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: Some(wrap_format!(
                     "Make the `main` function in the `{module}` module public."
@@ -905,6 +1026,7 @@ to `{}/{module}.gleam`.",
 target, so it cannot be run."
                 ),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: None,
             }],
@@ -915,6 +1037,7 @@ target, so it cannot be run."
                     "`{module}:main` should have an arity of 0 to be run but its arity is {arity}."
                 ),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: Some("Change the function signature of main to `pub fn main() {}`.".into()),
             }],
@@ -923,6 +1046,7 @@ target, so it cannot be run."
                 title: "Project folder already exists".into(),
                 text: format!("Project folder root:\n\n  {path}"),
                 level: Level::Error,
+                code: self.code(),
                 hint: None,
                 location: None,
             }],
@@ -947,6 +1071,7 @@ If you want to overwrite these files, delete them and run the command again.
                         .join("\n")
                 ),
                 level: Level::Error,
+                code: self.code(),
                 hint: None,
                 location: None,
             }],
@@ -965,6 +1090,7 @@ be removed.
                         .join("\n")
                 ),
                 level: Level::Error,
+                code: self.code(),
                 hint: None,
                 location: None,
             }],
@@ -984,6 +1110,7 @@ Please remove them and try again.
                         .join("\n")
                 ),
                 level: Level::Error,
+                code: self.code(),
                 hint: None,
                 location: None,
             }],
@@ -1003,6 +1130,7 @@ Please remove them and try again.
                         .join("\n")
                 ),
                 level: Level::Error,
+                code: self.code(),
                 hint: None,
                 location: None,
             }],
@@ -1019,6 +1147,7 @@ code to run on an earlier version like v{wrongfully_allowed_version}, \
 resulting in compilation errors!"
                 )),
                 level: Level::Error,
+                code: self.code(),
                 hint: Some(format!(
                     "Remove the version constraint from your `gleam.toml` or update it to be:
 
@@ -1042,6 +1171,7 @@ Please make sure internal types do not appear in public functions and try again.
                         .join("\n")
                 ),
                 level: Level::Error,
+                code: self.code(),
                 hint: None,
                 location: None,
             }],
@@ -1057,6 +1187,7 @@ We searched in {path} and all parent directories."
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1071,6 +1202,7 @@ your app.src file \"{app_ver}\"."
                     hint: None,
                     text,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1158,6 +1290,7 @@ https://git-scm.com/book/en/v2/Getting-Started-Installing-Git",
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1173,6 +1306,7 @@ https://git-scm.com/book/en/v2/Getting-Started-Installing-Git",
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1195,6 +1329,7 @@ The error from the shell command library was:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1215,6 +1350,7 @@ The error from the shell command was:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1232,6 +1368,7 @@ This was error from the gzip library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1250,6 +1387,7 @@ This was error from the tar library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1267,6 +1405,7 @@ This was error from the tar library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1284,6 +1423,7 @@ This was error from the tar library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1301,6 +1441,7 @@ This was error from the Hex client library:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1322,6 +1463,7 @@ Second: {second}"
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1351,6 +1493,7 @@ adding an `_ffi` suffix to the native file's name, and trying again."
                             .into(),
                     ),
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1360,6 +1503,7 @@ adding an `_ffi` suffix to the native file's name, and trying again."
                 text: format!("The file `{file}` is defined multiple times."),
                 hint: None,
                 level: Level::Error,
+                code: self.code(),
                 location: None,
             }],
 
@@ -1383,6 +1527,7 @@ Erlang modules must have unique names regardless of the subfolders where their
                     text,
                     hint: Some("Rename one of the native Erlang modules and try again.".into()),
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1422,6 +1567,7 @@ https://learn.microsoft.com/en-us/windows/apps/get-started/enable-your-device-fo
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1439,6 +1585,7 @@ The error from the encryption library was:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1455,6 +1602,7 @@ The error from the encryption library was:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1468,6 +1616,7 @@ The error from the encryption library was:
                     title: "Non UTF-8 Path Encountered".into(),
                     text,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                     hint: None,
                 }]
@@ -1484,6 +1633,7 @@ The error from the encryption library was:
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -1505,6 +1655,7 @@ Erlang's floating point type. To avoid this error float values must be in the ra
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -1536,6 +1687,7 @@ module to the src directory.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Imported here".into()),
@@ -1568,6 +1720,7 @@ module to the src directory.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Imported here".into()),
@@ -1599,6 +1752,7 @@ code. Perhaps move the `{imported_module}` module to the dev directory.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Imported here".into()),
@@ -1663,6 +1817,7 @@ constructor accepts."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label,
                                 path: path.clone(),
@@ -1682,6 +1837,7 @@ not expect any. Please remove the label `{label}`."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -1707,6 +1863,7 @@ also be labelled.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -1733,6 +1890,7 @@ Names in a Gleam module must be unique so one will need to be renamed."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Reimported here".into()),
@@ -1772,6 +1930,7 @@ Names in a Gleam module must be unique so one will need to be renamed."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Redefined here".into()),
@@ -1805,6 +1964,7 @@ Names in a Gleam module must be unique so one will need to be renamed."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Redefined here".into()),
@@ -1832,6 +1992,7 @@ Names in a Gleam module must be unique so one will need to be renamed."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -1852,6 +2013,7 @@ Names in a Gleam module must be unique so one will need to be renamed."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -1874,6 +2036,7 @@ value has. It seems to be defined in terms of itself.",
                             text,
                             hint: Some("Add some type annotations and try again.".into()),
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -1897,6 +2060,7 @@ value has. It seems to be defined in terms of itself.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -1991,6 +2155,7 @@ to call a method on this value you may want to use the function syntax instead."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(label),
@@ -2032,6 +2197,7 @@ But this argument has this type:
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -2081,6 +2247,7 @@ But function expects:
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(
@@ -2139,6 +2306,7 @@ But function expects:
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: main_message_text,
@@ -2175,6 +2343,7 @@ but {given} where provided."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(format!("Expected {expected}, got {given_number}")),
@@ -2222,6 +2391,7 @@ but {given} where provided."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(label),
@@ -2244,6 +2414,7 @@ assigned variables to all of them."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -2273,6 +2444,7 @@ constructing a new record with its values."
                                 text,
                                 hint: None,
                                 level: Level::Error,
+                                code: Some(error.code()),
                                 location: Some(Location {
                                     label: Label {
                                         text: Some(format!(
@@ -2303,6 +2475,7 @@ specify all fields explicitly instead of using the record update syntax."
                                 text,
                                 hint: None,
                                 level: Level::Error,
+                                code: Some(error.code()),
                                 location: Some(Location {
                                     label: Label {
                                         text: Some(format!("This is a `{spread_variant}`")),
@@ -2339,6 +2512,7 @@ need to be updated at the same time if their type changes."
                                 text,
                                 hint: None,
                                 level: Level::Error,
+                                code: Some(error.code()),
                                 location: Some(Location {
                                     label: Label {
                                         text: Some(format!("This is a `{record_variant}`")),
@@ -2383,6 +2557,7 @@ but no type in scope with that name."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: label_text,
@@ -2427,6 +2602,7 @@ but no type in scope with that name."
                                     "Change `_{name}` to `{name}` or reference another variable",
                                 )),
                                 level: Level::Error,
+                                code: Some(error.code()),
                                 location: Some(location),
                             }
                         } else {
@@ -2451,6 +2627,7 @@ but no type in scope with that name."
                                 text,
                                 hint: None,
                                 level: Level::Error,
+                                code: Some(error.code()),
                                 location: Some(Location {
                                     label: Label {
                                         text: did_you_mean(name, variables),
@@ -2486,6 +2663,7 @@ Private types can only be used within the module that defines them.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -2509,6 +2687,7 @@ Private types can only be used within the module that defines them.",
                             .first()
                             .map(|suggestion| suggestion.suggestion(name)),
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2537,6 +2716,7 @@ Private types can only be used within the module that defines them.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: if *imported_type_as_value {
@@ -2581,6 +2761,7 @@ it cannot be used as a value"
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: if *imported_value_as_type
@@ -2611,6 +2792,7 @@ to variables, pass them to functions, or anything else that you would do with a
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -2638,6 +2820,7 @@ Each clause must have a pattern for every subject value.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(format!(
@@ -2663,6 +2846,7 @@ function, or be an argument to the function. The variable \
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Is not locally defined".into()),
@@ -2685,6 +2869,7 @@ the initial pattern. This variable `{name}` has not been previously defined.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Has not been previously defined".into()),
@@ -2707,6 +2892,7 @@ as the initial pattern, but the `{name}` variable is missing.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(
@@ -2734,6 +2920,7 @@ e.g. (x, y) if x == y -> ...",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("This has already been used".into()),
@@ -2753,6 +2940,7 @@ e.g. (x, y) if x == y -> ...",
                         text: "This tuple has no elements so it cannot be indexed at all.".into(),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -2781,6 +2969,7 @@ tuple has {} elements so the highest valid index is {}.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("This index is too large".into()),
@@ -2807,6 +2996,7 @@ however it has this type:
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("This is not a tuple".into()),
@@ -2830,6 +3020,7 @@ Please add some type annotations so we can continue.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("What type is this?".into()),
@@ -2854,6 +3045,7 @@ function and try again.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("I don't know what type this is".into()),
@@ -2866,8 +3058,11 @@ function and try again.",
                         }
                     }
 
-                    TypeError::BitArraySegmentError { error, location } => {
-                        let (label, mut extra) = match error {
+                    TypeError::BitArraySegmentError {
+                        error: segment_error,
+                        location,
+                    } => {
+                        let (label, mut extra) = match segment_error {
                             bit_array::ErrorType::ConflictingTypeOptions { existing_type } => (
                                 "This is an extra type specifier",
                                 vec![format!(
@@ -2990,6 +3185,7 @@ UTF-codepoint pattern matching."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(label.into()),
@@ -3006,6 +3202,7 @@ UTF-codepoint pattern matching."
                         text: "Only record constructors can be used with the update syntax.".into(),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: Some("This is not a record constructor".into()),
@@ -3023,6 +3220,7 @@ UTF-codepoint pattern matching."
                             .into(),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: Some("I need to know what this is".into()),
@@ -3045,6 +3243,7 @@ Try a different name for this module."
                             hint: None,
                             location: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                         }
                     }
 
@@ -3060,6 +3259,7 @@ Try a different name for this module."
                             hint: None,
                             location: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                         }
                     }
 
@@ -3093,6 +3293,7 @@ value, or use `let assert` rather than `let`."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3112,6 +3313,7 @@ value, or use `let assert` rather than `let`."
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -3130,6 +3332,7 @@ value, or use `let assert` rather than `let`."
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -3153,6 +3356,7 @@ forever in a loop, and we'd never get the final type.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3181,6 +3385,7 @@ so we can tell what type of values they accept and return.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3203,6 +3408,7 @@ using the `@external` attribute."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3229,6 +3435,7 @@ implementation but the module path `{module}` is not valid."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3255,6 +3462,7 @@ implementation but the function name `{function}` is not valid."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3288,6 +3496,7 @@ The missing patterns are:\n",
                                 "Use a more general pattern or use `let assert` instead.".into(),
                             ),
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 src: src.clone(),
                                 path: path.to_path_buf(),
@@ -3317,6 +3526,7 @@ The missing patterns are:\n",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 src: src.clone(),
                                 path: path.to_path_buf(),
@@ -3336,6 +3546,7 @@ The missing patterns are:\n",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 src: src.clone(),
                                 path: path.to_path_buf(),
@@ -3366,6 +3577,7 @@ and there is no implementation for the {} target.\n",
                             text,
                             hint: Some(hint),
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 path: path.clone(),
                                 src: src.clone(),
@@ -3397,6 +3609,7 @@ must be able to compile for a module to be valid."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 path: path.clone(),
                                 src: src.clone(),
@@ -3418,6 +3631,7 @@ must be able to compile for a module to be valid."
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 path: path.clone(),
                                 src: src.clone(),
@@ -3440,6 +3654,7 @@ Rename or remove one of them.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 path: path.clone(),
                                 src: src.clone(),
@@ -3469,6 +3684,7 @@ See: https://tour.gleam.run/advanced-features/use/",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3502,6 +3718,7 @@ See: https://tour.gleam.run/advanced-features/use/",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some("Expected no arguments, got 1".into()),
@@ -3562,6 +3779,7 @@ so it cannot take the `use` callback function as a final argument.\n",
                             text: wrap(&text),
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(label),
@@ -3594,6 +3812,7 @@ See: https://tour.gleam.run/advanced-features/use/",
                             text: wrap(&text),
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3635,6 +3854,7 @@ See: https://tour.gleam.run/advanced-features/use/"
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3697,6 +3917,7 @@ Try: _{}",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: Some(label),
@@ -3725,6 +3946,7 @@ Try: _{}",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3748,6 +3970,7 @@ Consider removing the deprecation attribute on the variant.",
                             text,
                             hint: None,
                             level: Level::Error,
+                            code: Some(error.code()),
                             location: Some(Location {
                                 label: Label {
                                     text: None,
@@ -3765,6 +3988,7 @@ Consider removing the deprecation attribute on the variant.",
                         text: wrap("The `echo` keyword should be followed by a value to print."),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: Some("I was expecting a value after this".into()),
@@ -3784,6 +4008,7 @@ To join two strings together you can use the <> operator.",
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: Some("Use <> instead".into()),
@@ -3803,6 +4028,7 @@ To join two strings together you can use the <> operator.",
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: operator
@@ -3824,6 +4050,7 @@ To join two strings together you can use the <> operator.",
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: operator
@@ -3845,6 +4072,7 @@ at once, which is not possible in bit arrays.",
                         ),
                         hint: Some(wrap("Remove the `as` assignment.")),
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -3866,6 +4094,7 @@ and explain your usecase for this pattern, and how you would expect it to behave
                         ),
                         hint: None,
                         level: Level::Error,
+                        code: Some(error.code()),
                         location: Some(Location {
                             label: Label {
                                 text: None,
@@ -3900,6 +4129,7 @@ and explain your usecase for this pattern, and how you would expect it to behave
                     title,
                     text,
                     level: Level::Error,
+                    code: Some(error.error.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.clone(),
@@ -3940,6 +4170,7 @@ cycle to continue.",
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: Some(Location {
                         label: Label {
                             text: Some("Imported here".into()),
@@ -3966,6 +4197,7 @@ cycle to continue.",
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -3987,6 +4219,7 @@ but it cannot be found."
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: Some(Location {
                         label: Label {
                             text: did_you_mean(import, modules),
@@ -4019,6 +4252,7 @@ but it cannot be found."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4037,6 +4271,7 @@ but it cannot be found."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4054,6 +4289,26 @@ Fix the warnings and try again."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
+                }]
+            }
+
+            Error::DeniedWarnings { count } => {
+                let word_warning = match count {
+                    1 => "warning",
+                    _ => "warnings",
+                };
+                let text = wrap(
+                    "The `[warnings]` table of your gleam.toml sets these warnings \
+to `deny`. Fix the warnings and try again.",
+                );
+                vec![Diagnostic {
+                    title: format!("{count} denied {word_warning} generated."),
+                    text,
+                    hint: None,
+                    location: None,
+                    level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4074,6 +4329,7 @@ The error from the package manager client was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4090,6 +4346,7 @@ The error from the HTTP client was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4106,6 +4363,7 @@ The error from the parser was:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4122,6 +4380,7 @@ manifest.toml and a version range specified in gleam.toml:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4134,6 +4393,7 @@ manifest.toml and a version range specified in gleam.toml:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4143,6 +4403,7 @@ manifest.toml and a version range specified in gleam.toml:
                 hint: None,
                 location: None,
                 level: Level::Error,
+                code: self.code(),
             }],
 
             Error::WrongDependencyProvided {
@@ -4160,6 +4421,7 @@ manifest.toml and a version range specified in gleam.toml:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4178,6 +4440,7 @@ manifest.toml and a version range specified in gleam.toml:
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4192,6 +4455,7 @@ dev-dependencies sections of the gleam.toml file."
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4223,6 +4487,7 @@ licences = ["Apache-2.0"]"#
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4235,6 +4500,7 @@ because dependency `{package}` is not a Hex dependency.",
                 hint: None,
                 location: None,
                 level: Level::Error,
+                code: self.code(),
             }],
 
             Error::UnsupportedBuildTool {
@@ -4256,6 +4522,7 @@ issue in our tracker: https://github.com/gleam-lang/gleam/issues",
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4272,6 +4539,7 @@ issue in our tracker: https://github.com/gleam-lang/gleam/issues",
                     text,
                     hint: None,
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                 }]
             }
@@ -4291,6 +4559,7 @@ satisfying {required_version} but you are using v{gleam_version}.",
                     hint: None,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
@@ -4316,14 +4585,40 @@ satisfying {required_version} but you are using v{gleam_version}.",
                     hint,
                     location: None,
                     level: Level::Error,
+                    code: self.code(),
                 }]
             }
 
+            Error::CoverageUnsupportedRuntime { runtime } => vec![Diagnostic {
+                title: "Coverage not supported".into(),
+                text: wrap(&format!(
+                    "Code coverage cannot be measured when running the tests \
+with {runtime}. It is measured with Node.js and Deno on the JavaScript target."
+                )),
+                hint: Some("Run the tests with `--runtime node` or `--runtime deno`.".into()),
+                location: None,
+                level: Level::Error,
+                code: self.code(),
+            }],
+
+            Error::NoTestsMatchFilter => vec![Diagnostic {
+                title: "No tests to run".into(),
+                text: wrap(
+                    "None of the tests of the project match the `--filter`, \
+`--name`, and `--module` options given.",
+                ),
+                hint: Some("Check the spelling of the test or module names.".into()),
+                location: None,
+                level: Level::Error,
+                code: self.code(),
+            }],
+
             Error::JavaScriptPreludeRequired => vec![Diagnostic {
                 title: "JavaScript prelude required".into(),
                 text: "The --javascript-prelude flag must be given when compiling to JavaScript."
                     .into(),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: None,
             }],
@@ -4331,6 +4626,7 @@ satisfying {required_version} but you are using v{gleam_version}.",
                 title: "Corrupt manifest.toml".into(),
                 text: "The `manifest.toml` file is corrupt.".into(),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: Some("Please run `gleam update` to fix it.".into()),
             }],
@@ -4348,6 +4644,7 @@ causing confusing errors and crashes.
 "
                     ),
                     level: Level::Error,
+                    code: self.code(),
                     location: None,
                     hint: Some("Rename this module and try again.".into()),
                 }]
@@ -4361,6 +4658,7 @@ This release has been recently published so you can replace it \
 or you can publish it using a different version number"
                 ),
                 level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: Some(
                     "Please add the --replace flag if you want to replace the release.".into(),
@@ -4374,6 +4672,83 @@ or you can publish it using a different version number"
 add `gleam add {name}` in this project."
                 ),
                 level: Level::Error,
+                code: self.code(),
+                location: None,
+                hint: None,
+            }],
+
+            Error::UnknownDiagnosticCode { code } => vec![Diagnostic {
+                title: "Unknown error code".into(),
+                text: format!("`{code}` is not the code of any Gleam error or warning."),
+                level: Level::Error,
+                code: self.code(),
+                location: None,
+                hint: Some(
+                    "Codes are printed next to each error and warning, \
+for example `error[E0203]`."
+                        .into(),
+                ),
+            }],
+
+            Error::CannotPublishIncompatibleVersion {
+                previous,
+                version,
+                minimum,
+                changes,
+            } => vec![Diagnostic {
+                title: "Version too low for the API changes".into(),
+                text: wrap_format!(
+                    "The public API has {changes} changes since v{previous}, \
+so following semantic versioning the package cannot be published as v{version}. \
+The lowest version it can be published as is v{minimum}."
+                ),
+                level: Level::Error,
+                code: self.code(),
+                location: None,
+                hint: Some(
+                    "Update the version in `gleam.toml`, or add the --yes flag \
+to publish it anyway."
+                        .into(),
+                ),
+            }],
+
+            Error::PackageInterfaceUnavailable { from, reason } => vec![Diagnostic {
+                title: "Package interface unavailable".into(),
+                text: wrap_format!(
+                    "The public API of {from} cannot be compared with the current one. {reason}"
+                ),
+                level: Level::Error,
+                code: self.code(),
+                location: None,
+                hint: None,
+            }],
+
+            Error::NotAvailableInWorkspaceRoot { path, members } => vec![Diagnostic {
+                title: "Not a package".into(),
+                text: wrap_format!(
+                    "The directory {path} is the root of a workspace rather than a package, \
+so this command has to be run in the directory of one of its members."
+                ),
+                level: Level::Error,
+                code: self.code(),
+                location: None,
+                hint: (!members.is_empty()).then(|| {
+                    format!(
+                        "The members of this workspace are: {}",
+                        members.iter().join(", ")
+                    )
+                }),
+            }],
+
+            Error::CannotRenameModuleInWorkspace { workspace } => vec![Diagnostic {
+                title: "Cannot rename module".into(),
+                text: wrap_format!(
+                    "This package is a member of the workspace at {workspace}. \
+The other members may import its modules, and their imports cannot be updated \
+yet, so its modules have to be renamed by hand."
+                ),
+                level: Level::Error,
+                code: self.code(),
                 location: None,
                 hint: None,
             }],
@@ -35,3 +35,11 @@ fn test_shell_program_not_found_error() {
         }
     }
 }
+
+#[test]
+fn unknown_diagnostic_code_error() {
+    let error = Error::UnknownDiagnosticCode {
+        code: "E9999".into(),
+    };
+    assert_snapshot!(error.pretty_string());
+}
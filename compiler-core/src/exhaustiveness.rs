@@ -70,7 +70,7 @@ use crate::{
     strings::{convert_string_escape_chars, length_utf16, length_utf32},
     type_::{
         Environment, Opaque, Type, TypeValueConstructor, TypeValueConstructorField, TypeVar,
-        TypeVariantConstructors, collapse_links, error::UnreachablePatternReason,
+        TypeVarCell, TypeVariantConstructors, collapse_links, error::UnreachablePatternReason,
         is_prelude_module, string,
     },
 };
@@ -80,7 +80,6 @@ use itertools::Itertools;
 use num_bigint::BigInt;
 use radix_trie::{Trie, TrieCommon};
 use std::{
-    cell::RefCell,
     collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
     sync::Arc,
@@ -2664,7 +2663,7 @@ impl ConstructorSpecialiser {
             },
 
             Type::Var { type_ } => Type::Var {
-                type_: Arc::new(RefCell::new(self.specialise_var(type_))),
+                type_: Arc::new(TypeVarCell::new(self.specialise_var(type_))),
             },
 
             Type::Tuple { elements } => Type::Tuple {
@@ -2676,7 +2675,7 @@ impl ConstructorSpecialiser {
         })
     }
 
-    fn specialise_var(&self, type_: &RefCell<TypeVar>) -> TypeVar {
+    fn specialise_var(&self, type_: &TypeVarCell) -> TypeVar {
         match &*type_.borrow() {
             TypeVar::Unbound { id } => TypeVar::Unbound { id: *id },
 
@@ -10,10 +10,9 @@ use crate::{
     build::Target,
     docvec,
     io::Utf8Writer,
-    parse::SpannedString,
     parse::extra::{Comment, ModuleExtra},
     pretty::{self, *},
-    warning::WarningEmitter,
+    warning::{WarningEmitter, WarningKind},
 };
 use ecow::{EcoString, eco_format};
 use itertools::Itertools;
@@ -26,18 +25,128 @@ use camino::Utf8Path;
 const INDENT: isize = 2;
 
 pub fn pretty(writer: &mut impl Utf8Writer, src: &EcoString, path: &Utf8Path) -> Result<()> {
-    let parsed = crate::parse::parse_module(path.to_owned(), src, &WarningEmitter::null())
-        .map_err(|error| Error::Parse {
-            path: path.to_path_buf(),
-            src: src.clone(),
-            error: Box::new(error),
-        })?;
+    let parsed = parse(src, path)?;
     let intermediate = Intermediate::from_extra(&parsed.extra, src);
     Formatter::with_comments(&intermediate)
         .module(&parsed.module)
         .pretty_print(80, writer)
 }
 
+/// The formatted code of a top level definition, or of a statement in the body
+/// of a function, replacing the code at `location`.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedRange {
+    pub location: SrcSpan,
+    pub code: String,
+}
+
+/// Formats only the top level definitions intersecting the given byte range,
+/// leaving the rest of the module as it is. If the range is inside the body of
+/// a function then only the statements it intersects are formatted.
+///
+/// Definitions and statements that are already formatted are not returned.
+///
+pub fn pretty_range(
+    src: &EcoString,
+    path: &Utf8Path,
+    range: SrcSpan,
+) -> Result<Vec<FormattedRange>> {
+    let parsed = parse(src, path)?;
+    let intermediate = Intermediate::from_extra(&parsed.extra, src);
+    let mut formatter = Formatter::with_comments(&intermediate);
+    let intersects = |start: u32, end: u32| start <= range.end && range.start <= end;
+
+    let mut formatted = vec![];
+    let mut previous_end = 0;
+    for definition in parsed
+        .module
+        .definitions
+        .iter()
+        .sorted_by_key(|definition| definition.definition.location().start)
+    {
+        let start = definition.definition.location().start;
+        let end = definition_end(&definition.definition);
+        // Module comments are always printed at the top of the module, so
+        // they're left where they are rather than going with the definition.
+        let code_start = parsed
+            .extra
+            .module_comments
+            .iter()
+            .filter(|comment| comment.start < start)
+            .map(|comment| comment.end)
+            .fold(previous_end, u32::max);
+        previous_end = end;
+        if !intersects(start, end) {
+            continue;
+        }
+
+        let body = match &definition.definition {
+            Definition::Function(function) if !function.body.first().is_placeholder() => {
+                Some(&function.body)
+            }
+            _ => None,
+        };
+        let in_body = body.filter(|body| {
+            body.first().location().start <= range.start && range.end <= body.last().location().end
+        });
+        let Some(statements) = in_body else {
+            formatted.extend(formatter.range(src, code_start, end, 0, |formatter| {
+                formatter.targeted_definition(definition)
+            }));
+            continue;
+        };
+
+        // The statements are preceded by the function's opening brace.
+        let Some(mut previous_end) = src
+            .get(start as usize..)
+            .and_then(|code| code.find('{'))
+            .map(|brace| start + brace as u32 + 1)
+        else {
+            continue;
+        };
+        for statement in statements {
+            let location = statement.location();
+            let code_start = previous_end;
+            previous_end = location.end;
+            if intersects(location.start, location.end) {
+                formatted.extend(formatter.range(
+                    src,
+                    code_start,
+                    location.end,
+                    INDENT,
+                    |formatter| formatter.statement(statement).group(),
+                ));
+            }
+        }
+    }
+
+    Ok(formatted)
+}
+
+fn parse(src: &EcoString, path: &Utf8Path) -> Result<crate::parse::Parsed> {
+    crate::parse::parse_module(path.to_owned(), src, &WarningEmitter::null()).map_err(|error| {
+        Error::Parse {
+            path: path.to_path_buf(),
+            src: src.clone(),
+            error: Box::new(error),
+        }
+    })
+}
+
+/// The end of a definition, including the body of functions and the
+/// constructors of custom types.
+///
+fn definition_end(definition: &UntypedDefinition) -> u32 {
+    match definition {
+        Definition::Function(function) => function.end_position,
+        Definition::CustomType(custom_type) => custom_type.end_position,
+        Definition::ModuleConstant(constant) => constant.value.location().end,
+        Definition::TypeAlias(TypeAlias { location, .. })
+        | Definition::Import(Import { location, .. }) => location.end,
+    }
+}
+
 pub(crate) struct Intermediate<'a> {
     comments: Vec<Comment<'a>>,
     doc_comments: Vec<Comment<'a>>,
@@ -188,6 +297,56 @@ impl<'comments> Formatter<'comments> {
         end != 0
     }
 
+    /// Formats the code of a single definition or statement ending at `end`,
+    /// along with the comments preceding it since `previous_end`.
+    ///
+    /// Nothing is returned if the code is already formatted, or if some of its
+    /// comments wouldn't be printed.
+    ///
+    fn range<'a>(
+        &mut self,
+        src: &str,
+        previous_end: u32,
+        end: u32,
+        indent: isize,
+        document: impl FnOnce(&mut Self) -> Document<'a>,
+    ) -> Option<FormattedRange> {
+        // The empty lines before the code are left as they are, but its
+        // indentation is replaced if it starts on its own line.
+        let gap = src.get(previous_end as usize..end as usize)?;
+        let whitespace = gap.len() - gap.trim_start().len();
+        let (start, indented) = match gap.get(..whitespace)?.rfind('\n') {
+            Some(newline) => (previous_end + newline as u32 + 1, true),
+            None => (previous_end + whitespace as u32, false),
+        };
+        let _ = self.pop_comments(start);
+        let _ = self.pop_doc_comments(start);
+        let _ = self.pop_empty_lines(start);
+
+        let mut code = String::new();
+        line()
+            .append(document(self))
+            .nest(indent)
+            .pretty_print(80, &mut code)
+            .ok()?;
+        let code = code.strip_prefix('\n')?;
+        let code = if indented { code } else { code.trim_start() };
+
+        let location = SrcSpan::new(start, end);
+        let unprinted_comments = self.any_comments(end)
+            || self
+                .doc_comments
+                .first()
+                .is_some_and(|comment| comment.start < end);
+        if unprinted_comments || src.get(location.start as usize..location.end as usize)? == code {
+            return None;
+        }
+        Some(FormattedRange {
+            location,
+            code: code.into(),
+        })
+    }
+
     fn targeted_definition<'a>(&mut self, definition: &'a TargetedDefinition) -> Document<'a> {
         let target = definition.target;
         let definition = &definition.definition;
@@ -365,22 +524,7 @@ impl<'comments> Formatter<'comments> {
         match statement {
             Definition::Function(function) => self.statement_fn(function),
 
-            Definition::TypeAlias(TypeAlias {
-                alias,
-                parameters: arguments,
-                type_ast: resolved_type,
-                publicity,
-                deprecation,
-                location,
-                ..
-            }) => self.type_alias(
-                *publicity,
-                alias,
-                arguments,
-                resolved_type,
-                deprecation,
-                location,
-            ),
+            Definition::TypeAlias(alias) => self.type_alias(alias),
 
             Definition::CustomType(ct) => self.custom_type(ct),
 
@@ -440,9 +584,13 @@ impl<'comments> Formatter<'comments> {
                 name,
                 annotation,
                 value,
+                allowed_warnings,
                 ..
             }) => {
-                let attributes = AttributesPrinter::new().set_internal(*publicity).to_doc();
+                let attributes = AttributesPrinter::new()
+                    .set_allowed_warnings(allowed_warnings)
+                    .set_internal(*publicity)
+                    .to_doc();
                 let head = attributes
                     .append(pub_(*publicity))
                     .append("const ")
@@ -774,30 +922,31 @@ impl<'comments> Formatter<'comments> {
         self.wrap_arguments(arguments, location.end)
     }
 
-    pub fn type_alias<'a>(
-        &mut self,
-        publicity: Publicity,
-        name: &'a str,
-        arguments: &'a [SpannedString],
-        type_: &'a TypeAst,
-        deprecation: &'a Deprecation,
-        location: &SrcSpan,
-    ) -> Document<'a> {
+    pub fn type_alias<'a>(&mut self, alias: &'a UntypedTypeAlias) -> Document<'a> {
         let attributes = AttributesPrinter::new()
-            .set_deprecation(deprecation)
-            .set_internal(publicity)
+            .set_deprecation(&alias.deprecation)
+            .set_allowed_warnings(&alias.allowed_warnings)
+            .set_internal(alias.publicity)
             .to_doc();
 
-        let head = docvec![attributes, pub_(publicity), "type ", name];
-        let head = if arguments.is_empty() {
+        let head = docvec![attributes, pub_(alias.publicity), "type ", &alias.alias];
+        let head = if alias.parameters.is_empty() {
             head
         } else {
-            let arguments = arguments.iter().map(|(_, e)| e.to_doc()).collect_vec();
-            head.append(self.wrap_arguments(arguments, location.end).group())
+            let arguments = alias
+                .parameters
+                .iter()
+                .map(|(_, e)| e.to_doc())
+                .collect_vec();
+            head.append(self.wrap_arguments(arguments, alias.location.end).group())
         };
 
-        head.append(" =")
-            .append(line().append(self.type_ast(type_)).group().nest(INDENT))
+        head.append(" =").append(
+            line()
+                .append(self.type_ast(&alias.type_ast))
+                .group()
+                .nest(INDENT),
+        )
     }
 
     fn fn_arg<'a, A>(&mut self, arg: &'a Arg<A>) -> Document<'a> {
@@ -813,6 +962,7 @@ impl<'comments> Formatter<'comments> {
     fn statement_fn<'a>(&mut self, function: &'a UntypedFunction) -> Document<'a> {
         let attributes = AttributesPrinter::new()
             .set_deprecation(&function.deprecation)
+            .set_allowed_warnings(&function.allowed_warnings)
             .set_internal(function.publicity)
             .set_external_erlang(&function.external_erlang)
             .set_external_javascript(&function.external_javascript)
@@ -1718,6 +1868,7 @@ impl<'comments> Formatter<'comments> {
 
         let attributes = AttributesPrinter::new()
             .set_deprecation(&ct.deprecation)
+            .set_allowed_warnings(&ct.allowed_warnings)
             .set_internal(ct.publicity)
             .to_doc();
 
@@ -3377,6 +3528,7 @@ struct AttributesPrinter<'a> {
     external_erlang: &'a Option<(EcoString, EcoString, SrcSpan)>,
     external_javascript: &'a Option<(EcoString, EcoString, SrcSpan)>,
     deprecation: &'a Deprecation,
+    allowed_warnings: &'a [WarningKind],
     internal: bool,
 }
 
@@ -3386,6 +3538,7 @@ impl<'a> AttributesPrinter<'a> {
             external_erlang: &None,
             external_javascript: &None,
             deprecation: &Deprecation::NotDeprecated,
+            allowed_warnings: &[],
             internal: false,
         }
     }
@@ -3415,6 +3568,11 @@ impl<'a> AttributesPrinter<'a> {
         self.deprecation = deprecation;
         self
     }
+
+    pub fn set_allowed_warnings(mut self, allowed_warnings: &'a [WarningKind]) -> Self {
+        self.allowed_warnings = allowed_warnings;
+        self
+    }
 }
 
 impl<'a> Documentable<'a> for AttributesPrinter<'a> {
@@ -3426,6 +3584,15 @@ impl<'a> Documentable<'a> for AttributesPrinter<'a> {
             attributes.push(docvec!["@deprecated(\"", message, "\")"])
         };
 
+        // @allow attribute
+        if !self.allowed_warnings.is_empty() {
+            let kinds = self
+                .allowed_warnings
+                .iter()
+                .map(|kind| <&'static str>::from(kind).to_doc());
+            attributes.push(docvec!["@allow(", join(kinds, ", ".to_doc()), ")"])
+        };
+
         // @external attributes
         if let Some((m, f, _)) = self.external_erlang {
             attributes.push(docvec!["@external(erlang, \"", m, "\", \"", f, "\")"])
@@ -15,6 +15,7 @@ mod guards;
 mod imports;
 mod lists;
 mod pipeline;
+mod range;
 mod record_update;
 mod tuple;
 mod use_;
@@ -5187,6 +5188,25 @@ pub type Tiger =
     );
 }
 
+#[test]
+fn allow_type_alias() {
+    assert_format!(
+        r#"@allow(unused_type)
+type Tiger =
+  Nil
+"#
+    );
+}
+
+#[test]
+fn allow_constant() {
+    assert_format!(
+        r#"@allow(unused_private_module_constant)
+const tiger = Nil
+"#
+    );
+}
+
 // https://github.com/gleam-lang/gleam/issues/2423
 #[test]
 fn prefix_as() {
@@ -122,6 +122,17 @@ pub type One {
     );
 }
 
+#[test]
+fn allow_custom_type() {
+    assert_format!(
+        r#"@allow(unused_type)
+type One {
+  One
+}
+"#
+    );
+}
+
 #[test]
 fn doc_comments_7_test() {
     assert_format!(
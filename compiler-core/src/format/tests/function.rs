@@ -35,6 +35,30 @@ pub fn main() -> Nil
     );
 }
 
+#[test]
+fn allow() {
+    assert_format!(
+        r#"@allow(unused_variable, todo)
+pub fn main() -> Nil {
+  todo
+}
+"#
+    );
+}
+
+#[test]
+fn deprecated_allow_internal() {
+    assert_format!(
+        r#"@deprecated("use something else instead")
+@allow(deprecated_item)
+@internal
+pub fn main() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
 #[test]
 fn anonymous_function_as_final_function_argument() {
     assert_format!(
@@ -0,0 +1,164 @@
+use crate::{ast::SrcSpan, format::pretty_range};
+use itertools::Itertools;
+use pretty_assertions::assert_eq;
+
+/// Formats the code intersecting the range between the start of `from` and
+/// the end of `to`, returning the whole updated module.
+///
+fn format_range(src: &str, from: &str, to: &str) -> String {
+    let start = src.find(from).expect("range start") as u32;
+    let end = (src.find(to).expect("range end") + to.len()) as u32;
+    let edits = pretty_range(
+        &src.into(),
+        camino::Utf8Path::new("<stdin>"),
+        SrcSpan::new(start, end),
+    )
+    .expect("formatted range");
+
+    let mut code = src.to_string();
+    for edit in edits
+        .iter()
+        .sorted_by_key(|edit| std::cmp::Reverse(edit.location.start))
+    {
+        code.replace_range(
+            edit.location.start as usize..edit.location.end as usize,
+            &edit.code,
+        );
+    }
+    code
+}
+
+#[test]
+fn only_the_definitions_in_the_range_are_formatted() {
+    let src = "const   wibble =   1
+
+pub fn   wobble( ) {   wibble }
+
+type   Wubble { Wubble(  Int ) }
+";
+    assert_eq!(
+        format_range(src, "wobble(", "wobble("),
+        "const   wibble =   1
+
+pub fn wobble() {
+  wibble
+}
+
+type   Wubble { Wubble(  Int ) }
+"
+    );
+}
+
+#[test]
+fn all_the_definitions_intersecting_the_range_are_formatted() {
+    let src = "import   wibble
+
+const   wobble =   1
+
+pub fn   wubble( ) {   wobble }
+
+type   Wubble { Wubble(  Int ) }
+";
+    assert_eq!(
+        format_range(src, "wobble", "wubble"),
+        "import   wibble
+
+const wobble = 1
+
+pub fn wubble() {
+  wobble
+}
+
+type   Wubble { Wubble(  Int ) }
+"
+    );
+}
+
+#[test]
+fn comments_and_attributes_are_formatted_with_their_definition() {
+    let src = "//// The module
+
+fn   wibble() { 1 }
+
+// A comment
+   /// Some documentation
+@deprecated(   \"Use something else\")
+pub fn   wobble( ) {
+  // Inside
+     1
+}
+";
+    assert_eq!(
+        format_range(src, "wobble", "wobble"),
+        "//// The module
+
+fn   wibble() { 1 }
+
+// A comment
+/// Some documentation
+@deprecated(\"Use something else\")
+pub fn wobble() {
+  // Inside
+  1
+}
+"
+    );
+}
+
+#[test]
+fn only_the_statements_in_the_range_are_formatted_inside_a_function() {
+    let src = "pub fn main() {
+  let   wibble =   1
+      let wobble   = [wibble,   2]
+
+  // A comment
+  wobble   |> list.map(fn(x) {   x + 1 })
+}
+";
+    assert_eq!(
+        format_range(src, "wobble   =", "wobble   |>"),
+        "pub fn main() {
+  let   wibble =   1
+  let wobble = [wibble, 2]
+
+  // A comment
+  wobble |> list.map(fn(x) { x + 1 })
+}
+"
+    );
+}
+
+#[test]
+fn statements_spanning_multiple_lines_are_indented() {
+    let src = "pub fn main() {
+  let wibble = case   x { 1 ->   2  _ -> 3 }
+  wibble
+}
+";
+    assert_eq!(
+        format_range(src, "case", "case"),
+        "pub fn main() {
+  let wibble = case x {
+    1 -> 2
+    _ -> 3
+  }
+  wibble
+}
+"
+    );
+}
+
+#[test]
+fn formatted_definitions_are_left_as_they_are() {
+    let src = "pub fn main() {
+  1
+}
+";
+    let edits = pretty_range(
+        &src.into(),
+        camino::Utf8Path::new("<stdin>"),
+        SrcSpan::new(0, src.len() as u32),
+    )
+    .expect("formatted range");
+    assert_eq!(edits, vec![]);
+}
@@ -1,3 +1,5 @@
+use std::io::Read;
+
 use camino::Utf8Path;
 use debug_ignore::DebugIgnore;
 use flate2::read::GzDecoder;
@@ -306,3 +308,66 @@ pub async fn get_package_release<Http: HttpClient>(
     let response = http.send(request).await?;
     hexpm::get_package_release_response(response).map_err(Error::hex)
 }
+
+/// The versions of a package published to Hex, or none if it has never been
+/// published.
+pub async fn get_published_versions<Http: HttpClient>(
+    name: &str,
+    config: &hexpm::Config,
+    http: &Http,
+) -> Result<Vec<Version>> {
+    tracing::info!(name = name, "looking_up_published_versions");
+    let request = hexpm::get_package_request(name, None, config);
+    let response = http.send(request).await?;
+    match hexpm::get_package_response(response, HEXPM_PUBLIC_KEY) {
+        Ok(package) => Ok(package
+            .releases
+            .into_iter()
+            .map(|release| release.version)
+            .collect()),
+        Err(ApiError::NotFound) => Ok(vec![]),
+        Err(error) => Err(Error::hex(error)),
+    }
+}
+
+/// The `package-interface.json` file included in the HTML documentation of a
+/// release, or `None` if its documentation has not been published or was
+/// built by a version of Gleam that did not include it.
+pub async fn get_package_interface<Http: HttpClient>(
+    name: &str,
+    version: &Version,
+    config: &hexpm::Config,
+    http: &Http,
+) -> Result<Option<String>> {
+    tracing::info!(name = name, version = %version, "downloading_documentation");
+    let request = http::Request::get(format!(
+        "{}docs/{name}-{version}.tar.gz",
+        config.repository_base
+    ))
+    .body(vec![])
+    .map_err(Error::http)?;
+    let response = http.send(request).await?;
+    match response.status() {
+        http::StatusCode::OK => (),
+        http::StatusCode::FORBIDDEN | http::StatusCode::NOT_FOUND => return Ok(None),
+        status => {
+            return Err(Error::Http(format!(
+                "Unexpected response status {status} downloading the documentation"
+            )));
+        }
+    }
+
+    let mut archive = Archive::new(GzDecoder::new(response.body().as_slice()));
+    for entry in archive.entries().map_err(Error::expand_tar)? {
+        let mut file = entry.map_err(Error::expand_tar)?;
+        let path = file.header().path().map_err(Error::expand_tar)?;
+        if path.as_ref() == Utf8Path::new("package-interface.json") {
+            let mut interface = String::new();
+            let _ = file
+                .read_to_string(&mut interface)
+                .map_err(Error::expand_tar)?;
+            return Ok(Some(interface));
+        }
+    }
+    Ok(None)
+}
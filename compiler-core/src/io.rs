@@ -56,6 +56,12 @@ pub trait Utf8Writer: std::fmt::Write {
     }
 
     fn convert_err<E: std::error::Error>(&self, err: E) -> Error;
+
+    /// Called by the pretty printer when it reaches a source location: the
+    /// text written from now on was generated from the source code starting
+    /// at the given byte offset. Only writers building a source map need to
+    /// keep track of it.
+    fn source_location(&mut self, _start: u32) {}
 }
 
 impl Utf8Writer for String {
@@ -292,6 +298,38 @@ pub trait CommandExecutor {
     fn exec(&self, command: Command) -> Result<i32, Error>;
 }
 
+/// A trait used to run other programs in the background, reading what they
+/// print while they run.
+pub trait CommandSpawner {
+    /// Starts the command without waiting for it to finish. `on_output` is
+    /// called with each line the program prints, to either its standard output
+    /// or its standard error, and then once more with its exit code.
+    fn spawn(
+        &self,
+        command: BackgroundCommand,
+        on_output: Box<dyn FnMut(CommandOutput) + Send>,
+    ) -> Result<(), Error>;
+}
+
+/// A command one can start with a `CommandSpawner`. What it prints is always
+/// read and passed on, so unlike a `Command` it has no `stdio` setting.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BackgroundCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub cwd: Option<Utf8PathBuf>,
+}
+
+/// Something printed by a program started by a `CommandSpawner`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandOutput {
+    Line(String),
+    /// The program has finished, with the given exit code. There's no code
+    /// if it was stopped by a signal, or if it couldn't be waited on.
+    Exit(Option<i32>),
+}
+
 /// A command one can run with a `CommandExecutor`
 #[derive(Debug, Eq, PartialEq)]
 pub struct Command {
@@ -1,9 +1,7 @@
 use super::*;
-use std::ops::Deref;
 use std::{
-    cell::RefCell,
     collections::{HashMap, HashSet},
-    rc::Rc,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
     time::Duration,
 };
 
@@ -17,16 +15,25 @@ use camino::{Utf8Path, Utf8PathBuf};
 /// Useful in tests and in environments like the browser where there is no file
 /// system.
 ///
-/// Not thread safe. The compiler is single threaded, so that's OK.
+/// Thread safe, so that the compiler can share it between the threads it
+/// compiles modules and packages on.
 ///
 /// Only supports absolute paths. The root directory ("/") is always guaranteed
 /// to exist.
 ///
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 pub struct InMemoryFileSystem {
-    files: Rc<RefCell<HashMap<Utf8PathBuf, InMemoryFile>>>,
+    files: Arc<RwLock<HashMap<Utf8PathBuf, InMemoryFile>>>,
 }
 
+impl PartialEq for InMemoryFileSystem {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.files, &other.files) || *self.read_files() == *other.read_files()
+    }
+}
+
+impl Eq for InMemoryFileSystem {}
+
 impl Default for InMemoryFileSystem {
     fn default() -> Self {
         let mut files = HashMap::new();
@@ -35,7 +42,7 @@ impl Default for InMemoryFileSystem {
         let _ = files.insert(Utf8PathBuf::from("/"), InMemoryFile::directory());
 
         Self {
-            files: Rc::new(RefCell::new(files)),
+            files: Arc::new(RwLock::new(files)),
         }
     }
 }
@@ -45,8 +52,18 @@ impl InMemoryFileSystem {
         Self::default()
     }
 
+    fn read_files(&self) -> RwLockReadGuard<'_, HashMap<Utf8PathBuf, InMemoryFile>> {
+        self.files.read().expect("InMemoryFileSystem lock poisoned")
+    }
+
+    fn write_files(&self) -> RwLockWriteGuard<'_, HashMap<Utf8PathBuf, InMemoryFile>> {
+        self.files
+            .write()
+            .expect("InMemoryFileSystem lock poisoned")
+    }
+
     pub fn reset(&self) {
-        self.files.deref().borrow_mut().clear();
+        self.write_files().clear();
     }
 
     /// Returns the contents of each file, excluding directories.
@@ -56,9 +73,10 @@ impl InMemoryFileSystem {
     /// Panics if this is not the only reference to the underlying files.
     ///
     pub fn into_contents(self) -> HashMap<Utf8PathBuf, Content> {
-        Rc::try_unwrap(self.files)
+        Arc::try_unwrap(self.files)
             .expect("InMemoryFileSystem::into_files called on a clone")
             .into_inner()
+            .expect("InMemoryFileSystem lock poisoned")
             .into_iter()
             .filter_map(|(path, file)| file.into_content().map(|content| (path, content)))
             .collect()
@@ -66,8 +84,7 @@ impl InMemoryFileSystem {
 
     /// All files currently in the filesystem (directories are not included).
     pub fn files(&self) -> Vec<Utf8PathBuf> {
-        self.files
-            .borrow()
+        self.read_files()
             .iter()
             .filter(|(_, f)| !f.is_directory())
             .map(|(path, _)| path)
@@ -81,12 +98,7 @@ impl InMemoryFileSystem {
     /// Panics if the file does not exist.
     ///
     pub fn set_modification_time(&self, path: &Utf8Path, time: SystemTime) {
-        self.files
-            .deref()
-            .borrow_mut()
-            .get_mut(path)
-            .unwrap()
-            .modification_time = time;
+        self.write_files().get_mut(path).unwrap().modification_time = time;
     }
 
     pub fn try_set_modification_time(
@@ -94,9 +106,7 @@ impl InMemoryFileSystem {
         path: &Utf8Path,
         time: SystemTime,
     ) -> Result<(), Error> {
-        self.files
-            .deref()
-            .borrow_mut()
+        self.write_files()
             .get_mut(path)
             .ok_or_else(|| Error::FileIo {
                 kind: FileKind::File,
@@ -111,7 +121,7 @@ impl InMemoryFileSystem {
 
 impl FileSystemWriter for InMemoryFileSystem {
     fn delete_directory(&self, path: &Utf8Path) -> Result<(), Error> {
-        let mut files = self.files.deref().borrow_mut();
+        let mut files = self.write_files();
 
         if files.get(path).is_some_and(|f| !f.is_directory()) {
             return Err(Error::FileIo {
@@ -159,7 +169,7 @@ impl FileSystemWriter for InMemoryFileSystem {
             }
             // Ensure we don't overwrite an existing file.
             // We can ignore existing directories though.
-            let mut files = self.files.deref().borrow_mut();
+            let mut files = self.write_files();
             if files.get(ancestor).is_some_and(|f| !f.is_directory()) {
                 return Err(Error::FileIo {
                     kind: FileKind::Directory,
@@ -184,7 +194,7 @@ impl FileSystemWriter for InMemoryFileSystem {
     }
 
     fn delete_file(&self, path: &Utf8Path) -> Result<(), Error> {
-        let mut files = self.files.deref().borrow_mut();
+        let mut files = self.write_files();
         if files.get(path).is_some_and(|f| f.is_directory()) {
             return Err(Error::FileIo {
                 kind: FileKind::File,
@@ -209,16 +219,12 @@ impl FileSystemWriter for InMemoryFileSystem {
 
         let mut file = InMemoryFile::default();
         _ = io::Write::write(&mut file, content).expect("channel buffer write");
-        _ = self
-            .files
-            .deref()
-            .borrow_mut()
-            .insert(path.to_path_buf(), file);
+        _ = self.write_files().insert(path.to_path_buf(), file);
         Ok(())
     }
 
     fn exists(&self, path: &Utf8Path) -> bool {
-        self.files.deref().borrow().contains_key(path)
+        self.read_files().contains_key(path)
     }
 }
 
@@ -229,7 +235,7 @@ impl FileSystemReader for InMemoryFileSystem {
 
     fn read(&self, path: &Utf8Path) -> Result<String, Error> {
         let path = path.to_path_buf();
-        let files = self.files.deref().borrow();
+        let files = self.read_files();
         let buffer = files
             .get(&path)
             .and_then(|file| file.node.as_file_buffer())
@@ -239,7 +245,7 @@ impl FileSystemReader for InMemoryFileSystem {
                 path: path.clone(),
                 err: None,
             })?;
-        let bytes = buffer.borrow();
+        let bytes = buffer.read().expect("InMemoryFile lock poisoned");
         let unicode = String::from_utf8(bytes.clone()).map_err(|err| Error::FileIo {
             kind: FileKind::File,
             action: FileIoAction::Read,
@@ -251,7 +257,7 @@ impl FileSystemReader for InMemoryFileSystem {
 
     fn read_bytes(&self, path: &Utf8Path) -> Result<Vec<u8>, Error> {
         let path = path.to_path_buf();
-        let files = self.files.deref().borrow();
+        let files = self.read_files();
         let buffer = files
             .get(&path)
             .and_then(|file| file.node.as_file_buffer())
@@ -261,22 +267,18 @@ impl FileSystemReader for InMemoryFileSystem {
                 path: path.clone(),
                 err: None,
             })?;
-        let bytes = buffer.borrow().clone();
+        let bytes = buffer.read().expect("InMemoryFile lock poisoned").clone();
         Ok(bytes)
     }
 
     fn is_file(&self, path: &Utf8Path) -> bool {
-        self.files
-            .deref()
-            .borrow()
+        self.read_files()
             .get(path)
             .is_some_and(|file| !file.is_directory())
     }
 
     fn is_directory(&self, path: &Utf8Path) -> bool {
-        self.files
-            .deref()
-            .borrow()
+        self.read_files()
             .get(path)
             .is_some_and(|file| file.is_directory())
     }
@@ -288,9 +290,7 @@ impl FileSystemReader for InMemoryFileSystem {
 
     fn read_dir(&self, path: &Utf8Path) -> Result<ReadDir> {
         let read_dir = ReadDir::from_iter(
-            self.files
-                .deref()
-                .borrow()
+            self.read_files()
                 .keys()
                 .map(|file_path| file_path.to_path_buf())
                 .filter(|file_path| file_path.parent().is_some_and(|parent| path == parent))
@@ -302,7 +302,7 @@ impl FileSystemReader for InMemoryFileSystem {
     }
 
     fn modification_time(&self, path: &Utf8Path) -> Result<SystemTime, Error> {
-        let files = self.files.deref().borrow();
+        let files = self.read_files();
         let file = files.get(path).ok_or_else(|| Error::FileIo {
             kind: FileKind::File,
             action: FileIoAction::ReadMetadata,
@@ -317,15 +317,31 @@ impl FileSystemReader for InMemoryFileSystem {
 ///
 /// Stores a file's buffer of contents.
 ///
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub enum InMemoryFileNode {
-    File(Rc<RefCell<Vec<u8>>>),
+    File(Arc<RwLock<Vec<u8>>>),
     Directory,
 }
 
+impl PartialEq for InMemoryFileNode {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::File(one), Self::File(other)) => {
+                Arc::ptr_eq(one, other)
+                    || *one.read().expect("InMemoryFile lock poisoned")
+                        == *other.read().expect("InMemoryFile lock poisoned")
+            }
+            (Self::Directory, Self::Directory) => true,
+            (Self::File(_), Self::Directory) | (Self::Directory, Self::File(_)) => false,
+        }
+    }
+}
+
+impl Eq for InMemoryFileNode {}
+
 impl InMemoryFileNode {
     /// Returns this file's file buffer if this isn't a directory.
-    fn as_file_buffer(&self) -> Option<&Rc<RefCell<Vec<u8>>>> {
+    fn as_file_buffer(&self) -> Option<&Arc<RwLock<Vec<u8>>>> {
         match self {
             Self::File(buffer) => Some(buffer),
             Self::Directory => None,
@@ -333,7 +349,7 @@ impl InMemoryFileNode {
     }
 
     /// Returns this file's file buffer if this isn't a directory.
-    fn into_file_buffer(self) -> Option<Rc<RefCell<Vec<u8>>>> {
+    fn into_file_buffer(self) -> Option<Arc<RwLock<Vec<u8>>>> {
         match self {
             Self::File(buffer) => Some(buffer),
             Self::Directory => None,
@@ -352,8 +368,6 @@ impl InMemoryFileNode {
 /// (files and directories). The `node` field contains the file's content
 /// buffer, if this is not a directory.
 ///
-/// Not thread safe. The compiler is single threaded, so that's OK.
-///
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InMemoryFile {
     node: InMemoryFileNode,
@@ -382,9 +396,10 @@ impl InMemoryFile {
     ///
     pub fn into_content(self) -> Option<Content> {
         let buffer = self.node.into_file_buffer()?;
-        let contents = Rc::try_unwrap(buffer)
+        let contents = Arc::try_unwrap(buffer)
             .expect("InMemoryFile::into_content called with multiple references")
-            .into_inner();
+            .into_inner()
+            .expect("InMemoryFile lock poisoned");
         match String::from_utf8(contents) {
             Ok(s) => Some(Content::Text(s)),
             Err(e) => Some(Content::Binary(e.into_bytes())),
@@ -409,7 +424,7 @@ impl io::Write for InMemoryFile {
             // Not a file
             return Err(io::Error::from(io::ErrorKind::NotFound));
         };
-        let mut reference = (*buffer).borrow_mut();
+        let mut reference = buffer.write().expect("InMemoryFile lock poisoned");
         reference.write(buf)
     }
 
@@ -418,7 +433,7 @@ impl io::Write for InMemoryFile {
             // Not a file
             return Err(io::Error::from(io::ErrorKind::NotFound));
         };
-        let mut reference = (*buffer).borrow_mut();
+        let mut reference = buffer.write().expect("InMemoryFile lock poisoned");
         reference.flush()
     }
 }
@@ -1,6 +1,7 @@
 mod decision;
 mod expression;
 mod import;
+mod source_map;
 #[cfg(test)]
 mod tests;
 mod typescript;
@@ -24,6 +25,7 @@ use expression::Context;
 use itertools::Itertools;
 
 use self::import::{Imports, Member};
+pub use self::source_map::{DecodedMapping, SourceMap, SourceMapWriter};
 
 const INDENT: isize = 2;
 
@@ -48,10 +50,13 @@ pub struct Generator<'a> {
     /// Relative path to the module, surrounded in `"`s to make it a string, and with `\`s escaped
     /// to `\\`.
     src_path: EcoString,
+    /// Whether a source map is generated for the module, in which case the
+    /// location of each definition is added to the document.
+    source_maps: bool,
 }
 
 impl<'a> Generator<'a> {
-    pub fn new(config: ModuleConfig<'a>) -> Self {
+    pub fn new(config: ModuleConfig<'a>, source_maps: bool) -> Self {
         let ModuleConfig {
             typescript,
             stdlib_package,
@@ -79,6 +84,7 @@ impl<'a> Generator<'a> {
             module_scope: Default::default(),
             typescript,
             stdlib_package,
+            source_maps,
         }
     }
 
@@ -317,8 +323,9 @@ impl<'a> Generator<'a> {
                 name,
                 value,
                 documentation,
+                location,
                 ..
-            }) => Some(self.module_constant(*publicity, name, value, documentation)),
+            }) => Some(self.module_constant(*publicity, name, value, documentation, *location)),
 
             Definition::Function(function) => {
                 // If there's an external JavaScript implementation then it will be imported,
@@ -575,6 +582,7 @@ impl<'a> Generator<'a> {
         name: &'a EcoString,
         value: &'a TypedConstant,
         documentation: &'a Option<(u32, EcoString)>,
+        location: SrcSpan,
     ) -> Document<'a> {
         let head = if publicity.is_private() {
             "const "
@@ -590,6 +598,7 @@ impl<'a> Generator<'a> {
             vec![],
             &mut self.tracker,
             self.module_scope.clone(),
+            self.source_maps,
         );
 
         let document = generator.constant_expression(Context::Constant, value);
@@ -602,6 +611,7 @@ impl<'a> Generator<'a> {
 
         docvec![
             jsdoc,
+            self.source_location(location.start),
             head,
             maybe_escape_identifier(name),
             " = ",
@@ -610,6 +620,16 @@ impl<'a> Generator<'a> {
         ]
     }
 
+    /// The location of the Gleam code a definition was generated from, if a
+    /// source map is being generated.
+    fn source_location(&self, start: u32) -> Document<'a> {
+        if self.source_maps {
+            source_location(start)
+        } else {
+            nil()
+        }
+    }
+
     fn register_in_scope(&mut self, name: &str) {
         let _ = self.module_scope.insert(name.into(), 0);
     }
@@ -624,6 +644,7 @@ impl<'a> Generator<'a> {
             .iter()
             .map(|arg| arg.names.get_variable_name())
             .collect();
+        let source_location = self.source_location(function.location.start);
         let mut generator = expression::Generator::new(
             self.module.name.clone(),
             self.src_path.clone(),
@@ -632,6 +653,7 @@ impl<'a> Generator<'a> {
             argument_names,
             &mut self.tracker,
             self.module_scope.clone(),
+            self.source_maps,
         );
 
         let function_doc = match &function.documentation {
@@ -651,6 +673,7 @@ impl<'a> Generator<'a> {
 
         docvec![
             function_doc,
+            source_location,
             head,
             maybe_escape_identifier(name.as_str()),
             fun_arguments(function.arguments.as_slice(), generator.tail_recursion_used),
@@ -727,10 +750,32 @@ pub struct ModuleConfig<'a> {
 }
 
 pub fn module(config: ModuleConfig<'_>) -> String {
-    let document = Generator::new(config).compile();
+    let document = Generator::new(config, false).compile();
     document.to_pretty_string(80)
 }
 
+/// Generates a module together with the source map pointing back to its Gleam
+/// source code.
+///
+/// `file` is the name of the generated file, and `source` the path of the
+/// Gleam module relative to the directory the generated file is written to.
+///
+pub fn module_with_source_map(
+    config: ModuleConfig<'_>,
+    file: &str,
+    source: &Utf8Path,
+) -> (String, SourceMap) {
+    let ModuleConfig {
+        line_numbers, src, ..
+    } = config;
+    let document = Generator::new(config, true).compile();
+    let mut writer = SourceMapWriter::new();
+    document
+        .pretty_print(80, &mut writer)
+        .expect("Writing to string buffer failed");
+    writer.finish(file, source, src, line_numbers)
+}
+
 pub fn ts_declaration(module: &TypedModule) -> String {
     let document = typescript::TypeScriptGenerator::new(module).compile();
     document.to_pretty_string(80)
@@ -169,6 +169,10 @@ pub(crate) struct Generator<'module, 'ast> {
     /// This means we can stop code generation for all the following statements
     /// in the same block!
     pub let_assert_always_panics: bool,
+
+    /// Whether a source map is generated for the module, in which case the
+    /// location of each statement and expression is added to the document.
+    source_maps: bool,
 }
 
 impl<'module, 'a> Generator<'module, 'a> {
@@ -181,6 +185,7 @@ impl<'module, 'a> Generator<'module, 'a> {
         function_arguments: Vec<Option<&'module EcoString>>,
         tracker: &'module mut UsageTracker,
         mut current_scope_vars: im::HashMap<EcoString, usize>,
+        source_maps: bool,
     ) -> Self {
         let mut current_function = CurrentFunction::Module;
         for &name in function_arguments.iter().flatten() {
@@ -207,6 +212,7 @@ impl<'module, 'a> Generator<'module, 'a> {
             scope_position: Position::Tail,
             statement_level: Vec::new(),
             let_assert_always_panics: false,
+            source_maps,
         }
     }
 
@@ -263,7 +269,18 @@ impl<'module, 'a> Generator<'module, 'a> {
             Statement::Use(use_) => self.expression(&use_.call),
             Statement::Assert(assert) => self.assert(assert),
         };
-        self.add_statement_level(expression_doc)
+        let statement_doc = self.add_statement_level(expression_doc);
+        self.with_source_location(statement.location().start, statement_doc)
+    }
+
+    /// Adds the location of the Gleam code a document was generated from in
+    /// front of it, if a source map is being generated.
+    fn with_source_location(&self, start: u32, document: Document<'a>) -> Document<'a> {
+        if self.source_maps {
+            docvec![source_location(start), document]
+        } else {
+            document
+        }
     }
 
     fn add_statement_level(&mut self, expression: Document<'a>) -> Document<'a> {
@@ -379,6 +396,7 @@ impl<'module, 'a> Generator<'module, 'a> {
                 panic!("invalid expressions should not reach code generation")
             }
         };
+        let document = self.with_source_location(expression.location().start, document);
         if expression.handles_own_return() {
             document
         } else {
@@ -0,0 +1,476 @@
+//! Generation of [version 3 source maps](https://tc39.es/ecma426/), mapping
+//! the generated JavaScript back to the Gleam code it was compiled from.
+//!
+//! When a source map is to be generated, the JavaScript generator adds
+//! `source_location` documents in front of the code generated for each
+//! definition, statement and expression. When the
+//! module is pretty printed with a `SourceMapWriter` the position in the
+//! output where each of those is reached is recorded, and the source map is
+//! built from those positions once printing is over.
+//!
+//! The Erlang generator adds them to its statements and expressions too when
+//! building the Erlang code used to measure coverage, so that its lines can be
+//! mapped back to the Gleam code in the same way.
+
+use std::fmt::Write;
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, io::Utf8Writer, line_numbers::LineNumbers};
+
+/// A writer that keeps track of the line and column it has reached, so it can
+/// record where in the output the source locations of a document are printed.
+#[derive(Debug, Default)]
+pub struct SourceMapWriter {
+    output: String,
+    /// The 0-indexed line the next character will be written to.
+    line: u32,
+    /// The 0-indexed column the next character will be written to, counted
+    /// in UTF-16 code units as required by source maps.
+    column: u32,
+    mappings: Vec<Mapping>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mapping {
+    generated_line: u32,
+    generated_column: u32,
+    source_start: u32,
+}
+
+impl SourceMapWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the printed code and its source map.
+    ///
+    /// `source` is the path the source map uses to refer to the Gleam module,
+    /// relative to the directory of the generated file named `file`.
+    ///
+    pub fn finish(
+        self,
+        file: &str,
+        source: &Utf8Path,
+        source_content: &EcoString,
+        line_numbers: &LineNumbers,
+    ) -> (String, SourceMap) {
+        let source_map = SourceMap {
+            version: 3,
+            file: file.into(),
+            sources: vec![source.as_str().replace('\\', "/").into()],
+            sources_content: vec![source_content.clone()],
+            names: vec![],
+            mappings: encode_mappings(&self.mappings, line_numbers),
+        };
+        (self.output, source_map)
+    }
+}
+
+impl Write for SourceMapWriter {
+    fn write_str(&mut self, string: &str) -> std::fmt::Result {
+        for char in string.chars() {
+            if char == '\n' {
+                self.line += 1;
+                self.column = 0;
+            } else {
+                self.column += char.len_utf16() as u32;
+            }
+        }
+        self.output.push_str(string);
+        Ok(())
+    }
+}
+
+impl Utf8Writer for SourceMapWriter {
+    fn convert_err<E: std::error::Error>(&self, error: E) -> Error {
+        self.output.convert_err(error)
+    }
+
+    fn source_location(&mut self, start: u32) {
+        let mapping = Mapping {
+            generated_line: self.line,
+            generated_column: self.column,
+            source_start: start,
+        };
+
+        // When nested expressions start at the same position in the output
+        // only the innermost one is kept, as it is the most precise.
+        match self.mappings.last_mut() {
+            Some(last)
+                if last.generated_line == mapping.generated_line
+                    && last.generated_column == mapping.generated_column =>
+            {
+                *last = mapping
+            }
+            _ => self.mappings.push(mapping),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMap {
+    pub version: u8,
+    #[serde(default)]
+    pub file: EcoString,
+    pub sources: Vec<EcoString>,
+    #[serde(default)]
+    pub sources_content: Vec<EcoString>,
+    #[serde(default)]
+    pub names: Vec<EcoString>,
+    pub mappings: String,
+}
+
+/// A segment of the mappings of a source map, with all its lines and columns
+/// 0-indexed. Columns are counted in UTF-16 code units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedMapping {
+    pub generated_line: u32,
+    pub generated_column: u32,
+    pub source_line: u32,
+    pub source_column: u32,
+}
+
+impl SourceMap {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("source map serialisation")
+    }
+
+    /// Parses a source map, returning `None` if it is not valid.
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str(json).ok()
+    }
+
+    /// Decodes the mappings of the source map. Segments that do not point to
+    /// a position in a source, and any malformed segment, are skipped.
+    pub fn decode_mappings(&self) -> Vec<DecodedMapping> {
+        let mut decoded = vec![];
+        let mut source_line = 0;
+        let mut source_column = 0;
+
+        for (generated_line, line) in self.mappings.split(';').enumerate() {
+            let mut generated_column = 0;
+            for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+                let Some(fields) = decode_vlqs(segment) else {
+                    continue;
+                };
+                let (column, line, column_in_source) = match fields.as_slice() {
+                    [column] => {
+                        generated_column += column;
+                        continue;
+                    }
+                    [column, _source, line, column_in_source]
+                    | [column, _source, line, column_in_source, _] => {
+                        (*column, *line, *column_in_source)
+                    }
+                    _ => continue,
+                };
+                generated_column += column;
+                source_line += line;
+                source_column += column_in_source;
+
+                decoded.push(DecodedMapping {
+                    generated_line: generated_line as u32,
+                    generated_column: generated_column.max(0) as u32,
+                    source_line: source_line.max(0) as u32,
+                    source_column: source_column.max(0) as u32,
+                });
+            }
+        }
+        decoded
+    }
+
+    /// The comment to add at the end of the generated file to point to this
+    /// source map, when it is written to the file at `path`.
+    pub fn url_comment(path: &str) -> String {
+        format!("//# sourceMappingURL={path}\n")
+    }
+
+    /// The comment to add at the end of the generated file to embed this
+    /// source map in it.
+    pub fn inline_comment(&self) -> String {
+        let json = self.to_json();
+        Self::url_comment(&format!(
+            "data:application/json;charset=utf-8;base64,{}",
+            base64(json.as_bytes())
+        ))
+    }
+}
+
+/// Encodes the mappings in the format used by the `mappings` field of a
+/// source map: the segments of each generated line are separated by commas
+/// and the lines themselves by semicolons. Each segment is made of the
+/// generated column, the index of the source, and the source line and column,
+/// all encoded as base 64 VLQs relative to the previous segment.
+///
+fn encode_mappings(mappings: &[Mapping], line_numbers: &LineNumbers) -> String {
+    let mut encoded = String::new();
+    let mut line = 0;
+    let mut previous_generated_column = 0;
+    let mut previous_source_line = 0;
+    let mut previous_source_column = 0;
+
+    for mapping in mappings {
+        if mapping.generated_line != line {
+            while line < mapping.generated_line {
+                encoded.push(';');
+                line += 1;
+            }
+            previous_generated_column = 0;
+        } else if !encoded.is_empty() && !encoded.ends_with(';') {
+            encoded.push(',');
+        }
+
+        let source = line_numbers.line_and_column_number(mapping.source_start);
+        let source_line = source.line as i64 - 1;
+        let source_column = source.column as i64 - 1;
+        let generated_column = mapping.generated_column as i64;
+
+        vlq(&mut encoded, generated_column - previous_generated_column);
+        // There is a single source, so its index never changes.
+        vlq(&mut encoded, 0);
+        vlq(&mut encoded, source_line - previous_source_line);
+        vlq(&mut encoded, source_column - previous_source_column);
+
+        previous_generated_column = generated_column;
+        previous_source_line = source_line;
+        previous_source_column = source_column;
+    }
+
+    encoded
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_digit(value: u32) -> char {
+    BASE64_ALPHABET
+        .get(value as usize)
+        .map(|byte| *byte as char)
+        .expect("base 64 digit out of range")
+}
+
+/// Appends a number encoded as a base 64 VLQ: the sign is stored in the least
+/// significant bit, then the number is split into groups of 5 bits, each
+/// written as a base 64 digit with its 6th bit set if more groups follow.
+///
+fn vlq(output: &mut String, value: i64) {
+    let mut value = if value < 0 {
+        ((-value as u64) << 1) | 1
+    } else {
+        (value as u64) << 1
+    };
+
+    loop {
+        let mut digit = (value & 0b11111) as u32;
+        value >>= 5;
+        if value > 0 {
+            digit |= 0b100000;
+        }
+        output.push(base64_digit(digit));
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+/// Decodes the base 64 VLQs a segment of the mappings is made of, returning
+/// `None` if it is malformed.
+fn decode_vlqs(segment: &str) -> Option<Vec<i64>> {
+    let mut values = vec![];
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    for char in segment.bytes() {
+        let digit = BASE64_ALPHABET.iter().position(|byte| *byte == char)? as u64;
+        value |= (digit & 0b11111).checked_shl(shift)?;
+        if digit & 0b100000 == 0 {
+            let magnitude = (value >> 1) as i64;
+            values.push(if value & 1 == 1 {
+                -magnitude
+            } else {
+                magnitude
+            });
+            value = 0;
+            shift = 0;
+        } else {
+            shift += 5;
+        }
+    }
+    // A segment must not end in the middle of a number.
+    (shift == 0).then_some(values)
+}
+
+/// Encodes bytes as base 64, used to embed a source map in a data URL.
+fn base64(bytes: &[u8]) -> String {
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let [first, second, third] = match *chunk {
+            [first, second, third] => [first, second, third],
+            [first, second] => [first, second, 0],
+            [first] => [first, 0, 0],
+            _ => unreachable!("chunks are never empty or longer than 3 bytes"),
+        };
+        let group = ((first as u32) << 16) | ((second as u32) << 8) | third as u32;
+
+        output.push(base64_digit((group >> 18) & 0b111111));
+        output.push(base64_digit((group >> 12) & 0b111111));
+        if chunk.len() > 1 {
+            output.push(base64_digit((group >> 6) & 0b111111));
+        } else {
+            output.push('=');
+        }
+        if chunk.len() > 2 {
+            output.push(base64_digit(group & 0b111111));
+        } else {
+            output.push('=');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(value: i64) -> String {
+        let mut output = String::new();
+        vlq(&mut output, value);
+        output
+    }
+
+    #[test]
+    fn vlq_encoding() {
+        assert_eq!(encoded(0), "A");
+        assert_eq!(encoded(1), "C");
+        assert_eq!(encoded(-1), "D");
+        assert_eq!(encoded(15), "e");
+        assert_eq!(encoded(16), "gB");
+        assert_eq!(encoded(-16), "hB");
+        assert_eq!(encoded(1000), "w+B");
+    }
+
+    #[test]
+    fn vlq_decoding() {
+        for value in [0, 1, -1, 15, 16, -16, 1000, -123456] {
+            assert_eq!(decode_vlqs(&encoded(value)), Some(vec![value]));
+        }
+        assert_eq!(decode_vlqs("AACA"), Some(vec![0, 0, 1, 0]));
+        assert_eq!(decode_vlqs("g"), None);
+        assert_eq!(decode_vlqs("A!"), None);
+    }
+
+    #[test]
+    fn decoded_mappings_are_the_encoded_ones() {
+        let source = "pub fn main() {\n  wibble(\"🦊\")\n}\n";
+        let line_numbers = LineNumbers::new(source);
+        let mappings = [
+            Mapping {
+                generated_line: 0,
+                generated_column: 0,
+                source_start: 0,
+            },
+            Mapping {
+                generated_line: 1,
+                generated_column: 2,
+                source_start: 18,
+            },
+            Mapping {
+                generated_line: 1,
+                generated_column: 9,
+                source_start: 30,
+            },
+        ];
+        let source_map = SourceMap {
+            version: 3,
+            file: "wibble.mjs".into(),
+            sources: vec!["wibble.gleam".into()],
+            sources_content: vec![],
+            names: vec![],
+            mappings: encode_mappings(&mappings, &line_numbers),
+        };
+        let source_map = SourceMap::from_json(&source_map.to_json()).expect("valid source map");
+        assert_eq!(
+            source_map.decode_mappings(),
+            vec![
+                DecodedMapping {
+                    generated_line: 0,
+                    generated_column: 0,
+                    source_line: 0,
+                    source_column: 0,
+                },
+                DecodedMapping {
+                    generated_line: 1,
+                    generated_column: 2,
+                    source_line: 1,
+                    source_column: 2,
+                },
+                DecodedMapping {
+                    generated_line: 1,
+                    generated_column: 9,
+                    source_line: 1,
+                    source_column: 12,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn base64_encoding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn writer_tracks_utf16_columns() {
+        let mut writer = SourceMapWriter::new();
+        writer.str_write("let 🦊 = \"").expect("write");
+        writer.source_location(4);
+        writer.str_write("é\";\n  ").expect("write");
+        writer.source_location(8);
+        assert_eq!(
+            writer.mappings,
+            vec![
+                Mapping {
+                    generated_line: 0,
+                    generated_column: 10,
+                    source_start: 4,
+                },
+                Mapping {
+                    generated_line: 1,
+                    generated_column: 2,
+                    source_start: 8,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn innermost_location_is_kept() {
+        let mut writer = SourceMapWriter::new();
+        writer.source_location(1);
+        writer.source_location(5);
+        writer.str_write("wibble").expect("write");
+        writer.source_location(7);
+        assert_eq!(
+            writer.mappings,
+            vec![
+                Mapping {
+                    generated_line: 0,
+                    generated_column: 0,
+                    source_start: 5,
+                },
+                Mapping {
+                    generated_line: 0,
+                    generated_column: 6,
+                    source_start: 7,
+                },
+            ]
+        );
+    }
+}
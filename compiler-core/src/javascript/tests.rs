@@ -29,6 +29,7 @@ mod prelude;
 mod records;
 mod recursion;
 mod results;
+mod source_maps;
 mod strings;
 mod todo;
 mod tuples;
@@ -205,6 +206,24 @@ pub fn compile_js(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
     )
 }
 
+pub fn compile_js_with_source_map(src: &str) -> (String, SourceMap) {
+    let ast = compile(src, vec![]);
+    let line_numbers = LineNumbers::new(src);
+    module_with_source_map(
+        ModuleConfig {
+            module: &ast,
+            line_numbers: &line_numbers,
+            src: &src.into(),
+            typescript: TypeScriptDeclarations::None,
+            stdlib_package: StdlibPackage::Present,
+            path: Utf8Path::new("src/module.gleam"),
+            project_root: "project/root".into(),
+        },
+        "module.mjs",
+        Utf8Path::new("../../src/module.gleam"),
+    )
+}
+
 pub fn compile_ts(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
     let ast = compile(src, deps);
     ts_declaration(&ast)
@@ -0,0 +1,179 @@
+use itertools::Itertools;
+
+use crate::javascript::tests::compile_js_with_source_map;
+
+macro_rules! assert_source_map {
+    ($src:expr $(,)?) => {{
+        let (compiled, source_map) = compile_js_with_source_map($src);
+        let output = format!(
+            "----- SOURCE CODE\n{}\n\n----- COMPILED JAVASCRIPT\n{}\n\n----- MAPPINGS\n{}",
+            $src,
+            compiled,
+            print_mappings(&source_map.mappings, &compiled, $src)
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+/// Decodes the mappings of a source map, printing each one as the position in
+/// the generated code and the one in the source code, followed by the code
+/// found at each of them.
+fn print_mappings(mappings: &str, compiled: &str, src: &str) -> String {
+    let compiled_lines = compiled.lines().collect_vec();
+    let src_lines = src.lines().collect_vec();
+    let mut source_line = 0;
+    let mut source_column = 0;
+    let mut output = String::new();
+
+    for (generated_line, segments) in mappings.split(';').enumerate() {
+        let mut generated_column = 0;
+        for segment in segments.split(',').filter(|segment| !segment.is_empty()) {
+            let [column, source, line, line_column] = decode_vlqs(segment)[..] else {
+                panic!("segment `{segment}` does not have 4 fields")
+            };
+            assert_eq!(source, 0, "there is a single source");
+            generated_column += column;
+            source_line += line;
+            source_column += line_column;
+
+            output.push_str(&format!(
+                "{}:{} {} -> {}:{} {}\n",
+                generated_line + 1,
+                generated_column,
+                snippet(&compiled_lines, generated_line, generated_column),
+                source_line + 1,
+                source_column,
+                snippet(&src_lines, source_line as usize, source_column),
+            ));
+        }
+    }
+    output
+}
+
+/// The code starting at the given column, counted in UTF-16 code units.
+fn snippet(lines: &[&str], line: usize, column: i64) -> String {
+    let mut utf16_column = 0;
+    let code: String = lines
+        .get(line)
+        .expect("mapped line")
+        .chars()
+        .skip_while(|char| {
+            let skip = utf16_column < column;
+            utf16_column += char.len_utf16() as i64;
+            skip
+        })
+        .take(24)
+        .collect();
+    format!("`{code}`")
+}
+
+fn decode_vlqs(segment: &str) -> Vec<i64> {
+    let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut values = vec![];
+    let mut value = 0;
+    let mut shift = 0;
+    for char in segment.chars() {
+        let digit = alphabet.find(char).expect("base 64 digit") as i64;
+        value |= (digit & 0b11111) << shift;
+        shift += 5;
+        if digit & 0b100000 == 0 {
+            let decoded = value >> 1;
+            values.push(if value & 1 == 1 { -decoded } else { decoded });
+            value = 0;
+            shift = 0;
+        }
+    }
+    values
+}
+
+#[test]
+fn function_with_statements() {
+    assert_source_map!(
+        r#"
+pub fn go(x) {
+  let y = x + 1
+  wibble(y)
+}
+
+fn wibble(x) {
+  x
+}
+"#
+    );
+}
+
+#[test]
+fn panic_points_to_source() {
+    assert_source_map!(
+        r#"
+pub fn go() {
+  let x = 1
+  panic as "oh no"
+}
+"#
+    );
+}
+
+#[test]
+fn case_expression() {
+    assert_source_map!(
+        r#"
+pub fn go(x) {
+  case x {
+    1 -> "one"
+    _ -> "other"
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn module_constant() {
+    assert_source_map!(
+        r#"
+pub const answer = 42
+
+pub fn go() {
+  answer
+}
+"#
+    );
+}
+
+#[test]
+fn unicode_columns_are_utf16() {
+    assert_source_map!(
+        r#"
+pub fn go(x) {
+  let emoji = "🦊🦊"
+  wibble("🦊é", x)
+}
+
+fn wibble(a, b) {
+  a <> b
+}
+"#
+    );
+}
+
+#[test]
+fn source_map_json() {
+    let (_, source_map) = compile_js_with_source_map(
+        r#"pub fn go() {
+  1
+}
+"#,
+    );
+    insta::assert_snapshot!(source_map.to_json());
+}
+
+#[test]
+fn inline_source_map_comment() {
+    let (_, source_map) = compile_js_with_source_map("pub fn go() { 1 }\n");
+    let comment = source_map.inline_comment();
+    assert!(
+        comment.starts_with("//# sourceMappingURL=data:application/json;charset=utf-8;base64,")
+    );
+    assert!(comment.ends_with('\n'));
+}
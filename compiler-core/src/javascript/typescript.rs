@@ -314,12 +314,7 @@ impl<'a> TypeScriptGenerator<'a> {
                 }
             }
             Type::Var { type_ } => {
-                if let TypeVar::Link { type_ } = type_
-                    .as_ref()
-                    .try_borrow()
-                    .expect("borrow type after inference")
-                    .deref()
-                {
+                if let TypeVar::Link { type_ } = type_.as_ref().borrow().deref() {
                     self.collect_imports_for_type(type_, imports);
                 }
             }
@@ -1,17 +1,31 @@
+mod call_hierarchy;
+mod change_signature;
 mod code_action;
+mod code_lens;
 mod compiler;
 mod completer;
+mod configuration;
+mod diagnostics;
 mod edits;
 mod engine;
 mod feedback;
 mod files;
+mod folding_range;
+mod formatting;
+mod gleam_toml;
+mod implementation;
+mod inlay_hints;
 mod messages;
+mod move_definition;
 mod progress;
 mod reference;
 mod rename;
 mod router;
+mod selection_range;
+mod semantic_tokens;
 mod server;
 mod signature_help;
+mod workspace_symbol;
 
 #[cfg(test)]
 mod tests;
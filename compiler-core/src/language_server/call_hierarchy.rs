@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Range, SymbolKind,
+    SymbolTag,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::SrcSpan,
+    reference::{Reference, ReferenceKind},
+    type_::{ModuleInterface, ValueConstructorVariant},
+};
+
+use super::{compiler::ModuleSourceInformation, src_span_to_lsp_range, url_from_path};
+
+/// The function a call hierarchy item is for, which is stored in the item so
+/// it can be found again when the editor asks for its calls.
+///
+#[derive(Debug, Serialize, Deserialize)]
+struct ItemData {
+    module: EcoString,
+    name: EcoString,
+}
+
+/// The modules and sources call hierarchies are built from.
+///
+pub struct CallHierarchy<'a> {
+    pub modules: &'a im::HashMap<EcoString, ModuleInterface>,
+    pub sources: &'a HashMap<EcoString, ModuleSourceInformation>,
+}
+
+impl CallHierarchy<'_> {
+    /// The call hierarchy item for a module function, or `None` if the value
+    /// is not a function.
+    ///
+    pub fn item(&self, module: &str, name: &str) -> Option<CallHierarchyItem> {
+        let interface = self.modules.get(module)?;
+        let value = interface.values.get(name)?;
+        let ValueConstructorVariant::ModuleFn {
+            location,
+            module: defining_module,
+            ..
+        } = &value.variant
+        else {
+            return None;
+        };
+        // Functions which are imported unqualified are defined elsewhere
+        if defining_module != module {
+            return None;
+        }
+
+        let source = self.sources.get(module)?;
+        let range = src_span_to_lsp_range(*location, &source.line_numbers);
+        let data = ItemData {
+            module: module.into(),
+            name: name.into(),
+        };
+        Some(CallHierarchyItem {
+            name: name.to_string(),
+            kind: SymbolKind::FUNCTION,
+            tags: value
+                .deprecation
+                .is_deprecated()
+                .then(|| vec![SymbolTag::DEPRECATED]),
+            detail: Some(module.to_string()),
+            uri: url_from_path(&source.path)?,
+            range,
+            selection_range: range,
+            data: Some(serde_json::to_value(data).expect("call hierarchy item data to json")),
+        })
+    }
+
+    /// The functions calling the function of the given item, and where they
+    /// call it from. Any reference to the function counts as a call, so this
+    /// includes calls made through pipes, `use` and function captures, and
+    /// passing the function as an argument.
+    ///
+    pub fn incoming_calls(&self, item: &CallHierarchyItem) -> Vec<CallHierarchyIncomingCall> {
+        let Some(ItemData { module, name }) = item_data(item) else {
+            return vec![];
+        };
+        let key = (module, name);
+
+        let mut calls = vec![];
+        for interface in self.modules.values() {
+            let Some(references) = interface.references.value_references.get(&key) else {
+                continue;
+            };
+            let Some(source) = self.sources.get(&interface.name) else {
+                continue;
+            };
+            let callers = references
+                .iter()
+                .filter(|reference| is_call(reference))
+                .filter_map(|reference| {
+                    let caller = enclosing_function(interface, reference.location)?;
+                    Some((caller, reference.location))
+                })
+                .into_group_map();
+
+            for (caller, locations) in callers.into_iter().sorted() {
+                let Some(from) = self.item(&interface.name, &caller) else {
+                    continue;
+                };
+                calls.push(CallHierarchyIncomingCall {
+                    from,
+                    from_ranges: to_ranges(locations, source),
+                });
+            }
+        }
+
+        calls.sort_by(|one, other| {
+            (one.from.detail.as_ref(), one.from.range.start)
+                .cmp(&(other.from.detail.as_ref(), other.from.range.start))
+        });
+        calls
+    }
+
+    /// The functions called by the function of the given item, and where it
+    /// calls them from.
+    ///
+    pub fn outgoing_calls(&self, item: &CallHierarchyItem) -> Vec<CallHierarchyOutgoingCall> {
+        let Some(ItemData { module, name }) = item_data(item) else {
+            return vec![];
+        };
+        let (Some(interface), Some(source)) =
+            (self.modules.get(&module), self.sources.get(&module))
+        else {
+            return vec![];
+        };
+
+        let callees = interface
+            .references
+            .value_references
+            .iter()
+            .flat_map(|(callee, references)| {
+                references
+                    .iter()
+                    .filter(|reference| is_call(reference))
+                    .filter(|reference| {
+                        enclosing_function(interface, reference.location).as_ref() == Some(&name)
+                    })
+                    .map(move |reference| (callee, reference.location))
+            })
+            .into_group_map();
+
+        callees
+            .into_iter()
+            .filter_map(|((callee_module, callee_name), locations)| {
+                let to = self.item(callee_module, callee_name)?;
+                Some(CallHierarchyOutgoingCall {
+                    to,
+                    from_ranges: to_ranges(locations, source),
+                })
+            })
+            .sorted_by_key(|call| call.from_ranges.first().map(|range| range.start))
+            .collect()
+    }
+}
+
+fn item_data(item: &CallHierarchyItem) -> Option<ItemData> {
+    serde_json::from_value(item.data.clone()?).ok()
+}
+
+fn is_call(reference: &Reference) -> bool {
+    match reference.kind {
+        ReferenceKind::Qualified | ReferenceKind::Unqualified => true,
+        ReferenceKind::Import | ReferenceKind::Definition | ReferenceKind::Alias => false,
+    }
+}
+
+/// The name of the top level function a location is in. Only the head of each
+/// definition is known, but definitions can't be nested: the location is in the
+/// last one starting before it.
+///
+fn enclosing_function(module: &ModuleInterface, location: SrcSpan) -> Option<EcoString> {
+    let (name, is_function) = module
+        .values
+        .iter()
+        .filter_map(|(name, value)| match &value.variant {
+            ValueConstructorVariant::ModuleFn {
+                location: definition,
+                module: defining_module,
+                ..
+            } if *defining_module == module.name => Some((name, definition, true)),
+            ValueConstructorVariant::ModuleConstant {
+                location: definition,
+                module: defining_module,
+                ..
+            } if *defining_module == module.name => Some((name, definition, false)),
+            _ => None,
+        })
+        .filter(|(_, definition, _)| definition.start <= location.start)
+        .max_by_key(|(_, definition, _)| definition.start)
+        .map(|(name, _, is_function)| (name, is_function))?;
+
+    is_function.then(|| name.clone())
+}
+
+fn to_ranges(mut locations: Vec<SrcSpan>, source: &ModuleSourceInformation) -> Vec<Range> {
+    locations.sort_by_key(|location| location.start);
+    locations
+        .into_iter()
+        .map(|location| src_span_to_lsp_range(location, &source.line_numbers))
+        .collect()
+}
@@ -0,0 +1,539 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{TextDocumentPositionParams, WorkspaceEdit};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analyse::name,
+    ast::{
+        self, ArgNames, ImplicitCallArgOrigin, SrcSpan, TypedExpr, TypedFunction,
+        visit::{self, Visit},
+    },
+    build::Module,
+    reference::ReferenceKind,
+    type_::{ModuleValueConstructor, Type, TypedCallArg, ValueConstructorVariant, error::Named},
+};
+
+use super::{TextEdits, url_from_path};
+
+/// The command changing the parameters of a function and updating all of its
+/// calls. Its only argument is a `ChangeSignatureParams`.
+///
+pub const CHANGE_SIGNATURE_COMMAND: &str = "gleam.changeSignature";
+
+/// The function whose signature is changed, identified by the position of its
+/// definition or of a reference to it, and its new parameters.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSignatureParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+    pub parameters: Vec<NewParameter>,
+}
+
+/// A parameter of the changed function, in the order it is going to appear in
+/// its signature.
+///
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewParameter {
+    /// The position of the parameter in the current signature, or `None` if
+    /// it is a new one.
+    pub index: Option<usize>,
+    pub label: Option<EcoString>,
+    /// The name of a new parameter. Existing parameters keep theirs.
+    pub name: Option<EcoString>,
+    /// The type annotation of a new parameter.
+    pub annotation: Option<String>,
+    /// The expression passed as the argument for a new parameter at each of
+    /// the existing calls.
+    pub default: Option<String>,
+}
+
+/// Why the signature of a function can't be changed.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeSignatureError {
+    /// There's no function defined in the project at the given position.
+    NoFunction,
+    /// The function is implemented in Erlang or JavaScript, and those
+    /// implementations would have to change too.
+    External,
+    InvalidParameters,
+    /// The function is referenced other than by calling it, such as
+    /// `list.map(xs, wibble)` or `let f = wibble`, and those references can't
+    /// be updated.
+    UsedAsValue,
+    /// A call pipes a value into the function or uses it with `use`, and the
+    /// value can't be passed that way anymore.
+    ImplicitArgument,
+    /// The code of the function or of one of its calls couldn't be rewritten.
+    CannotRewrite,
+}
+
+impl ChangeSignatureError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            ChangeSignatureError::NoFunction => {
+                "There is no function defined in this project at this position."
+            }
+            ChangeSignatureError::External => {
+                "The function is implemented in Erlang or JavaScript, \
+and those implementations would have to change too."
+            }
+            ChangeSignatureError::InvalidParameters => {
+                "The new parameters are not valid: each existing parameter can only appear \
+once, new parameters need a name and a default value, names and labels must not \
+be repeated, and unlabelled parameters must come before labelled ones."
+            }
+            ChangeSignatureError::UsedAsValue => {
+                "The function is used as a value rather than being called, \
+and those uses cannot be updated."
+            }
+            ChangeSignatureError::ImplicitArgument => {
+                "A call pipes a value into the function or calls it with `use`, \
+and that value cannot be passed the same way with the new parameters."
+            }
+            ChangeSignatureError::CannotRewrite => {
+                "The code of the function or of one of its calls could not be rewritten."
+            }
+        }
+    }
+}
+
+/// The edits changing the parameters of a function defined in one of the
+/// given modules, rewriting the calls to it from all of them. Calls made by
+/// piping a value into the function or with `use` can only be rewritten if
+/// the implicit argument can still be passed the same way.
+///
+pub fn change_signature(
+    modules: &HashMap<EcoString, Module>,
+    module_name: &EcoString,
+    name: &EcoString,
+    parameters: &[NewParameter],
+) -> Result<WorkspaceEdit, ChangeSignatureError> {
+    let module = modules
+        .get(module_name)
+        .ok_or(ChangeSignatureError::NoFunction)?;
+    let function = module
+        .ast
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            ast::Definition::Function(function)
+                if function
+                    .name
+                    .as_ref()
+                    .is_some_and(|(_, function_name)| function_name == name) =>
+            {
+                Some(function)
+            }
+            _ => None,
+        })
+        .ok_or(ChangeSignatureError::NoFunction)?;
+
+    if function.external_erlang.is_some() || function.external_javascript.is_some() {
+        return Err(ChangeSignatureError::External);
+    }
+    if !are_valid_parameters(function, parameters) {
+        return Err(ChangeSignatureError::InvalidParameters);
+    }
+
+    let mut changes = HashMap::new();
+    for (calling_module_name, calling_module) in modules.iter().sorted_by_key(|(name, _)| *name) {
+        let references = calling_module
+            .ast
+            .type_info
+            .references
+            .value_references
+            .get(&(module_name.clone(), name.clone()))
+            .map(|references| {
+                references
+                    .iter()
+                    .filter(|reference| match reference.kind {
+                        ReferenceKind::Qualified | ReferenceKind::Unqualified => true,
+                        ReferenceKind::Import
+                        | ReferenceKind::Definition
+                        | ReferenceKind::Alias => false,
+                    })
+                    .map(|reference| reference.location)
+                    .collect_vec()
+            })
+            .unwrap_or_default();
+
+        let is_defining_module = calling_module_name == module_name;
+        if references.is_empty() && !is_defining_module {
+            continue;
+        }
+
+        let line_numbers = &calling_module.ast.type_info.line_numbers;
+        let mut edits = TextEdits::new(line_numbers);
+        if is_defining_module {
+            let (location, new_parameters) = definition_edit(module, function, parameters)
+                .ok_or(ChangeSignatureError::CannotRewrite)?;
+            edits.replace(location, new_parameters);
+        }
+
+        let mut finder = FunctionCalls {
+            module: module_name,
+            name,
+            calls: vec![],
+        };
+        finder.visit_typed_module(&calling_module.ast);
+        let is_called = |reference: &SrcSpan| {
+            finder
+                .calls
+                .iter()
+                .any(|call| call.function.contains(reference.start))
+        };
+        if !references.iter().all(is_called) {
+            return Err(ChangeSignatureError::UsedAsValue);
+        }
+
+        for call in finder.calls {
+            if !references
+                .iter()
+                .any(|reference| call.function.contains(reference.start))
+            {
+                continue;
+            }
+            if let Some((location, new_arguments)) =
+                call_edit(&calling_module.code, &call, parameters)?
+            {
+                edits.replace(location, new_arguments);
+            }
+        }
+
+        if edits.edits.is_empty() {
+            continue;
+        }
+        let uri = url_from_path(calling_module.input_path.as_str())
+            .ok_or(ChangeSignatureError::CannotRewrite)?;
+        let _ = changes.insert(uri, edits.edits);
+    }
+
+    Ok(WorkspaceEdit {
+        changes: Some(changes),
+        document_changes: None,
+        change_annotations: None,
+    })
+}
+
+fn are_valid_parameters(function: &TypedFunction, parameters: &[NewParameter]) -> bool {
+    let mut indices = HashSet::new();
+    let mut labels = HashSet::new();
+    let mut names = HashSet::new();
+    let mut found_labelled = false;
+
+    for parameter in parameters {
+        let name = match parameter.index {
+            Some(index) => {
+                if !indices.insert(index) {
+                    return false;
+                }
+                match function.arguments.get(index) {
+                    Some(argument) => argument.names.get_variable_name().cloned(),
+                    None => return false,
+                }
+            }
+            None => {
+                let (Some(name), Some(_)) = (&parameter.name, &parameter.default) else {
+                    return false;
+                };
+                let kind = if name.starts_with('_') {
+                    Named::Discard
+                } else {
+                    Named::Argument
+                };
+                if name::check_name_case(SrcSpan::default(), name, kind).is_err() {
+                    return false;
+                }
+                (kind == Named::Argument).then(|| name.clone())
+            }
+        };
+
+        if name.is_some_and(|name| !names.insert(name)) {
+            return false;
+        }
+
+        match &parameter.label {
+            Some(label) => {
+                let is_valid =
+                    name::check_name_case(SrcSpan::default(), label, Named::Label).is_ok();
+                if !is_valid || !labels.insert(label.clone()) {
+                    return false;
+                }
+                found_labelled = true;
+            }
+            // Unlabelled parameters must come before all the labelled ones.
+            None if found_labelled => return false,
+            None => {}
+        }
+    }
+
+    true
+}
+
+/// The location of the parameters of the function, between its parentheses,
+/// and the code to replace them with.
+///
+fn definition_edit(
+    module: &Module,
+    function: &TypedFunction,
+    parameters: &[NewParameter],
+) -> Option<(SrcSpan, String)> {
+    let code = &module.code;
+    let (name_location, _) = function.name.as_ref()?;
+    let open = code.get(name_location.end as usize..)?.find('(')? as u32 + name_location.end;
+    let last_parameter_end = function
+        .arguments
+        .last()
+        .map_or(open + 1, |argument| argument.location.end);
+    let close = closing_parenthesis(code, last_parameter_end)?;
+
+    let new_parameters = parameters
+        .iter()
+        .map(|parameter| {
+            let (name, annotation) = match parameter.index {
+                Some(index) => {
+                    let argument = function.arguments.get(index)?;
+                    let name = match &argument.names {
+                        ArgNames::Discard { name, .. }
+                        | ArgNames::LabelledDiscard { name, .. }
+                        | ArgNames::Named { name, .. }
+                        | ArgNames::NamedLabelled { name, .. } => name.clone(),
+                    };
+                    let annotation = match &argument.annotation {
+                        Some(annotation) => {
+                            let location = annotation.location();
+                            let annotation =
+                                code.get(location.start as usize..location.end as usize)?;
+                            Some(annotation.to_string())
+                        }
+                        None => None,
+                    };
+                    (name, annotation)
+                }
+                None => (parameter.name.clone()?, parameter.annotation.clone()),
+            };
+
+            let mut text = String::new();
+            if let Some(label) = &parameter.label {
+                text.push_str(label);
+                text.push(' ');
+            }
+            text.push_str(&name);
+            if let Some(annotation) = annotation {
+                text.push_str(": ");
+                text.push_str(&annotation);
+            }
+            Some(text)
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some((SrcSpan::new(open + 1, close), new_parameters.join(", ")))
+}
+
+/// The location of the arguments written in a call, and the code to replace
+/// them with. Returns `None` if the call doesn't need to change.
+///
+fn call_edit(
+    code: &str,
+    call: &FunctionCall<'_>,
+    parameters: &[NewParameter],
+) -> Result<Option<(SrcSpan, String)>, ChangeSignatureError> {
+    let implicit_argument = call
+        .arguments
+        .iter()
+        .enumerate()
+        .find_map(|(index, argument)| argument.implicit.map(|origin| (index, origin)));
+
+    let last = parameters.len().saturating_sub(1);
+    let mut arguments = vec![];
+    let mut found_implicit_argument = false;
+    let mut found_labelled = false;
+
+    for (position, parameter) in parameters.iter().enumerate() {
+        let Some(index) = parameter.index else {
+            let default = parameter
+                .default
+                .as_ref()
+                .ok_or(ChangeSignatureError::CannotRewrite)?;
+            let argument = match &parameter.label {
+                Some(label) => {
+                    found_labelled = true;
+                    format!("{label}: {default}")
+                }
+                None => default.clone(),
+            };
+            arguments.push(argument);
+            continue;
+        };
+
+        match implicit_argument {
+            // The value piped into the function is still passed as the first
+            // argument, or it can take the place of a hole.
+            Some((implicit_index, ImplicitCallArgOrigin::Pipe)) if implicit_index == index => {
+                found_implicit_argument = true;
+                if position != 0 {
+                    arguments.push(labelled(&parameter.label, found_labelled, "_".into()));
+                }
+                continue;
+            }
+            // The callback of `use` must still be the last argument.
+            Some((implicit_index, ImplicitCallArgOrigin::Use)) if implicit_index == index => {
+                if position != last {
+                    return Err(ChangeSignatureError::ImplicitArgument);
+                }
+                found_implicit_argument = true;
+                continue;
+            }
+            Some(_) | None => {}
+        }
+
+        let argument = call
+            .arguments
+            .get(index)
+            .ok_or(ChangeSignatureError::CannotRewrite)?;
+        let value = if argument.uses_label_shorthand() {
+            argument
+                .label
+                .clone()
+                .ok_or(ChangeSignatureError::CannotRewrite)?
+                .to_string()
+        } else {
+            let location = argument.value.location();
+            code.get(location.start as usize..location.end as usize)
+                .ok_or(ChangeSignatureError::CannotRewrite)?
+                .to_string()
+        };
+
+        let argument = match &parameter.label {
+            Some(label) if argument.uses_label_shorthand() && *label == value => {
+                found_labelled = true;
+                format!("{label}:")
+            }
+            Some(label) if argument.label.is_some() || found_labelled => {
+                found_labelled = true;
+                format!("{label}: {value}")
+            }
+            Some(_) | None => value,
+        };
+        arguments.push(argument);
+    }
+
+    // The implicit argument can't be passed anymore if its parameter was
+    // removed.
+    if implicit_argument.is_some() && !found_implicit_argument {
+        return Err(ChangeSignatureError::ImplicitArgument);
+    }
+
+    let arguments = arguments.join(", ");
+    let function_end = call.function.end;
+    let rest = code
+        .get(function_end as usize..)
+        .ok_or(ChangeSignatureError::CannotRewrite)?;
+    match rest.chars().next() {
+        Some('(') => {
+            let last_argument_end = call
+                .arguments
+                .iter()
+                .filter(|argument| argument.implicit.is_none())
+                .map(|argument| argument.location.end)
+                .max()
+                .unwrap_or(function_end + 1);
+            let close = closing_parenthesis(code, last_argument_end)
+                .ok_or(ChangeSignatureError::CannotRewrite)?;
+            Ok(Some((SrcSpan::new(function_end + 1, close), arguments)))
+        }
+        // A function can be piped into or used without parentheses if there
+        // are no other arguments.
+        _ if arguments.is_empty() => Ok(None),
+        _ => Ok(Some((
+            SrcSpan::new(function_end, function_end),
+            format!("({arguments})"),
+        ))),
+    }
+}
+
+fn labelled(label: &Option<EcoString>, found_labelled: bool, value: String) -> String {
+    match label {
+        Some(label) if found_labelled => format!("{label}: {value}"),
+        Some(_) | None => value,
+    }
+}
+
+/// The position of the parenthesis closing a list of arguments or parameters,
+/// given the end of the last one in it. Only whitespace, a trailing comma, and
+/// comments can come between the two.
+///
+fn closing_parenthesis(code: &str, last_end: u32) -> Option<u32> {
+    let mut rest = code.get(last_end as usize..)?;
+    loop {
+        let trimmed = rest.trim_start_matches(|char: char| char.is_whitespace() || char == ',');
+        if let Some(comment) = trimmed.strip_prefix("//") {
+            rest = comment.split_once('\n').map_or("", |(_, after)| after);
+        } else if trimmed.starts_with(')') {
+            return Some((code.len() - trimmed.len()) as u32);
+        } else {
+            return None;
+        }
+    }
+}
+
+/// A call to the function whose signature is changed.
+///
+struct FunctionCall<'a> {
+    /// The location of the function being called.
+    function: SrcSpan,
+    arguments: &'a [TypedCallArg],
+}
+
+struct FunctionCalls<'a> {
+    module: &'a EcoString,
+    name: &'a EcoString,
+    calls: Vec<FunctionCall<'a>>,
+}
+
+impl FunctionCalls<'_> {
+    fn is_reference_to_function(&self, expression: &TypedExpr) -> bool {
+        match expression {
+            TypedExpr::Var { constructor, .. } => match &constructor.variant {
+                ValueConstructorVariant::ModuleFn { module, name, .. } => {
+                    module == self.module && name == self.name
+                }
+                _ => false,
+            },
+            TypedExpr::ModuleSelect {
+                constructor: ModuleValueConstructor::Fn { module, .. },
+                label,
+                ..
+            } => module == self.module && label == self.name,
+            _ => false,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for FunctionCalls<'ast> {
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        arguments: &'ast [TypedCallArg],
+    ) {
+        if self.is_reference_to_function(fun) {
+            self.calls.push(FunctionCall {
+                function: fun.location(),
+                arguments,
+            });
+        }
+        visit::visit_typed_expr_call(self, location, type_, fun, arguments);
+    }
+}
@@ -17,6 +17,7 @@ use crate::{
     language_server::edits,
     line_numbers::LineNumbers,
     parse::{extra::ModuleExtra, lexer::str_to_keyword},
+    reference::ReferenceKind,
     strings::to_snake_case,
     type_::{
         self, FieldMap, ModuleValueConstructor, Type, TypeVar, TypedCallArg, ValueConstructor,
@@ -27,16 +28,21 @@ use crate::{
 use ecow::{EcoString, eco_format};
 use im::HashMap;
 use itertools::Itertools;
-use lsp_types::{CodeAction, CodeActionKind, CodeActionParams, Position, Range, TextEdit, Url};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionParams, Command, Position, Range,
+    TextDocumentPositionParams, TextEdit, Url,
+};
 use vec1::{Vec1, vec1};
 
 use super::{
     TextEdits,
+    change_signature::{CHANGE_SIGNATURE_COMMAND, ChangeSignatureParams, NewParameter},
     compiler::LspProjectCompiler,
     edits::{add_newlines_after_import, get_import_edit, position_of_first_definition_if_import},
     engine::{overlaps, within},
     files::FileSystemProxy,
-    reference::{VariableReferenceKind, find_variable_references},
+    lsp_range_to_src_span,
+    reference::{VariableReference, VariableReferenceKind, find_variable_references},
     src_span_to_lsp_range, url_from_path,
 };
 
@@ -76,6 +82,13 @@ impl CodeActionBuilder {
         self
     }
 
+    /// The command run when the action is chosen, for actions whose edits
+    /// depend on what the client asks the user for.
+    pub fn command(mut self, command: Command) -> Self {
+        self.action.command = Some(command);
+        self
+    }
+
     pub fn preferred(mut self, is_preferred: bool) -> Self {
         self.action.is_preferred = Some(is_preferred);
         self
@@ -3541,69 +3554,308 @@ impl<'ast> ast::visit::Visit<'ast> for ExtractConstant<'ast> {
     }
 }
 
-/// Builder for code action to apply the "expand function capture" action.
+/// Builder for code action to extract the selected statements or expression
+/// into a new private function, replacing them with a call to it.
 ///
-pub struct ExpandFunctionCapture<'a> {
+/// ```gleam
+/// pub fn main() {
+///   let x = 1
+///   let y = x + 1
+///   let z = y * 2
+///   z + x
+/// }
+/// ```
+///
+/// Extracting the second and third lines becomes:
+///
+/// ```gleam
+/// pub fn main() {
+///   let x = 1
+///   let z = function(x)
+///   z + x
+/// }
+///
+/// fn function(x: Int) -> Int {
+///   let y = x + 1
+///   let z = y * 2
+///   z
+/// }
+/// ```
+///
+/// The variables defined outside of the selection are passed as arguments,
+/// and the ones defined inside of it and used after it are returned, in a
+/// tuple if there's more than one.
+///
+pub struct ExtractFunction<'a> {
     module: &'a Module,
     params: &'a CodeActionParams,
     edits: TextEdits<'a>,
-    function_capture_data: Option<FunctionCaptureData>,
+    /// The trimmed selection, code can only be extracted if it starts and ends
+    /// exactly where the selection does.
+    selection: Option<SrcSpan>,
+    current_function: Option<&'a ast::TypedFunction>,
+    extracted: Option<ExtractedCode<'a>>,
 }
 
-pub struct FunctionCaptureData {
-    function_span: SrcSpan,
-    hole_span: SrcSpan,
-    hole_type: Arc<Type>,
-    reserved_names: VariablesNames,
+struct ExtractedCode<'a> {
+    location: SrcSpan,
+    kind: ExtractedCodeKind<'a>,
+    function: &'a ast::TypedFunction,
 }
 
-impl<'a> ExpandFunctionCapture<'a> {
+enum ExtractedCodeKind<'a> {
+    Expression(&'a TypedExpr),
+    Statements(&'a [TypedStatement]),
+}
+
+/// A variable used in the extracted code.
+struct ExtractedVariable {
+    name: EcoString,
+    type_: Arc<Type>,
+    definition_location: SrcSpan,
+}
+
+impl<'a> ExtractFunction<'a> {
     pub fn new(
         module: &'a Module,
         line_numbers: &'a LineNumbers,
         params: &'a CodeActionParams,
     ) -> Self {
+        let selection = lsp_range_to_src_span(params.range, line_numbers);
+        let selection = module
+            .code
+            .get(selection.start as usize..selection.end as usize)
+            .and_then(|code| {
+                let start = selection.start + (code.len() - code.trim_start().len()) as u32;
+                let end = selection.end - (code.len() - code.trim_end().len()) as u32;
+                (start < end).then(|| SrcSpan::new(start, end))
+            });
+
         Self {
             module,
             params,
             edits: TextEdits::new(line_numbers),
-            function_capture_data: None,
+            selection,
+            current_function: None,
+            extracted: None,
         }
     }
 
     pub fn code_actions(mut self) -> Vec<CodeAction> {
+        if self.selection.is_none() {
+            return vec![];
+        }
         self.visit_typed_module(&self.module.ast);
 
-        let Some(FunctionCaptureData {
-            function_span,
-            hole_span,
-            hole_type,
-            reserved_names,
-        }) = self.function_capture_data
+        let Some(ExtractedCode {
+            location,
+            kind,
+            function,
+        }) = self.extracted.take()
         else {
             return vec![];
         };
 
+        let mut free_variables = FreeVariables::new(location);
+        match kind {
+            ExtractedCodeKind::Expression(expression) => {
+                free_variables.visit_typed_expr(expression)
+            }
+            ExtractedCodeKind::Statements(statements) => statements
+                .iter()
+                .for_each(|statement| free_variables.visit_typed_statement(statement)),
+        }
+        // Compiler generated variables such as the ones for the steps of a
+        // pipeline don't exist in the code, so they can't be passed as
+        // arguments.
+        if free_variables.uses_generated_variable {
+            return vec![];
+        }
+        let arguments = free_variables.variables;
+
+        let returned = match kind {
+            ExtractedCodeKind::Expression(_) => vec![],
+            ExtractedCodeKind::Statements(_) => {
+                let mut escaping = EscapingVariables::new(location);
+                escaping.visit_typed_function(function);
+                escaping.variables
+            }
+        };
+
         let mut name_generator = NameGenerator::new();
-        name_generator.reserve_variable_names(reserved_names);
-        let name = name_generator.generate_name_from_type(&hole_type);
+        self.reserve_module_names(&mut name_generator);
+        let name = name_generator.rename_to_avoid_shadowing("function".into());
 
-        self.edits.replace(hole_span, name.clone().into());
-        self.edits.insert(function_span.end, " }".into());
-        self.edits
-            .insert(function_span.start, format!("fn({name}) {{ "));
+        let mut printer = Printer::new(&self.module.ast.names);
+        let parameters = arguments
+            .iter()
+            .map(|argument| format!("{}: {}", argument.name, printer.print_type(&argument.type_)))
+            .join(", ");
+        let call = format!(
+            "{name}({})",
+            arguments.iter().map(|argument| &argument.name).join(", ")
+        );
+
+        let (return_type, returned_value, replacement) = match returned.as_slice() {
+            [] => {
+                let type_ = match kind {
+                    ExtractedCodeKind::Expression(expression) => expression.type_(),
+                    ExtractedCodeKind::Statements(statements) => statements
+                        .last()
+                        .map(|statement| statement.type_())
+                        .unwrap_or_else(type_::nil),
+                };
+                (printer.print_type(&type_), None, call)
+            }
+            [variable] => (
+                printer.print_type(&variable.type_),
+                Some(variable.name.clone()),
+                format!("let {} = {call}", variable.name),
+            ),
+            variables => {
+                let types = variables
+                    .iter()
+                    .map(|variable| printer.print_type(&variable.type_))
+                    .join(", ");
+                let names = variables.iter().map(|variable| &variable.name).join(", ");
+                (
+                    eco_format!("#({types})"),
+                    Some(eco_format!("#({names})")),
+                    format!("let #({names}) = {call}"),
+                )
+            }
+        };
+
+        let mut body = self.reindented_code(location);
+        if let Some(returned_value) = returned_value {
+            body.push_str(&format!("\n  {returned_value}"));
+        }
+
+        self.edits.replace(location, replacement);
+        self.edits.insert(
+            function.end_position,
+            format!("\n\nfn {name}({parameters}) -> {return_type} {{\n  {body}\n}}"),
+        );
 
         let mut action = Vec::with_capacity(1);
-        CodeActionBuilder::new("Expand function capture")
-            .kind(CodeActionKind::REFACTOR_REWRITE)
+        CodeActionBuilder::new("Extract function")
+            .kind(CodeActionKind::REFACTOR_EXTRACT)
             .changes(self.params.text_document.uri.clone(), self.edits.edits)
             .preferred(false)
             .push_to(&mut action);
         action
     }
+
+    fn reserve_module_names(&self, name_generator: &mut NameGenerator) {
+        for definition in &self.module.ast.definitions {
+            match definition {
+                ast::Definition::Function(ast::Function {
+                    name: Some((_, name)),
+                    ..
+                })
+                | ast::Definition::ModuleConstant(ast::ModuleConstant { name, .. }) => {
+                    name_generator.add_used_name(name.clone())
+                }
+                ast::Definition::Import(import) => import
+                    .unqualified_values
+                    .iter()
+                    .for_each(|value| name_generator.add_used_name(value.used_name().clone())),
+                ast::Definition::Function(_)
+                | ast::Definition::TypeAlias(_)
+                | ast::Definition::CustomType(_) => {}
+            }
+        }
+    }
+
+    /// The code at the given location, with the indentation of its lines
+    /// changed to be the one of the body of a top level function.
+    fn reindented_code(&self, location: SrcSpan) -> String {
+        let code = self
+            .module
+            .code
+            .get(location.start as usize..location.end as usize)
+            .expect("extracted code");
+        let line = self.edits.line_numbers.line_number(location.start) - 1;
+        let indentation = count_indentation(&self.module.code, self.edits.line_numbers, line);
+
+        code.lines()
+            .enumerate()
+            .map(|(index, line)| {
+                if index == 0 {
+                    return line.to_string();
+                }
+                let leading_spaces = line.len() - line.trim_start_matches(' ').len();
+                let line = line.get(leading_spaces.min(indentation)..).unwrap_or(line);
+                if line.is_empty() {
+                    String::new()
+                } else {
+                    format!("  {line}")
+                }
+            })
+            .join("\n")
+    }
+
+    fn try_extract_statements(&mut self, statements: &'a [TypedStatement]) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+        let Some(function) = self.current_function else {
+            return;
+        };
+
+        let first = statements
+            .iter()
+            .position(|statement| statement.location().start == selection.start);
+        let last = statements
+            .iter()
+            .position(|statement| statement.location().end == selection.end);
+        let selected = match (first, last) {
+            (Some(first), Some(last)) if first <= last => statements.get(first..=last),
+            _ => None,
+        };
+        let Some(selected) = selected else {
+            return;
+        };
+
+        // A `use` takes all the statements following it as its callback, so
+        // it can't be moved on its own.
+        if selected
+            .iter()
+            .any(|statement| matches!(statement, TypedStatement::Use(_)))
+        {
+            return;
+        }
+
+        self.extracted = Some(ExtractedCode {
+            location: selection,
+            kind: ExtractedCodeKind::Statements(selected),
+            function,
+        });
+    }
 }
 
-impl<'ast> ast::visit::Visit<'ast> for ExpandFunctionCapture<'ast> {
+impl<'ast> ast::visit::Visit<'ast> for ExtractFunction<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        let Some(selection) = self.selection else {
+            return;
+        };
+        if !fun.full_location().contains(selection.start) {
+            return;
+        }
+        self.current_function = Some(fun);
+        self.try_extract_statements(&fun.body);
+        ast::visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_block(
+        &mut self,
+        location: &'ast SrcSpan,
+        statements: &'ast [TypedStatement],
+    ) {
+        self.try_extract_statements(statements);
+        ast::visit::visit_typed_expr_block(self, location, statements);
+    }
+
     fn visit_typed_expr_fn(
         &mut self,
         location: &'ast SrcSpan,
@@ -3613,18 +3865,7 @@ impl<'ast> ast::visit::Visit<'ast> for ExpandFunctionCapture<'ast> {
         body: &'ast Vec1<TypedStatement>,
         return_annotation: &'ast Option<ast::TypeAst>,
     ) {
-        let fn_range = self.edits.src_span_to_lsp_range(*location);
-        if within(self.params.range, fn_range) && kind.is_capture() {
-            if let [argument] = arguments {
-                self.function_capture_data = Some(FunctionCaptureData {
-                    function_span: *location,
-                    hole_span: argument.location,
-                    hole_type: argument.type_.clone(),
-                    reserved_names: VariablesNames::from_statements(body),
-                });
-            }
-        }
-
+        self.try_extract_statements(body);
         ast::visit::visit_typed_expr_fn(
             self,
             location,
@@ -3633,78 +3874,364 @@ impl<'ast> ast::visit::Visit<'ast> for ExpandFunctionCapture<'ast> {
             arguments,
             body,
             return_annotation,
-        )
+        );
+    }
+
+    fn visit_typed_expr(&mut self, expr: &'ast TypedExpr) {
+        // If some statements or an expression containing this one are
+        // selected those are extracted.
+        if self.extracted.is_some() {
+            return;
+        }
+
+        match (self.selection, self.current_function) {
+            (Some(selection), Some(function))
+                if expr.location() == selection
+                    && !matches!(expr, TypedExpr::Var { .. } | TypedExpr::Invalid { .. }) =>
+            {
+                self.extracted = Some(ExtractedCode {
+                    location: selection,
+                    kind: ExtractedCodeKind::Expression(expr),
+                    function,
+                });
+            }
+            _ => ast::visit::visit_typed_expr(self, expr),
+        }
     }
 }
 
-struct VariablesNames {
-    names: HashSet<EcoString>,
+/// Finds the local variables used in some code which are defined outside of
+/// it, in the order they are first used.
+///
+struct FreeVariables {
+    location: SrcSpan,
+    variables: Vec<ExtractedVariable>,
+    uses_generated_variable: bool,
 }
 
-impl VariablesNames {
-    fn from_statements(statements: &[TypedStatement]) -> Self {
-        let mut variables = Self {
-            names: HashSet::new(),
-        };
+impl FreeVariables {
+    fn new(location: SrcSpan) -> Self {
+        Self {
+            location,
+            variables: vec![],
+            uses_generated_variable: false,
+        }
+    }
 
-        for statement in statements {
-            variables.visit_typed_statement(statement);
+    fn register(&mut self, name: &EcoString, type_: &Arc<Type>, definition_location: SrcSpan) {
+        if self.location.contains(definition_location.start)
+            || self
+                .variables
+                .iter()
+                .any(|variable| variable.definition_location == definition_location)
+        {
+            return;
         }
-        variables
+        self.variables.push(ExtractedVariable {
+            name: name.clone(),
+            type_: type_.clone(),
+            definition_location,
+        });
+    }
+
+    fn register_constructor(&mut self, name: &EcoString, constructor: &ValueConstructor) {
+        let type_::ValueConstructorVariant::LocalVariable { location, origin } =
+            &constructor.variant
+        else {
+            return;
+        };
+        if self.location.contains(location.start) {
+            return;
+        }
+        if let type_::error::VariableSyntax::Generated = origin.syntax {
+            self.uses_generated_variable = true;
+        }
+        self.register(name, &constructor.type_, *location);
     }
 }
 
-impl<'ast> ast::visit::Visit<'ast> for VariablesNames {
+impl<'ast> ast::visit::Visit<'ast> for FreeVariables {
     fn visit_typed_expr_var(
         &mut self,
         _location: &'ast SrcSpan,
-        _constructor: &'ast ValueConstructor,
+        constructor: &'ast ValueConstructor,
         name: &'ast EcoString,
     ) {
-        let _ = self.names.insert(name.clone());
+        self.register_constructor(name, constructor);
+    }
+
+    fn visit_typed_clause_guard_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        type_: &'ast Arc<Type>,
+        definition_location: &'ast SrcSpan,
+    ) {
+        self.register(name, type_, *definition_location);
+    }
+
+    fn visit_typed_bit_array_size_variable(
+        &mut self,
+        _location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        constructor: &'ast Option<Box<ValueConstructor>>,
+        _type_: &'ast Arc<Type>,
+    ) {
+        if let Some(constructor) = constructor {
+            self.register_constructor(name, constructor);
+        }
     }
 }
 
-/// Builder for code action to apply the "generate dynamic decoder action.
+/// Finds the local variables defined in some code which are used after it, in
+/// the order they are defined.
 ///
-pub struct GenerateDynamicDecoder<'a> {
-    module: &'a Module,
-    params: &'a CodeActionParams,
-    edits: TextEdits<'a>,
-    printer: Printer<'a>,
-    actions: &'a mut Vec<CodeAction>,
+struct EscapingVariables {
+    location: SrcSpan,
+    variables: Vec<ExtractedVariable>,
 }
 
-const DECODE_MODULE: &str = "gleam/dynamic/decode";
-
-impl<'a> GenerateDynamicDecoder<'a> {
-    pub fn new(
-        module: &'a Module,
-        line_numbers: &'a LineNumbers,
-        params: &'a CodeActionParams,
-        actions: &'a mut Vec<CodeAction>,
-    ) -> Self {
-        let printer = Printer::new(&module.ast.names);
+impl EscapingVariables {
+    fn new(location: SrcSpan) -> Self {
         Self {
-            module,
-            params,
-            edits: TextEdits::new(line_numbers),
-            printer,
-            actions,
+            location,
+            variables: vec![],
         }
     }
 
-    pub fn code_actions(&mut self) {
-        self.visit_typed_module(&self.module.ast);
+    fn register(
+        &mut self,
+        location: SrcSpan,
+        name: &EcoString,
+        type_: &Arc<Type>,
+        definition_location: SrcSpan,
+    ) {
+        if location.start < self.location.end
+            || !self.location.contains(definition_location.start)
+            || self
+                .variables
+                .iter()
+                .any(|variable| variable.definition_location == definition_location)
+        {
+            return;
+        }
+        self.variables.push(ExtractedVariable {
+            name: name.clone(),
+            type_: type_.clone(),
+            definition_location,
+        });
+        self.variables
+            .sort_by_key(|variable| variable.definition_location.start);
     }
+}
 
-    fn custom_type_decoder_body(
+impl<'ast> ast::visit::Visit<'ast> for EscapingVariables {
+    fn visit_typed_expr_var(
         &mut self,
-        custom_type: &CustomType<Arc<Type>>,
-    ) -> Option<EcoString> {
-        // We cannot generate a decoder for an external type with no constructors!
-        let constructors_size = custom_type.constructors.len();
-        let (first, rest) = custom_type.constructors.split_first()?;
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        if let type_::ValueConstructorVariant::LocalVariable {
+            location: definition_location,
+            ..
+        } = &constructor.variant
+        {
+            self.register(*location, name, &constructor.type_, *definition_location);
+        }
+    }
+
+    fn visit_typed_clause_guard_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        type_: &'ast Arc<Type>,
+        definition_location: &'ast SrcSpan,
+    ) {
+        self.register(*location, name, type_, *definition_location);
+    }
+
+    fn visit_typed_bit_array_size_variable(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        constructor: &'ast Option<Box<ValueConstructor>>,
+        type_: &'ast Arc<Type>,
+    ) {
+        if let Some(type_::ValueConstructorVariant::LocalVariable {
+            location: definition_location,
+            ..
+        }) = constructor.as_ref().map(|constructor| &constructor.variant)
+        {
+            self.register(*location, name, type_, *definition_location);
+        }
+    }
+}
+
+/// Builder for code action to apply the "expand function capture" action.
+///
+pub struct ExpandFunctionCapture<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    edits: TextEdits<'a>,
+    function_capture_data: Option<FunctionCaptureData>,
+}
+
+pub struct FunctionCaptureData {
+    function_span: SrcSpan,
+    hole_span: SrcSpan,
+    hole_type: Arc<Type>,
+    reserved_names: VariablesNames,
+}
+
+impl<'a> ExpandFunctionCapture<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+    ) -> Self {
+        Self {
+            module,
+            params,
+            edits: TextEdits::new(line_numbers),
+            function_capture_data: None,
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(&self.module.ast);
+
+        let Some(FunctionCaptureData {
+            function_span,
+            hole_span,
+            hole_type,
+            reserved_names,
+        }) = self.function_capture_data
+        else {
+            return vec![];
+        };
+
+        let mut name_generator = NameGenerator::new();
+        name_generator.reserve_variable_names(reserved_names);
+        let name = name_generator.generate_name_from_type(&hole_type);
+
+        self.edits.replace(hole_span, name.clone().into());
+        self.edits.insert(function_span.end, " }".into());
+        self.edits
+            .insert(function_span.start, format!("fn({name}) {{ "));
+
+        let mut action = Vec::with_capacity(1);
+        CodeActionBuilder::new("Expand function capture")
+            .kind(CodeActionKind::REFACTOR_REWRITE)
+            .changes(self.params.text_document.uri.clone(), self.edits.edits)
+            .preferred(false)
+            .push_to(&mut action);
+        action
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for ExpandFunctionCapture<'ast> {
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        arguments: &'ast [TypedArg],
+        body: &'ast Vec1<TypedStatement>,
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        let fn_range = self.edits.src_span_to_lsp_range(*location);
+        if within(self.params.range, fn_range) && kind.is_capture() {
+            if let [argument] = arguments {
+                self.function_capture_data = Some(FunctionCaptureData {
+                    function_span: *location,
+                    hole_span: argument.location,
+                    hole_type: argument.type_.clone(),
+                    reserved_names: VariablesNames::from_statements(body),
+                });
+            }
+        }
+
+        ast::visit::visit_typed_expr_fn(
+            self,
+            location,
+            type_,
+            kind,
+            arguments,
+            body,
+            return_annotation,
+        )
+    }
+}
+
+struct VariablesNames {
+    names: HashSet<EcoString>,
+}
+
+impl VariablesNames {
+    fn from_statements(statements: &[TypedStatement]) -> Self {
+        let mut variables = Self {
+            names: HashSet::new(),
+        };
+
+        for statement in statements {
+            variables.visit_typed_statement(statement);
+        }
+        variables
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for VariablesNames {
+    fn visit_typed_expr_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let _ = self.names.insert(name.clone());
+    }
+}
+
+/// Builder for code action to apply the "generate dynamic decoder action.
+///
+pub struct GenerateDynamicDecoder<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    edits: TextEdits<'a>,
+    printer: Printer<'a>,
+    actions: &'a mut Vec<CodeAction>,
+}
+
+const DECODE_MODULE: &str = "gleam/dynamic/decode";
+
+impl<'a> GenerateDynamicDecoder<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+        actions: &'a mut Vec<CodeAction>,
+    ) -> Self {
+        let printer = Printer::new(&module.ast.names);
+        Self {
+            module,
+            params,
+            edits: TextEdits::new(line_numbers),
+            printer,
+            actions,
+        }
+    }
+
+    pub fn code_actions(&mut self) {
+        self.visit_typed_module(&self.module.ast);
+    }
+
+    fn custom_type_decoder_body(
+        &mut self,
+        custom_type: &CustomType<Arc<Type>>,
+    ) -> Option<EcoString> {
+        // We cannot generate a decoder for an external type with no constructors!
+        let constructors_size = custom_type.constructors.len();
+        let (first, rest) = custom_type.constructors.split_first()?;
         let mode = EncodingMode::for_custom_type(custom_type);
 
         // We generate a decoder for a type with a single constructor: it does not
@@ -4493,7 +5020,7 @@ pub enum PatternMatchedValue<'a> {
 
 impl<'a, IO> PatternMatchOnValue<'a, IO>
 where
-    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone,
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone + Send + Sync,
 {
     pub fn new(
         module: &'a Module,
@@ -4783,7 +5310,7 @@ where
 
 impl<'ast, IO> ast::visit::Visit<'ast> for PatternMatchOnValue<'ast, IO>
 where
-    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone,
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone + Send + Sync,
 {
     fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
         // If we're not inside the function there's no point in exploring its
@@ -4936,7 +5463,7 @@ fn get_type_constructors<'a, 'b, IO>(
     type_name: &'b EcoString,
 ) -> Vec<&'a ValueConstructor>
 where
-    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone,
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone + Send + Sync,
 {
     let type_is_inside_current_module = current_module == type_module;
     let module_interface = if !type_is_inside_current_module {
@@ -5288,7 +5815,7 @@ impl Argument<'_> {
 
 impl<'a, IO> GenerateVariant<'a, IO>
 where
-    IO: FileSystemReader + FileSystemWriter + BeamCompiler + CommandExecutor + Clone,
+    IO: FileSystemReader + FileSystemWriter + BeamCompiler + CommandExecutor + Clone + Send + Sync,
 {
     pub fn new(
         module: &'a Module,
@@ -5474,7 +6001,7 @@ where
 
 impl<'ast, IO> ast::visit::Visit<'ast> for GenerateVariant<'ast, IO>
 where
-    IO: FileSystemReader + FileSystemWriter + BeamCompiler + CommandExecutor + Clone,
+    IO: FileSystemReader + FileSystemWriter + BeamCompiler + CommandExecutor + Clone + Send + Sync,
 {
     fn visit_typed_expr_invalid(&mut self, location: &'ast SrcSpan, type_: &'ast Arc<Type>) {
         let invalid_range = src_span_to_lsp_range(*location, self.line_numbers);
@@ -6040,63 +6567,47 @@ impl<'ast> ast::visit::Visit<'ast> for InlineVariable<'ast> {
     }
 }
 
-/// Builder for the "convert to pipe" code action.
+/// Builder for code action to inline a function defined in the current
+/// module, either at the call under the cursor or at all of its calls.
 ///
 /// ```gleam
 /// pub fn main() {
-///   wibble(wobble, woo)
-///  //  ^ [convert to pipe]
+///   double(1 + 2)
+///   // ^ [inline function call]
+/// }
+///
+/// fn double(x) {
+///   x * 2
 /// }
 /// ```
 ///
-/// Will turn the code into the following pipeline:
+/// Becomes:
 ///
 /// ```gleam
 /// pub fn main() {
-///   wobble |> wibble(woo)
+///   {
+///     let x = 1 + 2
+///     x * 2
+///   }
 /// }
 /// ```
 ///
-pub struct ConvertToPipe<'a> {
+/// Arguments that are literals or variables take the place of the parameters
+/// of the function. Any other argument is assigned to a variable first, so it
+/// is still evaluated once and before the body of the function, even if it has
+/// side effects. Once no references to a private function are left its
+/// definition is removed.
+///
+pub struct InlineFunction<'a> {
     module: &'a Module,
     params: &'a CodeActionParams,
-    edits: TextEdits<'a>,
-    argument_to_pipe: Option<ConvertToPipeArg<'a>>,
-    /// this will be true if we're visiting the call on the right hand side of a
-    /// use expression. So we can skip it and not try to turn it into a
-    /// function.
-    visiting_use_call: bool,
-}
-
-/// Holds all the data needed by the "convert to pipe" code action to properly
-/// rewrite a call into a pipe. Here's what each span means:
-///
-/// ```gleam
-///    wibble(wobb|le, woo)
-/// // ^^^^^^^^^^^^^^^^^^^^ call
-/// // ^^^^^^ called
-/// //        ^^^^^^^ arg
-/// //                 ^^^ next arg
-/// ```
-///
-/// In this example `position` is 0, since the cursor is over the first
-/// argument.
-///
-pub struct ConvertToPipeArg<'a> {
-    /// The span of the called function.
-    called: SrcSpan,
-    /// The span of the entire function call.
-    call: SrcSpan,
-    /// The position (0-based) of the argument.
-    position: usize,
-    /// The argument we have to pipe.
-    arg: &'a TypedCallArg,
-    /// The span of the argument following the one we have to pipe, if there's
-    /// any.
-    next_arg: Option<SrcSpan>,
+    line_numbers: &'a LineNumbers,
+    /// The name of the function under the cursor, and the location of the
+    /// reference to it if the cursor is not over its definition.
+    target: Option<(EcoString, Option<SrcSpan>)>,
 }
 
-impl<'a> ConvertToPipe<'a> {
+impl<'a> InlineFunction<'a> {
     pub fn new(
         module: &'a Module,
         line_numbers: &'a LineNumbers,
@@ -6105,30 +6616,856 @@ impl<'a> ConvertToPipe<'a> {
         Self {
             module,
             params,
-            edits: TextEdits::new(line_numbers),
-            visiting_use_call: false,
-            argument_to_pipe: None,
+            line_numbers,
+            target: None,
         }
     }
 
     pub fn code_actions(mut self) -> Vec<CodeAction> {
         self.visit_typed_module(&self.module.ast);
-
-        let Some(ConvertToPipeArg {
-            called,
-            call,
-            position,
-            arg,
-            next_arg,
-        }) = self.argument_to_pipe
-        else {
+        let Some((name, selected_reference)) = self.target.take() else {
             return vec![];
         };
 
-        let arg_range = if arg.uses_label_shorthand() {
-            arg.location.start as usize..arg.location.end as usize - 1
-        } else if arg.label.is_some() {
-            let value = arg.value.location();
+        let Some(function) =
+            self.module
+                .ast
+                .definitions
+                .iter()
+                .find_map(|definition| match definition {
+                    ast::Definition::Function(function)
+                        if function
+                            .name
+                            .as_ref()
+                            .is_some_and(|(_, function_name)| *function_name == name) =>
+                    {
+                        Some(function)
+                    }
+                    _ => None,
+                })
+        else {
+            return vec![];
+        };
+
+        // The body of an external function is only used on the targets it
+        // has no external implementation for.
+        if function.external_erlang.is_some() || function.external_javascript.is_some() {
+            return vec![];
+        }
+
+        let references = self
+            .module
+            .ast
+            .type_info
+            .references
+            .value_references
+            .get(&(self.module.name.clone(), name.clone()))
+            .map(|references| {
+                references
+                    .iter()
+                    .filter(|reference| match reference.kind {
+                        ReferenceKind::Qualified | ReferenceKind::Unqualified => true,
+                        ReferenceKind::Import
+                        | ReferenceKind::Definition
+                        | ReferenceKind::Alias => false,
+                    })
+                    .map(|reference| reference.location)
+                    .collect_vec()
+            })
+            .unwrap_or_default();
+
+        // A recursive function would have to be inlined into itself.
+        if references
+            .iter()
+            .any(|reference| function.full_location().contains(reference.start))
+        {
+            return vec![];
+        }
+
+        let mut finder = FunctionCalls::new(&self.module.name, &name);
+        finder.visit_typed_module(&self.module.ast);
+        // A call nested in the arguments of another one can't be inlined at
+        // the same time as it.
+        let calls = finder
+            .calls
+            .iter()
+            .filter(|call| references.contains(&call.reference))
+            .filter(|call| {
+                !finder.calls.iter().any(|other| {
+                    other.location != call.location
+                        && other.location.start <= call.location.start
+                        && call.location.end <= other.location.end
+                })
+            })
+            .collect_vec();
+
+        let mut actions = vec![];
+        let selected_call = selected_reference
+            .and_then(|reference| calls.iter().find(|call| call.reference == reference));
+        if let Some(call) = selected_call {
+            self.push_action(
+                "Inline function call",
+                function,
+                &[call],
+                references.len(),
+                &finder.atomic_positions,
+                &mut actions,
+            );
+        }
+        if calls.len() > 1 || (selected_reference.is_none() && !calls.is_empty()) {
+            self.push_action(
+                "Inline all function calls",
+                function,
+                &calls,
+                references.len(),
+                &finder.atomic_positions,
+                &mut actions,
+            );
+        }
+        actions
+    }
+
+    fn push_action(
+        &self,
+        title: &str,
+        function: &ast::TypedFunction,
+        calls: &[&FunctionCall<'_>],
+        references: usize,
+        atomic_positions: &[SrcSpan],
+        actions: &mut Vec<CodeAction>,
+    ) {
+        let mut edits = TextEdits::new(self.line_numbers);
+        let mut inlined = 0;
+        for call in calls {
+            let Some(code) = self.inline_call(function, call, atomic_positions) else {
+                continue;
+            };
+            edits.replace(call.location, code);
+            inlined += 1;
+        }
+        if inlined == 0 {
+            return;
+        }
+
+        if inlined == references && function.publicity.is_private() {
+            edits.delete(self.definition_location(function));
+        }
+
+        CodeActionBuilder::new(title)
+            .kind(CodeActionKind::REFACTOR_INLINE)
+            .changes(self.params.text_document.uri.clone(), edits.edits)
+            .preferred(false)
+            .push_to(actions);
+    }
+
+    /// The location of the definition of a function, including its
+    /// documentation and the empty lines separating it from the code before
+    /// it.
+    ///
+    fn definition_location(&self, function: &ast::TypedFunction) -> SrcSpan {
+        // The documentation starts after the first `///`, so the whole line
+        // it's on is removed.
+        let start = match &function.documentation {
+            Some((start, _)) => self
+                .module
+                .code
+                .get(..*start as usize)
+                .and_then(|before| before.rfind('\n'))
+                .map_or(0, |newline| newline as u32 + 1),
+            None => function.location.start,
+        };
+        let before = self.module.code.get(..start as usize).unwrap_or_default();
+        let trimmed_start = before.trim_end().len() as u32;
+        if trimmed_start > 0 {
+            return SrcSpan::new(trimmed_start, function.end_position);
+        }
+
+        // If it's the first definition of the module, the empty lines after
+        // it are removed instead.
+        let after = self
+            .module
+            .code
+            .get(function.end_position as usize..)
+            .unwrap_or_default();
+        let whitespace = after.len() - after.trim_start().len();
+        SrcSpan::new(start, function.end_position + whitespace as u32)
+    }
+
+    /// The code taking the place of a call to the function.
+    ///
+    fn inline_call(
+        &self,
+        function: &ast::TypedFunction,
+        call: &FunctionCall<'_>,
+        atomic_positions: &[SrcSpan],
+    ) -> Option<String> {
+        let code = &self.module.code;
+
+        let mut argument_variables = CodeVariables::default();
+        for argument in call.arguments {
+            argument_variables.visit_typed_expr(&argument.value);
+        }
+        let mut body_variables = CodeVariables::default();
+        for statement in &function.body {
+            body_variables.visit_typed_statement(statement);
+        }
+
+        // The module values used by the function could be shadowed by the
+        // variables of the caller. Checking all the ones defined before the
+        // call is stricter than needed, but doesn't require tracking scopes.
+        let mut caller_variables = CodeVariables::default();
+        caller_variables.add_arguments(&call.caller.arguments);
+        caller_variables.visit_typed_function(call.caller);
+        let is_shadowed = caller_variables.definitions.iter().any(|(location, name)| {
+            location.start < call.location.start && body_variables.module_values.contains(name)
+        });
+        if is_shadowed {
+            return None;
+        }
+
+        let mut names = NameGenerator::new();
+        argument_variables
+            .referenced
+            .iter()
+            .chain(&body_variables.module_values)
+            .chain(body_variables.definitions.iter().map(|(_, name)| name))
+            .for_each(|name| names.add_used_name(name.clone()));
+
+        let body_start = function.body.first().location().start;
+        let body_end = code
+            .get(body_start as usize..function.end_position as usize - 1)?
+            .trim_end()
+            .len() as u32
+            + body_start;
+        let mut replacements = vec![];
+
+        // The variables of the function which have the same name as a variable
+        // used by the arguments are renamed, so they don't shadow it.
+        for (location, name) in &body_variables.definitions {
+            if !argument_variables.referenced.contains(name) {
+                continue;
+            }
+            let new_name = names.rename_to_avoid_shadowing(name.clone());
+            if code
+                .get(location.start as usize..location.end as usize)?
+                .ends_with(':')
+            {
+                replacements.push((
+                    SrcSpan::new(location.end, location.end),
+                    format!(" {new_name}"),
+                ));
+            } else {
+                replacements.push((*location, new_name.to_string()));
+            }
+            rename_references(
+                &self.module.ast,
+                *location,
+                name,
+                &new_name,
+                &mut replacements,
+            );
+        }
+
+        let mut bindings = vec![];
+        let mut capture_argument = None;
+        for (parameter, argument) in function.arguments.iter().zip(call.arguments) {
+            let parameter_name = match &parameter.names {
+                ArgNames::Named { name, location }
+                | ArgNames::NamedLabelled {
+                    name,
+                    name_location: location,
+                    ..
+                } => Some((*location, name)),
+                ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => None,
+            };
+            let references = parameter_name
+                .map(|(location, name)| {
+                    find_variable_references(&self.module.ast, location, name.clone())
+                })
+                .unwrap_or_default();
+
+            // A parameter with the same name as a variable used by the
+            // arguments would shadow it.
+            let name = parameter_name.map(|(_, name)| {
+                if argument_variables.referenced.contains(name) {
+                    names.rename_to_avoid_shadowing(name.clone())
+                } else {
+                    names.add_used_name(name.clone());
+                    name.clone()
+                }
+            });
+
+            let is_hole = call.is_capture
+                && matches!(&argument.value, TypedExpr::Var { name, .. } if name == ast::CAPTURE_VARIABLE);
+            let value = if is_hole {
+                let name = name.clone().unwrap_or_else(|| "_".into());
+                capture_argument = Some(name.clone());
+                name.to_string()
+            } else if argument.uses_label_shorthand() {
+                argument.label.clone()?.to_string()
+            } else {
+                let location = argument.value.location();
+                code.get(location.start as usize..location.end as usize)?
+                    .to_string()
+            };
+
+            // Only literals and variables can take the place of the parameter:
+            // evaluating them has no effects, so it doesn't matter how many
+            // times or in which order it happens.
+            let is_simple = is_hole
+                || matches!(
+                    argument.value,
+                    TypedExpr::Var { .. }
+                        | TypedExpr::Int { .. }
+                        | TypedExpr::Float { .. }
+                        | TypedExpr::String { .. }
+                );
+            if is_simple {
+                for reference in &references {
+                    push_reference_replacement(reference, value.clone(), &mut replacements);
+                }
+                continue;
+            }
+
+            match (parameter_name, name) {
+                (Some((_, original_name)), Some(name)) if !references.is_empty() => {
+                    bindings.push(format!("let {name} = {value}"));
+                    if *original_name != name {
+                        for reference in &references {
+                            push_reference_replacement(
+                                reference,
+                                name.to_string(),
+                                &mut replacements,
+                            );
+                        }
+                    }
+                }
+                _ => bindings.push(format!("let _ = {value}")),
+            }
+        }
+
+        let mut body = String::new();
+        let mut position = body_start;
+        for (location, replacement) in replacements
+            .into_iter()
+            .sorted_by_key(|(location, _)| location.start)
+        {
+            body.push_str(code.get(position as usize..location.start as usize)?);
+            body.push_str(&replacement);
+            position = location.end;
+        }
+        body.push_str(code.get(position as usize..body_end as usize)?);
+
+        let body_indentation = count_indentation(
+            code,
+            self.line_numbers,
+            self.line_numbers.line_number(body_start) - 1,
+        );
+        let call_indentation = count_indentation(
+            code,
+            self.line_numbers,
+            self.line_numbers.line_number(call.location.start) - 1,
+        );
+        let lines = body
+            .lines()
+            .map(|line| {
+                let leading_spaces = line.len() - line.trim_start_matches(' ').len();
+                line.get(leading_spaces.min(body_indentation)..)
+                    .unwrap_or(line)
+            })
+            .collect_vec();
+
+        let inlined = match (bindings.as_slice(), function.body.as_slice()) {
+            ([], [TypedStatement::Expression(expression)]) => {
+                let indentation = " ".repeat(call_indentation);
+                let code = lines
+                    .iter()
+                    .enumerate()
+                    .map(|(index, line)| match index {
+                        0 => line.to_string(),
+                        _ if line.is_empty() => String::new(),
+                        _ => format!("{indentation}{line}"),
+                    })
+                    .join("\n");
+                let is_atomic = !matches!(
+                    expression,
+                    TypedExpr::BinOp { .. } | TypedExpr::Pipeline { .. }
+                );
+                match &capture_argument {
+                    Some(_) if lines.len() == 1 => format!("{{ {code} }}"),
+                    Some(_) => block(&lines, call_indentation),
+                    None if is_atomic || !atomic_positions.contains(&call.location) => {
+                        return Some(code);
+                    }
+                    None => format!("{{ {code} }}"),
+                }
+            }
+            _ => {
+                let lines = bindings
+                    .iter()
+                    .map(String::as_str)
+                    .chain(lines)
+                    .collect_vec();
+                block(&lines, call_indentation)
+            }
+        };
+
+        match capture_argument {
+            Some(argument) => Some(format!("fn({argument}) {inlined}")),
+            None => Some(inlined),
+        }
+    }
+}
+
+/// A block with the given lines, indented one level more than the code it is
+/// in.
+///
+fn block(lines: &[&str], indentation: usize) -> String {
+    let inner_indentation = " ".repeat(indentation + 2);
+    let lines = lines
+        .iter()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{inner_indentation}{line}")
+            }
+        })
+        .join("\n");
+    format!("{{\n{lines}\n{}}}", " ".repeat(indentation))
+}
+
+fn push_reference_replacement(
+    reference: &VariableReference,
+    value: String,
+    replacements: &mut Vec<(SrcSpan, String)>,
+) {
+    match reference.kind {
+        VariableReferenceKind::Variable => replacements.push((reference.location, value)),
+        VariableReferenceKind::LabelShorthand => replacements.push((
+            SrcSpan::new(reference.location.end, reference.location.end),
+            format!(" {value}"),
+        )),
+    }
+}
+
+fn rename_references(
+    module: &ast::TypedModule,
+    definition_location: SrcSpan,
+    name: &EcoString,
+    new_name: &EcoString,
+    replacements: &mut Vec<(SrcSpan, String)>,
+) {
+    for reference in find_variable_references(module, definition_location, name.clone()) {
+        push_reference_replacement(&reference, new_name.to_string(), replacements);
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for InlineFunction<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        match &fun.name {
+            Some((location, name))
+                if overlaps(
+                    self.params.range,
+                    src_span_to_lsp_range(*location, self.line_numbers),
+                ) =>
+            {
+                self.target = Some((name.clone(), None));
+            }
+            _ => ast::visit::visit_typed_function(self, fun),
+        }
+    }
+
+    fn visit_typed_expr_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        match &constructor.variant {
+            type_::ValueConstructorVariant::ModuleFn { module, .. }
+                if *module == self.module.name
+                    && overlaps(
+                        self.params.range,
+                        src_span_to_lsp_range(*location, self.line_numbers),
+                    ) =>
+            {
+                self.target = Some((name.clone(), Some(*location)));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A call to a function that can be inlined.
+///
+struct FunctionCall<'a> {
+    /// The location of the code replaced by the body of the function.
+    location: SrcSpan,
+    /// The location of the reference to the function.
+    reference: SrcSpan,
+    /// The arguments of the call, in the same order as the parameters of the
+    /// function.
+    arguments: &'a [TypedCallArg],
+    /// Whether the call is a function capture, like `wibble(_, 1)`.
+    is_capture: bool,
+    /// The function the call is in.
+    caller: &'a ast::TypedFunction,
+}
+
+/// Finds the calls to a module function which can be inlined, and the
+/// positions where a non-atomic expression would have to be wrapped in a
+/// block.
+///
+struct FunctionCalls<'a> {
+    module: &'a EcoString,
+    name: &'a EcoString,
+    current_function: Option<&'a ast::TypedFunction>,
+    calls: Vec<FunctionCall<'a>>,
+    /// The locations of the operands of binary operators, of the records and
+    /// tuples being accessed, and of the functions being called.
+    atomic_positions: Vec<SrcSpan>,
+}
+
+impl<'a> FunctionCalls<'a> {
+    fn new(module: &'a EcoString, name: &'a EcoString) -> Self {
+        Self {
+            module,
+            name,
+            current_function: None,
+            calls: vec![],
+            atomic_positions: vec![],
+        }
+    }
+
+    fn is_reference_to_function(&self, expression: &TypedExpr) -> bool {
+        match expression {
+            TypedExpr::Var {
+                constructor:
+                    ValueConstructor {
+                        variant: type_::ValueConstructorVariant::ModuleFn { module, name, .. },
+                        ..
+                    },
+                ..
+            } => module == self.module && name == self.name,
+            _ => false,
+        }
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for FunctionCalls<'ast> {
+    fn visit_typed_function(&mut self, fun: &'ast ast::TypedFunction) {
+        self.current_function = Some(fun);
+        ast::visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        arguments: &'ast [TypedCallArg],
+    ) {
+        // Calls with arguments added by a pipe or `use` can't be replaced as
+        // a whole.
+        let has_implicit_arguments = arguments.iter().any(|argument| argument.implicit.is_some());
+        match self.current_function {
+            Some(caller) if !has_implicit_arguments && self.is_reference_to_function(fun) => {
+                self.calls.push(FunctionCall {
+                    location: *location,
+                    reference: fun.location(),
+                    arguments,
+                    is_capture: false,
+                    caller,
+                });
+            }
+            _ => {}
+        }
+        self.atomic_positions.push(fun.location());
+        ast::visit::visit_typed_expr_call(self, location, type_, fun, arguments);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        arguments: &'ast [TypedArg],
+        body: &'ast Vec1<TypedStatement>,
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        match (kind, body.as_slice(), self.current_function) {
+            (
+                FunctionLiteralKind::Capture { .. },
+                [
+                    TypedStatement::Expression(TypedExpr::Call {
+                        fun,
+                        arguments: call_arguments,
+                        ..
+                    }),
+                ],
+                Some(caller),
+            ) if self.is_reference_to_function(fun) => {
+                self.calls.push(FunctionCall {
+                    location: *location,
+                    reference: fun.location(),
+                    arguments: call_arguments,
+                    is_capture: true,
+                    caller,
+                });
+                for argument in call_arguments {
+                    self.visit_typed_expr(&argument.value);
+                }
+            }
+            _ => ast::visit::visit_typed_expr_fn(
+                self,
+                location,
+                type_,
+                kind,
+                arguments,
+                body,
+                return_annotation,
+            ),
+        }
+    }
+
+    fn visit_typed_expr_bin_op(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        name: &'ast ast::BinOp,
+        name_location: &'ast SrcSpan,
+        left: &'ast TypedExpr,
+        right: &'ast TypedExpr,
+    ) {
+        self.atomic_positions.push(left.location());
+        self.atomic_positions.push(right.location());
+        ast::visit::visit_typed_expr_bin_op(
+            self,
+            location,
+            type_,
+            name,
+            name_location,
+            left,
+            right,
+        );
+    }
+
+    fn visit_typed_expr_record_access(
+        &mut self,
+        location: &'ast SrcSpan,
+        field_start: &'ast u32,
+        type_: &'ast Arc<Type>,
+        label: &'ast EcoString,
+        index: &'ast u64,
+        record: &'ast TypedExpr,
+    ) {
+        self.atomic_positions.push(record.location());
+        ast::visit::visit_typed_expr_record_access(
+            self,
+            location,
+            field_start,
+            type_,
+            label,
+            index,
+            record,
+        );
+    }
+
+    fn visit_typed_expr_tuple_index(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        index: &'ast u64,
+        tuple: &'ast TypedExpr,
+    ) {
+        self.atomic_positions.push(tuple.location());
+        ast::visit::visit_typed_expr_tuple_index(self, location, type_, index, tuple);
+    }
+}
+
+/// The variables defined and used in some code.
+///
+#[derive(Default)]
+struct CodeVariables {
+    /// The location and name of each variable defined in the code.
+    definitions: Vec<(SrcSpan, EcoString)>,
+    /// The names of all the values referenced by the code.
+    referenced: HashSet<EcoString>,
+    /// The names of the module values referenced by the code.
+    module_values: HashSet<EcoString>,
+}
+
+impl CodeVariables {
+    fn add_arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            match &argument.names {
+                ArgNames::Named { name, location }
+                | ArgNames::NamedLabelled {
+                    name,
+                    name_location: location,
+                    ..
+                } => self.definitions.push((*location, name.clone())),
+                ArgNames::Discard { .. } | ArgNames::LabelledDiscard { .. } => {}
+            }
+        }
+    }
+}
+
+impl<'ast> ast::visit::Visit<'ast> for CodeVariables {
+    fn visit_typed_expr_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        let _ = self.referenced.insert(name.clone());
+        if !constructor.is_local_variable() {
+            let _ = self.module_values.insert(name.clone());
+        }
+    }
+
+    fn visit_typed_clause_guard_var(
+        &mut self,
+        _location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        _type_: &'ast Arc<Type>,
+        _definition_location: &'ast SrcSpan,
+    ) {
+        let _ = self.referenced.insert(name.clone());
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        arguments: &'ast [TypedArg],
+        body: &'ast Vec1<TypedStatement>,
+        return_annotation: &'ast Option<ast::TypeAst>,
+    ) {
+        self.add_arguments(arguments);
+        ast::visit::visit_typed_expr_fn(
+            self,
+            location,
+            type_,
+            kind,
+            arguments,
+            body,
+            return_annotation,
+        );
+    }
+
+    fn visit_typed_pattern_variable(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        _type_: &'ast Arc<Type>,
+        _origin: &'ast VariableOrigin,
+    ) {
+        self.definitions.push((*location, name.clone()));
+    }
+
+    fn visit_typed_pattern_assign(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        pattern: &'ast TypedPattern,
+    ) {
+        self.definitions.push((*location, name.clone()));
+        ast::visit::visit_typed_pattern_assign(self, location, name, pattern);
+    }
+}
+
+/// Builder for the "convert to pipe" code action.
+///
+/// ```gleam
+/// pub fn main() {
+///   wibble(wobble, woo)
+///  //  ^ [convert to pipe]
+/// }
+/// ```
+///
+/// Will turn the code into the following pipeline:
+///
+/// ```gleam
+/// pub fn main() {
+///   wobble |> wibble(woo)
+/// }
+/// ```
+///
+pub struct ConvertToPipe<'a> {
+    module: &'a Module,
+    params: &'a CodeActionParams,
+    edits: TextEdits<'a>,
+    argument_to_pipe: Option<ConvertToPipeArg<'a>>,
+    /// this will be true if we're visiting the call on the right hand side of a
+    /// use expression. So we can skip it and not try to turn it into a
+    /// function.
+    visiting_use_call: bool,
+}
+
+/// Holds all the data needed by the "convert to pipe" code action to properly
+/// rewrite a call into a pipe. Here's what each span means:
+///
+/// ```gleam
+///    wibble(wobb|le, woo)
+/// // ^^^^^^^^^^^^^^^^^^^^ call
+/// // ^^^^^^ called
+/// //        ^^^^^^^ arg
+/// //                 ^^^ next arg
+/// ```
+///
+/// In this example `position` is 0, since the cursor is over the first
+/// argument.
+///
+pub struct ConvertToPipeArg<'a> {
+    /// The span of the called function.
+    called: SrcSpan,
+    /// The span of the entire function call.
+    call: SrcSpan,
+    /// The position (0-based) of the argument.
+    position: usize,
+    /// The argument we have to pipe.
+    arg: &'a TypedCallArg,
+    /// The span of the argument following the one we have to pipe, if there's
+    /// any.
+    next_arg: Option<SrcSpan>,
+}
+
+impl<'a> ConvertToPipe<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+    ) -> Self {
+        Self {
+            module,
+            params,
+            edits: TextEdits::new(line_numbers),
+            visiting_use_call: false,
+            argument_to_pipe: None,
+        }
+    }
+
+    pub fn code_actions(mut self) -> Vec<CodeAction> {
+        self.visit_typed_module(&self.module.ast);
+
+        let Some(ConvertToPipeArg {
+            called,
+            call,
+            position,
+            arg,
+            next_arg,
+        }) = self.argument_to_pipe
+        else {
+            return vec![];
+        };
+
+        let arg_range = if arg.uses_label_shorthand() {
+            arg.location.start as usize..arg.location.end as usize - 1
+        } else if arg.label.is_some() {
+            let value = arg.value.location();
             value.start as usize..value.end as usize
         } else {
             arg.location.start as usize..arg.location.end as usize
@@ -7499,3 +8836,81 @@ impl<'ast> ast::visit::Visit<'ast> for RemoveBlock<'ast> {
         ast::visit::visit_typed_expr_block(self, location, statements);
     }
 }
+
+/// Code action to change the parameters of a function, offered on the head of
+/// its definition. The new parameters are picked by the client, so rather than
+/// edits the action carries the `gleam.changeSignature` command, with the
+/// current parameters of the function as the ones to start from.
+///
+pub struct ChangeSignature<'a> {
+    module: &'a Module,
+    line_numbers: &'a LineNumbers,
+    params: &'a CodeActionParams,
+}
+
+impl<'a> ChangeSignature<'a> {
+    pub fn new(
+        module: &'a Module,
+        line_numbers: &'a LineNumbers,
+        params: &'a CodeActionParams,
+    ) -> Self {
+        Self {
+            module,
+            line_numbers,
+            params,
+        }
+    }
+
+    pub fn code_actions(self) -> Vec<CodeAction> {
+        let Some(function) = self.module.ast.definitions.iter().find_map(|definition| {
+            let ast::Definition::Function(function) = definition else {
+                return None;
+            };
+            let head = src_span_to_lsp_range(function.location, self.line_numbers);
+            overlaps(head, self.params.range).then_some(function)
+        }) else {
+            return vec![];
+        };
+
+        // The signature of external functions can't be changed, as their
+        // implementations would have to change too.
+        if function.external_erlang.is_some() || function.external_javascript.is_some() {
+            return vec![];
+        }
+        let Some((name_location, _)) = &function.name else {
+            return vec![];
+        };
+
+        let parameters = function
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| NewParameter {
+                index: Some(index),
+                label: argument.names.get_label().cloned(),
+                ..Default::default()
+            })
+            .collect();
+        let arguments = ChangeSignatureParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: self.params.text_document.clone(),
+                position: src_span_to_lsp_range(*name_location, self.line_numbers).start,
+            },
+            parameters,
+        };
+        let Ok(arguments) = serde_json::to_value(arguments) else {
+            return vec![];
+        };
+
+        let mut action = Vec::with_capacity(1);
+        CodeActionBuilder::new("Change signature")
+            .kind(CodeActionKind::REFACTOR)
+            .command(Command {
+                title: "Change signature".into(),
+                command: CHANGE_SIGNATURE_COMMAND.into(),
+                arguments: Some(vec![arguments]),
+            })
+            .push_to(&mut action);
+        action
+    }
+}
@@ -0,0 +1,140 @@
+use camino::Utf8Path;
+use ecow::EcoString;
+use lsp_types::{CodeLens, Command, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::{Definition, SrcSpan},
+    build::{Module, Origin, Target},
+    io::BackgroundCommand,
+    line_numbers::LineNumbers,
+};
+
+use super::src_span_to_lsp_range;
+
+/// The command running the `main` function of a module with `gleam run`. Its
+/// only argument is a `RunParams`.
+///
+pub const RUN_COMMAND: &str = "gleam.run";
+
+/// The command running the tests of a module, or a single one of them, with
+/// `gleam test`. Its only argument is a `RunParams`.
+///
+pub const TEST_COMMAND: &str = "gleam.test";
+
+/// The module to run, and the target to run it on.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunParams {
+    pub text_document: TextDocumentIdentifier,
+    pub module: EcoString,
+    pub target: Target,
+    /// The full name of the test to run, such as `wibble_test.wobble_test`.
+    /// All the tests of the module are run if there's none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test: Option<String>,
+}
+
+/// The code lenses of a module: one to run its `main` function if it can be
+/// used as an entrypoint and, for test modules, one to run all of its tests
+/// and one above each test to run only that one.
+///
+pub fn code_lenses(
+    module: &Module,
+    text_document: TextDocumentIdentifier,
+    target: Target,
+) -> Vec<CodeLens> {
+    let line_numbers = LineNumbers::new(&module.code);
+    let interface = &module.ast.type_info;
+    let lens = |location: SrcSpan, title: &str, command: &str, test: Option<String>| {
+        let params = RunParams {
+            text_document: text_document.clone(),
+            module: module.name.clone(),
+            target,
+            test,
+        };
+        CodeLens {
+            range: src_span_to_lsp_range(location, &line_numbers),
+            command: Some(Command {
+                title: title.into(),
+                command: command.into(),
+                arguments: Some(vec![
+                    serde_json::to_value(params).expect("run params to json"),
+                ]),
+            }),
+            data: None,
+        }
+    };
+
+    let tests = interface.test_functions(target);
+    let mut lenses = vec![];
+    if module.origin == Origin::Test {
+        lenses.push(lens(
+            SrcSpan::new(0, 0),
+            "Run module tests",
+            TEST_COMMAND,
+            None,
+        ));
+    }
+
+    for definition in &module.ast.definitions {
+        let Definition::Function(function) = definition else {
+            continue;
+        };
+        let Some((_, name)) = &function.name else {
+            continue;
+        };
+
+        if name == "main" && interface.get_main_function(target).is_ok() {
+            lenses.push(lens(function.location, "Run", RUN_COMMAND, None));
+        }
+
+        if let Some(test) = tests.iter().find(|test| &test.name == name) {
+            let test = Some(test.full_name());
+            lenses.push(lens(function.location, "Run test", TEST_COMMAND, test));
+        }
+    }
+
+    lenses
+}
+
+/// The `gleam` command a code lens runs in the project at the given root.
+///
+pub fn run_command(
+    command: &str,
+    params: &RunParams,
+    root: &Utf8Path,
+) -> Option<BackgroundCommand> {
+    let mut args = match command {
+        RUN_COMMAND => vec!["run".into()],
+        TEST_COMMAND => vec!["test".into()],
+        _ => return None,
+    };
+    args.extend([
+        "--target".into(),
+        params.target.to_string(),
+        "--module".into(),
+        params.module.to_string(),
+    ]);
+    if let Some(test) = &params.test {
+        args.extend(["--name".into(), test.clone()]);
+    }
+
+    Some(BackgroundCommand {
+        program: gleam_program(),
+        args,
+        env: vec![],
+        cwd: Some(root.to_path_buf()),
+    })
+}
+
+/// The language server is run by the `gleam` executable, so the code lenses
+/// use that same one rather than whichever comes first on the `PATH`.
+///
+fn gleam_program() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| path.to_str().map(String::from))
+        .unwrap_or_else(|| "gleam".into())
+}
@@ -15,7 +15,10 @@ use crate::{
     type_::ModuleInterface,
     warning::VectorWarningEmitterIO,
 };
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use camino::Utf8PathBuf;
 
@@ -39,11 +42,15 @@ pub struct LspProjectCompiler<IO> {
     /// A lock to ensure that multiple instances of the LSP don't try and use
     /// build directory at the same time.
     pub locker: DebugIgnore<Box<dyn Locker>>,
+
+    /// Whether the dependency packages have been compiled successfully. Until
+    /// then every compilation has to compile the whole project.
+    dependencies_compiled: bool,
 }
 
 impl<IO> LspProjectCompiler<IO>
 where
-    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone,
+    IO: CommandExecutor + FileSystemWriter + FileSystemReader + BeamCompiler + Clone + Send + Sync,
 {
     pub fn new(
         manifest: Manifest,
@@ -95,6 +102,7 @@ where
             project_compiler,
             modules: HashMap::new(),
             sources: HashMap::new(),
+            dependencies_compiled: false,
         })
     }
 
@@ -114,10 +122,12 @@ where
 
         self.project_compiler.reset_state_for_new_compile_run();
 
+        self.dependencies_compiled = false;
         let compiled_dependencies = match self.project_compiler.compile_dependencies() {
             Ok(it) => it,
             Err(err) => return err.into(),
         };
+        self.dependencies_compiled = true;
 
         // Store the compiled dependency module information
         for module in &compiled_dependencies {
@@ -158,6 +168,98 @@ where
         // we don't bother them with diagnostics for them.
         let _ = self.take_warnings();
 
+        // Record the compiled dependency modules
+        let compiled_modules = compiled_dependencies
+            .into_iter()
+            .map(|m| m.input_path)
+            .collect_vec();
+
+        self.compile_root_package(compiled_modules)
+    }
+
+    /// Compiles the root package after some of its modules have changed,
+    /// without compiling the dependency packages again. Only the changed
+    /// modules and the modules depending on them are analysed again.
+    ///
+    /// This can only be used once the whole project has been compiled, with
+    /// the names returned by `changed_modules`.
+    ///
+    pub fn compile_changed_modules(
+        &mut self,
+        changed_modules: Vec<EcoString>,
+    ) -> Outcome<Vec<Utf8PathBuf>, Error> {
+        let _lock_guard: LockGuard = match self.locker.lock_for_build() {
+            Ok(it) => it,
+            Err(err) => return err.into(),
+        };
+
+        self.project_compiler.reset_state_for_new_compile_run();
+        // The changed modules are marked as stale so the modules depending on
+        // them are analysed again even if the changed modules themselves turn
+        // out to be unchanged, having been edited back to what was compiled.
+        self.project_compiler.mark_modules_stale(changed_modules);
+        self.compile_root_package(vec![])
+    }
+
+    /// The paths of the modules that are going to be analysed again after the
+    /// given files have changed: the changed modules and all the modules
+    /// depending on them, directly or not. Returns `None` if the whole project
+    /// has to be compiled, as with `changed_modules`.
+    ///
+    /// Modules that failed to compile before may also be analysed, as they
+    /// have not been cached.
+    ///
+    pub fn affected_modules(&self, changed: &HashSet<Utf8PathBuf>) -> Option<HashSet<Utf8PathBuf>> {
+        let mut stale: HashSet<EcoString> = self.changed_modules(changed)?.into_iter().collect();
+
+        let mut dependents: HashMap<&EcoString, Vec<&EcoString>> = HashMap::new();
+        for module in self.modules.values() {
+            for (dependency, _) in &module.dependencies {
+                dependents.entry(dependency).or_default().push(&module.name);
+            }
+        }
+
+        let mut to_visit = stale.iter().cloned().collect_vec();
+        while let Some(name) = to_visit.pop() {
+            for dependent in dependents.get(&name).into_iter().flatten() {
+                if stale.insert((*dependent).clone()) {
+                    to_visit.push((*dependent).clone());
+                }
+            }
+        }
+
+        Some(
+            stale
+                .iter()
+                .filter_map(|name| self.modules.get(name))
+                .map(|module| module.input_path.clone())
+                .collect(),
+        )
+    }
+
+    /// The names of the changed modules, if all the changed files are modules
+    /// of the root package that have already been compiled after the
+    /// dependency packages. Otherwise the whole project has to be compiled.
+    ///
+    pub fn changed_modules(&self, changed: &HashSet<Utf8PathBuf>) -> Option<Vec<EcoString>> {
+        if !self.dependencies_compiled {
+            return None;
+        }
+        changed
+            .iter()
+            .map(|path| {
+                self.modules
+                    .values()
+                    .find(|module| &module.input_path == path)
+                    .map(|module| module.name.clone())
+            })
+            .collect()
+    }
+
+    fn compile_root_package(
+        &mut self,
+        mut compiled_modules: Vec<Utf8PathBuf>,
+    ) -> Outcome<Vec<Utf8PathBuf>, Error> {
         // Compile the root package, that is, the one that the programmer is
         // working in.
         let (modules, error) = match self.project_compiler.compile_root_package() {
@@ -166,12 +268,6 @@ where
             Outcome::TotalFailure(error) => (vec![], Some(error)),
         };
 
-        // Record the compiled dependency modules
-        let mut compiled_modules = compiled_dependencies
-            .into_iter()
-            .map(|m| m.input_path)
-            .collect_vec();
-
         // Store the compiled module information
         for module in modules {
             let path = module.input_path.as_os_str().to_string_lossy().to_string();
@@ -104,7 +104,9 @@ where
         + CommandExecutor
         + DownloadDependencies
         + MakeLocker
-        + Clone,
+        + Clone
+        + Send
+        + Sync,
 {
     pub fn new(
         src: &'a EcoString,
@@ -0,0 +1,79 @@
+use serde::Deserialize;
+
+/// The configuration of the language server, which editors can send as the
+/// initialisation options of the `initialize` request:
+///
+/// ```json
+/// { "inlayHints": { "pipelines": false } }
+/// ```
+///
+/// Any option that is not given keeps its default value.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Configuration {
+    pub inlay_hints: InlayHintsConfig,
+}
+
+impl Configuration {
+    pub fn from_initialisation_options(options: Option<serde_json::Value>) -> Self {
+        let Some(options) = options else {
+            return Self::default();
+        };
+        match serde_json::from_value(options) {
+            Ok(configuration) => configuration,
+            Err(error) => {
+                tracing::warn!("invalid_initialisation_options: {error}");
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Which kinds of inlay hint are shown, all of them are by default.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InlayHintsConfig {
+    /// The inferred type of variables bound with `let` without an annotation.
+    pub variable_types: bool,
+    /// The inferred return type of functions without an annotation.
+    pub return_types: bool,
+    /// The type of each step of a multi-line pipeline.
+    pub pipelines: bool,
+    /// The labels of the arguments passed positionally to a function.
+    pub parameter_names: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> Self {
+        Self {
+            variable_types: true,
+            return_types: true,
+            pipelines: true,
+            parameter_names: true,
+        }
+    }
+}
+
+#[test]
+fn configuration_from_initialisation_options() {
+    let options = serde_json::json!({ "inlayHints": { "pipelines": false } });
+    assert_eq!(
+        Configuration::from_initialisation_options(Some(options)),
+        Configuration {
+            inlay_hints: InlayHintsConfig {
+                pipelines: false,
+                ..InlayHintsConfig::default()
+            }
+        }
+    );
+    assert_eq!(
+        Configuration::from_initialisation_options(Some(serde_json::json!("wibble"))),
+        Configuration::default()
+    );
+    assert_eq!(
+        Configuration::from_initialisation_options(None),
+        Configuration::default()
+    );
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use lsp_types::{
+    Diagnostic, DocumentDiagnosticReport, FullDocumentDiagnosticReport,
+    RelatedFullDocumentDiagnosticReport, RelatedUnchangedDocumentDiagnosticReport,
+    UnchangedDocumentDiagnosticReport, Url, WorkspaceDocumentDiagnosticReport,
+    WorkspaceFullDocumentDiagnosticReport, WorkspaceUnchangedDocumentDiagnosticReport,
+};
+
+/// The latest diagnostics of each file, for the clients that pull them with
+/// the `textDocument/diagnostic` and `workspace/diagnostic` requests rather
+/// than having them pushed by the server.
+///
+/// Each version of the diagnostics of a file gets its own result id, so the
+/// client can tell the server which version it already has and be told it is
+/// unchanged rather than be sent the same diagnostics again.
+///
+#[derive(Debug, Default)]
+pub struct DiagnosticStore {
+    documents: HashMap<Url, StoredDiagnostics>,
+    last_result_id: u64,
+}
+
+#[derive(Debug)]
+struct StoredDiagnostics {
+    result_id: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticStore {
+    /// Replaces the diagnostics of a file, returning whether they have
+    /// changed.
+    ///
+    pub fn update(&mut self, uri: Url, diagnostics: Vec<Diagnostic>) -> bool {
+        if self
+            .documents
+            .get(&uri)
+            .is_some_and(|stored| stored.diagnostics == diagnostics)
+        {
+            return false;
+        }
+
+        // A file that has never had any diagnostics doesn't need to be
+        // reported as having none.
+        if diagnostics.is_empty() && !self.documents.contains_key(&uri) {
+            return false;
+        }
+
+        self.last_result_id += 1;
+        let stored = StoredDiagnostics {
+            result_id: self.last_result_id.to_string(),
+            diagnostics,
+        };
+        _ = self.documents.insert(uri, stored);
+        true
+    }
+
+    /// The result id of the current diagnostics of a file, if it has any.
+    ///
+    pub fn result_id(&self, uri: &Url) -> Option<&str> {
+        self.documents
+            .get(uri)
+            .map(|stored| stored.result_id.as_str())
+    }
+
+    pub fn document_report(
+        &self,
+        uri: &Url,
+        previous_result_id: Option<&str>,
+    ) -> DocumentDiagnosticReport {
+        let Some(stored) = self.documents.get(uri) else {
+            return DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: None,
+                    items: vec![],
+                },
+            });
+        };
+
+        if previous_result_id == Some(stored.result_id.as_str()) {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id: stored.result_id.clone(),
+                },
+            })
+        } else {
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: stored.full_report(),
+            })
+        }
+    }
+
+    /// The reports of all the files with diagnostics that are included by
+    /// the given predicate, called with the uri and the result id of the
+    /// current diagnostics of each file, sorted by uri. Files whose
+    /// diagnostics the client already has, according to the result ids it
+    /// sent, are reported as unchanged.
+    ///
+    pub fn workspace_reports(
+        &self,
+        previous_result_ids: &HashMap<Url, String>,
+        include: impl Fn(&Url, &str) -> bool,
+    ) -> Vec<WorkspaceDocumentDiagnosticReport> {
+        self.documents
+            .iter()
+            .filter(|(uri, stored)| include(uri, &stored.result_id))
+            .map(|(uri, stored)| {
+                let uri = uri.clone();
+                if previous_result_ids.get(&uri) == Some(&stored.result_id) {
+                    let report = WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id: stored.result_id.clone(),
+                        },
+                    };
+                    WorkspaceDocumentDiagnosticReport::Unchanged(report)
+                } else {
+                    let report = WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        full_document_diagnostic_report: stored.full_report(),
+                    };
+                    WorkspaceDocumentDiagnosticReport::Full(report)
+                }
+            })
+            .sorted_by_key(|report| report_uri(report).to_string())
+            .collect()
+    }
+}
+
+pub fn report_uri(report: &WorkspaceDocumentDiagnosticReport) -> &Url {
+    match report {
+        WorkspaceDocumentDiagnosticReport::Full(report) => &report.uri,
+        WorkspaceDocumentDiagnosticReport::Unchanged(report) => &report.uri,
+    }
+}
+
+impl StoredDiagnostics {
+    fn full_report(&self) -> FullDocumentDiagnosticReport {
+        FullDocumentDiagnosticReport {
+            result_id: Some(self.result_id.clone()),
+            items: self.diagnostics.clone(),
+        }
+    }
+}
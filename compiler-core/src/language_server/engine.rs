@@ -7,7 +7,8 @@ use crate::{
         TypedModule, TypedPattern,
     },
     build::{
-        ExpressionPosition, Located, Module, UnqualifiedImport, type_constructor_from_modules,
+        ExpressionPosition, Located, Module, Outcome, UnqualifiedImport,
+        type_constructor_from_modules,
     },
     config::PackageConfig,
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
@@ -17,9 +18,10 @@ use crate::{
     },
     line_numbers::LineNumbers,
     paths::ProjectPaths,
+    rename_module::{import_edits, internal_modules_edits, is_valid_module_name, renamed_modules},
     type_::{
-        self, Deprecation, ModuleInterface, Type, TypeConstructor, ValueConstructor,
-        ValueConstructorVariant,
+        self, Deprecation, ModuleInterface, ModuleValueConstructor, Type, TypeConstructor,
+        ValueConstructor, ValueConstructorVariant,
         error::{Named, VariableSyntax},
         printer::Printer,
     },
@@ -33,29 +35,43 @@ use lsp_types::{
     PrepareRenameResponse, Range, SignatureHelp, SymbolKind, SymbolTag, TextEdit, Url,
     WorkspaceEdit,
 };
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use super::{
-    DownloadDependencies, MakeLocker,
+    DownloadDependencies, MakeLocker, TextEdits,
+    call_hierarchy::CallHierarchy,
+    change_signature::{ChangeSignatureError, ChangeSignatureParams, change_signature},
     code_action::{
-        AddAnnotations, CodeActionBuilder, ConvertFromUse, ConvertToFunctionCall, ConvertToPipe,
-        ConvertToUse, ExpandFunctionCapture, ExtractConstant, ExtractVariable,
-        FillInMissingLabelledArgs, FillUnusedFields, FixBinaryOperation,
+        AddAnnotations, ChangeSignature, CodeActionBuilder, ConvertFromUse, ConvertToFunctionCall,
+        ConvertToPipe, ConvertToUse, ExpandFunctionCapture, ExtractConstant, ExtractFunction,
+        ExtractVariable, FillInMissingLabelledArgs, FillUnusedFields, FixBinaryOperation,
         FixTruncatedBitArraySegment, GenerateDynamicDecoder, GenerateFunction, GenerateJsonEncoder,
-        GenerateVariant, InlineVariable, InterpolateString, LetAssertToCase, PatternMatchOnValue,
-        RedundantTupleInCaseSubject, RemoveEchos, RemoveUnusedImports, UseLabelShorthandSyntax,
-        WrapInBlock, code_action_add_missing_patterns,
+        GenerateVariant, InlineFunction, InlineVariable, InterpolateString, LetAssertToCase,
+        PatternMatchOnValue, RedundantTupleInCaseSubject, RemoveEchos, RemoveUnusedImports,
+        UseLabelShorthandSyntax, WrapInBlock, code_action_add_missing_patterns,
         code_action_convert_qualified_constructor_to_unqualified,
         code_action_convert_unqualified_constructor_to_qualified, code_action_import_module,
         code_action_inexhaustive_let_to_case,
     },
+    code_lens::code_lenses,
     completer::Completer,
+    configuration::InlayHintsConfig,
+    folding_range::folding_ranges,
+    implementation::{Externals, external_implementations},
+    inlay_hints::inlay_hints,
+    lsp_range_to_src_span,
+    move_definition::{MoveDefinitionError, MoveDefinitionParams, move_definition},
     reference::{
         Referenced, VariableReferenceKind, find_module_references, find_variable_references,
         reference_for_ast_node,
     },
     rename::{RenameTarget, Renamed, rename_local_variable, rename_module_entity},
-    signature_help, src_span_to_lsp_range,
+    selection_range::selection_range,
+    semantic_tokens, signature_help, src_span_to_lsp_range, url_from_path,
+    workspace_symbol::workspace_symbols,
 };
 
 #[derive(Debug, PartialEq, Eq)]
@@ -105,7 +121,9 @@ where
         + CommandExecutor
         + DownloadDependencies
         + MakeLocker
-        + Clone,
+        + Clone
+        + Send
+        + Sync,
     // IO to be supplied from inside of gleam-core
     Reporter: ProgressReporter + Clone + 'a,
 {
@@ -157,12 +175,42 @@ where
         self.respond(Self::compile)
     }
 
+    /// Compile the project after the given files have changed. When they are
+    /// all modules of the root package that have been compiled before, only
+    /// they and the modules depending on them are analysed again.
+    ///
+    pub fn compile_changed(&mut self, changed: &HashSet<Utf8PathBuf>) -> Response<()> {
+        match self.compiler.changed_modules(changed) {
+            Some(modules) => self.respond(|this| {
+                this.compile_with(|compiler| compiler.compile_changed_modules(modules))
+            }),
+            None => self.compile_please(),
+        }
+    }
+
+    /// The modules that `compile_changed` is going to analyse again after the
+    /// given files have changed, or `None` if it is going to compile the whole
+    /// project.
+    ///
+    pub fn affected_modules(&self, changed: &HashSet<Utf8PathBuf>) -> Option<HashSet<Utf8PathBuf>> {
+        self.compiler.affected_modules(changed)
+    }
+
     /// Compile the project if we are in one. Otherwise do nothing.
     fn compile(&mut self) -> Result<(), Error> {
+        self.compile_with(LspProjectCompiler::compile)
+    }
+
+    fn compile_with(
+        &mut self,
+        compile: impl FnOnce(
+            &mut LspProjectCompiler<FileSystemProxy<IO>>,
+        ) -> Outcome<Vec<Utf8PathBuf>, Error>,
+    ) -> Result<(), Error> {
         self.compiled_since_last_feedback = true;
 
         self.progress_reporter.compilation_started();
-        let outcome = self.compiler.compile();
+        let outcome = compile(&mut self.compiler);
         self.progress_reporter.compilation_finished();
 
         let result = outcome
@@ -234,6 +282,100 @@ where
         })
     }
 
+    /// The native implementations of the `@external` function at the given
+    /// position, in the Erlang, Elixir, or JavaScript files of its package.
+    ///
+    pub fn goto_implementation(
+        &mut self,
+        params: lsp::GotoDefinitionParams,
+    ) -> Response<Vec<lsp::Location>> {
+        self.respond(|this| {
+            let params = params.text_document_position_params;
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(vec![]);
+            };
+            let Some((line_numbers, node)) = this.module_node_at_position(&params, module) else {
+                return Ok(vec![]);
+            };
+
+            let (module_name, externals) = match node {
+                Located::Expression {
+                    expression: TypedExpr::Var { constructor, .. },
+                    ..
+                } => match &constructor.variant {
+                    ValueConstructorVariant::ModuleFn {
+                        module,
+                        external_erlang,
+                        external_javascript,
+                        ..
+                    } => (
+                        module,
+                        Externals::new(external_erlang.as_ref(), external_javascript.as_ref()),
+                    ),
+                    _ => return Ok(vec![]),
+                },
+
+                Located::Expression {
+                    expression:
+                        TypedExpr::ModuleSelect {
+                            constructor:
+                                ModuleValueConstructor::Fn {
+                                    module,
+                                    external_erlang,
+                                    external_javascript,
+                                    ..
+                                },
+                            ..
+                        },
+                    ..
+                } => (
+                    module,
+                    Externals::new(external_erlang.as_ref(), external_javascript.as_ref()),
+                ),
+
+                // The body of a function with no Gleam implementation is a
+                // placeholder spanning its head, so the function is looked up
+                // rather than matched on.
+                _ => {
+                    let byte_index = line_numbers.byte_index(params.position);
+                    let function =
+                        module
+                            .ast
+                            .definitions
+                            .iter()
+                            .find_map(|definition| match definition {
+                                Definition::Function(function)
+                                    if function.location.contains(byte_index) =>
+                                {
+                                    Some(function)
+                                }
+                                _ => None,
+                            });
+                    match function {
+                        Some(function) => (&module.name, Externals::of_function(function)),
+                        None => return Ok(vec![]),
+                    }
+                }
+            };
+
+            let path = if *module_name == module.name {
+                module.input_path.clone()
+            } else {
+                match this.compiler.get_source(module_name) {
+                    Some(source) => source.path.as_str().into(),
+                    None => return Ok(vec![]),
+                }
+            };
+
+            Ok(external_implementations(
+                &this.compiler.project_compiler.io,
+                module_name,
+                &path,
+                externals,
+            ))
+        })
+    }
+
     fn definition_location_to_lsp_location(
         &self,
         line_numbers: &LineNumbers,
@@ -425,6 +567,7 @@ where
             actions.extend(InterpolateString::new(module, &lines, &params).code_actions());
             actions.extend(ExtractVariable::new(module, &lines, &params).code_actions());
             actions.extend(ExtractConstant::new(module, &lines, &params).code_actions());
+            actions.extend(ExtractFunction::new(module, &lines, &params).code_actions());
             actions.extend(GenerateFunction::new(module, &lines, &params).code_actions());
             actions.extend(
                 GenerateVariant::new(module, &this.compiler, &lines, &params).code_actions(),
@@ -435,6 +578,7 @@ where
                 PatternMatchOnValue::new(module, &lines, &params, &this.compiler).code_actions(),
             );
             actions.extend(InlineVariable::new(module, &lines, &params).code_actions());
+            actions.extend(InlineFunction::new(module, &lines, &params).code_actions());
             actions.extend(WrapInBlock::new(module, &lines, &params).code_actions());
             actions.extend(RemoveBlock::new(module, &lines, &params).code_actions());
             GenerateDynamicDecoder::new(module, &lines, &params, &mut actions).code_actions();
@@ -447,6 +591,7 @@ where
             )
             .code_actions();
             AddAnnotations::new(module, &lines, &params).code_action(&mut actions);
+            actions.extend(ChangeSignature::new(module, &lines, &params).code_actions());
             Ok(if actions.is_empty() {
                 None
             } else {
@@ -600,6 +745,119 @@ where
         })
     }
 
+    pub fn code_lens(
+        &mut self,
+        params: lsp::CodeLensParams,
+    ) -> Response<Option<Vec<lsp::CodeLens>>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            let target = this.compiler.project_compiler.target();
+            Ok(Some(code_lenses(module, params.text_document, target)))
+        })
+    }
+
+    pub fn folding_range(
+        &mut self,
+        params: lsp::FoldingRangeParams,
+    ) -> Response<Option<Vec<lsp::FoldingRange>>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            Ok(Some(folding_ranges(module)))
+        })
+    }
+
+    pub fn selection_range(
+        &mut self,
+        params: lsp::SelectionRangeParams,
+    ) -> Response<Option<Vec<lsp::SelectionRange>>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            let ranges = params
+                .positions
+                .iter()
+                .map(|position| selection_range(module, *position))
+                .collect();
+            Ok(Some(ranges))
+        })
+    }
+
+    pub fn semantic_tokens_full(
+        &mut self,
+        params: lsp::SemanticTokensParams,
+    ) -> Response<Option<lsp::SemanticTokensResult>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            let data = semantic_tokens::semantic_tokens(
+                module,
+                this.compiler.project_compiler.get_importable_modules(),
+                None,
+            );
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: None,
+                    data,
+                },
+            )))
+        })
+    }
+
+    pub fn semantic_tokens_range(
+        &mut self,
+        params: lsp::SemanticTokensRangeParams,
+    ) -> Response<Option<lsp::SemanticTokensRangeResult>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(None);
+            };
+            let line_numbers = LineNumbers::new(&module.code);
+            let data = semantic_tokens::semantic_tokens(
+                module,
+                this.compiler.project_compiler.get_importable_modules(),
+                Some(lsp_range_to_src_span(params.range, &line_numbers)),
+            );
+            Ok(Some(lsp::SemanticTokensRangeResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: None,
+                    data,
+                },
+            )))
+        })
+    }
+
+    pub fn inlay_hints(
+        &mut self,
+        params: lsp::InlayHintParams,
+        config: InlayHintsConfig,
+    ) -> Response<Vec<lsp::InlayHint>> {
+        self.respond(|this| {
+            let Some(module) = this.module_for_uri(&params.text_document.uri) else {
+                return Ok(vec![]);
+            };
+            let line_numbers = LineNumbers::new(&module.code);
+            let range = lsp_range_to_src_span(params.range, &line_numbers);
+            Ok(inlay_hints(module, config, range))
+        })
+    }
+
+    pub fn workspace_symbol(&mut self, query: &str) -> Response<Vec<lsp::WorkspaceSymbol>> {
+        self.respond(|this| {
+            Ok(workspace_symbols(
+                query,
+                &this.compiler.project_compiler.config.name,
+                this.compiler.project_compiler.get_importable_modules(),
+                &this.compiler.sources,
+            ))
+        })
+    }
+
     /// Check whether a particular module is in the same package as this one
     fn is_same_package(&self, current_module: &Module, module_name: &str) -> bool {
         let other_module = self
@@ -763,85 +1021,308 @@ where
         &mut self,
         params: lsp::ReferenceParams,
     ) -> Response<Option<Vec<lsp::Location>>> {
+        self.respond(|this| Ok(this.references_at(&params.text_document_position)))
+    }
+
+    /// The uses of the symbol under the cursor in the current module.
+    ///
+    pub fn document_highlight(
+        &mut self,
+        params: lsp::DocumentHighlightParams,
+    ) -> Response<Option<Vec<lsp::DocumentHighlight>>> {
+        self.respond(|this| {
+            let position = &params.text_document_position_params;
+            let path = super::path(&position.text_document.uri);
+            let Some(references) = this.references_at(position) else {
+                return Ok(None);
+            };
+
+            let highlights = references
+                .into_iter()
+                .filter(|reference| super::path(&reference.uri) == path)
+                .map(|reference| lsp::DocumentHighlight {
+                    range: reference.range,
+                    kind: None,
+                })
+                .collect_vec();
+            Ok(Some(highlights))
+        })
+    }
+
+    fn references_at(
+        &self,
+        position: &lsp::TextDocumentPositionParams,
+    ) -> Option<Vec<lsp::Location>> {
+        let (lines, found) = self.node_at_position(position)?;
+        let uri = position.text_document.uri.clone();
+        let module = self.module_for_uri(&uri)?;
+
+        let byte_index = lines.byte_index(position.position);
+
+        match reference_for_ast_node(found, &module.name) {
+            Some(Referenced::LocalVariable {
+                origin,
+                definition_location,
+                location,
+                name,
+            }) if location.contains(byte_index) => match origin.map(|origin| origin.syntax) {
+                Some(VariableSyntax::Generated) => None,
+                Some(
+                    VariableSyntax::LabelShorthand(_)
+                    | VariableSyntax::AssignmentPattern
+                    | VariableSyntax::Variable { .. },
+                )
+                | None => {
+                    let variable_references =
+                        find_variable_references(&module.ast, definition_location, name);
+
+                    let mut reference_locations = Vec::with_capacity(variable_references.len() + 1);
+                    reference_locations.push(lsp::Location {
+                        uri: uri.clone(),
+                        range: src_span_to_lsp_range(definition_location, &lines),
+                    });
+
+                    for reference in variable_references {
+                        reference_locations.push(lsp::Location {
+                            uri: uri.clone(),
+                            range: src_span_to_lsp_range(reference.location, &lines),
+                        })
+                    }
+
+                    Some(reference_locations)
+                }
+            },
+            Some(Referenced::ModuleValue {
+                module,
+                name,
+                location,
+                ..
+            }) if location.contains(byte_index) => Some(find_module_references(
+                module,
+                name,
+                self.compiler.project_compiler.get_importable_modules(),
+                &self.compiler.sources,
+                ast::Layer::Value,
+            )),
+            Some(Referenced::ModuleType {
+                module,
+                name,
+                location,
+                ..
+            }) if location.contains(byte_index) => Some(find_module_references(
+                module,
+                name,
+                self.compiler.project_compiler.get_importable_modules(),
+                &self.compiler.sources,
+                ast::Layer::Type,
+            )),
+            _ => None,
+        }
+    }
+
+    /// The edits changing the parameters of the function at the given
+    /// position, and updating all the calls to it in the project.
+    ///
+    pub fn change_signature(
+        &mut self,
+        params: ChangeSignatureParams,
+    ) -> Response<Result<WorkspaceEdit, ChangeSignatureError>> {
         self.respond(|this| {
             let position = &params.text_document_position;
+            let Some((_, found)) = this.node_at_position(position) else {
+                return Ok(Err(ChangeSignatureError::NoFunction));
+            };
+            let Some(module) = this.module_for_uri(&position.text_document.uri) else {
+                return Ok(Err(ChangeSignatureError::NoFunction));
+            };
 
-            let (lines, found) = match this.node_at_position(position) {
-                Some(value) => value,
-                None => return Ok(None),
+            Ok(match reference_for_ast_node(found, &module.name) {
+                Some(Referenced::ModuleValue { module, name, .. }) => {
+                    change_signature(&this.compiler.modules, &module, &name, &params.parameters)
+                }
+                _ => Err(ChangeSignatureError::NoFunction),
+            })
+        })
+    }
+
+    pub fn move_definition(
+        &mut self,
+        params: MoveDefinitionParams,
+    ) -> Response<Result<WorkspaceEdit, MoveDefinitionError>> {
+        self.respond(|this| {
+            let position = &params.text_document_position;
+            let Some(module) = this.module_for_uri(&position.text_document.uri) else {
+                return Ok(Err(MoveDefinitionError::NoDefinition));
             };
+            let byte_index = module
+                .ast
+                .type_info
+                .line_numbers
+                .byte_index(position.position);
+
+            // Definitions can't be moved into the modules of dependencies.
+            let destination = &params.module;
+            if !this.compiler.modules.contains_key(destination)
+                && this
+                    .compiler
+                    .project_compiler
+                    .get_importable_modules()
+                    .contains_key(destination)
+            {
+                return Ok(Err(MoveDefinitionError::DependencyModule));
+            }
 
-            let uri = position.text_document.uri.clone();
+            Ok(move_definition(
+                &this.compiler.modules,
+                &module.name,
+                byte_index,
+                destination,
+            ))
+        })
+    }
 
-            let Some(module) = this.module_for_uri(&uri) else {
+    /// The edits keeping the imports of the project working after renaming
+    /// some modules, or directories containing modules.
+    ///
+    pub fn will_rename_files(
+        &mut self,
+        params: &lsp::RenameFilesParams,
+    ) -> Response<Option<WorkspaceEdit>> {
+        self.respond(|this| {
+            let mut renames = vec![];
+            for file in &params.files {
+                let (Ok(old_uri), Ok(new_uri)) =
+                    (Url::parse(&file.old_uri), Url::parse(&file.new_uri))
+                else {
+                    continue;
+                };
+                let (Some(old_path), Some(new_path)) =
+                    (this.module_path(&old_uri), this.module_path(&new_uri))
+                else {
+                    continue;
+                };
+                renames.extend(renamed_modules(
+                    this.compiler.modules.keys(),
+                    &old_path,
+                    &new_path,
+                ));
+            }
+            renames.retain(|rename| is_valid_module_name(&rename.new_name));
+            if renames.is_empty() {
                 return Ok(None);
-            };
+            }
 
-            let byte_index = lines.byte_index(position.position);
+            let mut changes = HashMap::new();
+            for module in this.compiler.modules.values() {
+                let edits = import_edits(module, &renames);
+                if edits.is_empty() {
+                    continue;
+                }
+                let mut text_edits = TextEdits::new(&module.ast.type_info.line_numbers);
+                for edit in edits {
+                    text_edits.replace(edit.location, edit.new_text);
+                }
+                if let Some(uri) = url_from_path(module.input_path.as_str()) {
+                    let _ = changes.insert(uri, text_edits.edits);
+                }
+            }
 
-            Ok(match reference_for_ast_node(found, &module.name) {
-                Some(Referenced::LocalVariable {
-                    origin,
-                    definition_location,
-                    location,
-                    name,
-                }) if location.contains(byte_index) => match origin.map(|origin| origin.syntax) {
-                    Some(VariableSyntax::Generated) => None,
-                    Some(
-                        VariableSyntax::LabelShorthand(_)
-                        | VariableSyntax::AssignmentPattern
-                        | VariableSyntax::Variable { .. },
-                    )
-                    | None => {
-                        let variable_references =
-                            find_variable_references(&module.ast, definition_location, name);
+            let config_path = this.paths.root_config();
+            if let Ok(toml) = this.compiler.project_compiler.io.read(&config_path) {
+                let config = &this.compiler.project_compiler.config;
+                let edits = internal_modules_edits(config, &toml, &renames);
+                let line_numbers = LineNumbers::new(&toml);
+                let mut text_edits = TextEdits::new(&line_numbers);
+                for edit in edits {
+                    text_edits.replace(edit.location, edit.new_text);
+                }
+                match url_from_path(config_path.as_str()) {
+                    Some(uri) if !text_edits.edits.is_empty() => {
+                        let _ = changes.insert(uri, text_edits.edits);
+                    }
+                    Some(_) | None => {}
+                }
+            }
 
-                        let mut reference_locations =
-                            Vec::with_capacity(variable_references.len() + 1);
-                        reference_locations.push(lsp::Location {
-                            uri: uri.clone(),
-                            range: src_span_to_lsp_range(definition_location, &lines),
-                        });
+            Ok(Some(WorkspaceEdit {
+                changes: Some(changes),
+                document_changes: None,
+                change_annotations: None,
+            }))
+        })
+    }
 
-                        for reference in variable_references {
-                            reference_locations.push(lsp::Location {
-                                uri: uri.clone(),
-                                range: src_span_to_lsp_range(reference.location, &lines),
-                            })
-                        }
+    /// The path of a module, or of a directory of modules, relative to the
+    /// source directory it is in, without the `.gleam` extension.
+    ///
+    fn module_path(&self, uri: &Url) -> Option<String> {
+        let path = super::path(uri);
+        let directories = [
+            self.paths.src_directory(),
+            self.paths.test_directory(),
+            self.paths.dev_directory(),
+        ];
+        let relative = directories
+            .iter()
+            .find_map(|directory| path.strip_prefix(directory).ok())?;
+        let relative = relative
+            .components()
+            .map(|component| component.as_str())
+            .join("/");
+        let module_path = relative.strip_suffix(".gleam").unwrap_or(&relative);
+        Some(module_path.to_string())
+    }
 
-                        Some(reference_locations)
-                    }
-                },
+    pub fn prepare_call_hierarchy(
+        &mut self,
+        params: lsp::CallHierarchyPrepareParams,
+    ) -> Response<Option<Vec<lsp::CallHierarchyItem>>> {
+        self.respond(|this| {
+            let position = &params.text_document_position_params;
+            let Some((lines, found)) = this.node_at_position(position) else {
+                return Ok(None);
+            };
+            let Some(module) = this.module_for_uri(&position.text_document.uri) else {
+                return Ok(None);
+            };
+            let byte_index = lines.byte_index(position.position);
+
+            Ok(match reference_for_ast_node(found, &module.name) {
                 Some(Referenced::ModuleValue {
                     module,
                     name,
                     location,
                     ..
-                }) if location.contains(byte_index) => Some(find_module_references(
-                    module,
-                    name,
-                    this.compiler.project_compiler.get_importable_modules(),
-                    &this.compiler.sources,
-                    ast::Layer::Value,
-                )),
-                Some(Referenced::ModuleType {
-                    module,
-                    name,
-                    location,
-                    ..
-                }) if location.contains(byte_index) => Some(find_module_references(
-                    module,
-                    name,
-                    this.compiler.project_compiler.get_importable_modules(),
-                    &this.compiler.sources,
-                    ast::Layer::Type,
-                )),
+                }) if location.contains(byte_index) => this
+                    .call_hierarchy()
+                    .item(&module, &name)
+                    .map(|item| vec![item]),
                 _ => None,
             })
         })
     }
 
+    pub fn incoming_calls(
+        &mut self,
+        item: &lsp::CallHierarchyItem,
+    ) -> Response<Vec<lsp::CallHierarchyIncomingCall>> {
+        self.respond(|this| Ok(this.call_hierarchy().incoming_calls(item)))
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: &lsp::CallHierarchyItem,
+    ) -> Response<Vec<lsp::CallHierarchyOutgoingCall>> {
+        self.respond(|this| Ok(this.call_hierarchy().outgoing_calls(item)))
+    }
+
+    fn call_hierarchy(&self) -> CallHierarchy<'_> {
+        CallHierarchy {
+            modules: self.compiler.project_compiler.get_importable_modules(),
+            sources: &self.compiler.sources,
+        }
+    }
+
     fn respond<T>(&mut self, handler: impl FnOnce(&mut Self) -> Result<T>) -> Response<T> {
         let result = handler(self);
         let warnings = self.take_warnings();
@@ -7,8 +7,9 @@ use crate::{
     Result,
     error::Error,
     io::{
-        BeamCompiler, Command, CommandExecutor, FileSystemReader, FileSystemWriter, ReadDir, Stdio,
-        WrappedReader, memory::InMemoryFileSystem,
+        BackgroundCommand, BeamCompiler, Command, CommandExecutor, CommandOutput, CommandSpawner,
+        FileSystemReader, FileSystemWriter, ReadDir, Stdio, WrappedReader,
+        memory::InMemoryFileSystem,
     },
 };
 
@@ -162,6 +163,21 @@ where
     }
 }
 
+// Unlike compiling, running the project is something the user explicitly asks
+// for, so the programs started to do that are allowed.
+impl<IO> CommandSpawner for FileSystemProxy<IO>
+where
+    IO: CommandSpawner,
+{
+    fn spawn(
+        &self,
+        command: BackgroundCommand,
+        on_output: Box<dyn FnMut(CommandOutput) + Send>,
+    ) -> Result<()> {
+        self.io.spawn(command, on_output)
+    }
+}
+
 impl<IO> BeamCompiler for FileSystemProxy<IO>
 where
     IO: BeamCompiler,
@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use itertools::Itertools;
+use lsp_types::{FoldingRange, FoldingRangeKind};
+use vec1::Vec1;
+
+use crate::{
+    ast::{
+        Definition, FunctionLiteralKind, SrcSpan, TypeAst, TypedArg, TypedClause, TypedCustomType,
+        TypedExpr, TypedFunction, TypedStatement,
+        visit::{self, Visit},
+    },
+    build::Module,
+    exhaustiveness::CompiledCase,
+    line_numbers::LineNumbers,
+    type_::Type,
+};
+
+/// The ranges of a module that can be folded: its functions, anonymous
+/// functions and custom types, `case` expressions and their clauses, blocks
+/// of imports, and blocks of comments.
+///
+pub fn folding_ranges(module: &Module) -> Vec<FoldingRange> {
+    let line_numbers = LineNumbers::new(&module.code);
+    let mut folder = Folder {
+        line_numbers: &line_numbers,
+        ranges: vec![],
+    };
+
+    folder.visit_typed_module(&module.ast);
+    folder.fold_imports(module);
+    folder.fold_comments(&module.extra.module_comments);
+    folder.fold_comments(&module.extra.doc_comments);
+    folder.fold_comments(&module.extra.comments);
+
+    folder
+        .ranges
+        .into_iter()
+        .sorted_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)))
+        .dedup_by(|one, other| one.start_line == other.start_line && one.end_line == other.end_line)
+        .collect()
+}
+
+struct Folder<'a> {
+    line_numbers: &'a LineNumbers,
+    ranges: Vec<FoldingRange>,
+}
+
+impl Folder<'_> {
+    fn fold(&mut self, location: SrcSpan, kind: Option<FoldingRangeKind>) {
+        let start_line = self.line_numbers.line_number(location.start) - 1;
+        let end_line = self.line_numbers.line_number(location.end) - 1;
+        if end_line <= start_line {
+            return;
+        }
+        self.ranges.push(FoldingRange {
+            start_line,
+            start_character: None,
+            end_line,
+            end_character: None,
+            kind,
+            collapsed_text: None,
+        });
+    }
+
+    /// Folds each run of imports that is not interrupted by other definitions.
+    ///
+    fn fold_imports(&mut self, module: &Module) {
+        let definitions = module
+            .ast
+            .definitions
+            .iter()
+            .sorted_by_key(|definition| definition.location().start);
+        let runs = definitions.chunk_by(|definition| matches!(definition, Definition::Import(_)));
+        for (is_import, mut imports) in &runs {
+            if !is_import {
+                continue;
+            }
+            let Some(first) = imports.next() else {
+                continue;
+            };
+            let last = imports.last().unwrap_or(first);
+            let location = SrcSpan::new(first.location().start, last.location().end);
+            self.fold(location, Some(FoldingRangeKind::Imports));
+        }
+    }
+
+    /// Folds each block of comments written on consecutive lines.
+    ///
+    fn fold_comments(&mut self, comments: &[SrcSpan]) {
+        let mut block: Option<(SrcSpan, u32)> = None;
+        for comment in comments {
+            let line = self.line_numbers.line_number(comment.start);
+            block = match block {
+                Some((location, last_line)) if line == last_line + 1 => {
+                    Some((location.merge(comment), line))
+                }
+                Some((location, _)) => {
+                    self.fold(location, Some(FoldingRangeKind::Comment));
+                    Some((*comment, line))
+                }
+                None => Some((*comment, line)),
+            };
+        }
+        if let Some((location, _)) = block {
+            self.fold(location, Some(FoldingRangeKind::Comment));
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for Folder<'_> {
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        self.fold(fun.full_location(), None);
+        visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_custom_type(&mut self, custom_type: &'ast TypedCustomType) {
+        self.fold(custom_type.full_location(), None);
+        visit::visit_typed_custom_type(self, custom_type);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        arguments: &'ast [TypedArg],
+        body: &'ast Vec1<TypedStatement>,
+        return_annotation: &'ast Option<TypeAst>,
+    ) {
+        // The functions created by `use` span the rest of the block they are
+        // in, so there's nothing to fold there.
+        if let FunctionLiteralKind::Anonymous { .. } = kind {
+            self.fold(*location, None);
+        }
+        visit::visit_typed_expr_fn(
+            self,
+            location,
+            type_,
+            kind,
+            arguments,
+            body,
+            return_annotation,
+        );
+    }
+
+    fn visit_typed_expr_case(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        subjects: &'ast [TypedExpr],
+        clauses: &'ast [TypedClause],
+        compiled_case: &'ast CompiledCase,
+    ) {
+        self.fold(*location, None);
+        visit::visit_typed_expr_case(self, location, type_, subjects, clauses, compiled_case);
+    }
+
+    fn visit_typed_clause(&mut self, clause: &'ast TypedClause) {
+        self.fold(clause.location, None);
+        visit::visit_typed_clause(self, clause);
+    }
+}
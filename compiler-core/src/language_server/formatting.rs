@@ -0,0 +1,63 @@
+use camino::Utf8Path;
+use ecow::EcoString;
+use lsp_types::{Position, Range, TextEdit};
+
+use crate::{Result, ast::SrcSpan, format, line_numbers::LineNumbers};
+
+use super::{lsp_range_to_src_span, src_span_to_lsp_range};
+
+/// The edits formatting the definitions, or the statements of a function,
+/// intersecting the given range.
+///
+pub fn range_formatting(src: &EcoString, path: &Utf8Path, range: Range) -> Result<Vec<TextEdit>> {
+    let line_numbers = LineNumbers::new(src);
+    let range = lsp_range_to_src_span(range, &line_numbers);
+    Ok(text_edits(
+        format::pretty_range(src, path, range)?,
+        &line_numbers,
+    ))
+}
+
+/// The edits formatting the definition or statement that was just completed
+/// by typing the given character: the one ending with the closing brace, or at
+/// the end of the line before the new one.
+///
+/// As the code is being typed it may not be valid yet, in which case there's
+/// nothing to format.
+///
+pub fn on_type_formatting(
+    src: &EcoString,
+    path: &Utf8Path,
+    position: Position,
+    character: &str,
+) -> Vec<TextEdit> {
+    let line_numbers = LineNumbers::new(src);
+    let end = match character {
+        "}" => line_numbers.byte_index(position),
+        "\n" if position.line > 0 => {
+            let line_start = line_numbers.byte_index(Position::new(position.line, 0));
+            let previous_line = src.get(..line_start as usize).unwrap_or_default();
+            previous_line.trim_end().len() as u32
+        }
+        _ => return vec![],
+    };
+
+    let Ok(formatted) = format::pretty_range(src, path, SrcSpan::new(end, end)) else {
+        return vec![];
+    };
+    let completed = formatted
+        .into_iter()
+        .filter(|formatted| formatted.location.end == end)
+        .collect();
+    text_edits(completed, &line_numbers)
+}
+
+fn text_edits(formatted: Vec<format::FormattedRange>, line_numbers: &LineNumbers) -> Vec<TextEdit> {
+    formatted
+        .into_iter()
+        .map(|formatted| TextEdit {
+            range: src_span_to_lsp_range(formatted.location, line_numbers),
+            new_text: formatted.code,
+        })
+        .collect()
+}
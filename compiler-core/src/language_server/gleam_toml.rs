@@ -0,0 +1,513 @@
+//! Language features for the `gleam.toml` file of a project or workspace:
+//! diagnostics for configuration that can't be read, completion of its keys
+//! and values, and hovering over dependencies.
+//!
+//! The file is often invalid while it's being edited, so rather than parsing
+//! it each feature looks at the lines of the file around the cursor to work
+//! out what is being written.
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use hexpm::version::Version;
+use itertools::Itertools;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Hover, HoverContents, MarkedString,
+    Position, Range, TextEdit,
+};
+use strum::VariantNames;
+
+use crate::{
+    ast::SrcSpan,
+    config::{PackageConfig, WorkspaceConfig},
+    diagnostic::{Diagnostic, Label, Level, Location},
+    io::FileSystemReader,
+    line_numbers::{LineColumn, LineNumbers},
+    manifest::{Manifest, ManifestPackageSource},
+    warning::WarningKind,
+};
+
+/// The keys that can be set in each table of `gleam.toml`, the top level one
+/// being named with the empty string.
+///
+const KEYS: &[(&str, &[&str])] = &[
+    (
+        "",
+        &[
+            "name",
+            "version",
+            "gleam",
+            "licences",
+            "description",
+            "target",
+            "internal_modules",
+            "repository",
+            "links",
+        ],
+    ),
+    ("documentation", &["pages"]),
+    ("documentation.pages", &["title", "path", "source"]),
+    ("links", &["title", "href"]),
+    (
+        "repository",
+        &["type", "user", "repo", "host", "path", "tag-prefix"],
+    ),
+    (
+        "erlang",
+        &["application_start_module", "extra_applications"],
+    ),
+    (
+        "javascript",
+        &["typescript_declarations", "runtime", "source_maps"],
+    ),
+    (
+        "javascript.deno",
+        &[
+            "allow_all",
+            "allow_env",
+            "allow_ffi",
+            "allow_hrtime",
+            "allow_net",
+            "allow_read",
+            "allow_run",
+            "allow_sys",
+            "allow_write",
+            "location",
+            "unstable",
+        ],
+    ),
+    ("workspace", &["members"]),
+];
+
+const TABLES: &[&str] = &[
+    "dependencies",
+    "dev-dependencies",
+    "documentation",
+    "erlang",
+    "javascript",
+    "javascript.deno",
+    "repository",
+    "warnings",
+    "workspace",
+];
+
+/// The tables that are written as arrays of tables, such as `[[links]]`.
+const ARRAYS_OF_TABLES: &[&str] = &["links", "documentation.pages"];
+
+pub fn is_gleam_toml(path: &Utf8Path) -> bool {
+    path.file_name() == Some("gleam.toml")
+}
+
+/// The diagnostics for a `gleam.toml` file that can't be read as the
+/// configuration of a package or, if it has a `[workspace]` table, of a
+/// workspace.
+///
+pub fn diagnostics(path: &Utf8Path, src: &EcoString) -> Vec<Diagnostic> {
+    #[derive(serde::Deserialize)]
+    struct Workspace {
+        #[allow(dead_code)]
+        workspace: WorkspaceConfig,
+    }
+
+    let is_workspace = src
+        .lines()
+        .any(|line| table_name(line) == Some("workspace"));
+    let result = if is_workspace {
+        toml::from_str::<Workspace>(src).map(|_| ())
+    } else {
+        toml::from_str::<PackageConfig>(src).map(|_| ())
+    };
+    let Err(error) = result else {
+        return vec![];
+    };
+
+    // The position of the error is part of its message, but it's shown by
+    // the diagnostic's location already.
+    let message = error.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(index) => message.get(..index).unwrap_or(&message),
+        None => &message,
+    };
+
+    // Errors in the values of the config are reported with the key they're
+    // for, but not with their position, so the key has to be found in the
+    // file. Syntax errors have a position instead.
+    let key_span = message
+        .rsplit_once(" for key `")
+        .and_then(|(_, key)| key.strip_suffix('`'))
+        .and_then(|key| key_span(src, key));
+    let span = match (key_span, error.line_col()) {
+        (Some(span), _) => span,
+        (None, Some((line, column))) => {
+            let line_start: usize = src
+                .split_terminator('\n')
+                .take(line)
+                .map(|line| line.len() + 1)
+                .sum();
+            let start = line_start + column;
+            let rest = src.get(start..).unwrap_or_default();
+            let rest = rest.split('\n').next().unwrap_or_default().trim_end();
+            SrcSpan::new(start as u32, (start + rest.len()) as u32)
+        }
+        // Errors with no position, such as a missing field, are about the
+        // file as a whole so they're shown on its first line.
+        (None, None) => SrcSpan::new(0, src.lines().next().unwrap_or_default().len() as u32),
+    };
+
+    vec![Diagnostic {
+        title: "Invalid gleam.toml".into(),
+        text: message.into(),
+        level: Level::Error,
+        code: None,
+        location: Some(Location {
+            src: src.clone(),
+            path: path.to_path_buf(),
+            label: Label { text: None, span },
+            extra_labels: vec![],
+        }),
+        hint: None,
+    }]
+}
+
+/// The location of the value of a key, such as `javascript.deno.allow_env`,
+/// or of the header of the table if the key is the name of a table.
+///
+fn key_span(src: &str, key: &str) -> Option<SrcSpan> {
+    let (table, name) = key.rsplit_once('.').unwrap_or(("", key));
+    let mut current_table = "";
+    let mut line_start = 0;
+    for line in src.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let code = line.trim_end();
+        let span =
+            |offset: usize| SrcSpan::new((start + offset) as u32, (start + code.len()) as u32);
+
+        if let Some(header) = table_name(line) {
+            if header == key {
+                return Some(span(line.len() - line.trim_start().len()));
+            }
+            current_table = header;
+            continue;
+        }
+
+        let Some((line_key, value)) = code.split_once('=') else {
+            continue;
+        };
+        if current_table == table && unquote(line_key.trim()) == name {
+            return Some(span(code.len() - value.trim_start().len()));
+        }
+    }
+    None
+}
+
+/// A version of a Hex package that has been downloaded to the local package
+/// cache.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedPackage {
+    pub name: EcoString,
+    pub version: Version,
+}
+
+/// The packages in the local package cache, whose tarballs are named after the
+/// package and its version, such as `gleam_stdlib-0.60.0.tar`.
+///
+pub fn cached_packages(io: &impl FileSystemReader, directory: &Utf8Path) -> Vec<CachedPackage> {
+    let Ok(entries) = io.read_dir(directory) else {
+        return vec![];
+    };
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let file_name = entry.as_path().file_name()?.strip_suffix(".tar")?;
+            // Package names can't contain a dash, but versions can.
+            let (name, version) = file_name.split_once('-')?;
+            Some(CachedPackage {
+                name: name.into(),
+                version: Version::parse(version).ok()?,
+            })
+        })
+        // The latest version of each package comes first.
+        .sorted_by(|one, other| {
+            one.name
+                .cmp(&other.name)
+                .then_with(|| other.version.cmp(&one.version))
+        })
+        .collect()
+}
+
+/// What's being written at the cursor.
+///
+enum Context<'a> {
+    /// The name of a table, or of an array of tables if it's opened with two
+    /// brackets.
+    Table { array: bool },
+    /// A key in the given table.
+    Key { table: &'a str },
+    /// The value of a key in the given table.
+    Value { table: &'a str, key: &'a str },
+}
+
+/// The part of the line being written at the cursor, along with what it is.
+///
+struct Cursor<'a> {
+    context: Context<'a>,
+    /// The start of the table name, key, or value being written.
+    start: Position,
+    position: Position,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(src: &'a str, position: Position) -> Option<Self> {
+        // Positions count UTF-16 code units, so they are converted to byte
+        // offsets before slicing the line, and back when reporting where the
+        // text being written starts.
+        let line_numbers = LineNumbers::new(src);
+        let line_start = line_numbers.byte_index(Position::new(position.line, 0));
+        let line = line_at(src, line_start);
+        let cursor = line_numbers.byte_index(position) - line_start;
+        let before = line.get(..cursor as usize)?;
+        let indentation = before.len() - before.trim_start().len();
+        let start = |offset: usize| lsp_position(&line_numbers, line_start + offset as u32);
+
+        if before.trim_start().starts_with('[') {
+            let array = before.trim_start().starts_with("[[");
+            return Some(Self {
+                context: Context::Table { array },
+                start: start(indentation),
+                position,
+            });
+        }
+
+        let table = current_table(src, position.line);
+        let (context, offset) = match before.split_once('=') {
+            Some((key, value)) => {
+                let key = unquote(key.trim());
+                let value_start = before.len() - value.trim_start().len();
+                (Context::Value { table, key }, value_start)
+            }
+            None => (Context::Key { table }, indentation),
+        };
+        Some(Self {
+            context,
+            start: start(offset),
+            position,
+        })
+    }
+
+    fn completion(
+        &self,
+        label: impl Into<String>,
+        new_text: impl Into<String>,
+        kind: CompletionItemKind,
+        detail: Option<String>,
+    ) -> CompletionItem {
+        CompletionItem {
+            label: label.into(),
+            kind: Some(kind),
+            detail,
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(self.start, self.position),
+                new_text: new_text.into(),
+            })),
+            ..Default::default()
+        }
+    }
+}
+
+/// The name of the table a line of the file is in, the top level one being
+/// named with the empty string.
+///
+fn current_table(src: &str, line: u32) -> &str {
+    src.lines()
+        .take(line as usize)
+        .filter_map(table_name)
+        .last()
+        .unwrap_or("")
+}
+
+/// The name of the table if the line is the header of one, such as
+/// `[javascript.deno]` or `[[links]]`.
+///
+fn table_name(line: &str) -> Option<&str> {
+    let name = line.trim().strip_prefix('[')?.trim_start_matches('[');
+    Some(name.split(']').next()?.trim())
+}
+
+fn unquote(text: &str) -> &str {
+    text.trim_matches('"')
+}
+
+/// The completions for the table name, key, or value being written at the
+/// given position. Dependencies are completed with the packages found in the
+/// local package cache.
+///
+pub fn completions(
+    src: &str,
+    position: Position,
+    cached_packages: &[CachedPackage],
+) -> Vec<CompletionItem> {
+    let Some(cursor) = Cursor::new(src, position) else {
+        return vec![];
+    };
+
+    match cursor.context {
+        Context::Table { array } => {
+            let (tables, brackets) = if array {
+                (ARRAYS_OF_TABLES, ("[[", "]]"))
+            } else {
+                (TABLES, ("[", "]"))
+            };
+            tables
+                .iter()
+                .map(|table| {
+                    let text = format!("{}{table}{}", brackets.0, brackets.1);
+                    cursor.completion(&text, &text, CompletionItemKind::MODULE, None)
+                })
+                .collect()
+        }
+
+        Context::Key {
+            table: "dependencies" | "dev-dependencies",
+        } => cached_packages
+            .iter()
+            .chunk_by(|package| &package.name)
+            .into_iter()
+            .filter_map(|(name, mut versions)| {
+                let latest = versions.next()?;
+                let detail = format!("Latest cached version {}", latest.version);
+                Some(cursor.completion(
+                    name.as_str(),
+                    name.as_str(),
+                    CompletionItemKind::MODULE,
+                    Some(detail),
+                ))
+            })
+            .collect(),
+
+        Context::Key { table: "warnings" } => WarningKind::VARIANTS
+            .iter()
+            .map(|kind| cursor.completion(*kind, *kind, CompletionItemKind::PROPERTY, None))
+            .collect(),
+
+        Context::Key { table } => KEYS
+            .iter()
+            .find(|(name, _)| *name == table)
+            .map(|(_, keys)| keys.iter())
+            .into_iter()
+            .flatten()
+            .map(|key| cursor.completion(*key, *key, CompletionItemKind::PROPERTY, None))
+            .collect(),
+
+        Context::Value {
+            table: "dependencies" | "dev-dependencies",
+            key: name,
+        } => cached_packages
+            .iter()
+            .filter(|package| package.name == name)
+            .map(|package| {
+                // The same requirement `gleam add` would add for the version.
+                let version = &package.version;
+                let requirement = format!(
+                    ">= {}.{}.{} and < {}.0.0",
+                    version.major,
+                    version.minor,
+                    version.patch,
+                    version.major + 1
+                );
+                cursor.completion(
+                    &requirement,
+                    format!("\"{requirement}\""),
+                    CompletionItemKind::VALUE,
+                    Some(format!("Cached version {version}")),
+                )
+            })
+            .collect(),
+
+        Context::Value { table, key } => {
+            let values: &[&str] = match (table, key) {
+                ("", "target") => &["\"erlang\"", "\"javascript\""],
+                ("javascript", "runtime") => &["\"nodejs\"", "\"deno\"", "\"bun\""],
+                ("javascript", "source_maps") => &["true", "false", "\"inline\""],
+                ("javascript", "typescript_declarations") | ("javascript.deno", _) => {
+                    &["true", "false"]
+                }
+                ("repository", "type") => &[
+                    "\"github\"",
+                    "\"gitlab\"",
+                    "\"bitbucket\"",
+                    "\"codeberg\"",
+                    "\"gitea\"",
+                    "\"forgejo\"",
+                    "\"sourcehut\"",
+                    "\"custom\"",
+                ],
+                ("warnings", _) => &["\"allow\"", "\"warn\"", "\"deny\""],
+                _ => &[],
+            };
+            values
+                .iter()
+                .map(|value| cursor.completion(*value, *value, CompletionItemKind::VALUE, None))
+                .collect()
+        }
+    }
+}
+
+/// The hover for the dependency at the given position, showing the version it
+/// is locked to in `manifest.toml`.
+///
+pub fn hover(src: &str, position: Position, manifest: Option<&Manifest>) -> Option<Hover> {
+    let line_numbers = LineNumbers::new(src);
+    let line_start = line_numbers.byte_index(Position::new(position.line, 0));
+    let line = line_at(src, line_start);
+    let (key, _) = line.split_once('=')?;
+    let name = unquote(key.trim());
+    let key_start = line_start + line.find(name)? as u32;
+    let key_end = key_start + name.len() as u32;
+    if !(key_start..=key_end).contains(&line_numbers.byte_index(position)) {
+        return None;
+    }
+    match current_table(src, position.line) {
+        "dependencies" | "dev-dependencies" => (),
+        _ => return None,
+    }
+
+    let package = manifest?
+        .packages
+        .iter()
+        .find(|package| package.name == name)?;
+    let source = match &package.source {
+        ManifestPackageSource::Hex { .. } => "from Hex".into(),
+        ManifestPackageSource::Git { repo, commit } => format!("from `{repo}` at `{commit}`"),
+        ManifestPackageSource::Local { path } => format!("from `{path}`"),
+    };
+    let contents = format!(
+        "```toml
+{name} = \"{version}\"
+```
+Locked in `manifest.toml`, {source}.",
+        version = package.version,
+    );
+
+    Some(Hover {
+        contents: HoverContents::Scalar(MarkedString::String(contents)),
+        range: Some(Range::new(
+            lsp_position(&line_numbers, key_start),
+            lsp_position(&line_numbers, key_end),
+        )),
+    })
+}
+
+/// The line starting at the given byte index, without its line ending.
+///
+fn line_at(src: &str, line_start: u32) -> &str {
+    src.get(line_start as usize..)
+        .and_then(|rest| rest.lines().next())
+        .unwrap_or_default()
+}
+
+fn lsp_position(line_numbers: &LineNumbers, byte_index: u32) -> Position {
+    let LineColumn { line, column } = line_numbers.line_and_column_number(byte_index);
+    Position::new(line - 1, column - 1)
+}
@@ -0,0 +1,258 @@
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use lsp_types::Location;
+
+use crate::{
+    ast::{SrcSpan, TypedFunction},
+    io::{DirWalker, FileSystemReader},
+    line_numbers::LineNumbers,
+};
+
+use super::{src_span_to_lsp_range, url_from_path};
+
+/// The Erlang and JavaScript implementations of an `@external` function.
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Externals<'a> {
+    pub erlang: Option<(&'a str, &'a str)>,
+    pub javascript: Option<(&'a str, &'a str)>,
+}
+
+impl<'a> Externals<'a> {
+    pub fn new(
+        erlang: Option<&'a (EcoString, EcoString)>,
+        javascript: Option<&'a (EcoString, EcoString)>,
+    ) -> Self {
+        Self {
+            erlang: erlang.map(|(module, function)| (module.as_str(), function.as_str())),
+            javascript: javascript.map(|(path, function)| (path.as_str(), function.as_str())),
+        }
+    }
+
+    pub fn of_function(function: &'a TypedFunction) -> Self {
+        let external = |external: &'a Option<(EcoString, EcoString, SrcSpan)>| {
+            external
+                .as_ref()
+                .map(|(module, function, _)| (module.as_str(), function.as_str()))
+        };
+        Self {
+            erlang: external(&function.external_erlang),
+            javascript: external(&function.external_javascript),
+        }
+    }
+}
+
+/// The locations of the native implementations of an `@external` function
+/// defined in the Gleam module at the given path. Only the native files of the
+/// package defining the function are looked at: implementations in other
+/// packages, or in the standard libraries of Erlang and JavaScript, can't be
+/// found.
+///
+pub fn external_implementations(
+    io: &impl FileSystemReader,
+    module_name: &str,
+    module_path: &Utf8Path,
+    externals: Externals<'_>,
+) -> Vec<Location> {
+    let mut locations = vec![];
+
+    if let Some((module, function)) = externals.erlang {
+        let found = match module.strip_prefix("Elixir.") {
+            Some(module) => {
+                let definition = format!("defmodule {module} do");
+                native_files(io, module_name, module_path, "ex")
+                    .into_iter()
+                    .find_map(|path| {
+                        let src = io.read(&path).ok()?;
+                        let start = src.find(&definition)?;
+                        let span = elixir_function(&src, start, function)
+                            .unwrap_or(SrcSpan::new(start as u32, start as u32));
+                        Some((path, src, span))
+                    })
+            }
+            None => native_files(io, module_name, module_path, "erl")
+                .into_iter()
+                .find(|path| path.file_stem() == Some(module))
+                .and_then(|path| {
+                    let src = io.read(&path).ok()?;
+                    let span = erlang_function(&src, function).unwrap_or_default();
+                    Some((path, src, span))
+                }),
+        };
+        locations.extend(found.and_then(|(path, src, span)| location(&path, &src, span)));
+    }
+
+    if let Some((path, function)) = externals.javascript {
+        let path = module_path
+            .parent()
+            .map(|directory| normalise(&directory.join(path)));
+        let found = path.and_then(|path| {
+            let src = io.read(&path).ok()?;
+            let span = javascript_function(&src, function).unwrap_or_default();
+            location(&path, &src, span)
+        });
+        locations.extend(found);
+    }
+
+    locations
+}
+
+fn location(path: &Utf8Path, src: &str, span: SrcSpan) -> Option<Location> {
+    Some(Location {
+        uri: url_from_path(path.as_str())?,
+        range: src_span_to_lsp_range(span, &LineNumbers::new(src)),
+    })
+}
+
+/// The paths of the native files with the given extension in the package of
+/// the Gleam module at the given path.
+///
+fn native_files(
+    io: &impl FileSystemReader,
+    module_name: &str,
+    module_path: &Utf8Path,
+    extension: &str,
+) -> Vec<Utf8PathBuf> {
+    // The module is in the `src`, `test`, or `dev` directory of its package.
+    let Some(package_root) = module_path
+        .as_str()
+        .strip_suffix(&format!("{module_name}.gleam"))
+        .map(Utf8Path::new)
+        .and_then(Utf8Path::parent)
+    else {
+        return vec![];
+    };
+
+    ["src", "test", "dev"]
+        .into_iter()
+        .map(|directory| package_root.join(directory))
+        .filter(|directory| io.is_directory(directory))
+        .flat_map(|directory| {
+            DirWalker::new(directory)
+                .into_file_iter(io)
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>()
+        })
+        .filter(|path| path.extension() == Some(extension))
+        .collect()
+}
+
+/// Removes the `.` and `..` components of a path, as the path to a JavaScript
+/// module is relative to the Gleam module using it.
+///
+fn normalise(path: &Utf8Path) -> Utf8PathBuf {
+    let mut normalised = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => (),
+            Utf8Component::ParentDir => {
+                let _ = normalised.pop();
+            }
+            component => normalised.push(component),
+        }
+    }
+    normalised
+}
+
+/// The location of the name of the first clause of an Erlang function, or of
+/// its export if there's none.
+///
+fn erlang_function(src: &str, name: &str) -> Option<SrcSpan> {
+    let quoted = format!("'{name}'");
+    let clause = lines(src).find_map(|(start, line)| {
+        [name, quoted.as_str()]
+            .into_iter()
+            .find(|name| {
+                line.strip_prefix(name)
+                    .is_some_and(|rest| rest.trim_start().starts_with('('))
+            })
+            .map(|name| span(start, name))
+    });
+
+    clause.or_else(|| {
+        lines(src)
+            .filter(|(_, line)| line.starts_with("-export("))
+            .find_map(|(start, line)| {
+                let exported = format!("{name}/");
+                let offset = find_name(line, &exported)?;
+                Some(span(start + offset, name))
+            })
+    })
+}
+
+/// The location of the name of a public Elixir function, defined after the
+/// start of its module.
+///
+fn elixir_function(src: &str, module_start: usize, name: &str) -> Option<SrcSpan> {
+    lines(src)
+        .filter(|(start, _)| *start >= module_start)
+        .find_map(|(start, line)| {
+            let indentation = line.len() - line.trim_start().len();
+            let rest = line.trim_start().strip_prefix("def ")?;
+            let offset = start + indentation + "def ".len();
+            starts_with_name(rest, name).then(|| span(offset, name))
+        })
+}
+
+/// The location of the name of an exported JavaScript function, or of a
+/// function with that name that is exported after being defined.
+///
+fn javascript_function(src: &str, name: &str) -> Option<SrcSpan> {
+    const EXPORTS: [&str; 8] = [
+        "export function ",
+        "export function* ",
+        "export async function ",
+        "export async function* ",
+        "export const ",
+        "export let ",
+        "export var ",
+        "function ",
+    ];
+
+    EXPORTS.into_iter().find_map(|export| {
+        lines(src).find_map(|(start, line)| {
+            let rest = line.strip_prefix(export)?;
+            starts_with_name(rest, name).then(|| span(start + export.len(), name))
+        })
+    })
+}
+
+/// The lines of some code, along with the byte offset of their start.
+///
+fn lines(src: &str) -> impl Iterator<Item = (usize, &str)> {
+    src.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line.trim_end()))
+    })
+}
+
+/// Whether some code starts with the given name, not followed by any other
+/// character that could be part of a longer name.
+///
+fn starts_with_name(code: &str, name: &str) -> bool {
+    code.strip_prefix(name).is_some_and(|rest| {
+        !rest
+            .chars()
+            .next()
+            .is_some_and(|char| char.is_alphanumeric() || char == '_' || char == '$')
+    })
+}
+
+/// The offset of the first occurrence of a name in a line, that is not part of
+/// a longer name.
+///
+fn find_name(line: &str, name: &str) -> Option<usize> {
+    line.match_indices(name)
+        .find(|(offset, _)| {
+            !line
+                .get(..*offset)
+                .and_then(|before| before.chars().next_back())
+                .is_some_and(|char| char.is_alphanumeric() || char == '_')
+        })
+        .map(|(offset, _)| offset)
+}
+
+fn span(start: usize, name: &str) -> SrcSpan {
+    SrcSpan::new(start as u32, (start + name.len()) as u32)
+}
@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use ecow::EcoString;
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Position, TextEdit};
+use vec1::Vec1;
+
+use crate::{
+    ast::{
+        AssignmentKind, FunctionLiteralKind, PipelineAssignmentKind, SrcSpan, TypeAst, TypedArg,
+        TypedAssignment, TypedExpr, TypedFunction, TypedPattern, TypedPipelineAssignment,
+        TypedStatement,
+        visit::{self, Visit},
+    },
+    build::Module,
+    line_numbers::LineNumbers,
+    type_::{Type, TypedCallArg, error::VariableOrigin, printer::Printer},
+};
+
+use super::{configuration::InlayHintsConfig, src_span_to_lsp_range};
+
+/// The inlay hints of a module that are enabled in the configuration and fall
+/// within the given range.
+///
+pub fn inlay_hints(module: &Module, config: InlayHintsConfig, range: SrcSpan) -> Vec<InlayHint> {
+    let mut collector = InlayHintsCollector {
+        module,
+        config,
+        line_numbers: LineNumbers::new(&module.code),
+        let_pattern: None,
+        hints: vec![],
+    };
+    collector.visit_typed_module(&module.ast);
+
+    collector
+        .hints
+        .into_iter()
+        .filter(|(position, _)| range.start <= *position && *position <= range.end)
+        .map(|(_, hint)| hint)
+        .collect()
+}
+
+struct InlayHintsCollector<'a> {
+    module: &'a Module,
+    config: InlayHintsConfig,
+    line_numbers: LineNumbers,
+    /// The location of the pattern of the `let` assignment being visited, if
+    /// its variables need a type hint.
+    let_pattern: Option<SrcSpan>,
+    /// The hints found so far, alongside the byte index they are shown at.
+    hints: Vec<(u32, InlayHint)>,
+}
+
+impl InlayHintsCollector<'_> {
+    fn position(&self, byte_index: u32) -> Position {
+        src_span_to_lsp_range(SrcSpan::new(byte_index, byte_index), &self.line_numbers).start
+    }
+
+    fn print_type(&self, type_: &Type) -> String {
+        Printer::new(&self.module.ast.names)
+            .print_type(type_)
+            .to_string()
+    }
+
+    /// Adds a type hint. If it is annotatable it can be double-clicked to
+    /// insert it into the code as an annotation.
+    ///
+    fn add_type_hint(
+        &mut self,
+        byte_index: u32,
+        label: String,
+        padding_left: bool,
+        is_annotatable: bool,
+    ) {
+        let position = self.position(byte_index);
+        let text_edits = is_annotatable.then(|| {
+            let new_text = if padding_left {
+                format!(" {label}")
+            } else {
+                label.clone()
+            };
+            vec![TextEdit {
+                range: lsp_types::Range::new(position, position),
+                new_text,
+            }]
+        });
+        let hint = InlayHint {
+            position,
+            label: InlayHintLabel::String(label),
+            kind: Some(InlayHintKind::TYPE),
+            text_edits,
+            tooltip: None,
+            padding_left: Some(padding_left),
+            padding_right: None,
+            data: None,
+        };
+        self.hints.push((byte_index, hint));
+    }
+
+    fn add_pipeline_step_hint(&mut self, step: &TypedExpr) {
+        let label = self.print_type(&step.type_());
+        self.add_type_hint(step.location().end, label, true, false);
+    }
+
+    fn add_return_type_hint(
+        &mut self,
+        head: SrcSpan,
+        return_annotation: &Option<TypeAst>,
+        type_: &Type,
+    ) {
+        if !self.config.return_types || return_annotation.is_some() {
+            return;
+        }
+        let label = format!("-> {}", self.print_type(type_));
+        self.add_type_hint(head.end, label, true, true);
+    }
+}
+
+impl<'ast> Visit<'ast> for InlayHintsCollector<'_> {
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        self.add_return_type_hint(fun.location, &fun.return_annotation, &fun.return_type);
+        visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        arguments: &'ast [TypedArg],
+        body: &'ast Vec1<TypedStatement>,
+        return_annotation: &'ast Option<TypeAst>,
+    ) {
+        if let (FunctionLiteralKind::Anonymous { head }, Some(return_type)) =
+            (kind, type_.return_type())
+        {
+            self.add_return_type_hint(*head, return_annotation, &return_type);
+        }
+        visit::visit_typed_expr_fn(
+            self,
+            location,
+            type_,
+            kind,
+            arguments,
+            body,
+            return_annotation,
+        );
+    }
+
+    fn visit_typed_assignment(&mut self, assignment: &'ast TypedAssignment) {
+        self.visit_typed_expr(&assignment.value);
+        let needs_hints = self.config.variable_types
+            && assignment.annotation.is_none()
+            && !matches!(assignment.kind, AssignmentKind::Generated);
+        if needs_hints {
+            self.let_pattern = Some(assignment.pattern.location());
+        }
+        self.visit_typed_pattern(&assignment.pattern);
+        self.let_pattern = None;
+    }
+
+    fn visit_typed_pattern_variable(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        type_: &'ast Arc<Type>,
+        origin: &'ast VariableOrigin,
+    ) {
+        let Some(pattern_location) = self.let_pattern else {
+            return;
+        };
+        // The location of a variable bound with the label shorthand syntax
+        // includes the colon after it.
+        let byte_index = location.start + name.len() as u32;
+        // Only a variable making up the whole pattern can be annotated.
+        let is_annotatable = *location == pattern_location;
+        let label = format!(": {}", self.print_type(type_));
+        self.add_type_hint(byte_index, label, false, is_annotatable);
+        visit::visit_typed_pattern_variable(self, location, name, type_, origin);
+    }
+
+    fn visit_typed_pattern_assign(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        pattern: &'ast TypedPattern,
+    ) {
+        if self.let_pattern.is_some() {
+            let label = format!(": {}", self.print_type(&pattern.type_()));
+            self.add_type_hint(location.end, label, false, false);
+        }
+        visit::visit_typed_pattern_assign(self, location, name, pattern);
+    }
+
+    fn visit_typed_expr_pipeline(
+        &mut self,
+        location: &'ast SrcSpan,
+        first_value: &'ast TypedPipelineAssignment,
+        assignments: &'ast [(TypedPipelineAssignment, PipelineAssignmentKind)],
+        finally: &'ast TypedExpr,
+        finally_kind: &'ast PipelineAssignmentKind,
+    ) {
+        // A pipeline written on a single line is short enough to not need
+        // the type of each of its steps.
+        let is_multiline = self.line_numbers.line_number(location.start)
+            != self.line_numbers.line_number(location.end);
+        if self.config.pipelines && is_multiline {
+            self.add_pipeline_step_hint(&first_value.value);
+            for (assignment, _) in assignments {
+                self.add_pipeline_step_hint(&assignment.value);
+            }
+            self.add_pipeline_step_hint(finally);
+        }
+        visit::visit_typed_expr_pipeline(
+            self,
+            location,
+            first_value,
+            assignments,
+            finally,
+            finally_kind,
+        );
+    }
+
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        type_: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        arguments: &'ast [TypedCallArg],
+    ) {
+        let field_map = fun.field_map().filter(|_| self.config.parameter_names);
+        if let Some(field_map) = field_map {
+            let labels = field_map.indices_to_labels();
+            for (index, argument) in arguments.iter().enumerate() {
+                // Arguments passed with a label, by a pipe or by `use` don't
+                // need a hint.
+                if argument.label.is_some() || argument.implicit.is_some() {
+                    continue;
+                }
+                let Some(label) = labels.get(&(index as u32)) else {
+                    continue;
+                };
+                // Neither do variables with the same name as the label.
+                if matches!(&argument.value, TypedExpr::Var { name, .. } if name == *label) {
+                    continue;
+                }
+                let byte_index = argument.location.start;
+                let hint = InlayHint {
+                    position: self.position(byte_index),
+                    label: InlayHintLabel::String(format!("{label}:")),
+                    kind: Some(InlayHintKind::PARAMETER),
+                    text_edits: None,
+                    tooltip: None,
+                    padding_left: None,
+                    padding_right: Some(true),
+                    data: None,
+                };
+                self.hints.push((byte_index, hint));
+            }
+        }
+        visit::visit_typed_expr_call(self, location, type_, fun, arguments);
+    }
+}
@@ -7,8 +7,13 @@ use lsp_types::{
     self as lsp,
     notification::{DidChangeTextDocument, DidCloseTextDocument, DidSaveTextDocument},
     request::{
-        CodeActionRequest, Completion, DocumentSymbolRequest, Formatting, GotoTypeDefinition,
-        HoverRequest, PrepareRenameRequest, References, Rename, SignatureHelpRequest,
+        CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+        CodeActionRequest, CodeLensRequest, Completion, DocumentDiagnosticRequest,
+        DocumentHighlightRequest, DocumentSymbolRequest, ExecuteCommand, FoldingRangeRequest,
+        Formatting, GotoImplementation, GotoTypeDefinition, HoverRequest, InlayHintRequest,
+        OnTypeFormatting, PrepareRenameRequest, RangeFormatting, References, Rename,
+        SelectionRangeRequest, SemanticTokensFullRequest, SemanticTokensRangeRequest,
+        SignatureHelpRequest, WillRenameFiles, WorkspaceDiagnosticRequest, WorkspaceSymbolRequest,
     },
 };
 use std::time::Duration;
@@ -22,9 +27,14 @@ pub enum Message {
 #[derive(Debug)]
 pub enum Request {
     Format(lsp::DocumentFormattingParams),
+    RangeFormat(lsp::DocumentRangeFormattingParams),
+    OnTypeFormat(lsp::DocumentOnTypeFormattingParams),
     Hover(lsp::HoverParams),
     GoToDefinition(lsp::GotoDefinitionParams),
     GoToTypeDefinition(lsp::GotoDefinitionParams),
+    GoToImplementation(lsp::GotoDefinitionParams),
+    DocumentDiagnostic(lsp::DocumentDiagnosticParams),
+    WorkspaceDiagnostic(lsp::WorkspaceDiagnosticParams),
     Completion(lsp::CompletionParams),
     CodeAction(lsp::CodeActionParams),
     SignatureHelp(lsp::SignatureHelpParams),
@@ -32,6 +42,19 @@ pub enum Request {
     PrepareRename(lsp::TextDocumentPositionParams),
     Rename(lsp::RenameParams),
     FindReferences(lsp::ReferenceParams),
+    WorkspaceSymbol(lsp::WorkspaceSymbolParams),
+    SemanticTokensFull(lsp::SemanticTokensParams),
+    SemanticTokensRange(lsp::SemanticTokensRangeParams),
+    InlayHint(lsp::InlayHintParams),
+    PrepareCallHierarchy(lsp::CallHierarchyPrepareParams),
+    IncomingCalls(Box<lsp::CallHierarchyIncomingCallsParams>),
+    OutgoingCalls(Box<lsp::CallHierarchyOutgoingCallsParams>),
+    ExecuteCommand(lsp::ExecuteCommandParams),
+    WillRenameFiles(lsp::RenameFilesParams),
+    DocumentHighlight(lsp::DocumentHighlightParams),
+    FoldingRange(lsp::FoldingRangeParams),
+    SelectionRange(lsp::SelectionRangeParams),
+    CodeLens(lsp::CodeLensParams),
 }
 
 impl Request {
@@ -42,6 +65,14 @@ impl Request {
                 let params = cast_request::<Formatting>(request);
                 Some(Message::Request(id, Request::Format(params)))
             }
+            "textDocument/rangeFormatting" => {
+                let params = cast_request::<RangeFormatting>(request);
+                Some(Message::Request(id, Request::RangeFormat(params)))
+            }
+            "textDocument/onTypeFormatting" => {
+                let params = cast_request::<OnTypeFormatting>(request);
+                Some(Message::Request(id, Request::OnTypeFormat(params)))
+            }
             "textDocument/hover" => {
                 let params = cast_request::<HoverRequest>(request);
                 Some(Message::Request(id, Request::Hover(params)))
@@ -78,10 +109,80 @@ impl Request {
                 let params = cast_request::<GotoTypeDefinition>(request);
                 Some(Message::Request(id, Request::GoToTypeDefinition(params)))
             }
+            "textDocument/implementation" => {
+                let params = cast_request::<GotoImplementation>(request);
+                Some(Message::Request(id, Request::GoToImplementation(params)))
+            }
+            "textDocument/diagnostic" => {
+                let params = cast_request::<DocumentDiagnosticRequest>(request);
+                Some(Message::Request(id, Request::DocumentDiagnostic(params)))
+            }
+            "workspace/diagnostic" => {
+                let params = cast_request::<WorkspaceDiagnosticRequest>(request);
+                Some(Message::Request(id, Request::WorkspaceDiagnostic(params)))
+            }
             "textDocument/references" => {
                 let params = cast_request::<References>(request);
                 Some(Message::Request(id, Request::FindReferences(params)))
             }
+            "workspace/symbol" => {
+                let params = cast_request::<WorkspaceSymbolRequest>(request);
+                Some(Message::Request(id, Request::WorkspaceSymbol(params)))
+            }
+            "textDocument/semanticTokens/full" => {
+                let params = cast_request::<SemanticTokensFullRequest>(request);
+                Some(Message::Request(id, Request::SemanticTokensFull(params)))
+            }
+            "textDocument/semanticTokens/range" => {
+                let params = cast_request::<SemanticTokensRangeRequest>(request);
+                Some(Message::Request(id, Request::SemanticTokensRange(params)))
+            }
+            "textDocument/inlayHint" => {
+                let params = cast_request::<InlayHintRequest>(request);
+                Some(Message::Request(id, Request::InlayHint(params)))
+            }
+            "textDocument/prepareCallHierarchy" => {
+                let params = cast_request::<CallHierarchyPrepare>(request);
+                Some(Message::Request(id, Request::PrepareCallHierarchy(params)))
+            }
+            "callHierarchy/incomingCalls" => {
+                let params = cast_request::<CallHierarchyIncomingCalls>(request);
+                Some(Message::Request(
+                    id,
+                    Request::IncomingCalls(Box::new(params)),
+                ))
+            }
+            "callHierarchy/outgoingCalls" => {
+                let params = cast_request::<CallHierarchyOutgoingCalls>(request);
+                Some(Message::Request(
+                    id,
+                    Request::OutgoingCalls(Box::new(params)),
+                ))
+            }
+            "workspace/executeCommand" => {
+                let params = cast_request::<ExecuteCommand>(request);
+                Some(Message::Request(id, Request::ExecuteCommand(params)))
+            }
+            "workspace/willRenameFiles" => {
+                let params = cast_request::<WillRenameFiles>(request);
+                Some(Message::Request(id, Request::WillRenameFiles(params)))
+            }
+            "textDocument/documentHighlight" => {
+                let params = cast_request::<DocumentHighlightRequest>(request);
+                Some(Message::Request(id, Request::DocumentHighlight(params)))
+            }
+            "textDocument/foldingRange" => {
+                let params = cast_request::<FoldingRangeRequest>(request);
+                Some(Message::Request(id, Request::FoldingRange(params)))
+            }
+            "textDocument/selectionRange" => {
+                let params = cast_request::<SelectionRangeRequest>(request);
+                Some(Message::Request(id, Request::SelectionRange(params)))
+            }
+            "textDocument/codeLens" => {
+                let params = cast_request::<CodeLensRequest>(request);
+                Some(Message::Request(id, Request::CodeLens(params)))
+            }
             _ => None,
         }
     }
@@ -210,8 +311,16 @@ impl MessageBuffer {
         };
 
         // Compile the code prior to attempting to process the response, to
-        // ensure that the response is based on the latest code.
-        self.push_compile_please_message();
+        // ensure that the response is based on the latest code. Workspace
+        // diagnostics are the exception: they compile the projects themselves
+        // so that they can report the diagnostics that are still valid before
+        // compiling.
+        if !matches!(
+            message,
+            Message::Request(_, Request::WorkspaceDiagnostic(_))
+        ) {
+            self.push_compile_please_message();
+        }
         self.messages.push(message);
         Next::Handle(self.take_messages())
     }
@@ -0,0 +1,998 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{
+    CreateFile, DocumentChangeOperation, DocumentChanges, OneOf,
+    OptionalVersionedTextDocumentIdentifier, ResourceOp, TextDocumentEdit,
+    TextDocumentPositionParams, TextEdit, WorkspaceEdit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::{Definition, Import, Layer, Publicity, SrcSpan, TypedDefinition, UnqualifiedImport},
+    build::Module,
+    reference::{Reference, ReferenceKind},
+    rename_module::{is_valid_module_name, qualifier},
+};
+
+use super::{TextEdits, url_from_path};
+
+/// The command moving a top-level definition to another module and updating
+/// all the references to it. Its only argument is a `MoveDefinitionParams`.
+///
+pub const MOVE_DEFINITION_COMMAND: &str = "gleam.moveDefinition";
+
+/// The definition to move, identified by a position inside of it, and the
+/// module it is moved to.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveDefinitionParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+    /// The name of the destination module. It is created if it doesn't exist.
+    pub module: EcoString,
+}
+
+/// Why a definition can't be moved.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveDefinitionError {
+    /// The destination is the module the definition is already in, or isn't
+    /// a valid module name.
+    InvalidDestination,
+    /// The destination is a module of one of the project's dependencies.
+    DependencyModule,
+    /// The destination module is in a different directory, such as a `test`
+    /// module for a definition in `src`.
+    DifferentOrigin,
+    /// There's no top-level definition at the given position.
+    NoDefinition,
+    /// A private definition the moved code depends on is also used by the
+    /// code staying in its module.
+    PrivateDefinitionUsed,
+    /// The moved type is opaque and the code staying in its module uses its
+    /// constructors, which would no longer be accessible.
+    OpaqueConstructorUsed,
+    /// A moved name is already taken in the destination module.
+    NameClash,
+    /// The move would make two modules import each other.
+    ImportCycle,
+    /// The code of the definition or of one of its references couldn't be
+    /// rewritten.
+    CannotRewrite,
+}
+
+impl MoveDefinitionError {
+    pub fn message(&self) -> &'static str {
+        match self {
+            MoveDefinitionError::InvalidDestination => {
+                "The destination must be a valid module name other than the module \
+the definition is in."
+            }
+            MoveDefinitionError::DependencyModule => {
+                "Definitions cannot be moved into the modules of dependencies."
+            }
+            MoveDefinitionError::DifferentOrigin => {
+                "The destination module is not in the same directory as the definition, \
+such as a `test` module for a definition in `src`."
+            }
+            MoveDefinitionError::NoDefinition => {
+                "There is no top-level definition at this position."
+            }
+            MoveDefinitionError::PrivateDefinitionUsed => {
+                "The definition depends on a private definition that is also used by \
+the rest of its module."
+            }
+            MoveDefinitionError::OpaqueConstructorUsed => {
+                "The type is opaque and its constructors are used by the rest of its module, \
+which could not use them once the type is moved."
+            }
+            MoveDefinitionError::NameClash => {
+                "A name moved by the definition is already taken in the destination module."
+            }
+            MoveDefinitionError::ImportCycle => {
+                "Moving the definition would make two modules import each other."
+            }
+            MoveDefinitionError::CannotRewrite => {
+                "The code of the definition or of one of its references could not be rewritten."
+            }
+        }
+    }
+}
+
+/// The edits moving the definition found at the given position of a module
+/// to the destination module, together with the private definitions it
+/// depends on. The imports of all the modules in the project are updated to
+/// follow the moved definitions.
+///
+pub fn move_definition(
+    modules: &HashMap<EcoString, Module>,
+    module_name: &EcoString,
+    byte_index: u32,
+    destination: &EcoString,
+) -> Result<WorkspaceEdit, MoveDefinitionError> {
+    if destination == module_name || !is_valid_module_name(destination) {
+        return Err(MoveDefinitionError::InvalidDestination);
+    }
+    let module = modules
+        .get(module_name)
+        .ok_or(MoveDefinitionError::NoDefinition)?;
+    let destination_module = modules.get(destination);
+    if destination_module.is_some_and(|destination| destination.origin != module.origin) {
+        return Err(MoveDefinitionError::DifferentOrigin);
+    }
+
+    let definitions = top_level_definitions(module);
+    let target = definitions
+        .iter()
+        .position(|definition| definition.code_location.contains(byte_index))
+        .ok_or(MoveDefinitionError::NoDefinition)?;
+    let moved = moved_definitions(module, &definitions, target);
+    let moved_spans = moved
+        .iter()
+        .filter_map(|index| definitions.get(*index))
+        .map(|definition| definition.code_location)
+        .collect_vec();
+
+    let mut values = HashSet::new();
+    let mut types = HashSet::new();
+    for definition in moved.iter().filter_map(|index| definitions.get(*index)) {
+        values.extend(definition.values.iter().cloned());
+        types.extend(definition.types.iter().cloned());
+    }
+    let move_ = Move {
+        source: module_name,
+        destination,
+        values,
+        types,
+    };
+
+    // The code staying in the module may only use the definitions that were
+    // moved if they are public in the destination module.
+    let mut make_target_public = false;
+    for (layer, (referenced_module, name), reference) in references(module) {
+        if !move_.is_moved(layer, referenced_module, name)
+            || within_any(reference.location, &moved_spans)
+            || !is_use(reference)
+        {
+            continue;
+        }
+        let owner = definitions
+            .iter()
+            .position(|definition| definition.defines(layer, name))
+            .ok_or(MoveDefinitionError::CannotRewrite)?;
+        let definition = definitions
+            .get(owner)
+            .ok_or(MoveDefinitionError::CannotRewrite)?;
+        match definition.definition {
+            _ if owner != target => return Err(MoveDefinitionError::PrivateDefinitionUsed),
+            Definition::CustomType(custom_type) if custom_type.opaque && layer == Layer::Value => {
+                return Err(MoveDefinitionError::OpaqueConstructorUsed);
+            }
+            _ => {}
+        }
+        make_target_public = definition.publicity == Publicity::Private;
+    }
+
+    if destination_module.is_some_and(|destination| move_.clashes_with(destination)) {
+        return Err(MoveDefinitionError::NameClash);
+    }
+
+    let imports_after_move = imports_after_move(modules, &move_, &moved_spans);
+    if has_import_cycle(&imports_after_move, destination) {
+        return Err(MoveDefinitionError::ImportCycle);
+    }
+
+    // The code of the moved definitions, rewritten to be valid in the
+    // destination module.
+    let mut required: BTreeMap<EcoString, Required> = BTreeMap::new();
+    let mut replacements = vec![];
+    for (layer, (referenced_module, name), reference) in references(module) {
+        if !within_any(reference.location, &moved_spans) || !is_use(reference) {
+            continue;
+        }
+        let name_start = reference.location.end - name.len() as u32;
+
+        if referenced_module == module_name {
+            if move_.is_moved(layer, referenced_module, name) {
+                continue;
+            }
+            let alias = destination_module
+                .map_or(Some(last_segment(module_name)), |destination| {
+                    alias_of(destination, module_name)
+                })
+                .ok_or(MoveDefinitionError::CannotRewrite)?;
+            let _ = required.entry(module_name.clone()).or_default();
+            replacements.push((reference.location, format!("{alias}.{name}")));
+            continue;
+        }
+
+        let Some(import) = import_of(module, referenced_module) else {
+            continue;
+        };
+        if referenced_module == destination {
+            match reference.kind {
+                ReferenceKind::Qualified => replacements.push((
+                    qualifier(&module.code, name_start)
+                        .ok_or(MoveDefinitionError::CannotRewrite)?,
+                    String::new(),
+                )),
+                ReferenceKind::Alias => replacements.push((reference.location, name.to_string())),
+                _ => {}
+            }
+            continue;
+        }
+
+        let requirement = required.entry(referenced_module.clone()).or_default();
+        match reference.kind {
+            ReferenceKind::Qualified => {
+                let alias = match destination_module
+                    .and_then(|destination| import_of(destination, referenced_module))
+                {
+                    Some(destination_import) => destination_import
+                        .used_name()
+                        .ok_or(MoveDefinitionError::CannotRewrite)?,
+                    None => {
+                        requirement.alias =
+                            import.as_name.as_ref().and_then(|_| import.used_name());
+                        import
+                            .used_name()
+                            .ok_or(MoveDefinitionError::CannotRewrite)?
+                    }
+                };
+                if Some(&alias) != import.used_name().as_ref() {
+                    replacements.push((
+                        qualifier(&module.code, name_start)
+                            .ok_or(MoveDefinitionError::CannotRewrite)?,
+                        format!("{alias}."),
+                    ));
+                }
+            }
+            _ => {
+                let item = unqualified_items(import)
+                    .find(|(item_layer, item)| *item_layer == layer && item.name == *name);
+                if let Some((_, item)) = item {
+                    let text = item_text(&module.code, layer, item)
+                        .ok_or(MoveDefinitionError::CannotRewrite)?;
+                    if !requirement.items.contains(&text) {
+                        requirement.items.push(text);
+                    }
+                }
+            }
+        }
+    }
+    // The unqualified items keep the order they had in the original imports.
+    for (imported, requirement) in required.iter_mut() {
+        let Some(import) = import_of(module, imported) else {
+            continue;
+        };
+        let order = unqualified_items(import)
+            .sorted_by_key(|(_, item)| item.location.start)
+            .filter_map(|(layer, item)| item_text(&module.code, layer, item))
+            .collect_vec();
+        requirement
+            .items
+            .sort_by_key(|item| order.iter().position(|text| text == item));
+    }
+
+    if make_target_public {
+        let start = definitions
+            .get(target)
+            .ok_or(MoveDefinitionError::CannotRewrite)?
+            .code_location
+            .start;
+        replacements.push((SrcSpan::new(start, start), "pub ".into()));
+    }
+
+    let mut moved_code = vec![];
+    for definition in moved.iter().filter_map(|index| definitions.get(*index)) {
+        let location = definition.location;
+        let inner = replacements
+            .iter()
+            .filter(|(replaced, _)| {
+                location.contains(replaced.start) || replaced.start == location.end
+            })
+            .cloned()
+            .collect_vec();
+        moved_code.push(
+            replace(&module.code, location, inner).ok_or(MoveDefinitionError::CannotRewrite)?,
+        );
+    }
+    let moved_code = moved_code.join("\n\n");
+
+    let mut changes = vec![];
+    for (name, other_module) in modules.iter().sorted_by_key(|(name, _)| *name) {
+        let is_source = name == module_name;
+        let is_destination = name == destination;
+        let removed = if is_source { &moved_spans[..] } else { &[] };
+        let mut module_required = if is_destination {
+            std::mem::take(&mut required)
+        } else {
+            BTreeMap::new()
+        };
+
+        let mut edits = TextEdits::new(&other_module.ast.type_info.line_numbers);
+        for (layer, (referenced_module, referenced_name), reference) in references(other_module) {
+            if !move_.is_moved(layer, referenced_module, referenced_name)
+                || within_any(reference.location, removed)
+                || !is_use(reference)
+            {
+                continue;
+            }
+            let name_start = reference.location.end - referenced_name.len() as u32;
+            let destination_alias = match import_of(other_module, destination) {
+                Some(import) => import.used_name(),
+                None => Some(last_segment(destination)),
+            };
+
+            match reference.kind {
+                ReferenceKind::Unqualified if is_source => {
+                    let _ = module_required.entry(destination.clone()).or_default();
+                    edits.replace(
+                        reference.location,
+                        format!(
+                            "{}.{referenced_name}",
+                            destination_alias.ok_or(MoveDefinitionError::CannotRewrite)?
+                        ),
+                    );
+                }
+                ReferenceKind::Qualified if is_destination => edits.delete(
+                    qualifier(&other_module.code, name_start)
+                        .ok_or(MoveDefinitionError::CannotRewrite)?,
+                ),
+                ReferenceKind::Qualified => {
+                    let _ = module_required.entry(destination.clone()).or_default();
+                    edits.replace(
+                        qualifier(&other_module.code, name_start)
+                            .ok_or(MoveDefinitionError::CannotRewrite)?,
+                        format!(
+                            "{}.",
+                            destination_alias.ok_or(MoveDefinitionError::CannotRewrite)?
+                        ),
+                    );
+                }
+                ReferenceKind::Alias if is_destination => {
+                    edits.replace(reference.location, referenced_name.to_string())
+                }
+                _ => {}
+            }
+        }
+
+        let mut deleted = vec![];
+        if is_source {
+            deleted.extend(
+                moved
+                    .iter()
+                    .filter_map(|index| definitions.get(*index))
+                    .map(|definition| deleted_location(&module.code, definition.location)),
+            );
+        }
+
+        update_imports(
+            other_module,
+            &move_,
+            removed,
+            &mut module_required,
+            &mut edits,
+            &mut deleted,
+        )
+        .ok_or(MoveDefinitionError::CannotRewrite)?;
+
+        for location in merge_locations(deleted) {
+            edits.delete(location);
+        }
+
+        if is_destination {
+            let code = &other_module.code;
+            let separator = if code.ends_with('\n') { "\n" } else { "\n\n" };
+            edits.insert(code.len() as u32, format!("{separator}{moved_code}\n"));
+        }
+
+        if edits.edits.is_empty() {
+            continue;
+        }
+        let uri = url_from_path(other_module.input_path.as_str())
+            .ok_or(MoveDefinitionError::CannotRewrite)?;
+        changes.push(document_edit(uri, edits.edits));
+    }
+
+    if destination_module.is_none() {
+        let mut path = module.input_path.clone();
+        for _ in module_name.split('/') {
+            let _ = path.pop();
+        }
+        let path = destination
+            .split('/')
+            .fold(path, |path, segment| path.join(segment));
+        let path = Utf8PathBuf::from(format!("{path}.gleam"));
+        let uri = url_from_path(path.as_str()).ok_or(MoveDefinitionError::CannotRewrite)?;
+
+        let imports = required
+            .iter()
+            .map(|(module, required)| required.import(module))
+            .join("\n");
+        let code = if imports.is_empty() {
+            format!("{moved_code}\n")
+        } else {
+            format!("{imports}\n\n{moved_code}\n")
+        };
+
+        changes.insert(
+            0,
+            DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                uri: uri.clone(),
+                options: None,
+                annotation_id: None,
+            })),
+        );
+        changes.insert(
+            1,
+            document_edit(
+                uri,
+                vec![TextEdit {
+                    range: Default::default(),
+                    new_text: code,
+                }],
+            ),
+        );
+    }
+
+    Ok(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(changes)),
+        change_annotations: None,
+    })
+}
+
+fn document_edit(uri: lsp_types::Url, edits: Vec<TextEdit>) -> DocumentChangeOperation {
+    DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+        edits: edits.into_iter().map(OneOf::Left).collect(),
+    })
+}
+
+fn last_segment(module: &EcoString) -> EcoString {
+    module.split('/').next_back().unwrap_or(module).into()
+}
+
+/// The names defined by the definitions being moved.
+///
+struct Move<'a> {
+    source: &'a EcoString,
+    destination: &'a EcoString,
+    values: HashSet<EcoString>,
+    types: HashSet<EcoString>,
+}
+
+impl Move<'_> {
+    fn is_moved(&self, layer: Layer, module: &EcoString, name: &EcoString) -> bool {
+        module == self.source
+            && match layer {
+                Layer::Value => self.values.contains(name),
+                Layer::Type => self.types.contains(name),
+            }
+    }
+
+    /// Whether a moved name is already used in the destination module, by one
+    /// of its definitions or by an unqualified import of another definition.
+    ///
+    fn clashes_with(&self, destination: &Module) -> bool {
+        destination
+            .ast
+            .definitions
+            .iter()
+            .any(|definition| match definition {
+                Definition::Import(import) => unqualified_items(import).any(|(layer, item)| {
+                    !self.is_moved(layer, &import.module, &item.name)
+                        && self.is_moved(layer, self.source, item.used_name())
+                }),
+                _ => {
+                    let definition = TopLevel::new(&destination.code, definition);
+                    definition
+                        .values
+                        .iter()
+                        .any(|name| self.values.contains(name))
+                        || definition
+                            .types
+                            .iter()
+                            .any(|name| self.types.contains(name))
+                }
+            })
+    }
+}
+
+/// A top-level definition of the module the definition is moved from.
+///
+struct TopLevel<'a> {
+    definition: &'a TypedDefinition,
+    /// The location of the definition including its documentation and
+    /// attributes.
+    location: SrcSpan,
+    code_location: SrcSpan,
+    publicity: Publicity,
+    values: Vec<EcoString>,
+    types: Vec<EcoString>,
+}
+
+impl<'a> TopLevel<'a> {
+    fn new(code: &str, definition: &'a TypedDefinition) -> Self {
+        let (code_location, publicity, values, types) = match definition {
+            Definition::Function(function) => (
+                function.full_location(),
+                function.publicity,
+                function.name.iter().map(|(_, name)| name.clone()).collect(),
+                vec![],
+            ),
+            Definition::TypeAlias(alias) => (
+                alias.location,
+                alias.publicity,
+                vec![],
+                vec![alias.alias.clone()],
+            ),
+            Definition::CustomType(custom_type) => (
+                custom_type.full_location(),
+                custom_type.publicity,
+                custom_type
+                    .constructors
+                    .iter()
+                    .map(|constructor| constructor.name.clone())
+                    .collect(),
+                vec![custom_type.name.clone()],
+            ),
+            Definition::ModuleConstant(constant) => (
+                SrcSpan::new(constant.location.start, constant.value.location().end),
+                constant.publicity,
+                vec![constant.name.clone()],
+                vec![],
+            ),
+            Definition::Import(import) => (import.location, Publicity::Private, vec![], vec![]),
+        };
+
+        TopLevel {
+            definition,
+            location: SrcSpan::new(leading_start(code, code_location.start), code_location.end),
+            code_location,
+            publicity,
+            values,
+            types,
+        }
+    }
+
+    fn defines(&self, layer: Layer, name: &EcoString) -> bool {
+        match layer {
+            Layer::Value => self.values.contains(name),
+            Layer::Type => self.types.contains(name),
+        }
+    }
+}
+
+fn top_level_definitions(module: &Module) -> Vec<TopLevel<'_>> {
+    module
+        .ast
+        .definitions
+        .iter()
+        .filter(|definition| !definition.is_import())
+        .map(|definition| TopLevel::new(&module.code, definition))
+        .sorted_by_key(|definition| definition.location.start)
+        .collect()
+}
+
+/// The start of the line of the documentation and attributes preceding the
+/// code starting at the given position.
+///
+fn leading_start(code: &str, start: u32) -> u32 {
+    let Some(before) = code.get(..start as usize) else {
+        return start;
+    };
+    let mut start = before.rfind('\n').map_or(0, |index| index + 1);
+    while let Some(previous) = before.get(..start).and_then(|code| code.strip_suffix('\n')) {
+        let line_start = previous.rfind('\n').map_or(0, |index| index + 1);
+        let line = previous.get(line_start..).unwrap_or_default().trim_start();
+        if !line.starts_with("///") && !line.starts_with('@') {
+            break;
+        }
+        start = line_start;
+    }
+    start as u32
+}
+
+/// The indices of the definitions being moved: the target one and all the
+/// private definitions it depends on.
+///
+fn moved_definitions(module: &Module, definitions: &[TopLevel<'_>], target: usize) -> Vec<usize> {
+    let mut moved = vec![target];
+    let mut queue = vec![target];
+    while let Some(index) = queue.pop() {
+        let Some(definition) = definitions.get(index) else {
+            continue;
+        };
+        for (layer, (referenced_module, name), reference) in references(module) {
+            if *referenced_module != module.name
+                || !is_use(reference)
+                || !definition.code_location.contains(reference.location.start)
+            {
+                continue;
+            }
+            let dependency = definitions.iter().position(|dependency| {
+                dependency.publicity == Publicity::Private && dependency.defines(layer, name)
+            });
+            match dependency {
+                Some(dependency) if !moved.contains(&dependency) => {
+                    moved.push(dependency);
+                    queue.push(dependency);
+                }
+                Some(_) | None => {}
+            }
+        }
+    }
+    moved.sort();
+    moved
+}
+
+/// The location to delete to remove a definition from its module, including
+/// the whitespace separating it from the following one. The last definition
+/// of a module takes the whitespace preceding it instead.
+///
+fn deleted_location(code: &str, location: SrcSpan) -> SrcSpan {
+    let SrcSpan { start, end } = location;
+    let after = code.get(end as usize..).unwrap_or_default();
+    let trimmed = after.trim_start();
+    if trimmed.is_empty() {
+        let before = code.get(..start as usize).unwrap_or_default().trim_end();
+        SrcSpan::new(before.len() as u32, end)
+    } else {
+        SrcSpan::new(start, end + (after.len() - trimmed.len()) as u32)
+    }
+}
+
+fn merge_locations(locations: Vec<SrcSpan>) -> Vec<SrcSpan> {
+    let mut merged: Vec<SrcSpan> = vec![];
+    for location in locations
+        .into_iter()
+        .sorted_by_key(|location| location.start)
+    {
+        match merged.last_mut() {
+            Some(last) if last.end >= location.start => last.end = last.end.max(location.end),
+            _ => merged.push(location),
+        }
+    }
+    merged
+}
+
+/// All the references made by a module, to values and to types.
+///
+fn references(
+    module: &Module,
+) -> impl Iterator<Item = (Layer, &(EcoString, EcoString), &Reference)> {
+    let references = &module.ast.type_info.references;
+    let values = references
+        .value_references
+        .iter()
+        .flat_map(|(key, references)| {
+            references
+                .iter()
+                .map(move |reference| (Layer::Value, key, reference))
+        });
+    let types = references
+        .type_references
+        .iter()
+        .flat_map(|(key, references)| {
+            references
+                .iter()
+                .map(move |reference| (Layer::Type, key, reference))
+        });
+    values.chain(types)
+}
+
+fn is_use(reference: &Reference) -> bool {
+    match reference.kind {
+        ReferenceKind::Qualified | ReferenceKind::Unqualified | ReferenceKind::Alias => true,
+        ReferenceKind::Import | ReferenceKind::Definition => false,
+    }
+}
+
+fn within_any(location: SrcSpan, spans: &[SrcSpan]) -> bool {
+    spans.iter().any(|span| span.contains(location.start))
+}
+
+fn import_of<'a>(module: &'a Module, imported: &EcoString) -> Option<&'a Import<EcoString>> {
+    module
+        .ast
+        .definitions
+        .iter()
+        .find_map(|definition| match definition {
+            Definition::Import(import) if import.module == *imported => Some(import),
+            _ => None,
+        })
+}
+
+fn alias_of(module: &Module, imported: &EcoString) -> Option<EcoString> {
+    match import_of(module, imported) {
+        Some(import) => import.used_name(),
+        None => Some(last_segment(imported)),
+    }
+}
+
+fn unqualified_items(
+    import: &Import<EcoString>,
+) -> impl Iterator<Item = (Layer, &UnqualifiedImport)> {
+    let types = import
+        .unqualified_types
+        .iter()
+        .map(|item| (Layer::Type, item));
+    let values = import
+        .unqualified_values
+        .iter()
+        .map(|item| (Layer::Value, item));
+    types.chain(values)
+}
+
+fn item_text(code: &str, layer: Layer, item: &UnqualifiedImport) -> Option<String> {
+    let text = code.get(item.imported_name_location.start as usize..item.location.end as usize)?;
+    Some(match layer {
+        Layer::Value => text.into(),
+        Layer::Type => format!("type {text}"),
+    })
+}
+
+/// The code at the given location with the replacements applied.
+///
+fn replace(code: &str, location: SrcSpan, replacements: Vec<(SrcSpan, String)>) -> Option<String> {
+    let mut result = String::new();
+    let mut position = location.start;
+    for (replaced, text) in replacements
+        .into_iter()
+        .sorted_by_key(|(replaced, _)| replaced.start)
+    {
+        result.push_str(code.get(position as usize..replaced.start as usize)?);
+        result.push_str(&text);
+        position = replaced.end;
+    }
+    result.push_str(code.get(position as usize..location.end as usize)?);
+    Some(result)
+}
+
+/// What a module needs to import from another one after the move.
+///
+#[derive(Debug, Default)]
+struct Required {
+    /// The explicit alias of a new import.
+    alias: Option<EcoString>,
+    items: Vec<String>,
+}
+
+impl Required {
+    fn import(&self, module: &EcoString) -> String {
+        import_code(module, &self.items, self.alias.as_ref())
+    }
+}
+
+fn import_code(module: &EcoString, items: &[String], alias: Option<&EcoString>) -> String {
+    let mut code = format!("import {module}");
+    if !items.is_empty() {
+        code.push_str(&format!(".{{{}}}", items.join(", ")));
+    }
+    if let Some(alias) = alias {
+        code.push_str(&format!(" as {alias}"));
+    }
+    code
+}
+
+/// Rewrites the imports of a module that are affected by the move and adds
+/// the required ones. An import is removed once nothing it was used for is
+/// left in the module.
+///
+fn update_imports(
+    module: &Module,
+    move_: &Move<'_>,
+    removed: &[SrcSpan],
+    required: &mut BTreeMap<EcoString, Required>,
+    edits: &mut TextEdits<'_>,
+    deleted: &mut Vec<SrcSpan>,
+) -> Option<()> {
+    let is_destination = module.name == *move_.destination;
+    let imports = module
+        .ast
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Import(import) => Some(import),
+            _ => None,
+        });
+
+    // The unqualified imports of moved definitions now come from the
+    // destination module.
+    if !is_destination {
+        for import in imports.clone() {
+            for (layer, item) in unqualified_items(import) {
+                if move_.is_moved(layer, &import.module, &item.name) {
+                    required
+                        .entry(move_.destination.clone())
+                        .or_default()
+                        .items
+                        .push(item_text(&module.code, layer, item)?);
+                }
+            }
+        }
+    }
+
+    let mut has_imports = false;
+    for import in imports {
+        let imported = &import.module;
+        let mut items = vec![];
+        let mut changed = false;
+        for (layer, item) in unqualified_items(import) {
+            let text = item_text(&module.code, layer, item)?;
+            if move_.is_moved(layer, imported, &item.name) {
+                changed = true;
+                continue;
+            }
+
+            let uses = references(module)
+                .filter(|(reference_layer, (referenced_module, name), reference)| {
+                    *reference_layer == layer
+                        && referenced_module == imported
+                        && *name == item.name
+                        && is_use(reference)
+                })
+                .map(|(_, _, reference)| reference.location)
+                .collect_vec();
+            if !uses.is_empty() && uses.iter().all(|location| within_any(*location, removed)) {
+                changed = true;
+                continue;
+            }
+            items.push(text);
+        }
+
+        let qualified_uses = references(module)
+            .filter(|(_, (referenced_module, _), reference)| {
+                referenced_module == imported && reference.kind == ReferenceKind::Qualified
+            })
+            .map(|(layer, (_, name), reference)| {
+                !within_any(reference.location, removed) && !move_.is_moved(layer, imported, name)
+            })
+            .collect_vec();
+        let is_qualified = qualified_uses.iter().any(|is_left| *is_left);
+        if !qualified_uses.is_empty() && !is_qualified {
+            changed = true;
+        }
+
+        let requirement = required.remove(imported);
+        let is_required = requirement.is_some();
+        for item in requirement.into_iter().flat_map(|required| required.items) {
+            if !items.contains(&item) {
+                items.push(item);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            has_imports = true;
+            continue;
+        }
+        if items.is_empty() && !is_qualified && !is_required {
+            deleted.push(deleted_location(&module.code, import.location));
+        } else {
+            has_imports = true;
+            let alias = import.as_name.as_ref().map(|(_, location)| {
+                EcoString::from(
+                    module
+                        .code
+                        .get(location.start as usize..location.end as usize)
+                        .unwrap_or_default(),
+                )
+            });
+            edits.replace(
+                import.location,
+                import_code(imported, &items, alias.as_ref()),
+            );
+        }
+    }
+
+    // The imports that are still missing are added before the first
+    // definition of the module.
+    if required.is_empty() {
+        return Some(());
+    }
+    let first = module
+        .ast
+        .definitions
+        .iter()
+        .map(|definition| TopLevel::new(&module.code, definition).location.start)
+        .min();
+    let imports = required
+        .iter()
+        .map(|(imported, required)| format!("{}\n", required.import(imported)))
+        .join("");
+    let (position, imports) = match first {
+        Some(first) if has_imports => (first, imports),
+        Some(first) => (first, format!("{imports}\n")),
+        None => (0, imports),
+    };
+    // The insertion goes before any other edit starting at the same position.
+    let position = edits.src_span_to_lsp_range(SrcSpan::new(position, position));
+    edits.edits.insert(
+        0,
+        TextEdit {
+            range: position,
+            new_text: imports,
+        },
+    );
+    required.clear();
+    Some(())
+}
+
+/// The imports of each module once the definitions have been moved.
+///
+fn imports_after_move(
+    modules: &HashMap<EcoString, Module>,
+    move_: &Move<'_>,
+    moved_spans: &[SrcSpan],
+) -> HashMap<EcoString, HashSet<EcoString>> {
+    let mut graph = HashMap::new();
+    let mut destination_imports = HashSet::new();
+
+    for (name, module) in modules {
+        let removed = if name == move_.source {
+            moved_spans
+        } else {
+            &[]
+        };
+        let mut imports = HashSet::new();
+        for (layer, (referenced_module, referenced_name), reference) in references(module) {
+            if !is_use(reference) {
+                continue;
+            }
+            let is_moved = move_.is_moved(layer, referenced_module, referenced_name);
+            if within_any(reference.location, removed) {
+                // The moved code keeps using the same modules from its new
+                // module.
+                if !is_moved {
+                    let _ = destination_imports.insert(referenced_module.clone());
+                }
+            } else if is_moved {
+                let _ = imports.insert(move_.destination.clone());
+            } else if referenced_module != name {
+                let _ = imports.insert(referenced_module.clone());
+            }
+        }
+
+        // Imports that aren't used still count, unless the move made them
+        // unused.
+        for definition in &module.ast.definitions {
+            match definition {
+                Definition::Import(import)
+                    if !references(module).any(|(_, (referenced_module, _), reference)| {
+                        referenced_module == &import.module && is_use(reference)
+                    }) =>
+                {
+                    let _ = imports.insert(import.module.clone());
+                }
+                _ => {}
+            }
+        }
+        let _ = graph.insert(name.clone(), imports);
+    }
+
+    let imports = graph.entry(move_.destination.clone()).or_default();
+    imports.extend(destination_imports);
+    let _ = imports.remove(move_.destination);
+    graph
+}
+
+fn has_import_cycle(graph: &HashMap<EcoString, HashSet<EcoString>>, module: &EcoString) -> bool {
+    let mut visited = HashSet::new();
+    let mut stack = graph.get(module).into_iter().flatten().collect_vec();
+    while let Some(imported) = stack.pop() {
+        if imported == module {
+            return true;
+        }
+        if visited.insert(imported) {
+            stack.extend(graph.get(imported).into_iter().flatten());
+        }
+    }
+    false
+}
@@ -1,6 +1,7 @@
 use crate::{
     Error, Result,
     build::SourceFingerprint,
+    config::WorkspaceConfig,
     error::{FileIoAction, FileKind},
     io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
     language_server::{
@@ -23,7 +24,9 @@ use super::feedback::FeedbackBookKeeper;
 /// an instance of the language server engine for each project.
 ///
 /// This router is responsible for finding or creating an engine for a given
-/// file using the nearest parent `gleam.toml` file.
+/// file using the nearest parent `gleam.toml` file. The projects that are
+/// members of a workspace each have their own engine, sharing the manifest
+/// and build directory of the workspace.
 ///
 #[derive(Debug)]
 pub(crate) struct Router<IO, Reporter> {
@@ -41,7 +44,9 @@ where
         + CommandExecutor
         + DownloadDependencies
         + MakeLocker
-        + Clone,
+        + Clone
+        + Send
+        + Sync,
     // IO to be supplied from inside of gleam-core
     Reporter: ProgressReporter + Clone,
 {
@@ -57,6 +62,11 @@ where
         find_gleam_project_parent(&self.io, path)
     }
 
+    /// The root directories of all the projects that have an engine.
+    pub fn project_roots(&self) -> Vec<Utf8PathBuf> {
+        self.engines.keys().cloned().collect()
+    }
+
     pub fn project_for_path(
         &mut self,
         path: Utf8PathBuf,
@@ -69,6 +79,10 @@ where
             let Some(path) = find_gleam_project_parent(&self.io, &path) else {
                 return Ok(None);
             };
+            // The root of a workspace is not a project, its members are
+            if WorkspaceConfig::read(path.join("gleam.toml"), &self.io)?.is_some() {
+                return Ok(None);
+            }
             path
         };
 
@@ -77,7 +91,7 @@ where
         // deps, etc may have changed and we need to rebuild taking them into
         // account.
         if let Some(project) = self.engines.get(&path) {
-            let paths = ProjectPaths::new(path.clone());
+            let paths = project_paths(&self.io, path.clone())?;
 
             if !self.io.exists(&paths.build_directory())
                 || Self::gleam_toml_changed(&paths, project, &self.io)?
@@ -130,7 +144,7 @@ where
         progress_reporter: Reporter,
     ) -> Result<Project<IO, Reporter>, Error> {
         tracing::info!(?path, "creating_new_language_server_engine");
-        let paths = ProjectPaths::new(path);
+        let paths = project_paths(&io, path)?;
         let config_path = paths.root_config();
         let modification_time = io.modification_time(&config_path)?;
         let toml = io.read(&config_path)?;
@@ -151,6 +165,18 @@ where
     }
 }
 
+/// The paths of the project with the given root, which shares the manifest
+/// and build directory of the workspace it is a member of, if any.
+pub fn project_paths<IO>(io: &IO, root: Utf8PathBuf) -> Result<ProjectPaths>
+where
+    IO: FileSystemReader,
+{
+    Ok(match WorkspaceConfig::find_root(&root, io)? {
+        Some(workspace) => ProjectPaths::workspace_member(root, workspace),
+        None => ProjectPaths::new(root),
+    })
+}
+
 /// Given a given path, find the nearest parent directory containing a
 /// `gleam.toml` file.
 ///
@@ -0,0 +1,156 @@
+use lsp_types::{Position, Range, SelectionRange};
+
+use crate::{
+    ast::{
+        SrcSpan, TypeAst, TypedClause, TypedCustomType, TypedExpr, TypedFunction,
+        TypedModuleConstant, TypedPattern, TypedStatement,
+        visit::{self, Visit},
+    },
+    build::Module,
+    line_numbers::LineNumbers,
+    type_::TypedCallArg,
+};
+
+use super::src_span_to_lsp_range;
+
+/// The ranges a selection starting at the given position can be expanded to,
+/// from the innermost node of the typed AST containing the position out to
+/// the definition it is in.
+///
+pub fn selection_range(module: &Module, position: Position) -> SelectionRange {
+    let line_numbers = LineNumbers::new(&module.code);
+    let byte_index = line_numbers.byte_index(position);
+    let mut finder = SelectionFinder {
+        byte_index,
+        locations: vec![],
+    };
+    finder.visit_typed_module(&module.ast);
+
+    // Each node containing the position is inside of the ones that were found
+    // before it, but a node can have the same location as its parent: a
+    // statement made of a single expression, for example.
+    let mut ranges: Option<SelectionRange> = None;
+    let mut parent_location: Option<SrcSpan> = None;
+    for location in finder.locations {
+        let is_nested = parent_location.is_none_or(|parent| {
+            parent != location && parent.start <= location.start && location.end <= parent.end
+        });
+        if !is_nested {
+            continue;
+        }
+        parent_location = Some(location);
+        ranges = Some(SelectionRange {
+            range: src_span_to_lsp_range(location, &line_numbers),
+            parent: ranges.map(Box::new),
+        });
+    }
+
+    // A position outside of any definition can only select itself.
+    ranges.unwrap_or_else(|| SelectionRange {
+        range: Range::new(position, position),
+        parent: None,
+    })
+}
+
+struct SelectionFinder {
+    byte_index: u32,
+    /// The locations of the nodes containing the position, from the outermost
+    /// to the innermost one.
+    locations: Vec<SrcSpan>,
+}
+
+impl SelectionFinder {
+    /// Records the location if it contains the position, returning whether it
+    /// does so the nodes inside of it are only visited when needed.
+    ///
+    fn found(&mut self, location: SrcSpan) -> bool {
+        let contains = location.contains(self.byte_index);
+        if contains {
+            self.locations.push(location);
+        }
+        contains
+    }
+}
+
+impl<'ast> Visit<'ast> for SelectionFinder {
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        if !self.found(fun.full_location()) {
+            return;
+        }
+        for argument in &fun.arguments {
+            let found = self.found(argument.location);
+            if let Some(annotation) = argument.annotation.as_ref().filter(|_| found) {
+                self.visit_type_ast(annotation);
+            }
+        }
+        if let Some(annotation) = &fun.return_annotation {
+            self.visit_type_ast(annotation);
+        }
+        let body = SrcSpan::new(
+            fun.body.first().location().start,
+            fun.body.last().location().end,
+        );
+        if self.found(body) {
+            visit::visit_typed_function(self, fun);
+        }
+    }
+
+    fn visit_typed_module_constant(&mut self, constant: &'ast TypedModuleConstant) {
+        let _ = self.found(SrcSpan::new(
+            constant.location.start,
+            constant.value.location().end,
+        ));
+    }
+
+    fn visit_typed_custom_type(&mut self, custom_type: &'ast TypedCustomType) {
+        if !self.found(custom_type.full_location()) {
+            return;
+        }
+        for constructor in &custom_type.constructors {
+            if !self.found(constructor.location) {
+                continue;
+            }
+            for argument in &constructor.arguments {
+                if self.found(argument.location) {
+                    self.visit_type_ast(&argument.ast);
+                }
+            }
+        }
+    }
+
+    fn visit_typed_statement(&mut self, stmt: &'ast TypedStatement) {
+        if self.found(stmt.location()) {
+            visit::visit_typed_statement(self, stmt);
+        }
+    }
+
+    fn visit_typed_expr(&mut self, expr: &'ast TypedExpr) {
+        if self.found(expr.location()) {
+            visit::visit_typed_expr(self, expr);
+        }
+    }
+
+    fn visit_typed_clause(&mut self, clause: &'ast TypedClause) {
+        if self.found(clause.location) {
+            visit::visit_typed_clause(self, clause);
+        }
+    }
+
+    fn visit_typed_call_arg(&mut self, arg: &'ast TypedCallArg) {
+        if self.found(arg.location) {
+            visit::visit_typed_call_arg(self, arg);
+        }
+    }
+
+    fn visit_typed_pattern(&mut self, pattern: &'ast TypedPattern) {
+        if self.found(pattern.location()) {
+            visit::visit_typed_pattern(self, pattern);
+        }
+    }
+
+    fn visit_type_ast(&mut self, node: &'ast TypeAst) {
+        if self.found(node.location()) {
+            visit::visit_type_ast(self, node);
+        }
+    }
+}
@@ -0,0 +1,851 @@
+use std::collections::{HashMap, HashSet};
+
+use ecow::EcoString;
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+use vec1::Vec1;
+
+use crate::{
+    analyse::Inferred,
+    ast::{
+        self, ArgNames, AssignName, CallArg, ClauseGuard, Constant, Definition,
+        FunctionLiteralKind, HasLocation, Import, SrcSpan, TypeAlias, TypeAst, TypedArg,
+        TypedAssignment, TypedClause, TypedClauseGuard, TypedConstant, TypedCustomType, TypedExpr,
+        TypedFunction, TypedModuleConstant, TypedPattern, TypedStatement,
+        visit::{self, Visit},
+    },
+    build::Module,
+    line_numbers::LineNumbers,
+    type_::{
+        ModuleInterface, ModuleValueConstructor, PatternConstructor, Type, TypedCallArg,
+        ValueConstructor, ValueConstructorVariant,
+        error::{VariableOrigin, VariableSyntax},
+    },
+};
+
+/// The kinds of token, in the order they are declared in the legend.
+///
+#[derive(Debug, Clone, Copy)]
+enum TokenType {
+    Namespace,
+    Type,
+    TypeParameter,
+    EnumMember,
+    Function,
+    Variable,
+    Property,
+}
+
+const TOKEN_TYPES: [SemanticTokenType; 7] = [
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+];
+
+// The bits of the token modifiers, in the order they are declared in the
+// legend.
+const DECLARATION: u32 = 1;
+const READONLY: u32 = 1 << 1;
+const DEPRECATED: u32 = 1 << 2;
+/// A variable which shadows another one with the same name, so it looks as if
+/// that variable had been changed.
+const MUTABLE: u32 = 1 << 3;
+const PARAMETER: u32 = 1 << 4;
+const LABEL: u32 = 1 << 5;
+const EXTERNAL: u32 = 1 << 6;
+
+const TOKEN_MODIFIERS: [SemanticTokenModifier; 7] = [
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DEPRECATED,
+    SemanticTokenModifier::new("mutable"),
+    SemanticTokenModifier::new("parameter"),
+    SemanticTokenModifier::new("label"),
+    SemanticTokenModifier::new("external"),
+];
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// The semantic tokens of a module, encoded as the language server protocol
+/// expects them. If a range is given only the tokens starting inside of it are
+/// returned.
+///
+pub fn semantic_tokens(
+    module: &Module,
+    modules: &im::HashMap<EcoString, ModuleInterface>,
+    range: Option<SrcSpan>,
+) -> Vec<SemanticToken> {
+    let mut builder = SemanticTokensBuilder::new(module, modules);
+    for definition in &module.ast.definitions {
+        builder.visit_typed_definition(definition);
+    }
+
+    let mut tokens = builder.tokens;
+    if let Some(range) = range {
+        tokens
+            .retain(|token| range.start <= token.location.start && token.location.end <= range.end);
+    }
+    encode(tokens, &LineNumbers::new(&module.code))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    location: SrcSpan,
+    type_: TokenType,
+    modifiers: u32,
+}
+
+/// Tokens are encoded relative to the previous one. Overlapping tokens and
+/// tokens spanning multiple lines are not allowed, so they are left out.
+///
+fn encode(mut tokens: Vec<Token>, line_numbers: &LineNumbers) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|token| token.location.start);
+
+    let mut encoded = Vec::with_capacity(tokens.len());
+    let mut previous_end = 0;
+    let mut previous_line = 0;
+    let mut previous_column = 0;
+    for token in tokens {
+        let SrcSpan { start, end } = token.location;
+        if start >= end || (start < previous_end && !encoded.is_empty()) {
+            continue;
+        }
+        let start = line_numbers.line_and_column_number(start);
+        let end = line_numbers.line_and_column_number(end);
+        if start.line != end.line {
+            continue;
+        }
+
+        let line = start.line - 1;
+        let column = start.column - 1;
+        let delta_line = line - previous_line;
+        encoded.push(SemanticToken {
+            delta_line,
+            delta_start: if delta_line == 0 {
+                column - previous_column
+            } else {
+                column
+            },
+            length: end.column - start.column,
+            token_type: token.type_ as u32,
+            token_modifiers_bitset: token.modifiers,
+        });
+
+        previous_end = token.location.end;
+        previous_line = line;
+        previous_column = column;
+    }
+    encoded
+}
+
+struct SemanticTokensBuilder<'a> {
+    module: &'a Module,
+    modules: &'a im::HashMap<EcoString, ModuleInterface>,
+    tokens: Vec<Token>,
+    /// The names of the variables in scope, used to tell which variables
+    /// shadow another one.
+    variables_in_scope: Vec<EcoString>,
+    /// Where the variables that shadow another one are defined.
+    mutable_variables: HashSet<u32>,
+    /// Where the parameters of functions are defined.
+    parameters: HashSet<u32>,
+    /// The names of the imported modules, by the name they are used with.
+    module_aliases: HashMap<EcoString, EcoString>,
+    /// The module and name of the types imported unqualified, by the name they
+    /// are used with.
+    unqualified_types: HashMap<EcoString, (EcoString, EcoString)>,
+}
+
+impl<'a> SemanticTokensBuilder<'a> {
+    fn new(module: &'a Module, modules: &'a im::HashMap<EcoString, ModuleInterface>) -> Self {
+        let mut module_aliases = HashMap::new();
+        let mut unqualified_types = HashMap::new();
+        for definition in &module.ast.definitions {
+            let Definition::Import(import) = definition else {
+                continue;
+            };
+            if let Some(alias) = import.used_name() {
+                let _ = module_aliases.insert(alias, import.module.clone());
+            }
+            for type_ in &import.unqualified_types {
+                let _ = unqualified_types.insert(
+                    type_.used_name().clone(),
+                    (import.module.clone(), type_.name.clone()),
+                );
+            }
+        }
+
+        Self {
+            module,
+            modules,
+            tokens: vec![],
+            variables_in_scope: vec![],
+            mutable_variables: HashSet::new(),
+            parameters: HashSet::new(),
+            module_aliases,
+            unqualified_types,
+        }
+    }
+
+    fn push(&mut self, location: SrcSpan, type_: TokenType, modifiers: u32) {
+        self.tokens.push(Token {
+            location,
+            type_,
+            modifiers,
+        });
+    }
+
+    fn interface(&self, module: &str) -> Option<&'a ModuleInterface> {
+        if module == self.module.name {
+            Some(&self.module.ast.type_info)
+        } else {
+            self.modules.get(module)
+        }
+    }
+
+    fn declare_variable(&mut self, name: &EcoString, location: SrcSpan, modifiers: u32) {
+        // Variables starting with an underscore are generated by the compiler
+        if name.starts_with('_') {
+            return;
+        }
+        let mut modifiers = modifiers | DECLARATION;
+        if modifiers & PARAMETER != 0 {
+            let _ = self.parameters.insert(location.start);
+        }
+        if self.variables_in_scope.contains(name) {
+            let _ = self.mutable_variables.insert(location.start);
+            modifiers |= MUTABLE;
+        }
+        self.variables_in_scope.push(name.clone());
+        self.push(location, TokenType::Variable, modifiers);
+    }
+
+    fn declare_label(&mut self, location: SrcSpan) {
+        self.push(location, TokenType::Property, LABEL | DECLARATION);
+    }
+
+    fn declare_arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            match &argument.names {
+                ArgNames::Discard { .. } => {}
+                ArgNames::LabelledDiscard { label_location, .. } => {
+                    self.declare_label(*label_location);
+                }
+                ArgNames::Named { name, location } => {
+                    self.declare_variable(name, *location, PARAMETER);
+                }
+                ArgNames::NamedLabelled {
+                    label_location,
+                    name,
+                    name_location,
+                    ..
+                } => {
+                    self.declare_label(*label_location);
+                    self.declare_variable(name, *name_location, PARAMETER);
+                }
+            }
+            if let Some(annotation) = &argument.annotation {
+                self.visit_type_ast(annotation);
+            }
+        }
+    }
+
+    /// The arguments of the callback function of a `use` are the patterns on
+    /// its left hand side. Those which are not a single variable are
+    /// assigned in the body of the function instead, so they are not included
+    /// here.
+    ///
+    fn declare_use_arguments(&mut self, arguments: &[TypedArg]) {
+        for argument in arguments {
+            if let ArgNames::Named { name, location } = &argument.names {
+                let location = SrcSpan::new(location.start, location.start + name.len() as u32);
+                self.declare_variable(name, location, 0);
+            }
+            if let Some(annotation) = &argument.annotation {
+                self.visit_type_ast(annotation);
+            }
+        }
+    }
+
+    fn local_variable(&mut self, location: SrcSpan, definition: SrcSpan) {
+        let mut modifiers = 0;
+        if self.parameters.contains(&definition.start) {
+            modifiers |= PARAMETER;
+        }
+        if self.mutable_variables.contains(&definition.start) {
+            modifiers |= MUTABLE;
+        }
+        self.push(location, TokenType::Variable, modifiers);
+    }
+
+    fn value(&mut self, location: SrcSpan, name: &EcoString, constructor: &ValueConstructor) {
+        let deprecated = if constructor.deprecation.is_deprecated() {
+            DEPRECATED
+        } else {
+            0
+        };
+        match &constructor.variant {
+            ValueConstructorVariant::LocalVariable {
+                location: definition,
+                origin,
+            } => {
+                if !name.starts_with('_') && origin.syntax != VariableSyntax::Generated {
+                    self.local_variable(location, *definition);
+                }
+            }
+            ValueConstructorVariant::ModuleConstant { .. }
+            | ValueConstructorVariant::LocalConstant { .. } => {
+                self.push(location, TokenType::Variable, READONLY | deprecated)
+            }
+            ValueConstructorVariant::ModuleFn {
+                external_erlang,
+                external_javascript,
+                ..
+            } => {
+                let external = if external_erlang.is_some() || external_javascript.is_some() {
+                    EXTERNAL
+                } else {
+                    0
+                };
+                self.push(location, TokenType::Function, external | deprecated)
+            }
+            ValueConstructorVariant::Record { .. } => {
+                self.push(location, TokenType::EnumMember, deprecated)
+            }
+        }
+    }
+
+    fn module_value(&mut self, location: SrcSpan, module: &str, name: &EcoString) {
+        let constructor = self
+            .interface(module)
+            .and_then(|interface| interface.values.get(name));
+        if let Some(constructor) = constructor {
+            self.value(location, name, constructor);
+        }
+    }
+
+    fn is_deprecated_type(&self, module: Option<&EcoString>, name: &EcoString) -> bool {
+        let (module, name) = match module {
+            Some(alias) => match self.module_aliases.get(alias) {
+                Some(module) => (module, name),
+                None => return false,
+            },
+            None => match self.unqualified_types.get(name) {
+                Some((module, name)) => (module, name),
+                None => (&self.module.name, name),
+            },
+        };
+        let Some(interface) = self.interface(module) else {
+            return false;
+        };
+        match (interface.types.get(name), interface.type_aliases.get(name)) {
+            (Some(type_), _) => type_.deprecation.is_deprecated(),
+            (None, Some(alias)) => alias.deprecation.is_deprecated(),
+            (None, None) => false,
+        }
+    }
+
+    fn namespace(&mut self, module: &Option<(EcoString, SrcSpan)>) {
+        if let Some((_, location)) = module {
+            self.push(*location, TokenType::Namespace, 0);
+        }
+    }
+
+    fn call_argument_label<T: HasLocation>(&mut self, argument: &CallArg<T>) {
+        match &argument.label {
+            Some(label) if argument.implicit.is_none() && !argument.uses_label_shorthand() => {
+                let start = argument.location.start;
+                let location = SrcSpan::new(start, start + label.len() as u32);
+                self.push(location, TokenType::Property, LABEL);
+            }
+            Some(_) | None => {}
+        }
+    }
+
+    fn import(&mut self, import: &Import<EcoString>) {
+        let module_start = self
+            .module
+            .code
+            .get(import.location.start as usize..import.location.end as usize)
+            .and_then(|code| code.find(import.module.as_str()));
+        if let Some(offset) = module_start {
+            let start = import.location.start + offset as u32;
+            let location = SrcSpan::new(start, start + import.module.len() as u32);
+            self.push(location, TokenType::Namespace, 0);
+        }
+        if let Some((AssignName::Variable(_), location)) = &import.as_name {
+            self.push(*location, TokenType::Namespace, DECLARATION);
+        }
+
+        for value in &import.unqualified_values {
+            let Some(constructor) = self
+                .interface(&import.module)
+                .and_then(|interface| interface.values.get(&value.name))
+            else {
+                continue;
+            };
+            self.value(value.imported_name_location, &value.name, constructor);
+            if let Some(as_name) = &value.as_name {
+                let location = SrcSpan::new(
+                    value.location.end - as_name.len() as u32,
+                    value.location.end,
+                );
+                self.value(location, as_name, constructor);
+            }
+        }
+
+        for type_ in &import.unqualified_types {
+            let deprecated = if self.is_deprecated_type(None, type_.used_name()) {
+                DEPRECATED
+            } else {
+                0
+            };
+            self.push(type_.imported_name_location, TokenType::Type, deprecated);
+            if let Some(as_name) = &type_.as_name {
+                let location = SrcSpan::new(
+                    type_.location.end - as_name.len() as u32,
+                    type_.location.end,
+                );
+                self.push(location, TokenType::Type, deprecated);
+            }
+        }
+    }
+
+    fn type_alias(&mut self, alias: &TypeAlias<std::sync::Arc<Type>>) {
+        let deprecated = if alias.deprecation.is_deprecated() {
+            DEPRECATED
+        } else {
+            0
+        };
+        self.push(
+            alias.name_location,
+            TokenType::Type,
+            DECLARATION | deprecated,
+        );
+        for (location, _) in &alias.parameters {
+            self.push(*location, TokenType::TypeParameter, DECLARATION);
+        }
+        self.visit_type_ast(&alias.type_ast);
+    }
+
+    fn constant(&mut self, constant: &TypedConstant) {
+        match constant {
+            Constant::Int { .. }
+            | Constant::Float { .. }
+            | Constant::String { .. }
+            | Constant::Invalid { .. } => {}
+
+            Constant::Tuple { elements, .. } | Constant::List { elements, .. } => {
+                for element in elements {
+                    self.constant(element);
+                }
+            }
+
+            Constant::Record {
+                location,
+                module,
+                name,
+                arguments,
+                record_constructor,
+                ..
+            } => {
+                self.namespace(module);
+                let start = match module {
+                    Some((_, module_location)) => module_location.end + 1,
+                    None => location.start,
+                };
+                if let Some(constructor) = record_constructor {
+                    let location = SrcSpan::new(start, start + name.len() as u32);
+                    self.value(location, name, constructor);
+                }
+                for argument in arguments {
+                    self.call_argument_label(argument);
+                    self.constant(&argument.value);
+                }
+            }
+
+            Constant::BitArray { segments, .. } => {
+                for segment in segments {
+                    self.constant(&segment.value);
+                }
+            }
+
+            Constant::Var {
+                location,
+                module,
+                name,
+                constructor,
+                ..
+            } => {
+                self.namespace(module);
+                if let Some(constructor) = constructor {
+                    let location = SrcSpan::new(location.end - name.len() as u32, location.end);
+                    self.value(location, name, constructor);
+                }
+            }
+
+            Constant::StringConcatenation { left, right, .. } => {
+                self.constant(left);
+                self.constant(right);
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for SemanticTokensBuilder<'_> {
+    fn visit_typed_definition(&mut self, def: &'ast ast::TypedDefinition) {
+        match def {
+            Definition::Import(import) => self.import(import),
+            Definition::TypeAlias(alias) => self.type_alias(alias),
+            Definition::Function(_) | Definition::CustomType(_) | Definition::ModuleConstant(_) => {
+                visit::visit_typed_definition(self, def)
+            }
+        }
+    }
+
+    fn visit_typed_function(&mut self, fun: &'ast TypedFunction) {
+        self.variables_in_scope.clear();
+
+        if let Some((location, _)) = &fun.name {
+            let mut modifiers = DECLARATION;
+            if fun.deprecation.is_deprecated() {
+                modifiers |= DEPRECATED;
+            }
+            if fun.external_erlang.is_some() || fun.external_javascript.is_some() {
+                modifiers |= EXTERNAL;
+            }
+            self.push(*location, TokenType::Function, modifiers);
+        }
+        self.declare_arguments(&fun.arguments);
+        if let Some(annotation) = &fun.return_annotation {
+            self.visit_type_ast(annotation);
+        }
+        visit::visit_typed_function(self, fun);
+    }
+
+    fn visit_typed_module_constant(&mut self, constant: &'ast TypedModuleConstant) {
+        let mut modifiers = DECLARATION | READONLY;
+        if constant.deprecation.is_deprecated() {
+            modifiers |= DEPRECATED;
+        }
+        self.push(constant.name_location, TokenType::Variable, modifiers);
+        if let Some(annotation) = &constant.annotation {
+            self.visit_type_ast(annotation);
+        }
+        self.constant(&constant.value);
+    }
+
+    fn visit_typed_custom_type(&mut self, custom_type: &'ast TypedCustomType) {
+        let mut modifiers = DECLARATION;
+        if custom_type.deprecation.is_deprecated() {
+            modifiers |= DEPRECATED;
+        }
+        self.push(custom_type.name_location, TokenType::Type, modifiers);
+        for (location, _) in &custom_type.parameters {
+            self.push(*location, TokenType::TypeParameter, DECLARATION);
+        }
+
+        for constructor in &custom_type.constructors {
+            let mut modifiers = DECLARATION;
+            if constructor.deprecation.is_deprecated() {
+                modifiers |= DEPRECATED;
+            }
+            self.push(constructor.name_location, TokenType::EnumMember, modifiers);
+            for argument in &constructor.arguments {
+                if let Some((location, _)) = &argument.label {
+                    self.declare_label(*location);
+                }
+                self.visit_type_ast(&argument.ast);
+            }
+        }
+    }
+
+    fn visit_typed_expr_block(
+        &mut self,
+        location: &'ast SrcSpan,
+        statements: &'ast [TypedStatement],
+    ) {
+        let scope = self.variables_in_scope.len();
+        visit::visit_typed_expr_block(self, location, statements);
+        self.variables_in_scope.truncate(scope);
+    }
+
+    fn visit_typed_expr_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        self.value(*location, name, constructor);
+    }
+
+    fn visit_typed_expr_fn(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _type_: &'ast std::sync::Arc<Type>,
+        kind: &'ast FunctionLiteralKind,
+        arguments: &'ast [TypedArg],
+        body: &'ast Vec1<TypedStatement>,
+        return_annotation: &'ast Option<TypeAst>,
+    ) {
+        let scope = self.variables_in_scope.len();
+        match kind {
+            FunctionLiteralKind::Capture { .. } => {}
+            FunctionLiteralKind::Anonymous { .. } => self.declare_arguments(arguments),
+            FunctionLiteralKind::Use { .. } => self.declare_use_arguments(arguments),
+        }
+        if let Some(annotation) = return_annotation {
+            self.visit_type_ast(annotation);
+        }
+        for statement in body {
+            self.visit_typed_statement(statement);
+        }
+        self.variables_in_scope.truncate(scope);
+    }
+
+    fn visit_typed_expr_record_access(
+        &mut self,
+        location: &'ast SrcSpan,
+        field_start: &'ast u32,
+        type_: &'ast std::sync::Arc<Type>,
+        label: &'ast EcoString,
+        index: &'ast u64,
+        record: &'ast TypedExpr,
+    ) {
+        visit::visit_typed_expr_record_access(
+            self,
+            location,
+            field_start,
+            type_,
+            label,
+            index,
+            record,
+        );
+        let location = SrcSpan::new(location.end - label.len() as u32, location.end);
+        self.push(location, TokenType::Property, 0);
+    }
+
+    fn visit_typed_expr_module_select(
+        &mut self,
+        location: &'ast SrcSpan,
+        _field_start: &'ast u32,
+        _type_: &'ast std::sync::Arc<Type>,
+        label: &'ast EcoString,
+        module_name: &'ast EcoString,
+        module_alias: &'ast EcoString,
+        _constructor: &'ast ModuleValueConstructor,
+    ) {
+        let module_location =
+            SrcSpan::new(location.start, location.start + module_alias.len() as u32);
+        self.push(module_location, TokenType::Namespace, 0);
+
+        let location = SrcSpan::new(location.end - label.len() as u32, location.end);
+        self.module_value(location, module_name, label);
+    }
+
+    fn visit_typed_assignment(&mut self, assignment: &'ast TypedAssignment) {
+        visit::visit_typed_assignment(self, assignment);
+        if let Some(annotation) = &assignment.annotation {
+            self.visit_type_ast(annotation);
+        }
+    }
+
+    fn visit_typed_call_arg(&mut self, arg: &'ast TypedCallArg) {
+        self.call_argument_label(arg);
+        visit::visit_typed_call_arg(self, arg);
+    }
+
+    fn visit_typed_clause(&mut self, clause: &'ast TypedClause) {
+        let scope = self.variables_in_scope.len();
+        for pattern in &clause.pattern {
+            self.visit_typed_pattern(pattern);
+        }
+
+        // Alternative patterns bind the same variables as the first one, so
+        // they don't shadow them.
+        let bound = self
+            .variables_in_scope
+            .split_off(scope.min(self.variables_in_scope.len()));
+        for patterns in &clause.alternative_patterns {
+            for pattern in patterns {
+                self.visit_typed_pattern(pattern);
+            }
+            self.variables_in_scope.truncate(scope);
+        }
+        self.variables_in_scope.extend(bound);
+
+        if let Some(guard) = &clause.guard {
+            self.visit_typed_clause_guard(guard);
+        }
+        self.visit_typed_expr(&clause.then);
+        self.variables_in_scope.truncate(scope);
+    }
+
+    fn visit_typed_clause_guard(&mut self, guard: &'ast TypedClauseGuard) {
+        match guard {
+            ClauseGuard::Constant(constant) => self.constant(constant),
+            _ => visit::visit_typed_clause_guard(self, guard),
+        }
+    }
+
+    fn visit_typed_clause_guard_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        _name: &'ast EcoString,
+        _type_: &'ast std::sync::Arc<Type>,
+        definition_location: &'ast SrcSpan,
+    ) {
+        self.local_variable(*location, *definition_location);
+    }
+
+    fn visit_typed_clause_guard_field_access(
+        &mut self,
+        location: &'ast SrcSpan,
+        index: &'ast Option<u64>,
+        label: &'ast EcoString,
+        type_: &'ast std::sync::Arc<Type>,
+        container: &'ast TypedClauseGuard,
+    ) {
+        visit::visit_typed_clause_guard_field_access(
+            self, location, index, label, type_, container,
+        );
+        let location = SrcSpan::new(location.end - label.len() as u32, location.end);
+        self.push(location, TokenType::Property, 0);
+    }
+
+    fn visit_typed_clause_guard_module_select(
+        &mut self,
+        location: &'ast SrcSpan,
+        _type_: &'ast std::sync::Arc<Type>,
+        label: &'ast EcoString,
+        module_name: &'ast EcoString,
+        module_alias: &'ast EcoString,
+        _literal: &'ast TypedConstant,
+    ) {
+        let module_location =
+            SrcSpan::new(location.start, location.start + module_alias.len() as u32);
+        self.push(module_location, TokenType::Namespace, 0);
+        let location = SrcSpan::new(location.end - label.len() as u32, location.end);
+        self.module_value(location, module_name, label);
+    }
+
+    fn visit_typed_pattern_variable(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        _type_: &'ast std::sync::Arc<Type>,
+        origin: &'ast VariableOrigin,
+    ) {
+        if origin.syntax != VariableSyntax::Generated {
+            // With the label shorthand syntax the location includes the colon
+            let location = SrcSpan::new(location.start, location.start + name.len() as u32);
+            self.declare_variable(name, location, 0);
+        }
+    }
+
+    fn visit_typed_bit_array_size_variable(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        constructor: &'ast Option<Box<ValueConstructor>>,
+        _type_: &'ast std::sync::Arc<Type>,
+    ) {
+        if let Some(constructor) = constructor {
+            self.value(*location, name, constructor);
+        }
+    }
+
+    fn visit_typed_pattern_assign(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        pattern: &'ast TypedPattern,
+    ) {
+        visit::visit_typed_pattern_assign(self, location, name, pattern);
+        self.declare_variable(name, *location, 0);
+    }
+
+    fn visit_typed_pattern_constructor(
+        &mut self,
+        location: &'ast SrcSpan,
+        name_location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        arguments: &'ast Vec<CallArg<TypedPattern>>,
+        module: &'ast Option<(EcoString, SrcSpan)>,
+        constructor: &'ast Inferred<PatternConstructor>,
+        spread: &'ast Option<SrcSpan>,
+        type_: &'ast std::sync::Arc<Type>,
+    ) {
+        self.namespace(module);
+        match constructor {
+            Inferred::Known(constructor) => {
+                self.module_value(*name_location, &constructor.module, name)
+            }
+            Inferred::Unknown => self.push(*name_location, TokenType::EnumMember, 0),
+        }
+        visit::visit_typed_pattern_constructor(
+            self,
+            location,
+            name_location,
+            name,
+            arguments,
+            module,
+            constructor,
+            spread,
+            type_,
+        );
+    }
+
+    fn visit_typed_pattern_call_arg(&mut self, arg: &'ast CallArg<TypedPattern>) {
+        self.call_argument_label(arg);
+        visit::visit_typed_pattern_call_arg(self, arg);
+    }
+
+    fn visit_typed_pattern_string_prefix(
+        &mut self,
+        _location: &'ast SrcSpan,
+        _left_location: &'ast SrcSpan,
+        left_side_assignment: &'ast Option<(EcoString, SrcSpan)>,
+        right_location: &'ast SrcSpan,
+        _left_side_string: &'ast EcoString,
+        right_side_assignment: &'ast AssignName,
+    ) {
+        if let Some((name, location)) = left_side_assignment {
+            self.declare_variable(name, *location, 0);
+        }
+        if let AssignName::Variable(name) = right_side_assignment {
+            self.declare_variable(name, *right_location, 0);
+        }
+    }
+
+    fn visit_type_ast_constructor(
+        &mut self,
+        location: &'ast SrcSpan,
+        name_location: &'ast SrcSpan,
+        module: &'ast Option<(EcoString, SrcSpan)>,
+        name: &'ast EcoString,
+        arguments: &'ast Vec<TypeAst>,
+    ) {
+        self.namespace(module);
+        let alias = module.as_ref().map(|(alias, _)| alias);
+        let modifiers = if self.is_deprecated_type(alias, name) {
+            DEPRECATED
+        } else {
+            0
+        };
+        self.push(*name_location, TokenType::Type, modifiers);
+        visit::visit_type_ast_constructor(self, location, name_location, module, name, arguments);
+    }
+
+    fn visit_type_ast_var(&mut self, location: &'ast SrcSpan, _name: &'ast EcoString) {
+        self.push(*location, TokenType::TypeParameter, 0);
+    }
+}
@@ -5,19 +5,30 @@ use super::{
 use crate::{
     Result,
     diagnostic::{Diagnostic, Level},
-    io::{BeamCompiler, CommandExecutor, FileSystemReader, FileSystemWriter},
+    io::{
+        BackgroundCommand, BeamCompiler, CommandExecutor, CommandOutput, CommandSpawner,
+        FileSystemReader, FileSystemWriter,
+    },
     language_server::{
         DownloadDependencies, MakeLocker,
+        change_signature::{CHANGE_SIGNATURE_COMMAND, ChangeSignatureParams},
+        code_lens::{RUN_COMMAND, RunParams, TEST_COMMAND, run_command},
+        configuration::Configuration,
+        diagnostics::{DiagnosticStore, report_uri},
         engine::{self, LanguageServerEngine},
         feedback::{Feedback, FeedbackBookKeeper},
         files::FileSystemProxy,
-        router::Router,
-        src_span_to_lsp_range,
+        formatting, gleam_toml,
+        move_definition::{MOVE_DEFINITION_COMMAND, MoveDefinitionParams},
+        router::{self, Router},
+        semantic_tokens, src_span_to_lsp_range,
+        workspace_symbol::merge_workspace_symbols,
     },
     line_numbers::LineNumbers,
 };
 use camino::{Utf8Path, Utf8PathBuf};
 use debug_ignore::DebugIgnore;
+use ecow::EcoString;
 use itertools::Itertools;
 use lsp_types::{
     self as lsp, HoverProviderCapability, InitializeParams, Position, PublishDiagnosticsParams,
@@ -26,24 +37,37 @@ use lsp_types::{
 use serde_json::Value as Json;
 use std::collections::{HashMap, HashSet};
 
+/// The id of the request registering the watcher of `gleam.toml`. The ids
+/// of the following requests sent to the client are counted up from it.
+const WATCH_GLEAM_TOML_REQUEST_ID: i32 = 1;
+
 /// This class is responsible for handling the language server protocol and
 /// delegating the work to the engine.
 ///
 /// - Configuring watching of the `gleam.toml` file.
 /// - Decoding requests.
 /// - Encoding responses.
-/// - Sending diagnostics and messages to the client.
+/// - Sending diagnostics and messages to the client, or storing the
+///   diagnostics for the clients that pull them.
 /// - Tracking the state of diagnostics and messages.
 /// - Performing the initialisation handshake.
 ///
 #[derive(Debug)]
 pub struct LanguageServer<'a, IO> {
     initialise_params: InitializeParams,
+    configuration: Configuration,
     connection: DebugIgnore<&'a lsp_server::Connection>,
     outside_of_project_feedback: FeedbackBookKeeper,
     router: Router<IO, ConnectionProgressReporter<'a>>,
-    changed_projects: HashSet<Utf8PathBuf>,
+    /// The projects that have changed since they were last compiled, along
+    /// with the files that changed in each of them.
+    changed_projects: HashMap<Utf8PathBuf, HashSet<Utf8PathBuf>>,
+    /// The diagnostics of the clients that pull them rather than having them
+    /// published.
+    diagnostics: DiagnosticStore,
     io: FileSystemProxy<IO>,
+    /// The id of the last request sent to the client.
+    outgoing_request_id: i32,
 }
 
 impl<'a, IO> LanguageServer<'a, IO>
@@ -52,22 +76,31 @@ where
         + FileSystemWriter
         + BeamCompiler
         + CommandExecutor
+        + CommandSpawner
         + DownloadDependencies
         + MakeLocker
-        + Clone,
+        + Clone
+        + Send
+        + Sync,
 {
     pub fn new(connection: &'a lsp_server::Connection, io: IO) -> Result<Self> {
         let initialise_params = initialisation_handshake(connection);
         let reporter = ConnectionProgressReporter::new(connection, &initialise_params);
         let io = FileSystemProxy::new(io);
         let router = Router::new(reporter, io.clone());
+        let configuration = Configuration::from_initialisation_options(
+            initialise_params.initialization_options.clone(),
+        );
         Ok(Self {
             connection: connection.into(),
             initialise_params,
-            changed_projects: HashSet::new(),
+            configuration,
+            changed_projects: HashMap::new(),
+            diagnostics: DiagnosticStore::default(),
             outside_of_project_feedback: FeedbackBookKeeper::default(),
             router,
             io,
+            outgoing_request_id: WATCH_GLEAM_TOML_REQUEST_ID,
         })
     }
 
@@ -100,6 +133,8 @@ where
     fn handle_request(&mut self, id: lsp_server::RequestId, request: Request) {
         let (payload, feedback) = match request {
             Request::Format(param) => self.format(param),
+            Request::RangeFormat(param) => self.range_format(param),
+            Request::OnTypeFormat(param) => self.on_type_format(param),
             Request::Hover(param) => self.hover(param),
             Request::GoToDefinition(param) => self.goto_definition(param),
             Request::Completion(param) => self.completion(param),
@@ -109,7 +144,23 @@ where
             Request::PrepareRename(param) => self.prepare_rename(param),
             Request::Rename(param) => self.rename(param),
             Request::GoToTypeDefinition(param) => self.goto_type_definition(param),
+            Request::GoToImplementation(param) => self.goto_implementation(param),
             Request::FindReferences(param) => self.find_references(param),
+            Request::WorkspaceSymbol(param) => self.workspace_symbol(param),
+            Request::SemanticTokensFull(param) => self.semantic_tokens_full(param),
+            Request::SemanticTokensRange(param) => self.semantic_tokens_range(param),
+            Request::InlayHint(param) => self.inlay_hint(param),
+            Request::PrepareCallHierarchy(param) => self.prepare_call_hierarchy(param),
+            Request::IncomingCalls(param) => self.incoming_calls(*param),
+            Request::OutgoingCalls(param) => self.outgoing_calls(*param),
+            Request::ExecuteCommand(param) => self.execute_command(param),
+            Request::WillRenameFiles(param) => self.will_rename_files(param),
+            Request::DocumentHighlight(param) => self.document_highlight(param),
+            Request::FoldingRange(param) => self.folding_range(param),
+            Request::SelectionRange(param) => self.selection_range(param),
+            Request::CodeLens(param) => self.code_lens(param),
+            Request::DocumentDiagnostic(param) => self.document_diagnostic(param),
+            Request::WorkspaceDiagnostic(param) => self.workspace_diagnostic(param),
         };
 
         self.publish_feedback(feedback);
@@ -137,11 +188,67 @@ where
         self.publish_feedback(feedback);
     }
 
-    fn publish_feedback(&self, feedback: Feedback) {
-        self.publish_diagnostics(feedback.diagnostics);
+    fn publish_feedback(&mut self, feedback: Feedback) {
+        if self.client_pulls_diagnostics() {
+            // The client is told to pull the diagnostics again, as they may
+            // have changed for files other than the one it is editing.
+            if self.store_diagnostics(feedback.diagnostics) {
+                self.refresh_diagnostics();
+            }
+        } else {
+            self.publish_diagnostics(feedback.diagnostics);
+        }
         self.publish_messages(feedback.messages);
     }
 
+    fn client_pulls_diagnostics(&self) -> bool {
+        self.initialise_params
+            .capabilities
+            .text_document
+            .as_ref()
+            .is_some_and(|text_document| text_document.diagnostic.is_some())
+    }
+
+    /// Stores the diagnostics for the client to pull, returning whether any
+    /// of them have changed.
+    ///
+    fn store_diagnostics(&mut self, diagnostics: HashMap<Utf8PathBuf, Vec<Diagnostic>>) -> bool {
+        let mut changed = false;
+        for (path, diagnostics) in diagnostics {
+            let diagnostics = diagnostics
+                .into_iter()
+                .flat_map(diagnostic_to_lsp)
+                .collect::<Vec<_>>();
+            changed |= self.diagnostics.update(path_to_uri(path), diagnostics);
+        }
+        changed
+    }
+
+    fn refresh_diagnostics(&mut self) {
+        let supports_refresh = self
+            .initialise_params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.diagnostic.as_ref())
+            .and_then(|diagnostic| diagnostic.refresh_support)
+            .unwrap_or(false);
+        if !supports_refresh {
+            return;
+        }
+
+        self.outgoing_request_id += 1;
+        let request = lsp_server::Request {
+            id: self.outgoing_request_id.into(),
+            method: "workspace/diagnostic/refresh".into(),
+            params: Json::Null,
+        };
+        self.connection
+            .sender
+            .send(lsp_server::Message::Request(request))
+            .expect("send workspace/diagnostic/refresh");
+    }
+
     fn publish_diagnostics(&self, diagnostics: HashMap<Utf8PathBuf, Vec<Diagnostic>>) {
         for (path, diagnostics) in diagnostics {
             let diagnostics = diagnostics
@@ -199,7 +306,7 @@ where
             ),
         };
         let request = lsp_server::Request {
-            id: 1.into(),
+            id: WATCH_GLEAM_TOML_REQUEST_ID.into(),
             method: "client/registerCapability".into(),
             params: serde_json::value::to_value(lsp::RegistrationParams {
                 registrations: vec![watch_config],
@@ -242,6 +349,24 @@ where
         Handler: FnOnce(
             &mut LanguageServerEngine<IO, ConnectionProgressReporter<'a>>,
         ) -> engine::Response<T>,
+    {
+        let (value, feedback) = self.engine_response(path, handler);
+        let json = match value {
+            Some(value) => serde_json::to_value(value).expect("response to json"),
+            None => Json::Null,
+        };
+        (json, feedback)
+    }
+
+    fn engine_response<T, Handler>(
+        &mut self,
+        path: Utf8PathBuf,
+        handler: Handler,
+    ) -> (Option<T>, Feedback)
+    where
+        Handler: FnOnce(
+            &mut LanguageServerEngine<IO, ConnectionProgressReporter<'a>>,
+        ) -> engine::Response<T>,
     {
         match self.router.project_for_path(path) {
             Ok(Some(project)) => {
@@ -253,19 +378,18 @@ where
                 match result {
                     Ok(value) => {
                         let feedback = project.feedback.response(compilation, warnings);
-                        let json = serde_json::to_value(value).expect("response to json");
-                        (json, feedback)
+                        (Some(value), feedback)
                     }
                     Err(e) => {
                         let feedback = project.feedback.build_with_error(e, compilation, warnings);
-                        (Json::Null, feedback)
+                        (None, feedback)
                     }
                 }
             }
 
-            Ok(None) => (Json::Null, Feedback::default()),
+            Ok(None) => (None, Feedback::default()),
 
-            Err(error) => (Json::Null, self.outside_of_project_feedback.error(error)),
+            Err(error) => (None, self.outside_of_project_feedback.error(error)),
         }
     }
 
@@ -301,11 +425,60 @@ where
         (json, Feedback::default())
     }
 
+    fn range_format(&mut self, params: lsp::DocumentRangeFormattingParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        let src = match self.io.read(&path) {
+            Ok(src) => src.into(),
+            Err(error) => return self.path_error_response(path, error),
+        };
+
+        match formatting::range_formatting(&src, &path, params.range) {
+            Ok(edits) => (
+                serde_json::to_value(edits).expect("to JSON value"),
+                Feedback::default(),
+            ),
+            Err(error) => self.path_error_response(path, error),
+        }
+    }
+
+    fn on_type_format(&mut self, params: lsp::DocumentOnTypeFormattingParams) -> (Json, Feedback) {
+        let position = params.text_document_position;
+        let path = super::path(&position.text_document.uri);
+        // The code is likely to be incomplete while it's being typed, so no
+        // error is reported if it can't be formatted.
+        let edits = match self.io.read(&path) {
+            Ok(src) => {
+                formatting::on_type_formatting(&src.into(), &path, position.position, &params.ch)
+            }
+            Err(_) => vec![],
+        };
+        let json = serde_json::to_value(edits).expect("to JSON value");
+        (json, Feedback::default())
+    }
+
     fn hover(&mut self, params: lsp::HoverParams) -> (Json, Feedback) {
         let path = super::path(&params.text_document_position_params.text_document.uri);
+        if gleam_toml::is_gleam_toml(&path) {
+            return self.gleam_toml_hover(path, params.text_document_position_params.position);
+        }
         self.respond_with_engine(path, |engine| engine.hover(params))
     }
 
+    fn gleam_toml_hover(&mut self, path: Utf8PathBuf, position: Position) -> (Json, Feedback) {
+        let src = match self.io.read(&path) {
+            Ok(src) => src,
+            Err(error) => return self.path_error_response(path, error),
+        };
+        let manifest = path
+            .parent()
+            .and_then(|root| router::project_paths(&self.io, root.to_path_buf()).ok())
+            .and_then(|paths| self.io.read(&paths.manifest()).ok())
+            .and_then(|manifest| toml::from_str(&manifest).ok());
+        let hover = gleam_toml::hover(&src, position, manifest.as_ref());
+        let json = serde_json::to_value(hover).expect("to JSON value");
+        (json, Feedback::default())
+    }
+
     fn goto_definition(&mut self, params: lsp::GotoDefinitionParams) -> (Json, Feedback) {
         let path = super::path(&params.text_document_position_params.text_document.uri);
         self.respond_with_engine(path, |engine| engine.goto_definition(params))
@@ -319,13 +492,26 @@ where
         self.respond_with_engine(path, |engine| engine.goto_type_definition(params))
     }
 
+    fn goto_implementation(&mut self, params: lsp::GotoDefinitionParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document_position_params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.goto_implementation(params))
+    }
+
     fn completion(&mut self, params: lsp::CompletionParams) -> (Json, Feedback) {
         let path = super::path(&params.text_document_position.text_document.uri);
 
-        let src = match self.io.read(&path) {
+        let src: EcoString = match self.io.read(&path) {
             Ok(src) => src.into(),
             Err(error) => return self.path_error_response(path, error),
         };
+        if gleam_toml::is_gleam_toml(&path) {
+            let packages =
+                gleam_toml::cached_packages(&self.io, &crate::paths::global_packages_cache());
+            let completions =
+                gleam_toml::completions(&src, params.text_document_position.position, &packages);
+            let json = serde_json::to_value(completions).expect("to JSON value");
+            return (json, Feedback::default());
+        }
         self.respond_with_engine(path, |engine| {
             engine.completion(params.text_document_position, src)
         })
@@ -361,12 +547,269 @@ where
         self.respond_with_engine(path, |engine| engine.find_references(params))
     }
 
+    fn document_highlight(&mut self, params: lsp::DocumentHighlightParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document_position_params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.document_highlight(params))
+    }
+
+    fn code_lens(&mut self, params: lsp::CodeLensParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.code_lens(params))
+    }
+
+    fn folding_range(&mut self, params: lsp::FoldingRangeParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.folding_range(params))
+    }
+
+    fn selection_range(&mut self, params: lsp::SelectionRangeParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.selection_range(params))
+    }
+
+    fn semantic_tokens_full(&mut self, params: lsp::SemanticTokensParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.semantic_tokens_full(params))
+    }
+
+    fn semantic_tokens_range(
+        &mut self,
+        params: lsp::SemanticTokensRangeParams,
+    ) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.semantic_tokens_range(params))
+    }
+
+    fn inlay_hint(&mut self, params: lsp::InlayHintParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
+        let config = self.configuration.inlay_hints;
+        self.respond_with_engine(path, |engine| engine.inlay_hints(params, config))
+    }
+
+    fn prepare_call_hierarchy(
+        &mut self,
+        params: lsp::CallHierarchyPrepareParams,
+    ) -> (Json, Feedback) {
+        let path = super::path(&params.text_document_position_params.text_document.uri);
+        self.respond_with_engine(path, |engine| engine.prepare_call_hierarchy(params))
+    }
+
+    /// Functions can be called from any of the projects open in the editor,
+    /// so all of them are searched for calls.
+    fn incoming_calls(
+        &mut self,
+        params: lsp::CallHierarchyIncomingCallsParams,
+    ) -> (Json, Feedback) {
+        self.respond_with_all_engines(|engine| engine.incoming_calls(&params.item))
+    }
+
+    /// The function may be defined in a dependency shared by more than one
+    /// of the projects open in the editor, so all of them are searched.
+    fn outgoing_calls(
+        &mut self,
+        params: lsp::CallHierarchyOutgoingCallsParams,
+    ) -> (Json, Feedback) {
+        self.respond_with_all_engines(|engine| engine.outgoing_calls(&params.item))
+    }
+
+    /// The files are renamed within a single project, the one the first of
+    /// them belongs to.
+    fn will_rename_files(&mut self, params: lsp::RenameFilesParams) -> (Json, Feedback) {
+        let Some(uri) = params
+            .files
+            .first()
+            .and_then(|file| Url::parse(&file.old_uri).ok())
+        else {
+            return (Json::Null, Feedback::default());
+        };
+        let path = super::path(&uri);
+        self.respond_with_engine(path, |engine| engine.will_rename_files(&params))
+    }
+
+    /// Commands don't return their edits, but ask the client to apply them.
+    fn execute_command(&mut self, params: lsp::ExecuteCommandParams) -> (Json, Feedback) {
+        match params.command.as_str() {
+            CHANGE_SIGNATURE_COMMAND => {
+                let Some(params) = params.arguments.into_iter().next().and_then(|argument| {
+                    serde_json::from_value::<ChangeSignatureParams>(argument).ok()
+                }) else {
+                    return (
+                        Json::Null,
+                        command_failed(
+                            "Cannot change signature",
+                            "The arguments of the change signature command are not valid.",
+                        ),
+                    );
+                };
+                let path = super::path(&params.text_document_position.text_document.uri);
+                let (edit, mut feedback) =
+                    self.engine_response(path, |engine| engine.change_signature(params));
+                match edit {
+                    Some(Ok(edit)) => self.apply_edit("Change signature", edit),
+                    Some(Err(error)) => feedback.append_feedback(command_failed(
+                        "Cannot change signature",
+                        error.message(),
+                    )),
+                    None => (),
+                }
+                (Json::Null, feedback)
+            }
+            MOVE_DEFINITION_COMMAND => {
+                let Some(params) = params.arguments.into_iter().next().and_then(|argument| {
+                    serde_json::from_value::<MoveDefinitionParams>(argument).ok()
+                }) else {
+                    return (
+                        Json::Null,
+                        command_failed(
+                            "Cannot move definition",
+                            "The arguments of the move definition command are not valid.",
+                        ),
+                    );
+                };
+                let path = super::path(&params.text_document_position.text_document.uri);
+                let (edit, mut feedback) =
+                    self.engine_response(path, |engine| engine.move_definition(params));
+                match edit {
+                    Some(Ok(edit)) => self.apply_edit("Move definition", edit),
+                    Some(Err(error)) => feedback
+                        .append_feedback(command_failed("Cannot move definition", error.message())),
+                    None => (),
+                }
+                (Json::Null, feedback)
+            }
+            RUN_COMMAND | TEST_COMMAND => {
+                let Some(arguments) = params
+                    .arguments
+                    .into_iter()
+                    .next()
+                    .and_then(|argument| serde_json::from_value::<RunParams>(argument).ok())
+                else {
+                    return (Json::Null, Feedback::default());
+                };
+                let path = super::path(&arguments.text_document.uri);
+                let Some(command) = self
+                    .router
+                    .project_path(&path)
+                    .and_then(|root| run_command(&params.command, &arguments, &root))
+                else {
+                    return (Json::Null, Feedback::default());
+                };
+                (Json::Null, self.spawn(command))
+            }
+            _ => (Json::Null, Feedback::default()),
+        }
+    }
+
+    /// Runs the command in the background, sending what it prints to the
+    /// client's log as it is printed.
+    ///
+    fn spawn(&mut self, command: BackgroundCommand) -> Feedback {
+        let sender = self.connection.sender.clone();
+        let log = move |typ: lsp::MessageType, message: String| {
+            let notification = lsp_server::Notification {
+                method: "window/logMessage".into(),
+                params: serde_json::to_value(lsp::LogMessageParams { typ, message })
+                    .expect("window/logMessage to json"),
+            };
+            // The client may have gone away while the command was running.
+            let _ = sender.send(lsp_server::Message::Notification(notification));
+        };
+
+        let name = std::iter::once(command.program.as_str())
+            .chain(command.args.iter().map(String::as_str))
+            .join(" ");
+        log(lsp::MessageType::INFO, format!("Running `{name}`"));
+
+        let on_output = move |output| match output {
+            CommandOutput::Line(line) => log(lsp::MessageType::LOG, line),
+            CommandOutput::Exit(Some(0)) => {
+                log(lsp::MessageType::INFO, format!("`{name}` finished"))
+            }
+            CommandOutput::Exit(Some(code)) => log(
+                lsp::MessageType::ERROR,
+                format!("`{name}` exited with code {code}"),
+            ),
+            CommandOutput::Exit(None) => log(
+                lsp::MessageType::ERROR,
+                format!("`{name}` was stopped before finishing"),
+            ),
+        };
+        match self.io.spawn(command, Box::new(on_output)) {
+            Ok(()) => Feedback::default(),
+            Err(error) => self.outside_of_project_feedback.error(error),
+        }
+    }
+
+    fn apply_edit(&mut self, label: &str, edit: lsp::WorkspaceEdit) {
+        self.outgoing_request_id += 1;
+        let request = lsp_server::Request {
+            id: self.outgoing_request_id.into(),
+            method: "workspace/applyEdit".into(),
+            params: serde_json::to_value(lsp::ApplyWorkspaceEditParams {
+                label: Some(label.into()),
+                edit,
+            })
+            .expect("workspace/applyEdit to json"),
+        };
+        self.connection
+            .sender
+            .send(lsp_server::Message::Request(request))
+            .expect("send workspace/applyEdit");
+    }
+
+    /// Collects the results of all the projects open in the editor, leaving
+    /// out the ones found by more than one of them.
+    fn respond_with_all_engines<T, Handler>(&mut self, handler: Handler) -> (Json, Feedback)
+    where
+        T: serde::Serialize + PartialEq,
+        Handler: Fn(
+            &mut LanguageServerEngine<IO, ConnectionProgressReporter<'a>>,
+        ) -> engine::Response<Vec<T>>,
+    {
+        let (found, feedback) = self.all_engines_results(handler);
+        let mut results: Vec<T> = vec![];
+        for result in found {
+            if !results.contains(&result) {
+                results.push(result);
+            }
+        }
+        let json = serde_json::to_value(results).expect("response to json");
+        (json, feedback)
+    }
+
+    /// The results of all the projects open in the editor, in the order the
+    /// projects are found.
+    fn all_engines_results<T, Handler>(&mut self, handler: Handler) -> (Vec<T>, Feedback)
+    where
+        Handler: Fn(
+            &mut LanguageServerEngine<IO, ConnectionProgressReporter<'a>>,
+        ) -> engine::Response<Vec<T>>,
+    {
+        let mut accumulator = Feedback::none();
+        let mut results = vec![];
+        for path in self.router.project_roots() {
+            let (found, feedback) = self.engine_response(path, &handler);
+            accumulator.append_feedback(feedback);
+            results.extend(found.unwrap_or_default());
+        }
+        (results, accumulator)
+    }
+
+    /// Searches the symbols of all the projects open in the editor.
+    fn workspace_symbol(&mut self, params: lsp::WorkspaceSymbolParams) -> (Json, Feedback) {
+        let (symbols, feedback) =
+            self.all_engines_results(|engine| engine.workspace_symbol(&params.query));
+        let symbols = merge_workspace_symbols(&params.query, symbols);
+        let json = serde_json::to_value(symbols).expect("response to json");
+        (json, feedback)
+    }
+
     fn cache_file_in_memory(&mut self, path: Utf8PathBuf, text: String) -> Feedback {
         self.project_changed(&path);
         if let Err(error) = self.io.write_mem_cache(&path, &text) {
             return self.outside_of_project_feedback.error(error);
         }
-        Feedback::none()
+        self.gleam_toml_feedback(path)
     }
 
     fn discard_in_memory_cache(&mut self, path: Utf8PathBuf) -> Feedback {
@@ -374,7 +817,26 @@ where
         if let Err(error) = self.io.delete_mem_cache(&path) {
             return self.outside_of_project_feedback.error(error);
         }
-        Feedback::none()
+        self.gleam_toml_feedback(path)
+    }
+
+    /// The diagnostics of a `gleam.toml` file, replacing the previous ones
+    /// each time it changes. There's no feedback for any other file, as it's
+    /// given once it's compiled.
+    ///
+    fn gleam_toml_feedback(&self, path: Utf8PathBuf) -> Feedback {
+        let mut feedback = Feedback::none();
+        if !gleam_toml::is_gleam_toml(&path) {
+            return feedback;
+        }
+        let Ok(src) = self.io.read(&path) else {
+            return feedback;
+        };
+        feedback.unset_existing_diagnostics(path.clone());
+        for diagnostic in gleam_toml::diagnostics(&path, &src.into()) {
+            feedback.append_diagnostic(path.clone(), diagnostic);
+        }
+        feedback
     }
 
     fn watched_files_changed(&mut self, path: Utf8PathBuf) -> Feedback {
@@ -385,19 +847,170 @@ where
     fn compile_please(&mut self) -> Feedback {
         let mut accumulator = Feedback::none();
         let projects = std::mem::take(&mut self.changed_projects);
-        for path in projects {
-            let (_, feedback) = self.respond_with_engine(path, |this| this.compile_please());
-            accumulator.append_feedback(feedback);
+        for (path, changed) in projects {
+            accumulator.append_feedback(self.compile_project(path, &changed));
         }
         accumulator
     }
 
+    fn compile_project(&mut self, path: Utf8PathBuf, changed: &HashSet<Utf8PathBuf>) -> Feedback {
+        let (_, feedback) = self.respond_with_engine(path, |this| this.compile_changed(changed));
+        feedback
+    }
+
     fn project_changed(&mut self, path: &Utf8Path) {
         let project_path = self.router.project_path(path);
         if let Some(project_path) = project_path {
-            _ = self.changed_projects.insert(project_path);
+            let _ = self
+                .changed_projects
+                .entry(project_path)
+                .or_default()
+                .insert(path.to_path_buf());
         }
     }
+
+    /// The diagnostics of a file. The projects have just been compiled, so
+    /// they are the latest ones.
+    ///
+    fn document_diagnostic(&mut self, params: lsp::DocumentDiagnosticParams) -> (Json, Feedback) {
+        let uri = path_to_uri(super::path(&params.text_document.uri));
+        let report = self
+            .diagnostics
+            .document_report(&uri, params.previous_result_id.as_deref());
+        let json = serde_json::to_value(lsp::DocumentDiagnosticReportResult::Report(report))
+            .expect("textDocument/diagnostic to json");
+        (json, Feedback::none())
+    }
+
+    /// The diagnostics of all the files of the projects open in the editor.
+    ///
+    /// If the client asked for partial results then the diagnostics of the
+    /// files that can't be affected by the changes made since the projects
+    /// were last compiled are sent right away, before compiling. The rest are
+    /// sent once per project, after the whole project has been compiled, as
+    /// the diagnostics of the modules being compiled are not available until
+    /// the compilation of their package is done.
+    ///
+    fn workspace_diagnostic(&mut self, params: lsp::WorkspaceDiagnosticParams) -> (Json, Feedback) {
+        let previous_result_ids = params
+            .previous_result_ids
+            .into_iter()
+            .map(|previous| (path_to_uri(super::path(&previous.uri)), previous.value))
+            .collect::<HashMap<_, _>>();
+        let token = params.partial_result_params.partial_result_token;
+        let projects = std::mem::take(&mut self.changed_projects);
+
+        let mut reports = WorkspaceReports {
+            token,
+            previous_result_ids,
+            reported: HashMap::new(),
+            items: vec![],
+        };
+
+        let affected = self.affected_files(&projects);
+        let may_change = |uri: &Url| {
+            let path = super::path(uri);
+            affected.iter().any(|(root, affected)| match affected {
+                Some(affected) => affected.contains(&path),
+                None => path.starts_with(root),
+            })
+        };
+        self.report_workspace_diagnostics(&mut reports, |uri| !may_change(uri));
+
+        let mut messages = vec![];
+        for (path, changed) in projects {
+            let feedback = self.compile_project(path, &changed);
+            messages.extend(feedback.messages);
+            let _ = self.store_diagnostics(feedback.diagnostics);
+            self.report_workspace_diagnostics(&mut reports, |_| true);
+        }
+
+        let report = lsp::WorkspaceDiagnosticReport {
+            items: reports.items,
+        };
+        let json = serde_json::to_value(lsp::WorkspaceDiagnosticReportResult::Report(report))
+            .expect("workspace/diagnostic to json");
+        let feedback = Feedback {
+            diagnostics: HashMap::new(),
+            messages,
+        };
+        (json, feedback)
+    }
+
+    /// The files of each of the given projects that are going to be analysed
+    /// again when compiling it after the given files have changed, or `None`
+    /// if the whole project is going to be compiled.
+    ///
+    fn affected_files(
+        &mut self,
+        projects: &HashMap<Utf8PathBuf, HashSet<Utf8PathBuf>>,
+    ) -> Vec<(Utf8PathBuf, Option<HashSet<Utf8PathBuf>>)> {
+        projects
+            .iter()
+            .map(|(path, changed)| {
+                let affected = match self.router.project_for_path(path.clone()) {
+                    Ok(Some(project)) => project.engine.affected_modules(changed),
+                    Ok(None) | Err(_) => None,
+                };
+                (path.clone(), affected)
+            })
+            .collect()
+    }
+
+    /// Reports the diagnostics of the files included by the predicate that
+    /// have not already been reported as they are now.
+    ///
+    fn report_workspace_diagnostics(
+        &self,
+        reports: &mut WorkspaceReports,
+        include: impl Fn(&Url) -> bool,
+    ) {
+        let items =
+            self.diagnostics
+                .workspace_reports(&reports.previous_result_ids, |uri, result_id| {
+                    include(uri) && reports.reported.get(uri).map(String::as_str) != Some(result_id)
+                });
+
+        for item in &items {
+            let uri = report_uri(item);
+            if let Some(result_id) = self.diagnostics.result_id(uri) {
+                let _ = reports.reported.insert(uri.clone(), result_id.into());
+            }
+        }
+
+        let Some(token) = &reports.token else {
+            reports.items.extend(items);
+            return;
+        };
+        if items.is_empty() {
+            return;
+        }
+
+        let partial_result = lsp::WorkspaceDiagnosticReportPartialResult { items };
+        let notification = lsp_server::Notification {
+            method: "$/progress".into(),
+            params: serde_json::json!({ "token": token, "value": partial_result }),
+        };
+        self.connection
+            .sender
+            .send(lsp_server::Message::Notification(notification))
+            .expect("send $/progress");
+    }
+}
+
+/// The diagnostics reported so far in response to a `workspace/diagnostic`
+/// request.
+///
+struct WorkspaceReports {
+    /// The token to send partial results with, if the client asked for them.
+    /// The reports are then sent before compiling and after compiling each
+    /// project, rather than with the response.
+    token: Option<lsp::ProgressToken>,
+    previous_result_ids: HashMap<Url, String>,
+    /// The result ids of the diagnostics reported so far for each file.
+    reported: HashMap<Url, String>,
+    /// The reports to respond with.
+    items: Vec<lsp::WorkspaceDocumentDiagnosticReport>,
 }
 
 fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializeParams {
@@ -415,7 +1028,7 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
                 )),
             },
         )),
-        selection_range_provider: None,
+        selection_range_provider: Some(lsp::SelectionRangeProviderCapability::Simple(true)),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
         completion_provider: Some(lsp::CompletionOptions {
             resolve_provider: None,
@@ -435,16 +1048,21 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         }),
         definition_provider: Some(lsp::OneOf::Left(true)),
         type_definition_provider: Some(lsp::TypeDefinitionProviderCapability::Simple(true)),
-        implementation_provider: None,
+        implementation_provider: Some(lsp::ImplementationProviderCapability::Simple(true)),
         references_provider: Some(lsp::OneOf::Left(true)),
-        document_highlight_provider: None,
+        document_highlight_provider: Some(lsp::OneOf::Left(true)),
         document_symbol_provider: Some(lsp::OneOf::Left(true)),
-        workspace_symbol_provider: None,
+        workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
         code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
-        code_lens_provider: None,
+        code_lens_provider: Some(lsp::CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
         document_formatting_provider: Some(lsp::OneOf::Left(true)),
-        document_range_formatting_provider: None,
-        document_on_type_formatting_provider: None,
+        document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
+        document_on_type_formatting_provider: Some(lsp::DocumentOnTypeFormattingOptions {
+            first_trigger_character: "}".into(),
+            more_trigger_character: Some(vec!["\n".into()]),
+        }),
         rename_provider: Some(lsp::OneOf::Right(RenameOptions {
             prepare_provider: Some(true),
             work_done_progress_options: lsp::WorkDoneProgressOptions {
@@ -453,19 +1071,74 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         })),
         document_link_provider: None,
         color_provider: None,
-        folding_range_provider: None,
+        folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
         declaration_provider: None,
-        execute_command_provider: None,
-        workspace: None,
-        call_hierarchy_provider: None,
-        semantic_tokens_provider: None,
+        execute_command_provider: Some(lsp::ExecuteCommandOptions {
+            commands: vec![
+                CHANGE_SIGNATURE_COMMAND.into(),
+                MOVE_DEFINITION_COMMAND.into(),
+                RUN_COMMAND.into(),
+                TEST_COMMAND.into(),
+            ],
+            work_done_progress_options: lsp::WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        workspace: Some(lsp::WorkspaceServerCapabilities {
+            workspace_folders: None,
+            file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                will_rename: Some(lsp::FileOperationRegistrationOptions {
+                    filters: vec![
+                        lsp::FileOperationFilter {
+                            scheme: Some("file".into()),
+                            pattern: lsp::FileOperationPattern {
+                                glob: "**/*.gleam".into(),
+                                matches: Some(lsp::FileOperationPatternKind::File),
+                                options: None,
+                            },
+                        },
+                        lsp::FileOperationFilter {
+                            scheme: Some("file".into()),
+                            pattern: lsp::FileOperationPattern {
+                                glob: "**".into(),
+                                matches: Some(lsp::FileOperationPatternKind::Folder),
+                                options: None,
+                            },
+                        },
+                    ],
+                }),
+                ..Default::default()
+            }),
+        }),
+        call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+        semantic_tokens_provider: Some(
+            lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                lsp::SemanticTokensOptions {
+                    work_done_progress_options: lsp::WorkDoneProgressOptions {
+                        work_done_progress: None,
+                    },
+                    legend: semantic_tokens::legend(),
+                    range: Some(true),
+                    full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
+                },
+            ),
+        ),
         moniker_provider: None,
         linked_editing_range_provider: None,
         experimental: None,
         position_encoding: None,
         inline_value_provider: None,
-        inlay_hint_provider: None,
-        diagnostic_provider: None,
+        inlay_hint_provider: Some(lsp::OneOf::Left(true)),
+        diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+            lsp::DiagnosticOptions {
+                identifier: Some("gleam".into()),
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: lsp::WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+            },
+        )),
     };
     let server_capabilities_json =
         serde_json::to_value(server_capabilities).expect("server_capabilities_serde");
@@ -536,7 +1209,9 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
     let main = lsp::Diagnostic {
         range,
         severity: Some(severity),
-        code: None,
+        code: diagnostic
+            .code
+            .map(|code| lsp::NumberOrString::String(code.into())),
         code_description: None,
         source: None,
         message: text,
@@ -562,6 +1237,21 @@ fn diagnostic_to_lsp(diagnostic: Diagnostic) -> Vec<lsp::Diagnostic> {
     }
 }
 
+/// Tells the user why a command they ran didn't change anything.
+///
+fn command_failed(title: &str, reason: &str) -> Feedback {
+    let mut feedback = Feedback::default();
+    feedback.append_message(Diagnostic {
+        title: title.into(),
+        text: reason.into(),
+        level: Level::Warning,
+        code: None,
+        location: None,
+        hint: None,
+    });
+    feedback
+}
+
 fn path_to_uri(path: Utf8PathBuf) -> Url {
     let mut file: String = "file://".into();
     file.push_str(&path.as_os_str().to_string_lossy());
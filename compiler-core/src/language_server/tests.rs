@@ -1,13 +1,28 @@
 mod action;
+mod call_hierarchy;
+mod change_signature;
+mod code_lens;
 mod compilation;
 mod completion;
 mod definition;
+mod diagnostics;
+mod document_highlight;
 mod document_symbols;
+mod folding_range;
+mod formatting;
+mod gleam_toml;
 mod hover;
+mod implementation;
+mod inlay_hints;
+mod move_definition;
 mod reference;
 mod rename;
+mod rename_module;
 mod router;
+mod selection_range;
+mod semantic_tokens;
 mod signature_help;
+mod workspace_symbols;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -407,6 +422,7 @@ struct TestProject<'a> {
     dev_hex_modules: Vec<(&'a str, &'a str)>,
     indirect_hex_modules: Vec<(&'a str, &'a str)>,
     package_modules: HashMap<&'a str, Vec<(&'a str, &'a str)>>,
+    native_files: Vec<(&'a str, &'a str)>,
 }
 
 impl<'a> TestProject<'a> {
@@ -421,6 +437,7 @@ impl<'a> TestProject<'a> {
             dev_hex_modules: vec![],
             indirect_hex_modules: vec![],
             package_modules: HashMap::new(),
+            native_files: vec![],
         }
     }
 
@@ -500,6 +517,12 @@ impl<'a> TestProject<'a> {
         self
     }
 
+    /// Adds a non-Gleam file at the given path, relative to the project root.
+    pub fn add_native_file(mut self, path: &'a str, src: &'a str) -> Self {
+        self.native_files.push((path, src));
+        self
+    }
+
     pub fn build_engine(
         &self,
         io: &mut LanguageServerTestIO,
@@ -547,6 +570,11 @@ impl<'a> TestProject<'a> {
             let _ = io.dev_module(name, code);
         });
 
+        // Add all the native files
+        self.native_files.iter().for_each(|(path, code)| {
+            io.module(&io.paths.root().join(path), code);
+        });
+
         for package in &io.manifest.packages {
             let toml_path = engine.paths.build_packages_package_config(&package.name);
             add_package_from_manifest(&mut engine, toml_path, package.clone());
@@ -116,6 +116,7 @@ const CONVERT_FROM_USE: &str = "Convert from `use`";
 const CONVERT_TO_USE: &str = "Convert to `use`";
 const EXTRACT_VARIABLE: &str = "Extract variable";
 const EXTRACT_CONSTANT: &str = "Extract constant";
+const EXTRACT_FUNCTION: &str = "Extract function";
 const EXPAND_FUNCTION_CAPTURE: &str = "Expand function capture";
 const GENERATE_DYNAMIC_DECODER: &str = "Generate dynamic decoder";
 const GENERATE_TO_JSON_FUNCTION: &str = "Generate to-JSON function";
@@ -124,6 +125,8 @@ const PATTERN_MATCH_ON_VARIABLE: &str = "Pattern match on variable";
 const GENERATE_FUNCTION: &str = "Generate function";
 const CONVERT_TO_FUNCTION_CALL: &str = "Convert to function call";
 const INLINE_VARIABLE: &str = "Inline variable";
+const INLINE_FUNCTION_CALL: &str = "Inline function call";
+const INLINE_ALL_FUNCTION_CALLS: &str = "Inline all function calls";
 const CONVERT_TO_PIPE: &str = "Convert to pipe";
 const INTERPOLATE_STRING: &str = "Interpolate string";
 const FILL_UNUSED_FIELDS: &str = "Fill unused fields";
@@ -8603,6 +8606,187 @@ pub fn main() {
     );
 }
 
+#[test]
+fn extract_function_from_expression() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let x = 1
+  let y = 2
+  x * y + 1
+}
+"#,
+        find_position_of("x * y").select_until(find_position_of("\n}"))
+    );
+}
+
+#[test]
+fn extract_function_from_statements_without_escaping_variables() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let name = "Lucy"
+  let greeting = "Hello, " <> name
+  echo greeting
+}
+"#,
+        find_position_of("let greeting").select_until(find_position_of("\n}"))
+    );
+}
+
+#[test]
+fn extract_function_returning_escaping_variable() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let x = 1
+  let y = x + 1
+  let z = y * 2
+  z + x
+}
+"#,
+        find_position_of("let y").select_until(find_position_of("z + x"))
+    );
+}
+
+#[test]
+fn extract_function_returning_several_escaping_variables() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let x = 1
+  let y = x + 1
+  let z = "wibble"
+  #(y, z)
+}
+"#,
+        find_position_of("let x").select_until(find_position_of("#(y"))
+    );
+}
+
+#[test]
+fn extract_function_without_free_variables() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let list = [1, 2, 3]
+  list
+}
+"#,
+        find_position_of("[1").select_until(find_position_of("\n  list"))
+    );
+}
+
+#[test]
+fn extract_function_with_generic_arguments() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn pair(a, b) {
+  let first = [a]
+  #(first, b)
+}
+"#,
+        find_position_of("#(first").select_until(find_position_of("\n}"))
+    );
+}
+
+#[test]
+fn extract_function_from_nested_block() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let x = {
+    let a = 1
+    let b = a + 1
+    b * 2
+  }
+  x
+}
+"#,
+        find_position_of("let b").select_until(find_position_of("\n  }"))
+    );
+}
+
+#[test]
+fn extract_function_avoids_existing_names() {
+    let src = r#"
+import wibble.{function}
+
+pub fn main() {
+  function(1 + 2)
+}
+
+fn function_2() {
+  Nil
+}
+"#;
+
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        TestProject::for_source(src).add_module("wibble", "pub fn function(x) { x }"),
+        find_position_of("1 + 2").select_until(find_position_of(")\n"))
+    );
+}
+
+#[test]
+fn no_extract_function_for_partial_selection() {
+    assert_no_code_actions!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let x = 1
+  let y = x + 1
+  y
+}
+"#,
+        find_position_of("= 1").select_until(find_position_of("+ 1"))
+    );
+}
+
+#[test]
+fn extract_function_with_use_and_its_callback() {
+    assert_code_action!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  let y = 1
+  use x <- wibble(y)
+  x
+}
+
+fn wibble(x, f) {
+  f(x)
+}
+"#,
+        find_position_of("use").select_until(find_position_of("\n}"))
+    );
+}
+
+#[test]
+fn no_extract_function_for_use_without_its_callback() {
+    assert_no_code_actions!(
+        EXTRACT_FUNCTION,
+        r#"
+pub fn main() {
+  use x <- wibble(1)
+  x
+}
+
+fn wibble(x, f) {
+  f(x)
+}
+"#,
+        find_position_of("use").select_until(find_position_of("\n  x"))
+    );
+}
+
 #[test]
 fn fix_float_operator_on_ints() {
     let name = "Use `>=`";
@@ -8932,6 +9116,300 @@ pub fn main() {
     );
 }
 
+#[test]
+fn inline_function_call() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  double(1 + 2)
+}
+
+fn double(x) {
+  x * 2
+}
+",
+        find_position_of("double(1").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_keeps_function_called_elsewhere() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  let a = double(1)
+  double(a)
+}
+
+fn double(x) {
+  x * 2
+}
+",
+        find_position_of("double(a)").to_selection()
+    );
+}
+
+#[test]
+fn inline_all_function_calls() {
+    assert_code_action!(
+        INLINE_ALL_FUNCTION_CALLS,
+        "
+pub fn main() {
+  let a = double(1)
+  double(a) + 1
+}
+
+/// Doubles a number.
+fn double(x) {
+  x * 2
+}
+
+pub fn other() {
+  Nil
+}
+",
+        find_position_of("fn double").under_char('d').to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_with_labelled_arguments() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        r#"
+pub fn main() {
+  let name = "Lucy"
+  greet(name:, greeting: "Hello")
+}
+
+fn greet(greeting greeting: String, name name: String) -> String {
+  greeting <> ", " <> name
+}
+"#,
+        find_position_of("greet(").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_in_capture() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  apply(add(_, 1))
+}
+
+fn add(x, y) {
+  x + y
+}
+
+fn apply(f) {
+  f(1)
+}
+",
+        find_position_of("add(_").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_assigns_arguments_used_more_than_once() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  square(compute())
+}
+
+fn square(x) {
+  x * x
+}
+
+fn compute() {
+  2
+}
+",
+        find_position_of("square(").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_assigns_effectful_argument_used_once() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        r#"
+pub fn main() {
+  wibble(print("one"), print("two"))
+}
+
+fn wibble(a, b) {
+  b
+  a
+}
+
+@external(erlang, "io", "format")
+fn print(message: String) -> Nil
+"#,
+        find_position_of("wibble(").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_assigns_argument_used_in_anonymous_function() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  wibble(compute())
+}
+
+fn wibble(x) {
+  fn() { x }
+}
+
+fn compute() {
+  2
+}
+",
+        find_position_of("wibble(").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_assigns_unused_effectful_argument() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  wibble(compute())
+}
+
+fn wibble(_x) {
+  1
+}
+
+fn compute() {
+  2
+}
+",
+        find_position_of("wibble(").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_with_multiple_statements() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  let result = wibble(1, 2)
+  result
+}
+
+fn wibble(a, b) {
+  let sum = a + b
+  sum * 2
+}
+",
+        find_position_of("wibble(1").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_renames_shadowing_variables() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  let sum = 1
+  wibble(sum)
+}
+
+fn wibble(a) {
+  let sum = a + 1
+  sum * a
+}
+",
+        find_position_of("wibble(sum)").to_selection()
+    );
+}
+
+#[test]
+fn inline_function_call_keeps_public_function() {
+    assert_code_action!(
+        INLINE_FUNCTION_CALL,
+        "
+pub fn main() {
+  double(1)
+}
+
+pub fn double(x) {
+  x * 2
+}
+",
+        find_position_of("double(1)").to_selection()
+    );
+}
+
+#[test]
+fn inline_all_function_calls_keeps_function_used_in_pipe() {
+    assert_code_action!(
+        INLINE_ALL_FUNCTION_CALLS,
+        "
+pub fn main() {
+  let a = double(1)
+  a |> double
+}
+
+fn double(x) {
+  x * 2
+}
+",
+        find_position_of("fn double").under_char('d').to_selection()
+    );
+}
+
+#[test]
+fn no_inline_function_call_for_recursive_function() {
+    assert_no_code_actions!(
+        INLINE_FUNCTION_CALL | INLINE_ALL_FUNCTION_CALLS,
+        "
+pub fn main() {
+  count(10)
+}
+
+fn count(x) {
+  case x {
+    0 -> 0
+    _ -> count(x - 1)
+  }
+}
+",
+        find_position_of("count(10)").to_selection()
+    );
+}
+
+#[test]
+fn no_inline_function_call_when_caller_shadows_module_value() {
+    assert_no_code_actions!(
+        INLINE_FUNCTION_CALL | INLINE_ALL_FUNCTION_CALLS,
+        "
+pub fn main() {
+  let value = 2
+  wibble(value)
+}
+
+const value = 1
+
+fn wibble(x) {
+  x + value
+}
+",
+        find_position_of("wibble(value)").to_selection()
+    );
+}
+
 #[test]
 fn no_inline_variable_action_for_use_pattern() {
     assert_no_code_actions!(
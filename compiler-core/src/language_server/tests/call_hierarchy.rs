@@ -0,0 +1,282 @@
+use insta::assert_snapshot;
+use lsp_types::{
+    CallHierarchyItem, CallHierarchyPrepareParams, Range, TextDocumentPositionParams, Url,
+};
+
+use crate::language_server::lsp_range_to_src_span;
+
+use super::*;
+
+fn prepare(
+    engine: &mut LanguageServerEngine<LanguageServerTestIO, LanguageServerTestIO>,
+    params: TextDocumentPositionParams,
+) -> Option<CallHierarchyItem> {
+    let params = CallHierarchyPrepareParams {
+        text_document_position_params: params,
+        work_done_progress_params: Default::default(),
+    };
+    engine
+        .prepare_call_hierarchy(params)
+        .result
+        .expect("prepare call hierarchy")
+        .and_then(|items| items.into_iter().next())
+}
+
+fn show_item(item: &CallHierarchyItem) -> String {
+    format!(
+        "{}.{} at {}:{}",
+        item.detail.as_deref().unwrap_or_default(),
+        item.name,
+        item.range.start.line,
+        item.range.start.character
+    )
+}
+
+fn show_ranges(tester: &TestProject<'_>, uri: &Url, ranges: &[Range]) -> String {
+    let src = tester.src_from_module_url(uri).expect("module source");
+    let line_numbers = LineNumbers::new(src);
+    ranges
+        .iter()
+        .map(|range| {
+            let span = lsp_range_to_src_span(*range, &line_numbers);
+            let text = src
+                .get(span.start as usize..span.end as usize)
+                .expect("range in source");
+            format!("`{text}` at {}:{}", range.start.line, range.start.character)
+        })
+        .join(", ")
+}
+
+/// Prints the item for the function at the given position, followed by the
+/// functions calling it and the ones it calls.
+///
+fn call_hierarchy(tester: TestProject<'_>, position: PositionFinder) -> String {
+    let position = position.find_position(tester.src);
+    tester.at(position, |engine, params, _| {
+        let Some(item) = prepare(engine, params) else {
+            return "No call hierarchy".into();
+        };
+
+        let mut output = format!("{}\n\n----- INCOMING CALLS\n", show_item(&item));
+        let incoming = engine.incoming_calls(&item).result.expect("incoming calls");
+        for call in incoming {
+            output.push_str(&format!(
+                "{} from {}\n",
+                show_item(&call.from),
+                show_ranges(&tester, &call.from.uri, &call.from_ranges)
+            ));
+        }
+
+        output.push_str("\n----- OUTGOING CALLS\n");
+        let outgoing = engine.outgoing_calls(&item).result.expect("outgoing calls");
+        for call in outgoing {
+            output.push_str(&format!(
+                "{} from {}\n",
+                show_item(&call.to),
+                show_ranges(&tester, &item.uri, &call.from_ranges)
+            ));
+        }
+        output
+    })
+}
+
+#[test]
+fn call_hierarchy_of_function_definition() {
+    let src = "
+pub fn main() {
+  wibble(1)
+}
+
+pub fn wibble(x) {
+  wobble(x) + wobble(x)
+}
+
+fn wobble(x) {
+  x
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("wibble(x)")
+    ));
+}
+
+#[test]
+fn call_hierarchy_of_function_call() {
+    let src = "
+pub fn main() {
+  wibble(1)
+}
+
+pub fn wibble(x) {
+  x
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("wibble(1)")
+    ));
+}
+
+#[test]
+fn call_hierarchy_includes_pipes_use_and_captures() {
+    let src = "
+pub fn main() {
+  1 |> wibble
+  use x <- wobble
+  let f = wibble(_)
+  f(x)
+}
+
+pub fn wibble(x) {
+  x
+}
+
+fn wobble(f) {
+  f(1) |> wibble
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("wibble(x)")
+    ));
+}
+
+#[test]
+fn incoming_calls_through_use() {
+    let src = "
+pub fn main() {
+  use x <- wibble(1)
+  x
+}
+
+pub fn wibble(x, f) {
+  f(x)
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("wibble(x, f)")
+    ));
+}
+
+#[test]
+fn call_hierarchy_across_modules() {
+    let src = "
+import wibble.{wobble}
+
+pub fn main() {
+  wibble.wibble()
+  wobble()
+}
+
+pub fn other() {
+  wobble()
+}
+";
+    let wibble = "
+pub fn wibble() {
+  wobble()
+}
+
+pub fn wobble() {
+  Nil
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src).add_module("wibble", wibble),
+        find_position_of("wobble()")
+    ));
+}
+
+#[test]
+fn call_hierarchy_of_dependency_function() {
+    let src = "
+import dep
+
+pub fn main() {
+  dep.wibble()
+}
+";
+    let dep = "
+pub fn wibble() {
+  wobble()
+}
+
+fn wobble() {
+  Nil
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src).add_hex_module("dep", dep),
+        find_position_of("wibble()")
+    ));
+}
+
+#[test]
+fn outgoing_calls_leave_out_constants_and_constructors() {
+    let src = "
+const value = 1
+
+type Wibble {
+  Wibble(Int)
+}
+
+pub fn main() {
+  let wibble = Wibble(value)
+  wobble(wibble)
+  Nil
+}
+
+fn wobble(x) {
+  x
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("main")
+    ));
+}
+
+#[test]
+fn references_from_constants_are_not_calls() {
+    let src = "
+const function = wibble
+
+pub fn main() {
+  function()
+}
+
+pub fn wibble() {
+  Nil
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("wibble()")
+    ));
+}
+
+#[test]
+fn no_call_hierarchy_for_constants() {
+    let src = "
+const value = 1
+
+pub fn main() {
+  value
+}
+";
+
+    assert_snapshot!(call_hierarchy(
+        TestProject::for_source(src),
+        find_position_of("value\n}")
+    ));
+}
@@ -0,0 +1,428 @@
+use insta::assert_snapshot;
+use lsp_types::{
+    CodeActionContext, CodeActionParams, PartialResultParams, Range, TextDocumentPositionParams,
+    WorkDoneProgressParams, WorkspaceEdit,
+};
+
+use crate::language_server::change_signature::{
+    CHANGE_SIGNATURE_COMMAND, ChangeSignatureError, ChangeSignatureParams, NewParameter,
+};
+
+use super::*;
+
+fn existing(index: usize, label: Option<&str>) -> NewParameter {
+    NewParameter {
+        index: Some(index),
+        label: label.map(EcoString::from),
+        ..Default::default()
+    }
+}
+
+fn new_parameter(label: Option<&str>, name: &str, default: &str) -> NewParameter {
+    NewParameter {
+        index: None,
+        label: label.map(EcoString::from),
+        name: Some(name.into()),
+        annotation: None,
+        default: Some(default.into()),
+    }
+}
+
+/// Prints every module of the project after changing the signature of the
+/// function at the given position.
+///
+fn change_signature(
+    tester: TestProject<'_>,
+    position: PositionFinder,
+    parameters: Vec<NewParameter>,
+) -> String {
+    let position = position.find_position(tester.src);
+    let edit = tester.at(position, |engine, params, _| {
+        let params = ChangeSignatureParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: params.text_document,
+                position,
+            },
+            parameters,
+        };
+        engine
+            .change_signature(params)
+            .result
+            .expect("change signature")
+    });
+    print_changes(tester, edit)
+}
+
+/// Prints every module of the project after running the command carried by
+/// the change signature code action offered at the given position, with the
+/// parameters picked from the ones the action starts from.
+///
+fn change_signature_with_code_action(
+    tester: TestProject<'_>,
+    position: PositionFinder,
+    pick_parameters: impl FnOnce(Vec<NewParameter>) -> Vec<NewParameter>,
+) -> String {
+    let position = position.find_position(tester.src);
+    let edit = tester.at(position, |engine, params, _| {
+        let action = engine
+            .code_actions(CodeActionParams {
+                text_document: params.text_document,
+                range: Range::new(position, position),
+                context: CodeActionContext::default(),
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            })
+            .result
+            .expect("code actions")
+            .into_iter()
+            .flatten()
+            .find(|action| action.title == "Change signature")
+            .expect("No change signature code action");
+        let command = action.command.expect("No command");
+        assert_eq!(command.command, CHANGE_SIGNATURE_COMMAND);
+        let argument = command
+            .arguments
+            .into_iter()
+            .flatten()
+            .next()
+            .expect("No command argument");
+        let mut params = serde_json::from_value::<ChangeSignatureParams>(argument)
+            .expect("Invalid command argument");
+        params.parameters = pick_parameters(params.parameters);
+        engine
+            .change_signature(params)
+            .result
+            .expect("change signature")
+    });
+    print_changes(tester, edit)
+}
+
+fn print_changes(
+    tester: TestProject<'_>,
+    edit: Result<WorkspaceEdit, ChangeSignatureError>,
+) -> String {
+    let edit = match edit {
+        Ok(edit) => edit,
+        Err(error) => return format!("No change: {}", error.message()),
+    };
+
+    let changes = edit.changes.unwrap_or_default();
+    let mut output = String::new();
+    for (name, src) in tester
+        .root_package_modules
+        .iter()
+        .chain(std::iter::once(&("app", tester.src)))
+    {
+        let edits = changes
+            .iter()
+            .find(|(uri, _)| tester.module_name_from_url(uri).as_deref() == Some(*name))
+            .map(|(_, edits)| edits.clone())
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "-- {name}.gleam\n{}\n",
+            apply_code_edit(src, edits)
+        ));
+    }
+    output
+}
+
+#[test]
+fn reorder_parameters() {
+    let src = r#"
+pub fn main() {
+  wibble(1, "a", second: 2.0)
+  wibble(3, second: 4.0, first: "b")
+}
+
+pub fn wibble(x: Int, first a: String, second b: Float) -> Nil {
+  Nil
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        vec![
+            existing(0, None),
+            existing(2, Some("second")),
+            existing(1, Some("first")),
+        ]
+    ));
+}
+
+#[test]
+fn add_parameter_across_modules() {
+    let src = r#"
+import wibble.{wobble}
+
+pub fn main() {
+  wobble(1)
+  wibble.wobble(2)
+}
+"#;
+    let wibble = r#"
+pub fn wobble(x: Int) -> Int {
+  x
+}
+
+pub fn other() {
+  wobble(3)
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src).add_module("wibble", wibble),
+        find_position_of("wobble(1)"),
+        vec![existing(0, None), new_parameter(Some("by"), "step", "1")]
+    ));
+}
+
+#[test]
+fn remove_parameter() {
+    let src = r#"
+pub fn main() {
+  wibble(1, 2)
+}
+
+pub fn wibble(_unused: Int, x: Int) -> Int {
+  x
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(_"),
+        vec![existing(1, None)]
+    ));
+}
+
+#[test]
+fn relabel_parameters() {
+    let src = r#"
+pub fn main() {
+  let name = "Lucy"
+  greet(name:, greeting: "Hello")
+  greet("Nubi", "Hi")
+}
+
+pub fn greet(name name: String, greeting greeting: String) -> String {
+  greeting <> name
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("greet("),
+        vec![existing(0, Some("name")), existing(1, Some("with"))]
+    ));
+}
+
+#[test]
+fn change_signature_of_piped_call() {
+    let src = r#"
+pub fn main() {
+  1 |> wibble(2)
+  wibble(3, 4)
+}
+
+pub fn wibble(x: Int, y: Int) -> Int {
+  x + y
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        vec![
+            new_parameter(None, "z", "0"),
+            existing(0, None),
+            existing(1, None)
+        ]
+    ));
+}
+
+#[test]
+fn change_signature_of_use_call() {
+    let src = r#"
+pub fn main() {
+  use x <- wibble(1)
+  use y <- wibble(x)
+  y
+}
+
+pub fn wibble(x: Int, f: fn(Int) -> a) -> a {
+  f(x)
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x:"),
+        vec![
+            existing(0, None),
+            new_parameter(None, "step", "2"),
+            existing(1, None)
+        ]
+    ));
+}
+
+#[test]
+fn no_change_for_use_call_if_callback_is_not_last() {
+    let src = r#"
+pub fn main() {
+  use x <- wibble(1)
+  x
+}
+
+pub fn wibble(x: Int, f: fn(Int) -> a) -> a {
+  f(x)
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x:"),
+        vec![existing(1, None), existing(0, None)]
+    ));
+}
+
+#[test]
+fn change_signature_of_function_capture() {
+    let src = r#"
+pub fn main() {
+  let f = wibble(_, 1)
+  f(2)
+}
+
+pub fn wibble(x: Int, y: Int) -> Int {
+  x - y
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        vec![existing(1, None), existing(0, None)]
+    ));
+}
+
+#[test]
+fn no_change_for_unlabelled_parameter_after_labelled_one() {
+    let src = r#"
+pub fn main() {
+  wibble(1, 2)
+}
+
+pub fn wibble(x: Int, y: Int) -> Int {
+  x + y
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        vec![existing(0, Some("first")), existing(1, None)]
+    ));
+}
+
+#[test]
+fn no_change_for_function_passed_as_a_value() {
+    let src = r#"
+pub fn main() {
+  wibble(1, 2)
+  apply(wibble)
+}
+
+pub fn wibble(x: Int, y: Int) -> Int {
+  x + y
+}
+
+fn apply(f: fn(Int, Int) -> Int) -> Int {
+  f(1, 2)
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        vec![existing(1, None), existing(0, None)]
+    ));
+}
+
+#[test]
+fn no_change_for_function_assigned_to_a_variable() {
+    let src = r#"
+pub fn main() {
+  let f = wibble
+  f(1, 2)
+}
+
+pub fn wibble(x: Int, y: Int) -> Int {
+  x + y
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        vec![existing(1, None), existing(0, None)]
+    ));
+}
+
+#[test]
+fn change_signature_with_parentheses_in_strings_and_comments() {
+    let src = r#"
+pub fn main() {
+  wibble(
+    "(",
+    ")",
+    // )
+  )
+}
+
+pub fn wibble(
+  x: String,
+  y: String,
+  // (
+) -> String {
+  x <> y
+}
+"#;
+
+    assert_snapshot!(change_signature(
+        TestProject::for_source(src),
+        find_position_of("wibble(\n  x"),
+        vec![existing(1, None), existing(0, None)]
+    ));
+}
+
+#[test]
+fn change_signature_through_code_action() {
+    let src = r#"
+pub fn main() {
+  wibble(1, "a", second: 2.0)
+  wibble(3, second: 4.0, first: "b")
+}
+
+pub fn wibble(x: Int, first a: String, second b: Float) -> Nil {
+  Nil
+}
+"#;
+
+    assert_snapshot!(change_signature_with_code_action(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        |mut parameters| {
+            assert_eq!(
+                parameters,
+                vec![
+                    existing(0, None),
+                    existing(1, Some("first")),
+                    existing(2, Some("second")),
+                ]
+            );
+            parameters.swap(1, 2);
+            parameters
+        }
+    ));
+}
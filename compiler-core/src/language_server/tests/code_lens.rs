@@ -0,0 +1,190 @@
+use insta::assert_snapshot;
+use lsp_types::{CodeLensParams, PartialResultParams, WorkDoneProgressParams};
+
+use crate::{
+    build::Target,
+    language_server::code_lens::{RUN_COMMAND, RunParams, TEST_COMMAND, run_command},
+};
+
+use super::*;
+
+/// Prints the title and arguments of each code lens, under the line it is
+/// shown above.
+///
+fn code_lenses(
+    src: &str,
+    (mut engine, params): (
+        LanguageServerEngine<LanguageServerTestIO, LanguageServerTestIO>,
+        TextDocumentPositionParams,
+    ),
+) -> String {
+    let lenses = engine
+        .code_lens(CodeLensParams {
+            text_document: params.text_document,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        })
+        .result
+        .expect("code lens")
+        .expect("code lenses");
+
+    if lenses.is_empty() {
+        return "No code lenses".into();
+    }
+
+    let mut output = String::new();
+    for lens in lenses {
+        let line = src
+            .lines()
+            .nth(lens.range.start.line as usize)
+            .unwrap_or_default();
+        let command = lens.command.expect("lens command");
+        let params = command
+            .arguments
+            .and_then(|arguments| arguments.into_iter().next())
+            .map(|argument| serde_json::from_value::<RunParams>(argument).expect("run params"))
+            .expect("lens arguments");
+        output.push_str(&format!(
+            "{line}\n  {} ({}): module {}, target {}, test {:?}\n",
+            command.title, command.command, params.module, params.target, params.test
+        ));
+    }
+    output
+}
+
+fn src_code_lenses(src: &str) -> String {
+    let tester = TestProject::for_source(src);
+    code_lenses(src, tester.positioned_with_io(Position::new(0, 0)))
+}
+
+fn test_code_lenses(src: &str) -> String {
+    let tester = TestProject::for_source("").add_test_module("app_test", src);
+    code_lenses(
+        src,
+        tester.positioned_with_io_in_test(Position::new(0, 0), "app_test"),
+    )
+}
+
+#[test]
+fn run_main_function() {
+    assert_snapshot!(src_code_lenses(
+        "
+pub fn helper() {
+  1
+}
+
+pub fn main() {
+  helper()
+}
+"
+    ));
+}
+
+#[test]
+fn no_run_for_main_with_arguments() {
+    assert_snapshot!(src_code_lenses(
+        "
+pub fn main(x) {
+  x
+}
+"
+    ));
+}
+
+#[test]
+fn no_run_for_private_main() {
+    assert_snapshot!(src_code_lenses(
+        "
+fn main() {
+  Nil
+}
+
+pub fn wibble_test() {
+  main()
+}
+"
+    ));
+}
+
+#[test]
+fn run_test_module() {
+    assert_snapshot!(test_code_lenses(
+        "
+pub fn main() {
+  Nil
+}
+
+pub fn wibble_test() {
+  Nil
+}
+
+fn private_test() {
+  Nil
+}
+
+pub fn wobble_test() {
+  private_test()
+}
+
+pub fn helper(x) {
+  x
+}
+"
+    ));
+}
+
+#[test]
+fn run_command_for_test() {
+    let params = RunParams {
+        text_document: TextDocumentIdentifier::new(
+            Url::parse("file:///project/test/app_test.gleam").expect("url"),
+        ),
+        module: "app_test".into(),
+        target: Target::JavaScript,
+        test: Some("app_test.wibble_test".into()),
+    };
+    let command =
+        run_command(TEST_COMMAND, &params, Utf8Path::new("/project")).expect("test command");
+    assert_eq!(
+        Some(command.program.as_str()),
+        std::env::current_exe()
+            .ok()
+            .as_deref()
+            .and_then(|path| path.to_str())
+    );
+    assert_eq!(
+        command.args,
+        vec![
+            "test",
+            "--target",
+            "javascript",
+            "--module",
+            "app_test",
+            "--name",
+            "app_test.wibble_test"
+        ]
+    );
+    assert_eq!(command.cwd, Some(Utf8PathBuf::from("/project")));
+}
+
+#[test]
+fn run_command_for_main() {
+    let params = RunParams {
+        text_document: TextDocumentIdentifier::new(
+            Url::parse("file:///project/src/app.gleam").expect("url"),
+        ),
+        module: "app".into(),
+        target: Target::Erlang,
+        test: None,
+    };
+    let command =
+        run_command(RUN_COMMAND, &params, Utf8Path::new("/project")).expect("run command");
+    assert_eq!(
+        command.args,
+        vec!["run", "--target", "erlang", "--module", "app"]
+    );
+    assert_eq!(
+        run_command("gleam.unknown", &params, Utf8Path::new("/project")),
+        None
+    );
+}
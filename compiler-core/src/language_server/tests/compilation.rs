@@ -244,3 +244,113 @@ fn dep_compile_recompile() {
         ]
     )
 }
+
+#[test]
+fn compile_changed_only_analyses_the_dependents_of_the_changed_module() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let wibble = io.src_module("app/wibble", "pub fn wibble() { 1 }");
+    let wobble = io.src_module(
+        "app/wobble",
+        "import app/wibble pub fn wobble() { wibble.wibble() }",
+    );
+    let wubble = io.src_module(
+        "app/wubble",
+        "import app/wobble pub fn wubble() { wobble.wobble() }",
+    );
+    let _ = io.src_module("app/other", "pub fn other() { 1 }");
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    _ = io.src_module("app/wibble", "pub fn wibble() { 2 }");
+    let changed = HashSet::from([wibble.clone()]);
+    assert_eq!(
+        engine.affected_modules(&changed),
+        Some(HashSet::from([
+            wibble.clone(),
+            wobble.clone(),
+            wubble.clone()
+        ]))
+    );
+
+    let response = engine.compile_changed(&changed);
+    assert!(response.result.is_ok());
+    assert_eq!(
+        response.compilation,
+        Compilation::Yes(vec![wibble, wobble, wubble])
+    );
+}
+
+#[test]
+fn compile_changed_analyses_dependents_of_a_module_edited_back() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let wibble = io.src_module("app/wibble", "pub fn wibble() { 1 }");
+    let wobble = io.src_module(
+        "app/wobble",
+        "import app/wibble pub fn wobble() { wibble.wibble() }",
+    );
+
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    // The module is reported as changed, but its code is what was compiled,
+    // so only the module depending on it is analysed again.
+    let response = engine.compile_changed(&HashSet::from([wibble]));
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![wobble]));
+}
+
+#[test]
+fn compile_changed_compiles_everything_before_the_first_compilation() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let wibble = io.src_module("app/wibble", "pub fn wibble() { 1 }");
+    let changed = HashSet::from([wibble.clone()]);
+    assert_eq!(engine.affected_modules(&changed), None);
+
+    let response = engine.compile_changed(&changed);
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![wibble]));
+}
+
+#[test]
+fn compile_changed_compiles_everything_when_a_module_is_added() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+
+    let _ = io.src_module("app/wibble", "pub fn wibble() { 1 }");
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    let wobble = io.src_module("app/wobble", "pub fn wobble() { 1 }");
+    let changed = HashSet::from([wobble.clone()]);
+    assert_eq!(engine.affected_modules(&changed), None);
+
+    let response = engine.compile_changed(&changed);
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![wobble]));
+}
+
+#[test]
+fn compile_changed_compiles_dependencies_when_one_changes() {
+    let io = LanguageServerTestIO::new();
+    let mut engine = setup_engine(&io);
+    add_path_dep(&mut engine, "mydep");
+
+    let dependency = io.path_dep_module("mydep", "moddy", "pub fn main() { 0 }");
+    let response = engine.compile_please();
+    assert!(response.result.is_ok());
+
+    _ = io.path_dep_module("mydep", "moddy", "pub fn main() { 1 }");
+    let changed = HashSet::from([dependency.clone()]);
+    assert_eq!(engine.affected_modules(&changed), None);
+
+    let response = engine.compile_changed(&changed);
+    assert!(response.result.is_ok());
+    assert_eq!(response.compilation, Compilation::Yes(vec![dependency]));
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use lsp_types::{
+    Diagnostic, DocumentDiagnosticReport, Position, Range, Url, WorkspaceDocumentDiagnosticReport,
+};
+
+use crate::language_server::diagnostics::DiagnosticStore;
+
+fn uri(path: &str) -> Url {
+    Url::parse(&format!("file:///src/{path}.gleam")).expect("uri")
+}
+
+fn diagnostic(message: &str) -> Diagnostic {
+    Diagnostic {
+        range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+        message: message.into(),
+        ..Default::default()
+    }
+}
+
+/// The workspace reports, each printed as the name of its file followed by
+/// the messages of its diagnostics, or `unchanged` if it is unchanged.
+///
+fn workspace_reports(
+    store: &DiagnosticStore,
+    previous_result_ids: &HashMap<Url, String>,
+) -> Vec<String> {
+    store
+        .workspace_reports(previous_result_ids, |_, _| true)
+        .into_iter()
+        .map(|report| match report {
+            WorkspaceDocumentDiagnosticReport::Full(report) => format!(
+                "{}: {}",
+                report.uri.path(),
+                report
+                    .full_document_diagnostic_report
+                    .items
+                    .iter()
+                    .map(|diagnostic| diagnostic.message.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            WorkspaceDocumentDiagnosticReport::Unchanged(report) => {
+                format!("{}: unchanged", report.uri.path())
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn updating_diagnostics_changes_their_result_id() {
+    let mut store = DiagnosticStore::default();
+    assert!(store.update(uri("wibble"), vec![diagnostic("one")]));
+    let first = store.result_id(&uri("wibble")).map(String::from);
+
+    assert!(!store.update(uri("wibble"), vec![diagnostic("one")]));
+    assert_eq!(store.result_id(&uri("wibble")).map(String::from), first);
+
+    assert!(store.update(uri("wibble"), vec![]));
+    assert_ne!(store.result_id(&uri("wibble")).map(String::from), first);
+}
+
+#[test]
+fn files_that_never_had_diagnostics_are_not_stored() {
+    let mut store = DiagnosticStore::default();
+    assert!(!store.update(uri("wibble"), vec![]));
+    assert_eq!(store.result_id(&uri("wibble")), None);
+}
+
+#[test]
+fn document_report_is_unchanged_for_the_current_result_id() {
+    let mut store = DiagnosticStore::default();
+    let _ = store.update(uri("wibble"), vec![diagnostic("one")]);
+    let result_id = store
+        .result_id(&uri("wibble"))
+        .expect("result id")
+        .to_string();
+
+    match store.document_report(&uri("wibble"), Some(&result_id)) {
+        DocumentDiagnosticReport::Unchanged(report) => assert_eq!(
+            report.unchanged_document_diagnostic_report.result_id,
+            result_id
+        ),
+        DocumentDiagnosticReport::Full(_) => panic!("expected an unchanged report"),
+    }
+
+    match store.document_report(&uri("wibble"), Some("outdated")) {
+        DocumentDiagnosticReport::Full(report) => assert_eq!(
+            report.full_document_diagnostic_report.items,
+            vec![diagnostic("one")]
+        ),
+        DocumentDiagnosticReport::Unchanged(_) => panic!("expected a full report"),
+    }
+}
+
+#[test]
+fn document_report_of_a_file_without_diagnostics_is_empty() {
+    let store = DiagnosticStore::default();
+    match store.document_report(&uri("wibble"), None) {
+        DocumentDiagnosticReport::Full(report) => {
+            assert_eq!(report.full_document_diagnostic_report.items, vec![])
+        }
+        DocumentDiagnosticReport::Unchanged(_) => panic!("expected a full report"),
+    }
+}
+
+#[test]
+fn workspace_reports_are_unchanged_for_the_current_result_ids() {
+    let mut store = DiagnosticStore::default();
+    let _ = store.update(uri("wobble"), vec![diagnostic("two"), diagnostic("three")]);
+    let _ = store.update(uri("wibble"), vec![diagnostic("one")]);
+    let _ = store.update(uri("wubble"), vec![diagnostic("four")]);
+    let _ = store.update(uri("wubble"), vec![]);
+
+    assert_eq!(
+        workspace_reports(&store, &HashMap::new()),
+        vec![
+            "/src/wibble.gleam: one",
+            "/src/wobble.gleam: two, three",
+            "/src/wubble.gleam: ",
+        ]
+    );
+
+    let previous_result_ids = HashMap::from([
+        (
+            uri("wibble"),
+            store.result_id(&uri("wibble")).expect("id").to_string(),
+        ),
+        (uri("wobble"), "outdated".to_string()),
+    ]);
+    assert_eq!(
+        workspace_reports(&store, &previous_result_ids),
+        vec![
+            "/src/wibble.gleam: unchanged",
+            "/src/wobble.gleam: two, three",
+            "/src/wubble.gleam: ",
+        ]
+    );
+}
+
+#[test]
+fn workspace_reports_only_include_the_files_selected() {
+    let mut store = DiagnosticStore::default();
+    let _ = store.update(uri("wibble"), vec![diagnostic("one")]);
+    let _ = store.update(uri("wobble"), vec![diagnostic("two")]);
+
+    let reports = store.workspace_reports(&HashMap::new(), |uri, _| uri.path().contains("wob"));
+    assert_eq!(reports.len(), 1);
+}
@@ -0,0 +1,165 @@
+use insta::assert_snapshot;
+use lsp_types::{DocumentHighlightParams, PartialResultParams, Range, WorkDoneProgressParams};
+
+use super::*;
+
+fn document_highlight(tester: TestProject<'_>, position: PositionFinder) -> String {
+    let position = position.find_position(tester.src);
+    let highlights = tester.at(position, |engine, params, _| {
+        let params = DocumentHighlightParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: params.text_document,
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        engine
+            .document_highlight(params)
+            .result
+            .expect("document highlight")
+    });
+    let Some(highlights) = highlights else {
+        return "No highlights".into();
+    };
+
+    let ranges = highlights
+        .into_iter()
+        .map(|highlight| highlight.range)
+        .collect_vec();
+    show_highlights(tester.src, position, &ranges)
+}
+
+/// Underlines every highlighted range of the code, and marks the position of
+/// the cursor with an arrow.
+///
+fn show_highlights(code: &str, position: Position, ranges: &[Range]) -> String {
+    let mut buffer = String::new();
+    for (line_number, line) in code.lines().enumerate() {
+        let line_number = line_number as u32;
+        let underline = (0..line.chars().count() as u32)
+            .map(|column| {
+                let current_position = Position::new(line_number, column);
+                if current_position == position {
+                    '↑'
+                } else if ranges
+                    .iter()
+                    .any(|range| range.start <= current_position && current_position < range.end)
+                {
+                    '▔'
+                } else {
+                    ' '
+                }
+            })
+            .collect::<String>();
+
+        buffer.push_str(line);
+        buffer.push('\n');
+        if !underline.trim().is_empty() {
+            buffer.push_str(underline.trim_end());
+            buffer.push('\n');
+        }
+    }
+    buffer
+}
+
+#[test]
+fn highlight_local_variable() {
+    let src = "
+pub fn main() {
+  let wibble = 1
+  let wobble = wibble + wibble
+  wibble * wobble
+}
+";
+
+    assert_snapshot!(document_highlight(
+        TestProject::for_source(src),
+        find_position_of("wibble +"),
+    ));
+}
+
+#[test]
+fn highlight_function_argument() {
+    let src = "
+pub fn main(wibble: Int) {
+  wibble + 1
+}
+";
+
+    assert_snapshot!(document_highlight(
+        TestProject::for_source(src),
+        find_position_of("wibble:"),
+    ));
+}
+
+#[test]
+fn highlight_module_function() {
+    let src = "
+pub fn main() {
+  wibble(wibble(1))
+}
+
+fn wibble(x) {
+  x
+}
+";
+
+    assert_snapshot!(document_highlight(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+    ));
+}
+
+#[test]
+fn highlight_only_uses_in_current_module() {
+    let src = "
+import other.{wibble}
+
+pub fn main() {
+  wibble() + other.wibble()
+}
+";
+
+    assert_snapshot!(document_highlight(
+        TestProject::for_source(src).add_module(
+            "other",
+            "pub fn wibble() { 1 }
+pub fn wobble() { wibble() }
+"
+        ),
+        find_position_of("wibble()"),
+    ));
+}
+
+#[test]
+fn highlight_type() {
+    let src = "
+pub type Wibble {
+  Wibble
+}
+
+pub fn main(wibble: Wibble) -> Wibble {
+  wibble
+}
+";
+
+    assert_snapshot!(document_highlight(
+        TestProject::for_source(src),
+        find_position_of("-> Wibble").under_char('W'),
+    ));
+}
+
+#[test]
+fn no_highlight_for_literals() {
+    let src = "
+pub fn main() {
+  1 + 2
+}
+";
+
+    assert_snapshot!(document_highlight(
+        TestProject::for_source(src),
+        find_position_of("1"),
+    ));
+}
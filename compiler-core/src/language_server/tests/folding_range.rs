@@ -0,0 +1,138 @@
+use insta::assert_snapshot;
+use lsp_types::{
+    FoldingRangeKind, FoldingRangeParams, PartialResultParams, WorkDoneProgressParams,
+};
+
+use super::*;
+
+/// Prints the code with its line numbers, followed by the lines of each range
+/// that can be folded.
+///
+fn folding_ranges(tester: TestProject<'_>) -> String {
+    let ranges = tester.at(Position::new(0, 0), |engine, params, _| {
+        let params = FoldingRangeParams {
+            text_document: params.text_document,
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        engine
+            .folding_range(params)
+            .result
+            .expect("folding range")
+            .expect("folding ranges")
+    });
+
+    let mut output = String::new();
+    for (line_number, line) in tester.src.lines().enumerate() {
+        output.push_str(&format!("{line_number:>2} | {line}\n"));
+    }
+    output.push('\n');
+    for range in ranges {
+        let kind = match range.kind {
+            Some(FoldingRangeKind::Comment) => " (comment)",
+            Some(FoldingRangeKind::Imports) => " (imports)",
+            Some(FoldingRangeKind::Region) => " (region)",
+            None => "",
+        };
+        output.push_str(&format!("{}-{}{kind}\n", range.start_line, range.end_line));
+    }
+    output
+}
+
+#[test]
+fn fold_functions() {
+    let src = "
+pub fn main() {
+  wibble(1)
+}
+
+fn wibble(x) {
+  let y = x + 1
+  y * 2
+}
+
+fn wobble() { Nil }
+";
+
+    assert_snapshot!(folding_ranges(TestProject::for_source(src)));
+}
+
+#[test]
+fn fold_case_clauses() {
+    let src = "
+pub fn main(x) {
+  case x {
+    1 -> {
+      let y = x + 1
+      y * 2
+    }
+    _ ->
+      0
+  }
+}
+";
+
+    assert_snapshot!(folding_ranges(TestProject::for_source(src)));
+}
+
+#[test]
+fn fold_imports() {
+    let src = "
+import one
+import two
+import three.{
+  type Wibble,
+}
+
+pub fn main() -> three.Wibble {
+  one.one() + two.two()
+  todo
+}
+";
+
+    assert_snapshot!(folding_ranges(
+        TestProject::for_source(src)
+            .add_module("one", "pub fn one() { 1 }\n")
+            .add_module("two", "pub fn two() { 2 }\n")
+            .add_module("three", "pub type Wibble\n")
+    ));
+}
+
+#[test]
+fn fold_comments() {
+    let src = "
+//// The main module.
+//// It does things.
+
+/// Wibbles a value.
+/// Twice.
+pub fn wibble(x) {
+  // Nothing to see
+  // here.
+  x
+}
+
+// A lone comment.
+pub type Wobble {
+  Wobble
+  Wubble
+}
+";
+
+    assert_snapshot!(folding_ranges(TestProject::for_source(src)));
+}
+
+#[test]
+fn fold_anonymous_functions() {
+    let src = "
+pub fn main() {
+  let f = fn(x) {
+    x + 1
+  }
+  use x <- f
+  x
+}
+";
+
+    assert_snapshot!(folding_ranges(TestProject::for_source(src)));
+}
@@ -0,0 +1,125 @@
+use camino::Utf8Path;
+use lsp_types::{Position, Range};
+
+use crate::language_server::formatting::{on_type_formatting, range_formatting};
+
+use super::*;
+
+fn format_range(src: &str, range: Range) -> String {
+    let edits =
+        range_formatting(&src.into(), Utf8Path::new("app.gleam"), range).expect("formatted range");
+    apply_code_edit(src, edits)
+}
+
+fn format_on_type(src: &str, position: Position, character: &str) -> String {
+    let edits = on_type_formatting(&src.into(), Utf8Path::new("app.gleam"), position, character);
+    apply_code_edit(src, edits)
+}
+
+#[test]
+fn range_formatting_formats_definitions_in_range() {
+    let src = "pub fn   wibble( ) {   1 }
+
+pub fn   wobble( ) {   2 }
+";
+    let range = Range::new(Position::new(2, 0), Position::new(2, 3));
+    assert_eq!(
+        format_range(src, range),
+        "pub fn   wibble( ) {   1 }
+
+pub fn wobble() {
+  2
+}
+"
+    );
+}
+
+#[test]
+fn range_formatting_reports_invalid_code() {
+    let src = "pub fn wibble( {";
+    let range = Range::new(Position::new(0, 0), Position::new(0, 3));
+    assert!(range_formatting(&src.into(), Utf8Path::new("app.gleam"), range).is_err());
+}
+
+#[test]
+fn closing_brace_formats_the_definition_it_closes() {
+    let src = "pub fn   wibble( ) {   1 }
+
+pub fn   wobble( ) {   2 }
+";
+    assert_eq!(
+        format_on_type(src, Position::new(0, 26), "}"),
+        "pub fn wibble() {
+  1
+}
+
+pub fn   wobble( ) {   2 }
+"
+    );
+}
+
+#[test]
+fn closing_brace_formats_the_statement_it_closes() {
+    let src = "pub fn main() {
+  let   wibble = {   1 }
+  let   wobble = {   2 }
+  wibble
+}
+";
+    assert_eq!(
+        format_on_type(src, Position::new(1, 24), "}"),
+        "pub fn main() {
+  let wibble = {
+    1
+  }
+  let   wobble = {   2 }
+  wibble
+}
+"
+    );
+}
+
+#[test]
+fn closing_brace_in_the_middle_of_a_statement_does_nothing() {
+    let src = "pub fn main() {
+  let   wibble = {   1 } + 2
+  wibble
+}
+";
+    assert_eq!(format_on_type(src, Position::new(1, 24), "}"), src);
+}
+
+#[test]
+fn new_line_formats_the_statement_on_the_previous_line() {
+    let src = "pub fn main() {
+  let   wibble =   [1,2]
+  
+}
+";
+    assert_eq!(
+        format_on_type(src, Position::new(2, 2), "\n"),
+        "pub fn main() {
+  let wibble = [1, 2]
+  
+}
+"
+    );
+}
+
+#[test]
+fn new_line_after_an_opening_brace_does_nothing() {
+    let src = "pub fn   main( ) {
+  
+}
+";
+    assert_eq!(format_on_type(src, Position::new(1, 2), "\n"), src);
+}
+
+#[test]
+fn on_type_formatting_ignores_invalid_code() {
+    let src = "pub fn main() {
+  let wibble =
+}
+";
+    assert_eq!(format_on_type(src, Position::new(2, 1), "}"), src);
+}
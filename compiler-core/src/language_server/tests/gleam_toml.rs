@@ -0,0 +1,370 @@
+use camino::Utf8Path;
+use hexpm::version::Version;
+use lsp_types::{CompletionTextEdit, HoverContents, MarkedString, Position};
+
+use crate::{
+    ast::SrcSpan,
+    io::{FileSystemWriter, memory::InMemoryFileSystem},
+    language_server::gleam_toml::{
+        CachedPackage, cached_packages, completions, diagnostics, hover,
+    },
+    manifest::Manifest,
+};
+
+/// The completions at the position of the `|` in the source, each printed
+/// with the code it replaces the code before the cursor with.
+///
+fn completions_at(src: &str, packages: &[CachedPackage]) -> Vec<String> {
+    let cursor = src.find('|').expect("cursor");
+    let line = src.get(..cursor).expect("cursor").matches('\n').count() as u32;
+    let line_start = src
+        .get(..cursor)
+        .expect("cursor")
+        .rfind('\n')
+        .map_or(0, |i| i + 1);
+    let character = src
+        .get(line_start..cursor)
+        .expect("cursor")
+        .encode_utf16()
+        .count();
+    let position = Position::new(line, character as u32);
+    let src = src.replace('|', "");
+
+    completions(&src, position, packages)
+        .into_iter()
+        .map(|completion| match completion.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => format!(
+                "{} ({}:{})",
+                edit.new_text, edit.range.start.character, edit.range.end.character
+            ),
+            _ => panic!("completion without an edit"),
+        })
+        .collect()
+}
+
+fn package(name: &str, version: &str) -> CachedPackage {
+    CachedPackage {
+        name: name.into(),
+        version: Version::parse(version).expect("version"),
+    }
+}
+
+fn hover_at(src: &str, position: Position) -> Option<String> {
+    let manifest: Manifest = toml::from_str(
+        r#"packages = [
+  { name = "gleam_stdlib", version = "0.60.0", build_tools = ["gleam"], requirements = [], source = "hex", outer_checksum = "0116" },
+  { name = "wibble", version = "1.2.3", build_tools = ["gleam"], requirements = [], source = "local", path = "../wibble" },
+]
+
+[requirements]
+gleam_stdlib = { version = ">= 0.60.0 and < 1.0.0" }
+wibble = { path = "../wibble" }
+"#,
+    )
+    .expect("manifest");
+
+    hover(src, position, Some(&manifest)).map(|hover| match hover.contents {
+        HoverContents::Scalar(MarkedString::String(contents)) => contents,
+        _ => panic!("unexpected hover contents"),
+    })
+}
+
+#[test]
+fn valid_config_has_no_diagnostics() {
+    let src = r#"name = "wibble"
+target = "javascript"
+"#;
+    assert_eq!(
+        diagnostics(Utf8Path::new("gleam.toml"), &src.into()),
+        vec![]
+    );
+}
+
+#[test]
+fn invalid_value_is_reported_at_its_location() {
+    let src = r#"name = "wibble"
+target = "wasm"
+"#;
+    let diagnostics = diagnostics(Utf8Path::new("gleam.toml"), &src.into());
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got {diagnostics:?}");
+    };
+    let location = diagnostic.location.as_ref().expect("location");
+    let span = location.label.span;
+    assert_eq!(
+        src.get(span.start as usize..span.end as usize),
+        Some("\"wasm\"")
+    );
+    assert_eq!(
+        diagnostic.text,
+        "unknown variant `wasm`, expected one of `erl`, `erlang`, `javascript`, `js` for key `target`"
+    );
+}
+
+#[test]
+fn invalid_value_in_a_table_is_reported_at_its_location() {
+    let src = r#"name = "wibble"
+allow_sys = true
+
+[javascript.deno]
+allow_sys = "yes"
+"#;
+    let diagnostics = diagnostics(Utf8Path::new("gleam.toml"), &src.into());
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got {diagnostics:?}");
+    };
+    let location = diagnostic.location.as_ref().expect("location");
+    assert_eq!(location.label.span, SrcSpan::new(64, 69));
+}
+
+#[test]
+fn missing_field_is_reported_on_the_first_line() {
+    let src = r#"version = "1.0.0"
+"#;
+    let diagnostics = diagnostics(Utf8Path::new("gleam.toml"), &src.into());
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got {diagnostics:?}");
+    };
+    let location = diagnostic.location.as_ref().expect("location");
+    assert_eq!(location.label.span, SrcSpan::new(0, 17));
+    assert_eq!(diagnostic.text, "missing field `name`");
+}
+
+#[test]
+fn valid_workspace_has_no_diagnostics() {
+    let src = r#"[workspace]
+members = ["wibble", "wobble"]
+"#;
+    assert_eq!(
+        diagnostics(Utf8Path::new("gleam.toml"), &src.into()),
+        vec![]
+    );
+}
+
+#[test]
+fn invalid_workspace_members_are_reported_at_their_location() {
+    let src = r#"[workspace]
+members = "wibble"
+"#;
+    let diagnostics = diagnostics(Utf8Path::new("gleam.toml"), &src.into());
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got {diagnostics:?}");
+    };
+    let location = diagnostic.location.as_ref().expect("location");
+    let span = location.label.span;
+    assert_eq!(
+        src.get(span.start as usize..span.end as usize),
+        Some("\"wibble\"")
+    );
+}
+
+#[test]
+fn completes_workspace_keys() {
+    let src = r#"[workspace]
+mem|
+"#;
+    assert_eq!(completions_at(src, &[]), vec!["members (0:3)"]);
+}
+
+#[test]
+fn completes_top_level_keys() {
+    let src = r#"name = "wibble"
+ta|
+"#;
+    assert_eq!(
+        completions_at(src, &[]),
+        vec![
+            "name (0:2)",
+            "version (0:2)",
+            "gleam (0:2)",
+            "licences (0:2)",
+            "description (0:2)",
+            "target (0:2)",
+            "internal_modules (0:2)",
+            "repository (0:2)",
+            "links (0:2)",
+        ]
+    );
+}
+
+#[test]
+fn completes_deno_flags() {
+    let src = r#"name = "wibble"
+
+[javascript.deno]
+allow_net = true
+|
+"#;
+    assert_eq!(
+        completions_at(src, &[]),
+        vec![
+            "allow_all (0:0)",
+            "allow_env (0:0)",
+            "allow_ffi (0:0)",
+            "allow_hrtime (0:0)",
+            "allow_net (0:0)",
+            "allow_read (0:0)",
+            "allow_run (0:0)",
+            "allow_sys (0:0)",
+            "allow_write (0:0)",
+            "location (0:0)",
+            "unstable (0:0)",
+        ]
+    );
+}
+
+#[test]
+fn completes_table_names() {
+    let src = r#"name = "wibble"
+
+[java|
+"#;
+    assert_eq!(
+        completions_at(src, &[]),
+        vec![
+            "[dependencies] (0:5)",
+            "[dev-dependencies] (0:5)",
+            "[documentation] (0:5)",
+            "[erlang] (0:5)",
+            "[javascript] (0:5)",
+            "[javascript.deno] (0:5)",
+            "[repository] (0:5)",
+            "[warnings] (0:5)",
+            "[workspace] (0:5)",
+        ]
+    );
+}
+
+#[test]
+fn completes_target_values() {
+    let src = r#"name = "wibble"
+target = "ja|
+"#;
+    assert_eq!(
+        completions_at(src, &[]),
+        vec!["\"erlang\" (9:12)", "\"javascript\" (9:12)"]
+    );
+}
+
+#[test]
+fn completion_positions_count_utf16_code_units() {
+    // The ideographic space is three bytes long but a single UTF-16 code
+    // unit.
+    let src = "name = \"wibble\"\n\u{3000}tar|\n";
+    assert_eq!(
+        completions_at(src, &[]).first().map(String::as_str),
+        Some("name (1:4)")
+    );
+}
+
+#[test]
+fn completes_dependency_names_from_the_package_cache() {
+    let src = r#"name = "wibble"
+
+[dependencies]
+gleam_stdlib = ">= 0.60.0 and < 1.0.0"
+gl|
+"#;
+    let packages = [
+        package("gleam_json", "3.0.1"),
+        package("gleam_json", "2.3.0"),
+        package("gleam_stdlib", "0.60.0"),
+    ];
+    assert_eq!(
+        completions_at(src, &packages),
+        vec!["gleam_json (0:2)", "gleam_stdlib (0:2)"]
+    );
+}
+
+#[test]
+fn completes_dependency_versions_from_the_package_cache() {
+    let src = r#"name = "wibble"
+
+[dev-dependencies]
+gleam_json = |
+"#;
+    let packages = [
+        package("gleam_json", "3.0.1"),
+        package("gleam_json", "2.3.0"),
+        package("gleam_stdlib", "0.60.0"),
+    ];
+    assert_eq!(
+        completions_at(src, &packages),
+        vec![
+            "\">= 3.0.1 and < 4.0.0\" (13:13)",
+            "\">= 2.3.0 and < 3.0.0\" (13:13)",
+        ]
+    );
+}
+
+#[test]
+fn cached_packages_are_read_from_tarball_names() {
+    let io = InMemoryFileSystem::new();
+    let directory = Utf8Path::new("/cache/packages");
+    for file in [
+        "gleam_stdlib-0.60.0.tar",
+        "gleam_json-2.3.0.tar",
+        "gleam_json-3.0.0-rc1.tar",
+        "not_a_tarball",
+    ] {
+        io.write(&directory.join(file), "").expect("write");
+    }
+
+    assert_eq!(
+        cached_packages(&io, directory),
+        vec![
+            package("gleam_json", "3.0.0-rc1"),
+            package("gleam_json", "2.3.0"),
+            package("gleam_stdlib", "0.60.0"),
+        ]
+    );
+}
+
+#[test]
+fn hover_shows_the_locked_version_of_a_dependency() {
+    let src = r#"name = "app"
+
+[dependencies]
+gleam_stdlib = ">= 0.60.0 and < 1.0.0"
+wibble = { path = "../wibble" }
+"#;
+    assert_eq!(
+        hover_at(src, Position::new(3, 4)),
+        Some(
+            "```toml
+gleam_stdlib = \"0.60.0\"
+```
+Locked in `manifest.toml`, from Hex."
+                .into()
+        )
+    );
+    assert_eq!(
+        hover_at(src, Position::new(4, 2)),
+        Some(
+            "```toml
+wibble = \"1.2.3\"
+```
+Locked in `manifest.toml`, from `../wibble`."
+                .into()
+        )
+    );
+}
+
+#[test]
+fn no_hover_outside_of_dependencies() {
+    let src = r#"name = "gleam_stdlib"
+
+[dependencies]
+gleam_stdlib = ">= 0.60.0 and < 1.0.0"
+"#;
+    assert_eq!(hover_at(src, Position::new(0, 1)), None);
+    assert_eq!(hover_at(src, Position::new(3, 20)), None);
+}
+
+#[test]
+fn hover_positions_count_utf16_code_units() {
+    let src =
+        "name = \"app\"\n\n[dependencies]\n\u{3000}gleam_stdlib = \">= 0.60.0 and < 1.0.0\"\n";
+    assert!(hover_at(src, Position::new(3, 13)).is_some());
+    assert_eq!(hover_at(src, Position::new(3, 14)), None);
+}
@@ -0,0 +1,208 @@
+use lsp_types::GotoDefinitionParams;
+
+use super::*;
+
+/// The locations of the implementations found at the given position, each
+/// printed as its path followed by the code it points to.
+///
+fn implementations(project: &TestProject<'_>, position: PositionFinder) -> Vec<String> {
+    let position = position.find_position(project.src);
+    let locations = project.at(position, |engine, params, _| {
+        let params = GotoDefinitionParams {
+            text_document_position_params: params,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        engine
+            .goto_implementation(params)
+            .result
+            .expect("implementations")
+    });
+
+    locations
+        .into_iter()
+        .map(|location| {
+            let path = location
+                .uri
+                .path_segments()
+                .expect("a location to jump to")
+                // Discard windows' `C:` path segment at the beginning of a uri.
+                .skip_while(|segment| *segment == "C:")
+                .join("/");
+            let src = project
+                .native_files
+                .iter()
+                .find(|(native_path, _)| path.ends_with(native_path))
+                .map(|(_, src)| *src)
+                .expect("a native file to jump to");
+            let line_numbers = LineNumbers::new(src);
+            let start = line_numbers.byte_index(location.range.start) as usize;
+            let end = line_numbers.byte_index(location.range.end) as usize;
+            let code = src.get(start..end).expect("a valid range");
+            format!("{path}: {code:?} at {}", location.range.start.line)
+        })
+        .collect()
+}
+
+const ERLANG_FFI: &str = "-module(app_ffi).
+-export([now/0, 'receive'/1]).
+
+%% Returns the current time.
+now() ->
+    erlang:system_time().
+
+'receive'(Timeout) ->
+    receive X -> X after Timeout -> nil end.
+";
+
+const JAVASCRIPT_FFI: &str = "import { Ok } from \"./gleam.mjs\";
+
+// Returns the current time.
+export function now() {
+  return Date.now();
+}
+
+export const receive = (timeout) => undefined;
+";
+
+#[test]
+fn implementation_of_an_erlang_external_call() {
+    let code = "
+@external(erlang, \"app_ffi\", \"now\")
+fn now() -> Int
+
+pub fn main() {
+  now()
+}
+";
+    let project = TestProject::for_source(code).add_native_file("src/app_ffi.erl", ERLANG_FFI);
+    assert_eq!(
+        implementations(&project, find_position_of("now()").nth_occurrence(2)),
+        vec!["src/app_ffi.erl: \"now\" at 4"]
+    );
+}
+
+#[test]
+fn implementation_of_a_quoted_erlang_function() {
+    let code = "
+@external(erlang, \"app_ffi\", \"receive\")
+fn receive(timeout: Int) -> Nil
+
+pub fn main() {
+  receive(10)
+}
+";
+    let project = TestProject::for_source(code).add_native_file("src/app_ffi.erl", ERLANG_FFI);
+    assert_eq!(
+        implementations(&project, find_position_of("receive(10)")),
+        vec!["src/app_ffi.erl: \"'receive'\" at 7"]
+    );
+}
+
+#[test]
+fn implementation_of_an_erlang_function_found_in_a_nested_directory() {
+    let code = "
+@external(erlang, \"app_ffi\", \"now\")
+fn now() -> Int
+";
+    let project =
+        TestProject::for_source(code).add_native_file("src/internal/app_ffi.erl", ERLANG_FFI);
+    assert_eq!(
+        implementations(&project, find_position_of("fn now")),
+        vec!["src/internal/app_ffi.erl: \"now\" at 4"]
+    );
+}
+
+#[test]
+fn implementation_of_an_elixir_external() {
+    let code = "
+@external(erlang, \"Elixir.App.Ffi\", \"now\")
+fn now() -> Int
+";
+    let elixir = "defmodule App.Ffi do
+  def now, do: System.system_time()
+end
+";
+    let project = TestProject::for_source(code).add_native_file("src/app_ffi.ex", elixir);
+    assert_eq!(
+        implementations(&project, find_position_of("fn now")),
+        vec!["src/app_ffi.ex: \"now\" at 1"]
+    );
+}
+
+#[test]
+fn implementations_of_a_function_with_both_externals() {
+    let code = "
+@external(erlang, \"app_ffi\", \"now\")
+@external(javascript, \"./app_ffi.mjs\", \"now\")
+fn now() -> Int
+
+pub fn main() {
+  now()
+}
+";
+    let project = TestProject::for_source(code)
+        .add_native_file("src/app_ffi.erl", ERLANG_FFI)
+        .add_native_file("src/app_ffi.mjs", JAVASCRIPT_FFI);
+    assert_eq!(
+        implementations(&project, find_position_of("now()").nth_occurrence(2)),
+        vec![
+            "src/app_ffi.erl: \"now\" at 4",
+            "src/app_ffi.mjs: \"now\" at 3",
+        ]
+    );
+}
+
+#[test]
+fn implementation_of_a_javascript_external_in_another_module() {
+    let code = "
+import wibble/time
+
+pub fn main() {
+  time.receive(10)
+}
+";
+    let time = "
+@external(erlang, \"time_ffi\", \"receive\")
+@external(javascript, \"../ffi/time.mjs\", \"receive\")
+pub fn receive(timeout: Int) -> Nil
+";
+    let project = TestProject::for_source(code)
+        .add_module("wibble/time", time)
+        .add_native_file("src/ffi/time.mjs", JAVASCRIPT_FFI);
+    assert_eq!(
+        implementations(&project, find_position_of("receive")),
+        vec!["src/ffi/time.mjs: \"receive\" at 7"]
+    );
+}
+
+#[test]
+fn implementation_of_a_missing_native_file() {
+    let code = "
+@external(erlang, \"app_ffi\", \"now\")
+fn now() -> Int
+";
+    let project = TestProject::for_source(code);
+    assert_eq!(
+        implementations(&project, find_position_of("fn now")),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn no_implementation_of_a_gleam_function() {
+    let code = "
+fn now() -> Int {
+  1
+}
+
+pub fn main() {
+  now()
+}
+";
+    let project = TestProject::for_source(code).add_native_file("src/app_ffi.erl", ERLANG_FFI);
+    assert_eq!(
+        implementations(&project, find_position_of("now()").nth_occurrence(2)),
+        Vec::<String>::new()
+    );
+}
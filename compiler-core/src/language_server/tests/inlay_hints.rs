@@ -0,0 +1,237 @@
+use insta::assert_snapshot;
+use lsp_types::{InlayHint, InlayHintLabel, InlayHintParams, Range};
+
+use crate::language_server::configuration::InlayHintsConfig;
+
+use super::*;
+
+/// Prints the source with each hint inserted where it would be shown, wrapped
+/// in square brackets.
+///
+fn show_hints(src: &str, hints: Vec<InlayHint>) -> String {
+    let line_numbers = LineNumbers::new(src);
+    let mut output = src.to_string();
+    let hints = hints
+        .into_iter()
+        .map(|hint| (line_numbers.byte_index(hint.position), hint))
+        .sorted_by_key(|(index, _)| *index)
+        .rev()
+        .collect_vec();
+    for (index, hint) in hints {
+        let label = match hint.label {
+            InlayHintLabel::String(label) => label,
+            InlayHintLabel::LabelParts(parts) => parts.into_iter().map(|part| part.value).join(""),
+        };
+        output.insert_str(index as usize, &format!("[{label}]"));
+    }
+    output
+}
+
+fn inlay_hints_in_range(tester: TestProject<'_>, config: InlayHintsConfig, range: Range) -> String {
+    let hints = tester.at(Position::default(), |engine, params, _| {
+        let params = InlayHintParams {
+            text_document: params.text_document,
+            range,
+            work_done_progress_params: Default::default(),
+        };
+        engine
+            .inlay_hints(params, config)
+            .result
+            .expect("inlay hints")
+    });
+    show_hints(tester.src, hints)
+}
+
+fn inlay_hints_with_config(tester: TestProject<'_>, config: InlayHintsConfig) -> String {
+    let whole_module = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+    inlay_hints_in_range(tester, config, whole_module)
+}
+
+fn inlay_hints(tester: TestProject<'_>) -> String {
+    inlay_hints_with_config(tester, InlayHintsConfig::default())
+}
+
+#[test]
+fn let_binding_types() {
+    let src = r#"
+pub fn main() {
+  let number = 1
+  let annotated: Int = 2
+  let #(first, _) = #("a", 1.0)
+  let assert [head, ..] = [number, annotated]
+  let _discarded = head
+  let [_, ..] as numbers = [head]
+  let Wrapper(value:) = Wrapper(numbers)
+  value
+}
+
+type Wrapper {
+  Wrapper(value: List(Int))
+}
+"#;
+    assert_snapshot!(inlay_hints(TestProject::for_source(src)));
+}
+
+#[test]
+fn type_hints_insert_annotations() {
+    let src = r#"
+pub fn main() {
+  let #(first, _) = #(1, 2)
+  let second = first
+  let id = fn(x) { x }
+  id(second)
+}
+"#;
+    let tester = TestProject::for_source(src);
+    let hints = tester.at(Position::default(), |engine, params, _| {
+        let params = InlayHintParams {
+            text_document: params.text_document,
+            range: Range::new(Position::new(0, 0), Position::new(u32::MAX, 0)),
+            work_done_progress_params: Default::default(),
+        };
+        engine
+            .inlay_hints(params, InlayHintsConfig::default())
+            .result
+            .expect("inlay hints")
+    });
+    let edits = hints
+        .into_iter()
+        .flat_map(|hint| hint.text_edits.unwrap_or_default())
+        .collect_vec();
+    assert_snapshot!(apply_code_edit(src, edits));
+}
+
+#[test]
+fn function_return_types() {
+    let src = r#"
+pub fn main() {
+  let add = fn(a, b) { a + b }
+  let annotated = fn(a) -> Int { a }
+  add(annotated(1), 2)
+}
+
+pub fn annotated() -> String {
+  "wibble"
+}
+
+pub fn identity(x) {
+  x
+}
+"#;
+    assert_snapshot!(inlay_hints(TestProject::for_source(src)));
+}
+
+#[test]
+fn pipeline_step_types() {
+    let src = r#"
+pub fn main() -> String {
+  [1, 2, 3]
+  |> map(fn(x) -> Int { x * 2 })
+  |> length
+  |> to_string
+}
+
+pub fn single_line() -> Int {
+  [1, 2, 3] |> length
+}
+
+fn map(list: List(a), fun: fn(a) -> b) -> List(b) { todo }
+fn length(list: List(a)) -> Int { todo }
+fn to_string(int: Int) -> String { todo }
+"#;
+    assert_snapshot!(inlay_hints(TestProject::for_source(src)));
+}
+
+#[test]
+fn positional_argument_labels() {
+    let src = r#"
+pub fn main() -> Nil {
+  let length = 2
+  let wibble = 3
+  replace(in: "wibble", 1, length)
+  replace("wibble", wibble, 3)
+  "wobble" |> replace(4, 5)
+  Nil
+}
+
+fn replace(in string: String, wibble at: Int, length length: Int) -> String {
+  todo
+}
+"#;
+    assert_snapshot!(inlay_hints(TestProject::for_source(src)));
+}
+
+#[test]
+fn record_constructor_labels() {
+    let src = r#"
+pub type Person {
+  Person(name: String, age: Int)
+}
+
+pub fn main() -> Person {
+  Person("Lucy", 10)
+}
+"#;
+    assert_snapshot!(inlay_hints(TestProject::for_source(src)));
+}
+
+#[test]
+fn imported_function_labels() {
+    let src = r#"
+import wibble
+
+pub fn main() -> Int {
+  wibble.add(1, second: 2)
+}
+"#;
+    let tester = TestProject::for_source(src).add_module(
+        "wibble",
+        "pub fn add(first a: Int, second b: Int) -> Int { a + b }",
+    );
+    assert_snapshot!(inlay_hints(tester));
+}
+
+#[test]
+fn disabled_hints() {
+    let src = r#"
+pub fn main() {
+  let number = 1
+  [number]
+  |> id
+  |> first(2)
+}
+
+fn id(x) { x }
+fn first(list: List(a), default: a) -> a { todo }
+"#;
+    let config = InlayHintsConfig {
+        variable_types: false,
+        return_types: false,
+        pipelines: false,
+        parameter_names: false,
+    };
+    assert_snapshot!(inlay_hints_with_config(
+        TestProject::for_source(src),
+        config
+    ));
+}
+
+#[test]
+fn hints_in_range() {
+    let src = r#"
+pub fn main() {
+  let first = 1
+  let second = 2
+  let third = 3
+  first + second + third
+}
+"#;
+    let range = find_position_of("let second")
+        .select_until(find_position_of("3"))
+        .find_range(src);
+    assert_snapshot!(inlay_hints_in_range(
+        TestProject::for_source(src),
+        InlayHintsConfig::default(),
+        range
+    ));
+}
@@ -0,0 +1,430 @@
+use insta::assert_snapshot;
+use lsp_types::{DocumentChangeOperation, DocumentChanges, OneOf, ResourceOp};
+
+use crate::language_server::move_definition::MoveDefinitionParams;
+
+use super::*;
+
+/// Prints every module of the project, and the ones created, after moving
+/// the definition at the given position to another module.
+///
+fn move_definition(tester: TestProject<'_>, position: PositionFinder, module: &str) -> String {
+    let position = position.find_position(tester.src);
+    let edit = tester.at(position, |engine, params, _| {
+        let params = MoveDefinitionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: params.text_document,
+                position,
+            },
+            module: module.into(),
+        };
+        engine
+            .move_definition(params)
+            .result
+            .expect("move definition")
+    });
+    let edit = match edit {
+        Ok(edit) => edit,
+        Err(error) => return format!("No change: {}", error.message()),
+    };
+
+    let Some(DocumentChanges::Operations(operations)) = edit.document_changes else {
+        panic!("Expected document change operations");
+    };
+    let mut created = vec![];
+    let mut changes = vec![];
+    for operation in operations {
+        match operation {
+            DocumentChangeOperation::Op(ResourceOp::Create(create)) => created.push(
+                tester
+                    .module_name_from_url(&create.uri)
+                    .expect("module name"),
+            ),
+            DocumentChangeOperation::Op(_) => panic!("Unexpected resource operation"),
+            DocumentChangeOperation::Edit(edit) => {
+                let name = tester
+                    .module_name_from_url(&edit.text_document.uri)
+                    .expect("module name");
+                let edits = edit
+                    .edits
+                    .into_iter()
+                    .map(|edit| match edit {
+                        OneOf::Left(edit) => edit,
+                        OneOf::Right(edit) => edit.text_edit,
+                    })
+                    .collect_vec();
+                changes.push((name, edits));
+            }
+        }
+    }
+
+    let edits_for = |name: &str| {
+        changes
+            .iter()
+            .filter(|(module, _)| module == name)
+            .flat_map(|(_, edits)| edits.clone())
+            .collect_vec()
+    };
+
+    let mut output = String::new();
+    for (name, src) in tester
+        .root_package_modules
+        .iter()
+        .chain(std::iter::once(&("app", tester.src)))
+    {
+        output.push_str(&format!(
+            "-- {name}.gleam\n{}\n",
+            apply_code_edit(src, edits_for(name))
+        ));
+    }
+    for name in created {
+        output.push_str(&format!(
+            "-- {name}.gleam (new)\n{}\n",
+            apply_code_edit("", edits_for(&name))
+        ));
+    }
+    output
+}
+
+#[test]
+fn move_function_to_new_module() {
+    let src = "
+pub fn main() {
+  wibble(1)
+}
+
+pub fn wibble(x: Int) -> Int {
+  x + 1
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn move_function_to_nested_new_module() {
+    let src = "
+pub fn wibble(x: Int) -> Int {
+  x + 1
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("x + 1"),
+        "wobble/wubble",
+    ));
+}
+
+#[test]
+fn move_function_with_private_helpers() {
+    let src = "
+/// Doubles and increments.
+pub fn wibble(x: Int) -> Int {
+  increment(double(x))
+}
+
+fn double(x: Int) -> Int {
+  x * 2
+}
+
+@deprecated(\"Use something else\")
+fn increment(x: Int) -> Int {
+  x + 1
+}
+
+pub fn main() {
+  Nil
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("wibble"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn move_function_carrying_imports() {
+    let src = "
+import other.{type Wobble, wobble as wob}
+
+pub fn wibble(x: Wobble) -> Int {
+  wob(x) + other.count
+}
+
+pub fn main() {
+  other.count
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src).add_module(
+            "other",
+            "pub type Wobble
+pub fn wobble(_: Wobble) -> Int { 1 }
+pub const count = 1
+"
+        ),
+        find_position_of("wibble"),
+        "wubble",
+    ));
+}
+
+#[test]
+fn move_function_to_existing_module() {
+    let src = "
+pub fn wibble(x: Int) -> Int {
+  x + 1
+}
+
+pub fn main() {
+  Nil
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src)
+            .add_module(
+                "wobble",
+                "import other
+
+pub fn wobble() {
+  other.thing
+}
+"
+            )
+            .add_module("other", "pub const thing = 1\n"),
+        find_position_of("wibble"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn move_function_updates_references_in_other_modules() {
+    let src = "
+pub fn wibble(x: Int) -> Int {
+  x + 1
+}
+
+pub fn wobble() {
+  Nil
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src)
+            .add_module(
+                "qualified",
+                "import app
+
+pub fn main() {
+  app.wibble(1)
+}
+"
+            )
+            .add_module(
+                "unqualified",
+                "import app.{wibble, wobble}
+
+pub fn main() {
+  wobble()
+  wibble(1)
+}
+"
+            )
+            .add_module(
+                "aliased",
+                "import app.{wibble as wib}
+import wubble
+
+pub fn main() {
+  app.wobble()
+  wubble.wubble(wib(1))
+}
+"
+            )
+            .add_module("wubble", "pub fn wubble(x) { x }\n"),
+        find_position_of("wibble"),
+        "wubble",
+    ));
+}
+
+#[test]
+fn move_type_used_by_module() {
+    let src = "
+pub type Wibble {
+  Wibble(Int)
+  Wobble
+}
+
+pub fn main(value: Wibble) -> Int {
+  case value {
+    Wibble(n) -> n
+    Wobble -> 0
+  }
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("Wibble(Int)"),
+        "wibble",
+    ));
+}
+
+#[test]
+fn move_private_function_used_by_module_makes_it_public() {
+    let src = "
+pub fn main() {
+  wibble(1)
+}
+
+fn wibble(x: Int) -> Int {
+  x + 1
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn move_function_into_module_using_it() {
+    let src = "
+import wobble.{wobble}
+
+pub fn wibble(x: Int) -> Int {
+  wobble(x) + wobble.count
+}
+
+pub fn main() {
+  Nil
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src).add_module(
+            "wobble",
+            "pub const count = 1
+
+pub fn wobble(x) {
+  x
+}
+"
+        ),
+        find_position_of("wibble"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn move_function_into_module_importing_it() {
+    let src = "
+pub fn wibble(x: Int) -> Int {
+  x + 1
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src).add_module(
+            "wobble",
+            "import app.{wibble as wib}
+
+pub fn main() {
+  app.wibble(wib(1))
+}
+"
+        ),
+        find_position_of("wibble"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn no_move_when_private_helper_is_shared() {
+    let src = "
+pub fn wibble(x: Int) -> Int {
+  double(x)
+}
+
+pub fn wobble(x: Int) -> Int {
+  double(x)
+}
+
+fn double(x: Int) -> Int {
+  x * 2
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("wibble"),
+        "wubble",
+    ));
+}
+
+#[test]
+fn no_move_when_name_is_taken() {
+    let src = "
+pub fn wibble(x: Int) -> Int {
+  x + 1
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src).add_module("wobble", "pub fn wibble() { Nil }\n"),
+        find_position_of("wibble"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn no_move_when_it_creates_an_import_cycle() {
+    let src = "
+pub fn main() {
+  wibble(1)
+}
+
+pub fn wibble(x: Int) -> Int {
+  x + one()
+}
+
+pub fn one() -> Int {
+  1
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("wibble(x"),
+        "wobble",
+    ));
+}
+
+#[test]
+fn no_move_when_opaque_constructor_is_used_by_module() {
+    let src = "
+pub opaque type Wibble {
+  Wibble(Int)
+}
+
+pub fn new() -> Wibble {
+  Wibble(1)
+}
+";
+
+    assert_snapshot!(move_definition(
+        TestProject::for_source(src),
+        find_position_of("Wibble {"),
+        "wobble",
+    ));
+}
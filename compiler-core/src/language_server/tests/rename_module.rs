@@ -0,0 +1,169 @@
+use insta::assert_snapshot;
+use lsp_types::{FileRename, RenameFilesParams};
+
+use super::*;
+
+/// Prints every module of the project after renaming the file or directory at
+/// the given path, relative to the source directory.
+///
+fn rename_module(tester: TestProject<'_>, old_path: &str, new_path: &str) -> String {
+    let edit = tester.at(Position::new(0, 0), |engine, params, _| {
+        let uri = params.text_document.uri;
+        let params = RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: uri.join(old_path).expect("old uri").to_string(),
+                new_uri: uri.join(new_path).expect("new uri").to_string(),
+            }],
+        };
+        engine
+            .will_rename_files(&params)
+            .result
+            .expect("will rename files")
+    });
+    let Some(edit) = edit else {
+        return "No change".into();
+    };
+
+    let changes = edit.changes.expect("changes");
+    let edits_for = |name: &str| {
+        changes
+            .iter()
+            .filter(|(uri, _)| tester.module_name_from_url(uri).as_deref() == Some(name))
+            .flat_map(|(_, edits)| edits.clone())
+            .collect_vec()
+    };
+
+    let mut output = String::new();
+    for (name, src) in tester
+        .root_package_modules
+        .iter()
+        .chain(std::iter::once(&("app", tester.src)))
+    {
+        output.push_str(&format!(
+            "-- {name}.gleam\n{}\n",
+            apply_code_edit(src, edits_for(name))
+        ));
+    }
+    output
+}
+
+#[test]
+fn rename_module_updates_qualified_references() {
+    let src = "
+import wibble
+
+pub fn main() -> wibble.Wibble {
+  wibble.wibble(wibble.value)
+}
+";
+
+    assert_snapshot!(rename_module(
+        TestProject::for_source(src).add_module(
+            "wibble",
+            "pub type Wibble
+pub fn wibble(x) -> Wibble { todo }
+pub const value = 1
+"
+        ),
+        "wibble.gleam",
+        "wobble.gleam",
+    ));
+}
+
+#[test]
+fn rename_module_keeps_explicit_alias() {
+    let src = "
+import wibble as w
+import wibble.{wibble}
+
+pub fn main() {
+  w.wibble(wibble(1))
+}
+";
+
+    assert_snapshot!(rename_module(
+        TestProject::for_source(src).add_module("wibble", "pub fn wibble(x) { x }\n"),
+        "wibble.gleam",
+        "wobble.gleam",
+    ));
+}
+
+#[test]
+fn rename_module_removes_redundant_alias() {
+    let src = "
+import wibble as wobble
+
+pub fn main() {
+  wobble.wibble(1)
+}
+";
+
+    assert_snapshot!(rename_module(
+        TestProject::for_source(src).add_module("wibble", "pub fn wibble(x) { x }\n"),
+        "wibble.gleam",
+        "wobble.gleam",
+    ));
+}
+
+#[test]
+fn rename_module_keeps_old_alias_when_new_one_clashes() {
+    let src = "
+import other/wobble
+import wibble
+
+pub fn main() {
+  wobble.wobble(wibble.wibble(1))
+}
+";
+
+    assert_snapshot!(rename_module(
+        TestProject::for_source(src)
+            .add_module("wibble", "pub fn wibble(x) { x }\n")
+            .add_module("other/wobble", "pub fn wobble(x) { x }\n"),
+        "wibble.gleam",
+        "wobble.gleam",
+    ));
+}
+
+#[test]
+fn rename_module_directory() {
+    let src = "
+import wibble/one
+import wibble/two.{two}
+
+pub fn main() {
+  one.one() + two()
+}
+";
+
+    assert_snapshot!(rename_module(
+        TestProject::for_source(src)
+            .add_module("wibble/one", "pub fn one() { 1 }\n")
+            .add_module(
+                "wibble/two",
+                "import wibble/one
+
+pub fn two() { one.one() + 1 }
+"
+            ),
+        "wibble",
+        "internal/wobble",
+    ));
+}
+
+#[test]
+fn rename_module_to_invalid_name() {
+    let src = "
+import wibble
+
+pub fn main() {
+  wibble.wibble(1)
+}
+";
+
+    assert_snapshot!(rename_module(
+        TestProject::for_source(src).add_module("wibble", "pub fn wibble(x) { x }\n"),
+        "wibble.gleam",
+        "Wobble.gleam",
+    ));
+}
@@ -1,5 +1,7 @@
 use std::time::SystemTime;
 
+use camino::{Utf8Path, Utf8PathBuf};
+
 use crate::{
     Error,
     io::FileSystemWriter,
@@ -99,6 +101,67 @@ fn changing_config_redownloads_dependencies() {
     }
 }
 
+#[test]
+fn workspace_members_share_the_build_directory() {
+    let io = LanguageServerTestIO::new();
+    let mut router = Router::new(io.clone(), FileSystemProxy::new(io.clone()));
+    io.write(
+        Utf8Path::new("/app/gleam.toml"),
+        "[workspace]\nmembers = [\"packages/wibble\", \"packages/wobble\"]",
+    )
+    .expect("write gleam.toml");
+    io.write(
+        Utf8Path::new("/app/packages/wibble/gleam.toml"),
+        "name = \"wibble\"",
+    )
+    .expect("write gleam.toml");
+    io.write(
+        Utf8Path::new("/app/packages/wobble/gleam.toml"),
+        "name = \"wobble\"",
+    )
+    .expect("write gleam.toml");
+
+    let wibble = router
+        .project_for_path("/app/packages/wibble/src/wibble.gleam".into())
+        .expect("find project")
+        .expect("file is in a project");
+    assert_eq!(
+        wibble.engine.paths.root(),
+        Utf8Path::new("/app/packages/wibble")
+    );
+    assert_eq!(
+        wibble.engine.paths.build_directory(),
+        Utf8PathBuf::from("/app/build")
+    );
+
+    let wobble = router
+        .project_for_path("/app/packages/wobble/test/wobble_test.gleam".into())
+        .expect("find project")
+        .expect("file is in a project");
+    assert_eq!(
+        wobble.engine.paths.manifest(),
+        Utf8PathBuf::from("/app/manifest.toml")
+    );
+}
+
+#[test]
+fn workspace_root_is_not_a_project() {
+    let io = LanguageServerTestIO::new();
+    let mut router = Router::new(io.clone(), FileSystemProxy::new(io.clone()));
+    io.write(
+        Utf8Path::new("/app/gleam.toml"),
+        "[workspace]\nmembers = [\"packages/wibble\"]",
+    )
+    .expect("write gleam.toml");
+
+    assert!(
+        router
+            .project_for_path("/app/gleam.toml".into())
+            .expect("find project")
+            .is_none()
+    );
+}
+
 fn compile(router: &mut Router, paths: &ProjectPaths) -> Result<(), Error> {
     router
         .project_for_path(paths.root().into())
@@ -0,0 +1,120 @@
+use insta::assert_snapshot;
+use lsp_types::{
+    PartialResultParams, SelectionRange, SelectionRangeParams, WorkDoneProgressParams,
+};
+
+use crate::language_server::lsp_range_to_src_span;
+
+use super::*;
+
+/// Prints the code each selection range covers, from the innermost to the
+/// outermost one.
+///
+fn selection_ranges(tester: TestProject<'_>, position: PositionFinder) -> String {
+    let position = position.find_position(tester.src);
+    let ranges = tester.at(position, |engine, params, _| {
+        let params = SelectionRangeParams {
+            text_document: params.text_document,
+            positions: vec![position],
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        engine
+            .selection_range(params)
+            .result
+            .expect("selection range")
+            .expect("selection ranges")
+    });
+
+    let line_numbers = LineNumbers::new(tester.src);
+    let mut output = String::new();
+    let mut range: Option<&SelectionRange> = ranges.first();
+    while let Some(selection) = range {
+        let location = lsp_range_to_src_span(selection.range, &line_numbers);
+        let code = tester
+            .src
+            .get(location.start as usize..location.end as usize)
+            .expect("range in code");
+        output.push_str(&format!("-- {code}\n"));
+        range = selection.parent.as_deref();
+    }
+    output
+}
+
+#[test]
+fn select_expression_in_function() {
+    let src = "
+pub fn main() {
+  let x = wibble(1 + 2, 3)
+  x
+}
+
+fn wibble(a, b) { a + b }
+";
+
+    assert_snapshot!(selection_ranges(
+        TestProject::for_source(src),
+        find_position_of("2,"),
+    ));
+}
+
+#[test]
+fn select_pattern_in_case_clause() {
+    let src = "
+pub fn main(x) {
+  case x {
+    [first, ..] -> first
+    [] -> 0
+  }
+}
+";
+
+    assert_snapshot!(selection_ranges(
+        TestProject::for_source(src),
+        find_position_of("first,"),
+    ));
+}
+
+#[test]
+fn select_argument_annotation() {
+    let src = "
+pub fn main(wibble: List(Int)) -> Int {
+  1
+}
+";
+
+    assert_snapshot!(selection_ranges(
+        TestProject::for_source(src),
+        find_position_of("Int)"),
+    ));
+}
+
+#[test]
+fn select_custom_type_field() {
+    let src = "
+pub type Wibble {
+  Wibble(name: String, age: Int)
+  Wobble
+}
+";
+
+    assert_snapshot!(selection_ranges(
+        TestProject::for_source(src),
+        find_position_of("age"),
+    ));
+}
+
+#[test]
+fn select_outside_definitions() {
+    let src = "
+// The entrypoint.
+pub fn main() {
+  1
+}
+";
+
+    assert_snapshot!(selection_ranges(
+        TestProject::for_source(src),
+        find_position_of("entrypoint"),
+    ));
+}
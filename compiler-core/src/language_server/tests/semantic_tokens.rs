@@ -0,0 +1,263 @@
+use insta::assert_snapshot;
+use lsp_types::{SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult};
+
+use crate::language_server::semantic_tokens::legend;
+
+use super::*;
+
+/// Prints the tokens alongside the code they highlight, one per line.
+///
+fn show_tokens(src: &str, tokens: &[lsp_types::SemanticToken]) -> String {
+    let legend = legend();
+    let lines = src.lines().collect_vec();
+    let mut line = 0;
+    let mut column = 0;
+    let mut output = format!("{src}\n----- TOKENS\n");
+
+    for token in tokens {
+        if token.delta_line == 0 {
+            column += token.delta_start;
+        } else {
+            line += token.delta_line;
+            column = token.delta_start;
+        }
+        let text = lines
+            .get(line as usize)
+            .and_then(|text| text.get(column as usize..(column + token.length) as usize))
+            .expect("token in source");
+        let type_ = legend
+            .token_types
+            .get(token.token_type as usize)
+            .expect("token type in legend");
+        let modifiers = legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| token.token_modifiers_bitset & (1 << bit) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+            .join(", ");
+        output.push_str(&format!(
+            "{}:{} {text} {} [{modifiers}]\n",
+            line + 1,
+            column + 1,
+            type_.as_str(),
+        ));
+    }
+    output
+}
+
+fn semantic_tokens(tester: TestProject<'_>) -> String {
+    let tokens = tester.at(Position::default(), |engine, params, _| {
+        let params = SemanticTokensParams {
+            text_document: params.text_document,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        match engine
+            .semantic_tokens_full(params)
+            .result
+            .expect("semantic tokens")
+        {
+            Some(lsp_types::SemanticTokensResult::Tokens(tokens)) => tokens.data,
+            Some(lsp_types::SemanticTokensResult::Partial(_)) | None => vec![],
+        }
+    });
+    show_tokens(tester.src, &tokens)
+}
+
+fn semantic_tokens_in_range(tester: TestProject<'_>, range: RangeSelector) -> String {
+    let tokens = tester.at(Position::default(), |engine, params, src| {
+        let params = SemanticTokensRangeParams {
+            text_document: params.text_document,
+            range: range.find_range(&src),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        match engine
+            .semantic_tokens_range(params)
+            .result
+            .expect("semantic tokens")
+        {
+            Some(SemanticTokensRangeResult::Tokens(tokens)) => tokens.data,
+            Some(SemanticTokensRangeResult::Partial(_)) | None => vec![],
+        }
+    });
+
+    // Tokens in a range are relative to the start of the document, like all
+    // the others.
+    show_tokens(tester.src, &tokens)
+}
+
+#[test]
+fn semantic_tokens_for_definitions() {
+    let src = r#"
+pub type Wibble(a) {
+  Wibble(label: a)
+  Wobble
+}
+
+pub type Alias(a) = Wibble(a)
+
+pub const wubble: Wibble(Int) = Wobble
+
+pub fn wabble(first: Int, with second: Wibble(a)) -> Alias(a) {
+  second
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_tell_local_variables_from_constants() {
+    let src = r#"
+const wibble = 1
+
+pub fn main() {
+  let wobble = wibble
+  wobble + wibble
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_for_imported_values() {
+    let src = r#"
+import other.{Wibble, wobble as wubble}
+
+pub fn main() {
+  #(Wibble, other.Wobble, wubble(), other.wobble(), other.wabble)
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src).add_module(
+        "other",
+        "pub type Wibble { Wibble Wobble }
+pub fn wobble() { 1 }
+pub const wabble = 2"
+    )));
+}
+
+#[test]
+fn semantic_tokens_for_deprecated_items() {
+    let src = r#"
+import other
+
+@deprecated("Use wobble")
+pub fn wibble() { 1 }
+
+pub fn main() -> other.Wibble {
+  wibble()
+  other.wobble()
+  other.Wibble
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src).add_module(
+        "other",
+        r#"@deprecated("Use something else")
+pub type Wibble { Wibble }
+
+@deprecated("Use something else")
+pub fn wobble() { 1 }"#
+    )));
+}
+
+#[test]
+fn semantic_tokens_for_shadowed_variables() {
+    let src = r#"
+pub fn main(wibble) {
+  let wibble = wibble + 1
+  let wobble = 1
+  case wibble {
+    wobble if wobble > 1 -> wobble
+    _ -> wibble
+  }
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_for_alternative_patterns() {
+    let src = r#"
+pub fn main(x) {
+  case x {
+    Ok(wibble) | Error(wibble) -> wibble
+  }
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_for_parameters_and_labels() {
+    let src = r#"
+pub type Wibble {
+  Wibble(wobble: Int)
+}
+
+pub fn wubble(wabble wabble: Int) -> Wibble {
+  let record = Wibble(wobble: wabble)
+  let Wibble(wobble:) = record
+  wubble(wabble: wobble + record.wobble)
+  |> fn(x) { x }
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_for_external_functions() {
+    let src = r#"
+@external(erlang, "wibble", "wobble")
+pub fn wibble() -> Int
+
+pub fn main() {
+  wibble()
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_for_use() {
+    let src = r#"
+pub fn main() {
+  use wibble <- apply(1)
+  use #(wobble, _) <- apply(#(wibble, 2))
+  wobble
+}
+
+fn apply(value, callback) {
+  callback(value)
+}
+"#;
+
+    assert_snapshot!(semantic_tokens(TestProject::for_source(src)));
+}
+
+#[test]
+fn semantic_tokens_in_a_range() {
+    let src = r#"
+pub fn wibble() { 1 }
+
+pub fn wobble() {
+  wibble()
+}
+
+pub fn wubble() { 1 }
+"#;
+
+    assert_snapshot!(semantic_tokens_in_range(
+        TestProject::for_source(src),
+        find_position_of("pub fn wobble")
+            .select_until(find_position_of("}").nth_occurrence(2).under_char('}')),
+    ));
+}
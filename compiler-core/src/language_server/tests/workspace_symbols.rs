@@ -0,0 +1,151 @@
+use insta::assert_snapshot;
+use lsp_types::{OneOf, SymbolTag, WorkspaceSymbol};
+
+use super::*;
+use crate::language_server::workspace_symbol::MAXIMUM_WORKSPACE_SYMBOLS;
+
+fn workspace_symbols(tester: TestProject<'_>, query: &str) -> String {
+    let symbols: Vec<WorkspaceSymbol> = tester.at(Position::default(), |engine, _, _| {
+        engine
+            .workspace_symbol(query)
+            .result
+            .expect("workspace symbols")
+    });
+
+    symbols
+        .into_iter()
+        .map(|symbol| {
+            let OneOf::Left(location) = symbol.location else {
+                panic!("symbol without a range");
+            };
+            let deprecated = if symbol
+                .tags
+                .is_some_and(|tags| tags.contains(&SymbolTag::DEPRECATED))
+            {
+                " deprecated"
+            } else {
+                ""
+            };
+            format!(
+                "{} {:?} in {} at {}:{}{deprecated}",
+                symbol.name,
+                symbol.kind,
+                symbol.container_name.unwrap_or_default(),
+                location.range.start.line,
+                location.range.start.character,
+            )
+        })
+        .join("\n")
+}
+
+#[test]
+fn workspace_symbols_of_all_kinds() {
+    let code = "
+pub fn wibble() { 1 }
+fn wobble() { 2 }
+pub const wubble = 3
+pub type Wabble {
+  Wabble(Int)
+  Wobble
+}
+type Alias = Int
+";
+
+    assert_snapshot!(workspace_symbols(TestProject::for_source(code), ""));
+}
+
+#[test]
+fn workspace_symbols_are_fuzzy_matched() {
+    let code = "
+pub fn map() { 1 }
+pub fn map_error() { 1 }
+pub fn flat_map() { 1 }
+pub fn fold_map() { 1 }
+pub fn filter() { 1 }
+";
+
+    assert_snapshot!(workspace_symbols(TestProject::for_source(code), "map"));
+}
+
+#[test]
+fn workspace_symbols_fuzzy_match_out_of_order_characters() {
+    let code = "
+pub fn flat_map() { 1 }
+pub fn fold() { 1 }
+pub fn from_list() { 1 }
+";
+
+    assert_snapshot!(workspace_symbols(TestProject::for_source(code), "fm"));
+}
+
+#[test]
+fn workspace_symbols_include_other_modules() {
+    let code = "
+pub fn wibble() { 1 }
+";
+
+    assert_snapshot!(workspace_symbols(
+        TestProject::for_source(code).add_module("app/other", "fn wibble_private() { 1 }"),
+        "wibble"
+    ));
+}
+
+#[test]
+fn workspace_symbols_include_public_definitions_of_dependencies() {
+    let code = "
+pub fn wibble() { 1 }
+";
+    let dep = "
+pub fn wibble_dep() { 1 }
+fn wibble_private() { 1 }
+pub type WibbleType
+type WibblePrivateType
+";
+
+    assert_snapshot!(workspace_symbols(
+        TestProject::for_source(code)
+            .add_dep_module("dep", dep)
+            .add_hex_module("hex", "pub const wibble_hex = 1"),
+        "wibble"
+    ));
+}
+
+#[test]
+fn workspace_symbols_are_tagged_deprecated() {
+    let code = r#"
+@deprecated("Use wobble")
+pub fn wibble() { 1 }
+
+@deprecated("Use Wobble")
+pub type Wibble
+
+pub type Wobble {
+  @deprecated("Use Wobble")
+  Wubble
+  Wobble
+}
+
+@deprecated("Use wobble")
+pub const wibble_constant = 1
+
+pub fn wobble() { 1 }
+"#;
+
+    assert_snapshot!(workspace_symbols(TestProject::for_source(code), "w"));
+}
+
+#[test]
+fn workspace_symbols_are_limited_to_the_closest_matches() {
+    let code = (0..MAXIMUM_WORKSPACE_SYMBOLS)
+        .map(|index| format!("pub fn wibble_{index}() {{ 1 }}\n"))
+        .join("")
+        + "pub fn wibble() { 1 }\n";
+
+    let symbols = workspace_symbols(TestProject::for_source(&code), "wibble");
+    let names = symbols
+        .lines()
+        .map(|line| line.split(' ').next())
+        .collect_vec();
+    assert_eq!(names.len(), MAXIMUM_WORKSPACE_SYMBOLS);
+    assert_eq!(names.first(), Some(&Some("wibble")));
+}
@@ -0,0 +1,239 @@
+use std::collections::{HashMap, HashSet};
+
+use ecow::EcoString;
+use itertools::Itertools;
+use lsp_types::{self as lsp, OneOf, SymbolKind, SymbolTag, WorkspaceSymbol};
+
+use crate::{
+    ast::Publicity,
+    type_::{Deprecation, ModuleInterface, ValueConstructorVariant},
+};
+
+use super::{compiler::ModuleSourceInformation, src_span_to_lsp_range, url_from_path};
+
+/// The most symbols returned for a query. A short query matches every
+/// definition of the project and of its dependencies, so only the closest
+/// matches are returned. The response has no way to say it's incomplete, but
+/// editors search again as the query is typed, and a longer query narrows the
+/// results down to the symbol the programmer is looking for.
+///
+pub const MAXIMUM_WORKSPACE_SYMBOLS: usize = 128;
+
+/// Finds the top level definitions of all the given modules whose name
+/// matches the query. The definitions of the root package are all included,
+/// while only the public ones of its dependencies are, since those are the
+/// only ones that can be used.
+///
+/// The results are sorted so that the closest matches come first, and only
+/// the first `MAXIMUM_WORKSPACE_SYMBOLS` are kept.
+///
+pub fn workspace_symbols(
+    query: &str,
+    root_package: &str,
+    modules: &im::HashMap<EcoString, ModuleInterface>,
+    sources: &HashMap<EcoString, ModuleSourceInformation>,
+) -> Vec<WorkspaceSymbol> {
+    let mut symbols = vec![];
+
+    for module in modules.values() {
+        let Some(source) = sources.get(&module.name) else {
+            continue;
+        };
+        let Some(uri) = url_from_path(&source.path) else {
+            continue;
+        };
+        let is_visible =
+            |publicity: &Publicity| module.package == root_package || publicity.is_importable();
+
+        let mut add_symbol = |name: &EcoString, kind, location, deprecation: &Deprecation| {
+            if fuzzy_match(query, name).is_none() {
+                return;
+            }
+            let symbol = WorkspaceSymbol {
+                name: name.to_string(),
+                kind,
+                tags: deprecation
+                    .is_deprecated()
+                    .then(|| vec![SymbolTag::DEPRECATED]),
+                container_name: Some(module.name.to_string()),
+                location: OneOf::Left(lsp::Location {
+                    uri: uri.clone(),
+                    range: src_span_to_lsp_range(location, &source.line_numbers),
+                }),
+                data: None,
+            };
+            symbols.push(symbol);
+        };
+
+        for (name, value) in &module.values {
+            if !is_visible(&value.publicity) {
+                continue;
+            }
+            let (kind, location, defining_module) = match &value.variant {
+                ValueConstructorVariant::ModuleFn {
+                    location, module, ..
+                } => (SymbolKind::FUNCTION, *location, module),
+                ValueConstructorVariant::ModuleConstant {
+                    location, module, ..
+                } => (SymbolKind::CONSTANT, *location, module),
+                ValueConstructorVariant::Record {
+                    location,
+                    module,
+                    arity: 0,
+                    ..
+                } => (SymbolKind::ENUM_MEMBER, *location, module),
+                ValueConstructorVariant::Record {
+                    location, module, ..
+                } => (SymbolKind::CONSTRUCTOR, *location, module),
+                ValueConstructorVariant::LocalVariable { .. }
+                | ValueConstructorVariant::LocalConstant { .. } => continue,
+            };
+            // Values which are imported into the module are defined elsewhere
+            if *defining_module != module.name {
+                continue;
+            }
+            add_symbol(name, kind, location, &value.deprecation);
+        }
+
+        for (name, type_) in &module.types {
+            // Type aliases are also registered as types, but they are
+            // reported separately below
+            if module.type_aliases.contains_key(name) || !is_visible(&type_.publicity) {
+                continue;
+            }
+            add_symbol(name, SymbolKind::CLASS, type_.origin, &type_.deprecation);
+        }
+
+        for (name, alias) in &module.type_aliases {
+            if !is_visible(&alias.publicity) {
+                continue;
+            }
+            add_symbol(name, SymbolKind::CLASS, alias.origin, &alias.deprecation);
+        }
+    }
+
+    sorted(query, symbols)
+}
+
+/// Merges the symbols found in each of the projects open in the editor,
+/// sorting them so that the closest matches come first and keeping the first
+/// `MAXIMUM_WORKSPACE_SYMBOLS`. The members of a
+/// workspace share their dependencies, so the symbols found in more than one
+/// project are only kept once.
+///
+pub fn merge_workspace_symbols(query: &str, symbols: Vec<WorkspaceSymbol>) -> Vec<WorkspaceSymbol> {
+    let mut seen = HashSet::new();
+    let unique = symbols
+        .into_iter()
+        .filter(|symbol| {
+            let location = match &symbol.location {
+                OneOf::Left(location) => Some(location.clone()),
+                OneOf::Right(_) => None,
+            };
+            seen.insert((symbol.name.clone(), symbol.container_name.clone(), location))
+        })
+        .collect_vec();
+    sorted(query, unique)
+}
+
+fn sorted(query: &str, symbols: Vec<WorkspaceSymbol>) -> Vec<WorkspaceSymbol> {
+    symbols
+        .into_iter()
+        .map(|symbol| (fuzzy_match(query, &symbol.name), symbol))
+        .sorted_by(|(one_score, one), (other_score, other)| {
+            one_score
+                .cmp(other_score)
+                .then_with(|| one.name.cmp(&other.name))
+                .then_with(|| one.container_name.cmp(&other.container_name))
+                .then_with(|| location_start(one).cmp(&location_start(other)))
+        })
+        .map(|(_, symbol)| symbol)
+        .take(MAXIMUM_WORKSPACE_SYMBOLS)
+        .collect_vec()
+}
+
+fn location_start(symbol: &WorkspaceSymbol) -> Option<lsp::Position> {
+    match &symbol.location {
+        OneOf::Left(location) => Some(location.range.start),
+        OneOf::Right(_) => None,
+    }
+}
+
+/// How closely a name matches a query, the lower the score the closer the
+/// match. Names match if they contain all the characters of the query in the
+/// same order, ignoring case, so `lmap` matches `list_map`.
+///
+/// Returns `None` if the name doesn't match the query at all.
+///
+pub fn fuzzy_match(query: &str, name: &str) -> Option<u8> {
+    let query = query.to_lowercase();
+    let name = name.to_lowercase();
+
+    if name == query {
+        return Some(0);
+    }
+    if name.starts_with(&query) {
+        return Some(1);
+    }
+    if name.contains(&query) {
+        return Some(2);
+    }
+
+    let mut name_chars = name.chars();
+    query
+        .chars()
+        .all(|query_char| name_chars.any(|name_char| name_char == query_char))
+        .then_some(3)
+}
+
+#[test]
+fn fuzzy_match_test() {
+    assert_eq!(fuzzy_match("map", "map"), Some(0));
+    assert_eq!(fuzzy_match("Map", "map_error"), Some(1));
+    assert_eq!(fuzzy_match("map", "flat_map"), Some(2));
+    assert_eq!(fuzzy_match("fm", "flat_map"), Some(3));
+    assert_eq!(fuzzy_match("", "anything"), Some(1));
+    assert_eq!(fuzzy_match("mf", "flat_map"), None);
+    assert_eq!(fuzzy_match("maps", "map"), None);
+}
+
+#[test]
+fn merge_workspace_symbols_test() {
+    let symbol = |name: &str, module: &str| WorkspaceSymbol {
+        name: name.into(),
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        container_name: Some(module.into()),
+        location: OneOf::Left(lsp::Location {
+            uri: url_from_path("/src/wibble.gleam").expect("url"),
+            range: lsp::Range::default(),
+        }),
+        data: None,
+    };
+    let merged = merge_workspace_symbols(
+        "map",
+        vec![
+            symbol("flat_map", "list"),
+            symbol("map_error", "result"),
+            symbol("map", "list"),
+            symbol("flat_map", "list"),
+            symbol("map", "option"),
+        ],
+    );
+    assert_eq!(
+        merged
+            .iter()
+            .map(|symbol| format!(
+                "{}.{}",
+                symbol.container_name.as_deref().unwrap_or(""),
+                symbol.name
+            ))
+            .collect_vec(),
+        vec![
+            "list.map",
+            "option.map",
+            "result.map_error",
+            "list.flat_map"
+        ]
+    );
+}
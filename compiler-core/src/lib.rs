@@ -63,6 +63,7 @@ pub mod bit_array;
 pub mod build;
 pub mod codegen;
 pub mod config;
+pub mod coverage;
 pub mod dependency;
 pub mod diagnostic;
 pub mod docs;
@@ -82,8 +83,10 @@ pub mod package_interface;
 pub mod parse;
 pub mod paths;
 pub mod pretty;
+pub mod rename_module;
 pub mod requirement;
 pub mod strings;
+pub mod test_runner;
 pub mod type_;
 pub mod uid;
 pub mod version;
@@ -95,6 +98,7 @@ mod dep_tree;
 pub(crate) mod derivation_tree;
 mod exhaustiveness;
 pub(crate) mod graph;
+mod parallel;
 mod reference;
 
 pub use error::{Error, Result};
@@ -264,7 +264,7 @@ pub fn main() {
 }
 
 /// A 1-index line and column position
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
 pub struct LineColumn {
     pub line: u32,
     pub column: u32,
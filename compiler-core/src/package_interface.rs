@@ -1,8 +1,9 @@
 use std::{collections::HashMap, ops::Deref};
 
 use ecow::EcoString;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
+pub mod diff;
 #[cfg(test)]
 mod tests;
 
@@ -17,7 +18,7 @@ use crate::{
 use crate::build::Package;
 
 /// The public interface of a package that gets serialised as a json object.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct PackageInterface {
     name: EcoString,
@@ -29,7 +30,7 @@ pub struct PackageInterface {
     modules: HashMap<EcoString, ModuleInterface>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct ModuleInterface {
     /// A vector with the lines composing the module's documentation (that is
@@ -49,7 +50,7 @@ pub struct ModuleInterface {
     functions: HashMap<EcoString, FunctionInterface>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct TypeDefinitionInterface {
     /// The definition's documentation comment (that is every line preceded by
@@ -72,7 +73,7 @@ pub struct TypeDefinitionInterface {
     constructors: Vec<TypeConstructorInterface>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct TypeConstructorInterface {
     /// The constructor's documentation comment (that is every line preceded by
@@ -96,7 +97,7 @@ pub struct TypeConstructorInterface {
     parameters: Vec<ParameterInterface>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct TypeAliasInterface {
     /// The constructor's documentation comment (that is every line preceded by
@@ -119,7 +120,7 @@ pub struct TypeAliasInterface {
     alias: TypeInterface,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct ConstantInterface {
     /// The constant's documentation comment (that is every line preceded by
@@ -136,7 +137,7 @@ pub struct ConstantInterface {
 
 /// A module's function. This differs from a simple `Fn` type as its arguments
 /// can be labelled.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct FunctionInterface {
     /// The function's documentation comment (that is every line preceded by
@@ -152,7 +153,7 @@ pub struct FunctionInterface {
 }
 
 /// Informations about how a value is implemented.
-#[derive(Debug, Serialize, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct ImplementationsInterface {
     /// Set to `true` if the const/function has a pure Gleam implementation
@@ -274,7 +275,7 @@ impl ImplementationsInterface {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct DeprecationInterface {
     /// The reason for the deprecation.
@@ -292,7 +293,7 @@ impl DeprecationInterface {
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(tag = "kind")]
 #[serde(rename_all = "kebab-case")]
 pub enum TypeInterface {
@@ -340,7 +341,7 @@ pub enum TypeInterface {
     },
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub struct ParameterInterface {
     /// If the parameter is labelled this will hold the label's name.
@@ -386,6 +387,14 @@ impl PackageInterface {
                 .collect(),
         }
     }
+
+    pub fn name(&self) -> &EcoString {
+        &self.name
+    }
+
+    pub fn version(&self) -> &EcoString {
+        &self.version
+    }
 }
 
 impl ModuleInterface {
@@ -574,12 +583,7 @@ fn from_type_helper(type_: &Type, id_map: &mut IdMap) -> TypeInterface {
                 .collect(),
         },
 
-        Type::Var { type_ } => match type_
-            .as_ref()
-            .try_borrow()
-            .expect("borrow type after inference")
-            .deref()
-        {
+        Type::Var { type_ } => match type_.as_ref().borrow().deref() {
             TypeVar::Link { type_ } => from_type_helper(type_, id_map),
             // Since package serialisation happens after inference there
             // should be no unbound type variables.
@@ -662,12 +666,7 @@ impl IdMap {
             // These types have no id to add to the map.
             Type::Named { .. } | Type::Fn { .. } | Type::Tuple { .. } => (),
             // If the type is actually a type variable whose id needs to be mapped.
-            Type::Var { type_ } => match type_
-                .as_ref()
-                .try_borrow()
-                .expect("borrow type after inference")
-                .deref()
-            {
+            Type::Var { type_ } => match type_.as_ref().borrow().deref() {
                 TypeVar::Link { .. } => (),
                 TypeVar::Unbound { id } | TypeVar::Generic { id } => {
                     let _ = self.map_id(*id);
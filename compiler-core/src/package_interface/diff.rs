@@ -0,0 +1,546 @@
+//! Compares the public interfaces of two versions of a package, classifying
+//! each change by the version bump semantic versioning requires for it.
+//!
+//! The classification errs on the side of caution: any change to the type of
+//! a public value is breaking, even if some code using it would still
+//! compile, and so is adding a constructor to a type as it can make a case
+//! expression on that type inexhaustive.
+
+#[cfg(test)]
+mod tests;
+
+use std::{collections::HashMap, fmt::Display, fmt::Write};
+
+use ecow::EcoString;
+use hexpm::version::Version;
+use itertools::Itertools;
+
+use super::{
+    DeprecationInterface, ImplementationsInterface, ModuleInterface, PackageInterface,
+    ParameterInterface, TypeDefinitionInterface, TypeInterface,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeKind {
+    /// A change that does not affect code using the package, such as a change
+    /// to the documentation.
+    Patch,
+    /// A backward compatible change, such as a new function.
+    Additive,
+    /// A change that can break code using the package, such as a removed
+    /// function.
+    Breaking,
+}
+
+impl Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChangeKind::Patch => "patch",
+            ChangeKind::Additive => "additive",
+            ChangeKind::Breaking => "breaking",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub description: String,
+}
+
+/// The changes between a previous and the current interface of a package, in
+/// the order of the modules and definitions they are about.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceDiff {
+    pub changes: Vec<Change>,
+}
+
+impl InterfaceDiff {
+    pub fn new(previous: &PackageInterface, current: &PackageInterface) -> Self {
+        let mut diff = Self::default();
+        if previous.gleam_version_constraint != current.gleam_version_constraint {
+            diff.push(
+                ChangeKind::Patch,
+                format!(
+                    "The Gleam version constraint changed from `{}` to `{}`",
+                    previous
+                        .gleam_version_constraint
+                        .as_deref()
+                        .unwrap_or("any"),
+                    current.gleam_version_constraint.as_deref().unwrap_or("any"),
+                ),
+            );
+        }
+        diff.compare_maps(
+            &previous.modules,
+            &current.modules,
+            |name| format!("Module `{name}`"),
+            |diff, name, previous, current| diff.compare_modules(name, previous, current),
+        );
+        diff
+    }
+
+    /// The kind of the most significant change, or `None` if nothing changed.
+    pub fn kind(&self) -> Option<ChangeKind> {
+        self.changes.iter().map(|change| change.kind).max()
+    }
+
+    /// The lowest version the package can be published as after the
+    /// `previous` one with these changes.
+    ///
+    /// Before 1.0.0 the minor version takes the place of the major one, so a
+    /// breaking change needs a new minor version and an additive change a new
+    /// patch version.
+    ///
+    /// This is `None` if any later version would do, as for patch changes or
+    /// after a pre-release version, for which semantic versioning makes no
+    /// promises.
+    ///
+    pub fn minimum_version(&self, previous: &Version) -> Option<Version> {
+        if previous.is_pre() {
+            return None;
+        }
+        match (self.kind()?, previous.major) {
+            (ChangeKind::Breaking, 0) => Some(Version::new(0, previous.minor + 1, 0)),
+            (ChangeKind::Additive, 0) => Some(Version::new(0, previous.minor, previous.patch + 1)),
+            (ChangeKind::Breaking, major) => Some(Version::new(major + 1, 0, 0)),
+            (ChangeKind::Additive, major) => Some(Version::new(major, previous.minor + 1, 0)),
+            (ChangeKind::Patch, _) => None,
+        }
+    }
+
+    /// The changes grouped by kind, the most significant first.
+    pub fn render(&self) -> String {
+        if self.changes.is_empty() {
+            return "No changes to the public API.\n".into();
+        }
+        let mut text = String::new();
+        for kind in [
+            ChangeKind::Breaking,
+            ChangeKind::Additive,
+            ChangeKind::Patch,
+        ] {
+            let changes = self
+                .changes
+                .iter()
+                .filter(|change| change.kind == kind)
+                .collect_vec();
+            if changes.is_empty() {
+                continue;
+            }
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            writeln!(text, "{} changes:", capitalise(&kind.to_string())).expect("write to string");
+            for change in changes {
+                writeln!(text, "  - {}", change.description).expect("write to string");
+            }
+        }
+        text
+    }
+
+    fn push(&mut self, kind: ChangeKind, description: String) {
+        self.changes.push(Change { kind, description });
+    }
+
+    /// Compares the definitions found in both maps, a removed one being a
+    /// breaking change and a new one an additive change.
+    fn compare_maps<T>(
+        &mut self,
+        previous: &HashMap<EcoString, T>,
+        current: &HashMap<EcoString, T>,
+        describe: impl Fn(&EcoString) -> String,
+        mut compare: impl FnMut(&mut Self, &EcoString, &T, &T),
+    ) {
+        let names = previous.keys().chain(current.keys()).unique().sorted();
+        for name in names {
+            match (previous.get(name), current.get(name)) {
+                (Some(previous), Some(current)) => compare(self, name, previous, current),
+                (Some(_), None) => self.push(
+                    ChangeKind::Breaking,
+                    format!("{} was removed", describe(name)),
+                ),
+                (None, Some(_)) => self.push(
+                    ChangeKind::Additive,
+                    format!("{} was added", describe(name)),
+                ),
+                (None, None) => (),
+            }
+        }
+    }
+
+    fn compare_modules(
+        &mut self,
+        module: &str,
+        previous: &ModuleInterface,
+        current: &ModuleInterface,
+    ) {
+        if previous.documentation != current.documentation {
+            self.push(
+                ChangeKind::Patch,
+                format!("The documentation of module `{module}` changed"),
+            );
+        }
+
+        self.compare_maps(
+            &previous.types,
+            &current.types,
+            |name| format!("Type `{module}.{name}`"),
+            |diff, name, previous, current| {
+                diff.compare_types(&format!("{module}.{name}"), previous, current)
+            },
+        );
+
+        self.compare_maps(
+            &previous.type_aliases,
+            &current.type_aliases,
+            |name| format!("Type alias `{module}.{name}`"),
+            |diff, name, previous, current| {
+                let item = format!("{module}.{name}");
+                diff.compare_documentation(&item, &previous.documentation, &current.documentation);
+                diff.compare_deprecation(&item, &previous.deprecation, &current.deprecation);
+                diff.compare_type_parameters(&item, previous.parameters, current.parameters);
+                if previous.alias != current.alias {
+                    diff.push(
+                        ChangeKind::Breaking,
+                        format!(
+                            "Type alias `{item}` changed from `{}` to `{}`",
+                            type_to_string(&previous.alias),
+                            type_to_string(&current.alias)
+                        ),
+                    );
+                }
+            },
+        );
+
+        self.compare_maps(
+            &previous.constants,
+            &current.constants,
+            |name| format!("Constant `{module}.{name}`"),
+            |diff, name, previous, current| {
+                let item = format!("{module}.{name}");
+                diff.compare_documentation(&item, &previous.documentation, &current.documentation);
+                diff.compare_deprecation(&item, &previous.deprecation, &current.deprecation);
+                diff.compare_implementations(
+                    &item,
+                    &previous.implementations,
+                    &current.implementations,
+                );
+                if previous.type_ != current.type_ {
+                    diff.push(
+                        ChangeKind::Breaking,
+                        format!(
+                            "The type of `{item}` changed from `{}` to `{}`",
+                            type_to_string(&previous.type_),
+                            type_to_string(&current.type_)
+                        ),
+                    );
+                }
+            },
+        );
+
+        self.compare_maps(
+            &previous.functions,
+            &current.functions,
+            |name| format!("Function `{module}.{name}`"),
+            |diff, name, previous, current| {
+                let item = format!("{module}.{name}");
+                diff.compare_documentation(&item, &previous.documentation, &current.documentation);
+                diff.compare_deprecation(&item, &previous.deprecation, &current.deprecation);
+                diff.compare_implementations(
+                    &item,
+                    &previous.implementations,
+                    &current.implementations,
+                );
+                diff.compare_parameters(
+                    &item,
+                    "argument",
+                    &previous.parameters,
+                    &current.parameters,
+                );
+                if previous.return_ != current.return_ {
+                    diff.push(
+                        ChangeKind::Breaking,
+                        format!(
+                            "The return type of `{item}` changed from `{}` to `{}`",
+                            type_to_string(&previous.return_),
+                            type_to_string(&current.return_)
+                        ),
+                    );
+                }
+            },
+        );
+    }
+
+    fn compare_types(
+        &mut self,
+        item: &str,
+        previous: &TypeDefinitionInterface,
+        current: &TypeDefinitionInterface,
+    ) {
+        self.compare_documentation(item, &previous.documentation, &current.documentation);
+        self.compare_deprecation(item, &previous.deprecation, &current.deprecation);
+        self.compare_type_parameters(item, previous.parameters, current.parameters);
+
+        // Opaque and external types have no constructors in the interface, so
+        // making a type opaque looks like removing all of its constructors.
+        match (
+            previous.constructors.is_empty(),
+            current.constructors.is_empty(),
+        ) {
+            (true, true) => return,
+            (false, true) => {
+                self.push(
+                    ChangeKind::Breaking,
+                    format!("The constructors of type `{item}` are no longer public"),
+                );
+                return;
+            }
+            (true, false) => {
+                self.push(
+                    ChangeKind::Additive,
+                    format!("The constructors of type `{item}` are now public"),
+                );
+                return;
+            }
+            (false, false) => (),
+        }
+
+        let module = item.rsplit_once('.').map_or(item, |(module, _)| module);
+        for constructor in &previous.constructors {
+            let constructor_item = format!("{module}.{}", constructor.name);
+            match current
+                .constructors
+                .iter()
+                .find(|current| current.name == constructor.name)
+            {
+                None => self.push(
+                    ChangeKind::Breaking,
+                    format!("Constructor `{constructor_item}` of type `{item}` was removed"),
+                ),
+                Some(current) => {
+                    self.compare_documentation(
+                        &constructor_item,
+                        &constructor.documentation,
+                        &current.documentation,
+                    );
+                    self.compare_parameters(
+                        &constructor_item,
+                        "field",
+                        &constructor.parameters,
+                        &current.parameters,
+                    );
+                }
+            }
+        }
+        for constructor in &current.constructors {
+            if !previous
+                .constructors
+                .iter()
+                .any(|previous| previous.name == constructor.name)
+            {
+                // A new constructor makes any case expression on the type that
+                // matches each constructor inexhaustive.
+                self.push(
+                    ChangeKind::Breaking,
+                    format!(
+                        "Constructor `{module}.{}` was added to type `{item}`",
+                        constructor.name
+                    ),
+                );
+            }
+        }
+    }
+
+    fn compare_documentation(
+        &mut self,
+        item: &str,
+        previous: &Option<EcoString>,
+        current: &Option<EcoString>,
+    ) {
+        if previous != current {
+            self.push(
+                ChangeKind::Patch,
+                format!("The documentation of `{item}` changed"),
+            );
+        }
+    }
+
+    fn compare_deprecation(
+        &mut self,
+        item: &str,
+        previous: &Option<DeprecationInterface>,
+        current: &Option<DeprecationInterface>,
+    ) {
+        match (previous, current) {
+            (None, Some(_)) => self.push(ChangeKind::Additive, format!("`{item}` was deprecated")),
+            (Some(_), None) => self.push(
+                ChangeKind::Patch,
+                format!("`{item}` is no longer deprecated"),
+            ),
+            (Some(previous), Some(current)) if previous != current => self.push(
+                ChangeKind::Patch,
+                format!("The deprecation message of `{item}` changed"),
+            ),
+            (Some(_), Some(_)) | (None, None) => (),
+        }
+    }
+
+    fn compare_type_parameters(&mut self, item: &str, previous: usize, current: usize) {
+        if previous != current {
+            self.push(
+                ChangeKind::Breaking,
+                format!(
+                    "`{item}` now has {} instead of {previous}",
+                    count(current, "type parameter")
+                ),
+            );
+        }
+    }
+
+    fn compare_implementations(
+        &mut self,
+        item: &str,
+        previous: &ImplementationsInterface,
+        current: &ImplementationsInterface,
+    ) {
+        let targets = [
+            (
+                "Erlang",
+                previous.can_run_on_erlang,
+                current.can_run_on_erlang,
+            ),
+            (
+                "JavaScript",
+                previous.can_run_on_javascript,
+                current.can_run_on_javascript,
+            ),
+        ];
+        for (target, previous, current) in targets {
+            match (previous, current) {
+                (true, false) => self.push(
+                    ChangeKind::Breaking,
+                    format!("`{item}` can no longer be used on {target}"),
+                ),
+                (false, true) => self.push(
+                    ChangeKind::Additive,
+                    format!("`{item}` can now be used on {target}"),
+                ),
+                (true, true) | (false, false) => (),
+            }
+        }
+    }
+
+    /// Compares the arguments of a function or the fields of a constructor.
+    /// Labelling one that was not labelled is additive, as code passing it
+    /// positionally still compiles, but any other change is breaking.
+    fn compare_parameters(
+        &mut self,
+        item: &str,
+        noun: &str,
+        previous: &[ParameterInterface],
+        current: &[ParameterInterface],
+    ) {
+        if previous.len() != current.len() {
+            self.push(
+                ChangeKind::Breaking,
+                format!(
+                    "`{item}` now has {} instead of {}",
+                    count(current.len(), noun),
+                    previous.len()
+                ),
+            );
+            return;
+        }
+
+        for (index, (previous, current)) in previous.iter().zip(current).enumerate() {
+            let position = index + 1;
+            if previous.type_ != current.type_ {
+                self.push(
+                    ChangeKind::Breaking,
+                    format!(
+                        "The type of {noun} {position} of `{item}` changed from `{}` to `{}`",
+                        type_to_string(&previous.type_),
+                        type_to_string(&current.type_)
+                    ),
+                );
+            }
+            match (&previous.label, &current.label) {
+                (None, Some(label)) => self.push(
+                    ChangeKind::Additive,
+                    format!("{} {position} of `{item}` is now labelled `{label}`", capitalise(noun)),
+                ),
+                (Some(label), None) => self.push(
+                    ChangeKind::Breaking,
+                    format!(
+                        "{} {position} of `{item}` is no longer labelled `{label}`",
+                        capitalise(noun)
+                    ),
+                ),
+                (Some(previous), Some(current)) if previous != current => self.push(
+                    ChangeKind::Breaking,
+                    format!(
+                        "The label of {noun} {position} of `{item}` changed from `{previous}` to `{current}`"
+                    ),
+                ),
+                (Some(_), Some(_)) | (None, None) => (),
+            }
+        }
+    }
+}
+
+fn count(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{count} {noun}")
+    } else {
+        format!("{count} {noun}s")
+    }
+}
+
+fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Prints a type as it would be written in Gleam, qualifying the types that
+/// are not defined in the prelude with the module defining them.
+fn type_to_string(type_: &TypeInterface) -> String {
+    match type_ {
+        TypeInterface::Tuple { elements } => {
+            format!("#({})", elements.iter().map(type_to_string).join(", "))
+        }
+        TypeInterface::Fn {
+            parameters,
+            return_,
+        } => format!(
+            "fn({}) -> {}",
+            parameters.iter().map(type_to_string).join(", "),
+            type_to_string(return_)
+        ),
+        TypeInterface::Variable { id } if *id < 26 => char::from(b'a' + *id as u8).to_string(),
+        TypeInterface::Variable { id } => format!("t{id}"),
+        TypeInterface::Named {
+            name,
+            module,
+            parameters,
+            ..
+        } => {
+            let name = if module == "gleam" {
+                name.to_string()
+            } else {
+                format!("{module}.{name}")
+            };
+            if parameters.is_empty() {
+                name
+            } else {
+                format!(
+                    "{name}({})",
+                    parameters.iter().map(type_to_string).join(", ")
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use hexpm::version::Version;
+
+use crate::package_interface::{PackageInterface, tests::compile_package};
+
+use super::{ChangeKind, InterfaceDiff};
+
+fn interface(src: &str) -> PackageInterface {
+    serde_json::from_str(&compile_package(None, src, None)).expect("interface deserialises")
+}
+
+fn diff(previous: &str, current: &str) -> InterfaceDiff {
+    InterfaceDiff::new(&interface(previous), &interface(current))
+}
+
+macro_rules! assert_diff {
+    ($previous:expr, $current:expr $(,)?) => {{
+        let output = format!(
+            "----- PREVIOUS\n{}\n----- CURRENT\n{}\n----- CHANGES\n{}",
+            $previous,
+            $current,
+            diff($previous, $current).render()
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output);
+    }};
+}
+
+#[test]
+fn interface_round_trips_through_json() {
+    let src = r#"
+/// A type
+pub type Wibble(a) {
+  Wibble(a, label: Int)
+  Wobble
+}
+
+pub type Wubble = #(Int, fn(String) -> Bool)
+
+@deprecated("Use something else")
+pub const wabble = 1
+
+@external(erlang, "wibble", "wobble")
+pub fn wobble(a: a, b labelled: List(a)) -> Wibble(a)
+"#;
+    let json = compile_package(None, src, None);
+    let interface: PackageInterface = serde_json::from_str(&json).expect("interface deserialises");
+    assert_eq!(
+        serde_json::to_string_pretty(&interface).expect("interface serialises"),
+        json
+    );
+}
+
+#[test]
+fn unchanged_interface() {
+    let src = "pub type Wibble { Wibble(Int) }\npub fn wobble(x: Int) -> Int { x }";
+    let diff = diff(src, src);
+    assert_eq!(diff.kind(), None);
+    assert_eq!(diff.render(), "No changes to the public API.\n");
+}
+
+#[test]
+fn changes_to_private_definitions_are_ignored() {
+    let diff = diff(
+        "fn wibble() { 1 }",
+        "fn wibble() { \"wobble\" }\ntype Wobble",
+    );
+    assert_eq!(diff.kind(), None);
+}
+
+#[test]
+fn removed_and_added_definitions() {
+    assert_diff!(
+        "pub fn wibble() { 1 }\npub const wobble = 1\npub type Wubble\npub type Wabble = Int",
+        "pub fn wobble() { 1 }\npub const wibble = 1\npub type Wabble\npub type Wubble = Int",
+    );
+}
+
+#[test]
+fn changed_function_signatures() {
+    assert_diff!(
+        r#"
+pub fn wibble(a: Int) -> Int { a }
+pub fn wobble(a: Int, b: Int) -> Int { a + b }
+pub fn wubble(a: Int) -> String { "" }
+"#,
+        r#"
+pub fn wibble(a: Int, b: Int) -> Int { a + b }
+pub fn wobble(a: Int, b: Float) -> Int { a }
+pub fn wubble(a: Int) -> List(#(a, fn(Int) -> Bool)) { [] }
+"#,
+    );
+}
+
+#[test]
+fn changed_labels() {
+    assert_diff!(
+        r#"
+pub fn wibble(a: Int, b b: Int, c c: Int) -> Int { a + b + c }
+pub fn wobble(a: Int) -> Int { a }
+"#,
+        r#"
+pub fn wibble(a: Int, b: Int, d c: Int) -> Int { a + b + c }
+pub fn wobble(a a: Int) -> Int { a }
+"#,
+    );
+}
+
+#[test]
+fn changed_constructors() {
+    assert_diff!(
+        r#"
+pub type Wibble {
+  Wibble(Int)
+  Wobble(label: Int)
+  Wubble
+}
+"#,
+        r#"
+pub type Wibble {
+  Wibble(String)
+  Wobble(label: Int, other: Int)
+  Wabble
+}
+"#,
+    );
+}
+
+#[test]
+fn opacity_changes() {
+    assert_diff!(
+        r#"
+pub type Wibble { Wibble }
+pub opaque type Wobble { Wobble }
+"#,
+        r#"
+pub opaque type Wibble { Wibble }
+pub type Wobble { Wobble }
+"#,
+    );
+}
+
+#[test]
+fn changed_type_parameters_and_aliases() {
+    assert_diff!(
+        r#"
+pub type Wibble(a)
+pub type Wobble = List(Int)
+pub const wubble: List(Int) = []
+"#,
+        r#"
+pub type Wibble(a, b)
+pub type Wobble = List(Float)
+pub const wubble: List(Float) = []
+"#,
+    );
+}
+
+#[test]
+fn documentation_and_deprecation_changes() {
+    assert_diff!(
+        r#"
+//// The module.
+
+/// Old documentation.
+pub fn wibble() { 1 }
+
+@deprecated("Use wibble")
+pub fn wobble() { 1 }
+
+pub fn wubble() { 1 }
+"#,
+        r#"
+//// The updated module.
+
+/// New documentation.
+pub fn wibble() { 1 }
+
+pub fn wobble() { 1 }
+
+@deprecated("Use wibble")
+pub fn wubble() { 1 }
+"#,
+    );
+}
+
+#[test]
+fn target_support_changes() {
+    assert_diff!(
+        r#"
+pub fn wibble() -> Int { 1 }
+
+@external(erlang, "wibble", "wobble")
+pub fn wobble() -> Int
+"#,
+        r#"
+@external(erlang, "wibble", "wibble")
+pub fn wibble() -> Int
+
+pub fn wobble() -> Int { 1 }
+"#,
+    );
+}
+
+#[test]
+fn kind_is_the_most_significant_change() {
+    assert_eq!(
+        diff("/// Docs\npub fn wibble() { 1 }", "pub fn wibble() { 1 }").kind(),
+        Some(ChangeKind::Patch)
+    );
+    assert_eq!(
+        diff(
+            "pub fn wibble() { 1 }",
+            "/// Docs\npub fn wibble() { 1 }\npub fn wobble() { 1 }"
+        )
+        .kind(),
+        Some(ChangeKind::Additive)
+    );
+    assert_eq!(
+        diff(
+            "pub fn wibble() { 1 }\npub fn wobble() { 1 }",
+            "pub fn wubble() { 1 }"
+        )
+        .kind(),
+        Some(ChangeKind::Breaking)
+    );
+}
+
+#[test]
+fn minimum_version() {
+    let breaking = diff("pub fn wibble() { 1 }", "");
+    let additive = diff("", "pub fn wibble() { 1 }");
+    let patch = diff("/// Docs\npub fn wibble() { 1 }", "pub fn wibble() { 1 }");
+    let version = Version::new(1, 2, 3);
+
+    assert_eq!(
+        breaking.minimum_version(&version),
+        Some(Version::new(2, 0, 0))
+    );
+    assert_eq!(
+        additive.minimum_version(&version),
+        Some(Version::new(1, 3, 0))
+    );
+    assert_eq!(patch.minimum_version(&version), None);
+    assert_eq!(diff("", "").minimum_version(&version), None);
+
+    // Before 1.0.0 the minor version is bumped for breaking changes
+    let version = Version::new(0, 2, 3);
+    assert_eq!(
+        breaking.minimum_version(&version),
+        Some(Version::new(0, 3, 0))
+    );
+    assert_eq!(
+        additive.minimum_version(&version),
+        Some(Version::new(0, 2, 4))
+    );
+    assert_eq!(patch.minimum_version(&version), None);
+    assert_eq!(
+        breaking.minimum_version(&Version::new(0, 0, 1)),
+        Some(Version::new(0, 1, 0))
+    );
+
+    // Semantic versioning makes no promises for pre-release versions
+    let pre = Version::parse("1.0.0-rc1").expect("version parses");
+    assert_eq!(breaking.minimum_version(&pre), None);
+}
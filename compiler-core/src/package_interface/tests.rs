@@ -172,6 +172,7 @@ fn package_from_module(module: Module) -> Package {
                     .build()
                     .expect("internals glob"),
             ]),
+            warnings: Default::default(),
         },
         cached_module_names: Vec::new(),
         modules: vec![module],
@@ -0,0 +1,103 @@
+//! Running independent pieces of work on multiple threads.
+
+use std::{num::NonZeroUsize, sync::Mutex, thread};
+
+/// The stack size of the threads that work is run on. Type checking and code
+/// generation are deeply recursive, so the threads are given as much stack as
+/// the main thread usually has.
+const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Apply `f` to each of the items using one thread per core, returning the
+/// results in the same order as the items.
+///
+/// When there is only one item, or threads cannot be used on this platform,
+/// the items are processed on the current thread.
+///
+pub fn map<T, R>(items: Vec<T>, f: impl Fn(T) -> R + Sync) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+{
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    map_with_threads(items, threads, f)
+}
+
+fn map_with_threads<T, R>(items: Vec<T>, threads: usize, f: impl Fn(T) -> R + Sync) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+{
+    let threads = threads.min(items.len());
+    if threads <= 1 {
+        return items.into_iter().map(f).collect();
+    }
+
+    let count = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    let results = Mutex::new(Vec::with_capacity(count));
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            let _ = thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn_scoped(scope, || {
+                    loop {
+                        let next = queue.lock().expect("Work queue lock poisoned").next();
+                        let Some((index, item)) = next else {
+                            break;
+                        };
+                        let result = f(item);
+                        results
+                            .lock()
+                            .expect("Results lock poisoned")
+                            .push((index, result));
+                    }
+                })
+                .expect("Spawning worker thread");
+        }
+    });
+
+    let mut results = results.into_inner().expect("Results lock poisoned");
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_preserves_order() {
+        let items = (0..100).collect::<Vec<u64>>();
+        let results = map_with_threads(items, 4, |item| item * 2);
+        assert_eq!(results, (0..100).map(|item| item * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn map_uses_multiple_threads() {
+        let results = map_with_threads(vec![(); 8], 4, |()| {
+            // Each item waits long enough for the other threads to take items
+            // from the queue.
+            thread::sleep(std::time::Duration::from_millis(20));
+            thread::current().id()
+        });
+        assert!(results.iter().all(|id| *id != thread::current().id()));
+        assert!(results.iter().any(|id| Some(id) != results.first()));
+    }
+
+    #[test]
+    fn map_single_item() {
+        assert_eq!(map_with_threads(vec!["wibble"], 4, str::len), vec![6]);
+    }
+
+    #[test]
+    fn map_single_thread() {
+        let results = map_with_threads(vec![1, 2, 3], 1, |_| thread::current().id());
+        assert_eq!(results, vec![thread::current().id(); 3]);
+    }
+
+    #[test]
+    fn map_no_items() {
+        assert_eq!(map(Vec::<u64>::new(), |item| item), Vec::<u64>::new());
+    }
+}
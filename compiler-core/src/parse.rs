@@ -74,7 +74,7 @@ use crate::parse::extra::ModuleExtra;
 use crate::type_::Deprecation;
 use crate::type_::error::{VariableDeclaration, VariableOrigin, VariableSyntax};
 use crate::type_::expression::{Implementations, Purity};
-use crate::warning::{DeprecatedSyntaxWarning, WarningEmitter};
+use crate::warning::{DeprecatedSyntaxWarning, WarningEmitter, WarningKind};
 use camino::Utf8PathBuf;
 use ecow::EcoString;
 use error::{LexicalError, ParseError, ParseErrorType};
@@ -124,6 +124,7 @@ struct Attributes {
     external_erlang: Option<(EcoString, EcoString, SrcSpan)>,
     external_javascript: Option<(EcoString, EcoString, SrcSpan)>,
     internal: InternalAttribute,
+    allowed_warnings: Vec<WarningKind>,
 }
 
 impl Attributes {
@@ -2101,6 +2102,7 @@ where
             return_type: (),
             return_annotation,
             deprecation: std::mem::take(&mut attributes.deprecated),
+            allowed_warnings: std::mem::take(&mut attributes.allowed_warnings),
             external_erlang: attributes.external_erlang.take(),
             external_javascript: attributes.external_javascript.take(),
             implementations: Implementations {
@@ -2400,6 +2402,7 @@ where
                             || attributes.external_javascript.is_some()
                             || attributes.target.is_some()
                             || attributes.internal != InternalAttribute::Missing
+                            || !attributes.allowed_warnings.is_empty()
                         {
                             return parse_error(
                                 ParseErrorType::UnknownAttributeRecordVariant,
@@ -2458,6 +2461,7 @@ where
                                 type_ast: t,
                                 type_: (),
                                 deprecation: std::mem::take(&mut attributes.deprecated),
+                                allowed_warnings: std::mem::take(&mut attributes.allowed_warnings),
                             })));
                         }
                         _ => {
@@ -2484,6 +2488,7 @@ where
             constructors,
             typed_parameters: vec![],
             deprecation: std::mem::take(&mut attributes.deprecated),
+            allowed_warnings: std::mem::take(&mut attributes.allowed_warnings),
         })))
     }
 
@@ -2959,6 +2964,7 @@ where
                     value: Box::new(value),
                     type_: (),
                     deprecation: attributes.deprecated.clone(),
+                    allowed_warnings: attributes.allowed_warnings.clone(),
                     implementations: Implementations {
                         gleam: true,
                         can_run_on_erlang: true,
@@ -4007,6 +4013,7 @@ functions are declared separately from types.";
                 self.parse_deprecated_attribute(start, end, attributes)
             }
             "internal" => self.parse_internal_attribute(start, end, attributes),
+            "allow" => self.parse_allow_attribute(start, end, attributes),
             _ => parse_error(ParseErrorType::UnknownAttribute, SrcSpan { start, end }),
         }?;
 
@@ -4079,6 +4086,52 @@ functions are declared separately from types.";
         Ok(end)
     }
 
+    fn parse_allow_attribute(
+        &mut self,
+        start: u32,
+        end: u32,
+        attributes: &mut Attributes,
+    ) -> Result<u32, ParseError> {
+        if !attributes.allowed_warnings.is_empty() {
+            return parse_error(ParseErrorType::DuplicateAttribute, SrcSpan::new(start, end));
+        }
+
+        let _ = self.expect_one(&Token::LeftParen)?;
+        let mut allowed_warnings = vec![self.expect_warning_kind()?];
+        while self.maybe_one(&Token::Comma).is_some() {
+            // A trailing comma is allowed after the last warning.
+            if let Some((_, end)) = self.maybe_one(&Token::RightParen) {
+                attributes.allowed_warnings = allowed_warnings;
+                return Ok(end);
+            }
+            allowed_warnings.push(self.expect_warning_kind()?);
+        }
+        let (_, end) = self.expect_one(&Token::RightParen)?;
+
+        attributes.allowed_warnings = allowed_warnings;
+        Ok(end)
+    }
+
+    fn expect_warning_kind(&mut self) -> Result<WarningKind, ParseError> {
+        let (start, name, end) = match self.next_tok() {
+            Some((start, Token::Name { name }, end)) => (start, name, end),
+            // These kinds of warning are named after the keyword causing them.
+            Some((start, Token::Todo, end)) => (start, "todo".into(), end),
+            Some((start, Token::Echo, end)) => (start, "echo".into(), end),
+            Some((start, _, end)) => {
+                return parse_error(ParseErrorType::ExpectedName, SrcSpan::new(start, end));
+            }
+            None => {
+                return parse_error(ParseErrorType::UnexpectedEof, SrcSpan { start: 0, end: 0 });
+            }
+        };
+
+        name.parse().map_err(|_| ParseError {
+            error: ParseErrorType::UnknownWarningKind { name },
+            location: SrcSpan::new(start, end),
+        })
+    }
+
     fn parse_internal_attribute(
         &mut self,
         start: u32,
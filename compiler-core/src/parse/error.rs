@@ -1,9 +1,11 @@
 use crate::ast::{SrcSpan, TypeAst};
 use crate::diagnostic::{ExtraLabel, Label};
-use crate::error::wrap;
+use crate::error::{did_you_mean, wrap};
 use crate::parse::Token;
+use crate::warning::WarningKind;
 use ecow::EcoString;
 use itertools::Itertools;
+use strum::VariantNames;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub struct LexicalError {
@@ -112,6 +114,9 @@ pub enum ParseErrorType {
         module: EcoString,
         item: EcoString,
     },
+    UnknownWarningKind {
+        name: EcoString,
+    }, // an `@allow` attribute was given a name that is not a kind of warning
 }
 
 pub(crate) struct ParseErrorDetails {
@@ -122,6 +127,67 @@ pub(crate) struct ParseErrorDetails {
 }
 
 impl ParseErrorType {
+    /// The stable code identifying this kind of error, as accepted by
+    /// `gleam explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseErrorType::ExpectedEqual => "E0100",
+            ParseErrorType::ExpectedExpr => "E0101",
+            ParseErrorType::ExpectedName => "E0102",
+            ParseErrorType::ExpectedPattern => "E0103",
+            ParseErrorType::ExpectedType => "E0104",
+            ParseErrorType::ExpectedUpName => "E0105",
+            ParseErrorType::ExpectedValue => "E0106",
+            ParseErrorType::ExpectedDefinition => "E0107",
+            ParseErrorType::ExpectedDeprecationMessage => "E0108",
+            ParseErrorType::ExpectedFunctionDefinition => "E0109",
+            ParseErrorType::ExpectedTargetName => "E0110",
+            ParseErrorType::ExprLparStart => "E0111",
+            ParseErrorType::ExtraSeparator => "E0112",
+            ParseErrorType::IncorrectName => "E0113",
+            ParseErrorType::IncorrectUpName => "E0114",
+            ParseErrorType::InvalidBitArraySegment => "E0115",
+            ParseErrorType::InvalidBitArrayUnit => "E0116",
+            ParseErrorType::InvalidTailPattern => "E0117",
+            ParseErrorType::InvalidTupleAccess => "E0118",
+            ParseErrorType::LexError { .. } => "E0119",
+            ParseErrorType::NestedBitArrayPattern => "E0120",
+            ParseErrorType::NoLetBinding => "E0121",
+            ParseErrorType::NoValueAfterEqual => "E0122",
+            ParseErrorType::NotConstType => "E0123",
+            ParseErrorType::OpNakedRight => "E0124",
+            ParseErrorType::OpaqueTypeAlias => "E0125",
+            ParseErrorType::TooManyArgHoles => "E0126",
+            ParseErrorType::DuplicateAttribute => "E0127",
+            ParseErrorType::UnknownAttribute => "E0128",
+            ParseErrorType::UnknownTarget => "E0129",
+            ParseErrorType::ListSpreadWithoutElements => "E0130",
+            ParseErrorType::ListSpreadFollowedByElements => "E0131",
+            ParseErrorType::ListSpreadWithAnotherSpread { .. } => "E0132",
+            ParseErrorType::LowcaseBooleanPattern => "E0133",
+            ParseErrorType::UnexpectedLabel => "E0134",
+            ParseErrorType::UnexpectedEof => "E0135",
+            ParseErrorType::UnexpectedReservedWord => "E0136",
+            ParseErrorType::UnexpectedToken { .. } => "E0137",
+            ParseErrorType::UnexpectedFunction => "E0138",
+            ParseErrorType::ConcatPatternVariableLeftHandSide => "E0139",
+            ParseErrorType::ListSpreadWithoutTail => "E0140",
+            ParseErrorType::ExpectedFunctionBody => "E0141",
+            ParseErrorType::RedundantInternalAttribute => "E0142",
+            ParseErrorType::InvalidModuleTypePattern => "E0143",
+            ParseErrorType::ListPatternSpreadFollowedByElements => "E0144",
+            ParseErrorType::ExpectedRecordConstructor { .. } => "E0145",
+            ParseErrorType::CallInClauseGuard => "E0146",
+            ParseErrorType::IfExpression => "E0147",
+            ParseErrorType::ConstantRecordConstructorNoArguments => "E0148",
+            ParseErrorType::TypeConstructorNoArguments => "E0149",
+            ParseErrorType::TypeDefinitionNoArguments => "E0150",
+            ParseErrorType::UnknownAttributeRecordVariant => "E0151",
+            ParseErrorType::IncorrectImportModuleSeparator { .. } => "E0152",
+            ParseErrorType::UnknownWarningKind { .. } => "E0153",
+        }
+    }
+
     pub(crate) fn details(&self) -> ParseErrorDetails {
         match self {
             ParseErrorType::ExpectedEqual => ParseErrorDetails {
@@ -482,7 +548,7 @@ utf16_codepoint, utf32_codepoint, signed, unsigned, big, little, native, size, u
 
             ParseErrorType::UnknownAttribute => ParseErrorDetails {
                 text: "".into(),
-                hint: Some("Try `deprecated`, `external` or `target` instead.".into()),
+                hint: Some("Try `allow`, `deprecated`, `external` or `target` instead.".into()),
                 label_text: "I don't recognise this attribute".into(),
                 extra_labels: vec![],
             },
@@ -627,6 +693,19 @@ utf16_codepoint, utf32_codepoint, signed, unsigned, big, little, native, size, u
                 label_text: "I was expecting either `/` or `.{` here.".into(),
                 extra_labels: vec![],
             },
+
+            ParseErrorType::UnknownWarningKind { name } => {
+                let kinds = WarningKind::VARIANTS
+                    .iter()
+                    .map(|kind| EcoString::from(*kind))
+                    .collect_vec();
+                ParseErrorDetails {
+                    text: format!("`{name}` is not a kind of warning that can be allowed."),
+                    hint: did_you_mean(name, &kinds),
+                    label_text: "I don't recognise this warning".into(),
+                    extra_labels: vec![],
+                }
+            }
         }
     }
 }
@@ -807,6 +807,43 @@ pub fn main() -> Nil {
     );
 }
 
+#[test]
+fn multiple_allow_attributes() {
+    assert_module_error!(
+        r#"
+@allow(todo)
+@allow(unused_variable)
+pub fn main() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn allow_attribute_with_unknown_warning() {
+    assert_module_error!(
+        r#"
+@allow(unused_varaible)
+pub fn main() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn allow_attribute_without_warnings() {
+    assert_module_error!(
+        r#"
+@allow()
+pub fn main() -> Nil {
+  Nil
+}
+"#
+    );
+}
+
 #[test]
 fn attributes_with_no_definition() {
     assert_module_error!(
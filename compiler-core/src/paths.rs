@@ -7,11 +7,24 @@ pub const ARTEFACT_DIRECTORY_NAME: &str = "_gleam_artefacts";
 #[derive(Debug, Clone)]
 pub struct ProjectPaths {
     root: Utf8PathBuf,
+    workspace: Option<Utf8PathBuf>,
 }
 
 impl ProjectPaths {
     pub fn new(root: Utf8PathBuf) -> Self {
-        Self { root }
+        Self {
+            root,
+            workspace: None,
+        }
+    }
+
+    /// The paths of a package that is a member of the workspace at the given
+    /// root, which it shares the manifest and build directory of.
+    pub fn workspace_member(root: Utf8PathBuf, workspace: Utf8PathBuf) -> Self {
+        Self {
+            root,
+            workspace: Some(workspace),
+        }
     }
 
     pub fn at_filesystem_root() -> Self {
@@ -28,6 +41,19 @@ impl ProjectPaths {
         &self.root
     }
 
+    /// The root of the workspace the package is a member of, if any.
+    pub fn workspace(&self) -> Option<&Utf8Path> {
+        self.workspace.as_deref()
+    }
+
+    /// The directory holding the manifest and the build directory, and that
+    /// the paths of local dependencies in the manifest are relative to. This
+    /// is the root of the workspace for a workspace member, and the root of
+    /// the package otherwise.
+    pub fn workspace_root(&self) -> &Utf8Path {
+        self.workspace.as_deref().unwrap_or(&self.root)
+    }
+
     pub fn root_config(&self) -> Utf8PathBuf {
         self.root.join("gleam.toml")
     }
@@ -37,7 +63,7 @@ impl ProjectPaths {
     }
 
     pub fn manifest(&self) -> Utf8PathBuf {
-        self.root.join("manifest.toml")
+        self.workspace_root().join("manifest.toml")
     }
 
     pub fn src_directory(&self) -> Utf8PathBuf {
@@ -53,7 +79,7 @@ impl ProjectPaths {
     }
 
     pub fn build_directory(&self) -> Utf8PathBuf {
-        self.root.join("build")
+        self.workspace_root().join("build")
     }
 
     pub fn build_packages_directory(&self) -> Utf8PathBuf {
@@ -86,6 +112,10 @@ impl ProjectPaths {
         self.build_directory().join("erlang-shipment")
     }
 
+    pub fn build_coverage_report_directory(&self) -> Utf8PathBuf {
+        self.build_directory().join("coverage-report")
+    }
+
     pub fn build_documentation_directory(&self, package: &str) -> Utf8PathBuf {
         self.build_directory_for_mode(Mode::Dev)
             .join("docs")
@@ -138,7 +168,7 @@ fn global_hexpm_cache() -> Utf8PathBuf {
     default_global_gleam_cache().join("hex").join("hexpm")
 }
 
-fn global_packages_cache() -> Utf8PathBuf {
+pub fn global_packages_cache() -> Utf8PathBuf {
     global_hexpm_cache().join("packages")
 }
 
@@ -175,3 +205,19 @@ fn paths() {
             .ends_with("hex/hexpm/packages/elli-1.0.0.tar")
     );
 }
+
+#[test]
+fn workspace_member_paths() {
+    let paths = ProjectPaths::workspace_member("/app/packages/wibble".into(), "/app".into());
+    assert_eq!(
+        paths.root_config(),
+        Utf8PathBuf::from("/app/packages/wibble/gleam.toml")
+    );
+    assert_eq!(
+        paths.src_directory(),
+        Utf8PathBuf::from("/app/packages/wibble/src")
+    );
+    assert_eq!(paths.manifest(), Utf8PathBuf::from("/app/manifest.toml"));
+    assert_eq!(paths.build_directory(), Utf8PathBuf::from("/app/build"));
+    assert_eq!(paths.workspace_root(), Utf8Path::new("/app"));
+}
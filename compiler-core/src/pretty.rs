@@ -262,6 +262,11 @@ pub enum Document<'a> {
     /// This is useful for additional formatting text which won't be rendered
     /// in the final output, such as ANSI codes or HTML elements.
     ZeroWidthString { string: EcoString },
+
+    /// Marks the position in the source code that the documents following it
+    /// were generated from. It is not printed and has no width, it is only
+    /// reported to the writer so that it can build a source map.
+    SourceLocation { start: u32 },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -405,8 +410,9 @@ fn fits(
                 current_width += graphemes
             }
 
-            // Zero width strings do nothing: they do not contribute to line length
-            Document::ZeroWidthString { .. } => {}
+            // Zero width strings and source locations do nothing: they do not
+            // contribute to line length
+            Document::ZeroWidthString { .. } | Document::SourceLocation { .. } => {}
 
             // If we get to a break we need to first see if it has to be
             // rendered as its unbroken or broken string, depending on the mode.
@@ -578,6 +584,10 @@ fn format(
                 writer.str_write(string)?;
             }
 
+            // Nothing is written, the writer is only told where the code that
+            // follows comes from.
+            Document::SourceLocation { start } => writer.source_location(*start),
+
             // If multiple documents need to be printed, then they are all
             // pushed to the front of the queue and will be printed one by one.
             Document::Vec(vec) => {
@@ -717,6 +727,20 @@ pub fn zero_width_string<'a>(string: EcoString) -> Document<'a> {
     Document::ZeroWidthString { string }
 }
 
+/// Marks the documents following it as generated from the source code
+/// starting at the given byte offset. It is never printed, but writers
+/// building a source map are told about it.
+///
+/// For example:
+/// ```rust:norun
+/// let document = docvec![source_location(12), "wibble()"];
+/// assert_eq!(document.to_pretty_string(80), "wibble()");
+/// ```
+///
+pub fn source_location<'a>(start: u32) -> Document<'a> {
+    Document::SourceLocation { start }
+}
+
 impl<'a> Document<'a> {
     /// Creates a document from a string slice.
     pub fn str(string: &'a str) -> Self {
@@ -839,6 +863,7 @@ impl<'a> Document<'a> {
             // still printed and so are not empty. (Unless their string contents
             // is also empty)
             ZeroWidthString { string } => string.is_empty(),
+            SourceLocation { .. } => true,
         }
     }
 }
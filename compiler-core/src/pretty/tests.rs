@@ -339,6 +339,7 @@ fn empty_documents() {
     assert!(!"wibble".to_doc().append("".to_doc()).is_empty());
     assert!(!"".to_doc().append("wibble".to_doc()).is_empty());
     assert!(!zero_width_string("wibble".into()).is_empty());
+    assert!(source_location(1).is_empty());
 }
 
 #[test]
@@ -349,3 +350,55 @@ fn set_nesting() {
         doc.set_nesting(0).nest(2).to_pretty_string(1)
     );
 }
+
+#[test]
+fn source_locations_are_not_printed() {
+    let doc = Vec(vec![
+        source_location(1),
+        "wibble".to_doc(),
+        break_("", " "),
+        source_location(10),
+        "wobble".to_doc(),
+    ])
+    .group();
+    assert_eq!("wibble wobble", doc.clone().to_pretty_string(80));
+    assert_eq!("wibble\nwobble", doc.to_pretty_string(10));
+}
+
+#[test]
+fn source_locations_are_reported_to_the_writer() {
+    #[derive(Default)]
+    struct LocationsWriter {
+        output: String,
+        locations: std::vec::Vec<(usize, u32)>,
+    }
+
+    impl std::fmt::Write for LocationsWriter {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.output.push_str(s);
+            Ok(())
+        }
+    }
+
+    impl Utf8Writer for LocationsWriter {
+        fn convert_err<E: std::error::Error>(&self, error: E) -> crate::Error {
+            self.output.convert_err(error)
+        }
+
+        fn source_location(&mut self, start: u32) {
+            self.locations.push((self.output.len(), start));
+        }
+    }
+
+    let doc = Vec(vec![
+        source_location(1),
+        "wibble".to_doc(),
+        line(),
+        source_location(10),
+        "wobble".to_doc(),
+    ]);
+    let mut writer = LocationsWriter::default();
+    doc.pretty_print(80, &mut writer).expect("pretty print");
+    assert_eq!(writer.output, "wibble\nwobble");
+    assert_eq!(writer.locations, vec![(0, 1), (7, 10)]);
+}
@@ -0,0 +1,321 @@
+//! Renaming modules: rewriting the imports of the modules importing them and
+//! the references made through those imports, and updating the
+//! `internal_modules` globs of the project configuration.
+
+use ecow::EcoString;
+use globset::Glob;
+use itertools::Itertools;
+
+use crate::{
+    ast::{AssignName, Definition, SrcSpan},
+    build::Module,
+    config::PackageConfig,
+    reference::ReferenceKind,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A module being given a new name.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleRename {
+    pub old_name: EcoString,
+    pub new_name: EcoString,
+}
+
+/// A change to some source code, replacing the code at a location.
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub location: SrcSpan,
+    pub new_text: String,
+}
+
+pub fn is_valid_module_name(name: &str) -> bool {
+    name.split('/').all(|segment| {
+        segment.starts_with(|char: char| char.is_ascii_lowercase())
+            && segment
+                .chars()
+                .all(|char| char.is_ascii_lowercase() || char.is_ascii_digit() || char == '_')
+    })
+}
+
+/// The modules renamed by renaming a path, relative to the source directory
+/// and without the `.gleam` extension. The path can be the one of a module or
+/// of a directory containing modules.
+///
+pub fn renamed_modules<'a>(
+    module_names: impl IntoIterator<Item = &'a EcoString>,
+    old_path: &str,
+    new_path: &str,
+) -> Vec<ModuleRename> {
+    module_names
+        .into_iter()
+        .filter_map(|name| {
+            let new_name = if name == old_path {
+                new_path.into()
+            } else {
+                let nested = name.strip_prefix(old_path)?.strip_prefix('/')?;
+                EcoString::from(format!("{new_path}/{nested}"))
+            };
+            Some(ModuleRename {
+                old_name: name.clone(),
+                new_name,
+            })
+        })
+        .sorted_by(|one, other| one.old_name.cmp(&other.old_name))
+        .collect()
+}
+
+/// The edits to the code of a module needed to keep importing the renamed
+/// modules.
+///
+/// The path of each import is replaced. If the last segment of the path
+/// changes, the references qualified with the implicit alias are updated to
+/// use the new one. An explicit alias that becomes redundant is removed.
+///
+pub fn import_edits(module: &Module, renames: &[ModuleRename]) -> Vec<Edit> {
+    let code = &module.code;
+    let mut edits = vec![];
+
+    for definition in &module.ast.definitions {
+        let Definition::Import(import) = definition else {
+            continue;
+        };
+        let Some(rename) = renames
+            .iter()
+            .find(|rename| rename.old_name == import.module)
+        else {
+            continue;
+        };
+        let import_code = code
+            .get(import.location.start as usize..import.location.end as usize)
+            .unwrap_or_default();
+        let Some(offset) = import_code.find(rename.old_name.as_str()) else {
+            continue;
+        };
+        let start = import.location.start + offset as u32;
+        edits.push(Edit {
+            location: SrcSpan::new(start, start + rename.old_name.len() as u32),
+            new_text: rename.new_name.to_string(),
+        });
+
+        let old_alias = last_segment(&rename.old_name);
+        let new_alias = last_segment(&rename.new_name);
+        match &import.as_name {
+            Some((AssignName::Variable(alias), location)) if alias == new_alias => {
+                let before = code
+                    .get(..location.start as usize)
+                    .unwrap_or_default()
+                    .trim_end();
+                edits.push(Edit {
+                    location: SrcSpan::new(before.len() as u32, location.end),
+                    new_text: String::new(),
+                });
+            }
+            Some(_) => {}
+            None if old_alias == new_alias => {}
+
+            // The new name would clash with another import, so the old one is
+            // kept as an explicit alias.
+            None if module.ast.definitions.iter().any(|definition| {
+                matches!(
+                    definition,
+                    Definition::Import(other)
+                        if other.module != import.module
+                            && other.used_name().as_deref() == Some(new_alias)
+                )
+            }) =>
+            {
+                edits.push(Edit {
+                    location: SrcSpan::new(import.location.end, import.location.end),
+                    new_text: format!(" as {old_alias}"),
+                });
+            }
+
+            None => {
+                let references = &module.ast.type_info.references;
+                let qualified = references
+                    .value_references
+                    .iter()
+                    .chain(references.type_references.iter())
+                    .filter(|((referenced_module, _), _)| *referenced_module == import.module)
+                    .flat_map(|((_, name), references)| {
+                        references
+                            .iter()
+                            .filter(|reference| reference.kind == ReferenceKind::Qualified)
+                            .map(move |reference| reference.location.end - name.len() as u32)
+                    });
+                // The same module could be imported again with another
+                // alias, so only the references using this one are updated.
+                let old_qualifier = format!("{old_alias}.");
+                for name_start in qualified {
+                    let location = qualifier(code, name_start).filter(|location| {
+                        code.get(location.start as usize..location.end as usize)
+                            == Some(old_qualifier.as_str())
+                    });
+                    if let Some(location) = location {
+                        edits.push(Edit {
+                            location,
+                            new_text: format!("{new_alias}."),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    edits
+}
+
+fn last_segment(module: &str) -> &str {
+    module.split('/').next_back().unwrap_or(module)
+}
+
+/// The location of the module alias and dot qualifying the name starting at
+/// the given position.
+///
+pub(crate) fn qualifier(code: &str, name_start: u32) -> Option<SrcSpan> {
+    let before = code.get(..name_start as usize)?.strip_suffix('.')?;
+    let alias = before.len()
+        - before
+            .trim_end_matches(|char: char| char.is_ascii_alphanumeric() || char == '_')
+            .len();
+    Some(SrcSpan::new(name_start - 1 - alias as u32, name_start))
+}
+
+/// The edits to the `gleam.toml` of the project needed for the renamed
+/// modules to stay internal, or public, after being renamed.
+///
+/// The globs matching the old path of a renamed directory or module are
+/// updated to match its new path. A module that is still not matched by any
+/// glob is added to the list.
+///
+pub fn internal_modules_edits(
+    config: &PackageConfig,
+    toml: &str,
+    renames: &[ModuleRename],
+) -> Vec<Edit> {
+    let Some(globs) = &config.internal_modules else {
+        return vec![];
+    };
+    let Some((strings, end)) = internal_modules_strings(toml) else {
+        return vec![];
+    };
+
+    let mut edits = vec![];
+    let mut new_globs = vec![];
+    for (location, glob) in strings {
+        let new_glob = renamed_glob(glob, renames);
+        if let Some(new_glob) = &new_glob {
+            edits.push(Edit {
+                location,
+                new_text: new_glob.clone(),
+            });
+        }
+        new_globs.extend(Glob::new(new_glob.as_deref().unwrap_or(glob)).ok());
+    }
+
+    let matches = |globs: &[Glob], name: &str| {
+        globs
+            .iter()
+            .any(|glob| glob.compile_matcher().is_match(name))
+    };
+    let added = renames
+        .iter()
+        .filter(|rename| matches(globs, &rename.old_name) && !matches(&new_globs, &rename.new_name))
+        .map(|rename| format!("\"{}\"", rename.new_name))
+        .collect_vec();
+    if !added.is_empty() {
+        let before = toml.get(..end as usize).unwrap_or_default().trim_end();
+        let separator = match before.chars().next_back() {
+            Some('[') => "",
+            Some(',') => " ",
+            _ => ", ",
+        };
+        let position = before.len() as u32;
+        edits.push(Edit {
+            location: SrcSpan::new(position, position),
+            new_text: format!("{separator}{}", added.join(", ")),
+        });
+    }
+
+    edits
+}
+
+/// The new glob matching the renamed modules instead of the old ones.
+///
+fn renamed_glob(glob: &str, renames: &[ModuleRename]) -> Option<String> {
+    renames.iter().find_map(|rename| {
+        if glob == rename.old_name {
+            Some(rename.new_name.to_string())
+        } else {
+            let nested = glob
+                .strip_prefix(rename.old_name.as_str())?
+                .strip_prefix('/')?;
+            Some(format!("{}/{nested}", rename.new_name))
+        }
+    })
+}
+
+/// The location and contents of the strings in the `internal_modules` array,
+/// and the position of its closing bracket.
+///
+fn internal_modules_strings(toml: &str) -> Option<(Vec<(SrcSpan, &str)>, u32)> {
+    let mut offset = 0;
+    let start = loop {
+        let line = toml.get(offset..)?.lines().next()?;
+        let value = line
+            .trim_start()
+            .strip_prefix("internal_modules")
+            .map(str::trim_start)
+            .and_then(|rest| rest.strip_prefix('='));
+        if let Some(value) = value {
+            break offset + line.len() - value.len();
+        }
+        offset += line.len() + 1;
+    };
+
+    let mut strings = vec![];
+    let mut string_start = None;
+    let mut in_comment = false;
+    let mut found_array = false;
+    for (index, char) in toml.get(start..)?.char_indices() {
+        let position = start + index;
+        match (string_start, char) {
+            (_, '\n') if in_comment => in_comment = false,
+            _ if in_comment => {}
+            (Some((quote, contents_start)), _) if char == quote => {
+                strings.push((
+                    SrcSpan::new(contents_start as u32, position as u32),
+                    toml.get(contents_start..position)?,
+                ));
+                string_start = None;
+            }
+            (Some(_), _) => {}
+            (None, '"' | '\'') => string_start = Some((char, position + 1)),
+            (None, '#') => in_comment = true,
+            (None, '[') => found_array = true,
+            (None, ']') if found_array => return Some((strings, position as u32)),
+            (None, _) => {}
+        }
+    }
+    None
+}
+
+/// Applies the edits to some source code.
+///
+pub fn apply_edits(code: &str, edits: &[Edit]) -> String {
+    let mut code = code.to_string();
+    for edit in edits
+        .iter()
+        .sorted_by_key(|edit| std::cmp::Reverse(edit.location.start))
+    {
+        code.replace_range(
+            edit.location.start as usize..edit.location.end as usize,
+            &edit.new_text,
+        );
+    }
+    code
+}
@@ -0,0 +1,137 @@
+use globset::Glob;
+
+use super::*;
+
+fn rename(old_name: &str, new_name: &str) -> ModuleRename {
+    ModuleRename {
+        old_name: old_name.into(),
+        new_name: new_name.into(),
+    }
+}
+
+fn config_with_internal_modules(globs: &[&str]) -> PackageConfig {
+    PackageConfig {
+        internal_modules: Some(
+            globs
+                .iter()
+                .map(|glob| Glob::new(glob).expect("glob"))
+                .collect(),
+        ),
+        ..Default::default()
+    }
+}
+
+fn edit_toml(globs: &[&str], toml: &str, renames: &[ModuleRename]) -> String {
+    let config = config_with_internal_modules(globs);
+    apply_edits(toml, &internal_modules_edits(&config, toml, renames))
+}
+
+#[test]
+fn renamed_module() {
+    let names = [
+        "app".into(),
+        "app/wibble".into(),
+        "app/wibble_wobble".into(),
+    ];
+    assert_eq!(
+        renamed_modules(&names, "app/wibble", "app/wobble"),
+        vec![rename("app/wibble", "app/wobble")]
+    );
+}
+
+#[test]
+fn renamed_directory() {
+    let names = [
+        "app".into(),
+        "app/wibble".into(),
+        "app/wibble/wobble".into(),
+        "app/wibble/wobble/wubble".into(),
+    ];
+    assert_eq!(
+        renamed_modules(&names, "app/wibble", "app/internal/wibble"),
+        vec![
+            rename("app/wibble", "app/internal/wibble"),
+            rename("app/wibble/wobble", "app/internal/wibble/wobble"),
+            rename(
+                "app/wibble/wobble/wubble",
+                "app/internal/wibble/wobble/wubble"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn valid_module_names() {
+    assert!(is_valid_module_name("wibble"));
+    assert!(is_valid_module_name("wibble/wobble_1"));
+    assert!(!is_valid_module_name("Wibble"));
+    assert!(!is_valid_module_name("wibble/"));
+    assert!(!is_valid_module_name("wibble//wobble"));
+    assert!(!is_valid_module_name("1wibble"));
+}
+
+#[test]
+fn internal_modules_glob_is_renamed() {
+    let toml = r#"name = "app"
+internal_modules = ["app/internal", "app/internal/*"] # Hidden from the docs
+"#;
+    assert_eq!(
+        edit_toml(
+            &["app/internal", "app/internal/*"],
+            toml,
+            &[
+                rename("app/internal", "app/private"),
+                rename("app/internal/wibble", "app/private/wibble"),
+            ]
+        ),
+        r#"name = "app"
+internal_modules = ["app/private", "app/private/*"] # Hidden from the docs
+"#
+    );
+}
+
+#[test]
+fn internal_module_moved_out_of_glob_is_added() {
+    let toml = r#"name = "app"
+internal_modules = [
+  "app/internal/*",
+]
+"#;
+    assert_eq!(
+        edit_toml(
+            &["app/internal/*"],
+            toml,
+            &[rename("app/internal/wibble", "app/wibble")]
+        ),
+        r#"name = "app"
+internal_modules = [
+  "app/internal/*", "app/wibble"
+]
+"#
+    );
+}
+
+#[test]
+fn public_module_is_left_alone() {
+    let toml = r#"internal_modules = ["app/internal/*"]
+"#;
+    assert_eq!(
+        edit_toml(
+            &["app/internal/*"],
+            toml,
+            &[rename("app/wibble", "app/wobble")]
+        ),
+        toml
+    );
+}
+
+#[test]
+fn no_edits_without_internal_modules() {
+    let toml = r#"name = "app"
+"#;
+    let config = PackageConfig::default();
+    assert_eq!(
+        internal_modules_edits(&config, toml, &[rename("app/internal", "app/wibble")]),
+        vec![]
+    );
+}
@@ -0,0 +1,406 @@
+//! The built-in test runner.
+//!
+//! Every public function with no arguments whose name ends with `_test`,
+//! defined in a module of the root package's `test` directory, is a test.
+//!
+//! The tests are run by a small runner written in the target language: the
+//! Erlang one is the `{package}@@test` module compiled with the root package,
+//! while the JavaScript one is written together with the list of tests to run
+//! by `javascript_runner`. The runner calls each test in turn, printing an
+//! event before and after it, starting with `EVENT_PREFIX`. Anything else
+//! printed is output of the tests themselves. A test may print without a
+//! trailing newline, so an event can come after such output on the same line.
+//!
+//! The build tool reads those events to measure how long each test takes, to
+//! stop a test that takes longer than its timeout, and to report the tests
+//! that failed.
+
+#[cfg(test)]
+mod tests;
+
+use std::{fmt::Write, time::Duration};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Deserialize;
+
+use crate::{
+    build::{Origin, Target},
+    type_::{ModuleInterface, ValueConstructorVariant},
+};
+
+/// The prefix of the lines printed by a test runner to report its progress.
+pub const EVENT_PREFIX: &str = "\u{1}gleam-test:";
+
+/// The name of the JavaScript module that runs the tests.
+pub const JAVASCRIPT_RUNNER_FILE: &str = "gleam.test.mjs";
+
+/// The name of the file listing the tests the Erlang runner has to run.
+pub const ERLANG_TESTS_FILE: &str = "gleam@@test.eterm";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFunction {
+    pub module: EcoString,
+    pub name: EcoString,
+    pub src_path: Utf8PathBuf,
+    pub line: u32,
+}
+
+impl TestFunction {
+    /// The name the test is reported with, and which `--filter` matches.
+    pub fn full_name(&self) -> String {
+        format!("{}.{}", self.module, self.name)
+    }
+
+    /// Whether this is the test a runner has reported by module and name.
+    pub fn is(&self, module: &str, name: &str) -> bool {
+        self.module == module && self.name == name
+    }
+}
+
+impl ModuleInterface {
+    /// The tests defined in this module that can be run on the given target,
+    /// in the order they are defined.
+    ///
+    pub fn test_functions(&self, target: Target) -> Vec<TestFunction> {
+        if self.origin != Origin::Test {
+            return vec![];
+        }
+
+        self.values
+            .iter()
+            .filter(|(name, value)| name.ends_with("_test") && value.publicity.is_importable())
+            .filter_map(|(name, value)| match &value.variant {
+                ValueConstructorVariant::ModuleFn {
+                    arity: 0,
+                    implementations,
+                    location,
+                    ..
+                } if implementations.supports(target) => Some(TestFunction {
+                    module: self.name.clone(),
+                    name: name.clone(),
+                    src_path: self.src_path.clone(),
+                    line: self.line_numbers.line_number(location.start),
+                }),
+                _ => None,
+            })
+            .sorted_by_key(|test| test.line)
+            .collect()
+    }
+}
+
+/// Which of the discovered tests are to be run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestFilter {
+    /// Only tests whose full name, such as `wibble_test.wobble_test`,
+    /// contains this pattern are run.
+    pub pattern: Option<String>,
+    /// Only the test with exactly this full name is run.
+    pub name: Option<String>,
+    /// Only the tests defined in this module are run.
+    pub module: Option<String>,
+}
+
+impl TestFilter {
+    pub fn matches(&self, test: &TestFunction) -> bool {
+        let module_matches = self
+            .module
+            .as_ref()
+            .is_none_or(|module| test.module == module.as_str());
+        let pattern_matches = self
+            .pattern
+            .as_ref()
+            .is_none_or(|pattern| test.full_name().contains(pattern.as_str()));
+        let name_matches = self
+            .name
+            .as_ref()
+            .is_none_or(|name| test.full_name() == *name);
+        module_matches && pattern_matches && name_matches
+    }
+}
+
+/// An event printed by a test runner. The tests are run one at a time, so a
+/// `Start` event is always followed by the outcome of that same test.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// The test with the given name has started.
+    Start { module: EcoString, name: EcoString },
+    /// The running test has returned.
+    Pass,
+    /// The running test has crashed.
+    Fail(Failure),
+    /// All the tests have been run.
+    Done,
+}
+
+impl Event {
+    /// Parses a line printed by a test runner, returning the event along with
+    /// any output of the tests printed before it on the same line, or `None`
+    /// if the line is only output of the tests.
+    pub fn parse(line: &str) -> Option<(&str, Self)> {
+        let (output, event) = line.split_once(EVENT_PREFIX)?;
+        let event = serde_json::from_str(event).ok()?;
+        Some((output, event))
+    }
+}
+
+/// Why a test has crashed, as reported by the runner.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Failure {
+    /// The kind of Gleam runtime error, such as `panic` or `let_assert`, or
+    /// the kind of error of the target language.
+    pub kind: EcoString,
+    pub message: EcoString,
+    /// The Gleam module the error was raised in, if it is a Gleam error.
+    #[serde(default)]
+    pub module: Option<EcoString>,
+    /// The line the error was raised on, if it is a Gleam error.
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed(Failure),
+    /// The test was stopped as it did not finish in the allotted time.
+    TimedOut,
+    /// The test runner exited while the test was running.
+    Crashed {
+        status: Option<i32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    pub test: TestFunction,
+    pub outcome: Outcome,
+    pub duration: Duration,
+    /// Where the failure of the test was raised, as a path relative to the
+    /// project root and a line number.
+    pub location: Option<(Utf8PathBuf, u32)>,
+}
+
+impl TestResult {
+    pub fn new(
+        test: TestFunction,
+        outcome: Outcome,
+        duration: Duration,
+        modules: &im::HashMap<EcoString, ModuleInterface>,
+        root: &Utf8Path,
+    ) -> Self {
+        let location = match &outcome {
+            Outcome::Failed(Failure {
+                module: Some(module),
+                line: Some(line),
+                ..
+            }) => modules
+                .get(module)
+                .map(|module| (relative_path(&module.src_path, root), *line)),
+            Outcome::Passed | Outcome::Failed(_) | Outcome::TimedOut | Outcome::Crashed { .. } => {
+                None
+            }
+        };
+        let test = TestFunction {
+            src_path: relative_path(&test.src_path, root),
+            ..test
+        };
+        Self {
+            test,
+            outcome,
+            duration,
+            location,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.outcome == Outcome::Passed
+    }
+
+    /// A description of why the test did not pass, or `None` if it did.
+    pub fn failure_text(&self) -> Option<String> {
+        match &self.outcome {
+            Outcome::Passed => None,
+            Outcome::Failed(failure) => {
+                let kind = failure.kind.replace("_", " ");
+                Some(match &self.location {
+                    Some((path, line)) => format!("{kind} at {path}:{line}\n{}", failure.message),
+                    None => format!("{kind}\n{}", failure.message),
+                })
+            }
+            Outcome::TimedOut => Some(format!(
+                "The test did not finish in {}s and was stopped.",
+                self.duration.as_secs()
+            )),
+            Outcome::Crashed {
+                status: Some(status),
+            } => Some(format!(
+                "The test runner exited with status {status} while running the test."
+            )),
+            Outcome::Crashed { status: None } => {
+                Some("The test runner was stopped while running the test.".into())
+            }
+        }
+    }
+}
+
+fn relative_path(path: &Utf8Path, root: &Utf8Path) -> Utf8PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+/// The number of tests that passed and failed, for the summary printed once
+/// all the tests have been run.
+pub fn summary(results: &[TestResult]) -> String {
+    let failed = results.iter().filter(|result| !result.passed()).count();
+    let passed = results.len() - failed;
+    format!("{passed} passed, {failed} failed")
+}
+
+/// The JavaScript module that runs the given tests, to be written to the
+/// build directory of the root package as `JAVASCRIPT_RUNNER_FILE`.
+pub fn javascript_runner(tests: &[TestFunction]) -> String {
+    let mut runner = String::new();
+    let modules = tests.iter().map(|test| &test.module).unique().collect_vec();
+    for (index, module) in modules.iter().enumerate() {
+        writeln!(
+            runner,
+            "import * as $module{index} from \"./{module}.mjs\";"
+        )
+        .expect("writing to string");
+    }
+
+    runner.push_str("\nconst tests = [\n");
+    for test in tests {
+        let index = modules
+            .iter()
+            .position(|module| *module == &test.module)
+            .expect("test module imported");
+        writeln!(
+            runner,
+            "  [\"{module}\", \"{name}\", $module{index}.{name}],",
+            module = test.module,
+            name = test.name
+        )
+        .expect("writing to string");
+    }
+    runner.push_str("];\n\n");
+    runner.push_str(include_str!("../templates/gleam@@test.mjs"));
+    runner
+}
+
+/// The list of tests for the Erlang runner, as Erlang terms to be read with
+/// `file:consult`.
+pub fn erlang_tests(tests: &[TestFunction]) -> String {
+    tests
+        .iter()
+        .map(|test| {
+            let module = test.module.replace("/", "@");
+            format!("{{'{module}', '{name}'}}.\n", name = test.name)
+        })
+        .join("")
+}
+
+/// Renders the results as a JUnit XML report, with a test suite for each
+/// test module.
+pub fn junit_xml(package: &str, results: &[TestResult]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let (failures, errors) = failure_counts(results.iter());
+    let time: Duration = results.iter().map(|result| result.duration).sum();
+    writeln!(
+        xml,
+        r#"<testsuites name="{}" tests="{}" failures="{failures}" errors="{errors}" time="{}">"#,
+        escape_xml(package),
+        results.len(),
+        seconds(time),
+    )
+    .expect("writing to string");
+
+    let suites = results.iter().chunk_by(|result| &result.test.module);
+    for (module, results) in &suites {
+        let results = results.collect_vec();
+        let (failures, errors) = failure_counts(results.iter().copied());
+        let time: Duration = results.iter().map(|result| result.duration).sum();
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" tests="{}" failures="{failures}" errors="{errors}" time="{}">"#,
+            escape_xml(module),
+            results.len(),
+            seconds(time),
+        )
+        .expect("writing to string");
+
+        for result in results {
+            write!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" file="{}" line="{}" time="{}""#,
+                escape_xml(&result.test.name),
+                escape_xml(module),
+                escape_xml(result.test.src_path.as_str()),
+                result.test.line,
+                seconds(result.duration),
+            )
+            .expect("writing to string");
+
+            let (element, type_, message) = match &result.outcome {
+                Outcome::Passed => {
+                    xml.push_str("/>\n");
+                    continue;
+                }
+                Outcome::Failed(failure) => {
+                    ("failure", failure.kind.as_str(), failure.message.as_str())
+                }
+                Outcome::TimedOut => ("error", "timeout", "Timed out"),
+                Outcome::Crashed { .. } => ("error", "crash", "Crashed"),
+            };
+            let text = result.failure_text().unwrap_or_default();
+            writeln!(
+                xml,
+                r#">
+      <{element} type="{}" message="{}">{}</{element}>
+    </testcase>"#,
+                escape_xml(type_),
+                escape_xml(message),
+                escape_xml(&text),
+            )
+            .expect("writing to string");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// The number of tests that failed, and of those that timed out or crashed
+/// the runner, which JUnit calls errors.
+fn failure_counts<'a>(results: impl Iterator<Item = &'a TestResult>) -> (usize, usize) {
+    results.fold((0, 0), |(failures, errors), result| match result.outcome {
+        Outcome::Passed => (failures, errors),
+        Outcome::Failed(_) => (failures + 1, errors),
+        Outcome::TimedOut | Outcome::Crashed { .. } => (failures, errors + 1),
+    })
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than whitespace are not allowed in
+            // XML 1.0, not even as character references.
+            '\t' | '\n' | '\r' => escaped.push(char),
+            char if char.is_control() => (),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,356 @@
+use std::time::Duration;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    analyse::TargetSupport,
+    build::{Origin, Target},
+    type_::ModuleInterface,
+};
+
+use super::{
+    Event, Failure, Outcome, TestFilter, TestFunction, TestResult, erlang_tests, javascript_runner,
+    junit_xml, summary,
+};
+
+fn test_module(src: &str) -> ModuleInterface {
+    test_module_for_target(src, Target::Erlang)
+}
+
+fn test_module_for_target(src: &str, target: Target) -> ModuleInterface {
+    let mut module = crate::type_::tests::compile_module_with_opts(
+        "wibble/wobble_test",
+        src,
+        None,
+        vec![],
+        target,
+        TargetSupport::NotEnforced,
+        None,
+    )
+    .expect("module should compile")
+    .type_info;
+    module.origin = Origin::Test;
+    module
+}
+
+fn test_names(module: &ModuleInterface, target: Target) -> Vec<EcoString> {
+    module
+        .test_functions(target)
+        .into_iter()
+        .map(|test| test.name)
+        .collect_vec()
+}
+
+fn test_function(module: &str, name: &str, line: u32) -> TestFunction {
+    TestFunction {
+        module: module.into(),
+        name: name.into(),
+        src_path: Utf8PathBuf::from(format!("/project/test/{module}.gleam")),
+        line,
+    }
+}
+
+fn result(test: TestFunction, outcome: Outcome, milliseconds: u64) -> TestResult {
+    let mut module = test_module("");
+    module.name = "wibble".into();
+    module.src_path = "/project/src/wibble.gleam".into();
+    let modules = im::hashmap! { module.name.clone() => module };
+    TestResult::new(
+        test,
+        outcome,
+        Duration::from_millis(milliseconds),
+        &modules,
+        Utf8Path::new("/project"),
+    )
+}
+
+fn panic_in_wibble() -> Outcome {
+    Outcome::Failed(Failure {
+        kind: "panic".into(),
+        message: "oh <no> & \"that\"".into(),
+        module: Some("wibble".into()),
+        line: Some(12),
+    })
+}
+
+#[test]
+fn public_zero_arity_test_functions_are_tests() {
+    let module = test_module(
+        r#"
+pub fn wobble_test() { Nil }
+
+pub fn wibble_test() { Nil }
+
+fn private_test() { Nil }
+
+pub fn argument_test(x) { x }
+
+pub fn helper() { Nil }
+
+pub const constant_test = 1
+"#,
+    );
+    assert_eq!(
+        test_names(&module, Target::Erlang),
+        vec![EcoString::from("wobble_test"), "wibble_test".into()]
+    );
+}
+
+#[test]
+fn test_functions_have_their_line() {
+    let module = test_module(
+        r#"
+pub fn wobble_test() {
+  Nil
+}
+
+pub fn wibble_test() { Nil }
+"#,
+    );
+    assert_eq!(
+        module.test_functions(Target::Erlang),
+        vec![
+            TestFunction {
+                module: "wibble/wobble_test".into(),
+                name: "wobble_test".into(),
+                src_path: module.src_path.clone(),
+                line: 2,
+            },
+            TestFunction {
+                module: "wibble/wobble_test".into(),
+                name: "wibble_test".into(),
+                src_path: module.src_path.clone(),
+                line: 6,
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_functions_not_supporting_the_target_are_skipped() {
+    let module = test_module_for_target(
+        r#"
+pub fn wobble_test() { Nil }
+
+@external(javascript, "./ffi.mjs", "wibble")
+pub fn wibble_test() -> Nil
+"#,
+        Target::JavaScript,
+    );
+    assert_eq!(test_names(&module, Target::Erlang), vec!["wobble_test"]);
+    assert_eq!(
+        test_names(&module, Target::JavaScript),
+        vec!["wobble_test", "wibble_test"]
+    );
+}
+
+#[test]
+fn src_modules_have_no_tests() {
+    let mut module = test_module("pub fn wobble_test() { Nil }");
+    module.origin = Origin::Src;
+    assert_eq!(test_names(&module, Target::Erlang), Vec::<EcoString>::new());
+}
+
+#[test]
+fn filter_by_pattern() {
+    let filter = TestFilter {
+        pattern: Some("wobble_test.wib".into()),
+        name: None,
+        module: None,
+    };
+    assert!(filter.matches(&test_function("wibble/wobble_test", "wibble_test", 1)));
+    assert!(!filter.matches(&test_function("wibble/wobble_test", "wobble_test", 1)));
+}
+
+#[test]
+fn filter_by_module() {
+    let filter = TestFilter {
+        pattern: None,
+        name: None,
+        module: Some("wibble".into()),
+    };
+    assert!(filter.matches(&test_function("wibble", "wobble_test", 1)));
+    assert!(!filter.matches(&test_function("wibble/wobble", "wobble_test", 1)));
+}
+
+#[test]
+fn filter_by_name() {
+    let filter = TestFilter {
+        pattern: None,
+        name: Some("wibble_test.wobble_test".into()),
+        module: None,
+    };
+    assert!(filter.matches(&test_function("wibble_test", "wobble_test", 1)));
+    assert!(!filter.matches(&test_function("wibble_test", "wobble_test_2", 1)));
+    assert!(!filter.matches(&test_function("wibble/wibble_test", "wobble_test", 1)));
+}
+
+#[test]
+fn empty_filter_matches_everything() {
+    assert!(TestFilter::default().matches(&test_function("wibble", "wobble_test", 1)));
+}
+
+#[test]
+fn parse_events() {
+    assert_eq!(
+        Event::parse(
+            "\u{1}gleam-test:{\"event\":\"start\",\"module\":\"wibble/wobble\",\"name\":\"wubble_test\"}"
+        ),
+        Some((
+            "",
+            Event::Start {
+                module: "wibble/wobble".into(),
+                name: "wubble_test".into()
+            }
+        ))
+    );
+    assert_eq!(
+        Event::parse("\u{1}gleam-test:{\"event\":\"pass\"}"),
+        Some(("", Event::Pass))
+    );
+    assert_eq!(
+        Event::parse("\u{1}gleam-test:{\"event\":\"done\"}"),
+        Some(("", Event::Done))
+    );
+    assert_eq!(
+        Event::parse(
+            "\u{1}gleam-test:{\"event\":\"fail\",\"kind\":\"panic\",\"message\":\"oh no\",\"module\":\"wibble\",\"line\":3}"
+        ),
+        Some((
+            "",
+            Event::Fail(Failure {
+                kind: "panic".into(),
+                message: "oh no".into(),
+                module: Some("wibble".into()),
+                line: Some(3),
+            })
+        ))
+    );
+    assert_eq!(
+        Event::parse(
+            "\u{1}gleam-test:{\"event\":\"fail\",\"kind\":\"erlang_error\",\"message\":\"badarg\"}"
+        ),
+        Some((
+            "",
+            Event::Fail(Failure {
+                kind: "erlang_error".into(),
+                message: "badarg".into(),
+                module: None,
+                line: None,
+            })
+        ))
+    );
+}
+
+#[test]
+fn test_output_is_not_an_event() {
+    assert_eq!(Event::parse("{\"event\":\"pass\"}"), None);
+    assert_eq!(Event::parse("Hello, Joe!"), None);
+    assert_eq!(Event::parse("\u{1}gleam-test:Hello, Joe!"), None);
+}
+
+#[test]
+fn event_after_output_without_newline() {
+    assert_eq!(
+        Event::parse("Hello, Joe!\u{1}gleam-test:{\"event\":\"pass\"}"),
+        Some(("Hello, Joe!", Event::Pass))
+    );
+}
+
+#[test]
+fn failure_location_is_relative_to_the_project() {
+    let result = result(
+        test_function("wibble_test", "wobble_test", 1),
+        panic_in_wibble(),
+        1,
+    );
+    assert_eq!(
+        result.location,
+        Some((Utf8PathBuf::from("src/wibble.gleam"), 12))
+    );
+    assert_eq!(
+        result.test.src_path,
+        Utf8PathBuf::from("test/wibble_test.gleam")
+    );
+}
+
+#[test]
+fn failure_texts() {
+    let test = test_function("wibble_test", "wobble_test", 1);
+    let texts = [
+        Outcome::Passed,
+        panic_in_wibble(),
+        Outcome::Failed(Failure {
+            kind: "let_assert".into(),
+            message: "Pattern match failed".into(),
+            module: Some("unknown".into()),
+            line: Some(1),
+        }),
+        Outcome::TimedOut,
+        Outcome::Crashed { status: Some(1) },
+        Outcome::Crashed { status: None },
+    ]
+    .into_iter()
+    .map(|outcome| result(test.clone(), outcome, 5000).failure_text())
+    .collect_vec();
+    insta::assert_debug_snapshot!(texts);
+}
+
+#[test]
+fn summary_counts_failures() {
+    let test = test_function("wibble_test", "wobble_test", 1);
+    let results = [
+        result(test.clone(), Outcome::Passed, 1),
+        result(test.clone(), Outcome::TimedOut, 1),
+        result(test.clone(), Outcome::Passed, 1),
+    ];
+    assert_eq!(summary(&results), "2 passed, 1 failed");
+}
+
+#[test]
+fn junit_report() {
+    let results = [
+        result(
+            test_function("wibble_test", "wibble_test", 3),
+            Outcome::Passed,
+            12,
+        ),
+        result(
+            test_function("wibble_test", "wobble_test", 7),
+            panic_in_wibble(),
+            1,
+        ),
+        result(
+            test_function("wibble/wobble_test", "slow_test", 1),
+            Outcome::TimedOut,
+            5000,
+        ),
+    ];
+    insta::assert_snapshot!(junit_xml("my_project", &results));
+}
+
+#[test]
+fn javascript_runner_imports_each_module_once() {
+    let runner = javascript_runner(&[
+        test_function("wibble_test", "wibble_test", 1),
+        test_function("wibble/wobble_test", "wobble_test", 1),
+        test_function("wibble_test", "wobble_test", 2),
+    ]);
+    let (tests, _runner) = runner
+        .split_once("];\n")
+        .expect("runner should list the tests");
+    insta::assert_snapshot!(tests);
+}
+
+#[test]
+fn erlang_tests_list() {
+    assert_eq!(
+        erlang_tests(&[
+            test_function("wibble_test", "wibble_test", 1),
+            test_function("wibble/wobble_test", "wobble_test", 1),
+        ]),
+        "{'wibble_test', 'wibble_test'}.\n{'wibble@wobble_test', 'wobble_test'}.\n"
+    );
+}
@@ -39,10 +39,9 @@ use error::*;
 use hydrator::Hydrator;
 use itertools::Itertools;
 use std::{
-    cell::RefCell,
     collections::{HashMap, HashSet},
-    ops::Deref,
-    sync::Arc,
+    ops::{Deref, DerefMut},
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
 };
 
 pub trait HasType {
@@ -104,7 +103,7 @@ pub enum Type {
 
     /// A type variable. See the contained `TypeVar` enum for more information.
     ///
-    Var { type_: Arc<RefCell<TypeVar>> },
+    Var { type_: Arc<TypeVarCell> },
 
     /// A tuple is an ordered collection of 0 or more values, each of which
     /// can have a different type, so the `tuple` type is the sum of all the
@@ -1186,6 +1185,152 @@ pub enum TypeVar {
     Generic { id: u64 },
 }
 
+/// The mutable cell holding a type variable, which is updated to link to
+/// another type when the variable is unified.
+///
+/// A lock is used rather than a `RefCell` so that types are `Send` and `Sync`,
+/// allowing modules to be analysed and generated on multiple threads. Unlike a
+/// `RefCell`, borrowing a variable mutably while the same thread still holds a
+/// borrow of it would deadlock rather than panic, so each thread keeps track of
+/// the variables it has borrowed and panics instead.
+///
+pub struct TypeVarCell(RwLock<TypeVar>);
+
+impl TypeVarCell {
+    pub fn new(type_: TypeVar) -> Self {
+        Self(RwLock::new(type_))
+    }
+
+    pub fn borrow(&self) -> TypeVarRef<'_> {
+        let borrow = TypeVarBorrow::new(self, false);
+        TypeVarRef {
+            guard: self.0.read().expect("Type variable lock poisoned"),
+            _borrow: borrow,
+        }
+    }
+
+    pub fn borrow_mut(&self) -> TypeVarRefMut<'_> {
+        let borrow = TypeVarBorrow::new(self, true);
+        TypeVarRefMut {
+            guard: self.0.write().expect("Type variable lock poisoned"),
+            _borrow: borrow,
+        }
+    }
+}
+
+/// A shared borrow of the variable held by a `TypeVarCell`.
+#[derive(Debug)]
+pub struct TypeVarRef<'a> {
+    guard: RwLockReadGuard<'a, TypeVar>,
+    _borrow: TypeVarBorrow,
+}
+
+impl Deref for TypeVarRef<'_> {
+    type Target = TypeVar;
+
+    fn deref(&self) -> &TypeVar {
+        &self.guard
+    }
+}
+
+/// A mutable borrow of the variable held by a `TypeVarCell`.
+#[derive(Debug)]
+pub struct TypeVarRefMut<'a> {
+    guard: RwLockWriteGuard<'a, TypeVar>,
+    _borrow: TypeVarBorrow,
+}
+
+impl Deref for TypeVarRefMut<'_> {
+    type Target = TypeVar;
+
+    fn deref(&self) -> &TypeVar {
+        &self.guard
+    }
+}
+
+impl DerefMut for TypeVarRefMut<'_> {
+    fn deref_mut(&mut self) -> &mut TypeVar {
+        &mut self.guard
+    }
+}
+
+thread_local! {
+    /// The type variables borrowed by the current thread, by address, with the
+    /// number of shared borrows of each or -1 if it is borrowed mutably. Only
+    /// a handful of variables are borrowed at any one time, so a vector is
+    /// faster to search than a hash map.
+    static BORROWED_TYPE_VARS: std::cell::RefCell<Vec<(usize, isize)>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Records that the current thread borrows a type variable for as long as it
+/// is held.
+#[derive(Debug)]
+struct TypeVarBorrow {
+    address: usize,
+}
+
+impl TypeVarBorrow {
+    fn new(cell: &TypeVarCell, mutable: bool) -> Self {
+        let address = std::ptr::from_ref(cell) as usize;
+        BORROWED_TYPE_VARS.with_borrow_mut(|borrowed| {
+            match borrowed
+                .iter_mut()
+                .find(|(borrowed, _)| *borrowed == address)
+            {
+                None if mutable => borrowed.push((address, -1)),
+                None => borrowed.push((address, 1)),
+                Some((_, count)) if *count > 0 && !mutable => *count += 1,
+                Some((_, count)) if *count > 0 => {
+                    panic!("Type variable already borrowed by this thread")
+                }
+                Some(_) => panic!("Type variable already mutably borrowed by this thread"),
+            }
+        });
+        Self { address }
+    }
+}
+
+impl Drop for TypeVarBorrow {
+    fn drop(&mut self) {
+        BORROWED_TYPE_VARS.with_borrow_mut(|borrowed| {
+            match borrowed
+                .iter_mut()
+                .find(|(address, _)| *address == self.address)
+            {
+                Some((_, count)) if *count > 1 => *count -= 1,
+                Some(_) | None => borrowed.retain(|(address, _)| *address != self.address),
+            }
+        });
+    }
+}
+
+impl std::fmt::Debug for TypeVarCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TypeVarCell").field(&*self.borrow()).finish()
+    }
+}
+
+impl PartialEq for TypeVarCell {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other) || *self.borrow() == *other.borrow()
+    }
+}
+
+impl Eq for TypeVarCell {}
+
+impl Serialize for TypeVarCell {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for TypeVarCell {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TypeVar::deserialize(deserializer).map(Self::new)
+    }
+}
+
 impl TypeVar {
     pub fn is_unbound(&self) -> bool {
         match self {
@@ -6,6 +6,7 @@ use crate::{
     ast::{BinOp, BitArraySegmentTruncation, Layer, SrcSpan, TodoKind},
     build::Target,
     type_::{Type, expression::ComparisonOutcome},
+    warning::WarningKind,
 };
 
 use camino::Utf8PathBuf;
@@ -1051,6 +1052,18 @@ pub enum Warning {
         location: SrcSpan,
         outcome: ComparisonOutcome,
     },
+
+    /// This warning is raised when `echo` is used. It is allowed by default,
+    /// so it is only reported if a package sets a level for it, making sure
+    /// no debug printing is left in the code:
+    ///
+    /// ```toml
+    /// [warnings]
+    /// echo = "deny"
+    /// ```
+    Echo {
+        location: SrcSpan,
+    },
 }
 
 #[derive(Debug, Eq, Copy, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
@@ -1219,6 +1232,80 @@ impl Error {
         }
     }
 
+    /// The stable code identifying this kind of error, as accepted by
+    /// `gleam explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidImport { .. } => "E0200",
+            Error::BitArraySegmentError { .. } => "E0201",
+            Error::UnknownLabels { .. } => "E0202",
+            Error::UnknownVariable { .. } => "E0203",
+            Error::UnknownType { .. } => "E0204",
+            Error::UnknownModule { .. } => "E0205",
+            Error::UnknownModuleType { .. } => "E0206",
+            Error::UnknownModuleValue { .. } => "E0207",
+            Error::ModuleAliasUsedAsName { .. } => "E0208",
+            Error::NotFn { .. } => "E0209",
+            Error::UnknownRecordField { .. } => "E0210",
+            Error::IncorrectArity { .. } => "E0211",
+            Error::UnsafeRecordUpdate { .. } => "E0212",
+            Error::UnnecessarySpreadOperator { .. } => "E0213",
+            Error::IncorrectTypeArity { .. } => "E0214",
+            Error::CouldNotUnify { .. } => "E0215",
+            Error::RecursiveType { .. } => "E0216",
+            Error::DuplicateName { .. } => "E0217",
+            Error::DuplicateImport { .. } => "E0218",
+            Error::DuplicateTypeName { .. } => "E0219",
+            Error::DuplicateArgument { .. } => "E0220",
+            Error::DuplicateField { .. } => "E0221",
+            Error::PrivateTypeLeak { .. } => "E0222",
+            Error::UnexpectedLabelledArg { .. } => "E0223",
+            Error::PositionalArgumentAfterLabelled { .. } => "E0224",
+            Error::IncorrectNumClausePatterns { .. } => "E0225",
+            Error::NonLocalClauseGuardVariable { .. } => "E0226",
+            Error::ExtraVarInAlternativePattern { .. } => "E0227",
+            Error::MissingVarInAlternativePattern { .. } => "E0228",
+            Error::DuplicateVarInPattern { .. } => "E0229",
+            Error::OutOfBoundsTupleIndex { .. } => "E0230",
+            Error::NotATuple { .. } => "E0231",
+            Error::NotATupleUnbound { .. } => "E0232",
+            Error::RecordAccessUnknownType { .. } => "E0233",
+            Error::RecordUpdateInvalidConstructor { .. } => "E0234",
+            Error::UnexpectedTypeHole { .. } => "E0235",
+            Error::ReservedModuleName { .. } => "E0236",
+            Error::KeywordInModuleName { .. } => "E0237",
+            Error::NotExhaustivePatternMatch { .. } => "E0238",
+            Error::ArgumentNameAlreadyUsed { .. } => "E0239",
+            Error::UnlabelledAfterlabelled { .. } => "E0240",
+            Error::RecursiveTypeAlias { .. } => "E0241",
+            Error::ExternalMissingAnnotation { .. } => "E0242",
+            Error::NoImplementation { .. } => "E0243",
+            Error::UnsupportedExpressionTarget { .. } => "E0244",
+            Error::InvalidExternalJavascriptModule { .. } => "E0245",
+            Error::InvalidExternalJavascriptFunction { .. } => "E0246",
+            Error::InexhaustiveCaseExpression { .. } => "E0247",
+            Error::MissingCaseBody { .. } => "E0248",
+            Error::InexhaustiveLetAssignment { .. } => "E0249",
+            Error::UnusedTypeAliasParameter { .. } => "E0250",
+            Error::DuplicateTypeParameter { .. } => "E0251",
+            Error::UnsupportedPublicFunctionTarget { .. } => "E0252",
+            Error::NotFnInUse { .. } => "E0253",
+            Error::UseFnIncorrectArity { .. } => "E0254",
+            Error::UseCallbackIncorrectArity { .. } => "E0255",
+            Error::UseFnDoesntTakeCallback { .. } => "E0256",
+            Error::BadName { .. } => "E0257",
+            Error::AllVariantsDeprecated { .. } => "E0258",
+            Error::DeprecatedVariantOnDeprecatedType { .. } => "E0259",
+            Error::ErlangFloatUnsafe { .. } => "E0260",
+            Error::EchoWithNoFollowingExpression { .. } => "E0261",
+            Error::StringConcatenationWithAddInt { .. } => "E0262",
+            Error::FloatOperatorOnInts { .. } => "E0263",
+            Error::IntOperatorOnFloats { .. } => "E0264",
+            Error::DoubleVariableAssignmentInBitArray { .. } => "E0265",
+            Error::NonUtf8StringAssignmentInBitArray { .. } => "E0266",
+        }
+    }
+
     pub fn with_unify_error_situation(mut self, new_situation: UnifyErrorSituation) -> Self {
         match self {
             Error::CouldNotUnify {
@@ -1241,6 +1328,114 @@ impl Warning {
         }
     }
 
+    /// The stable code identifying this kind of warning, as accepted by
+    /// `gleam explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Warning::Todo { .. } => "W0200",
+            Warning::ImplicitlyDiscardedResult { .. } => "W0201",
+            Warning::UnusedLiteral { .. } => "W0202",
+            Warning::UnusedValue { .. } => "W0203",
+            Warning::NoFieldsRecordUpdate { .. } => "W0204",
+            Warning::AllFieldsRecordUpdate { .. } => "W0205",
+            Warning::UnusedType { .. } => "W0206",
+            Warning::UnusedConstructor { .. } => "W0207",
+            Warning::UnusedImportedValue { .. } => "W0208",
+            Warning::UnusedImportedModule { .. } => "W0209",
+            Warning::UnusedImportedModuleAlias { .. } => "W0210",
+            Warning::UnusedPrivateModuleConstant { .. } => "W0211",
+            Warning::UnusedPrivateFunction { .. } => "W0212",
+            Warning::UnusedVariable { .. } => "W0213",
+            Warning::UnnecessaryDoubleIntNegation { .. } => "W0214",
+            Warning::UnnecessaryDoubleBoolNegation { .. } => "W0215",
+            Warning::InefficientEmptyListCheck { .. } => "W0216",
+            Warning::TransitiveDependencyImported { .. } => "W0217",
+            Warning::DeprecatedItem { .. } => "W0218",
+            Warning::UnreachableCasePattern { .. } => "W0219",
+            Warning::UnusedDiscardPattern { .. } => "W0220",
+            Warning::CaseMatchOnLiteralCollection { .. } => "W0221",
+            Warning::CaseMatchOnLiteralValue { .. } => "W0222",
+            Warning::OpaqueExternalType { .. } => "W0223",
+            Warning::InternalTypeLeak { .. } => "W0224",
+            Warning::RedundantAssertAssignment { .. } => "W0225",
+            Warning::AssertAssignmentOnInferredVariant { .. } => "W0226",
+            Warning::TodoOrPanicUsedAsFunction { .. } => "W0227",
+            Warning::UnreachableCodeAfterPanic { .. } => "W0228",
+            Warning::RedundantPipeFunctionCapture { .. } => "W0229",
+            Warning::FeatureRequiresHigherGleamVersion { .. } => "W0230",
+            Warning::JavaScriptIntUnsafe { .. } => "W0231",
+            Warning::AssertLiteralBool { .. } => "W0232",
+            Warning::BitArraySegmentTruncatedValue { .. } => "W0233",
+            Warning::ModuleImportedTwice { .. } => "W0234",
+            Warning::TopLevelDefinitionShadowsImport { .. } => "W0235",
+            Warning::RedundantComparison { .. } => "W0236",
+            Warning::Echo { .. } => "W0237",
+        }
+    }
+
+    /// The kind of this warning, used to look up its level in the package's
+    /// config and by the `@allow` attribute.
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            Warning::Todo { .. } => WarningKind::Todo,
+            Warning::ImplicitlyDiscardedResult { .. } => WarningKind::ImplicitlyDiscardedResult,
+            Warning::UnusedLiteral { .. } => WarningKind::UnusedLiteral,
+            Warning::UnusedValue { .. } => WarningKind::UnusedValue,
+            Warning::NoFieldsRecordUpdate { .. } => WarningKind::NoFieldsRecordUpdate,
+            Warning::AllFieldsRecordUpdate { .. } => WarningKind::AllFieldsRecordUpdate,
+            Warning::UnusedType { .. } => WarningKind::UnusedType,
+            Warning::UnusedConstructor { .. } => WarningKind::UnusedConstructor,
+            Warning::UnusedImportedValue { .. } => WarningKind::UnusedImportedValue,
+            Warning::UnusedImportedModule { .. } => WarningKind::UnusedImportedModule,
+            Warning::UnusedImportedModuleAlias { .. } => WarningKind::UnusedImportedModuleAlias,
+            Warning::UnusedPrivateModuleConstant { .. } => WarningKind::UnusedPrivateModuleConstant,
+            Warning::UnusedPrivateFunction { .. } => WarningKind::UnusedPrivateFunction,
+            Warning::UnusedVariable { .. } => WarningKind::UnusedVariable,
+            Warning::UnnecessaryDoubleIntNegation { .. } => {
+                WarningKind::UnnecessaryDoubleIntNegation
+            }
+            Warning::UnnecessaryDoubleBoolNegation { .. } => {
+                WarningKind::UnnecessaryDoubleBoolNegation
+            }
+            Warning::InefficientEmptyListCheck { .. } => WarningKind::InefficientEmptyListCheck,
+            Warning::TransitiveDependencyImported { .. } => {
+                WarningKind::TransitiveDependencyImported
+            }
+            Warning::DeprecatedItem { .. } => WarningKind::DeprecatedItem,
+            Warning::UnreachableCasePattern { .. } => WarningKind::UnreachableCasePattern,
+            Warning::UnusedDiscardPattern { .. } => WarningKind::UnusedDiscardPattern,
+            Warning::CaseMatchOnLiteralCollection { .. } => {
+                WarningKind::CaseMatchOnLiteralCollection
+            }
+            Warning::CaseMatchOnLiteralValue { .. } => WarningKind::CaseMatchOnLiteralValue,
+            Warning::OpaqueExternalType { .. } => WarningKind::OpaqueExternalType,
+            Warning::InternalTypeLeak { .. } => WarningKind::InternalTypeLeak,
+            Warning::RedundantAssertAssignment { .. } => WarningKind::RedundantAssertAssignment,
+            Warning::AssertAssignmentOnInferredVariant { .. } => {
+                WarningKind::AssertAssignmentOnInferredVariant
+            }
+            Warning::TodoOrPanicUsedAsFunction { .. } => WarningKind::TodoOrPanicUsedAsFunction,
+            Warning::UnreachableCodeAfterPanic { .. } => WarningKind::UnreachableCodeAfterPanic,
+            Warning::RedundantPipeFunctionCapture { .. } => {
+                WarningKind::RedundantPipeFunctionCapture
+            }
+            Warning::FeatureRequiresHigherGleamVersion { .. } => {
+                WarningKind::FeatureRequiresHigherGleamVersion
+            }
+            Warning::JavaScriptIntUnsafe { .. } => WarningKind::JavaScriptIntUnsafe,
+            Warning::AssertLiteralBool { .. } => WarningKind::AssertLiteralBool,
+            Warning::BitArraySegmentTruncatedValue { .. } => {
+                WarningKind::BitArraySegmentTruncatedValue
+            }
+            Warning::ModuleImportedTwice { .. } => WarningKind::ModuleImportedTwice,
+            Warning::TopLevelDefinitionShadowsImport { .. } => {
+                WarningKind::TopLevelDefinitionShadowsImport
+            }
+            Warning::RedundantComparison { .. } => WarningKind::RedundantComparison,
+            Warning::Echo { .. } => WarningKind::Echo,
+        }
+    }
+
     pub(crate) fn location(&self) -> SrcSpan {
         match self {
             Warning::Todo { location, .. }
@@ -1281,7 +1476,8 @@ impl Warning {
             | Warning::ModuleImportedTwice {
                 second: location, ..
             }
-            | Warning::RedundantComparison { location, .. } => *location,
+            | Warning::RedundantComparison { location, .. }
+            | Warning::Echo { location } => *location,
         }
     }
 
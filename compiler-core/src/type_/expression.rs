@@ -625,6 +625,7 @@ impl<'a, 'b> ExprTyper<'a, 'b> {
     ) -> TypedExpr {
         self.environment.echo_found = true;
         self.purity = Purity::Impure;
+        self.problems.warning(Warning::Echo { location });
 
         let expression = if let Some(expression) = expression {
             let expression = self.infer(*expression);
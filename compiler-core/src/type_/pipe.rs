@@ -188,6 +188,7 @@ impl<'a, 'b, 'c> PipeTyper<'a, 'b, 'c> {
                 } => {
                     self.expr_typer.environment.echo_found = true;
                     self.expr_typer.purity = Purity::Impure;
+                    self.expr_typer.problems.warning(Warning::Echo { location });
                     // An echo that is not followed by an expression that is
                     // used as a pipeline's step is just like the identity
                     // function.
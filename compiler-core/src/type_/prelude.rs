@@ -10,11 +10,11 @@ use crate::{
 
 use super::{
     ModuleInterface, Opaque, References, Type, TypeConstructor, TypeValueConstructor,
-    TypeValueConstructorField, TypeVar, TypeVariantConstructors, ValueConstructor,
+    TypeValueConstructorField, TypeVar, TypeVarCell, TypeVariantConstructors, ValueConstructor,
     ValueConstructorVariant,
 };
 use crate::type_::Deprecation::NotDeprecated;
-use std::{cell::RefCell, collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc};
 
 const BIT_ARRAY: &str = "BitArray";
 const BOOL: &str = "Bool";
@@ -202,20 +202,20 @@ pub fn utf_codepoint() -> Arc<Type> {
 
 pub fn generic_var(id: u64) -> Arc<Type> {
     Arc::new(Type::Var {
-        type_: Arc::new(RefCell::new(TypeVar::Generic { id })),
+        type_: Arc::new(TypeVarCell::new(TypeVar::Generic { id })),
     })
 }
 
 pub fn unbound_var(id: u64) -> Arc<Type> {
     Arc::new(Type::Var {
-        type_: Arc::new(RefCell::new(TypeVar::Unbound { id })),
+        type_: Arc::new(TypeVarCell::new(TypeVar::Unbound { id })),
     })
 }
 
 #[cfg(test)]
 pub fn link(type_: Arc<Type>) -> Arc<Type> {
     Arc::new(Type::Var {
-        type_: Arc::new(RefCell::new(TypeVar::Link { type_ })),
+        type_: Arc::new(TypeVarCell::new(TypeVar::Link { type_ })),
     })
 }
 
@@ -507,7 +507,12 @@ pub fn compile_module_with_opts(
     config.name = "thepackage".into();
     config.gleam_version = gleam_version.map(|v| GleamVersion::from_pubgrub(v));
 
-    let warnings = TypeWarningEmitter::new("/src/warning/wrn.gleam".into(), src.into(), emitter);
+    let warnings = TypeWarningEmitter::new(
+        "/src/warning/wrn.gleam".into(),
+        src.into(),
+        config.warnings.clone(),
+        emitter,
+    );
     let inference_result = crate::analyse::ModuleAnalyzerConstructor::<()> {
         target,
         ids: &ids,
@@ -3204,3 +3209,30 @@ pub fn main() {
         vec![("main", "fn() -> Nil")]
     );
 }
+
+#[test]
+#[should_panic(expected = "Type variable already borrowed by this thread")]
+fn borrowing_a_type_variable_mutably_while_it_is_borrowed_panics() {
+    let cell = TypeVarCell::new(TypeVar::Unbound { id: 1 });
+    let _borrowed = cell.borrow();
+    let _ = cell.borrow_mut();
+}
+
+#[test]
+fn type_variables_can_be_borrowed_again_once_released() {
+    let cell = TypeVarCell::new(TypeVar::Unbound { id: 1 });
+    let one = cell.borrow();
+    let two = cell.borrow();
+    assert_eq!(*one, *two);
+    drop((one, two));
+    *cell.borrow_mut() = TypeVar::Generic { id: 2 };
+    assert_eq!(*cell.borrow(), TypeVar::Generic { id: 2 });
+}
+
+#[test]
+#[should_panic(expected = "Type variable already mutably borrowed by this thread")]
+fn borrowing_a_type_variable_while_it_is_mutably_borrowed_panics() {
+    let cell = TypeVarCell::new(TypeVar::Unbound { id: 1 });
+    let _borrowed = cell.borrow_mut();
+    let _ = cell.borrow();
+}
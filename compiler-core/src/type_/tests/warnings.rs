@@ -4202,3 +4202,90 @@ fn unused_discard_pattern() {
 "
     );
 }
+
+#[test]
+fn allow_attribute_silences_warning() {
+    assert_no_warnings!(
+        "
+@allow(unused_variable)
+pub fn main() {
+  let a = 10
+  Nil
+}
+"
+    );
+}
+
+#[test]
+fn allow_attribute_silences_multiple_warnings() {
+    assert_no_warnings!(
+        "
+@allow(unused_variable, todo)
+pub fn main() {
+  let a = 10
+  todo
+}
+"
+    );
+}
+
+#[test]
+fn allow_attribute_on_constant() {
+    assert_no_warnings!(
+        "
+@allow(unused_private_module_constant)
+const wibble = [1, 2]
+"
+    );
+}
+
+#[test]
+fn constant_without_allow_attribute_warns() {
+    assert_warning!(
+        "
+const wibble = [1, 2]
+"
+    );
+}
+
+#[test]
+fn allow_attribute_only_silences_listed_warnings() {
+    assert_warning!(
+        "
+@allow(todo)
+pub fn main() {
+  let a = 10
+  todo
+}
+"
+    );
+}
+
+#[test]
+fn allow_attribute_only_silences_its_own_definition() {
+    assert_warning!(
+        "
+@allow(unused_variable)
+pub fn wibble() {
+  let a = 10
+  Nil
+}
+
+pub fn main() {
+  let b = 10
+  Nil
+}
+"
+    );
+}
+
+#[test]
+fn echo_is_allowed_by_default() {
+    assert_no_warnings!(
+        "
+pub fn main() {
+  echo 1
+}
+"
+    );
+}
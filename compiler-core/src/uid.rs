@@ -5,9 +5,22 @@ use std::sync::{
 
 /// A generator of unique ids. Only one should be used per compilation run to
 /// ensure ids do not get reused.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct UniqueIdGenerator {
     id: Arc<AtomicU64>,
+    /// How much the id is increased by each time one is generated. This is
+    /// more than one for generators created by `split`, so that they do not
+    /// generate the same ids as each other.
+    step: u64,
+}
+
+impl Default for UniqueIdGenerator {
+    fn default() -> Self {
+        Self {
+            id: Arc::new(AtomicU64::new(0)),
+            step: 1,
+        }
+    }
 }
 
 impl UniqueIdGenerator {
@@ -16,7 +29,42 @@ impl UniqueIdGenerator {
     }
 
     pub fn next(&self) -> u64 {
-        self.id.fetch_add(1, Ordering::Relaxed)
+        self.id.fetch_add(self.step, Ordering::Relaxed)
+    }
+
+    /// Create `count` generators that can be used at the same time, such as
+    /// on different threads, without generating the same ids. The ids each
+    /// one generates only depend on its position and on how many ids it has
+    /// generated, so they are the same no matter the order the threads run in.
+    ///
+    /// This generator must not be used until the split generators have been
+    /// passed to `join`.
+    ///
+    pub fn split(&self, count: usize) -> Vec<Self> {
+        let base = self.id.load(Ordering::Relaxed);
+        let count = count as u64;
+        (0..count)
+            .map(|index| Self {
+                id: Arc::new(AtomicU64::new(base + index * self.step)),
+                step: self.step * count,
+            })
+            .collect()
+    }
+
+    /// Continue generating ids after all the ids generated by generators
+    /// created with `split`.
+    pub fn join(&self, generators: &[Self]) {
+        let current = self.id.load(Ordering::Relaxed);
+        let Some(last) = generators
+            .iter()
+            .map(|generator| generator.id.load(Ordering::Relaxed))
+            .max()
+        else {
+            return;
+        };
+        let steps = last.saturating_sub(current).div_ceil(self.step);
+        self.id
+            .store(current + steps * self.step, Ordering::Relaxed);
     }
 }
 
@@ -38,3 +86,37 @@ fn id_geneation() {
     assert_eq!(ids.next(), 6);
     assert_eq!(ids.next(), 7);
 }
+
+#[test]
+fn split_id_generation() {
+    let ids = UniqueIdGenerator::new();
+    assert_eq!(ids.next(), 0);
+
+    let split = ids.split(3);
+    let [first, second, third] = split.as_slice() else {
+        panic!("Expected three generators");
+    };
+    assert_eq!(second.next(), 2);
+    assert_eq!(first.next(), 1);
+    assert_eq!(first.next(), 4);
+    assert_eq!(third.next(), 3);
+    assert_eq!(first.next(), 7);
+
+    // Cloned ones use the same counter
+    let clone = second.clone();
+    assert_eq!(clone.next(), 5);
+    assert_eq!(second.next(), 8);
+
+    // The original continues after all the ids of the split ones
+    ids.join(&split);
+    assert_eq!(ids.next(), 11);
+    assert_eq!(ids.next(), 12);
+}
+
+#[test]
+fn join_without_splits() {
+    let ids = UniqueIdGenerator::new();
+    assert_eq!(ids.next(), 0);
+    ids.join(&[]);
+    assert_eq!(ids.next(), 1);
+}
@@ -3,6 +3,7 @@ use crate::{
     build::Target,
     diagnostic::{self, Diagnostic, ExtraLabel, Location},
     error::wrap,
+    io::ordered_map,
     type_::{
         self,
         error::{
@@ -16,7 +17,9 @@ use crate::{
 use camino::Utf8PathBuf;
 use debug_ignore::DebugIgnore;
 use ecow::EcoString;
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     io::Write,
     sync::{Arc, atomic::Ordering},
 };
@@ -78,6 +81,10 @@ pub struct WarningEmitter {
     /// package only, the count is reset back to zero after the dependencies are
     /// compiled.
     count: Arc<AtomicUsize>,
+    /// The number of emitted warnings that were set to `deny` in the
+    /// `[warnings]` table of the package's config. It is reset together with
+    /// `count`.
+    denied_count: Arc<AtomicUsize>,
     emitter: DebugIgnore<Rc<dyn WarningEmitterIO>>,
 }
 
@@ -85,6 +92,7 @@ impl WarningEmitter {
     pub fn new(emitter: Rc<dyn WarningEmitterIO>) -> Self {
         Self {
             count: Arc::new(AtomicUsize::new(0)),
+            denied_count: Arc::new(AtomicUsize::new(0)),
             emitter: DebugIgnore(emitter),
         }
     }
@@ -95,17 +103,46 @@ impl WarningEmitter {
 
     pub fn reset_count(&self) {
         self.count.store(0, Ordering::Relaxed);
+        self.denied_count.store(0, Ordering::Relaxed);
     }
 
     pub fn count(&self) -> usize {
         self.count.load(Ordering::Relaxed)
     }
 
+    pub fn denied_count(&self) -> usize {
+        self.denied_count.load(Ordering::Relaxed)
+    }
+
     pub fn emit(&self, warning: Warning) {
         _ = self.count.fetch_add(1, Ordering::Relaxed);
         self.emitter.emit_warning(warning);
     }
 
+    /// Emits a warning according to the level it has been given in the
+    /// package's config: allowed warnings are dropped, while denied ones are
+    /// counted so that the build can fail once they have all been reported.
+    pub fn emit_with_level(&self, warning: Warning, level: WarningLevel) {
+        match level {
+            WarningLevel::Allow => (),
+            WarningLevel::Warn => self.emit(warning),
+            WarningLevel::Deny => {
+                _ = self.denied_count.fetch_add(1, Ordering::Relaxed);
+                self.emit(warning);
+            }
+        }
+    }
+
+    /// Emits the warnings collected by another emitter, such as one created
+    /// with `vector`, which have already been given their level. `denied_count`
+    /// is the number of them that were denied.
+    pub fn emit_collected(&self, warnings: Vec<Warning>, denied_count: usize) {
+        for warning in warnings {
+            self.emit(warning);
+        }
+        _ = self.denied_count.fetch_add(denied_count, Ordering::Relaxed);
+    }
+
     pub fn vector() -> (Self, Rc<VectorWarningEmitterIO>) {
         let io = Rc::new(VectorWarningEmitterIO::default());
         let emitter = Self::new(io.clone());
@@ -117,14 +154,21 @@ impl WarningEmitter {
 pub struct TypeWarningEmitter {
     module_path: Utf8PathBuf,
     module_src: EcoString,
+    levels: WarningLevels,
     emitter: WarningEmitter,
 }
 
 impl TypeWarningEmitter {
-    pub fn new(module_path: Utf8PathBuf, module_src: EcoString, emitter: WarningEmitter) -> Self {
+    pub fn new(
+        module_path: Utf8PathBuf,
+        module_src: EcoString,
+        levels: WarningLevels,
+        emitter: WarningEmitter,
+    ) -> Self {
         Self {
             module_path,
             module_src,
+            levels,
             emitter,
         }
     }
@@ -133,16 +177,151 @@ impl TypeWarningEmitter {
         Self {
             module_path: Utf8PathBuf::new(),
             module_src: EcoString::from(""),
+            levels: WarningLevels::default(),
             emitter: WarningEmitter::new(Rc::new(NullWarningEmitterIO)),
         }
     }
 
     pub fn emit(&self, warning: type_::Warning) {
-        self.emitter.emit(Warning::Type {
+        let level = self.levels.level(warning.kind());
+        let warning = Warning::Type {
             path: self.module_path.clone(),
             src: self.module_src.clone(),
             warning,
-        });
+        };
+        self.emitter.emit_with_level(warning, level);
+    }
+}
+
+/// The kinds of warning that can be given a level in the `[warnings]` table of
+/// `gleam.toml`, or allowed for a single definition with the `@allow`
+/// attribute.
+///
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    strum::Display,
+    strum::EnumString,
+    strum::IntoStaticStr,
+    strum::VariantNames,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum WarningKind {
+    Todo,
+    Echo,
+    ImplicitlyDiscardedResult,
+    UnusedLiteral,
+    UnusedValue,
+    NoFieldsRecordUpdate,
+    AllFieldsRecordUpdate,
+    UnusedType,
+    UnusedConstructor,
+    UnusedImportedValue,
+    UnusedImportedModule,
+    UnusedImportedModuleAlias,
+    UnusedPrivateModuleConstant,
+    UnusedPrivateFunction,
+    UnusedVariable,
+    UnnecessaryDoubleIntNegation,
+    UnnecessaryDoubleBoolNegation,
+    InefficientEmptyListCheck,
+    TransitiveDependencyImported,
+    DeprecatedItem,
+    UnreachableCasePattern,
+    UnusedDiscardPattern,
+    CaseMatchOnLiteralCollection,
+    CaseMatchOnLiteralValue,
+    OpaqueExternalType,
+    InternalTypeLeak,
+    RedundantAssertAssignment,
+    AssertAssignmentOnInferredVariant,
+    TodoOrPanicUsedAsFunction,
+    UnreachableCodeAfterPanic,
+    RedundantPipeFunctionCapture,
+    FeatureRequiresHigherGleamVersion,
+    #[strum(serialize = "javascript_int_unsafe")]
+    JavaScriptIntUnsafe,
+    AssertLiteralBool,
+    BitArraySegmentTruncatedValue,
+    ModuleImportedTwice,
+    TopLevelDefinitionShadowsImport,
+    RedundantComparison,
+}
+
+impl WarningKind {
+    /// The level used for this kind of warning when the package's config
+    /// doesn't set one.
+    pub fn default_level(&self) -> WarningLevel {
+        match self {
+            // `echo` is meant to be used while debugging, so it is only
+            // reported if a package asks for it.
+            WarningKind::Echo => WarningLevel::Allow,
+            _ => WarningLevel::Warn,
+        }
+    }
+}
+
+impl Serialize for WarningKind {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.into())
+    }
+}
+
+impl<'de> Deserialize<'de> for WarningKind {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| serde::de::Error::custom(format!("`{name}` is not a kind of warning")))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WarningLevel {
+    /// The warning is not reported.
+    Allow,
+    /// The warning is reported.
+    #[default]
+    Warn,
+    /// The warning is reported, and the build fails once compilation has
+    /// finished.
+    Deny,
+}
+
+/// The level of each kind of warning, as set in the `[warnings]` table of
+/// `gleam.toml`:
+///
+/// ```toml
+/// [warnings]
+/// todo = "deny"
+/// unused_variable = "allow"
+/// ```
+///
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WarningLevels {
+    #[serde(serialize_with = "ordered_map")]
+    levels: HashMap<WarningKind, WarningLevel>,
+}
+
+impl WarningLevels {
+    pub fn level(&self, kind: WarningKind) -> WarningLevel {
+        self.levels
+            .get(&kind)
+            .copied()
+            .unwrap_or_else(|| kind.default_level())
     }
 }
 
@@ -248,7 +427,32 @@ pub enum DeprecatedSyntaxWarning {
     },
 }
 
+impl DeprecatedSyntaxWarning {
+    /// The stable code identifying this kind of warning, as accepted by
+    /// `gleam explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DeprecatedSyntaxWarning::DeprecatedListPrepend { .. } => "W0100",
+            DeprecatedSyntaxWarning::DeprecatedListPattern { .. } => "W0101",
+            DeprecatedSyntaxWarning::DeprecatedListCatchAllPattern { .. } => "W0102",
+            DeprecatedSyntaxWarning::DeprecatedRecordSpreadPattern { .. } => "W0103",
+            DeprecatedSyntaxWarning::DeprecatedTargetShorthand { .. } => "W0104",
+        }
+    }
+}
+
 impl Warning {
+    /// The stable code identifying this kind of warning, as printed alongside
+    /// its diagnostic and accepted by `gleam explain`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Warning::Type { warning, .. } => warning.code(),
+            Warning::DeprecatedSyntax { warning, .. } => warning.code(),
+            Warning::InvalidSource { .. } => "W0001",
+            Warning::DeprecatedEnvironmentVariable { .. } => "W0002",
+        }
+    }
+
     pub fn to_diagnostic(&self) -> Diagnostic {
         match self {
             Warning::InvalidSource { path } => Diagnostic {
@@ -258,6 +462,7 @@ Module names must begin with a lowercase letter and contain
 only lowercase alphanumeric characters or underscores."
                     .into(),
                 level: diagnostic::Level::Warning,
+                code: Some(self.code()),
                 location: None,
                 hint: Some(format!(
                     "Rename `{path}` to be valid, or remove this file from the project source."
@@ -278,6 +483,7 @@ like this: `[item, ..list]`.",
 
                 hint: None,
                 level: diagnostic::Level::Warning,
+                code: Some(self.code()),
                 location: Some(Location {
                     label: diagnostic::Label {
                         text: Some("This spread should be preceded by a comma".into()),
@@ -302,6 +508,7 @@ like this: `[item, ..list]`.",
                 ),
                 hint: None,
                 level: diagnostic::Level::Warning,
+                code: Some(self.code()),
                 location: Some(Location {
                     label: diagnostic::Label {
                         text: Some("This spread should be preceded by a comma".into()),
@@ -322,6 +529,7 @@ like this: `[item, ..list]`.",
                 text: wrap("This syntax for pattern matching on a record is deprecated."),
                 hint: None,
                 level: diagnostic::Level::Warning,
+                code: Some(self.code()),
                 location: Some(Location {
                     label: diagnostic::Label {
                         text: Some("This should be preceded by a comma".into()),
@@ -345,6 +553,7 @@ To match on all possible lists, use the `_` catch-all pattern instead.",
                 ),
                 hint: None,
                 level: diagnostic::Level::Warning,
+                code: Some(self.code()),
                 location: Some(Location {
                     label: diagnostic::Label {
                         text: Some("This can be replaced with `_`".into()),
@@ -373,6 +582,7 @@ To match on all possible lists, use the `_` catch-all pattern instead.",
                     )),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         label: diagnostic::Label {
                             text: Some(format!("This should be replaced with `{full_name}`")),
@@ -427,6 +637,7 @@ A use expression must always be followed by at least one expression.",
                         title,
                         text,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             path: path.to_path_buf(),
                             src: src.clone(),
@@ -447,6 +658,7 @@ A use expression must always be followed by at least one expression.",
                         "If you are sure you don't need it you can assign it to `_`.".into(),
                     ),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -463,6 +675,7 @@ A use expression must always be followed by at least one expression.",
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -481,6 +694,7 @@ A use expression must always be followed by at least one expression.",
                         "Add some fields to change or replace it with the record itself.".into(),
                     ),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -497,6 +711,7 @@ A use expression must always be followed by at least one expression.",
                     text: "".into(),
                     hint: Some("It is better style to use the record creation syntax.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -526,6 +741,7 @@ A use expression must always be followed by at least one expression.",
                         text: "".into(),
                         hint: Some("You can safely remove it.".into()),
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -556,6 +772,7 @@ A use expression must always be followed by at least one expression.",
                         text: "".into(),
                         hint: Some("You can safely remove it.".into()),
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -573,6 +790,7 @@ A use expression must always be followed by at least one expression.",
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -601,6 +819,7 @@ Hint: You can safely remove it.
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -618,6 +837,7 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -634,6 +854,7 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -650,6 +871,7 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove it.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -666,6 +888,7 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: origin.how_to_ignore(),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -681,6 +904,7 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove this.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -696,6 +920,7 @@ Hint: You can safely remove it.
                     text: "".into(),
                     hint: Some("You can safely remove this.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -725,6 +950,7 @@ need to know if the list is empty or not.
                         text,
                         hint,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -758,6 +984,7 @@ Run this command to add it to your dependencies:
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -793,6 +1020,7 @@ Run this command to add it to your dependencies:
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -821,6 +1049,7 @@ a variant of a type which is never present.\n",
                         text,
                         hint: Some("It can be safely removed.".into()),
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -858,6 +1087,7 @@ See: https://tour.gleam.run/flow-control/multiple-subjects/"
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             src: src.clone(),
                             path: path.to_path_buf(),
@@ -878,6 +1108,7 @@ can already tell which branch is going to match with this value.",
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -894,6 +1125,7 @@ can already tell which branch is going to match with this value.",
                     text: "This type has no constructors so making it opaque is redundant.".into(),
                     hint: Some("Remove the `opaque` qualifier from the type definition.".into()),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -914,6 +1146,7 @@ variable, or delete the expression entirely if it's not needed.",
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -947,6 +1180,7 @@ hidden from the package's documentation.",
                         text,
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             label: diagnostic::Label {
                                 text: None,
@@ -964,6 +1198,7 @@ hidden from the package's documentation.",
                         .into(),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         label: diagnostic::Label {
                             text: Some("You can remove this".into()),
@@ -985,6 +1220,7 @@ Either change the pattern or use `panic` to unconditionally fail.",
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         label: diagnostic::Label {
                             text: None,
@@ -1041,6 +1277,7 @@ See: https://tour.gleam.run/advanced-features/{name}/"
                         text: wrap(&text),
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             label: diagnostic::Label {
                                 text: None,
@@ -1080,6 +1317,7 @@ should be printing always panics."
                         text: wrap(text),
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             label: diagnostic::Label {
                                 text: None,
@@ -1102,6 +1340,7 @@ See: https://tour.gleam.run/functions/pipelines/",
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         label: diagnostic::Label {
                             text: Some("You can safely remove this".into()),
@@ -1168,6 +1407,7 @@ See: https://tour.gleam.run/functions/pipelines/",
     gleam = \">= {minimum_required_version}\""
                         )),
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             label: diagnostic::Label {
                                 text: Some(format!(
@@ -1194,6 +1434,7 @@ information.",
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         path: path.to_path_buf(),
                         src: src.clone(),
@@ -1245,6 +1486,7 @@ doesn't fit in that many {unit}. It would be truncated by taking its {taken}, re
                         text: wrap(&text),
                         hint: None,
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             path: path.to_path_buf(),
                             src: src.clone(),
@@ -1267,6 +1509,7 @@ can already tell whether it will be `True` or `False`.",
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -1287,6 +1530,7 @@ can already tell whether it will be `True` or `False`.",
                     text: format!("The {name} module has been imported twice."),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -1308,6 +1552,7 @@ can already tell whether it will be `True` or `False`.",
                     title: "Unused discard pattern".into(),
                     text: format!("`_ as {name}` can be written more concisely as `{name}`"),
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         src: src.clone(),
                         path: path.to_path_buf(),
@@ -1332,6 +1577,7 @@ The imported value could not be used in this module anyway."
                         title: "Shadowed Import".into(),
                         text: wrap(&text),
                         level: diagnostic::Level::Warning,
+                        code: Some(self.code()),
                         location: Some(Location {
                             path: path.clone(),
                             src: src.clone(),
@@ -1356,6 +1602,7 @@ The imported value could not be used in this module anyway."
                     ),
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: Some(Location {
                         label: diagnostic::Label {
                             text: Some(format!(
@@ -1372,6 +1619,26 @@ The imported value could not be used in this module anyway."
                         extra_labels: vec![],
                     }),
                 },
+
+                type_::Warning::Echo { location } => Diagnostic {
+                    title: "Echo found".into(),
+                    text: wrap(
+                        "`echo` is a debugging tool and should be removed before \
+publishing or deploying the code.",
+                    ),
+                    hint: None,
+                    level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
+                    location: Some(Location {
+                        label: diagnostic::Label {
+                            text: Some("This prints a value".into()),
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                },
             },
 
             Warning::DeprecatedEnvironmentVariable { variable } => {
@@ -1387,6 +1654,7 @@ The imported value could not be used in this module anyway."
                     text,
                     hint: None,
                     level: diagnostic::Level::Warning,
+                    code: Some(self.code()),
                     location: None,
                 }
             }
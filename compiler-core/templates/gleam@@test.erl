@@ -0,0 +1,101 @@
+-module('{{ application }}@@test').
+-export([run/1]).
+
+run(TestsFile) ->
+    io:setopts(standard_io, [binary, {encoding, utf8}]),
+    io:setopts(standard_error, [{encoding, utf8}]),
+    {ok, Terms} = file:consult(TestsFile),
+    Coverage = [Term || {coverage, _, _} = Term <- Terms],
+    Tests = [Test || {_, _} = Test <- Terms],
+    % The modules are instrumented before the application is started so that
+    % the calls made while starting it are counted too.
+    lists:foreach(fun start_coverage/1, Coverage),
+    {ok, _} = application:ensure_all_started('{{ application }}'),
+    lists:foreach(fun run_test/1, Tests),
+    lists:foreach(fun write_coverage/1, Coverage),
+    report(<<"done">>, []),
+    erlang:halt(0).
+
+start_coverage({coverage, _OutputFile, Modules}) ->
+    _ = cover:start(),
+    lists:foreach(fun(Module) -> cover:compile_beam(Module) end, Modules).
+
+write_coverage({coverage, OutputFile, Modules}) ->
+    Calls = [
+        [<<"function\t">>, atom_to_binary(Module), $\t, atom_to_binary(Function),
+         $\t, integer_to_binary(Arity), $\t, integer_to_binary(Count), $\n]
+     || Module <- Modules,
+        {ok, Functions} <- [cover:analyse(Module, calls, function)],
+        {Id, Count} <- Functions,
+        {_, Function, Arity} <- [Id]
+    ],
+    Lines = [
+        [<<"line\t">>, atom_to_binary(Module), $\t, integer_to_binary(Line), $\t,
+         integer_to_binary(Count), $\n]
+     || Module <- Modules,
+        {ok, Counts} <- [cover:analyse(Module, calls, line)],
+        { {_, Line}, Count} <- Counts
+    ],
+    ok = file:write_file(OutputFile, [Calls, Lines]).
+
+run_test({Module, Function}) ->
+    report(<<"start">>, [
+        {<<"module">>, json_string(gleam_module_name(Module))},
+        {<<"name">>, json_string(atom_to_binary(Function))}
+    ]),
+    % Each test is run in its own process so that any process linked to it
+    % crashing is reported as a failure of that test.
+    {Pid, Ref} = spawn_monitor(fun() ->
+        Result = try
+            Module:Function(),
+            passed
+        catch
+            Class:Reason -> {failed, Class, Reason}
+        end,
+        exit({test_result, Result})
+    end),
+    receive
+        {'DOWN', Ref, process, Pid, {test_result, passed}} ->
+            report(<<"pass">>, []);
+        {'DOWN', Ref, process, Pid, {test_result, {failed, Class, Reason}}} ->
+            report(<<"fail">>, failure(Class, Reason));
+        {'DOWN', Ref, process, Pid, Reason} ->
+            report(<<"fail">>, failure(exit, Reason))
+    end.
+
+failure(_, #{gleam_error := Kind, message := Message, module := Module, line := Line}) ->
+    [
+        {<<"kind">>, json_string(atom_to_binary(Kind))},
+        {<<"message">>, json_string(Message)},
+        {<<"module">>, json_string(Module)},
+        {<<"line">>, integer_to_binary(Line)}
+    ];
+failure(Class, Reason) ->
+    [
+        {<<"kind">>, json_string(<<"erlang_", (atom_to_binary(Class))/binary>>)},
+        {<<"message">>, json_string(print_term(Reason))}
+    ].
+
+gleam_module_name(Module) ->
+    binary:replace(atom_to_binary(Module), <<"@">>, <<"/">>, [global]).
+
+print_term(Term) ->
+    try
+        gleam@string:inspect(Term)
+    catch
+        _:_ -> unicode:characters_to_binary(io_lib:format("~tp", [Term]))
+    end.
+
+report(Event, Fields) ->
+    Object = [{<<"event">>, json_string(Event)} | Fields],
+    Members = lists:join($,, [[json_string(Key), $:, Value] || {Key, Value} <- Object]),
+    io:put_chars(standard_io, [1, <<"gleam-test:">>, ${, Members, $}, $\n]).
+
+json_string(String) ->
+    [$", [json_char(Char) || <<Char/utf8>> <= String], $"].
+
+json_char($") -> <<"\\\"">>;
+json_char($\\) -> <<"\\\\">>;
+json_char($\n) -> <<"\\n">>;
+json_char(Char) when Char < 16#20 -> io_lib:format("\\u~4.16.0b", [Char]);
+json_char(Char) -> <<Char/utf8>>.
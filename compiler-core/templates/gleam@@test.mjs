@@ -0,0 +1,49 @@
+function report(event) {
+  globalThis.console.log("\u0001gleam-test:" + JSON.stringify(event));
+}
+
+function failure(error) {
+  if (error?.gleam_error) {
+    return {
+      kind: error.gleam_error,
+      message: error.message,
+      module: error.module,
+      line: error.line,
+    };
+  }
+  if (error instanceof globalThis.Error) {
+    return {
+      kind: "javascript_error",
+      message: `${error.name}: ${error.message}`,
+    };
+  }
+  return { kind: "javascript_error", message: String(error) };
+}
+
+async function run(tests) {
+  for (const [module, name, test] of tests) {
+    report({ event: "start", module, name });
+    try {
+      const result = test();
+      if (result instanceof globalThis.Promise) await result;
+      report({ event: "pass" });
+    } catch (error) {
+      report({ event: "fail", ...failure(error) });
+    }
+  }
+  report({ event: "done" });
+  exit();
+}
+
+// The runner exits once done rather than waiting for any timer started by the
+// tests, so that the runtime writes any coverage it has collected.
+function exit() {
+  if (globalThis.Deno) {
+    globalThis.Deno.exit(0);
+  } else if (globalThis.process) {
+    // Writing nothing waits for the events printed before to be flushed.
+    globalThis.process.stdout.write("", () => globalThis.process.exit(0));
+  }
+}
+
+run(tests);
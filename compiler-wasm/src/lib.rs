@@ -8,9 +8,10 @@ use gleam_core::{
     Error,
     analyse::TargetSupport,
     build::{
-        Mode, NullTelemetry, PackageCompiler, StaleTracker, Target, TargetCodegenConfiguration,
+        Mode, NullTelemetry, PackageCompiler, SourceFingerprints, StaleTracker, Target,
+        TargetCodegenConfiguration,
     },
-    config::PackageConfig,
+    config::{PackageConfig, SourceMaps},
     io::{FileSystemReader, FileSystemWriter},
     uid::UniqueIdGenerator,
     warning::{VectorWarningEmitterIO, WarningEmitter},
@@ -182,6 +183,7 @@ fn do_compile_package(project: Project, target: Target) -> Result<(), Error> {
         Target::Erlang => TargetCodegenConfiguration::Erlang { app_file: None },
         Target::JavaScript => TargetCodegenConfiguration::JavaScript {
             emit_typescript_definitions: false,
+            source_maps: SourceMaps::None,
             prelude_location: Utf8PathBuf::from("./gleam_prelude.mjs"),
         },
     };
@@ -212,6 +214,7 @@ fn do_compile_package(project: Project, target: Target) -> Result<(), Error> {
             &mut defined_modules,
             &mut StaleTracker::default(),
             &mut HashSet::new(),
+            &mut SourceFingerprints::default(),
             &NullTelemetry,
         )
         .into_result()
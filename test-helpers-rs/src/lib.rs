@@ -43,6 +43,10 @@ impl TestCompileOutput {
                     write!(buffer, "<erlang entrypoint>").unwrap()
                 }
 
+                Content::Text(_) if normalised_path.ends_with("@@test.erl") => {
+                    write!(buffer, "<erlang test runner>").unwrap()
+                }
+
                 Content::Text(text) => {
                     let format_path = |caps: &regex::Captures| {
                         caps.get(1)
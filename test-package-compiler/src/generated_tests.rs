@@ -122,6 +122,17 @@ fn erlang_import_shadowing_prelude() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn erlang_independent_modules() {
+    let output = crate::prepare("./cases/erlang_independent_modules");
+    insta::assert_snapshot!(
+        "erlang_independent_modules",
+        output,
+        "./cases/erlang_independent_modules",
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn erlang_nested() {
@@ -254,6 +265,28 @@ fn javascript_import() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn javascript_source_maps() {
+    let output = crate::prepare("./cases/javascript_source_maps");
+    insta::assert_snapshot!(
+        "javascript_source_maps",
+        output,
+        "./cases/javascript_source_maps",
+    );
+}
+
+#[rustfmt::skip]
+#[test]
+fn javascript_source_maps_inline() {
+    let output = crate::prepare("./cases/javascript_source_maps_inline");
+    insta::assert_snapshot!(
+        "javascript_source_maps_inline",
+        output,
+        "./cases/javascript_source_maps_inline",
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn not_overwriting_erlang_module() {
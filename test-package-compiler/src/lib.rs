@@ -4,8 +4,8 @@ mod generated_tests;
 use camino::Utf8PathBuf;
 use gleam_core::{
     build::{
-        ErlangAppCodegenConfiguration, Mode, NullTelemetry, Outcome, StaleTracker, Target,
-        TargetCodegenConfiguration,
+        ErlangAppCodegenConfiguration, Mode, NullTelemetry, Outcome, SourceFingerprints,
+        StaleTracker, Target, TargetCodegenConfiguration,
     },
     config::PackageConfig,
     io::{FileSystemReader, FileSystemWriter},
@@ -31,6 +31,7 @@ pub fn prepare(path: &str) -> String {
         },
         Target::JavaScript => TargetCodegenConfiguration::JavaScript {
             emit_typescript_definitions: config.javascript.typescript_declarations,
+            source_maps: config.javascript.source_maps,
             prelude_location: Utf8PathBuf::from("../prelude.mjs"),
         },
     };
@@ -64,6 +65,7 @@ pub fn prepare(path: &str) -> String {
         &mut im::HashMap::new(),
         &mut StaleTracker::default(),
         &mut HashSet::new(),
+        &mut SourceFingerprints::default(),
         &NullTelemetry,
     );
     match result {